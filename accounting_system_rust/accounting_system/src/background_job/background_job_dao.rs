@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::background_job::background_job_models::{
+    BackgroundJob, ClaimedJob, EnqueueJobDb, JobKind, JobStatus,
+};
+use crate::common_utils::dao_error::DaoError;
+
+const ENQUEUE_JOB: &str = "select enqueue_background_job($1)";
+
+const CLAIM_JOBS: &str = "select id,tenant_id,kind,payload,attempt_count,max_attempts \
+from claim_background_jobs($1,$2,$3,$4)";
+
+const COMPLETE_JOB: &str = "update background_job set status='succeeded',locked_by=null,\
+locked_until_ms=null,updated_at=extract(epoch from now()) * 1000000 \
+where id=$1 and locked_by=$2 and status='running'";
+
+const FAIL_JOB: &str = "update background_job set status=case when attempt_count>=max_attempts \
+then 'dead'::background_job_status else 'pending'::background_job_status end,run_after_ms=$3,\
+last_error=$4,locked_by=null,locked_until_ms=null,updated_at=extract(epoch from now()) * 1000000 \
+where id=$1 and locked_by=$2 and status='running'";
+
+const LIST_JOBS: &str = "select id,tenant_id,kind::text,status::text,attempt_count,max_attempts,\
+run_after_ms,last_error,created_at,updated_at from background_job \
+where tenant_id=$1 and ($2::text is null or status::text=$2) order by created_at desc limit $3";
+
+const RETRY_DEAD_JOB: &str = "update background_job set status='pending',attempt_count=0,\
+run_after_ms=$3,last_error=null,updated_at=extract(epoch from now()) * 1000000 \
+where tenant_id=$1 and id=$2 and status='dead'";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait BackgroundJobDao: Send + Sync {
+    async fn enqueue_job(&self, job: &EnqueueJobDb) -> Result<Uuid, DaoError>;
+    ///due jobs of all tenants, locked for the worker till now plus the visibility timeout
+    async fn claim_jobs(
+        &self,
+        worker_id: Uuid,
+        now_ms: i64,
+        visibility_timeout_ms: i64,
+        limit: i32,
+    ) -> Result<Vec<ClaimedJob>, DaoError>;
+    ///false when the lock of the worker expired and the job was claimed by another worker
+    async fn complete_job(&self, job_id: Uuid, worker_id: Uuid) -> Result<bool, DaoError>;
+    ///moves the job back to pending, or to dead when it has no attempts left
+    async fn fail_job(
+        &self,
+        job_id: Uuid,
+        worker_id: Uuid,
+        run_after_ms: i64,
+        error: &str,
+    ) -> Result<bool, DaoError>;
+    async fn list_jobs(
+        &self,
+        tenant_id: Uuid,
+        status: Option<JobStatus>,
+        limit: i64,
+    ) -> Result<Vec<BackgroundJob>, DaoError>;
+    async fn retry_dead_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        run_after_ms: i64,
+    ) -> Result<bool, DaoError>;
+}
+
+struct BackgroundJobDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_background_job_dao(arc: Arc<Pool>) -> Arc<dyn BackgroundJobDao> {
+    let dao = BackgroundJobDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for ClaimedJob {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let kind: &str = row.get(2);
+        Ok(ClaimedJob {
+            id: row.get(0),
+            tenant_id: row.get(1),
+            kind: JobKind::from_db_str(kind)?,
+            payload: row.get(3),
+            attempt_count: row.get(4),
+            max_attempts: row.get(5),
+        })
+    }
+}
+
+impl TryFrom<Row> for BackgroundJob {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let kind: &str = row.get(2);
+        let status: &str = row.get(3);
+        Ok(BackgroundJob {
+            id: row.get(0),
+            tenant_id: row.get(1),
+            kind: JobKind::from_db_str(kind)?,
+            status: JobStatus::from_db_str(status)?,
+            attempt_count: row.get(4),
+            max_attempts: row.get(5),
+            run_after_ms: row.get(6),
+            last_error: row.get(7),
+            created_at: row.get(8),
+            updated_at: row.get(9),
+        })
+    }
+}
+
+fn job_status_db_str(status: JobStatus) -> &'static str {
+    match status {
+        JobStatus::Pending => "pending",
+        JobStatus::Running => "running",
+        JobStatus::Succeeded => "succeeded",
+        JobStatus::Dead => "dead",
+    }
+}
+
+#[async_trait]
+impl BackgroundJobDao for BackgroundJobDaoImpl {
+    async fn enqueue_job(&self, job: &EnqueueJobDb) -> Result<Uuid, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(ENQUEUE_JOB, &[job])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn claim_jobs(
+        &self,
+        worker_id: Uuid,
+        now_ms: i64,
+        visibility_timeout_ms: i64,
+        limit: i32,
+    ) -> Result<Vec<ClaimedJob>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(
+                CLAIM_JOBS,
+                &[&worker_id, &now_ms, &visibility_timeout_ms, &limit],
+            )
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn complete_job(&self, job_id: Uuid, worker_id: Uuid) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(COMPLETE_JOB, &[&job_id, &worker_id])
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn fail_job(
+        &self,
+        job_id: Uuid,
+        worker_id: Uuid,
+        run_after_ms: i64,
+        error: &str,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(FAIL_JOB, &[&job_id, &worker_id, &run_after_ms, &error])
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn list_jobs(
+        &self,
+        tenant_id: Uuid,
+        status: Option<JobStatus>,
+        limit: i64,
+    ) -> Result<Vec<BackgroundJob>, DaoError> {
+        let status = status.map(job_status_db_str);
+        self.postgres_client
+            .get()
+            .await?
+            .query(LIST_JOBS, &[&tenant_id, &status, &limit])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn retry_dead_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        run_after_ms: i64,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(RETRY_DEAD_JOB, &[&tenant_id, &job_id, &run_after_ms])
+            .await?;
+        Ok(updated != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::background_job::background_job_dao::{BackgroundJobDao, BackgroundJobDaoImpl};
+    use crate::background_job::background_job_models::{EnqueueJobDb, JobKind, JobStatus};
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn an_enqueue_job_db(max_attempts: i32) -> EnqueueJobDb {
+        EnqueueJobDb {
+            tenant_id: *SEED_TENANT_ID,
+            kind: JobKind::InvoicePdf,
+            idempotence_key: Uuid::now_v7(),
+            payload: serde_json::json!({}),
+            max_attempts,
+            run_after_ms: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_job_is_idempotent() {
+        let dao = get_dao_generic(|c| BackgroundJobDaoImpl { postgres_client: c }, None).await;
+        let job = an_enqueue_job_db(3);
+        let first = dao.enqueue_job(&job).await.unwrap();
+        let second = dao.enqueue_job(&job).await.unwrap();
+        assert_that!(second).is_equal_to(first);
+    }
+
+    #[tokio::test]
+    async fn test_failed_job_is_retried_till_it_is_dead() {
+        let dao = get_dao_generic(|c| BackgroundJobDaoImpl { postgres_client: c }, None).await;
+        let job_id = dao.enqueue_job(&an_enqueue_job_db(2)).await.unwrap();
+        let worker_id = Uuid::now_v7();
+        let claimed = dao.claim_jobs(worker_id, 10, 100, 1000).await.unwrap();
+        assert!(claimed.iter().any(|a| a.id == job_id));
+        //claimed jobs are not handed out again while locked
+        let other_worker = dao.claim_jobs(Uuid::now_v7(), 20, 100, 1000).await.unwrap();
+        assert!(!other_worker.iter().any(|a| a.id == job_id));
+        assert!(dao
+            .fail_job(job_id, worker_id, 50, "timeout")
+            .await
+            .unwrap());
+        let claimed = dao.claim_jobs(worker_id, 40, 100, 1000).await.unwrap();
+        assert!(!claimed.iter().any(|a| a.id == job_id));
+        let claimed = dao.claim_jobs(worker_id, 60, 100, 1000).await.unwrap();
+        let job = claimed.iter().find(|a| a.id == job_id).unwrap();
+        assert_that!(job.attempt_count).is_equal_to(2);
+        dao.fail_job(job_id, worker_id, 500, "timeout")
+            .await
+            .unwrap();
+        let dead = dao
+            .list_jobs(*SEED_TENANT_ID, Some(JobStatus::Dead), 100)
+            .await
+            .unwrap();
+        assert!(dead.iter().any(|a| a.id == job_id));
+        assert!(dao
+            .retry_dead_job(*SEED_TENANT_ID, job_id, 0)
+            .await
+            .unwrap());
+        let pending = dao
+            .list_jobs(*SEED_TENANT_ID, Some(JobStatus::Pending), 100)
+            .await
+            .unwrap();
+        assert_that!(pending
+            .iter()
+            .filter(|a| a.id == job_id)
+            .collect::<Vec<_>>())
+        .has_length(1);
+    }
+
+    #[tokio::test]
+    async fn test_job_of_a_dead_worker_is_claimed_after_visibility_timeout() {
+        let dao = get_dao_generic(|c| BackgroundJobDaoImpl { postgres_client: c }, None).await;
+        let job_id = dao.enqueue_job(&an_enqueue_job_db(3)).await.unwrap();
+        let dead_worker = Uuid::now_v7();
+        dao.claim_jobs(dead_worker, 10, 100, 1000).await.unwrap();
+        let worker_id = Uuid::now_v7();
+        let claimed = dao.claim_jobs(worker_id, 120, 100, 1000).await.unwrap();
+        assert!(claimed.iter().any(|a| a.id == job_id));
+        //the dead worker can no longer complete the job
+        assert!(!dao.complete_job(job_id, dead_worker).await.unwrap());
+        assert!(dao.complete_job(job_id, worker_id).await.unwrap());
+        let succeeded = dao
+            .list_jobs(*SEED_TENANT_ID, Some(JobStatus::Succeeded), 100)
+            .await
+            .unwrap();
+        assert!(succeeded.iter().any(|a| a.id == job_id));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct BackgroundJobDbMapping {}
+
+const BACKGROUND_JOB_DDL_SQL: &str = include_str!("./background_job_sql/background_job_ddl.sql");
+const BACKGROUND_JOB_SEED_DATA: &str = include_str!("./background_job_sql/background_job.csv");
+const BACKGROUND_JOB_INDEXES_SQL: &str =
+    include_str!("./background_job_sql/background_job_indexes.sql");
+const BACKGROUND_JOB_FUNCTIONS_SQL: &str =
+    include_str!("./background_job_sql/background_job_functions_and_procedures.sql");
+impl DbStructMapping for BackgroundJobDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("background_job")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        BACKGROUND_JOB_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        BACKGROUND_JOB_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        BACKGROUND_JOB_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        BACKGROUND_JOB_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::background_job::background_job_models::ListJobsRequest;
+use crate::background_job::background_job_service::{
+    BackgroundJobService, BackgroundJobServiceError,
+};
+use crate::common_utils::utils::TenantId;
+use crate::setup_routes;
+
+impl ResponseError for BackgroundJobServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BackgroundJobServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            BackgroundJobServiceError::DeadJobNotFound(_) => StatusCode::NOT_FOUND,
+        }
+    }
+}
+
+async fn list_jobs(
+    data: Data<Arc<dyn BackgroundJobService>>,
+    request: web::Json<ListJobsRequest>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .list_jobs(tenant_id.inner(), request.into_inner().status)
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn retry_dead_job(
+    data: Data<Arc<dyn BackgroundJobService>>,
+    job_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    data.retry_dead_job(tenant_id.inner(), job_id.into_inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+setup_routes!(
+    BackgroundJobService,
+    "/background-job",
+    "/list",
+    web::post().to(list_jobs),
+    "/id/{job_id}/retry",
+    web::post().to(retry_dead_job)
+);
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use anyhow::bail;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///attempts of a job before it is moved to dead
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 8;
+const RETRY_BACKOFF_BASE: Duration = Duration::from_secs(30);
+const RETRY_BACKOFF_MAX: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "background_job_kind", rename_all = "snake_case")]
+pub enum JobKind {
+    InvoicePdf,
+    EInvoice,
+    InvoiceEmail,
+}
+
+impl JobKind {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let kind = match value {
+            "invoice_pdf" => JobKind::InvoicePdf,
+            "e_invoice" => JobKind::EInvoice,
+            "invoice_email" => JobKind::InvoiceEmail,
+            _ => bail!("{} is not a valid background job kind", value),
+        };
+        Ok(kind)
+    }
+}
+
+///a job is pending until a worker claims it, failed attempts put it back to pending till it runs out of attempts
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "background_job_status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Dead,
+}
+
+impl JobStatus {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let status = match value {
+            "pending" => JobStatus::Pending,
+            "running" => JobStatus::Running,
+            "succeeded" => JobStatus::Succeeded,
+            "dead" => JobStatus::Dead,
+            _ => bail!("{} is not a valid background job status", value),
+        };
+        Ok(status)
+    }
+}
+
+///wait before the next attempt, doubles with every failed attempt
+pub fn retry_backoff(attempt_count: i32) -> Duration {
+    let exponent = attempt_count.clamp(1, 16) as u32 - 1;
+    RETRY_BACKOFF_BASE
+        .saturating_mul(2_u32.pow(exponent))
+        .min(RETRY_BACKOFF_MAX)
+}
+
+#[derive(Debug, Clone)]
+pub struct EnqueueJobRequest {
+    pub kind: JobKind,
+    ///enqueueing the same kind and key again returns the existing job
+    pub idempotence_key: Uuid,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "enqueue_background_job_request")]
+pub(crate) struct EnqueueJobDb {
+    pub tenant_id: Uuid,
+    pub kind: JobKind,
+    pub idempotence_key: Uuid,
+    pub payload: serde_json::Value,
+    pub max_attempts: i32,
+    pub run_after_ms: i64,
+}
+
+///a job locked by a worker for one attempt, attempt_count includes the current attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimedJob {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: JobKind,
+    pub payload: serde_json::Value,
+    pub attempt_count: i32,
+    pub max_attempts: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BackgroundJob {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub attempt_count: i32,
+    pub max_attempts: i32,
+    pub run_after_ms: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListJobsRequest {
+    ///all statuses when not given
+    pub status: Option<JobStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use crate::background_job::background_job_models::retry_backoff;
+
+    #[rstest]
+    #[case(1, 30)]
+    #[case(2, 60)]
+    #[case(4, 240)]
+    #[case(8, 3600)]
+    #[case(100, 3600)]
+    fn test_retry_backoff(#[case] attempt_count: i32, #[case] seconds: u64) {
+        assert_eq!(retry_backoff(attempt_count), Duration::from_secs(seconds));
+    }
+}
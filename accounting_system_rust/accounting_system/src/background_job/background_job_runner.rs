@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+#[cfg(test)]
+use mockall::automock;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::background_job::background_job_models::{ClaimedJob, JobKind};
+use crate::background_job::background_job_service::{
+    BackgroundJobService, BackgroundJobServiceError,
+};
+
+pub const BACKGROUND_JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+///jobs claimed by a worker in one pass, they are run concurrently
+pub const BACKGROUND_JOB_BATCH_SIZE: i32 = 20;
+const BACKGROUND_JOB_VISIBILITY_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+///an attempt is abandoned before its visibility timeout expires so that it is not run twice in parallel
+const BACKGROUND_JOB_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(4 * 60);
+
+///runs the jobs of one kind. a job can be attempted more than once, handlers have to be idempotent
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    fn kind(&self) -> JobKind;
+    async fn handle(&self, job: &ClaimedJob) -> anyhow::Result<()>;
+}
+
+async fn run_job(
+    service: &dyn BackgroundJobService,
+    handlers: &HashMap<JobKind, Arc<dyn JobHandler>>,
+    job: &ClaimedJob,
+    worker_id: Uuid,
+) -> Result<(), BackgroundJobServiceError> {
+    let outcome = match handlers.get(&job.kind) {
+        Some(handler) => {
+            match tokio::time::timeout(BACKGROUND_JOB_ATTEMPT_TIMEOUT, handler.handle(job)).await {
+                Ok(outcome) => outcome.map_err(|e| format!("{:#}", e)),
+                Err(_) => Err("attempt timed out".to_string()),
+            }
+        }
+        None => Err(format!("no handler registered for {:?} jobs", job.kind)),
+    };
+    match outcome {
+        Ok(()) => service.complete_job(job, worker_id).await,
+        Err(e) => {
+            warn!(
+                job_id = %job.id,
+                tenant_id = %job.tenant_id,
+                attempt = job.attempt_count,
+                error = e.as_str(),
+                "background job attempt failed"
+            );
+            service.fail_job(job, worker_id, e.as_str()).await
+        }
+    }
+}
+
+///claims a batch of due jobs and runs them, returns the number of jobs claimed
+pub async fn run_due_jobs(
+    service: &dyn BackgroundJobService,
+    handlers: &HashMap<JobKind, Arc<dyn JobHandler>>,
+    worker_id: Uuid,
+) -> Result<usize, BackgroundJobServiceError> {
+    let jobs = service
+        .claim_jobs(
+            worker_id,
+            BACKGROUND_JOB_VISIBILITY_TIMEOUT,
+            BACKGROUND_JOB_BATCH_SIZE,
+        )
+        .await?;
+    let results = join_all(
+        jobs.iter()
+            .map(|job| run_job(service, handlers, job, worker_id)),
+    )
+    .await;
+    for e in results.into_iter().filter_map(|a| a.err()) {
+        //the job is claimed again once its visibility timeout expires
+        error!(%e, "could not record outcome of background job");
+    }
+    Ok(jobs.len())
+}
+
+///polls for due jobs every interval, full batches are followed by the next batch right away
+pub fn spawn_background_job_runner(
+    service: Arc<dyn BackgroundJobService>,
+    handlers: Vec<Arc<dyn JobHandler>>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    let worker_id = Uuid::now_v7();
+    let handlers: HashMap<JobKind, Arc<dyn JobHandler>> =
+        handlers.into_iter().map(|a| (a.kind(), a)).collect();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            loop {
+                match run_due_jobs(service.as_ref(), &handlers, worker_id).await {
+                    Ok(claimed) if claimed == BACKGROUND_JOB_BATCH_SIZE as usize => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        error!(%e, "background job run failed");
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use anyhow::anyhow;
+    use uuid::Uuid;
+
+    use crate::background_job::background_job_models::{ClaimedJob, JobKind};
+    use crate::background_job::background_job_runner::{run_due_jobs, JobHandler, MockJobHandler};
+    use crate::background_job::background_job_service::MockBackgroundJobService;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_claimed_job(kind: JobKind, payload: serde_json::Value) -> ClaimedJob {
+        ClaimedJob {
+            id: Uuid::now_v7(),
+            tenant_id: *SEED_TENANT_ID,
+            kind,
+            payload,
+            attempt_count: 1,
+            max_attempts: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_due_jobs_records_outcome_of_each_job() {
+        let succeeding = a_claimed_job(JobKind::InvoicePdf, serde_json::json!({"fail": false}));
+        let failing = a_claimed_job(JobKind::InvoicePdf, serde_json::json!({"fail": true}));
+        let unhandled = a_claimed_job(JobKind::InvoiceEmail, serde_json::json!({}));
+        let jobs = vec![succeeding.clone(), failing.clone(), unhandled.clone()];
+        let mut service = MockBackgroundJobService::new();
+        service
+            .expect_claim_jobs()
+            .times(1)
+            .returning(move |_, _, _| Ok(jobs.clone()));
+        let succeeding_id = succeeding.id;
+        service
+            .expect_complete_job()
+            .withf(move |job, _| job.id == succeeding_id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+        let failing_id = failing.id;
+        service
+            .expect_fail_job()
+            .withf(move |job, _, error| job.id == failing_id && error == "could not render pdf")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let unhandled_id = unhandled.id;
+        service
+            .expect_fail_job()
+            .withf(move |job, _, error| {
+                job.id == unhandled_id && error.starts_with("no handler registered")
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let mut handler = MockJobHandler::new();
+        handler.expect_handle().returning(|job| {
+            if job.payload["fail"].as_bool().unwrap() {
+                Err(anyhow!("could not render pdf"))
+            } else {
+                Ok(())
+            }
+        });
+        let handlers: HashMap<JobKind, Arc<dyn JobHandler>> = HashMap::from([(
+            JobKind::InvoicePdf,
+            Arc::new(handler) as Arc<dyn JobHandler>,
+        )]);
+        let claimed = run_due_jobs(&service, &handlers, Uuid::now_v7())
+            .await
+            .unwrap();
+        assert_eq!(claimed, 3);
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::background_job::background_job_dao::{get_background_job_dao, BackgroundJobDao};
+use crate::background_job::background_job_models::{
+    retry_backoff, BackgroundJob, ClaimedJob, EnqueueJobDb, EnqueueJobRequest, JobStatus,
+    DEFAULT_MAX_ATTEMPTS,
+};
+use crate::common_utils::dao_error::DaoError;
+
+///jobs returned by the admin listing, newest first
+const LIST_JOBS_LIMIT: i64 = 200;
+const LAST_ERROR_MAX_LENGTH: usize = 1000;
+
+#[derive(Debug, Error)]
+pub enum BackgroundJobServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("background job {0} not found or not dead")]
+    DeadJobNotFound(Uuid),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait BackgroundJobService: Send + Sync {
+    ///enqueueing the same kind and idempotence key again returns the existing job
+    async fn enqueue(
+        &self,
+        tenant_id: Uuid,
+        req: EnqueueJobRequest,
+    ) -> Result<Uuid, BackgroundJobServiceError>;
+    async fn list_jobs(
+        &self,
+        tenant_id: Uuid,
+        status: Option<JobStatus>,
+    ) -> Result<Vec<BackgroundJob>, BackgroundJobServiceError>;
+    ///moves a dead job back to pending with all its attempts available again
+    async fn retry_dead_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<(), BackgroundJobServiceError>;
+    ///due jobs of all tenants, no other worker is handed these jobs till the visibility timeout expires
+    async fn claim_jobs(
+        &self,
+        worker_id: Uuid,
+        visibility_timeout: Duration,
+        limit: i32,
+    ) -> Result<Vec<ClaimedJob>, BackgroundJobServiceError>;
+    async fn complete_job(
+        &self,
+        job: &ClaimedJob,
+        worker_id: Uuid,
+    ) -> Result<(), BackgroundJobServiceError>;
+    ///the job is attempted again after an exponential backoff, or moved to dead when out of attempts
+    async fn fail_job(
+        &self,
+        job: &ClaimedJob,
+        worker_id: Uuid,
+        error: &str,
+    ) -> Result<(), BackgroundJobServiceError>;
+}
+
+struct BackgroundJobServiceImpl {
+    dao: Arc<dyn BackgroundJobDao>,
+}
+
+pub fn get_background_job_service(arc: Arc<Pool>) -> Arc<dyn BackgroundJobService> {
+    let dao = get_background_job_dao(arc);
+    let service = BackgroundJobServiceImpl { dao };
+    Arc::new(service)
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[async_trait]
+impl BackgroundJobService for BackgroundJobServiceImpl {
+    async fn enqueue(
+        &self,
+        tenant_id: Uuid,
+        req: EnqueueJobRequest,
+    ) -> Result<Uuid, BackgroundJobServiceError> {
+        let job = EnqueueJobDb {
+            tenant_id,
+            kind: req.kind,
+            idempotence_key: req.idempotence_key,
+            payload: req.payload,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            run_after_ms: now_ms(),
+        };
+        Ok(self.dao.enqueue_job(&job).await?)
+    }
+
+    async fn list_jobs(
+        &self,
+        tenant_id: Uuid,
+        status: Option<JobStatus>,
+    ) -> Result<Vec<BackgroundJob>, BackgroundJobServiceError> {
+        Ok(self
+            .dao
+            .list_jobs(tenant_id, status, LIST_JOBS_LIMIT)
+            .await?)
+    }
+
+    async fn retry_dead_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<(), BackgroundJobServiceError> {
+        let updated = self.dao.retry_dead_job(tenant_id, job_id, now_ms()).await?;
+        if !updated {
+            return Err(BackgroundJobServiceError::DeadJobNotFound(job_id));
+        }
+        Ok(())
+    }
+
+    async fn claim_jobs(
+        &self,
+        worker_id: Uuid,
+        visibility_timeout: Duration,
+        limit: i32,
+    ) -> Result<Vec<ClaimedJob>, BackgroundJobServiceError> {
+        Ok(self
+            .dao
+            .claim_jobs(
+                worker_id,
+                now_ms(),
+                visibility_timeout.as_millis() as i64,
+                limit,
+            )
+            .await?)
+    }
+
+    async fn complete_job(
+        &self,
+        job: &ClaimedJob,
+        worker_id: Uuid,
+    ) -> Result<(), BackgroundJobServiceError> {
+        //a job whose lock expired is already claimed by another worker, its outcome is not recorded here
+        self.dao.complete_job(job.id, worker_id).await?;
+        Ok(())
+    }
+
+    async fn fail_job(
+        &self,
+        job: &ClaimedJob,
+        worker_id: Uuid,
+        error: &str,
+    ) -> Result<(), BackgroundJobServiceError> {
+        let run_after_ms = now_ms() + retry_backoff(job.attempt_count).as_millis() as i64;
+        let error: String = error.chars().take(LAST_ERROR_MAX_LENGTH).collect();
+        self.dao
+            .fail_job(job.id, worker_id, run_after_ms, error.as_str())
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+create type background_job_kind as enum ('invoice_pdf','e_invoice','invoice_email');
+create type background_job_status as enum ('pending','running','succeeded','dead');
+
+create table background_job
+(
+    id              uuid primary key,
+    tenant_id       uuid references tenant (id) not null,
+    kind            background_job_kind         not null,
+    idempotence_key uuid                        not null,--a job is enqueued once for a kind and key
+    payload         jsonb                       not null,
+    status          background_job_status       not null,
+    attempt_count   integer                     not null,
+    max_attempts    integer                     not null,
+    run_after_ms    bigint                      not null,--earliest time of the next attempt
+    locked_by       uuid,--worker running the current attempt
+    locked_until_ms bigint,--visibility timeout of the current attempt, expired jobs are claimed again
+    last_error      varchar(1000),--reason of the last failed attempt
+    created_at      bigint default extract(epoch from now()) * 1000000,
+    updated_at      bigint default extract(epoch from now()) * 1000000
+);
@@ -0,0 +1,78 @@
+create type enqueue_background_job_request as
+(
+    tenant_id       uuid,
+    kind            background_job_kind,
+    idempotence_key uuid,
+    payload         jsonb,
+    max_attempts    integer,
+    run_after_ms    bigint
+);
+
+--returns the id of the job already enqueued for the kind and key, if any
+create or replace function enqueue_background_job(req enqueue_background_job_request) returns uuid as
+$$
+DECLARE
+    _job_id uuid;
+BEGIN
+    insert into background_job (id, tenant_id, kind, idempotence_key, payload, status, attempt_count, max_attempts,
+                                run_after_ms, locked_by, locked_until_ms, last_error, created_at, updated_at)
+    values (uuid_generate_v7(), req.tenant_id, req.kind, req.idempotence_key, req.payload, 'pending', 0,
+            req.max_attempts, req.run_after_ms, null, null, null, default, default)
+    on conflict (tenant_id, kind, idempotence_key) do nothing
+    returning id into _job_id;
+    if _job_id is null then
+        select id
+        from background_job
+        where background_job.tenant_id = req.tenant_id
+          and background_job.kind = req.kind
+          and background_job.idempotence_key = req.idempotence_key
+        into _job_id;
+    end if;
+    return _job_id;
+end;
+$$ language plpgsql;
+
+--claims the due jobs of all tenants for a worker. pending jobs are due once run_after_ms has passed and running jobs
+--once their visibility timeout expired, which happens when a worker died midway. skip locked lets parallel workers
+--claim disjoint jobs. an expired job without attempts left is moved to dead instead of being claimed again
+create or replace function claim_background_jobs(_worker_id uuid, _now_ms bigint, _visibility_timeout_ms bigint,
+                                                 _limit integer)
+    returns table
+            (
+                id            uuid,
+                tenant_id     uuid,
+                kind          text,
+                payload       jsonb,
+                attempt_count integer,
+                max_attempts  integer
+            )
+as
+$$
+BEGIN
+    update background_job
+    set status          = 'dead',
+        locked_by       = null,
+        locked_until_ms = null,
+        last_error      = coalesce(background_job.last_error, 'visibility timeout expired on the last attempt'),
+        updated_at      = extract(epoch from now()) * 1000000
+    where background_job.status = 'running'
+      and background_job.locked_until_ms <= _now_ms
+      and background_job.attempt_count >= background_job.max_attempts;
+    return query
+        with due as (select j.id
+                     from background_job j
+                     where (j.status = 'pending' and j.run_after_ms <= _now_ms)
+                        or (j.status = 'running' and j.locked_until_ms <= _now_ms)
+                     order by j.run_after_ms
+                     limit _limit for update skip locked)
+        update background_job j
+        set status          = 'running',
+            attempt_count   = j.attempt_count + 1,
+            locked_by       = _worker_id,
+            locked_until_ms = _now_ms + _visibility_timeout_ms,
+            updated_at      = extract(epoch from now()) * 1000000
+        from due
+        where j.id = due.id
+        returning j.id, j.tenant_id, j.kind::text, j.payload, j.attempt_count, j.max_attempts;
+end;
+$$ language plpgsql;
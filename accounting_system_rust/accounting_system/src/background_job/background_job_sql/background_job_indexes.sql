@@ -0,0 +1,4 @@
+create unique index if not exists background_job_idempotence_idx on background_job (tenant_id, kind, idempotence_key);
+create index if not exists background_job_pending_idx on background_job (run_after_ms) where status = 'pending';
+create index if not exists background_job_running_idx on background_job (locked_until_ms) where status = 'running';
+create index if not exists background_job_tenant_status_idx on background_job (tenant_id, status);
@@ -0,0 +1,6 @@
+mod background_job_dao;
+pub mod background_job_db_mapping;
+pub mod background_job_http_api;
+pub mod background_job_models;
+pub mod background_job_runner;
+pub mod background_job_service;
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+
+///delivery of documents to the customers of a tenant
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait EmailClient: Send + Sync {
+    ///false till the email provider is set up, no email job is enqueued then
+    fn is_configured(&self) -> bool;
+    async fn send_invoice_email(
+        &self,
+        to: &str,
+        invoice_number: &str,
+        pdf_url: &str,
+    ) -> anyhow::Result<()>;
+}
+
+struct UnconfiguredEmailClient {}
+
+#[async_trait]
+impl EmailClient for UnconfiguredEmailClient {
+    fn is_configured(&self) -> bool {
+        false
+    }
+
+    async fn send_invoice_email(
+        &self,
+        _to: &str,
+        invoice_number: &str,
+        _pdf_url: &str,
+    ) -> anyhow::Result<()> {
+        bail!(
+            "email provider is not configured, invoice {} could not be sent",
+            invoice_number
+        )
+    }
+}
+
+//todo plug in smtp/email provider client once credentials management is in place
+pub fn get_email_client() -> Arc<dyn EmailClient> {
+    Arc::new(UnconfiguredEmailClient {})
+}
@@ -2,6 +2,7 @@ pub mod cache_utils;
 pub mod common_utils_db_mapping;
 pub mod dao_error;
 pub mod db_row_conversion_utils;
+pub mod email_client;
 pub mod macro_utils;
 pub mod mime_types;
 pub mod pagination;
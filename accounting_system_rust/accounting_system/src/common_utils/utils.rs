@@ -70,6 +70,15 @@ pub fn current_indian_date() -> NaiveDate {
     current_date
 }
 
+///calendar date in indian standard time of the given epoch millis
+pub fn epoch_ms_to_indian_date(epoch_ms: i64) -> anyhow::Result<NaiveDate> {
+    let date = DateTime::from_timestamp_millis(epoch_ms)
+        .ok_or_else(|| anyhow!("invalid epoch millis {}", epoch_ms))?
+        .with_timezone(&chrono_tz::Asia::Kolkata)
+        .date_naive();
+    Ok(date)
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Error)]
 pub enum TenantIdHeaderError {
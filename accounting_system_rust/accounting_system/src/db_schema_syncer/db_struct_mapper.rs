@@ -13,16 +13,26 @@ use crate::accounting::account::account_type::account_type_db_mapping::AccountTy
 use crate::accounting::currency::currency_db_mapping::CurrencyDbMapping;
 use crate::accounting::user::user_db_mapping::UserDbMapping;
 use crate::audit_table::audit_table_db_mapping::AuditTableDbMapping;
+use crate::background_job::background_job_db_mapping::BackgroundJobDbMapping;
 use crate::common_utils::common_utils_db_mapping::CommonUtilsDbMapping;
 use crate::common_utils::pagination::pagination_db_mapping::PaginationDataDbMapping;
+use crate::gst_returns::gstr1::gstr1_db_mapping::Gstr1FilingDbMapping;
 use crate::invoicing::additional_charge::additional_charge_db_mapping::AdditionalChargeDbMapping;
+use crate::invoicing::delivery_challan::delivery_challan_db_mapping::DeliveryChallanDbMapping;
+use crate::invoicing::eway_bill::eway_bill_db_mapping::EwayBillDbMapping;
+use crate::invoicing::invoice_approval::invoice_approval_db_mapping::InvoiceApprovalDbMapping;
 use crate::invoicing::invoice_template::invoice_template_db_mapping::InvoiceTemplateDbMapping;
+use crate::invoicing::invoice_template::invoice_template_version_db_mapping::InvoiceTemplateVersionDbMapping;
 use crate::invoicing::invoicing_db_mapping::InvoicingDbMapping;
 use crate::invoicing::invoicing_series::invoicing_series_counter_db_mapping::InvoicingSeriesCounterDbMapping;
 use crate::invoicing::invoicing_series::invoicing_series_mst_db_mapping::InvoicingSeriesMstDbMapping;
 use crate::invoicing::line_subtitle::line_subtitle_db_mapping::LineSubtitleDbMapping;
 use crate::invoicing::line_title::line_title_db_mapping::LineTitleDbMapping;
 use crate::invoicing::payment_term::payment_term_db_mapping::PaymentTermDbMapping;
+use crate::invoicing::purchase_invoice::purchase_invoice_db_mapping::PurchaseInvoiceDbMapping;
+use crate::invoicing::quotation::quotation_db_mapping::QuotationDbMapping;
+use crate::invoicing::receipt::receipt_db_mapping::ReceiptDbMapping;
+use crate::invoicing::recurring_invoice::recurring_invoice_db_mapping::RecurringInvoiceDbMapping;
 use crate::ledger::ledger_transfer_db_mapping::LedgerTransferDbMapping;
 use crate::ledger::ledgermaster::ledger_db_mapping::LedgerMasterDbMapping;
 use crate::masters::address_master::address_db_mapping::AddressDbMapping;
@@ -116,11 +126,21 @@ fn get_registered_table_mappings() -> Vec<Box<dyn DbStructMapping>> {
         Box::new(InvoicingSeriesMstDbMapping {}),
         Box::new(InvoicingSeriesCounterDbMapping {}),
         Box::new(InvoiceTemplateDbMapping {}),
+        Box::new(InvoiceTemplateVersionDbMapping {}),
         Box::new(InvoicingDbMapping {}),
         Box::new(AdditionalChargeDbMapping {}),
+        Box::new(EwayBillDbMapping {}),
+        Box::new(InvoiceApprovalDbMapping {}),
+        Box::new(ReceiptDbMapping {}),
+        Box::new(QuotationDbMapping {}),
+        Box::new(RecurringInvoiceDbMapping {}),
+        Box::new(DeliveryChallanDbMapping {}),
         Box::new(ProductItemDbMapping {}),
         Box::new(ProductTaxRateDbMapping {}),
         Box::new(ProductCessRateDbMapping {}),
+        Box::new(PurchaseInvoiceDbMapping {}),
+        Box::new(Gstr1FilingDbMapping {}),
+        Box::new(BackgroundJobDbMapping {}),
     ];
     list
 }
@@ -0,0 +1,108 @@
+use anyhow::{anyhow, ensure};
+use chrono::{NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+///tax period of a return in portal format MMYYYY
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct ReturnPeriod {
+    month: u32,
+    year: i32,
+}
+
+impl ReturnPeriod {
+    pub fn new(month: u32, year: i32) -> anyhow::Result<Self> {
+        ensure!(
+            (1..=12).contains(&month),
+            "return period month should be between 1 and 12"
+        );
+        ensure!(
+            (2017..=9999).contains(&year),
+            "return period year cannot be before gst roll out in 2017"
+        );
+        Ok(ReturnPeriod { month, year })
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    ///MMYYYY as expected by the gst portal
+    pub fn to_portal_format(self) -> String {
+        format!("{:02}{}", self.month, self.year)
+    }
+
+    ///start(inclusive) and end(exclusive) of the period in epoch millis as per indian standard time
+    pub fn epoch_millis_range(&self) -> anyhow::Result<(i64, i64)> {
+        let (next_month, next_year) = if self.month == 12 {
+            (1, self.year + 1)
+        } else {
+            (self.month + 1, self.year)
+        };
+        let start = to_ist_epoch_millis(self.year, self.month)?;
+        let end = to_ist_epoch_millis(next_year, next_month)?;
+        Ok((start, end))
+    }
+}
+
+fn to_ist_epoch_millis(year: i32, month: u32) -> anyhow::Result<i64> {
+    let date = NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|a| a.and_hms_opt(0, 0, 0))
+        .ok_or_else(|| anyhow!("invalid return period {:02}{}", month, year))?;
+    let ist = chrono_tz::Asia::Kolkata
+        .from_local_datetime(&date)
+        .single()
+        .ok_or_else(|| anyhow!("invalid return period {:02}{}", month, year))?;
+    Ok(ist.timestamp_millis())
+}
+
+impl TryFrom<String> for ReturnPeriod {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        ensure!(
+            value.len() == 6 && value.chars().all(|a| a.is_ascii_digit()),
+            "return period should be in MMYYYY format"
+        );
+        let month = value[0..2].parse::<u32>()?;
+        let year = value[2..6].parse::<i32>()?;
+        ReturnPeriod::new(month, year)
+    }
+}
+
+impl From<ReturnPeriod> for String {
+    fn from(value: ReturnPeriod) -> Self {
+        value.to_portal_format()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::gst_returns::gst_returns_models::ReturnPeriod;
+
+    #[rstest]
+    #[case("012024", true)]
+    #[case("132024", false)]
+    #[case("012016", false)]
+    #[case("1-2024", false)]
+    fn test_return_period_parsing(#[case] input: &str, #[case] valid: bool) {
+        assert_eq!(ReturnPeriod::try_from(input.to_string()).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_epoch_millis_range() {
+        let period = ReturnPeriod::new(12, 2023).unwrap();
+        let (start, end) = period.epoch_millis_range().unwrap();
+        //2023-11-30T18:30:00Z and 2023-12-31T18:30:00Z
+        assert_eq!(start, 1701369000000);
+        assert_eq!(end, 1704047400000);
+        assert_eq!(period.to_portal_format(), "122023");
+    }
+}
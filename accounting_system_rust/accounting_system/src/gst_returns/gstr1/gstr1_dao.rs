@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::gst_returns::gstr1::gstr1_models::{
+    Gstr1AdditionalChargeDb, Gstr1FilingDb, Gstr1InvoiceLineDb,
+};
+
+const GSTR1_LINES_QUERY: &str = "select i.id,i.invoice_number,i.invoice_date_ms,i.service_invoice,\
+coalesce(i.igst_applicable,false),i.ecommerce_gstin,i.total_payable_amount,ss.state_code,\
+b.gstin,bs.state_code,ba.country,shs.state_code,sha.country,\
+lt.hsn_code,lt.description,il.uqc,il.quantity,il.unit_price,il.discount_percentage,il.tax_percentage,\
+il.cess_percentage,il.cess_amount_per_unit,il.retail_sale_price_for_cess,\
+il.cess_calculation_strategy::text,il.reverse_charge_applicable,\
+i.export_type::text,i.port_code,i.shipping_bill_number,i.shipping_bill_date_ms,i.place_of_supply,\
+i.supply_classification::text,i.document_type::text \
+from invoice i \
+join business_entity s on i.supplier_business_entity=s.id \
+left join address sa on s.address_id=sa.id \
+left join state_master ss on sa.state_id=ss.id \
+left join business_entity b on i.billed_to_business_entity=b.id \
+left join address ba on b.address_id=ba.id \
+left join state_master bs on ba.state_id=bs.id \
+left join business_entity sh on i.shipped_to_business_entity=sh.id \
+left join address sha on sh.address_id=sha.id \
+left join state_master shs on sha.state_id=shs.id \
+join invoice_line il on il.invoice_table_id=i.id and il.tenant_id=i.tenant_id \
+join line_title lt on il.line_title_hsn_sac_id=lt.id \
+where i.tenant_id=$1 and upper(s.gstin)=upper($2) and i.invoice_date_ms>=$3 and i.invoice_date_ms<$4 \
+and i.active and il.active and i.invoice_status='issued' \
+order by i.invoice_date_ms,i.invoice_number,il.line_number";
+
+const GSTR1_ADDITIONAL_CHARGES_QUERY: &str = "select ac.invoice_table_id,ac.rate,ac.tax_percentage \
+from additional_charge ac \
+join invoice i on ac.invoice_table_id=i.id and ac.tenant_id=i.tenant_id \
+join business_entity s on i.supplier_business_entity=s.id \
+where i.tenant_id=$1 and upper(s.gstin)=upper($2) and i.invoice_date_ms>=$3 and i.invoice_date_ms<$4 \
+and i.active and i.invoice_status='issued' \
+order by i.invoice_date_ms,i.invoice_number,ac.line_no";
+
+const MARK_GSTR1_FILED_QUERY: &str =
+    "insert into gstr1_filing (id,tenant_id,active,approval_status,\
+gstin,return_period,period_start_ms,period_end_ms,arn,created_by,updated_by) \
+values ($1,$2,true,1,upper($3),$4,$5,$6,$7,$8,$8) \
+on conflict (tenant_id,gstin,return_period) do nothing";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait Gstr1Dao: Send + Sync {
+    ///invoice lines of invoices issued by the gstin with invoice date in [start_ms,end_ms)
+    async fn get_invoice_lines_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<Gstr1InvoiceLineDb>, DaoError>;
+    ///additional charges of the invoices returned by get_invoice_lines_for_period
+    async fn get_additional_charges_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<Gstr1AdditionalChargeDb>, DaoError>;
+    ///returns false when the period was already marked as filed
+    async fn mark_gstr1_filed<'a>(&self, filing: &Gstr1FilingDb<'a>) -> Result<bool, DaoError>;
+}
+
+struct Gstr1DaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_gstr1_dao(arc: Arc<Pool>) -> Arc<dyn Gstr1Dao> {
+    let dao = Gstr1DaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for Gstr1InvoiceLineDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(Gstr1InvoiceLineDb {
+            invoice_id: row.get(0),
+            invoice_number: row.get(1),
+            invoice_date_ms: row.get(2),
+            service_invoice: row.get(3),
+            igst_applicable: row.get(4),
+            ecommerce_gstin: row.get(5),
+            total_payable_amount: row.get(6),
+            supplier_state_code: row.get(7),
+            billed_to_gstin: row.get(8),
+            billed_to_state_code: row.get(9),
+            billed_to_country_id: row.get(10),
+            shipped_to_state_code: row.get(11),
+            shipped_to_country_id: row.get(12),
+            hsn_code: row.get(13),
+            description: row.get(14),
+            uqc: row.get(15),
+            quantity: row.get(16),
+            unit_price: row.get(17),
+            discount_percentage: row.get(18),
+            tax_percentage: row.get(19),
+            cess_percentage: row.get(20),
+            cess_amount_per_unit: row.get(21),
+            retail_sale_price_for_cess: row.get(22),
+            cess_calculation_strategy: row.get(23),
+            reverse_charge_applicable: row.get(24),
+            export_type: row.get(25),
+            port_code: row.get(26),
+            shipping_bill_number: row.get(27),
+            shipping_bill_date_ms: row.get(28),
+            place_of_supply: row.get(29),
+            supply_classification: row.get(30),
+            document_type: row.get(31),
+        })
+    }
+}
+
+#[async_trait]
+impl Gstr1Dao for Gstr1DaoImpl {
+    async fn get_invoice_lines_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<Gstr1InvoiceLineDb>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(GSTR1_LINES_QUERY, &[&tenant_id, &gstin, &start_ms, &end_ms])
+            .await?;
+        rows.into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<Gstr1InvoiceLineDb>, DaoError>>()
+    }
+
+    async fn get_additional_charges_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<Gstr1AdditionalChargeDb>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(
+                GSTR1_ADDITIONAL_CHARGES_QUERY,
+                &[&tenant_id, &gstin, &start_ms, &end_ms],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|a| Gstr1AdditionalChargeDb {
+                invoice_id: a.get(0),
+                rate: a.get(1),
+                tax_percentage: a.get(2),
+            })
+            .collect())
+    }
+
+    async fn mark_gstr1_filed<'a>(&self, filing: &Gstr1FilingDb<'a>) -> Result<bool, DaoError> {
+        let inserted = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(
+                MARK_GSTR1_FILED_QUERY,
+                &[
+                    &Uuid::now_v7(),
+                    &filing.tenant_id,
+                    &filing.gstin,
+                    &filing.return_period,
+                    &filing.period_start_ms,
+                    &filing.period_end_ms,
+                    &filing.arn,
+                    &filing.created_by,
+                ],
+            )
+            .await?;
+        Ok(inserted == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::gst_returns::gstr1::gstr1_dao::{Gstr1Dao, Gstr1DaoImpl};
+    use crate::gst_returns::gstr1::gstr1_models::Gstr1FilingDb;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_get_invoice_lines_for_period() {
+        let dao = get_dao_generic(|c| Gstr1DaoImpl { postgres_client: c }, None).await;
+        let lines = dao
+            .get_invoice_lines_for_period(
+                *SEED_TENANT_ID,
+                "05AABCA5291p1ZD",
+                1704047400000,
+                1706725800000,
+            )
+            .await
+            .unwrap();
+        assert!(lines
+            .iter()
+            .all(|a| (1704047400000..1706725800000).contains(&a.invoice_date_ms)));
+        let charges = dao
+            .get_additional_charges_for_period(
+                *SEED_TENANT_ID,
+                "05AABCA5291p1ZD",
+                1704047400000,
+                1706725800000,
+            )
+            .await
+            .unwrap();
+        assert!(charges
+            .iter()
+            .all(|a| lines.iter().any(|l| l.invoice_id == a.invoice_id)));
+    }
+
+    #[tokio::test]
+    async fn test_mark_gstr1_filed_is_idempotent() {
+        let dao = get_dao_generic(|c| Gstr1DaoImpl { postgres_client: c }, None).await;
+        let filing = Gstr1FilingDb {
+            tenant_id: *SEED_TENANT_ID,
+            gstin: "05AABCA5291p1ZD",
+            return_period: "012024".to_string(),
+            period_start_ms: 1704047400000,
+            period_end_ms: 1706725800000,
+            arn: None,
+            created_by: *SEED_USER_ID,
+        };
+        assert!(dao.mark_gstr1_filed(&filing).await.unwrap());
+        assert!(!dao.mark_gstr1_filed(&filing).await.unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct Gstr1FilingDbMapping {}
+
+const GSTR1_FILING_DDL_SQL: &str = include_str!("./gstr1_sql/gstr1_filing_ddl.sql");
+const GSTR1_FILING_SEED_DATA: &str = include_str!("./gstr1_sql/gstr1_filing.csv");
+impl DbStructMapping for Gstr1FilingDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("gstr1_filing")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        GSTR1_FILING_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        ""
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        ""
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        GSTR1_FILING_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
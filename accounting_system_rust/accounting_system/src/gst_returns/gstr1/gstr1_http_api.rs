@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+
+use crate::common_utils::mime_types::MimeType;
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::gst_returns::gstr1::gstr1_models::{Gstr1Request, Gstr1Section, MarkGstr1FiledRequest};
+use crate::gst_returns::gstr1::gstr1_service::{Gstr1Service, Gstr1ServiceError};
+use crate::setup_routes;
+
+impl ResponseError for Gstr1ServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Gstr1ServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Gstr1ServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            Gstr1ServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn generate_gstr1_json(
+    data: Data<Arc<dyn Gstr1Service>>,
+    request: web::Json<Gstr1Request>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let json = data
+        .generate_gstr1_summary(&request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(json))
+}
+
+async fn generate_gstr1_section_csv(
+    data: Data<Arc<dyn Gstr1Service>>,
+    section: Path<Gstr1Section>,
+    request: web::Json<Gstr1Request>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let csv = data
+        .generate_gstr1_section_csv(
+            &request.into_inner(),
+            section.into_inner(),
+            tenant_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .content_type(MimeType::Csv.get_mime_type())
+        .body(csv))
+}
+
+async fn mark_gstr1_filed(
+    data: Data<Arc<dyn Gstr1Service>>,
+    request: web::Json<MarkGstr1FiledRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.mark_gstr1_filed(&request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+setup_routes!(
+    Gstr1Service,
+    "/gst-returns/gstr1",
+    "/json",
+    web::post().to(generate_gstr1_json),
+    "/csv/{section}",
+    web::post().to(generate_gstr1_section_csv),
+    "/filed",
+    web::post().to(mark_gstr1_filed)
+);
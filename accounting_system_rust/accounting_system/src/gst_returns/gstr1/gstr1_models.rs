@@ -0,0 +1,321 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::gst_returns::gst_returns_models::ReturnPeriod;
+use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct Gstr1Request {
+    pub gstin: GstinNo,
+    pub return_period: ReturnPeriod,
+}
+
+///records that gstr-1 of the period has been filed on the portal, invoices of the period can no longer be amended
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarkGstr1FiledRequest {
+    pub gstin: GstinNo,
+    pub return_period: ReturnPeriod,
+    pub arn: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Gstr1FilingDb<'a> {
+    pub tenant_id: Uuid,
+    pub gstin: &'a str,
+    pub return_period: String,
+    pub period_start_ms: i64,
+    pub period_end_ms: i64,
+    pub arn: Option<&'a str>,
+    pub created_by: Uuid,
+}
+
+///sections of gstr-1 which are generated from invoices
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Gstr1Section {
+    B2b,
+    B2cl,
+    B2cs,
+    Cdnr,
+    Exp,
+    Nil,
+    Hsn,
+}
+
+///gstr-1 json in the format accepted by the gst portal offline tool
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1Json {
+    pub gstin: String,
+    pub fp: String,
+    pub b2b: Vec<Gstr1B2b>,
+    pub b2cl: Vec<Gstr1B2cl>,
+    pub b2cs: Vec<Gstr1B2cs>,
+    pub cdnr: Vec<Gstr1Cdnr>,
+    pub exp: Vec<Gstr1Exp>,
+    pub nil: Gstr1Nil,
+    pub hsn: Gstr1Hsn,
+}
+
+///sections_not_generated lists the sections which cannot be generated from the stored documents, the json has
+/// them empty and they have to be added before filing
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1Summary {
+    pub gstr1: Gstr1Json,
+    pub sections_not_generated: Vec<Gstr1Section>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1ItemDetail {
+    pub rt: f32,
+    pub txval: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iamt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samt: Option<f64>,
+    pub csamt: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1Item {
+    pub num: u32,
+    pub itm_det: Gstr1ItemDetail,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1B2bInvoice {
+    pub inum: String,
+    pub idt: String,
+    pub val: f64,
+    pub pos: String,
+    pub rchrg: String,
+    pub inv_typ: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etin: Option<String>,
+    pub itms: Vec<Gstr1Item>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1B2b {
+    pub ctin: String,
+    pub inv: Vec<Gstr1B2bInvoice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1B2clInvoice {
+    pub inum: String,
+    pub idt: String,
+    pub val: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etin: Option<String>,
+    pub itms: Vec<Gstr1Item>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1B2cl {
+    pub pos: String,
+    pub inv: Vec<Gstr1B2clInvoice>,
+}
+
+///b2c small supplies aggregated by supply type, place of supply, rate and e-commerce operator
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1B2cs {
+    pub sply_ty: String,
+    pub pos: String,
+    pub typ: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etin: Option<String>,
+    pub rt: f32,
+    pub txval: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iamt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camt: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samt: Option<f64>,
+    pub csamt: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1CdnrNote {
+    pub ntty: String,
+    pub nt_num: String,
+    pub nt_dt: String,
+    pub val: f64,
+    pub pos: String,
+    pub rchrg: String,
+    pub inv_typ: String,
+    pub itms: Vec<Gstr1Item>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1Cdnr {
+    pub ctin: String,
+    pub nt: Vec<Gstr1CdnrNote>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1ExpItem {
+    pub rt: f32,
+    pub txval: f64,
+    pub iamt: f64,
+    pub csamt: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1ExpInvoice {
+    pub inum: String,
+    pub idt: String,
+    pub val: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sbpcode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sbnum: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sbdt: Option<String>,
+    pub itms: Vec<Gstr1ExpItem>,
+}
+
+///exp_typ is WPAY for exports with payment of igst and WOPAY for exports under lut/bond
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1Exp {
+    pub exp_typ: String,
+    pub inv: Vec<Gstr1ExpInvoice>,
+}
+
+///sply_ty is INTRB2B, INTRAB2B, INTRB2C or INTRAB2C
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1NilInvoice {
+    pub sply_ty: String,
+    pub expt_amt: f64,
+    pub nil_amt: f64,
+    pub ngsup_amt: f64,
+}
+
+///nil rated, exempted and non-gst outward supplies
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Gstr1Nil {
+    pub inv: Vec<Gstr1NilInvoice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr1HsnData {
+    pub num: u32,
+    pub hsn_sc: String,
+    pub desc: String,
+    pub uqc: String,
+    pub qty: f64,
+    pub rt: f32,
+    pub txval: f64,
+    pub iamt: f64,
+    pub camt: f64,
+    pub samt: f64,
+    pub csamt: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Gstr1Hsn {
+    pub data: Vec<Gstr1HsnData>,
+}
+
+///one invoice line of an invoice issued by the gstin along with the party details needed for gstr-1
+#[derive(Debug, Clone)]
+pub(crate) struct Gstr1InvoiceLineDb {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub invoice_date_ms: i64,
+    pub service_invoice: bool,
+    pub igst_applicable: bool,
+    pub ecommerce_gstin: Option<String>,
+    pub total_payable_amount: f64,
+    pub supplier_state_code: Option<String>,
+    pub billed_to_gstin: Option<String>,
+    pub billed_to_state_code: Option<String>,
+    pub billed_to_country_id: Option<Uuid>,
+    pub shipped_to_state_code: Option<String>,
+    pub shipped_to_country_id: Option<Uuid>,
+    pub hsn_code: Option<String>,
+    pub description: String,
+    pub uqc: Option<String>,
+    pub quantity: f64,
+    pub unit_price: f32,
+    pub discount_percentage: f32,
+    pub tax_percentage: f32,
+    pub cess_percentage: f32,
+    pub cess_amount_per_unit: f32,
+    pub retail_sale_price_for_cess: f32,
+    pub cess_calculation_strategy: String,
+    pub reverse_charge_applicable: bool,
+    ///with_payment_of_igst or under_lut, present only on export invoices
+    pub export_type: Option<String>,
+    pub port_code: Option<String>,
+    pub shipping_bill_number: Option<String>,
+    pub shipping_bill_date_ms: Option<i64>,
+    ///state code decided while invoicing, none for invoices created before it was stored
+    pub place_of_supply: Option<String>,
+    ///regular, sez with/without payment, deemed export or export
+    pub supply_classification: String,
+    ///tax_invoice or bill_of_supply
+    pub document_type: String,
+}
+
+///additional charge of an invoice issued by the gstin, taxed at the rate stored while invoicing
+#[derive(Debug, Clone)]
+pub(crate) struct Gstr1AdditionalChargeDb {
+    pub invoice_id: Uuid,
+    pub rate: f64,
+    pub tax_percentage: f32,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use crate::gst_returns::gstr1::gstr1_models::{Gstr1InvoiceLineDb, Gstr1Section};
+
+    ///intra state b2c line of 1000 at 18% for a supplier in state 29
+    pub(crate) fn a_gstr1_invoice_line_db() -> Gstr1InvoiceLineDb {
+        Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            invoice_number: "INV-1".to_string(),
+            invoice_date_ms: 1706534012000,
+            service_invoice: false,
+            igst_applicable: false,
+            ecommerce_gstin: None,
+            total_payable_amount: 1180.0,
+            supplier_state_code: Some("29".to_string()),
+            billed_to_gstin: None,
+            billed_to_state_code: Some("29".to_string()),
+            billed_to_country_id: None,
+            shipped_to_state_code: None,
+            shipped_to_country_id: None,
+            hsn_code: Some("8471".to_string()),
+            description: "laptop".to_string(),
+            uqc: Some("NOS".to_string()),
+            quantity: 1.0,
+            unit_price: 1000.0,
+            discount_percentage: 0.0,
+            tax_percentage: 18.0,
+            cess_percentage: 0.0,
+            cess_amount_per_unit: 0.0,
+            retail_sale_price_for_cess: 0.0,
+            cess_calculation_strategy: "percentage_of_assessable_value".to_string(),
+            reverse_charge_applicable: false,
+            export_type: None,
+            port_code: None,
+            shipping_bill_number: None,
+            shipping_bill_date_ms: None,
+            place_of_supply: None,
+            supply_classification: "regular".to_string(),
+            document_type: "tax_invoice".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_section_deserialization() {
+        let section: Gstr1Section = serde_json::from_str("\"b2cl\"").unwrap();
+        assert_eq!(section, Gstr1Section::B2cl);
+    }
+}
@@ -0,0 +1,1080 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context};
+use async_trait::async_trait;
+use cess_models::CessStrategy;
+use chrono::DateTime;
+use deadpool_postgres::Pool;
+use invoicing_calculations::invoice_line::InvoiceLine;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::gst_returns::gst_returns_models::ReturnPeriod;
+use crate::gst_returns::gstr1::gstr1_dao::{get_gstr1_dao, Gstr1Dao};
+use crate::gst_returns::gstr1::gstr1_models::{
+    Gstr1AdditionalChargeDb, Gstr1B2b, Gstr1B2bInvoice, Gstr1B2cl, Gstr1B2clInvoice, Gstr1B2cs,
+    Gstr1Exp, Gstr1ExpInvoice, Gstr1ExpItem, Gstr1FilingDb, Gstr1Hsn, Gstr1HsnData,
+    Gstr1InvoiceLineDb, Gstr1Item, Gstr1ItemDetail, Gstr1Json, Gstr1Nil, Gstr1NilInvoice,
+    Gstr1Request, Gstr1Section, Gstr1Summary, MarkGstr1FiledRequest,
+};
+use crate::invoicing::invoicing_request_models::{DocumentType, SupplyClassification};
+use crate::invoicing::place_of_supply::EXPORT_PLACE_OF_SUPPLY;
+use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+
+///inter state b2c invoices above this value are reported invoice wise in b2cl
+const B2CL_INVOICE_VALUE_LIMIT: f64 = 100000.0;
+const ARN_MAX_LENGTH: usize = 20;
+///credit and debit notes are not recorded by the invoicing module, so cdnr is out of scope till they are. it is
+/// left empty in the json and has to be filled in the offline tool before filing
+pub const GSTR1_SECTIONS_NOT_GENERATED: [Gstr1Section; 1] = [Gstr1Section::Cdnr];
+
+#[derive(Debug, Error)]
+pub enum Gstr1ServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait Gstr1Service: Send + Sync {
+    async fn generate_gstr1_json(
+        &self,
+        req: &Gstr1Request,
+        tenant_id: Uuid,
+    ) -> Result<Gstr1Json, Gstr1ServiceError>;
+    ///the json along with the sections it could not be generated for
+    async fn generate_gstr1_summary(
+        &self,
+        req: &Gstr1Request,
+        tenant_id: Uuid,
+    ) -> Result<Gstr1Summary, Gstr1ServiceError>;
+    ///fails for the sections which cannot be generated instead of returning an empty csv
+    async fn generate_gstr1_section_csv(
+        &self,
+        req: &Gstr1Request,
+        section: Gstr1Section,
+        tenant_id: Uuid,
+    ) -> Result<String, Gstr1ServiceError>;
+    ///invoices dated within a filed period cannot be amended
+    async fn mark_gstr1_filed(
+        &self,
+        req: &MarkGstr1FiledRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), Gstr1ServiceError>;
+}
+
+struct Gstr1ServiceImpl {
+    dao: Arc<dyn Gstr1Dao>,
+}
+
+pub fn get_gstr1_service(arc: Arc<Pool>) -> Arc<dyn Gstr1Service> {
+    let dao = get_gstr1_dao(arc);
+    let service = Gstr1ServiceImpl { dao };
+    Arc::new(service)
+}
+
+fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+///rates are keyed in basis points so that they can be used for grouping
+fn rate_key(rate: f32) -> i64 {
+    (rate as f64 * 100.0).round() as i64
+}
+
+fn rate_from_key(key: i64) -> f32 {
+    key as f32 / 100.0
+}
+
+fn epoch_ms_to_portal_date(epoch_ms: i64) -> anyhow::Result<String> {
+    let date = DateTime::from_timestamp_millis(epoch_ms)
+        .ok_or_else(|| anyhow!("error parsing date"))?
+        .with_timezone(&chrono_tz::Asia::Kolkata);
+    Ok(date.format("%d-%m-%Y").to_string())
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct LineAmounts {
+    taxable_amount: f64,
+    tax_amount: f64,
+    cess_amount: f64,
+}
+
+impl LineAmounts {
+    fn add(&mut self, other: &LineAmounts) {
+        self.taxable_amount += other.taxable_amount;
+        self.tax_amount += other.tax_amount;
+        self.cess_amount += other.cess_amount;
+    }
+
+    ///(igst, cgst, sgst)
+    fn split_tax(&self, igst: bool) -> (Option<f64>, Option<f64>, Option<f64>) {
+        if igst {
+            (Some(round_2(self.tax_amount)), None, None)
+        } else {
+            let half = round_2(self.tax_amount / 2.0);
+            (None, Some(half), Some(half))
+        }
+    }
+}
+
+fn compute_line_amounts(line: &Gstr1InvoiceLineDb) -> anyhow::Result<LineAmounts> {
+    let cess_strategy = CessStrategy::new(
+        line.cess_calculation_strategy.as_str(),
+        line.cess_percentage,
+        line.retail_sale_price_for_cess as f64,
+        line.cess_amount_per_unit as f64,
+    )?;
+    let invoice_line = InvoiceLine::new(
+        line.quantity,
+        line.unit_price as f64,
+        line.discount_percentage,
+        line.tax_percentage,
+        cess_strategy,
+    )
+    .with_context(|| format!("invalid line in invoice {}", line.invoice_number))?;
+    Ok(LineAmounts {
+        taxable_amount: invoice_line.compute_taxable_amount(),
+        tax_amount: invoice_line.compute_tax_amount(),
+        cess_amount: invoice_line.compute_cess_amount(),
+    })
+}
+
+///place of supply decided while invoicing. for invoices without it, place of supply for goods is where they
+/// are shipped to and for services the location of the recipient
+fn place_of_supply(invoice: &Gstr1InvoiceLineDb) -> (String, bool) {
+    if let Some(place_of_supply) = invoice.place_of_supply.as_ref() {
+        return (
+            place_of_supply.clone(),
+            place_of_supply == EXPORT_PLACE_OF_SUPPLY,
+        );
+    }
+    if invoice.export_type.is_some() {
+        return (EXPORT_PLACE_OF_SUPPLY.to_string(), true);
+    }
+    let (state_code, country_id) =
+        if !invoice.service_invoice && invoice.shipped_to_country_id.is_some() {
+            (
+                &invoice.shipped_to_state_code,
+                invoice.shipped_to_country_id,
+            )
+        } else {
+            (&invoice.billed_to_state_code, invoice.billed_to_country_id)
+        };
+    if country_id.is_some_and(|a| a != *INDIA_COUNTRY_ID) {
+        return (EXPORT_PLACE_OF_SUPPLY.to_string(), true);
+    }
+    let state_code = state_code
+        .as_ref()
+        .or(invoice.supplier_state_code.as_ref())
+        .map(|a| format!("{:0>2}", a.trim()))
+        .unwrap_or_default();
+    (state_code, false)
+}
+
+struct Gstr1Invoice<'a> {
+    header: &'a Gstr1InvoiceLineDb,
+    place_of_supply: String,
+    export: bool,
+    reverse_charge: bool,
+    ///amounts grouped by rate in basis points
+    rate_wise_amounts: BTreeMap<i64, LineAmounts>,
+    lines: Vec<(&'a Gstr1InvoiceLineDb, LineAmounts)>,
+}
+
+impl Gstr1Invoice<'_> {
+    fn igst(&self) -> bool {
+        self.export || self.header.igst_applicable
+    }
+
+    fn items(&self) -> Vec<Gstr1Item> {
+        self.rate_wise_amounts
+            .iter()
+            .enumerate()
+            .map(|(index, (rate, amounts))| {
+                let (iamt, camt, samt) = amounts.split_tax(self.igst());
+                Gstr1Item {
+                    num: index as u32 + 1,
+                    itm_det: Gstr1ItemDetail {
+                        rt: rate_from_key(*rate),
+                        txval: round_2(amounts.taxable_amount),
+                        iamt,
+                        camt,
+                        samt,
+                        csamt: round_2(amounts.cess_amount),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+///expects lines ordered by invoice, as returned by the dao. additional charges are added to the amounts
+/// of their invoice at the rate they were taxed at
+fn group_into_invoices<'a>(
+    lines: &'a [Gstr1InvoiceLineDb],
+    charges: &[Gstr1AdditionalChargeDb],
+) -> anyhow::Result<Vec<Gstr1Invoice<'a>>> {
+    let mut invoices: Vec<Gstr1Invoice> = Vec::new();
+    for line in lines {
+        let amounts = compute_line_amounts(line)?;
+        let is_new_invoice = invoices
+            .last()
+            .is_none_or(|inv| inv.header.invoice_id != line.invoice_id);
+        if is_new_invoice {
+            let (place_of_supply, export) = place_of_supply(line);
+            invoices.push(Gstr1Invoice {
+                header: line,
+                place_of_supply,
+                export,
+                reverse_charge: false,
+                rate_wise_amounts: BTreeMap::new(),
+                lines: Vec::new(),
+            });
+        }
+        let invoice = invoices
+            .last_mut()
+            .ok_or_else(|| anyhow!("invoice not initialised for line"))?;
+        invoice.reverse_charge |= line.reverse_charge_applicable;
+        invoice
+            .rate_wise_amounts
+            .entry(rate_key(line.tax_percentage))
+            .or_default()
+            .add(&amounts);
+        invoice.lines.push((line, amounts));
+    }
+    for charge in charges {
+        let Some(invoice) = invoices
+            .iter_mut()
+            .find(|a| a.header.invoice_id == charge.invoice_id)
+        else {
+            continue;
+        };
+        invoice
+            .rate_wise_amounts
+            .entry(rate_key(charge.tax_percentage))
+            .or_default()
+            .add(&LineAmounts {
+                taxable_amount: charge.rate,
+                tax_amount: charge.rate * charge.tax_percentage as f64 / 100.0,
+                cess_amount: 0.0,
+            });
+    }
+    Ok(invoices)
+}
+
+#[derive(Debug, Default)]
+struct HsnSummary {
+    description: String,
+    quantity: f64,
+    inter_state: LineAmounts,
+    intra_state: LineAmounts,
+}
+
+fn non_empty_gstin(gstin: &Option<String>) -> Option<String> {
+    gstin
+        .as_ref()
+        .map(|a| a.trim().to_uppercase())
+        .filter(|a| !a.is_empty())
+}
+
+pub(crate) fn build_gstr1_json(
+    gstin: &str,
+    return_period: &ReturnPeriod,
+    lines: &[Gstr1InvoiceLineDb],
+    charges: &[Gstr1AdditionalChargeDb],
+) -> anyhow::Result<Gstr1Json> {
+    let invoices = group_into_invoices(lines, charges)?;
+    let mut b2b: BTreeMap<String, Vec<Gstr1B2bInvoice>> = BTreeMap::new();
+    let mut b2cl: BTreeMap<String, Vec<Gstr1B2clInvoice>> = BTreeMap::new();
+    let mut b2cs: BTreeMap<(bool, String, i64, Option<String>), LineAmounts> = BTreeMap::new();
+    let mut exp: BTreeMap<&str, Vec<Gstr1ExpInvoice>> = BTreeMap::new();
+    let mut nil: BTreeMap<&str, f64> = BTreeMap::new();
+    let mut hsn: BTreeMap<(String, String, i64), HsnSummary> = BTreeMap::new();
+    for invoice in invoices.iter() {
+        let header = invoice.header;
+        let idt = epoch_ms_to_portal_date(header.invoice_date_ms)?;
+        let val = round_2(header.total_payable_amount);
+        let etin = non_empty_gstin(&header.ecommerce_gstin);
+        let document_type = DocumentType::try_from(header.document_type.as_str())?;
+        if document_type == DocumentType::BillOfSupply {
+            //no tax is charged on a bill of supply, it is reported as an exempt supply
+            let registered = non_empty_gstin(&header.billed_to_gstin).is_some();
+            let sply_ty = match (invoice.igst(), registered) {
+                (true, true) => "INTRB2B",
+                (false, true) => "INTRAB2B",
+                (true, false) => "INTRB2C",
+                (false, false) => "INTRAB2C",
+            };
+            *nil.entry(sply_ty).or_default() += invoice
+                .rate_wise_amounts
+                .values()
+                .map(|a| a.taxable_amount)
+                .sum::<f64>();
+        } else if invoice.export {
+            //invoices to a foreign customer without export details are classified by the tax charged
+            let with_payment = match header.export_type.as_deref() {
+                Some(export_type) => export_type == "with_payment_of_igst",
+                None => invoice
+                    .rate_wise_amounts
+                    .values()
+                    .any(|a| a.tax_amount > 0.0),
+            };
+            exp.entry(if with_payment { "WPAY" } else { "WOPAY" })
+                .or_default()
+                .push(Gstr1ExpInvoice {
+                    inum: header.invoice_number.clone(),
+                    idt,
+                    val,
+                    sbpcode: header.port_code.clone(),
+                    sbnum: header.shipping_bill_number.clone(),
+                    sbdt: header
+                        .shipping_bill_date_ms
+                        .map(epoch_ms_to_portal_date)
+                        .transpose()?,
+                    itms: invoice
+                        .rate_wise_amounts
+                        .iter()
+                        .map(|(rate, amounts)| Gstr1ExpItem {
+                            rt: rate_from_key(*rate),
+                            txval: round_2(amounts.taxable_amount),
+                            iamt: round_2(amounts.tax_amount),
+                            csamt: round_2(amounts.cess_amount),
+                        })
+                        .collect(),
+                });
+        } else if let Some(ctin) = non_empty_gstin(&header.billed_to_gstin) {
+            let supply_classification =
+                SupplyClassification::try_from(header.supply_classification.as_str())?;
+            b2b.entry(ctin).or_default().push(Gstr1B2bInvoice {
+                inum: header.invoice_number.clone(),
+                idt,
+                val,
+                pos: invoice.place_of_supply.clone(),
+                rchrg: if invoice.reverse_charge { "Y" } else { "N" }.to_string(),
+                inv_typ: supply_classification.b2b_invoice_type().to_string(),
+                etin,
+                itms: invoice.items(),
+            });
+        } else if header.igst_applicable && header.total_payable_amount > B2CL_INVOICE_VALUE_LIMIT {
+            b2cl.entry(invoice.place_of_supply.clone())
+                .or_default()
+                .push(Gstr1B2clInvoice {
+                    inum: header.invoice_number.clone(),
+                    idt,
+                    val,
+                    etin,
+                    itms: invoice.items(),
+                });
+        } else {
+            for (rate, amounts) in invoice.rate_wise_amounts.iter() {
+                b2cs.entry((
+                    header.igst_applicable,
+                    invoice.place_of_supply.clone(),
+                    *rate,
+                    etin.clone(),
+                ))
+                .or_default()
+                .add(amounts);
+            }
+        }
+        for (line, amounts) in invoice.lines.iter() {
+            let key = (
+                line.hsn_code.clone().unwrap_or_default(),
+                line.uqc.clone().unwrap_or_else(|| "OTH".to_string()),
+                rate_key(line.tax_percentage),
+            );
+            let entry = hsn.entry(key).or_insert_with(|| HsnSummary {
+                description: line.description.clone(),
+                ..Default::default()
+            });
+            entry.quantity += line.quantity;
+            if invoice.igst() {
+                entry.inter_state.add(amounts);
+            } else {
+                entry.intra_state.add(amounts);
+            }
+        }
+    }
+    Ok(Gstr1Json {
+        gstin: gstin.to_uppercase(),
+        fp: return_period.to_portal_format(),
+        b2b: b2b
+            .into_iter()
+            .map(|(ctin, inv)| Gstr1B2b { ctin, inv })
+            .collect(),
+        b2cl: b2cl
+            .into_iter()
+            .map(|(pos, inv)| Gstr1B2cl { pos, inv })
+            .collect(),
+        b2cs: b2cs
+            .into_iter()
+            .map(|((inter_state, pos, rate, etin), amounts)| {
+                let (iamt, camt, samt) = amounts.split_tax(inter_state);
+                Gstr1B2cs {
+                    sply_ty: if inter_state { "INTER" } else { "INTRA" }.to_string(),
+                    pos,
+                    typ: if etin.is_some() { "E" } else { "OE" }.to_string(),
+                    etin,
+                    rt: rate_from_key(rate),
+                    txval: round_2(amounts.taxable_amount),
+                    iamt,
+                    camt,
+                    samt,
+                    csamt: round_2(amounts.cess_amount),
+                }
+            })
+            .collect(),
+        //reported in GSTR1_SECTIONS_NOT_GENERATED
+        cdnr: vec![],
+        exp: exp
+            .into_iter()
+            .map(|(exp_typ, inv)| Gstr1Exp {
+                exp_typ: exp_typ.to_string(),
+                inv,
+            })
+            .collect(),
+        nil: Gstr1Nil {
+            inv: nil
+                .into_iter()
+                .map(|(sply_ty, expt_amt)| Gstr1NilInvoice {
+                    sply_ty: sply_ty.to_string(),
+                    expt_amt: round_2(expt_amt),
+                    nil_amt: 0.0,
+                    ngsup_amt: 0.0,
+                })
+                .collect(),
+        },
+        hsn: Gstr1Hsn {
+            data: hsn
+                .into_iter()
+                .enumerate()
+                .map(|(index, ((hsn_sc, uqc, rate), summary))| {
+                    let (inter, intra) = (summary.inter_state, summary.intra_state);
+                    let intra_tax = round_2(intra.tax_amount / 2.0);
+                    Gstr1HsnData {
+                        num: index as u32 + 1,
+                        hsn_sc,
+                        desc: summary.description,
+                        uqc,
+                        qty: summary.quantity,
+                        rt: rate_from_key(rate),
+                        txval: round_2(inter.taxable_amount + intra.taxable_amount),
+                        iamt: round_2(inter.tax_amount),
+                        camt: intra_tax,
+                        samt: intra_tax,
+                        csamt: round_2(inter.cess_amount + intra.cess_amount),
+                    }
+                })
+                .collect(),
+        },
+    })
+}
+
+fn opt_to_string(value: Option<f64>) -> String {
+    value.map(|a| a.to_string()).unwrap_or_default()
+}
+
+fn item_detail_record(det: &Gstr1ItemDetail) -> [String; 6] {
+    [
+        det.rt.to_string(),
+        det.txval.to_string(),
+        opt_to_string(det.iamt),
+        opt_to_string(det.camt),
+        opt_to_string(det.samt),
+        det.csamt.to_string(),
+    ]
+}
+
+const ITEM_HEADERS: [&str; 6] = [
+    "Rate",
+    "Taxable Value",
+    "Integrated Tax Amount",
+    "Central Tax Amount",
+    "State/UT Tax Amount",
+    "Cess Amount",
+];
+
+///human readable csv of a section, one row per rate of each invoice
+pub(crate) fn build_gstr1_section_csv(
+    json: &Gstr1Json,
+    section: Gstr1Section,
+) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    match section {
+        Gstr1Section::B2b => {
+            let mut headers = vec![
+                "GSTIN/UIN of Recipient",
+                "Invoice Number",
+                "Invoice Date",
+                "Invoice Value",
+                "Place Of Supply",
+                "Reverse Charge",
+                "Invoice Type",
+                "E-Commerce GSTIN",
+            ];
+            headers.extend(ITEM_HEADERS);
+            writer.write_record(headers)?;
+            for party in json.b2b.iter() {
+                for inv in party.inv.iter() {
+                    for item in inv.itms.iter() {
+                        let mut record = vec![
+                            party.ctin.clone(),
+                            inv.inum.clone(),
+                            inv.idt.clone(),
+                            inv.val.to_string(),
+                            inv.pos.clone(),
+                            inv.rchrg.clone(),
+                            inv.inv_typ.clone(),
+                            inv.etin.clone().unwrap_or_default(),
+                        ];
+                        record.extend(item_detail_record(&item.itm_det));
+                        writer.write_record(record)?;
+                    }
+                }
+            }
+        }
+        Gstr1Section::B2cl => {
+            let mut headers = vec![
+                "Invoice Number",
+                "Invoice Date",
+                "Invoice Value",
+                "Place Of Supply",
+                "E-Commerce GSTIN",
+            ];
+            headers.extend(ITEM_HEADERS);
+            writer.write_record(headers)?;
+            for pos in json.b2cl.iter() {
+                for inv in pos.inv.iter() {
+                    for item in inv.itms.iter() {
+                        let mut record = vec![
+                            inv.inum.clone(),
+                            inv.idt.clone(),
+                            inv.val.to_string(),
+                            pos.pos.clone(),
+                            inv.etin.clone().unwrap_or_default(),
+                        ];
+                        record.extend(item_detail_record(&item.itm_det));
+                        writer.write_record(record)?;
+                    }
+                }
+            }
+        }
+        Gstr1Section::B2cs => {
+            let mut headers = vec!["Supply Type", "Type", "Place Of Supply", "E-Commerce GSTIN"];
+            headers.extend(ITEM_HEADERS);
+            writer.write_record(headers)?;
+            for row in json.b2cs.iter() {
+                writer.write_record([
+                    row.sply_ty.clone(),
+                    row.typ.clone(),
+                    row.pos.clone(),
+                    row.etin.clone().unwrap_or_default(),
+                    row.rt.to_string(),
+                    row.txval.to_string(),
+                    opt_to_string(row.iamt),
+                    opt_to_string(row.camt),
+                    opt_to_string(row.samt),
+                    row.csamt.to_string(),
+                ])?;
+            }
+        }
+        Gstr1Section::Cdnr => {
+            bail!("cdnr is not generated as credit and debit notes are not recorded");
+        }
+        Gstr1Section::Exp => {
+            writer.write_record([
+                "Export Type",
+                "Invoice Number",
+                "Invoice Date",
+                "Invoice Value",
+                "Port Code",
+                "Shipping Bill Number",
+                "Shipping Bill Date",
+                "Rate",
+                "Taxable Value",
+                "Integrated Tax Amount",
+                "Cess Amount",
+            ])?;
+            for exp in json.exp.iter() {
+                for inv in exp.inv.iter() {
+                    for item in inv.itms.iter() {
+                        writer.write_record([
+                            exp.exp_typ.clone(),
+                            inv.inum.clone(),
+                            inv.idt.clone(),
+                            inv.val.to_string(),
+                            inv.sbpcode.clone().unwrap_or_default(),
+                            inv.sbnum.clone().unwrap_or_default(),
+                            inv.sbdt.clone().unwrap_or_default(),
+                            item.rt.to_string(),
+                            item.txval.to_string(),
+                            item.iamt.to_string(),
+                            item.csamt.to_string(),
+                        ])?;
+                    }
+                }
+            }
+        }
+        Gstr1Section::Nil => {
+            writer.write_record([
+                "Description",
+                "Nil Rated Supplies",
+                "Exempted (other than nil rated/non GST supply)",
+                "Non-GST Supplies",
+            ])?;
+            for row in json.nil.inv.iter() {
+                writer.write_record([
+                    row.sply_ty.clone(),
+                    row.nil_amt.to_string(),
+                    row.expt_amt.to_string(),
+                    row.ngsup_amt.to_string(),
+                ])?;
+            }
+        }
+        Gstr1Section::Hsn => {
+            writer.write_record([
+                "HSN",
+                "Description",
+                "UQC",
+                "Total Quantity",
+                "Rate",
+                "Taxable Value",
+                "Integrated Tax Amount",
+                "Central Tax Amount",
+                "State/UT Tax Amount",
+                "Cess Amount",
+            ])?;
+            for row in json.hsn.data.iter() {
+                writer.write_record([
+                    row.hsn_sc.clone(),
+                    row.desc.clone(),
+                    row.uqc.clone(),
+                    row.qty.to_string(),
+                    row.rt.to_string(),
+                    row.txval.to_string(),
+                    row.iamt.to_string(),
+                    row.camt.to_string(),
+                    row.samt.to_string(),
+                    row.csamt.to_string(),
+                ])?;
+            }
+        }
+    }
+    let bytes = writer.into_inner().context("could not write gstr-1 csv")?;
+    String::from_utf8(bytes).context("gstr-1 csv is not valid utf-8")
+}
+
+impl Gstr1ServiceImpl {
+    async fn build_json(
+        &self,
+        req: &Gstr1Request,
+        tenant_id: Uuid,
+    ) -> Result<Gstr1Json, Gstr1ServiceError> {
+        let (start_ms, end_ms) = req.return_period.epoch_millis_range()?;
+        let lines = self
+            .dao
+            .get_invoice_lines_for_period(tenant_id, req.gstin.get_str(), start_ms, end_ms)
+            .await?;
+        let charges = self
+            .dao
+            .get_additional_charges_for_period(tenant_id, req.gstin.get_str(), start_ms, end_ms)
+            .await?;
+        let json = build_gstr1_json(req.gstin.get_str(), &req.return_period, &lines, &charges)?;
+        Ok(json)
+    }
+}
+
+#[async_trait]
+impl Gstr1Service for Gstr1ServiceImpl {
+    async fn generate_gstr1_json(
+        &self,
+        req: &Gstr1Request,
+        tenant_id: Uuid,
+    ) -> Result<Gstr1Json, Gstr1ServiceError> {
+        self.build_json(req, tenant_id).await
+    }
+
+    async fn generate_gstr1_summary(
+        &self,
+        req: &Gstr1Request,
+        tenant_id: Uuid,
+    ) -> Result<Gstr1Summary, Gstr1ServiceError> {
+        Ok(Gstr1Summary {
+            gstr1: self.build_json(req, tenant_id).await?,
+            sections_not_generated: GSTR1_SECTIONS_NOT_GENERATED.to_vec(),
+        })
+    }
+
+    async fn generate_gstr1_section_csv(
+        &self,
+        req: &Gstr1Request,
+        section: Gstr1Section,
+        tenant_id: Uuid,
+    ) -> Result<String, Gstr1ServiceError> {
+        if GSTR1_SECTIONS_NOT_GENERATED.contains(&section) {
+            return Err(Gstr1ServiceError::Validation(vec![format!(
+                "{:?} section cannot be generated as credit and debit notes are not recorded",
+                section
+            )]));
+        }
+        let json = self.build_json(req, tenant_id).await?;
+        let csv = build_gstr1_section_csv(&json, section)?;
+        Ok(csv)
+    }
+
+    async fn mark_gstr1_filed(
+        &self,
+        req: &MarkGstr1FiledRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), Gstr1ServiceError> {
+        let arn = req.arn.as_deref().map(str::trim).filter(|a| !a.is_empty());
+        if arn.is_some_and(|a| a.len() > ARN_MAX_LENGTH) {
+            return Err(Gstr1ServiceError::Validation(vec![format!(
+                "arn cannot be longer than {} characters",
+                ARN_MAX_LENGTH
+            )]));
+        }
+        let (period_start_ms, period_end_ms) = req.return_period.epoch_millis_range()?;
+        let filing = Gstr1FilingDb {
+            tenant_id,
+            gstin: req.gstin.get_str(),
+            return_period: req.return_period.to_portal_format(),
+            period_start_ms,
+            period_end_ms,
+            arn,
+            created_by: user_id,
+        };
+        self.dao.mark_gstr1_filed(&filing).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use crate::gst_returns::gst_returns_models::ReturnPeriod;
+    use crate::gst_returns::gstr1::gstr1_dao::MockGstr1Dao;
+    use crate::gst_returns::gstr1::gstr1_models::tests::a_gstr1_invoice_line_db;
+    use crate::gst_returns::gstr1::gstr1_models::{
+        Gstr1AdditionalChargeDb, Gstr1InvoiceLineDb, Gstr1Request, Gstr1Section,
+        MarkGstr1FiledRequest,
+    };
+    use crate::gst_returns::gstr1::gstr1_service::{
+        build_gstr1_json, build_gstr1_section_csv, Gstr1Service, Gstr1ServiceError,
+        Gstr1ServiceImpl,
+    };
+    use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+    use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn sample_lines() -> Vec<Gstr1InvoiceLineDb> {
+        let b2b_invoice_id = Uuid::now_v7();
+        let b2b_line_1 = Gstr1InvoiceLineDb {
+            invoice_id: b2b_invoice_id,
+            invoice_number: "INV-B2B".to_string(),
+            billed_to_gstin: Some("06MFNMS5291p1ZA".to_string()),
+            billed_to_state_code: Some("6".to_string()),
+            billed_to_country_id: Some(*INDIA_COUNTRY_ID),
+            igst_applicable: true,
+            total_payable_amount: 1180.0 + 560.0,
+            ..a_gstr1_invoice_line_db()
+        };
+        let b2b_line_2 = Gstr1InvoiceLineDb {
+            unit_price: 500.0,
+            tax_percentage: 12.0,
+            hsn_code: Some("8473".to_string()),
+            reverse_charge_applicable: true,
+            ..b2b_line_1.clone()
+        };
+        let b2cl_line = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            invoice_number: "INV-B2CL".to_string(),
+            billed_to_state_code: Some("27".to_string()),
+            igst_applicable: true,
+            quantity: 100.0,
+            total_payable_amount: 118000.0,
+            ..a_gstr1_invoice_line_db()
+        };
+        let b2cs_line_1 = a_gstr1_invoice_line_db();
+        let b2cs_line_2 = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            invoice_number: "INV-2".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let export_line = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            invoice_number: "INV-EXP".to_string(),
+            billed_to_state_code: None,
+            billed_to_country_id: Some(Uuid::now_v7()),
+            tax_percentage: 0.0,
+            total_payable_amount: 1000.0,
+            ..a_gstr1_invoice_line_db()
+        };
+        vec![
+            b2b_line_1,
+            b2b_line_2,
+            b2cl_line,
+            b2cs_line_1,
+            b2cs_line_2,
+            export_line,
+        ]
+    }
+
+    #[test]
+    fn test_build_gstr1_json() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let json = build_gstr1_json("05aabca5291p1zd", &period, &sample_lines(), &[]).unwrap();
+        assert_eq!(json.gstin, "05AABCA5291P1ZD");
+        assert_eq!(json.fp, "012024");
+
+        assert_eq!(json.b2b.len(), 1);
+        assert_eq!(json.b2b[0].ctin, "06MFNMS5291P1ZA");
+        let b2b_invoice = &json.b2b[0].inv[0];
+        assert_eq!(b2b_invoice.pos, "06");
+        assert_eq!(b2b_invoice.rchrg, "Y");
+        assert_eq!(b2b_invoice.idt, "29-01-2024");
+        assert_eq!(b2b_invoice.itms.len(), 2);
+        assert_eq!(b2b_invoice.itms[0].itm_det.rt, 12.0);
+        assert_eq!(b2b_invoice.itms[0].itm_det.iamt, Some(60.0));
+        assert_eq!(b2b_invoice.itms[0].itm_det.camt, None);
+
+        assert_eq!(json.b2cl.len(), 1);
+        assert_eq!(json.b2cl[0].pos, "27");
+        assert_eq!(json.b2cl[0].inv[0].itms[0].itm_det.txval, 100000.0);
+
+        assert_eq!(json.b2cs.len(), 1);
+        let b2cs = &json.b2cs[0];
+        assert_eq!(b2cs.sply_ty, "INTRA");
+        assert_eq!(b2cs.typ, "OE");
+        assert_eq!(b2cs.pos, "29");
+        assert_eq!(b2cs.txval, 2000.0);
+        assert_eq!(b2cs.camt, Some(180.0));
+        assert_eq!(b2cs.samt, Some(180.0));
+
+        assert_eq!(json.exp.len(), 1);
+        assert_eq!(json.exp[0].exp_typ, "WOPAY");
+        assert_eq!(json.exp[0].inv[0].itms[0].txval, 1000.0);
+
+        let hsn_8471_18 = json
+            .hsn
+            .data
+            .iter()
+            .find(|a| a.hsn_sc == "8471" && a.rt == 18.0)
+            .unwrap();
+        assert_eq!(hsn_8471_18.qty, 103.0);
+        assert_eq!(hsn_8471_18.txval, 103000.0);
+        assert_eq!(hsn_8471_18.iamt, 18180.0);
+        assert_eq!(hsn_8471_18.camt, 180.0);
+        assert_eq!(json.hsn.data.len(), 3);
+    }
+
+    #[test]
+    fn test_stored_place_of_supply_takes_precedence() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let line = Gstr1InvoiceLineDb {
+            billed_to_gstin: Some("29MFNMS5291P1ZA".to_string()),
+            shipped_to_state_code: Some("27".to_string()),
+            shipped_to_country_id: Some(*INDIA_COUNTRY_ID),
+            place_of_supply: Some("29".to_string()),
+            ..a_gstr1_invoice_line_db()
+        };
+        let json = build_gstr1_json("29AABCA5291P1ZD", &period, &[line], &[]).unwrap();
+        assert_eq!(json.b2b[0].inv[0].pos, "29");
+    }
+
+    #[test]
+    fn test_sez_invoice_type_in_b2b() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let line = Gstr1InvoiceLineDb {
+            igst_applicable: true,
+            billed_to_gstin: Some("29MFNMS5291P1ZA".to_string()),
+            tax_percentage: 0.0,
+            total_payable_amount: 1000.0,
+            supply_classification: "sez_without_payment".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let json = build_gstr1_json("29AABCA5291P1ZD", &period, &[line], &[]).unwrap();
+        let inv = &json.b2b[0].inv[0];
+        assert_eq!(inv.inv_typ, "SEWOP");
+        assert_eq!(inv.itms[0].itm_det.iamt, Some(0.0));
+    }
+
+    #[test]
+    fn test_bill_of_supply_is_reported_as_exempt() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let registered = Gstr1InvoiceLineDb {
+            billed_to_gstin: Some("29MFNMS5291P1ZA".to_string()),
+            tax_percentage: 0.0,
+            document_type: "bill_of_supply".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let unregistered = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            tax_percentage: 0.0,
+            document_type: "bill_of_supply".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let json =
+            build_gstr1_json("29AABCA5291P1ZD", &period, &[registered, unregistered], &[]).unwrap();
+        assert!(json.b2b.is_empty());
+        assert!(json.b2cs.is_empty());
+        assert_eq!(json.nil.inv.len(), 2);
+        assert_eq!(json.nil.inv[0].sply_ty, "INTRAB2B");
+        assert_eq!(json.nil.inv[0].expt_amt, 1000.0);
+        assert_eq!(json.nil.inv[1].sply_ty, "INTRAB2C");
+        let csv = build_gstr1_section_csv(&json, Gstr1Section::Nil).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_additional_charges_are_added_to_their_invoice() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let line = a_gstr1_invoice_line_db();
+        let charges = [
+            Gstr1AdditionalChargeDb {
+                invoice_id: line.invoice_id,
+                rate: 100.0,
+                tax_percentage: 18.0,
+            },
+            Gstr1AdditionalChargeDb {
+                invoice_id: line.invoice_id,
+                rate: 50.0,
+                tax_percentage: 0.0,
+            },
+        ];
+        let json = build_gstr1_json("29AABCA5291P1ZD", &period, &[line], &charges).unwrap();
+        assert_eq!(json.b2cs.len(), 2);
+        let taxed = json.b2cs.iter().find(|a| a.rt == 18.0).unwrap();
+        assert_eq!(taxed.txval, 1100.0);
+        assert_eq!(taxed.camt, Some(99.0));
+        let untaxed = json.b2cs.iter().find(|a| a.rt == 0.0).unwrap();
+        assert_eq!(untaxed.txval, 50.0);
+    }
+
+    #[test]
+    fn test_export_invoice_uses_export_details() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let line = Gstr1InvoiceLineDb {
+            invoice_number: "INV-EXP".to_string(),
+            igst_applicable: true,
+            billed_to_state_code: None,
+            export_type: Some("with_payment_of_igst".to_string()),
+            port_code: Some("INMAA1".to_string()),
+            shipping_bill_number: Some("1234567".to_string()),
+            shipping_bill_date_ms: Some(1706486400000),
+            ..a_gstr1_invoice_line_db()
+        };
+        let json = build_gstr1_json("05AABCA5291P1ZD", &period, &[line], &[]).unwrap();
+        assert_eq!(json.exp.len(), 1);
+        assert_eq!(json.exp[0].exp_typ, "WPAY");
+        let inv = &json.exp[0].inv[0];
+        assert_eq!(inv.sbpcode.as_deref(), Some("INMAA1"));
+        assert_eq!(inv.sbnum.as_deref(), Some("1234567"));
+        assert_eq!(inv.sbdt.as_deref(), Some("29-01-2024"));
+        assert_eq!(inv.itms[0].iamt, 180.0);
+        assert!(json.b2cs.is_empty());
+    }
+
+    #[test]
+    fn test_build_gstr1_section_csv() {
+        let period = ReturnPeriod::new(1, 2024).unwrap();
+        let json = build_gstr1_json("05AABCA5291P1ZD", &period, &sample_lines(), &[]).unwrap();
+        let csv = build_gstr1_section_csv(&json, Gstr1Section::B2b).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].starts_with("GSTIN/UIN of Recipient,Invoice Number"));
+        assert!(
+            rows[1].starts_with("06MFNMS5291P1ZA,INV-B2B,29-01-2024,1740,06,Y,R,,12,500,60,,,0")
+        );
+        assert!(build_gstr1_section_csv(&json, Gstr1Section::Cdnr).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_gstr1_json_uses_period_range() {
+        let mut dao = MockGstr1Dao::new();
+        dao.expect_get_invoice_lines_for_period()
+            .withf(|_, gstin, start, end| {
+                gstin == "05AABCA5291p1ZD" && *start == 1704047400000 && *end == 1706725800000
+            })
+            .returning(|_, _, _, _| Ok(vec![a_gstr1_invoice_line_db()]));
+        dao.expect_get_additional_charges_for_period()
+            .returning(|_, _, _, _| Ok(vec![]));
+        let service = Gstr1ServiceImpl { dao: Arc::new(dao) };
+        let req = Gstr1Request {
+            gstin: GstinNo::default(),
+            return_period: ReturnPeriod::new(1, 2024).unwrap(),
+        };
+        let json = service
+            .generate_gstr1_json(&req, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_eq!(json.b2cs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sections_not_generated_are_flagged() {
+        let mut dao = MockGstr1Dao::new();
+        dao.expect_get_invoice_lines_for_period()
+            .returning(|_, _, _, _| Ok(vec![a_gstr1_invoice_line_db()]));
+        dao.expect_get_additional_charges_for_period()
+            .returning(|_, _, _, _| Ok(vec![]));
+        let service = Gstr1ServiceImpl { dao: Arc::new(dao) };
+        let req = Gstr1Request {
+            gstin: GstinNo::default(),
+            return_period: ReturnPeriod::new(1, 2024).unwrap(),
+        };
+        let summary = service
+            .generate_gstr1_summary(&req, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_eq!(summary.sections_not_generated, vec![Gstr1Section::Cdnr]);
+        let csv = service
+            .generate_gstr1_section_csv(&req, Gstr1Section::Cdnr, *SEED_TENANT_ID)
+            .await;
+        assert!(matches!(csv, Err(Gstr1ServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mark_gstr1_filed() {
+        let mut dao = MockGstr1Dao::new();
+        dao.expect_mark_gstr1_filed()
+            .withf(|filing| {
+                filing.return_period == "012024"
+                    && filing.period_start_ms == 1704047400000
+                    && filing.period_end_ms == 1706725800000
+                    && filing.arn == Some("AA050124000001A")
+            })
+            .times(1)
+            .returning(|_| Ok(true));
+        let service = Gstr1ServiceImpl { dao: Arc::new(dao) };
+        let mut req = MarkGstr1FiledRequest {
+            gstin: GstinNo::default(),
+            return_period: ReturnPeriod::new(1, 2024).unwrap(),
+            arn: Some(" AA050124000001A ".to_string()),
+        };
+        service
+            .mark_gstr1_filed(&req, *SEED_TENANT_ID, Uuid::now_v7())
+            .await
+            .unwrap();
+        req.arn = Some("A".repeat(21));
+        let err = service
+            .mark_gstr1_filed(&req, *SEED_TENANT_ID, Uuid::now_v7())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Gstr1ServiceError::Validation(_)));
+    }
+}
@@ -0,0 +1,19 @@
+create table gstr1_filing
+(
+    id                uuid primary key,
+    entity_version_id integer default 0,
+    tenant_id         uuid references tenant (id)   not null,
+    active            bool,
+    approval_status   smallint                      not null,
+    remarks           varchar(70),
+    gstin             varchar(16)                   not null,
+    return_period     varchar(6)                    not null,--MMYYYY
+    period_start_ms   bigint                        not null,
+    period_end_ms     bigint                        not null,--exclusive
+    arn               varchar(20),--acknowledgement reference number issued by the portal
+    created_by        uuid references app_user (id) not null,
+    updated_by        uuid references app_user (id),
+    created_at        bigint  default extract(epoch from now()) * 1000000,
+    updated_at        bigint  default extract(epoch from now()) * 1000000,
+    unique (tenant_id, gstin, return_period)
+);
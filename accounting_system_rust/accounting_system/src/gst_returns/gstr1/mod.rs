@@ -0,0 +1,5 @@
+mod gstr1_dao;
+pub mod gstr1_db_mapping;
+pub mod gstr1_http_api;
+pub mod gstr1_models;
+pub mod gstr1_service;
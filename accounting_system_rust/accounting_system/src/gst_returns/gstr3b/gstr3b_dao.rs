@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::gst_returns::gstr3b::gstr3b_models::{LedgerAccountNetCredit, PurchaseItcDb};
+use crate::invoicing::purchase_invoice::purchase_invoice_models::ItcEligibility;
+
+//only regular and post pending transfers affect the posted balance. transfers of an invoice or a purchase invoice
+//fall in the period of its document date, irrespective of when they were posted
+const NET_CREDIT_QUERY: &str = "with period_transfer as (\
+select t.debit_account_id,t.credit_account_id,t.amount from transfer t \
+left join invoice i on i.id=t.grouping_id and i.tenant_id=t.tenant_id \
+left join purchase_invoice p on p.id=t.grouping_id and p.tenant_id=t.tenant_id \
+where t.tenant_id=$1 and t.transfer_type in (1,3) \
+and (t.credit_account_id=any($2) or t.debit_account_id=any($2)) \
+and coalesce(i.invoice_date_ms,p.purchase_invoice_date_ms,t.created_at/1000)>=$3 \
+and coalesce(i.invoice_date_ms,p.purchase_invoice_date_ms,t.created_at/1000)<$4) \
+select a.id,\
+(coalesce(sum(case when t.credit_account_id=a.id then t.amount else 0 end),0)-\
+coalesce(sum(case when t.debit_account_id=a.id then t.amount else 0 end),0))::bigint,cm.scale \
+from user_account a \
+join ledger_master lm on a.ledger_master_id=lm.id \
+join currency_master cm on lm.currency_master_id=cm.id \
+left join period_transfer t on t.credit_account_id=a.id or t.debit_account_id=a.id \
+where a.tenant_id=$1 and a.id=any($2) group by a.id,cm.scale";
+
+const PURCHASE_ITC_QUERY: &str = "select p.reverse_charge,l.itc_eligibility::text,\
+sum(l.taxable_amount),sum(l.igst_amount),sum(l.cgst_amount),sum(l.sgst_amount),sum(l.cess_amount) \
+from purchase_invoice p \
+join business_entity r on p.recipient_business_entity=r.id \
+join purchase_invoice_line l on l.purchase_invoice_id=p.id and l.tenant_id=p.tenant_id \
+where p.tenant_id=$1 and upper(r.gstin)=upper($2) and p.purchase_invoice_date_ms>=$3 \
+and p.purchase_invoice_date_ms<$4 and p.active \
+group by p.reverse_charge,l.itc_eligibility";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait Gstr3bDao: Send + Sync {
+    ///net credits of the accounts for transfers with document date in [start_ms,end_ms), accounts not found are
+    /// omitted
+    async fn get_net_credits_for_accounts(
+        &self,
+        tenant_id: Uuid,
+        account_ids: &[Uuid],
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<LedgerAccountNetCredit>, DaoError>;
+    ///tax on purchases received by the gstin with purchase invoice date in [start_ms,end_ms)
+    async fn get_purchase_itc_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<PurchaseItcDb>, DaoError>;
+}
+
+struct Gstr3bDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_gstr3b_dao(arc: Arc<Pool>) -> Arc<dyn Gstr3bDao> {
+    let dao = Gstr3bDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for LedgerAccountNetCredit {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let amount: i64 = row.get(1);
+        let scale: i16 = row.get(2);
+        Ok(LedgerAccountNetCredit {
+            account_id: row.get(0),
+            net_credit: amount as f64 / 10_f64.powi(scale as i32),
+        })
+    }
+}
+
+impl TryFrom<Row> for PurchaseItcDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let itc_eligibility: &str = row.get(1);
+        Ok(PurchaseItcDb {
+            reverse_charge: row.get(0),
+            itc_eligibility: ItcEligibility::from_db_str(itc_eligibility)?,
+            txval: row.get(2),
+            iamt: row.get(3),
+            camt: row.get(4),
+            samt: row.get(5),
+            csamt: row.get(6),
+        })
+    }
+}
+
+#[async_trait]
+impl Gstr3bDao for Gstr3bDaoImpl {
+    async fn get_net_credits_for_accounts(
+        &self,
+        tenant_id: Uuid,
+        account_ids: &[Uuid],
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<LedgerAccountNetCredit>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(
+                NET_CREDIT_QUERY,
+                &[&tenant_id, &account_ids, &start_ms, &end_ms],
+            )
+            .await?;
+        rows.into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<LedgerAccountNetCredit>, DaoError>>()
+    }
+
+    async fn get_purchase_itc_for_period(
+        &self,
+        tenant_id: Uuid,
+        gstin: &str,
+        start_ms: i64,
+        end_ms: i64,
+    ) -> Result<Vec<PurchaseItcDb>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(
+                PURCHASE_ITC_QUERY,
+                &[&tenant_id, &gstin, &start_ms, &end_ms],
+            )
+            .await?;
+        rows.into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<PurchaseItcDb>, DaoError>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::gst_returns::gstr3b::gstr3b_dao::{Gstr3bDao, Gstr3bDaoImpl};
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_net_credits_omit_unknown_accounts() {
+        let dao = get_dao_generic(|c| Gstr3bDaoImpl { postgres_client: c }, None).await;
+        let balances = dao
+            .get_net_credits_for_accounts(*SEED_TENANT_ID, &[Uuid::now_v7()], 0, i64::MAX)
+            .await
+            .unwrap();
+        assert!(balances.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_purchase_itc_of_other_gstin_is_empty() {
+        let dao = get_dao_generic(|c| Gstr3bDaoImpl { postgres_client: c }, None).await;
+        let itc = dao
+            .get_purchase_itc_for_period(*SEED_TENANT_ID, "27AAPFU0939F1ZV", 0, i64::MAX)
+            .await
+            .unwrap();
+        assert!(itc.is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+
+use crate::common_utils::utils::TenantId;
+use crate::gst_returns::gstr3b::gstr3b_models::Gstr3bRequest;
+use crate::gst_returns::gstr3b::gstr3b_service::{Gstr3bService, Gstr3bServiceError};
+use crate::setup_routes;
+
+impl ResponseError for Gstr3bServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Gstr3bServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Gstr3bServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            Gstr3bServiceError::Gstr1(e) => e.status_code(),
+            Gstr3bServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn generate_gstr3b_summary(
+    data: Data<Arc<dyn Gstr3bService>>,
+    request: web::Json<Gstr3bRequest>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let summary = data
+        .generate_gstr3b_summary(&request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(summary))
+}
+
+setup_routes!(
+    Gstr3bService,
+    "/gst-returns/gstr3b",
+    "/summary",
+    web::post().to(generate_gstr3b_summary)
+);
@@ -0,0 +1,194 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::gst_returns::gst_returns_models::ReturnPeriod;
+use crate::invoicing::purchase_invoice::purchase_invoice_models::ItcEligibility;
+use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+
+///ledger accounts in which gst is booked, one per tax head
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct GstLedgerAccounts {
+    pub igst_account_id: Uuid,
+    pub cgst_account_id: Uuid,
+    pub sgst_account_id: Uuid,
+    pub cess_account_id: Uuid,
+}
+
+impl GstLedgerAccounts {
+    pub fn account_ids(&self) -> [Uuid; 4] {
+        [
+            self.igst_account_id,
+            self.cgst_account_id,
+            self.sgst_account_id,
+            self.cess_account_id,
+        ]
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct Gstr3bRequest {
+    ///gstin of the company unit filing the return
+    pub gstin: GstinNo,
+    pub return_period: ReturnPeriod,
+    ///output tax accounts against which the computed liability is reconciled
+    #[builder(default)]
+    pub output_tax_accounts: Option<GstLedgerAccounts>,
+    ///input tax accounts from which eligible itc is taken
+    #[builder(default)]
+    pub input_tax_accounts: Option<GstLedgerAccounts>,
+    ///accounts credited with the tax on purchases under reverse charge, against which the reverse
+    ///charge liability is reconciled
+    #[builder(default)]
+    pub reverse_charge_tax_accounts: Option<GstLedgerAccounts>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct Gstr3bTaxAmounts {
+    pub txval: f64,
+    pub iamt: f64,
+    pub camt: f64,
+    pub samt: f64,
+    pub csamt: f64,
+}
+
+///table 3.1 of gstr-3b
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Gstr3bSupplyDetails {
+    ///outward taxable supplies other than zero rated, nil rated and exempted
+    pub osup_det: Gstr3bTaxAmounts,
+    ///outward taxable supplies which are zero rated
+    pub osup_zero: Gstr3bTaxAmounts,
+    ///other outward supplies which are nil rated or exempted
+    pub osup_nil_exmp: Gstr3bTaxAmounts,
+    ///inward supplies liable to reverse charge
+    pub isup_rev: Gstr3bTaxAmounts,
+    ///non-gst outward supplies
+    pub osup_nongst: Gstr3bTaxAmounts,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Gstr3bItcDetail {
+    pub ty: String,
+    pub iamt: f64,
+    pub camt: f64,
+    pub samt: f64,
+    pub csamt: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct Gstr3bItcAmounts {
+    pub iamt: f64,
+    pub camt: f64,
+    pub samt: f64,
+    pub csamt: f64,
+}
+
+///table 4 of gstr-3b
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Gstr3bItcEligibility {
+    pub itc_avl: Vec<Gstr3bItcDetail>,
+    pub itc_rev: Vec<Gstr3bItcDetail>,
+    pub itc_net: Gstr3bItcAmounts,
+    pub itc_inelg: Vec<Gstr3bItcDetail>,
+}
+
+///gstr-3b json in the format accepted by the gst portal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr3bJson {
+    pub gstin: String,
+    pub ret_period: String,
+    pub sup_details: Gstr3bSupplyDetails,
+    pub itc_elg: Gstr3bItcEligibility,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxHead {
+    Igst,
+    Cgst,
+    Sgst,
+    Cess,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr3bReconciliationLine {
+    pub tax_head: TaxHead,
+    ///tax payable as computed from invoices of the period
+    pub computed_liability: f64,
+    ///net credit to the tax account of the head during the period
+    pub ledger_balance: f64,
+    pub difference: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Gstr3bSummary {
+    pub gstr3b: Gstr3bJson,
+    ///outward supplies against the output tax accounts, present only when they are given in the
+    ///request
+    pub reconciliation: Option<Vec<Gstr3bReconciliationLine>>,
+    ///inward supplies under reverse charge against the reverse charge tax accounts, present only
+    ///when they are given in the request
+    pub reverse_charge_reconciliation: Option<Vec<Gstr3bReconciliationLine>>,
+}
+
+///net credit (credits - debits) to an account during the return period, in currency units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct LedgerAccountNetCredit {
+    pub account_id: Uuid,
+    pub net_credit: f64,
+}
+
+///tax on purchase invoice lines of the return period for one combination of reverse charge and
+///itc eligibility, in currency units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PurchaseItcDb {
+    pub reverse_charge: bool,
+    pub itc_eligibility: ItcEligibility,
+    pub txval: f64,
+    pub iamt: f64,
+    pub camt: f64,
+    pub samt: f64,
+    pub csamt: f64,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use crate::gst_returns::gst_returns_models::ReturnPeriod;
+    use crate::gst_returns::gstr3b::gstr3b_models::{
+        GstLedgerAccounts, Gstr3bRequest, Gstr3bRequestBuilder,
+    };
+    use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+
+    pub fn a_gst_ledger_accounts() -> GstLedgerAccounts {
+        GstLedgerAccounts {
+            igst_account_id: Uuid::now_v7(),
+            cgst_account_id: Uuid::now_v7(),
+            sgst_account_id: Uuid::now_v7(),
+            cess_account_id: Uuid::now_v7(),
+        }
+    }
+
+    pub fn a_gstr3b_request(builder: Gstr3bRequestBuilder) -> Gstr3bRequest {
+        Gstr3bRequest {
+            gstin: builder.gstin.unwrap_or_default(),
+            return_period: builder
+                .return_period
+                .unwrap_or_else(|| ReturnPeriod::new(1, 2024).unwrap()),
+            output_tax_accounts: builder.output_tax_accounts.flatten(),
+            input_tax_accounts: builder.input_tax_accounts.flatten(),
+            reverse_charge_tax_accounts: builder.reverse_charge_tax_accounts.flatten(),
+        }
+    }
+
+    #[test]
+    fn test_request_deserialization_without_ledger_accounts() {
+        let req: Gstr3bRequest =
+            serde_json::from_str(r#"{"gstin":"05AABCA5291p1ZD","return_period":"012024"}"#)
+                .unwrap();
+        assert_eq!(req.gstin, GstinNo::default());
+        assert!(req.output_tax_accounts.is_none());
+    }
+}
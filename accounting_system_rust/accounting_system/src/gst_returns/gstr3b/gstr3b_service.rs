@@ -0,0 +1,682 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::gst_returns::gstr1::gstr1_models::{Gstr1ItemDetail, Gstr1Json, Gstr1Request};
+use crate::gst_returns::gstr1::gstr1_service::{Gstr1Service, Gstr1ServiceError};
+use crate::gst_returns::gstr3b::gstr3b_dao::{get_gstr3b_dao, Gstr3bDao};
+use crate::gst_returns::gstr3b::gstr3b_models::{
+    GstLedgerAccounts, Gstr3bItcAmounts, Gstr3bItcDetail, Gstr3bItcEligibility, Gstr3bJson,
+    Gstr3bReconciliationLine, Gstr3bRequest, Gstr3bSummary, Gstr3bSupplyDetails, Gstr3bTaxAmounts,
+    LedgerAccountNetCredit, PurchaseItcDb, TaxHead,
+};
+use crate::invoicing::purchase_invoice::purchase_invoice_models::ItcEligibility;
+
+#[derive(Debug, Error)]
+pub enum Gstr3bServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error(transparent)]
+    Gstr1(#[from] Gstr1ServiceError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait Gstr3bService: Send + Sync {
+    async fn generate_gstr3b_summary(
+        &self,
+        req: &Gstr3bRequest,
+        tenant_id: Uuid,
+    ) -> Result<Gstr3bSummary, Gstr3bServiceError>;
+}
+
+struct Gstr3bServiceImpl {
+    dao: Arc<dyn Gstr3bDao>,
+    gstr1_service: Arc<dyn Gstr1Service>,
+}
+
+pub fn get_gstr3b_service(
+    arc: Arc<Pool>,
+    gstr1_service: Arc<dyn Gstr1Service>,
+) -> Arc<dyn Gstr3bService> {
+    let dao = get_gstr3b_dao(arc);
+    let service = Gstr3bServiceImpl { dao, gstr1_service };
+    Arc::new(service)
+}
+
+fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl Gstr3bTaxAmounts {
+    fn add_item(&mut self, item: &Gstr1ItemDetail) {
+        self.txval += item.txval;
+        self.iamt += item.iamt.unwrap_or_default();
+        self.camt += item.camt.unwrap_or_default();
+        self.samt += item.samt.unwrap_or_default();
+        self.csamt += item.csamt;
+    }
+
+    fn rounded(self) -> Self {
+        Gstr3bTaxAmounts {
+            txval: round_2(self.txval),
+            iamt: round_2(self.iamt),
+            camt: round_2(self.camt),
+            samt: round_2(self.samt),
+            csamt: round_2(self.csamt),
+        }
+    }
+}
+
+///tax on items taxed at zero rate is nil and the value goes to nil rated/exempt supplies
+fn add_outward_item(details: &mut Gstr3bSupplyDetails, item: &Gstr1ItemDetail) {
+    if item.rt == 0.0 {
+        details.osup_nil_exmp.txval += item.txval;
+    } else {
+        details.osup_det.add_item(item);
+    }
+}
+
+///table 3.1 from the outward supplies reported in gstr-1 and the purchases under reverse charge
+pub(crate) fn compute_supply_details(
+    gstr1: &Gstr1Json,
+    purchases: &[PurchaseItcDb],
+) -> Gstr3bSupplyDetails {
+    let mut details = Gstr3bSupplyDetails::default();
+    //tax on outward supplies under reverse charge is paid by the recipient
+    for inv in gstr1
+        .b2b
+        .iter()
+        .flat_map(|a| a.inv.iter())
+        .filter(|inv| inv.rchrg != "Y")
+    {
+        //supplies to sez are zero rated like exports
+        let sez = inv.inv_typ == "SEWP" || inv.inv_typ == "SEWOP";
+        for item in inv.itms.iter() {
+            if sez {
+                details.osup_zero.add_item(&item.itm_det);
+            } else {
+                add_outward_item(&mut details, &item.itm_det);
+            }
+        }
+    }
+    gstr1
+        .b2cl
+        .iter()
+        .flat_map(|a| a.inv.iter())
+        .flat_map(|inv| inv.itms.iter())
+        .for_each(|item| add_outward_item(&mut details, &item.itm_det));
+    for b2cs in gstr1.b2cs.iter() {
+        add_outward_item(
+            &mut details,
+            &Gstr1ItemDetail {
+                rt: b2cs.rt,
+                txval: b2cs.txval,
+                iamt: b2cs.iamt,
+                camt: b2cs.camt,
+                samt: b2cs.samt,
+                csamt: b2cs.csamt,
+            },
+        );
+    }
+    for item in gstr1
+        .exp
+        .iter()
+        .flat_map(|a| a.inv.iter())
+        .flat_map(|inv| inv.itms.iter())
+    {
+        details.osup_zero.txval += item.txval;
+        details.osup_zero.iamt += item.iamt;
+        details.osup_zero.csamt += item.csamt;
+    }
+    for nil in gstr1.nil.inv.iter() {
+        details.osup_nil_exmp.txval += nil.expt_amt + nil.nil_amt;
+        details.osup_nongst.txval += nil.ngsup_amt;
+    }
+    for purchase in purchases.iter().filter(|a| a.reverse_charge) {
+        details.isup_rev.txval += purchase.txval;
+        details.isup_rev.iamt += purchase.iamt;
+        details.isup_rev.camt += purchase.camt;
+        details.isup_rev.samt += purchase.samt;
+        details.isup_rev.csamt += purchase.csamt;
+    }
+    Gstr3bSupplyDetails {
+        osup_det: details.osup_det.rounded(),
+        osup_zero: details.osup_zero.rounded(),
+        osup_nil_exmp: details.osup_nil_exmp.rounded(),
+        isup_rev: details.isup_rev.rounded(),
+        osup_nongst: details.osup_nongst.rounded(),
+    }
+}
+
+fn tax_head_amounts(
+    accounts: &GstLedgerAccounts,
+    balances: &HashMap<Uuid, f64>,
+) -> Result<[(TaxHead, f64); 4], Gstr3bServiceError> {
+    let missing: Vec<String> = accounts
+        .account_ids()
+        .iter()
+        .filter(|a| !balances.contains_key(a))
+        .map(|a| format!("ledger account {} not found", a))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Gstr3bServiceError::Validation(missing));
+    }
+    Ok([
+        (TaxHead::Igst, balances[&accounts.igst_account_id]),
+        (TaxHead::Cgst, balances[&accounts.cgst_account_id]),
+        (TaxHead::Sgst, balances[&accounts.sgst_account_id]),
+        (TaxHead::Cess, balances[&accounts.cess_account_id]),
+    ])
+}
+
+fn itc_detail<'a>(ty: &str, purchases: impl Iterator<Item = &'a PurchaseItcDb>) -> Gstr3bItcDetail {
+    let detail = purchases.fold(
+        Gstr3bItcDetail {
+            ty: ty.to_string(),
+            ..Default::default()
+        },
+        |acc, a| Gstr3bItcDetail {
+            iamt: acc.iamt + a.iamt,
+            camt: acc.camt + a.camt,
+            samt: acc.samt + a.samt,
+            csamt: acc.csamt + a.csamt,
+            ..acc
+        },
+    );
+    Gstr3bItcDetail {
+        iamt: round_2(detail.iamt),
+        camt: round_2(detail.camt),
+        samt: round_2(detail.samt),
+        csamt: round_2(detail.csamt),
+        ..detail
+    }
+}
+
+///itc of the period from the recorded purchase invoices, blocked credits are reported as
+///ineligible under section 17(5) and the others as ineligible for other reasons
+pub(crate) fn compute_purchase_itc_eligibility(
+    purchases: &[PurchaseItcDb],
+) -> Gstr3bItcEligibility {
+    if purchases.is_empty() {
+        return Gstr3bItcEligibility::default();
+    }
+    let of = |reverse_charge: Option<bool>, eligibility: ItcEligibility| {
+        purchases.iter().filter(move |a| {
+            a.itc_eligibility == eligibility && reverse_charge.is_none_or(|r| a.reverse_charge == r)
+        })
+    };
+    let itc_avl = vec![
+        itc_detail("ISRC", of(Some(true), ItcEligibility::Eligible)),
+        itc_detail("OTH", of(Some(false), ItcEligibility::Eligible)),
+    ];
+    let itc_net = itc_avl
+        .iter()
+        .fold(Gstr3bItcAmounts::default(), |acc, a| Gstr3bItcAmounts {
+            iamt: round_2(acc.iamt + a.iamt),
+            camt: round_2(acc.camt + a.camt),
+            samt: round_2(acc.samt + a.samt),
+            csamt: round_2(acc.csamt + a.csamt),
+        });
+    Gstr3bItcEligibility {
+        itc_avl,
+        itc_rev: vec![],
+        itc_net,
+        itc_inelg: vec![
+            itc_detail("RUL", of(None, ItcEligibility::Blocked)),
+            itc_detail("OTH", of(None, ItcEligibility::Ineligible)),
+        ],
+    }
+}
+
+///input tax accounts are debited when itc is availed, so the eligible itc is the net debit.
+///without input tax accounts the itc is taken from the purchase invoices of the period
+pub(crate) fn compute_itc_eligibility(
+    input_tax_accounts: Option<&GstLedgerAccounts>,
+    balances: &HashMap<Uuid, f64>,
+    purchases: &[PurchaseItcDb],
+) -> Result<Gstr3bItcEligibility, Gstr3bServiceError> {
+    let Some(accounts) = input_tax_accounts else {
+        return Ok(compute_purchase_itc_eligibility(purchases));
+    };
+    let [(_, igst), (_, cgst), (_, sgst), (_, cess)] = tax_head_amounts(accounts, balances)?;
+    let itc_net = Gstr3bItcAmounts {
+        iamt: round_2(-igst),
+        camt: round_2(-cgst),
+        samt: round_2(-sgst),
+        csamt: round_2(-cess),
+    };
+    Ok(Gstr3bItcEligibility {
+        itc_avl: vec![Gstr3bItcDetail {
+            ty: "OTH".to_string(),
+            iamt: itc_net.iamt,
+            camt: itc_net.camt,
+            samt: itc_net.samt,
+            csamt: itc_net.csamt,
+        }],
+        itc_rev: vec![],
+        itc_net,
+        itc_inelg: vec![],
+    })
+}
+
+///computed liability of each tax head against the net credit to the matching tax account
+fn reconcile_with_ledger(
+    liabilities: &[&Gstr3bTaxAmounts],
+    tax_accounts: &GstLedgerAccounts,
+    balances: &HashMap<Uuid, f64>,
+) -> Result<Vec<Gstr3bReconciliationLine>, Gstr3bServiceError> {
+    let liability = liabilities
+        .iter()
+        .fold(Gstr3bTaxAmounts::default(), |acc, a| Gstr3bTaxAmounts {
+            txval: acc.txval + a.txval,
+            iamt: acc.iamt + a.iamt,
+            camt: acc.camt + a.camt,
+            samt: acc.samt + a.samt,
+            csamt: acc.csamt + a.csamt,
+        });
+    let lines = tax_head_amounts(tax_accounts, balances)?
+        .into_iter()
+        .map(|(tax_head, ledger_balance)| {
+            let computed_liability = round_2(match tax_head {
+                TaxHead::Igst => liability.iamt,
+                TaxHead::Cgst => liability.camt,
+                TaxHead::Sgst => liability.samt,
+                TaxHead::Cess => liability.csamt,
+            });
+            let ledger_balance = round_2(ledger_balance);
+            Gstr3bReconciliationLine {
+                tax_head,
+                computed_liability,
+                ledger_balance,
+                difference: round_2(computed_liability - ledger_balance),
+            }
+        })
+        .collect();
+    Ok(lines)
+}
+
+///tax on outward supplies is credited to the output tax accounts when invoices are issued
+pub(crate) fn reconcile_with_output_ledger(
+    supply_details: &Gstr3bSupplyDetails,
+    output_tax_accounts: &GstLedgerAccounts,
+    balances: &HashMap<Uuid, f64>,
+) -> Result<Vec<Gstr3bReconciliationLine>, Gstr3bServiceError> {
+    reconcile_with_ledger(
+        &[&supply_details.osup_det, &supply_details.osup_zero],
+        output_tax_accounts,
+        balances,
+    )
+}
+
+///tax on purchases under reverse charge is credited to the reverse charge tax accounts, not to the
+///output tax accounts
+pub(crate) fn reconcile_with_reverse_charge_ledger(
+    supply_details: &Gstr3bSupplyDetails,
+    reverse_charge_tax_accounts: &GstLedgerAccounts,
+    balances: &HashMap<Uuid, f64>,
+) -> Result<Vec<Gstr3bReconciliationLine>, Gstr3bServiceError> {
+    reconcile_with_ledger(
+        &[&supply_details.isup_rev],
+        reverse_charge_tax_accounts,
+        balances,
+    )
+}
+
+#[async_trait]
+impl Gstr3bService for Gstr3bServiceImpl {
+    async fn generate_gstr3b_summary(
+        &self,
+        req: &Gstr3bRequest,
+        tenant_id: Uuid,
+    ) -> Result<Gstr3bSummary, Gstr3bServiceError> {
+        let gstr1_req = Gstr1Request {
+            gstin: req.gstin.clone(),
+            return_period: req.return_period,
+        };
+        let gstr1 = self
+            .gstr1_service
+            .generate_gstr1_json(&gstr1_req, tenant_id)
+            .await?;
+        let account_ids: Vec<Uuid> = req
+            .output_tax_accounts
+            .iter()
+            .chain(req.input_tax_accounts.iter())
+            .chain(req.reverse_charge_tax_accounts.iter())
+            .flat_map(|a| a.account_ids())
+            .collect();
+        let (start_ms, end_ms) = req.return_period.epoch_millis_range()?;
+        let purchases = self
+            .dao
+            .get_purchase_itc_for_period(tenant_id, req.gstin.get_str(), start_ms, end_ms)
+            .await?;
+        let balances: HashMap<Uuid, f64> = if account_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.dao
+                .get_net_credits_for_accounts(tenant_id, &account_ids, start_ms, end_ms)
+                .await?
+                .into_iter()
+                .map(
+                    |LedgerAccountNetCredit {
+                         account_id,
+                         net_credit,
+                     }| (account_id, net_credit),
+                )
+                .collect()
+        };
+        let sup_details = compute_supply_details(&gstr1, &purchases);
+        let itc_elg =
+            compute_itc_eligibility(req.input_tax_accounts.as_ref(), &balances, &purchases)?;
+        let reconciliation = req
+            .output_tax_accounts
+            .as_ref()
+            .map(|accounts| reconcile_with_output_ledger(&sup_details, accounts, &balances))
+            .transpose()?;
+        let reverse_charge_reconciliation = req
+            .reverse_charge_tax_accounts
+            .as_ref()
+            .map(|accounts| reconcile_with_reverse_charge_ledger(&sup_details, accounts, &balances))
+            .transpose()?;
+        Ok(Gstr3bSummary {
+            gstr3b: Gstr3bJson {
+                gstin: gstr1.gstin,
+                ret_period: gstr1.fp,
+                sup_details,
+                itc_elg,
+            },
+            reconciliation,
+            reverse_charge_reconciliation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use uuid::Uuid;
+
+    use crate::gst_returns::gst_returns_models::ReturnPeriod;
+    use crate::gst_returns::gstr1::gstr1_models::tests::a_gstr1_invoice_line_db;
+    use crate::gst_returns::gstr1::gstr1_models::{Gstr1InvoiceLineDb, Gstr1Json};
+    use crate::gst_returns::gstr1::gstr1_service::{build_gstr1_json, MockGstr1Service};
+    use crate::gst_returns::gstr3b::gstr3b_dao::MockGstr3bDao;
+    use crate::gst_returns::gstr3b::gstr3b_models::tests::{
+        a_gst_ledger_accounts, a_gstr3b_request,
+    };
+    use crate::gst_returns::gstr3b::gstr3b_models::{
+        Gstr3bRequestBuilder, LedgerAccountNetCredit, PurchaseItcDb, TaxHead,
+    };
+    use crate::gst_returns::gstr3b::gstr3b_service::{
+        compute_itc_eligibility, compute_supply_details, reconcile_with_output_ledger,
+        reconcile_with_reverse_charge_ledger, Gstr3bService, Gstr3bServiceError, Gstr3bServiceImpl,
+    };
+    use crate::invoicing::purchase_invoice::purchase_invoice_models::ItcEligibility;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_gstr1_json() -> Gstr1Json {
+        let b2b = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            billed_to_gstin: Some("06MFNMS5291p1ZA".to_string()),
+            billed_to_state_code: Some("06".to_string()),
+            igst_applicable: true,
+            ..a_gstr1_invoice_line_db()
+        };
+        let b2b_reverse_charge = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            reverse_charge_applicable: true,
+            ..b2b.clone()
+        };
+        let b2cs = a_gstr1_invoice_line_db();
+        let b2cs_exempt = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            tax_percentage: 0.0,
+            unit_price: 250.0,
+            ..a_gstr1_invoice_line_db()
+        };
+        let export = Gstr1InvoiceLineDb {
+            invoice_id: Uuid::now_v7(),
+            billed_to_country_id: Some(Uuid::now_v7()),
+            igst_applicable: true,
+            ..a_gstr1_invoice_line_db()
+        };
+        let lines = vec![b2b, b2b_reverse_charge, b2cs, b2cs_exempt, export];
+        build_gstr1_json(
+            "05AABCA5291p1ZD",
+            &ReturnPeriod::new(1, 2024).unwrap(),
+            &lines,
+            &[],
+        )
+        .unwrap()
+    }
+
+    fn a_purchase_itc(reverse_charge: bool, itc_eligibility: ItcEligibility) -> PurchaseItcDb {
+        PurchaseItcDb {
+            reverse_charge,
+            itc_eligibility,
+            txval: 1000.0,
+            iamt: 0.0,
+            camt: 25.0,
+            samt: 25.0,
+            csamt: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_compute_supply_details() {
+        let details = compute_supply_details(&a_gstr1_json(), &[]);
+        assert_eq!(details.osup_det.txval, 2000.0);
+        assert_eq!(details.osup_det.iamt, 180.0);
+        assert_eq!(details.osup_det.camt, 90.0);
+        assert_eq!(details.osup_det.samt, 90.0);
+        assert_eq!(details.osup_nil_exmp.txval, 250.0);
+        assert_eq!(details.osup_zero.txval, 1000.0);
+        assert_eq!(details.osup_zero.iamt, 180.0);
+        assert_eq!(details.isup_rev.txval, 0.0);
+    }
+
+    #[test]
+    fn test_sez_supplies_are_zero_rated() {
+        let sez = Gstr1InvoiceLineDb {
+            billed_to_gstin: Some("05MFNMS5291P1ZC".to_string()),
+            igst_applicable: true,
+            supply_classification: "sez_with_payment".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let gstr1 = build_gstr1_json(
+            "05AABCA5291p1ZD",
+            &ReturnPeriod::new(1, 2024).unwrap(),
+            &[sez],
+            &[],
+        )
+        .unwrap();
+        let details = compute_supply_details(&gstr1, &[]);
+        assert_eq!(details.osup_zero.txval, 1000.0);
+        assert_eq!(details.osup_zero.iamt, 180.0);
+        assert_eq!(details.osup_det.txval, 0.0);
+    }
+
+    #[test]
+    fn test_bill_of_supply_is_excluded_from_output_tax() {
+        let bill_of_supply = Gstr1InvoiceLineDb {
+            billed_to_gstin: Some("05MFNMS5291P1ZC".to_string()),
+            tax_percentage: 0.0,
+            document_type: "bill_of_supply".to_string(),
+            ..a_gstr1_invoice_line_db()
+        };
+        let gstr1 = build_gstr1_json(
+            "05AABCA5291p1ZD",
+            &ReturnPeriod::new(1, 2024).unwrap(),
+            &[bill_of_supply],
+            &[],
+        )
+        .unwrap();
+        let details = compute_supply_details(&gstr1, &[]);
+        assert_eq!(details.osup_nil_exmp.txval, 1000.0);
+        assert_eq!(details.osup_det.txval, 0.0);
+        assert_eq!(details.osup_det.camt, 0.0);
+    }
+
+    #[test]
+    fn test_reverse_charge_purchases_are_inward_supplies() {
+        let purchases = [
+            a_purchase_itc(true, ItcEligibility::Eligible),
+            a_purchase_itc(true, ItcEligibility::Blocked),
+            a_purchase_itc(false, ItcEligibility::Eligible),
+        ];
+        let details = compute_supply_details(&a_gstr1_json(), &purchases);
+        assert_eq!(details.isup_rev.txval, 2000.0);
+        assert_eq!(details.isup_rev.camt, 50.0);
+        assert_eq!(details.osup_det.txval, 2000.0);
+    }
+
+    #[test]
+    fn test_itc_eligibility_from_purchases() {
+        let purchases = [
+            a_purchase_itc(true, ItcEligibility::Eligible),
+            a_purchase_itc(false, ItcEligibility::Eligible),
+            a_purchase_itc(false, ItcEligibility::Blocked),
+            a_purchase_itc(true, ItcEligibility::Ineligible),
+        ];
+        let itc = compute_itc_eligibility(None, &HashMap::new(), &purchases).unwrap();
+        let isrc = itc.itc_avl.iter().find(|a| a.ty == "ISRC").unwrap();
+        assert_eq!(isrc.camt, 25.0);
+        assert_eq!(itc.itc_net.camt, 50.0);
+        assert_eq!(itc.itc_net.samt, 50.0);
+        let blocked = itc.itc_inelg.iter().find(|a| a.ty == "RUL").unwrap();
+        assert_eq!(blocked.samt, 25.0);
+        let no_purchases = compute_itc_eligibility(None, &HashMap::new(), &[]).unwrap();
+        assert!(no_purchases.itc_avl.is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_with_output_ledger() {
+        let details = compute_supply_details(&a_gstr1_json(), &[]);
+        let accounts = a_gst_ledger_accounts();
+        let balances = HashMap::from([
+            (accounts.igst_account_id, 360.0),
+            (accounts.cgst_account_id, 80.0),
+            (accounts.sgst_account_id, 90.0),
+            (accounts.cess_account_id, 0.0),
+        ]);
+        let lines = reconcile_with_output_ledger(&details, &accounts, &balances).unwrap();
+        let cgst = lines.iter().find(|a| a.tax_head == TaxHead::Cgst).unwrap();
+        assert_eq!(cgst.computed_liability, 90.0);
+        assert_eq!(cgst.difference, 10.0);
+        assert!(lines
+            .iter()
+            .filter(|a| a.tax_head != TaxHead::Cgst)
+            .all(|a| a.difference == 0.0));
+    }
+
+    #[test]
+    fn test_reverse_charge_is_reconciled_apart_from_output_tax() {
+        let purchases = [a_purchase_itc(true, ItcEligibility::Eligible)];
+        let details = compute_supply_details(&a_gstr1_json(), &purchases);
+        let output_accounts = a_gst_ledger_accounts();
+        let reverse_charge_accounts = a_gst_ledger_accounts();
+        let balances = HashMap::from([
+            (output_accounts.igst_account_id, 360.0),
+            (output_accounts.cgst_account_id, 90.0),
+            (output_accounts.sgst_account_id, 90.0),
+            (output_accounts.cess_account_id, 0.0),
+            (reverse_charge_accounts.igst_account_id, 0.0),
+            (reverse_charge_accounts.cgst_account_id, 25.0),
+            (reverse_charge_accounts.sgst_account_id, 20.0),
+            (reverse_charge_accounts.cess_account_id, 0.0),
+        ]);
+        let output = reconcile_with_output_ledger(&details, &output_accounts, &balances).unwrap();
+        assert!(output.iter().all(|a| a.difference == 0.0));
+        let reverse_charge =
+            reconcile_with_reverse_charge_ledger(&details, &reverse_charge_accounts, &balances)
+                .unwrap();
+        let sgst = reverse_charge
+            .iter()
+            .find(|a| a.tax_head == TaxHead::Sgst)
+            .unwrap();
+        assert_eq!(sgst.computed_liability, 25.0);
+        assert_eq!(sgst.difference, 5.0);
+        assert!(reverse_charge
+            .iter()
+            .filter(|a| a.tax_head != TaxHead::Sgst)
+            .all(|a| a.difference == 0.0));
+    }
+
+    #[test]
+    fn test_itc_eligibility_requires_all_accounts() {
+        let accounts = a_gst_ledger_accounts();
+        let balances = HashMap::from([(accounts.igst_account_id, -100.0)]);
+        let res = compute_itc_eligibility(Some(&accounts), &balances, &[]);
+        assert!(matches!(res, Err(Gstr3bServiceError::Validation(a)) if a.len() == 3));
+        let balances = HashMap::from([
+            (accounts.igst_account_id, -100.0),
+            (accounts.cgst_account_id, -25.5),
+            (accounts.sgst_account_id, -25.5),
+            (accounts.cess_account_id, 0.0),
+        ]);
+        let itc = compute_itc_eligibility(Some(&accounts), &balances, &[]).unwrap();
+        assert_eq!(itc.itc_net.iamt, 100.0);
+        assert_eq!(itc.itc_net.camt, 25.5);
+        assert_eq!(itc.itc_avl[0].ty, "OTH");
+    }
+
+    #[tokio::test]
+    async fn test_generate_gstr3b_summary() {
+        let output_accounts = a_gst_ledger_accounts();
+        let mut gstr1_service = MockGstr1Service::new();
+        gstr1_service
+            .expect_generate_gstr1_json()
+            .returning(|_, _| Ok(a_gstr1_json()));
+        let mut dao = MockGstr3bDao::new();
+        dao.expect_get_purchase_itc_for_period()
+            .withf(|_, gstin, start, end| {
+                gstin == "05AABCA5291p1ZD" && *start == 1704047400000 && *end == 1706725800000
+            })
+            .returning(|_, _, _, _| Ok(vec![]));
+        dao.expect_get_net_credits_for_accounts()
+            .withf(|_, ids, start, end| {
+                ids.len() == 4 && *start == 1704047400000 && *end == 1706725800000
+            })
+            .returning(move |_, ids, _, _| {
+                Ok(ids
+                    .iter()
+                    .map(|id| LedgerAccountNetCredit {
+                        account_id: *id,
+                        net_credit: 0.0,
+                    })
+                    .collect())
+            });
+        let service = Gstr3bServiceImpl {
+            dao: Arc::new(dao),
+            gstr1_service: Arc::new(gstr1_service),
+        };
+        let mut builder = Gstr3bRequestBuilder::default();
+        builder.output_tax_accounts(Some(output_accounts));
+        let summary = service
+            .generate_gstr3b_summary(&a_gstr3b_request(builder), *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_eq!(summary.gstr3b.ret_period, "012024");
+        let reconciliation = summary.reconciliation.unwrap();
+        let igst = reconciliation
+            .iter()
+            .find(|a| a.tax_head == TaxHead::Igst)
+            .unwrap();
+        assert_eq!(igst.difference, 360.0);
+        assert!(summary.gstr3b.itc_elg.itc_avl.is_empty());
+        assert!(summary.reverse_charge_reconciliation.is_none());
+    }
+}
@@ -0,0 +1,4 @@
+mod gstr3b_dao;
+pub mod gstr3b_http_api;
+pub mod gstr3b_models;
+pub mod gstr3b_service;
@@ -0,0 +1,3 @@
+pub mod gst_returns_models;
+pub mod gstr1;
+pub mod gstr3b;
@@ -19,8 +19,8 @@ mod tests {
         let mut k: Vec<String> = Vec::new();
         for x in charges {
             let p = format!(
-                "('{}',{}::smallint,'{}',{},{})",
-                x.line_id, x.line_no, x.line_title, x.title_xx_hash, x.rate
+                "('{}',{}::smallint,'{}',{},{},{})",
+                x.line_id, x.line_no, x.line_title, x.title_xx_hash, x.rate, x.tax_percentage
             );
             k.push(p);
         }
@@ -8,6 +8,7 @@ pub struct CreateAdditionalChargeRequestDbModel {
     pub line_title: String,
     pub title_xx_hash: u32,
     pub rate: f64,
+    pub tax_percentage: f32,
 }
 
 #[cfg(test)]
@@ -40,6 +41,7 @@ pub mod tests {
             line_title,
             title_xx_hash: builder.title_xx_hash.unwrap_or(a),
             rate: builder.rate.unwrap_or(20.0),
+            tax_percentage: builder.tax_percentage.unwrap_or(18.0),
         }
     }
 }
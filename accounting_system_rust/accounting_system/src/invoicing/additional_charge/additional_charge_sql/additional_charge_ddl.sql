@@ -6,6 +6,7 @@ create table additional_charge
     line_no          smallint                        not null,
     line_title_id    uuid references line_title (id) not null,
     rate             double precision                         not null,
+    tax_percentage   real             default 0               not null,
     created_by       uuid references app_user (id)   not null,
     updated_by       uuid references app_user (id),
     created_at       bigint default extract(epoch from now()) * 1000000,
@@ -17,5 +18,6 @@ create type create_additional_charge_request as
     line_no       smallint,
     line_title    text,
     title_xx_hash bigint,
-    rate          double precision
+    rate          double precision,
+    tax_percentage real
 );
@@ -15,9 +15,9 @@ BEGIN
                                             null, _tenant_id)
             into title_id;
             insert into additional_charge (id, tenant_id, invoice_table_id, line_no, line_title_id, rate,
-                                           created_by, updated_by, created_at, updated_at)
+                                           tax_percentage, created_by, updated_by, created_at, updated_at)
             values (line.line_id, _tenant_id, invoice_tab_id, line.line_no, title_id, line.rate,
-                    _created_by, _created_by, default, default);
+                    coalesce(line.tax_percentage, 0), _created_by, _created_by, default, default);
         end loop;
 end;
 $$
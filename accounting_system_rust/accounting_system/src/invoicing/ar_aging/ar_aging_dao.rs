@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::ar_aging::ar_aging_models::{ArAgingInvoiceDb, UnappliedCreditDb};
+
+const AGING_INVOICES_QUERY: &str = "select i.id,i.invoice_number,i.invoice_date_ms,\
+i.invoice_date_ms+coalesce(pt.due_days,0)::bigint*86400000,i.billed_to_business_entity,b.name,\
+i.currency_id,cm.display_name,cu.id,s.gstin,i.total_payable_amount,\
+coalesce((select sum(ra.amount+ra.discount_amount) from receipt_allocation ra join receipt r on ra.receipt_id=r.id \
+where ra.invoice_id=i.id and ra.tenant_id=i.tenant_id and r.receipt_date_ms<=$2),0) \
+from invoice i \
+join business_entity s on i.supplier_business_entity=s.id \
+left join business_entity b on i.billed_to_business_entity=b.id \
+left join payment_term pt on i.payment_term_id=pt.id \
+join currency_master cm on i.currency_id=cm.id \
+left join lateral (select c.id from company_unit_master c where c.tenant_id=i.tenant_id \
+and upper(c.gstin)=upper(s.gstin) order by c.id limit 1) cu on true \
+where i.tenant_id=$1 and i.active and i.invoice_status='issued' and i.invoice_date_ms<=$2 \
+and ($3::uuid is null or i.billed_to_business_entity=$3) \
+order by b.name,i.billed_to_business_entity,i.invoice_date_ms,i.invoice_number";
+
+const UNAPPLIED_CREDITS_QUERY: &str = "select r.business_entity_id,b.name,r.currency_id,\
+cm.display_name,sum(r.unallocated_amount) \
+from receipt r \
+join business_entity b on r.business_entity_id=b.id \
+join currency_master cm on r.currency_id=cm.id \
+where r.tenant_id=$1 and r.active and r.receipt_date_ms<=$2 and r.unallocated_amount>0 \
+and ($3::uuid is null or r.business_entity_id=$3) \
+group by r.business_entity_id,b.name,r.currency_id,cm.display_name";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ArAgingDao: Send + Sync {
+    ///invoices dated on or before as of date along with amount received till then
+    async fn get_invoices_for_aging(
+        &self,
+        tenant_id: Uuid,
+        as_of_date_ms: i64,
+        business_entity_id: Option<Uuid>,
+    ) -> Result<Vec<ArAgingInvoiceDb>, DaoError>;
+    ///receipt amounts dated on or before as of date which are not allocated to any invoice
+    async fn get_unapplied_credits(
+        &self,
+        tenant_id: Uuid,
+        as_of_date_ms: i64,
+        business_entity_id: Option<Uuid>,
+    ) -> Result<Vec<UnappliedCreditDb>, DaoError>;
+}
+
+struct ArAgingDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_ar_aging_dao(arc: Arc<Pool>) -> Arc<dyn ArAgingDao> {
+    let dao = ArAgingDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for ArAgingInvoiceDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(ArAgingInvoiceDb {
+            invoice_id: row.get(0),
+            invoice_number: row.get(1),
+            invoice_date_ms: row.get(2),
+            due_date_ms: row.get(3),
+            customer_id: row.get(4),
+            customer_name: row.get(5),
+            currency_id: row.get(6),
+            currency: row.get(7),
+            company_unit_id: row.get(8),
+            supplier_gstin: row.get(9),
+            total_payable_amount: row.get(10),
+            amount_received: row.get(11),
+        })
+    }
+}
+
+impl TryFrom<Row> for UnappliedCreditDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(UnappliedCreditDb {
+            customer_id: row.get(0),
+            customer_name: row.get(1),
+            currency_id: row.get(2),
+            currency: row.get(3),
+            amount: row.get(4),
+        })
+    }
+}
+
+#[async_trait]
+impl ArAgingDao for ArAgingDaoImpl {
+    async fn get_invoices_for_aging(
+        &self,
+        tenant_id: Uuid,
+        as_of_date_ms: i64,
+        business_entity_id: Option<Uuid>,
+    ) -> Result<Vec<ArAgingInvoiceDb>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(
+                AGING_INVOICES_QUERY,
+                &[&tenant_id, &as_of_date_ms, &business_entity_id],
+            )
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn get_unapplied_credits(
+        &self,
+        tenant_id: Uuid,
+        as_of_date_ms: i64,
+        business_entity_id: Option<Uuid>,
+    ) -> Result<Vec<UnappliedCreditDb>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(
+                UNAPPLIED_CREDITS_QUERY,
+                &[&tenant_id, &as_of_date_ms, &business_entity_id],
+            )
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::invoicing::ar_aging::ar_aging_dao::{ArAgingDao, ArAgingDaoImpl};
+    use crate::invoicing::ar_aging::ar_aging_models::tests::AS_OF_DATE_MS;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_get_invoices_for_aging() {
+        let dao = get_dao_generic(|c| ArAgingDaoImpl { postgres_client: c }, None).await;
+        let invoices = dao
+            .get_invoices_for_aging(*SEED_TENANT_ID, AS_OF_DATE_MS, None)
+            .await
+            .unwrap();
+        assert_that!(invoices).has_length(1);
+        let credits = dao
+            .get_unapplied_credits(*SEED_TENANT_ID, AS_OF_DATE_MS, None)
+            .await
+            .unwrap();
+        assert_that!(credits).is_empty();
+    }
+}
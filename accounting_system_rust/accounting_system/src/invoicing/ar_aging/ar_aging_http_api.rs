@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+
+use crate::common_utils::mime_types::MimeType;
+use crate::common_utils::utils::TenantId;
+use crate::invoicing::ar_aging::ar_aging_models::ArAgingRequest;
+use crate::invoicing::ar_aging::ar_aging_service::{ArAgingService, ArAgingServiceError};
+use crate::setup_routes;
+
+impl ResponseError for ArAgingServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ArAgingServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ArAgingServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn get_ar_aging_report(
+    data: Data<Arc<dyn ArAgingService>>,
+    request: web::Json<ArAgingRequest>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let report = data
+        .get_ar_aging_report(&request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(report))
+}
+
+async fn get_ar_aging_csv(
+    data: Data<Arc<dyn ArAgingService>>,
+    request: web::Json<ArAgingRequest>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let csv = data
+        .get_ar_aging_csv(&request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .content_type(MimeType::Csv.get_mime_type())
+        .body(csv))
+}
+
+async fn get_ar_aging_pdf(
+    data: Data<Arc<dyn ArAgingService>>,
+    request: web::Json<ArAgingRequest>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let pdf = data
+        .get_ar_aging_pdf(&request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .content_type(MimeType::Pdf.get_mime_type())
+        .body(pdf))
+}
+
+setup_routes!(
+    ArAgingService,
+    "/ar-aging",
+    "/report",
+    web::post().to(get_ar_aging_report),
+    "/csv",
+    web::post().to(get_ar_aging_csv),
+    "/pdf",
+    web::post().to(get_ar_aging_pdf)
+);
@@ -0,0 +1,221 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::common_utils::utils::epoch_ms_to_indian_date;
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct ArAgingRequest {
+    ///outstanding and days overdue are computed as of this instant
+    pub as_of_date_ms: i64,
+    ///restricts the report to a single customer
+    #[serde(default)]
+    pub business_entity_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgingBucket {
+    Current,
+    Days1To30,
+    Days31To60,
+    Days61To90,
+    Above90,
+}
+
+impl AgingBucket {
+    pub fn from_days_overdue(days: i64) -> Self {
+        match days {
+            i64::MIN..=0 => AgingBucket::Current,
+            1..=30 => AgingBucket::Days1To30,
+            31..=60 => AgingBucket::Days31To60,
+            61..=90 => AgingBucket::Days61To90,
+            _ => AgingBucket::Above90,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct AgingBucketAmounts {
+    pub current: f64,
+    pub days_1_30: f64,
+    pub days_31_60: f64,
+    pub days_61_90: f64,
+    pub above_90: f64,
+    pub total: f64,
+}
+
+impl AgingBucketAmounts {
+    pub fn add(&mut self, bucket: AgingBucket, amount: f64) {
+        let slot = match bucket {
+            AgingBucket::Current => &mut self.current,
+            AgingBucket::Days1To30 => &mut self.days_1_30,
+            AgingBucket::Days31To60 => &mut self.days_31_60,
+            AgingBucket::Days61To90 => &mut self.days_61_90,
+            AgingBucket::Above90 => &mut self.above_90,
+        };
+        *slot = round_2(*slot + amount);
+        self.total = round_2(self.total + amount);
+    }
+
+    pub fn merge(&mut self, other: &AgingBucketAmounts) {
+        self.add(AgingBucket::Current, other.current);
+        self.add(AgingBucket::Days1To30, other.days_1_30);
+        self.add(AgingBucket::Days31To60, other.days_31_60);
+        self.add(AgingBucket::Days61To90, other.days_61_90);
+        self.add(AgingBucket::Above90, other.above_90);
+    }
+}
+
+pub(crate) fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+///calendar days between the due date and the as of date in indian standard time
+pub(crate) fn days_overdue(due_date_ms: i64, as_of_date_ms: i64) -> anyhow::Result<i64> {
+    let days = epoch_ms_to_indian_date(as_of_date_ms)? - epoch_ms_to_indian_date(due_date_ms)?;
+    Ok(days.num_days())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArAgingInvoice {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub invoice_date_ms: i64,
+    pub due_date_ms: i64,
+    pub days_overdue: i64,
+    pub total_payable_amount: f64,
+    pub amount_received: f64,
+    pub outstanding_amount: f64,
+    pub bucket: AgingBucket,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArAgingCustomer {
+    ///none for invoices without a billed to entity (b2c)
+    pub customer_id: Option<Uuid>,
+    pub customer_name: String,
+    pub currency_id: Uuid,
+    pub currency: String,
+    pub buckets: AgingBucketAmounts,
+    ///receipt amounts not yet allocated to any invoice
+    pub unapplied_credits: f64,
+    pub net_outstanding: f64,
+    pub invoices: Vec<ArAgingInvoice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArAgingCompanyUnitTotal {
+    ///none when the supplier gstin is not registered as a company unit
+    pub company_unit_id: Option<Uuid>,
+    pub supplier_gstin: Option<String>,
+    pub currency_id: Uuid,
+    pub currency: String,
+    pub buckets: AgingBucketAmounts,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArAgingCurrencyTotal {
+    pub currency_id: Uuid,
+    pub currency: String,
+    pub buckets: AgingBucketAmounts,
+    pub unapplied_credits: f64,
+    pub net_outstanding: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArAgingReport {
+    pub as_of_date_ms: i64,
+    pub customers: Vec<ArAgingCustomer>,
+    pub company_unit_totals: Vec<ArAgingCompanyUnitTotal>,
+    pub currency_totals: Vec<ArAgingCurrencyTotal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ArAgingInvoiceDb {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub invoice_date_ms: i64,
+    pub due_date_ms: i64,
+    pub customer_id: Option<Uuid>,
+    pub customer_name: Option<String>,
+    pub currency_id: Uuid,
+    pub currency: String,
+    pub company_unit_id: Option<Uuid>,
+    pub supplier_gstin: Option<String>,
+    pub total_payable_amount: f64,
+    ///allocations and early payment discounts from receipts dated on or before the as of date
+    pub amount_received: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct UnappliedCreditDb {
+    pub customer_id: Uuid,
+    pub customer_name: Option<String>,
+    pub currency_id: Uuid,
+    pub currency: String,
+    pub amount: f64,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::invoicing::ar_aging::ar_aging_models::{
+        days_overdue, AgingBucket, ArAgingInvoiceDb, UnappliedCreditDb,
+    };
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::invoicing::receipt::receipt_models::tests::SEED_CUSTOMER_ID;
+
+    ///31 jan 2024 12:00 IST
+    pub const AS_OF_DATE_MS: i64 = 1706682600000;
+    pub const DAY_MS: i64 = 86_400_000;
+
+    pub(crate) fn an_ar_aging_invoice_db(days_past_due: i64) -> ArAgingInvoiceDb {
+        ArAgingInvoiceDb {
+            invoice_id: *SEED_INVOICE_ID,
+            invoice_number: "TES1".to_string(),
+            invoice_date_ms: AS_OF_DATE_MS - (days_past_due + 30) * DAY_MS,
+            due_date_ms: AS_OF_DATE_MS - days_past_due * DAY_MS,
+            customer_id: Some(Uuid::parse_str(SEED_CUSTOMER_ID).unwrap()),
+            customer_name: Some("acme traders".to_string()),
+            currency_id: *SEED_CURRENCY_ID,
+            currency: "INR".to_string(),
+            company_unit_id: None,
+            supplier_gstin: Some("05AABCA5291P1ZD".to_string()),
+            total_payable_amount: 1180.0,
+            amount_received: 0.0,
+        }
+    }
+
+    pub(crate) fn an_unapplied_credit_db() -> UnappliedCreditDb {
+        UnappliedCreditDb {
+            customer_id: Uuid::parse_str(SEED_CUSTOMER_ID).unwrap(),
+            customer_name: Some("acme traders".to_string()),
+            currency_id: *SEED_CURRENCY_ID,
+            currency: "INR".to_string(),
+            amount: 100.0,
+        }
+    }
+
+    #[rstest]
+    #[case(- 5, AgingBucket::Current)]
+    #[case(0, AgingBucket::Current)]
+    #[case(1, AgingBucket::Days1To30)]
+    #[case(30, AgingBucket::Days1To30)]
+    #[case(31, AgingBucket::Days31To60)]
+    #[case(61, AgingBucket::Days61To90)]
+    #[case(91, AgingBucket::Above90)]
+    fn test_bucket_from_days_overdue(#[case] days: i64, #[case] bucket: AgingBucket) {
+        assert_eq!(AgingBucket::from_days_overdue(days), bucket);
+    }
+
+    #[test]
+    fn test_days_overdue_uses_ist_calendar_days() {
+        //30 jan 2024 23:30 IST vs 31 jan 2024 00:30 IST
+        assert_eq!(days_overdue(1706637600000, 1706641200000).unwrap(), 1);
+        assert_eq!(days_overdue(AS_OF_DATE_MS, AS_OF_DATE_MS).unwrap(), 0);
+    }
+}
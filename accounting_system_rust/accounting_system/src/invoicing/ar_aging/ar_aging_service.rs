@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use chrono::DateTime;
+use chrono_tz::Asia::Kolkata;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use pdf_doc_generator::aging_report_template::{
+    create_aging_report_pdf, AgingBuckets, AgingReport, AgingReportCustomerRow, AgingReportTotalRow,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::ar_aging::ar_aging_dao::{get_ar_aging_dao, ArAgingDao};
+use crate::invoicing::ar_aging::ar_aging_models::{
+    days_overdue, round_2, AgingBucket, AgingBucketAmounts, ArAgingCompanyUnitTotal,
+    ArAgingCurrencyTotal, ArAgingCustomer, ArAgingInvoice, ArAgingInvoiceDb, ArAgingReport,
+    ArAgingRequest, UnappliedCreditDb,
+};
+
+const UNREGISTERED_CUSTOMER_NAME: &str = "unregistered customers";
+
+#[derive(Debug, Error)]
+pub enum ArAgingServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ArAgingService: Send + Sync {
+    async fn get_ar_aging_report(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<ArAgingReport, ArAgingServiceError>;
+    async fn get_ar_aging_csv(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<String, ArAgingServiceError>;
+    async fn get_ar_aging_pdf(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<Vec<u8>, ArAgingServiceError>;
+}
+
+struct ArAgingServiceImpl {
+    dao: Arc<dyn ArAgingDao>,
+}
+
+pub fn get_ar_aging_service(arc: Arc<Pool>) -> Arc<dyn ArAgingService> {
+    let dao = get_ar_aging_dao(arc);
+    let service = ArAgingServiceImpl { dao };
+    Arc::new(service)
+}
+
+fn customer_name(id: Option<Uuid>, name: Option<&str>) -> String {
+    match (id, name) {
+        (_, Some(name)) => name.to_string(),
+        (Some(id), None) => id.to_string(),
+        (None, None) => UNREGISTERED_CUSTOMER_NAME.to_string(),
+    }
+}
+
+///buckets the outstanding of each invoice as of the given date and nets unapplied credits per customer.
+///invoices settled on or before the as of date are left out
+pub(crate) fn build_ar_aging_report(
+    as_of_date_ms: i64,
+    invoices: &[ArAgingInvoiceDb],
+    credits: &[UnappliedCreditDb],
+) -> anyhow::Result<ArAgingReport> {
+    let mut customers: Vec<ArAgingCustomer> = vec![];
+    let mut customer_idx: HashMap<(Option<Uuid>, Uuid), usize> = HashMap::new();
+    let mut unit_totals: Vec<ArAgingCompanyUnitTotal> = vec![];
+    let mut unit_idx: HashMap<(Option<Uuid>, Option<String>, Uuid), usize> = HashMap::new();
+    for inv in invoices {
+        let outstanding = round_2(inv.total_payable_amount - inv.amount_received);
+        if outstanding <= 0.0 {
+            continue;
+        }
+        let days = days_overdue(inv.due_date_ms, as_of_date_ms)?;
+        let bucket = AgingBucket::from_days_overdue(days);
+        let idx = *customer_idx
+            .entry((inv.customer_id, inv.currency_id))
+            .or_insert_with(|| {
+                customers.push(ArAgingCustomer {
+                    customer_id: inv.customer_id,
+                    customer_name: customer_name(inv.customer_id, inv.customer_name.as_deref()),
+                    currency_id: inv.currency_id,
+                    currency: inv.currency.clone(),
+                    buckets: Default::default(),
+                    unapplied_credits: 0.0,
+                    net_outstanding: 0.0,
+                    invoices: vec![],
+                });
+                customers.len() - 1
+            });
+        let customer = &mut customers[idx];
+        customer.buckets.add(bucket, outstanding);
+        customer.invoices.push(ArAgingInvoice {
+            invoice_id: inv.invoice_id,
+            invoice_number: inv.invoice_number.clone(),
+            invoice_date_ms: inv.invoice_date_ms,
+            due_date_ms: inv.due_date_ms,
+            days_overdue: days,
+            total_payable_amount: inv.total_payable_amount,
+            amount_received: inv.amount_received,
+            outstanding_amount: outstanding,
+            bucket,
+        });
+        let unit_key = (
+            inv.company_unit_id,
+            inv.supplier_gstin.clone(),
+            inv.currency_id,
+        );
+        let idx = *unit_idx.entry(unit_key).or_insert_with(|| {
+            unit_totals.push(ArAgingCompanyUnitTotal {
+                company_unit_id: inv.company_unit_id,
+                supplier_gstin: inv.supplier_gstin.clone(),
+                currency_id: inv.currency_id,
+                currency: inv.currency.clone(),
+                buckets: Default::default(),
+            });
+            unit_totals.len() - 1
+        });
+        unit_totals[idx].buckets.add(bucket, outstanding);
+    }
+    for credit in credits {
+        let idx = *customer_idx
+            .entry((Some(credit.customer_id), credit.currency_id))
+            .or_insert_with(|| {
+                customers.push(ArAgingCustomer {
+                    customer_id: Some(credit.customer_id),
+                    customer_name: customer_name(
+                        Some(credit.customer_id),
+                        credit.customer_name.as_deref(),
+                    ),
+                    currency_id: credit.currency_id,
+                    currency: credit.currency.clone(),
+                    buckets: Default::default(),
+                    unapplied_credits: 0.0,
+                    net_outstanding: 0.0,
+                    invoices: vec![],
+                });
+                customers.len() - 1
+            });
+        let customer = &mut customers[idx];
+        customer.unapplied_credits = round_2(customer.unapplied_credits + credit.amount);
+    }
+    let mut currency_totals: Vec<ArAgingCurrencyTotal> = vec![];
+    for customer in customers.iter_mut() {
+        customer.net_outstanding = round_2(customer.buckets.total - customer.unapplied_credits);
+        let total = match currency_totals
+            .iter_mut()
+            .find(|a| a.currency_id == customer.currency_id)
+        {
+            Some(total) => total,
+            None => {
+                currency_totals.push(ArAgingCurrencyTotal {
+                    currency_id: customer.currency_id,
+                    currency: customer.currency.clone(),
+                    buckets: Default::default(),
+                    unapplied_credits: 0.0,
+                    net_outstanding: 0.0,
+                });
+                currency_totals.last_mut().unwrap()
+            }
+        };
+        total.buckets.merge(&customer.buckets);
+        total.unapplied_credits = round_2(total.unapplied_credits + customer.unapplied_credits);
+        total.net_outstanding = round_2(total.buckets.total - total.unapplied_credits);
+    }
+    Ok(ArAgingReport {
+        as_of_date_ms,
+        customers,
+        company_unit_totals: unit_totals,
+        currency_totals,
+    })
+}
+
+fn bucket_record(buckets: &AgingBucketAmounts) -> [String; 6] {
+    [
+        buckets.current.to_string(),
+        buckets.days_1_30.to_string(),
+        buckets.days_31_60.to_string(),
+        buckets.days_61_90.to_string(),
+        buckets.above_90.to_string(),
+        buckets.total.to_string(),
+    ]
+}
+
+fn unit_label(total: &ArAgingCompanyUnitTotal) -> String {
+    total
+        .supplier_gstin
+        .clone()
+        .or_else(|| total.company_unit_id.map(|a| a.to_string()))
+        .unwrap_or_default()
+}
+
+///customer rows followed by company unit and currency totals, distinguished by the row type column
+pub(crate) fn build_ar_aging_csv(report: &ArAgingReport) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record([
+        "Row Type",
+        "Name",
+        "Currency",
+        "Current",
+        "1-30 Days",
+        "31-60 Days",
+        "61-90 Days",
+        "90+ Days",
+        "Total",
+        "Unapplied Credits",
+        "Net Outstanding",
+    ])?;
+    for customer in report.customers.iter() {
+        let mut record = vec![
+            "customer".to_string(),
+            customer.customer_name.clone(),
+            customer.currency.clone(),
+        ];
+        record.extend(bucket_record(&customer.buckets));
+        record.push(customer.unapplied_credits.to_string());
+        record.push(customer.net_outstanding.to_string());
+        writer.write_record(record)?;
+    }
+    for total in report.company_unit_totals.iter() {
+        let mut record = vec![
+            "company_unit".to_string(),
+            unit_label(total),
+            total.currency.clone(),
+        ];
+        record.extend(bucket_record(&total.buckets));
+        record.push(String::new());
+        record.push(String::new());
+        writer.write_record(record)?;
+    }
+    for total in report.currency_totals.iter() {
+        let mut record = vec![
+            "currency_total".to_string(),
+            "total".to_string(),
+            total.currency.clone(),
+        ];
+        record.extend(bucket_record(&total.buckets));
+        record.push(total.unapplied_credits.to_string());
+        record.push(total.net_outstanding.to_string());
+        writer.write_record(record)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .context("error while flushing aging report csv")?;
+    let csv = String::from_utf8(bytes).context("aging report csv is not valid utf8")?;
+    Ok(csv)
+}
+
+fn convert_buckets(buckets: &AgingBucketAmounts) -> AgingBuckets {
+    AgingBuckets {
+        current: buckets.current,
+        days_1_30: buckets.days_1_30,
+        days_31_60: buckets.days_31_60,
+        days_61_90: buckets.days_61_90,
+        above_90: buckets.above_90,
+        total: buckets.total,
+    }
+}
+
+pub(crate) fn convert_to_aging_report_doc(report: &ArAgingReport) -> anyhow::Result<AgingReport> {
+    let as_of_date = DateTime::from_timestamp_millis(report.as_of_date_ms)
+        .ok_or_else(|| anyhow!("invalid as of date {}", report.as_of_date_ms))?
+        .with_timezone(&Kolkata)
+        .format("%d-%b-%Y")
+        .to_string();
+    Ok(AgingReport {
+        title: "accounts receivable aging".to_string(),
+        as_of_date,
+        customers: report
+            .customers
+            .iter()
+            .map(|a| AgingReportCustomerRow {
+                customer_name: a.customer_name.clone(),
+                currency: a.currency.clone(),
+                buckets: convert_buckets(&a.buckets),
+                unapplied_credits: a.unapplied_credits,
+                net_outstanding: a.net_outstanding,
+            })
+            .collect(),
+        company_unit_totals: report
+            .company_unit_totals
+            .iter()
+            .map(|a| AgingReportTotalRow {
+                label: unit_label(a),
+                currency: a.currency.clone(),
+                buckets: convert_buckets(&a.buckets),
+            })
+            .collect(),
+        currency_totals: report
+            .currency_totals
+            .iter()
+            .map(|a| AgingReportTotalRow {
+                label: "total".to_string(),
+                currency: a.currency.clone(),
+                buckets: convert_buckets(&a.buckets),
+            })
+            .collect(),
+    })
+}
+
+#[async_trait]
+impl ArAgingService for ArAgingServiceImpl {
+    async fn get_ar_aging_report(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<ArAgingReport, ArAgingServiceError> {
+        let invoices = self
+            .dao
+            .get_invoices_for_aging(tenant_id, req.as_of_date_ms, req.business_entity_id)
+            .await?;
+        let credits = self
+            .dao
+            .get_unapplied_credits(tenant_id, req.as_of_date_ms, req.business_entity_id)
+            .await?;
+        let report = build_ar_aging_report(req.as_of_date_ms, &invoices, &credits)?;
+        Ok(report)
+    }
+
+    async fn get_ar_aging_csv(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<String, ArAgingServiceError> {
+        let report = self.get_ar_aging_report(req, tenant_id).await?;
+        let csv = build_ar_aging_csv(&report)?;
+        Ok(csv)
+    }
+
+    async fn get_ar_aging_pdf(
+        &self,
+        req: &ArAgingRequest,
+        tenant_id: Uuid,
+    ) -> Result<Vec<u8>, ArAgingServiceError> {
+        let report = self.get_ar_aging_report(req, tenant_id).await?;
+        let doc = convert_to_aging_report_doc(&report)?;
+        let pdf = create_aging_report_pdf(&doc)?;
+        Ok(pdf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::{ResultAssertions, VecAssertions};
+    use uuid::Uuid;
+
+    use crate::invoicing::ar_aging::ar_aging_dao::MockArAgingDao;
+    use crate::invoicing::ar_aging::ar_aging_models::tests::{
+        an_ar_aging_invoice_db, an_unapplied_credit_db, AS_OF_DATE_MS,
+    };
+    use crate::invoicing::ar_aging::ar_aging_models::{AgingBucket, ArAgingRequestBuilder};
+    use crate::invoicing::ar_aging::ar_aging_service::{
+        build_ar_aging_csv, build_ar_aging_report, convert_to_aging_report_doc, ArAgingService,
+        ArAgingServiceImpl,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[test]
+    fn test_build_ar_aging_report() {
+        let current = an_ar_aging_invoice_db(0);
+        let mut partially_paid = an_ar_aging_invoice_db(45);
+        partially_paid.invoice_id = Uuid::now_v7();
+        partially_paid.amount_received = 589.5;
+        let mut paid = an_ar_aging_invoice_db(100);
+        paid.invoice_id = Uuid::now_v7();
+        paid.amount_received = paid.total_payable_amount;
+        let mut b2c = an_ar_aging_invoice_db(95);
+        b2c.invoice_id = Uuid::now_v7();
+        b2c.customer_id = None;
+        b2c.customer_name = None;
+        let report = build_ar_aging_report(
+            AS_OF_DATE_MS,
+            &[current, partially_paid, paid, b2c],
+            &[an_unapplied_credit_db()],
+        )
+        .unwrap();
+        assert_that!(report.customers).has_length(2);
+        let customer = &report.customers[0];
+        assert_that!(customer.invoices).has_length(2);
+        assert_eq!(customer.buckets.current, 1180.0);
+        assert_eq!(customer.buckets.days_31_60, 590.5);
+        assert_eq!(customer.buckets.total, 1770.5);
+        assert_eq!(customer.unapplied_credits, 100.0);
+        assert_eq!(customer.net_outstanding, 1670.5);
+        assert_eq!(customer.invoices[1].bucket, AgingBucket::Days31To60);
+        assert_eq!(report.customers[1].buckets.above_90, 1180.0);
+        assert_that!(report.company_unit_totals).has_length(1);
+        assert_eq!(report.company_unit_totals[0].buckets.total, 2950.5);
+        assert_that!(report.currency_totals).has_length(1);
+        assert_eq!(report.currency_totals[0].net_outstanding, 2850.5);
+        let csv = build_ar_aging_csv(&report).unwrap();
+        assert_eq!(csv.lines().count(), 5);
+        let doc = convert_to_aging_report_doc(&report).unwrap();
+        assert_eq!(doc.as_of_date, "31-Jan-2024");
+    }
+
+    #[tokio::test]
+    async fn test_get_ar_aging_report() {
+        let mut dao = MockArAgingDao::new();
+        dao.expect_get_invoices_for_aging()
+            .returning(|_, _, _| Ok(vec![an_ar_aging_invoice_db(10)]));
+        dao.expect_get_unapplied_credits()
+            .returning(|_, _, _| Ok(vec![]));
+        let service = ArAgingServiceImpl { dao: Arc::new(dao) };
+        let mut builder = ArAgingRequestBuilder::default();
+        builder
+            .as_of_date_ms(AS_OF_DATE_MS)
+            .business_entity_id(None);
+        let req = builder.build().unwrap();
+        let report = service.get_ar_aging_report(&req, *SEED_TENANT_ID).await;
+        assert_that!(report).is_ok();
+    }
+}
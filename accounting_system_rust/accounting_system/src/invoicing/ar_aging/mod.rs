@@ -0,0 +1,4 @@
+mod ar_aging_dao;
+pub mod ar_aging_http_api;
+pub mod ar_aging_models;
+pub mod ar_aging_service;
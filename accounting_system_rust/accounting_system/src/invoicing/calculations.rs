@@ -1,11 +1,14 @@
 use std::ops::Add;
 
-use anyhow::{ensure, Context};
+use anyhow::{anyhow, ensure, Context};
+use cess_models::CessStrategy;
 use itertools::Itertools;
 
+use invoicing_calculations::additional_charge::{compute_tax_amount, AdditionalCharge};
 use invoicing_calculations::invoice_line::InvoiceLine;
 
 use crate::invoicing::invoicing_request_models::{
+    AdditionalChargeTaxability, CreateAdditionalChargeRequest,
     CreateInvoiceLineRequestWithAllDetails, CreateInvoiceWithAllDetailsIncluded,
 };
 
@@ -17,17 +20,33 @@ impl TryFrom<&CreateInvoiceLineRequestWithAllDetails> for InvoiceLine {
             value.quantity.get_quantity(),
             value.unit_price.inner(),
             value.discount_percentage.inner(),
-            value
-                .product_item_id
-                .get_tax_rate()?
-                .tax_rate_percentage
-                .inner(),
-            value.product_item_id.get_cess_rate()?.cess_strategy.clone(),
+            value.tax_percentage()?,
+            value.cess_strategy()?,
         )
         .context("error while calculating line taxable amount")?)
     }
 }
 impl CreateInvoiceLineRequestWithAllDetails {
+    pub fn tax_percentage(&self) -> anyhow::Result<f32> {
+        if self.no_tax_charged {
+            return Ok(0.0);
+        }
+        Ok(self
+            .product_item_id
+            .get_tax_rate()?
+            .tax_rate_percentage
+            .inner())
+    }
+
+    pub fn cess_strategy(&self) -> anyhow::Result<CessStrategy> {
+        if self.no_tax_charged {
+            return Ok(CessStrategy::PercentageOfAssessableValue {
+                cess_rate_percentage: 0.0,
+            });
+        }
+        Ok(self.product_item_id.get_cess_rate()?.cess_strategy.clone())
+    }
+
     pub fn taxable_amount(&self) -> anyhow::Result<f64> {
         let line: InvoiceLine = self.try_into()?;
         Ok(line.compute_taxable_amount())
@@ -61,11 +80,57 @@ impl CreateInvoiceWithAllDetailsIncluded {
             .fold_ok(0.0, Add::add)
     }
 
+    ///tax on the invoice lines and the additional charges
     pub fn total_tax_amount(&self) -> anyhow::Result<f64> {
-        self.invoice_lines
+        let lines_tax = self
+            .invoice_lines
             .iter()
             .map(|line| line.tax_amount())
-            .fold_ok(0.0, Add::add)
+            .fold_ok(0.0, Add::add)?;
+        let charges_tax = self
+            .additional_charges
+            .iter()
+            .map(|ch| self.additional_charge_tax_amount(ch))
+            .fold_ok(0.0, Add::add)?;
+        Ok(lines_tax + charges_tax)
+    }
+
+    ///principal supply is the line with the highest taxable amount
+    fn principal_supply_tax_percentage(&self) -> anyhow::Result<f32> {
+        let mut principal: Option<(f64, f32)> = None;
+        for line in self.invoice_lines.iter() {
+            let taxable_amount = line.taxable_amount()?;
+            if principal.is_none_or(|(amount, _)| taxable_amount > amount) {
+                principal = Some((taxable_amount, line.tax_percentage()?));
+            }
+        }
+        Ok(principal.map(|(_, rate)| rate).unwrap_or(0.0))
+    }
+
+    pub fn additional_charge_tax_percentage(
+        &self,
+        charge: &CreateAdditionalChargeRequest,
+    ) -> anyhow::Result<f32> {
+        if self.invoice_lines.iter().any(|a| a.no_tax_charged) {
+            return Ok(0.0);
+        }
+        match &charge.taxability {
+            AdditionalChargeTaxability::PrincipalSupply => self.principal_supply_tax_percentage(),
+            AdditionalChargeTaxability::DeclaredRate { tax_percentage } => {
+                Ok(tax_percentage.inner())
+            }
+            AdditionalChargeTaxability::NonTaxable => Ok(0.0),
+        }
+    }
+
+    pub fn additional_charge_tax_amount(
+        &self,
+        charge: &CreateAdditionalChargeRequest,
+    ) -> anyhow::Result<f64> {
+        let tax_percentage = self.additional_charge_tax_percentage(charge)?;
+        let charge = AdditionalCharge::new(charge.rate.inner(), tax_percentage as f64)
+            .map_err(|e| anyhow!(e.iter().join(",")))?;
+        Ok(compute_tax_amount(&charge))
     }
 
     pub fn total_additional_charge_amount(&self) -> f64 {
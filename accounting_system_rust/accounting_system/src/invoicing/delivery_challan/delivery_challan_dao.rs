@@ -0,0 +1,375 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::delivery_challan::delivery_challan_models::{
+    CreateDeliveryChallanDbResponse, DeliveryChallanDb, DeliveryChallanDetail,
+    DeliveryChallanReason,
+};
+
+const CREATE_DELIVERY_CHALLAN: &str = "select create_delivery_challan($1)";
+
+const DELIVERY_CHALLAN_QUERY: &str = "select id,reason::text,challan_number,challan_date_ms,\
+total_value,linked_invoice_id,challan_request from delivery_challan where id=$1 and tenant_id=$2";
+
+const CLAIM_DELIVERY_CHALLAN: &str = "update delivery_challan set link_claim_key=$3 \
+where tenant_id=$1 and id=$2 and linked_invoice_id is null \
+and (link_claim_key is null or link_claim_key=$3)";
+
+const RELEASE_DELIVERY_CHALLAN_CLAIM: &str = "update delivery_challan set link_claim_key=null \
+where tenant_id=$1 and id=$2 and linked_invoice_id is null and link_claim_key=$3";
+
+const LINK_DELIVERY_CHALLAN: &str = "select link_delivery_challan_to_invoice($1,$2,$3,$4)";
+
+const IS_INVOICE_OF_TENANT: &str =
+    "select exists(select 1 from invoice where tenant_id=$1 and id=$2)";
+
+const IS_DELIVERY_CHALLAN_PDF_CREATED: &str = "select exists(select 1 from delivery_challan \
+where tenant_id=$1 and id=$2 and challan_pdf_s3_id is not null)";
+
+const PERSIST_DELIVERY_CHALLAN_PDF: &str =
+    "update delivery_challan set challan_pdf_s3_id=$3 where tenant_id=$1 and id=$2";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait DeliveryChallanDao: Send + Sync {
+    ///takes the number from the challan series and stores the challan
+    async fn create_delivery_challan(
+        &self,
+        challan_db: &DeliveryChallanDb,
+    ) -> Result<CreateDeliveryChallanDbResponse, DaoError>;
+    async fn get_delivery_challan_by_id(
+        &self,
+        delivery_challan_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<DeliveryChallanDetail>, DaoError>;
+    ///reserves the challan for the conversion or link with this key before it is linked, returns false if it is
+    /// linked or another conversion holds it
+    async fn claim_delivery_challan(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        claim_key: Uuid,
+    ) -> Result<bool, DaoError>;
+    ///frees the claim of a conversion or link which failed before the invoice was linked
+    async fn release_delivery_challan_claim(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        claim_key: Uuid,
+    ) -> Result<(), DaoError>;
+    ///links the invoice to the challan, returns false if it is linked to another invoice
+    async fn link_delivery_challan_to_invoice(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        invoice_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn is_invoice_of_tenant(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn is_delivery_challan_pdf_created(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn persist_delivery_challan_pdf_dtl(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        pdf_key: &str,
+    ) -> Result<(), DaoError>;
+}
+
+struct DeliveryChallanDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_delivery_challan_dao(arc: Arc<Pool>) -> Arc<dyn DeliveryChallanDao> {
+    let dao = DeliveryChallanDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for DeliveryChallanDetail {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let reason: &str = row.get(1);
+        let request: serde_json::Value = row.get(6);
+        Ok(DeliveryChallanDetail {
+            id: row.get(0),
+            reason: DeliveryChallanReason::from_db_str(reason)?,
+            challan_number: row.get(2),
+            challan_date_ms: row.get(3),
+            total_value: row.get(4),
+            linked_invoice_id: row.get(5),
+            request: serde_json::from_value(request)
+                .context("could not deserialize stored delivery challan request")?,
+        })
+    }
+}
+
+#[async_trait]
+impl DeliveryChallanDao for DeliveryChallanDaoImpl {
+    async fn create_delivery_challan(
+        &self,
+        challan_db: &DeliveryChallanDb,
+    ) -> Result<CreateDeliveryChallanDbResponse, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_DELIVERY_CHALLAN, &[challan_db])
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let resp =
+            serde_json::from_value(json).context("invalid create delivery challan response")?;
+        Ok(resp)
+    }
+
+    async fn get_delivery_challan_by_id(
+        &self,
+        delivery_challan_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<DeliveryChallanDetail>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query_opt(DELIVERY_CHALLAN_QUERY, &[&delivery_challan_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()
+    }
+
+    async fn claim_delivery_challan(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        claim_key: Uuid,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(
+                CLAIM_DELIVERY_CHALLAN,
+                &[&tenant_id, &delivery_challan_id, &claim_key],
+            )
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn release_delivery_challan_claim(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        claim_key: Uuid,
+    ) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(
+                RELEASE_DELIVERY_CHALLAN_CLAIM,
+                &[&tenant_id, &delivery_challan_id, &claim_key],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn link_delivery_challan_to_invoice(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        invoice_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                LINK_DELIVERY_CHALLAN,
+                &[&tenant_id, &delivery_challan_id, &invoice_id, &user_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn is_invoice_of_tenant(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(IS_INVOICE_OF_TENANT, &[&tenant_id, &invoice_id])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn is_delivery_challan_pdf_created(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                IS_DELIVERY_CHALLAN_PDF_CREATED,
+                &[&tenant_id, &delivery_challan_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn persist_delivery_challan_pdf_dtl(
+        &self,
+        tenant_id: Uuid,
+        delivery_challan_id: Uuid,
+        pdf_key: &str,
+    ) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(
+                PERSIST_DELIVERY_CHALLAN_PDF,
+                &[&tenant_id, &delivery_challan_id, &pdf_key],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::boolean::BooleanAssertions;
+    use speculoos::option::OptionAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::delivery_challan::delivery_challan_dao::{
+        DeliveryChallanDao, DeliveryChallanDaoImpl,
+    };
+    use crate::invoicing::delivery_challan::delivery_challan_models::tests::a_create_delivery_challan_request;
+    use crate::invoicing::delivery_challan::delivery_challan_models::{
+        DeliveryChallanDb, DeliveryChallanReason,
+    };
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_delivery_challan_db(idempotence_key: Uuid) -> DeliveryChallanDb {
+        let req = a_create_delivery_challan_request(Default::default());
+        DeliveryChallanDb {
+            idempotence_key,
+            tenant_id: *SEED_TENANT_ID,
+            reason: DeliveryChallanReason::SupplyOnApproval,
+            challan_series_mst_id: req.challan_series_mst_id,
+            financial_year: 2024,
+            challan_date_ms: 0,
+            currency_id: req.currency_id,
+            supplier_id: req.supplier_id,
+            consignee_id: req
+                .bill_ship_detail
+                .as_ref()
+                .map(|a| a.billed_to_customer_id),
+            total_value: 10.0,
+            challan_request: serde_json::to_value(&req).unwrap(),
+            created_by: *SEED_USER_ID,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_delivery_challan_is_idempotent() {
+        let dao = get_dao_generic(|c| DeliveryChallanDaoImpl { postgres_client: c }, None).await;
+        let db = a_delivery_challan_db(Uuid::now_v7());
+        let first = dao.create_delivery_challan(&db).await.unwrap();
+        let second = dao.create_delivery_challan(&db).await.unwrap();
+        assert_that!(second).is_equal_to(&first);
+        let fetched = dao
+            .get_delivery_challan_by_id(first.delivery_challan_id, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(fetched.map(|a| a.challan_number))
+            .is_some()
+            .is_equal_to(first.challan_number);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_challan_is_linked_only_once() {
+        let dao = get_dao_generic(|c| DeliveryChallanDaoImpl { postgres_client: c }, None).await;
+        let created = dao
+            .create_delivery_challan(&a_delivery_challan_db(Uuid::now_v7()))
+            .await
+            .unwrap();
+        let is_invoice = dao
+            .is_invoice_of_tenant(*SEED_TENANT_ID, *SEED_INVOICE_ID)
+            .await
+            .unwrap();
+        assert_that!(is_invoice).is_true();
+        let linked = dao
+            .link_delivery_challan_to_invoice(
+                *SEED_TENANT_ID,
+                created.delivery_challan_id,
+                *SEED_INVOICE_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(linked).is_true();
+        let relinked = dao
+            .link_delivery_challan_to_invoice(
+                *SEED_TENANT_ID,
+                created.delivery_challan_id,
+                Uuid::now_v7(),
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(relinked).is_false();
+    }
+
+    #[tokio::test]
+    async fn test_delivery_challan_is_claimed_by_one_conversion_at_a_time() {
+        let dao = get_dao_generic(|c| DeliveryChallanDaoImpl { postgres_client: c }, None).await;
+        let created = dao
+            .create_delivery_challan(&a_delivery_challan_db(Uuid::now_v7()))
+            .await
+            .unwrap();
+        let challan_id = created.delivery_challan_id;
+        let (first, second) = (Uuid::now_v7(), Uuid::now_v7());
+        let claim = |key: Uuid| dao.claim_delivery_challan(*SEED_TENANT_ID, challan_id, key);
+        assert_that!(claim(first).await.unwrap()).is_true();
+        assert_that!(claim(second).await.unwrap()).is_false();
+        dao.release_delivery_challan_claim(*SEED_TENANT_ID, challan_id, first)
+            .await
+            .unwrap();
+        assert_that!(claim(second).await.unwrap()).is_true();
+        dao.link_delivery_challan_to_invoice(
+            *SEED_TENANT_ID,
+            challan_id,
+            *SEED_INVOICE_ID,
+            *SEED_USER_ID,
+        )
+        .await
+        .unwrap();
+        assert_that!(claim(second).await.unwrap()).is_false();
+    }
+}
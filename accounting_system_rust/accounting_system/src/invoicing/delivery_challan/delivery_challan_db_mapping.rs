@@ -0,0 +1,53 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct DeliveryChallanDbMapping {}
+
+const DELIVERY_CHALLAN_DDL_SQL: &str =
+    include_str!("./delivery_challan_sql/delivery_challan_ddl.sql");
+const DELIVERY_CHALLAN_SEED_DATA: &str =
+    include_str!("./delivery_challan_sql/delivery_challan.csv");
+const DELIVERY_CHALLAN_INDEXES_SQL: &str =
+    include_str!("./delivery_challan_sql/delivery_challan_indexes.sql");
+const DELIVERY_CHALLAN_FUNCTIONS_SQL: &str =
+    include_str!("./delivery_challan_sql/delivery_challan_functions_and_procedures.sql");
+impl DbStructMapping for DeliveryChallanDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("delivery_challan")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        DELIVERY_CHALLAN_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        DELIVERY_CHALLAN_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        DELIVERY_CHALLAN_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        DELIVERY_CHALLAN_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
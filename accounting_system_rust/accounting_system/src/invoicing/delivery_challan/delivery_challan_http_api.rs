@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::delivery_challan::delivery_challan_models::{
+    ConvertDeliveryChallanRequest, CreateDeliveryChallanRequest, DeliveryChallanPdfRequest,
+    LinkDeliveryChallanRequest,
+};
+use crate::invoicing::delivery_challan::delivery_challan_service::{
+    DeliveryChallanService, DeliveryChallanServiceError,
+};
+use crate::setup_routes;
+
+impl ResponseError for DeliveryChallanServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeliveryChallanServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            DeliveryChallanServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            DeliveryChallanServiceError::DeliveryChallanNotFound(_) => StatusCode::NOT_FOUND,
+            DeliveryChallanServiceError::AlreadyLinked(_) => StatusCode::CONFLICT,
+            DeliveryChallanServiceError::InvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            DeliveryChallanServiceError::Invoicing(e) => e.status_code(),
+            DeliveryChallanServiceError::Approval(e) => e.status_code(),
+            DeliveryChallanServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn create_delivery_challan(
+    data: Data<Arc<dyn DeliveryChallanService>>,
+    request: web::Json<CreateDeliveryChallanRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_delivery_challan(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn create_delivery_challan_pdf(
+    data: Data<Arc<dyn DeliveryChallanService>>,
+    request: web::Json<DeliveryChallanPdfRequest>,
+    _tenant_id: TenantId,
+    _user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_delivery_challan_pdf(request.into_inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_delivery_challan(
+    data: Data<Arc<dyn DeliveryChallanService>>,
+    delivery_challan_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_delivery_challan(delivery_challan_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn convert_delivery_challan(
+    data: Data<Arc<dyn DeliveryChallanService>>,
+    delivery_challan_id: Path<Uuid>,
+    request: web::Json<ConvertDeliveryChallanRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .convert_delivery_challan(
+            delivery_challan_id.into_inner(),
+            request.into_inner(),
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn link_invoice(
+    data: Data<Arc<dyn DeliveryChallanService>>,
+    delivery_challan_id: Path<Uuid>,
+    request: web::Json<LinkDeliveryChallanRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.link_invoice(
+        delivery_challan_id.into_inner(),
+        request.into_inner(),
+        tenant_id.inner(),
+        user_id.inner(),
+    )
+    .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+setup_routes!(
+    DeliveryChallanService,
+    "/delivery-challan",
+    "/create",
+    web::post().to(create_delivery_challan),
+    "/create-pdf",
+    web::post().to(create_delivery_challan_pdf),
+    "/id/{delivery_challan_id}",
+    web::get().to(get_delivery_challan),
+    "/id/{delivery_challan_id}/convert",
+    web::post().to(convert_delivery_challan),
+    "/id/{delivery_challan_id}/link-invoice",
+    web::post().to(link_invoice)
+);
@@ -0,0 +1,269 @@
+use anyhow::bail;
+use derive_builder::Builder;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use pdf_doc_generator::delivery_challan_template::DeliveryChallan;
+
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoicing_request_models::{
+    BillShipDetail, CreateInvoiceLineRequest, CreateInvoiceRequest, InvoiceLedgerAccounts,
+    InvoicePdfRequest, InvoiceRemarks, PaymentTermsValidated, PurchaseOrderDate, PurchaseOrderNo,
+};
+
+///goods moved without a supply, or before the quantity supplied is known, go under a challan
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "delivery_challan_reason", rename_all = "snake_case")]
+pub enum DeliveryChallanReason {
+    JobWork,
+    SupplyOnApproval,
+    BranchTransfer,
+    ///liquid gas and other goods where the quantity is known only at delivery
+    QuantityNotKnown,
+    Exhibition,
+    Others,
+}
+
+impl DeliveryChallanReason {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let reason = match value {
+            "job_work" => DeliveryChallanReason::JobWork,
+            "supply_on_approval" => DeliveryChallanReason::SupplyOnApproval,
+            "branch_transfer" => DeliveryChallanReason::BranchTransfer,
+            "quantity_not_known" => DeliveryChallanReason::QuantityNotKnown,
+            "exhibition" => DeliveryChallanReason::Exhibition,
+            "others" => DeliveryChallanReason::Others,
+            _ => bail!("{} is not a valid delivery challan reason", value),
+        };
+        Ok(reason)
+    }
+
+    ///printed on the document
+    pub fn description(&self) -> &'static str {
+        match self {
+            DeliveryChallanReason::JobWork => "goods sent for job work",
+            DeliveryChallanReason::SupplyOnApproval => "goods sent on approval",
+            DeliveryChallanReason::BranchTransfer => "transfer between branches",
+            DeliveryChallanReason::QuantityNotKnown => "supply where quantity is not known",
+            DeliveryChallanReason::Exhibition => "goods sent for exhibition",
+            DeliveryChallanReason::Others => "other than by way of supply",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreateDeliveryChallanRequest {
+    pub idempotence_key: Uuid,
+    pub reason: DeliveryChallanReason,
+    ///numbers are taken from this series, it has to be different from the tax invoice series
+    pub challan_series_mst_id: Uuid,
+    ///template of the tax invoice created on conversion
+    pub invoice_template_id: Uuid,
+    pub currency_id: Uuid,
+    pub b2b_invoice: bool,
+    ///consignor
+    pub supplier_id: Uuid,
+    ///if  none then same as that of supplier id
+    pub dispatch_from_id: Option<Uuid>,
+    ///consignee, a branch of the supplier for branch transfers
+    pub bill_ship_detail: Option<BillShipDetail>,
+    pub invoice_lines: Vec<CreateInvoiceLineRequest>,
+    pub remarks: Option<InvoiceRemarks>,
+}
+
+impl CreateDeliveryChallanRequest {
+    ///invoice request carrying the challan lines, also used to compute the value of the challan itself
+    pub fn into_create_invoice_request(
+        self,
+        idempotence_key: Uuid,
+        invoicing_series_mst_id: Uuid,
+        invoice_details: InvoiceDetailsOnConversion,
+    ) -> CreateInvoiceRequest {
+        CreateInvoiceRequest {
+            idempotence_key,
+            invoice_template_id: self.invoice_template_id,
+            invoicing_series_mst_id,
+            currency_id: self.currency_id,
+            service_invoice: false,
+            einvoicing_applicable: invoice_details.einvoicing_applicable,
+            b2b_invoice: self.b2b_invoice,
+            supplier_id: self.supplier_id,
+            dispatch_from_id: self.dispatch_from_id,
+            bill_ship_detail: self.bill_ship_detail,
+            order_number: invoice_details.order_number,
+            order_date: invoice_details.order_date,
+            payment_terms: invoice_details.payment_terms,
+            invoice_lines: self.invoice_lines,
+            additional_charges: vec![],
+            invoice_remarks: self.remarks,
+            ecommerce_gstin: None,
+            export_detail: None,
+            service_category: None,
+            sez_lut_reference: None,
+            deemed_export: false,
+            email_to_customer: false,
+            ledger_accounts: invoice_details.ledger_accounts,
+        }
+    }
+}
+
+///details of the tax invoice which are not known when the goods are moved
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct InvoiceDetailsOnConversion {
+    pub einvoicing_applicable: bool,
+    pub order_number: Option<PurchaseOrderNo>,
+    pub order_date: Option<PurchaseOrderDate>,
+    pub payment_terms: Option<PaymentTermsValidated>,
+    #[serde(default)]
+    pub ledger_accounts: Option<InvoiceLedgerAccounts>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder)]
+pub struct ConvertDeliveryChallanRequest {
+    pub idempotence_key: Uuid,
+    pub invoicing_series_mst_id: Uuid,
+    #[serde(flatten)]
+    pub invoice_details: InvoiceDetailsOnConversion,
+}
+
+///links a tax invoice raised separately, e.g. for the goods kept out of those sent on approval
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkDeliveryChallanRequest {
+    pub invoice_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvertDeliveryChallanResponse {
+    pub invoice_id: Uuid,
+    ///draft when invoices of the tenant need approval, issued otherwise
+    pub invoice_status: InvoiceStatus,
+    ///present when the invoice was issued
+    pub invoice_pdf_request: Option<InvoicePdfRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryChallanPdfRequest {
+    pub tenant_id: Uuid,
+    pub delivery_challan_id: Uuid,
+    pub challan: DeliveryChallan,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CreateDeliveryChallanDbResponse {
+    pub delivery_challan_id: Uuid,
+    pub challan_number: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryChallanDetail {
+    pub id: Uuid,
+    pub reason: DeliveryChallanReason,
+    pub challan_number: String,
+    pub challan_date_ms: i64,
+    ///value of the goods excluding tax
+    pub total_value: f64,
+    ///tax invoice created from or linked to this challan
+    pub linked_invoice_id: Option<Uuid>,
+    pub request: CreateDeliveryChallanRequest,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_delivery_challan_request")]
+pub(crate) struct DeliveryChallanDb {
+    pub idempotence_key: Uuid,
+    pub tenant_id: Uuid,
+    pub reason: DeliveryChallanReason,
+    pub challan_series_mst_id: Uuid,
+    pub financial_year: i16,
+    pub challan_date_ms: i64,
+    pub currency_id: Uuid,
+    pub supplier_id: Uuid,
+    pub consignee_id: Option<Uuid>,
+    pub total_value: f64,
+    pub challan_request: serde_json::Value,
+    pub created_by: Uuid,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::invoicing::delivery_challan::delivery_challan_models::{
+        CreateDeliveryChallanRequest, CreateDeliveryChallanRequestBuilder, DeliveryChallanReason,
+    };
+    use crate::invoicing::invoice_template::invoice_template_models::tests::SEED_INVOICE_TEMPLATE_ID;
+    use crate::invoicing::invoicing_request_models::tests::{
+        a_bill_ship_detail, a_create_invoice_line_request,
+    };
+    use crate::invoicing::invoicing_series::invoicing_series_models::tests::SEED_INVOICING_SERIES_MST_ID;
+    use crate::masters::business_entity_master::business_entity_models::tests::SEED_BUSINESS_ENTITY_ID1;
+
+    pub fn a_create_delivery_challan_request(
+        builder: CreateDeliveryChallanRequestBuilder,
+    ) -> CreateDeliveryChallanRequest {
+        CreateDeliveryChallanRequest {
+            idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
+            reason: builder.reason.unwrap_or(DeliveryChallanReason::JobWork),
+            challan_series_mst_id: builder
+                .challan_series_mst_id
+                .unwrap_or(*SEED_INVOICING_SERIES_MST_ID),
+            invoice_template_id: builder
+                .invoice_template_id
+                .unwrap_or(*SEED_INVOICE_TEMPLATE_ID),
+            currency_id: builder.currency_id.unwrap_or(*SEED_CURRENCY_ID),
+            b2b_invoice: builder.b2b_invoice.unwrap_or(true),
+            supplier_id: builder.supplier_id.unwrap_or(*SEED_BUSINESS_ENTITY_ID1),
+            dispatch_from_id: builder.dispatch_from_id.flatten(),
+            bill_ship_detail: builder
+                .bill_ship_detail
+                .unwrap_or_else(|| Some(a_bill_ship_detail(Default::default()))),
+            invoice_lines: builder
+                .invoice_lines
+                .unwrap_or_else(|| vec![a_create_invoice_line_request(Default::default())]),
+            remarks: builder.remarks.flatten(),
+        }
+    }
+
+    #[test]
+    fn test_challan_request_round_trip() {
+        //challans are stored as json and deserialized again on conversion
+        let req = a_create_delivery_challan_request(Default::default());
+        let json = serde_json::to_value(&req).unwrap();
+        let parsed: CreateDeliveryChallanRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_that!(serde_json::to_value(&parsed).unwrap()).is_equal_to(json);
+    }
+
+    #[test]
+    fn test_converted_invoice_has_no_additional_charges() {
+        let req = a_create_delivery_challan_request(Default::default());
+        let invoice_req = req.into_create_invoice_request(
+            Uuid::now_v7(),
+            *SEED_INVOICING_SERIES_MST_ID,
+            Default::default(),
+        );
+        assert_that!(invoice_req.additional_charges).is_empty();
+        assert_that!(invoice_req.invoice_lines).has_length(1);
+        assert!(!invoice_req.service_invoice);
+    }
+
+    #[test]
+    fn test_reason_serde_matches_db_names() {
+        for reason in [
+            DeliveryChallanReason::JobWork,
+            DeliveryChallanReason::SupplyOnApproval,
+            DeliveryChallanReason::BranchTransfer,
+            DeliveryChallanReason::QuantityNotKnown,
+            DeliveryChallanReason::Exhibition,
+            DeliveryChallanReason::Others,
+        ] {
+            let json = serde_json::to_value(reason).unwrap();
+            let parsed = DeliveryChallanReason::from_db_str(json.as_str().unwrap()).unwrap();
+            assert_that!(parsed).is_equal_to(reason);
+        }
+    }
+}
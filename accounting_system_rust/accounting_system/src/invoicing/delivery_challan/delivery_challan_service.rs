@@ -0,0 +1,652 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use pdf_doc_generator::delivery_challan_template::{create_delivery_challan_pdf, DeliveryChallan};
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::delivery_challan::delivery_challan_dao::{
+    get_delivery_challan_dao, DeliveryChallanDao,
+};
+use crate::invoicing::delivery_challan::delivery_challan_models::{
+    ConvertDeliveryChallanRequest, ConvertDeliveryChallanResponse, CreateDeliveryChallanRequest,
+    DeliveryChallanDb, DeliveryChallanDetail, DeliveryChallanPdfRequest,
+    LinkDeliveryChallanRequest,
+};
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoice_approval::invoice_approval_service::{
+    InvoiceApprovalService, InvoiceApprovalServiceError,
+};
+use crate::invoicing::invoicing_request_models::CreateInvoiceRequest;
+use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
+use crate::invoicing::invoicing_series::invoicing_series_service::InvoicingSeriesService;
+use crate::invoicing::invoicing_service::{InvoicingService, InvoicingServiceError};
+use crate::storage::storage_service::{StorageService, FINANCIAL_DOCS_BUCKET_NAME};
+
+#[derive(Debug, Error)]
+pub enum DeliveryChallanServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("delivery challan {0} not found")]
+    DeliveryChallanNotFound(Uuid),
+    #[error("delivery challan {0} is already linked to an invoice")]
+    AlreadyLinked(Uuid),
+    #[error("invoice {0} not found")]
+    InvoiceNotFound(Uuid),
+    #[error("{0}")]
+    Invoicing(#[from] InvoicingServiceError),
+    #[error("{0}")]
+    Approval(#[from] InvoiceApprovalServiceError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait DeliveryChallanService: Send + Sync {
+    ///lines are validated like those of a tax invoice, the number comes from the challan series
+    async fn create_delivery_challan(
+        &self,
+        req: CreateDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<DeliveryChallanPdfRequest, DeliveryChallanServiceError>;
+    async fn create_delivery_challan_pdf(
+        &self,
+        pdf_data: DeliveryChallanPdfRequest,
+    ) -> Result<String, DeliveryChallanServiceError>;
+    async fn get_delivery_challan(
+        &self,
+        delivery_challan_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<DeliveryChallanDetail, DeliveryChallanServiceError>;
+    ///creates the tax invoice from the challan lines, as a draft if invoices of the tenant need approval. the
+    /// challan is claimed before the invoice is created so only one conversion or link of it can run
+    async fn convert_delivery_challan(
+        &self,
+        delivery_challan_id: Uuid,
+        req: ConvertDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertDeliveryChallanResponse, DeliveryChallanServiceError>;
+    async fn link_invoice(
+        &self,
+        delivery_challan_id: Uuid,
+        req: LinkDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), DeliveryChallanServiceError>;
+}
+
+struct DeliveryChallanServiceImpl {
+    dao: Arc<dyn DeliveryChallanDao>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    storage_service: Arc<dyn StorageService>,
+}
+
+pub fn get_delivery_challan_service(
+    arc: Arc<Pool>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    storage_service: Arc<dyn StorageService>,
+) -> Arc<dyn DeliveryChallanService> {
+    let dao = get_delivery_challan_dao(arc);
+    let service = DeliveryChallanServiceImpl {
+        dao,
+        invoicing_service,
+        invoicing_series_service,
+        invoice_approval_service,
+        storage_service,
+    };
+    Arc::new(service)
+}
+
+impl DeliveryChallanServiceImpl {
+    async fn create_converted_invoice(
+        &self,
+        invoice_req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertDeliveryChallanResponse, DeliveryChallanServiceError> {
+        let response = if self
+            .invoice_approval_service
+            .is_approval_required(tenant_id)
+            .await?
+        {
+            let draft = self
+                .invoicing_service
+                .create_draft_invoice(invoice_req, tenant_id, user_id)
+                .await?;
+            ConvertDeliveryChallanResponse {
+                invoice_id: draft.invoice_id,
+                invoice_status: InvoiceStatus::Draft,
+                invoice_pdf_request: None,
+            }
+        } else {
+            let pdf_request = self
+                .invoicing_service
+                .create_invoice(invoice_req, tenant_id, user_id)
+                .await?;
+            ConvertDeliveryChallanResponse {
+                invoice_id: pdf_request.invoice_id,
+                invoice_status: InvoiceStatus::Issued,
+                invoice_pdf_request: Some(pdf_request),
+            }
+        };
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl DeliveryChallanService for DeliveryChallanServiceImpl {
+    async fn create_delivery_challan(
+        &self,
+        req: CreateDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<DeliveryChallanPdfRequest, DeliveryChallanServiceError> {
+        let valid_series = self
+            .invoicing_series_service
+            .is_valid_invoicing_series_id(
+                req.challan_series_mst_id,
+                InvoicingSeriesType::DeliveryChallan,
+                tenant_id,
+            )
+            .await
+            .context("error during delivery challan series validation")?;
+        if !valid_series {
+            return Err(DeliveryChallanServiceError::Validation(vec![
+                "delivery challan series id is not a delivery challan series of this tenant"
+                    .to_string(),
+            ]));
+        }
+        let challan_request =
+            serde_json::to_value(&req).context("could not serialize delivery challan request")?;
+        let reason = req.reason;
+        let consignee_id = req
+            .bill_ship_detail
+            .as_ref()
+            .map(|a| a.billed_to_customer_id);
+        let (idempotence_key, series_id, currency_id, supplier_id) = (
+            req.idempotence_key,
+            req.challan_series_mst_id,
+            req.currency_id,
+            req.supplier_id,
+        );
+        let invoice_req =
+            req.into_create_invoice_request(idempotence_key, series_id, Default::default());
+        let computed = self
+            .invoicing_service
+            .compute_invoice_document(invoice_req, tenant_id, user_id)
+            .await?;
+        let db = DeliveryChallanDb {
+            idempotence_key,
+            tenant_id,
+            reason,
+            challan_series_mst_id: series_id,
+            financial_year: computed.financial_year,
+            challan_date_ms: computed.document_date_ms,
+            currency_id,
+            supplier_id,
+            consignee_id,
+            //no tax is payable on a challan
+            total_value: computed.total_taxable_amount,
+            challan_request,
+            created_by: user_id,
+        };
+        let created = self.dao.create_delivery_challan(&db).await?;
+        let mut document = computed.document;
+        document.invoice_number = created.challan_number;
+        Ok(DeliveryChallanPdfRequest {
+            tenant_id,
+            delivery_challan_id: created.delivery_challan_id,
+            challan: DeliveryChallan::from_invoice_document(
+                reason.description().to_string(),
+                document,
+            ),
+        })
+    }
+
+    async fn create_delivery_challan_pdf(
+        &self,
+        pdf_data: DeliveryChallanPdfRequest,
+    ) -> Result<String, DeliveryChallanServiceError> {
+        let is_processed = self
+            .dao
+            .is_delivery_challan_pdf_created(pdf_data.tenant_id, pdf_data.delivery_challan_id)
+            .await?;
+        let key = create_storage_file_key(pdf_data.tenant_id, pdf_data.delivery_challan_id);
+        if is_processed {
+            let url = self
+                .storage_service
+                .get_object_url(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), None)
+                .await?;
+            return Ok(url);
+        }
+        let pdf_bytes = create_delivery_challan_pdf(&pdf_data.challan)?;
+        let uploaded_url = self
+            .storage_service
+            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), pdf_bytes, None)
+            .await?;
+        self.dao
+            .persist_delivery_challan_pdf_dtl(
+                pdf_data.tenant_id,
+                pdf_data.delivery_challan_id,
+                key.as_str(),
+            )
+            .await?;
+        Ok(uploaded_url)
+    }
+
+    async fn get_delivery_challan(
+        &self,
+        delivery_challan_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<DeliveryChallanDetail, DeliveryChallanServiceError> {
+        self.dao
+            .get_delivery_challan_by_id(delivery_challan_id, tenant_id)
+            .await?
+            .ok_or(DeliveryChallanServiceError::DeliveryChallanNotFound(
+                delivery_challan_id,
+            ))
+    }
+
+    async fn convert_delivery_challan(
+        &self,
+        delivery_challan_id: Uuid,
+        req: ConvertDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertDeliveryChallanResponse, DeliveryChallanServiceError> {
+        let challan = self
+            .get_delivery_challan(delivery_challan_id, tenant_id)
+            .await?;
+        if challan.linked_invoice_id.is_some() {
+            return Err(DeliveryChallanServiceError::AlreadyLinked(
+                delivery_challan_id,
+            ));
+        }
+        let valid_series = self
+            .invoicing_series_service
+            .is_valid_invoicing_series_id(
+                req.invoicing_series_mst_id,
+                InvoicingSeriesType::TaxInvoice,
+                tenant_id,
+            )
+            .await
+            .context("error during invoicing series validation")?;
+        if !valid_series {
+            return Err(DeliveryChallanServiceError::Validation(vec![
+                "invoicing series id is not a tax invoice series of this tenant".to_string(),
+            ]));
+        }
+        let idempotence_key = req.idempotence_key;
+        let invoice_req = challan.request.into_create_invoice_request(
+            idempotence_key,
+            req.invoicing_series_mst_id,
+            req.invoice_details,
+        );
+        //claimed before the invoice is created so that parallel conversions cannot create two invoices
+        let claimed = self
+            .dao
+            .claim_delivery_challan(tenant_id, delivery_challan_id, idempotence_key)
+            .await?;
+        if !claimed {
+            return Err(DeliveryChallanServiceError::AlreadyLinked(
+                delivery_challan_id,
+            ));
+        }
+        let response = match self
+            .create_converted_invoice(invoice_req, tenant_id, user_id)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.dao
+                    .release_delivery_challan_claim(tenant_id, delivery_challan_id, idempotence_key)
+                    .await?;
+                return Err(e);
+            }
+        };
+        //a retry with the same idempotence key gets the same invoice back and links it again
+        let linked = self
+            .dao
+            .link_delivery_challan_to_invoice(
+                tenant_id,
+                delivery_challan_id,
+                response.invoice_id,
+                user_id,
+            )
+            .await?;
+        if !linked {
+            return Err(DeliveryChallanServiceError::AlreadyLinked(
+                delivery_challan_id,
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn link_invoice(
+        &self,
+        delivery_challan_id: Uuid,
+        req: LinkDeliveryChallanRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), DeliveryChallanServiceError> {
+        let challan = self
+            .get_delivery_challan(delivery_challan_id, tenant_id)
+            .await?;
+        if challan.linked_invoice_id == Some(req.invoice_id) {
+            return Ok(());
+        }
+        if !self
+            .dao
+            .is_invoice_of_tenant(tenant_id, req.invoice_id)
+            .await?
+        {
+            return Err(DeliveryChallanServiceError::InvoiceNotFound(req.invoice_id));
+        }
+        //the invoice id is the claim key, a challan being converted cannot be linked to another invoice
+        let claimed = self
+            .dao
+            .claim_delivery_challan(tenant_id, delivery_challan_id, req.invoice_id)
+            .await?;
+        if !claimed {
+            return Err(DeliveryChallanServiceError::AlreadyLinked(
+                delivery_challan_id,
+            ));
+        }
+        let linked = self
+            .dao
+            .link_delivery_challan_to_invoice(
+                tenant_id,
+                delivery_challan_id,
+                req.invoice_id,
+                user_id,
+            )
+            .await?;
+        if !linked {
+            self.dao
+                .release_delivery_challan_claim(tenant_id, delivery_challan_id, req.invoice_id)
+                .await?;
+            return Err(DeliveryChallanServiceError::AlreadyLinked(
+                delivery_challan_id,
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn create_storage_file_key(tenant_id: Uuid, delivery_challan_id: Uuid) -> String {
+    format!("{}-delivery-challan-{}.pdf", tenant_id, delivery_challan_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::ResultAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::delivery_challan::delivery_challan_dao::MockDeliveryChallanDao;
+    use crate::invoicing::delivery_challan::delivery_challan_models::tests::a_create_delivery_challan_request;
+    use crate::invoicing::delivery_challan::delivery_challan_models::{
+        ConvertDeliveryChallanRequest, DeliveryChallanDetail, DeliveryChallanReason,
+        LinkDeliveryChallanRequest,
+    };
+    use crate::invoicing::delivery_challan::delivery_challan_service::{
+        DeliveryChallanService, DeliveryChallanServiceError, DeliveryChallanServiceImpl,
+    };
+    use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+    use crate::invoicing::invoice_approval::invoice_approval_service::MockInvoiceApprovalService;
+    use crate::invoicing::invoicing_request_models::CreateDraftInvoiceResponse;
+    use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
+    use crate::invoicing::invoicing_series::invoicing_series_service::MockInvoicingSeriesService;
+    use crate::invoicing::invoicing_service::{InvoicingServiceError, MockInvoicingService};
+    use crate::storage::storage_service::MockStorageService;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_delivery_challan_detail(linked_invoice_id: Option<Uuid>) -> DeliveryChallanDetail {
+        DeliveryChallanDetail {
+            id: Uuid::now_v7(),
+            reason: DeliveryChallanReason::JobWork,
+            challan_number: "DC1".to_string(),
+            challan_date_ms: 0,
+            total_value: 20.0,
+            linked_invoice_id,
+            request: a_create_delivery_challan_request(Default::default()),
+        }
+    }
+
+    fn a_convert_request() -> ConvertDeliveryChallanRequest {
+        ConvertDeliveryChallanRequest {
+            idempotence_key: Uuid::now_v7(),
+            invoicing_series_mst_id: Uuid::now_v7(),
+            invoice_details: Default::default(),
+        }
+    }
+
+    fn a_service(
+        dao: MockDeliveryChallanDao,
+        invoicing_service: MockInvoicingService,
+        invoice_approval_service: MockInvoiceApprovalService,
+        tax_invoice_series: bool,
+    ) -> DeliveryChallanServiceImpl {
+        let mut invoicing_series_service = MockInvoicingSeriesService::new();
+        invoicing_series_service
+            .expect_is_valid_invoicing_series_id()
+            .returning(move |_, series_type, _| {
+                Ok(tax_invoice_series && series_type == InvoicingSeriesType::TaxInvoice)
+            });
+        DeliveryChallanServiceImpl {
+            dao: Arc::new(dao),
+            invoicing_service: Arc::new(invoicing_service),
+            invoicing_series_service: Arc::new(invoicing_series_service),
+            invoice_approval_service: Arc::new(invoice_approval_service),
+            storage_service: Arc::new(MockStorageService::new()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_convert_already_linked_challan() {
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .returning(|_, _| Ok(Some(a_delivery_challan_detail(Some(Uuid::now_v7())))));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service.expect_create_draft_invoice().never();
+        let service = a_service(
+            dao,
+            invoicing_service,
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let resp = service
+            .convert_delivery_challan(
+                Uuid::now_v7(),
+                a_convert_request(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(DeliveryChallanServiceError::AlreadyLinked(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_convert_rejects_series_of_other_documents() {
+        let challan = a_delivery_challan_detail(None);
+        let mut req = a_convert_request();
+        req.invoicing_series_mst_id = challan.request.challan_series_mst_id;
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .return_once(move |_, _| Ok(Some(challan)));
+        dao.expect_claim_delivery_challan().never();
+        let service = a_service(
+            dao,
+            MockInvoicingService::new(),
+            MockInvoiceApprovalService::new(),
+            false,
+        );
+        let resp = service
+            .convert_delivery_challan(Uuid::now_v7(), req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(
+            resp,
+            Err(DeliveryChallanServiceError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_convert_creates_draft_when_approval_required() {
+        let invoice_id = Uuid::now_v7();
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .returning(|_, _| Ok(Some(a_delivery_challan_detail(None))));
+        dao.expect_claim_delivery_challan()
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        dao.expect_link_delivery_challan_to_invoice()
+            .withf(move |_, _, inv, _| *inv == invoice_id)
+            .times(1)
+            .returning(|_, _, _, _| Ok(true));
+        let mut approval_service = MockInvoiceApprovalService::new();
+        approval_service
+            .expect_is_approval_required()
+            .returning(|_| Ok(true));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service
+            .expect_create_draft_invoice()
+            .withf(|req, _, _| req.additional_charges.is_empty() && !req.service_invoice)
+            .times(1)
+            .returning(move |_, _, _| Ok(CreateDraftInvoiceResponse { invoice_id }));
+        let service = a_service(dao, invoicing_service, approval_service, true);
+        let resp = service
+            .convert_delivery_challan(
+                Uuid::now_v7(),
+                a_convert_request(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert_that!(resp).is_ok();
+        let resp = resp.unwrap();
+        assert_that!(resp.invoice_id).is_equal_to(invoice_id);
+        assert_that!(resp.invoice_status).is_equal_to(InvoiceStatus::Draft);
+        assert!(resp.invoice_pdf_request.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_link_invoice_of_other_tenant() {
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .returning(|_, _| Ok(Some(a_delivery_challan_detail(None))));
+        dao.expect_is_invoice_of_tenant()
+            .returning(|_, _| Ok(false));
+        dao.expect_link_delivery_challan_to_invoice().never();
+        let service = a_service(
+            dao,
+            MockInvoicingService::new(),
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let resp = service
+            .link_invoice(
+                Uuid::now_v7(),
+                LinkDeliveryChallanRequest {
+                    invoice_id: Uuid::now_v7(),
+                },
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(DeliveryChallanServiceError::InvoiceNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_conversion_releases_the_claim() {
+        let req = a_convert_request();
+        let idempotence_key = req.idempotence_key;
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .returning(|_, _| Ok(Some(a_delivery_challan_detail(None))));
+        dao.expect_claim_delivery_challan()
+            .withf(move |_, _, key| *key == idempotence_key)
+            .returning(|_, _, _| Ok(true));
+        dao.expect_release_delivery_challan_claim()
+            .withf(move |_, _, key| *key == idempotence_key)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dao.expect_link_delivery_challan_to_invoice().never();
+        let mut approval_service = MockInvoiceApprovalService::new();
+        approval_service
+            .expect_is_approval_required()
+            .returning(|_| Ok(false));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_create_invoice()
+            .returning(|_, _, _| {
+                Err(InvoicingServiceError::Validation(vec![
+                    "supplier id does not exists for this tenant id".to_string(),
+                ]))
+            });
+        let service = a_service(dao, invoicing_service, approval_service, true);
+        let resp = service
+            .convert_delivery_challan(Uuid::now_v7(), req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(
+            resp,
+            Err(DeliveryChallanServiceError::Invoicing(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_challan_being_converted_is_not_linked() {
+        let mut dao = MockDeliveryChallanDao::new();
+        dao.expect_get_delivery_challan_by_id()
+            .returning(|_, _| Ok(Some(a_delivery_challan_detail(None))));
+        dao.expect_is_invoice_of_tenant().returning(|_, _| Ok(true));
+        dao.expect_claim_delivery_challan()
+            .returning(|_, _, _| Ok(false));
+        dao.expect_link_delivery_challan_to_invoice().never();
+        let service = a_service(
+            dao,
+            MockInvoicingService::new(),
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let resp = service
+            .link_invoice(
+                Uuid::now_v7(),
+                LinkDeliveryChallanRequest {
+                    invoice_id: Uuid::now_v7(),
+                },
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(DeliveryChallanServiceError::AlreadyLinked(_))
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+create type delivery_challan_reason as enum ('job_work','supply_on_approval','branch_transfer','quantity_not_known',
+    'exhibition','others');
+
+create table delivery_challan
+(
+    id                        uuid primary key,
+    entity_version_id         integer default 0,
+    tenant_id                 uuid references tenant (id)               not null,
+    active                    bool,
+    approval_status           smallint                                  not null,
+    remarks                   varchar(70),
+    reason                    delivery_challan_reason                   not null,
+    challan_series_mst_id     uuid references invoicing_series_mst (id) not null,--own series, separate from tax invoices
+    financial_year            smallint                                  not null,
+    challan_number            varchar(20)                               not null,
+    challan_date_ms           bigint                                    not null,
+    currency_id               uuid references currency_master (id)      not null,
+    supplier_business_entity  uuid references business_entity (id)      not null,
+    consignee_business_entity uuid references business_entity (id),
+    total_value               double precision                          not null,--value of goods, no tax is payable on a challan
+    challan_request           jsonb                                     not null,--lines and parties, used to build the tax invoice on conversion
+    challan_pdf_s3_id         varchar(200),
+    linked_invoice_id         uuid references invoice (id),--tax invoice created from or linked to this challan
+    linked_by                 uuid references app_user (id),
+    linked_at                 bigint,
+    link_claim_key            uuid,--held by the conversion or link in progress, released when it fails
+    created_by                uuid references app_user (id)             not null,
+    updated_by                uuid references app_user (id),
+    created_at                bigint  default extract(epoch from now()) * 1000000,
+    updated_at                bigint  default extract(epoch from now()) * 1000000
+);
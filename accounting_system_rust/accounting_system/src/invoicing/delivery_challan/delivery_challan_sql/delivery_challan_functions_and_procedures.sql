@@ -0,0 +1,85 @@
+create type create_delivery_challan_request as
+(
+    idempotence_key       uuid,
+    tenant_id             uuid,
+    reason                delivery_challan_reason,
+    challan_series_mst_id uuid,
+    financial_year        smallint,
+    challan_date_ms       bigint,
+    currency_id           uuid,
+    supplier_id           uuid,
+    consignee_id          uuid,
+    total_value           double precision,
+    challan_request       jsonb,
+    created_by            uuid
+);
+
+--numbers are taken from the challan series with the same counter logic as invoices
+create or replace function create_delivery_challan(req create_delivery_challan_request) returns jsonb as
+$$
+DECLARE
+    resp          jsonb;
+    _challan_id   uuid := uuid_generate_v7();
+    _number       text;
+    impacted_rows int;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_delivery_challan', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        select create_invoice_number(req.challan_series_mst_id, req.financial_year, req.tenant_id,
+                                     req.created_by)
+        into _number;
+        insert into delivery_challan (id, entity_version_id, tenant_id, active, approval_status, remarks, reason,
+                                      challan_series_mst_id, financial_year, challan_number, challan_date_ms,
+                                      currency_id, supplier_business_entity, consignee_business_entity,
+                                      total_value, challan_request, challan_pdf_s3_id, linked_invoice_id, linked_by,
+                                      linked_at, created_by, updated_by, created_at, updated_at)
+        values (_challan_id, 0, req.tenant_id, true, 1, null, req.reason, req.challan_series_mst_id,
+                req.financial_year, _number, req.challan_date_ms, req.currency_id, req.supplier_id,
+                req.consignee_id, req.total_value, req.challan_request, null, null, null, null, req.created_by,
+                req.created_by, default, default);
+        resp := jsonb_build_object('delivery_challan_id', _challan_id, 'challan_number', _number);
+        update idempotence_store
+        set response=resp
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_delivery_challan';
+        return resp;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_delivery_challan'
+        into resp;
+        return resp;
+    end if;
+end;
+$$ language plpgsql;
+
+--links the tax invoice to the challan, returns false if it was already linked to another invoice
+create or replace function link_delivery_challan_to_invoice(_tenant_id uuid, _challan_id uuid, _invoice_id uuid,
+                                                            _linked_by uuid) returns bool as
+$$
+DECLARE
+    impacted_rows int;
+BEGIN
+    update delivery_challan
+    set linked_invoice_id = _invoice_id,
+        linked_by         = _linked_by,
+        linked_at         = extract(epoch from now()) * 1000000,
+        updated_by        = _linked_by,
+        updated_at        = extract(epoch from now()) * 1000000
+    where id = _challan_id
+      and tenant_id = _tenant_id
+      and (linked_invoice_id is null or linked_invoice_id = _invoice_id);
+    get diagnostics impacted_rows= row_count;
+    return impacted_rows != 0;
+end;
+$$ language plpgsql;
+
+create trigger delivery_challan_audit_trigger
+    after update or delete
+    on delivery_challan
+    for each row
+execute function create_audit_entry();
@@ -0,0 +1,2 @@
+create unique index if not exists delivery_challan_number_idx on delivery_challan (tenant_id, challan_series_mst_id, financial_year, challan_number);
+create index if not exists delivery_challan_linked_invoice_idx on delivery_challan (tenant_id, linked_invoice_id);
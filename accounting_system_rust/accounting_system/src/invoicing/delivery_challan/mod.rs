@@ -0,0 +1,5 @@
+mod delivery_challan_dao;
+pub mod delivery_challan_db_mapping;
+pub mod delivery_challan_http_api;
+pub mod delivery_challan_models;
+pub mod delivery_challan_service;
@@ -10,14 +10,18 @@ use pdf_doc_generator::invoice_template::InvoiceTableHeaderNameEnum::{
     Cgst, ItemDescription, LineTotal, Qty, SerialNo, Sgst, UnitPrice, Uqc,
 };
 use pdf_doc_generator::invoice_template::{
-    AdditionalCharge, Address, DocDate, Invoice, InvoiceLineTable, InvoiceParty, InvoiceSummary,
-    InvoiceTableHeaderNameEnum, TaxLine, TaxSummary,
+    AdditionalCharge, Address, DocDate, EInvoiceDetail, ExportDeclaration, Invoice,
+    InvoiceLineTable, InvoiceParty, InvoiceSummary, InvoiceTableHeaderNameEnum, TaxLine,
+    TaxSummary,
 };
 
 use crate::accounting::currency::currency_models::CurrencyMaster;
-use crate::invoicing::invoicing_dao_models::{InvoiceDb, InvoiceLineDb};
+use crate::common_utils::utils::epoch_ms_to_indian_date;
+use crate::invoicing::invoicing_dao_models::{InvoiceDb, InvoiceLineDb, PaymentTermsDb};
+use crate::invoicing::invoicing_domain_models::EInvoicePortalResponse;
 use crate::invoicing::invoicing_request_models::{
-    CreateInvoiceLineRequestWithAllDetails, CreateInvoiceWithAllDetailsIncluded,
+    CreateInvoiceLineRequestWithAllDetails, CreateInvoiceWithAllDetailsIncluded, DocumentType,
+    ExportType,
 };
 use crate::masters::business_entity_master::business_entity_models::BusinessEntityDto;
 use crate::masters::business_entity_master::business_entity_service::BusinessEntityService;
@@ -65,13 +69,35 @@ pub async fn convert_to_invoice_doc_model<'a>(
         business_entity_service.clone(),
     )
     .await?;
+    let b2c_qr_payload = if invoice.e_invoicing_applicable && !invoice.b2b_invoice {
+        Some(create_b2c_dynamic_qr_payload(
+            data_input,
+            &invoice_number,
+            supplier
+                .business_entity
+                .entity_type
+                .extract_gstin()
+                .map(|a| a.get_str()),
+        )?)
+    } else {
+        None
+    };
+    let export_declaration = create_export_declaration(
+        data_input,
+        &currency,
+        [shipped_to.as_ref(), billed_to.as_ref()],
+    )?;
     Ok(Invoice {
+        document_title: data_input.req.document_type.title().to_string(),
         invoice_number,
         invoice_date: epoch_ms_to_doc_date(invoice.invoice_date_ms)?,
         order_date: invoice.order_date.map(epoch_ms_to_doc_date).transpose()?,
-        payment_term: "".to_string(), //todo how to derive correct payment term. credit, cash, advance,etc?
+        payment_term: format_payment_terms(invoice.payment_terms.as_ref(), invoice.invoice_date_ms)?,
         order_number: invoice.order_number.map(|a| a.to_string()),
-        irn_no: "".to_string(), //todo without einvoicing this is garbage. how to derive it
+        place_of_supply: invoice.place_of_supply.to_string(),
+        //irn is generated by irp after invoice creation, it is filled from the stored irn when the pdf is rendered
+        einvoice_detail: None,
+        b2c_qr_payload,
         service_invoice: invoice.service_invoice,
         supplier: convert_business_entity_to_invoice_party(supplier),
         dispatch_from: dispatcher,
@@ -83,16 +109,63 @@ pub async fn convert_to_invoice_doc_model<'a>(
             .map(|a| AdditionalCharge {
                 name: a.line_title.to_string(),
                 rate: a.rate,
+                tax_percentage: a.tax_percentage,
             })
             .collect_vec(),
         tax_summary: create_invoice_tax_summary(data_input)?,
-        invoice_summary: create_invoice_summary(&invoice),
+        invoice_summary: create_invoice_summary(data_input)?,
         invoice_lines_table: create_invoice_line_table(&data_input, currency)?,
         invoice_remarks: invoice.invoice_remarks.map(|a| a.to_string()),
         ecommerce_gstin: invoice.ecommerce_gstin.map(|a| a.to_string()),
+        export_declaration,
+        bill_of_supply_declaration: data_input
+            .req
+            .bill_of_supply_declaration()
+            .map(|a| a.to_string()),
+        watermark: None,
     })
 }
 
+///destination country is printed from the address of the customer located in the export country
+fn create_export_declaration(
+    data: &InvoiceDocCreationDataInput,
+    currency: &CurrencyMaster,
+    customers: [Option<&Arc<BusinessEntityDto>>; 2],
+) -> anyhow::Result<Option<ExportDeclaration>> {
+    let (Some(detail), Some(detail_db)) = (
+        data.req.export_detail.as_ref(),
+        data.invoice.export_detail.as_ref(),
+    ) else {
+        return Ok(None);
+    };
+    let declaration = match detail.export_type {
+        ExportType::UnderLut => {
+            "SUPPLY MEANT FOR EXPORT UNDER BOND OR LETTER OF UNDERTAKING WITHOUT PAYMENT OF INTEGRATED TAX"
+        }
+        ExportType::WithPaymentOfIgst => "SUPPLY MEANT FOR EXPORT ON PAYMENT OF INTEGRATED TAX",
+    };
+    let destination_country = customers
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.address.as_ref())
+        .find(|a| a.country.id == detail.country_id)
+        .map(|a| a.country.name.inner().to_string());
+    Ok(Some(ExportDeclaration {
+        declaration: declaration.to_string(),
+        lut_reference: detail.lut_reference.clone(),
+        port_code: detail.port_code.clone(),
+        shipping_bill_number: detail.shipping_bill_number.clone(),
+        shipping_bill_date: detail_db
+            .shipping_bill_date_ms
+            .map(epoch_ms_to_doc_date)
+            .transpose()?,
+        destination_country,
+        currency: currency.display_name.clone(),
+        exchange_rate: detail.exchange_rate,
+        total_payable_amount_inr: detail_db.total_payable_amount_inr,
+    }))
+}
+
 fn create_invoice_line_table<'a>(
     data: &'a InvoiceDocCreationDataInput<'a>,
     currency: Arc<CurrencyMaster>,
@@ -109,21 +182,32 @@ fn create_invoice_line_table<'a>(
     })
 }
 
-fn create_invoice_summary(invoice: &InvoiceDb) -> InvoiceSummary {
-    InvoiceSummary {
+fn create_invoice_summary<'a>(
+    data: &'a InvoiceDocCreationDataInput<'a>,
+) -> anyhow::Result<InvoiceSummary> {
+    let invoice = data.invoice;
+    Ok(InvoiceSummary {
         taxable_amt: invoice.total_taxable_amount,
         tax_amt: invoice.total_tax_amount,
+        cess_amt: data.req.total_cess_amount()?,
         additional_charges_amt: invoice.total_additional_charges_amount,
         round_off: invoice.round_off,
         total_payable_amount: invoice.total_payable_amount,
-    }
+    })
 }
 
-fn create_invoice_tax_summary<'a>(
+pub(crate) fn create_invoice_tax_summary<'a>(
     data: &'a InvoiceDocCreationDataInput<'a>,
 ) -> anyhow::Result<TaxSummary> {
     let invoice = data.invoice;
-    if invoice.igst_applicable {
+    if invoice.bill_of_supply() {
+        Ok(TaxSummary {
+            igst_lines: vec![],
+            cgst_lines: vec![],
+            sgst_lines: vec![],
+            total_tax_amount: 0.0,
+        })
+    } else if invoice.igst_applicable {
         Ok(TaxSummary {
             igst_lines: convert_to_tax_lines(data.req, invoice.igst_applicable)?,
             cgst_lines: vec![],
@@ -141,6 +225,10 @@ fn create_invoice_tax_summary<'a>(
 }
 
 impl InvoiceDb<'_> {
+    ///no tax is charged on a bill of supply, its tax columns are not printed
+    fn bill_of_supply(&self) -> bool {
+        self.document_type == DocumentType::BillOfSupply.as_str()
+    }
     fn hsn_sac_header(&self) -> Option<InvoiceTableHeaderNameEnum> {
         if self.service_invoice {
             Some(InvoiceTableHeaderNameEnum::Sac("".to_string()))
@@ -185,7 +273,10 @@ impl InvoiceDb<'_> {
         }
     }
     fn igst_header(&self) -> Option<InvoiceTableHeaderNameEnum> {
-        if self.igst_applicable && self.invoice_lines.iter().any(|a| a.tax_percentage != 0.0) {
+        if self.igst_applicable
+            && !self.bill_of_supply()
+            && self.invoice_lines.iter().any(|a| a.tax_percentage != 0.0)
+        {
             Some(InvoiceTableHeaderNameEnum::Igst("%".to_string()))
         } else {
             None
@@ -201,7 +292,10 @@ impl InvoiceDb<'_> {
         h: InvoiceTableHeaderNameEnum,
     ) -> Option<InvoiceTableHeaderNameEnum> {
         // assert!(h==Cgst||h==Sgst,"input arg can only be one Sgst or Cgst but was {h}");
-        if !self.igst_applicable && self.invoice_lines.iter().any(|a| a.tax_percentage != 0.0) {
+        if !self.igst_applicable
+            && !self.bill_of_supply()
+            && self.invoice_lines.iter().any(|a| a.tax_percentage != 0.0)
+        {
             Some(h)
         } else {
             None
@@ -212,7 +306,7 @@ impl InvoiceDb<'_> {
         self.cgst_sgst_header(po)
     }
     fn cess_header(&self) -> Option<InvoiceTableHeaderNameEnum> {
-        if self.invoice_lines.iter().all(|l| l.cess_percentage == 0.0) {
+        if self.bill_of_supply() || self.invoice_lines.iter().all(|l| l.cess_percentage == 0.0) {
             None
         } else {
             Some(InvoiceTableHeaderNameEnum::Cess("%".to_string()))
@@ -288,6 +382,74 @@ fn convert_db_line_to_doc_line(
     Ok(line)
 }
 
+///qr with the invoice details for b2c invoices of einvoicing applicable taxpayers, irn is not generated for b2c
+fn create_b2c_dynamic_qr_payload<'a>(
+    data: &'a InvoiceDocCreationDataInput<'a>,
+    invoice_number: &str,
+    supplier_gstin: Option<&str>,
+) -> anyhow::Result<String> {
+    let invoice = data.invoice;
+    let invoice_date = epoch_ms_to_doc_date(invoice.invoice_date_ms)?;
+    let cess_amount = data.req.total_cess_amount()?;
+    let gst_amount = invoice.total_tax_amount;
+    let (igst_amount, cgst_amount, sgst_amount) = if invoice.igst_applicable {
+        (gst_amount, 0.0, 0.0)
+    } else {
+        (0.0, gst_amount / 2.0, gst_amount / 2.0)
+    };
+    Ok(format!(
+        "SellerGstin:{}|DocNo:{}|DocDt:{:02}/{:02}/{}|TotInvVal:{:.2}|IgstVal:{:.2}|CgstVal:{:.2}|SgstVal:{:.2}|CessVal:{:.2}",
+        supplier_gstin.unwrap_or(""),
+        invoice_number,
+        invoice_date.day,
+        invoice_date.month,
+        invoice_date.year,
+        invoice.total_payable_amount,
+        igst_amount,
+        cgst_amount,
+        sgst_amount,
+        cess_amount
+    ))
+}
+
+///due days along with the early payment discount offer e.g. "due in 30 days, 2% if paid by 10-Feb-2024"
+fn format_payment_terms(
+    terms: Option<&PaymentTermsDb>,
+    invoice_date_ms: i64,
+) -> anyhow::Result<String> {
+    let Some(terms) = terms else {
+        return Ok(String::new());
+    };
+    let mut text = if terms.due_days == 0 {
+        "due on receipt".to_string()
+    } else {
+        format!("due in {} days", terms.due_days)
+    };
+    if let (Some(days), Some(percent)) = (terms.discount_days, terms.discount_percent) {
+        if days > 0 && percent > 0.0 {
+            let pay_by = epoch_ms_to_indian_date(invoice_date_ms)? + chrono::Days::new(days as u64);
+            text.push_str(&format!(
+                ", {}% if paid by {}",
+                percent,
+                pay_by.format("%d-%b-%Y")
+            ));
+        }
+    }
+    Ok(text)
+}
+
+///irn details printed on the e-invoice pdf as returned by the invoice registration portal
+pub fn convert_to_einvoice_detail(
+    detail: EInvoicePortalResponse,
+) -> anyhow::Result<EInvoiceDetail> {
+    Ok(EInvoiceDetail {
+        irn_no: detail.irn,
+        ack_no: detail.ack_no,
+        ack_date: epoch_ms_to_doc_date(detail.ack_date_ms)?,
+        signed_qr_code: detail.signed_qr_code,
+    })
+}
+
 fn epoch_ms_to_doc_date(epoch_ms: i64) -> anyhow::Result<DocDate> {
     let jp = DateTime::from_timestamp_millis(epoch_ms)
         .ok_or_else(|| anyhow!("error parsing date"))?
@@ -325,13 +487,19 @@ fn convert_to_tax_lines(
     for line in lines_and_tax_amt.iter() {
         let req = line.0;
         let tax_amt = line.1;
-        let tax_bps = (req
-            .product_item_id
-            .get_tax_rate()?
-            .tax_rate_percentage
-            .inner()
-            * 100.0)
-            .round() as u32;
+        let tax_bps = (req.tax_percentage()? * 100.0).round() as u32;
+        grouped_tax_bps_with_tax_amt_lines
+            .entry(tax_bps)
+            .and_modify(|a| *a += tax_amt)
+            .or_insert(tax_amt);
+    }
+    //tax on additional charges is shown along with the lines of the same rate
+    for charge in data.additional_charges.iter() {
+        let tax_amt = data.additional_charge_tax_amount(charge)?;
+        if tax_amt == 0.0 {
+            continue;
+        }
+        let tax_bps = (data.additional_charge_tax_percentage(charge)? * 100.0).round() as u32;
         grouped_tax_bps_with_tax_amt_lines
             .entry(tax_bps)
             .and_modify(|a| *a += tax_amt)
@@ -384,3 +552,74 @@ fn convert_business_entity_to_invoice_party(e: Arc<BusinessEntityDto>) -> Invoic
             }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rstest::rstest;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::doc_conversion::{
+        create_invoice_tax_summary, format_payment_terms, InvoiceDocCreationDataInput,
+    };
+    use crate::invoicing::invoicing_dao_models::{convert_to_invoice_db, PaymentTermsDb};
+    use crate::invoicing::invoicing_request_models::tests::a_create_invoice_request;
+    use crate::invoicing::invoicing_request_models::SupplyClassification;
+    use crate::invoicing::place_of_supply::tests::a_place_of_supply;
+    use crate::invoicing::place_of_supply::SupplyType;
+    use crate::masters::product_item_master::product_item_models::tests::{
+        a_product_item_response, SEED_PRODUCT_ITEM_ID,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+    use crate::tenant::tenant_models::GstRegistrationType;
+
+    #[test]
+    fn test_format_payment_terms() {
+        let terms = PaymentTermsDb {
+            due_days: 30,
+            discount_days: Some(10),
+            discount_percent: Some(2.0),
+        };
+        //29 jan 2024 IST
+        let text = format_payment_terms(Some(&terms), 1706534012000).unwrap();
+        assert_eq!(text, "due in 30 days, 2% if paid by 08-Feb-2024");
+        let no_discount = PaymentTermsDb {
+            due_days: 0,
+            discount_days: Some(0),
+            discount_percent: Some(0.0),
+        };
+        let text = format_payment_terms(Some(&no_discount), 1706534012000).unwrap();
+        assert_eq!(text, "due on receipt");
+        assert_eq!(format_payment_terms(None, 1706534012000).unwrap(), "");
+    }
+
+    #[rstest]
+    #[case(GstRegistrationType::Regular, true)]
+    #[case(GstRegistrationType::Composition, false)]
+    fn test_tax_columns_suppressed_on_bill_of_supply(
+        #[case] registration_type: GstRegistrationType,
+        #[case] tax_columns: bool,
+    ) {
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        let req = a_create_invoice_request(Default::default())
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                registration_type,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let invoice = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        assert_eq!(invoice.cgst_header().is_some(), tax_columns);
+        assert_eq!(invoice.sgst_header().is_some(), tax_columns);
+        assert!(invoice.igst_header().is_none());
+        let data = InvoiceDocCreationDataInput {
+            invoice: &invoice,
+            req: &req,
+        };
+        let summary = create_invoice_tax_summary(&data).unwrap();
+        assert_eq!(summary.cgst_lines.is_empty(), !tax_columns);
+    }
+}
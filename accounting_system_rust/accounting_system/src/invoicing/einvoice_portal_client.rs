@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+use uuid::Uuid;
+
+use crate::invoicing::invoicing_domain_models::EInvoicePortalResponse;
+
+///registration of an issued invoice with the invoice registration portal (directly or through a gsp)
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait EInvoicePortalClient: Send + Sync {
+    ///false till the portal credentials are set up, invoices are then rendered without an irn
+    fn is_configured(&self) -> bool;
+    ///registering an already registered invoice returns its existing irn
+    async fn generate_irn(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> anyhow::Result<EInvoicePortalResponse>;
+}
+
+struct UnconfiguredEInvoicePortalClient {}
+
+#[async_trait]
+impl EInvoicePortalClient for UnconfiguredEInvoicePortalClient {
+    fn is_configured(&self) -> bool {
+        false
+    }
+
+    async fn generate_irn(
+        &self,
+        tenant_id: Uuid,
+        _invoice_id: Uuid,
+    ) -> anyhow::Result<EInvoicePortalResponse> {
+        bail!(
+            "e-invoice portal credentials are not configured for tenant {}",
+            tenant_id
+        )
+    }
+}
+
+//todo plug in gsp/irp api client once credentials management is in place
+pub fn get_einvoice_portal_client() -> Arc<dyn EInvoicePortalClient> {
+    Arc::new(UnconfiguredEInvoicePortalClient {})
+}
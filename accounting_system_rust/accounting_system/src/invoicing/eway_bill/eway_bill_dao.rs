@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::eway_bill::eway_bill_models::{
+    EwayBill, EwayBillInvoiceDb, EwayBillInvoiceLineDb, EwayBillJson, EwayBillPortalResponse,
+};
+
+const INVOICE_QUERY: &str = "select invoice_number,invoice_date_ms,service_invoice,igst_applicable,\
+supplier_business_entity,dispatch_from_business_entity,billed_to_business_entity,shipped_to_business_entity,\
+total_taxable_amount,total_tax_amount,total_additional_charges_amount,round_off,total_payable_amount,\
+coalesce(total_payable_amount_inr,total_payable_amount) \
+from invoice where id=$1 and tenant_id=$2 and invoice_status='issued'";
+
+const INVOICE_LINES_QUERY: &str =
+    "select lt.hsn_code,lt.description,il.quantity,il.uqc,il.unit_price,\
+il.discount_percentage,il.tax_percentage,il.cess_percentage,il.cess_amount_per_unit,\
+il.retail_sale_price_for_cess,il.cess_calculation_strategy::text \
+from invoice_line il join line_title lt on il.line_title_hsn_sac_id=lt.id \
+where il.invoice_table_id=$1 and il.tenant_id=$2 order by il.line_number";
+
+const UPSERT_EWAY_BILL_JSON: &str = "insert into eway_bill (id,entity_version_id,tenant_id,active,\
+approval_status,remarks,invoice_id,eway_bill_json,created_by,updated_by) \
+values (uuid_generate_v7(),0,$1,true,1,null,$2,$3,$4,$4) \
+on conflict (tenant_id,invoice_id) do update set eway_bill_json=excluded.eway_bill_json,\
+entity_version_id=eway_bill.entity_version_id+1,updated_by=excluded.updated_by,\
+updated_at=extract(epoch from now()) * 1000000 \
+where eway_bill.eway_bill_no is null returning id";
+
+const QUERY_BY_INVOICE_ID: &str = "select id,invoice_id,eway_bill_json,eway_bill_no,\
+eway_bill_date_ms,valid_upto_ms from eway_bill where invoice_id=$1 and tenant_id=$2";
+
+const UPDATE_PORTAL_RESPONSE: &str = "update eway_bill set eway_bill_no=$1,eway_bill_date_ms=$2,\
+valid_upto_ms=$3,entity_version_id=entity_version_id+1,updated_by=$4,\
+updated_at=extract(epoch from now()) * 1000000 \
+where invoice_id=$5 and tenant_id=$6 and eway_bill_no is null";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait EwayBillDao: Send + Sync {
+    async fn get_invoice_for_eway_bill(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBillInvoiceDb>, DaoError>;
+    ///returns none if e-way bill is already generated on the portal for this invoice
+    async fn upsert_eway_bill_json(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        eway_bill_json: &EwayBillJson,
+    ) -> Result<Option<Uuid>, DaoError>;
+    async fn get_eway_bill_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBill>, DaoError>;
+    async fn persist_eway_bill_portal_response(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        response: &EwayBillPortalResponse,
+    ) -> Result<(), DaoError>;
+}
+
+struct EwayBillDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_eway_bill_dao(arc: Arc<Pool>) -> Arc<dyn EwayBillDao> {
+    let dao = EwayBillDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for EwayBillInvoiceLineDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(EwayBillInvoiceLineDb {
+            hsn_code: row.get(0),
+            description: row.get(1),
+            quantity: row.get(2),
+            uqc: row.get(3),
+            unit_price: row.get(4),
+            discount_percentage: row.get(5),
+            tax_percentage: row.get(6),
+            cess_percentage: row.get(7),
+            cess_amount_per_unit: row.get(8),
+            retail_sale_price_for_cess: row.get(9),
+            cess_calculation_strategy: row.get(10),
+        })
+    }
+}
+
+impl TryFrom<Row> for EwayBill {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let json: serde_json::Value = row.get(2);
+        Ok(EwayBill {
+            id: row.get(0),
+            invoice_id: row.get(1),
+            eway_bill_json: serde_json::from_value(json)
+                .context("could not deserialize stored e-way bill json")?,
+            eway_bill_no: row.get(3),
+            eway_bill_date_ms: row.get(4),
+            valid_upto_ms: row.get(5),
+        })
+    }
+}
+
+#[async_trait]
+impl EwayBillDao for EwayBillDaoImpl {
+    async fn get_invoice_for_eway_bill(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBillInvoiceDb>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let Some(row) = conn
+            .query_opt(INVOICE_QUERY, &[&invoice_id, &tenant_id])
+            .await?
+        else {
+            return Ok(None);
+        };
+        let lines = conn
+            .query(INVOICE_LINES_QUERY, &[&invoice_id, &tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<EwayBillInvoiceLineDb>, DaoError>>()?;
+        Ok(Some(EwayBillInvoiceDb {
+            invoice_number: row.get(0),
+            invoice_date_ms: row.get(1),
+            service_invoice: row.get(2),
+            igst_applicable: row.get::<_, Option<bool>>(3).unwrap_or(false),
+            supplier_id: row.get(4),
+            dispatch_from_id: row.get(5),
+            billed_to_id: row.get(6),
+            shipped_to_id: row.get(7),
+            total_taxable_amount: row.get(8),
+            total_tax_amount: row.get(9),
+            total_additional_charges_amount: row.get(10),
+            round_off: row.get(11),
+            total_payable_amount: row.get(12),
+            total_payable_amount_inr: row.get(13),
+            lines,
+        }))
+    }
+
+    async fn upsert_eway_bill_json(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        eway_bill_json: &EwayBillJson,
+    ) -> Result<Option<Uuid>, DaoError> {
+        let json =
+            serde_json::to_value(eway_bill_json).context("could not serialize e-way bill json")?;
+        let conn = self.postgres_client.get().await?;
+        let id = conn
+            .query_opt(
+                UPSERT_EWAY_BILL_JSON,
+                &[&tenant_id, &invoice_id, &json, &user_id],
+            )
+            .await?
+            .map(|row| row.get(0));
+        Ok(id)
+    }
+
+    async fn get_eway_bill_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBill>, DaoError> {
+        let entity = self
+            .postgres_client
+            .get()
+            .await?
+            .query_opt(QUERY_BY_INVOICE_ID, &[&invoice_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()?;
+        Ok(entity)
+    }
+
+    async fn persist_eway_bill_portal_response(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        response: &EwayBillPortalResponse,
+    ) -> Result<(), DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let updated = conn
+            .execute(
+                UPDATE_PORTAL_RESPONSE,
+                &[
+                    &response.eway_bill_no,
+                    &response.eway_bill_date_ms,
+                    &response.valid_upto_ms,
+                    &user_id,
+                    &invoice_id,
+                    &tenant_id,
+                ],
+            )
+            .await?;
+        if updated == 0 {
+            return Err(DaoError::ReturnedValueNone);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::option::OptionAssertions;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::eway_bill::eway_bill_dao::{EwayBillDao, EwayBillDaoImpl};
+    use crate::invoicing::eway_bill::eway_bill_models::tests::an_eway_bill_json;
+    use crate::invoicing::eway_bill::eway_bill_models::EwayBillPortalResponse;
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_upsert_and_submit_eway_bill() {
+        let dao = get_dao_generic(|c| EwayBillDaoImpl { postgres_client: c }, None).await;
+        let invoice = dao
+            .get_invoice_for_eway_bill(*SEED_INVOICE_ID, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(invoice).is_some();
+        let json = an_eway_bill_json();
+        let id = dao
+            .upsert_eway_bill_json(*SEED_INVOICE_ID, *SEED_TENANT_ID, *SEED_USER_ID, &json)
+            .await
+            .unwrap();
+        assert_that!(id).is_some();
+        let regenerated_id = dao
+            .upsert_eway_bill_json(*SEED_INVOICE_ID, *SEED_TENANT_ID, *SEED_USER_ID, &json)
+            .await
+            .unwrap();
+        assert_eq!(id, regenerated_id);
+        let resp = EwayBillPortalResponse {
+            eway_bill_no: 331001234567,
+            eway_bill_date_ms: 1706534012000,
+            valid_upto_ms: Some(1706620412000),
+        };
+        dao.persist_eway_bill_portal_response(
+            *SEED_INVOICE_ID,
+            *SEED_TENANT_ID,
+            *SEED_USER_ID,
+            &resp,
+        )
+        .await
+        .unwrap();
+        let stored = dao
+            .get_eway_bill_by_invoice_id(*SEED_INVOICE_ID, *SEED_TENANT_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.eway_bill_no, Some(331001234567));
+        assert_eq!(stored.eway_bill_json, json);
+        let after_submission = dao
+            .upsert_eway_bill_json(*SEED_INVOICE_ID, *SEED_TENANT_ID, *SEED_USER_ID, &json)
+            .await
+            .unwrap();
+        assert_that!(after_submission).is_none();
+    }
+}
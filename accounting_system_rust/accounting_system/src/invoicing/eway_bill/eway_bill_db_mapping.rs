@@ -0,0 +1,47 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct EwayBillDbMapping {}
+
+const EWAY_BILL_DDL_SQL: &str = include_str!("./eway_bill_sql/eway_bill_ddl.sql");
+const EWAY_BILL_SEED_DATA: &str = include_str!("./eway_bill_sql/eway_bill.csv");
+impl DbStructMapping for EwayBillDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("eway_bill")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        EWAY_BILL_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        ""
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        ""
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        EWAY_BILL_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::eway_bill::eway_bill_models::CreateEwayBillRequest;
+use crate::invoicing::eway_bill::eway_bill_service::{EwayBillService, EwayBillServiceError};
+use crate::setup_routes;
+
+impl ResponseError for EwayBillServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EwayBillServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            EwayBillServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            EwayBillServiceError::InvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            EwayBillServiceError::EwayBillNotFound(_) => StatusCode::NOT_FOUND,
+            EwayBillServiceError::AlreadySubmitted(_) => StatusCode::CONFLICT,
+            EwayBillServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn generate_eway_bill(
+    data: Data<Arc<dyn EwayBillService>>,
+    request: web::Json<CreateEwayBillRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .generate_eway_bill(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_eway_bill(
+    data: Data<Arc<dyn EwayBillService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_eway_bill_by_invoice_id(invoice_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn submit_eway_bill(
+    data: Data<Arc<dyn EwayBillService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .submit_eway_bill(invoice_id.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    EwayBillService,
+    "/eway-bill",
+    "/generate",
+    web::post().to(generate_eway_bill),
+    "/invoice-id/{invoice_id}",
+    web::get().to(get_eway_bill),
+    "/submit/invoice-id/{invoice_id}",
+    web::post().to(submit_eway_bill)
+);
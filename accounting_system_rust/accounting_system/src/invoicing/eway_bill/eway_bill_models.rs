@@ -0,0 +1,471 @@
+use anyhow::ensure;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportMode {
+    Road,
+    Rail,
+    Air,
+    Ship,
+}
+
+impl TransportMode {
+    pub fn nic_code(&self) -> &'static str {
+        match self {
+            TransportMode::Road => "1",
+            TransportMode::Rail => "2",
+            TransportMode::Air => "3",
+            TransportMode::Ship => "4",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VehicleType {
+    #[default]
+    Regular,
+    OverDimensionalCargo,
+}
+
+impl VehicleType {
+    pub fn nic_code(&self) -> &'static str {
+        match self {
+            VehicleType::Regular => "R",
+            VehicleType::OverDimensionalCargo => "O",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct VehicleNo(String);
+
+impl VehicleNo {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim().to_uppercase();
+        ensure!(
+            (7..=15).contains(&value.len()),
+            "vehicle no should be between 7 and 15 chars"
+        );
+        ensure!(
+            value.chars().all(|a| a.is_ascii_alphanumeric()),
+            "vehicle no can only contain alphanumeric characters"
+        );
+        Ok(VehicleNo(value))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for VehicleNo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        VehicleNo::new(value.as_str())
+    }
+}
+
+///gstin or the 15 char transin of the transporter enrolled on the e-way bill portal
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct TransporterId(String);
+
+impl TransporterId {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim().to_uppercase();
+        ensure!(value.len() == 15, "transporter id should be 15 chars");
+        ensure!(
+            value.chars().all(|a| a.is_ascii_alphanumeric()),
+            "transporter id can only contain alphanumeric characters"
+        );
+        Ok(TransporterId(value))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for TransporterId {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        TransporterId::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct TransportDocNo(String);
+
+impl TransportDocNo {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "transport doc no cannot be empty");
+        ensure!(
+            value.len() <= 15,
+            "transport doc no cannot be more than 15 chars"
+        );
+        ensure!(
+            value
+                .chars()
+                .all(|a| a.is_ascii_alphanumeric() || a == '/' || a == '-'),
+            "transport doc no can only contain alphanumeric characters or / or -"
+        );
+        Ok(TransportDocNo(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for TransportDocNo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        TransportDocNo::new(value.as_str())
+    }
+}
+
+///approximate distance in km between dispatch from and ship to pincode. 0 lets the portal compute it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "i32")]
+pub struct TransportDistanceKm(u16);
+
+impl TransportDistanceKm {
+    pub fn new(value: i32) -> anyhow::Result<Self> {
+        ensure!(value >= 0, "transport distance cannot be less than 0");
+        ensure!(
+            value <= 4000,
+            "transport distance cannot be more than 4000 km"
+        );
+        Ok(TransportDistanceKm(value as u16))
+    }
+    pub fn inner(&self) -> u16 {
+        self.0
+    }
+}
+
+impl TryFrom<i32> for TransportDistanceKm {
+    type Error = anyhow::Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        TransportDistanceKm::new(value)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreateEwayBillRequest {
+    pub invoice_id: Uuid,
+    pub transporter_id: Option<TransporterId>,
+    pub transporter_name: Option<String>,
+    ///none when only part a is generated and vehicle details are updated later by the transporter
+    pub transport_mode: Option<TransportMode>,
+    pub vehicle_no: Option<VehicleNo>,
+    #[serde(default)]
+    pub vehicle_type: VehicleType,
+    pub transport_doc_no: Option<TransportDocNo>,
+    pub transport_doc_date_ms: Option<i64>,
+    pub distance_km: TransportDistanceKm,
+}
+
+impl CreateEwayBillRequest {
+    pub fn validate_transport_details(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self
+            .transporter_name
+            .as_ref()
+            .is_some_and(|a| a.trim().len() > 100)
+        {
+            errors.push("transporter name cannot be more than 100 chars".to_string());
+        }
+        match self.transport_mode {
+            None => {
+                if self.transporter_id.is_none() {
+                    errors.push(
+                        "transporter id is mandatory if transport mode is not provided".to_string(),
+                    );
+                }
+            }
+            Some(TransportMode::Road) => {
+                if self.vehicle_no.is_none() && self.transporter_id.is_none() {
+                    errors.push(
+                        "vehicle no or transporter id is mandatory for road transport".to_string(),
+                    );
+                }
+            }
+            Some(_) => {
+                if self.transport_doc_no.is_none() || self.transport_doc_date_ms.is_none() {
+                    errors.push(
+                        "transport doc no and date are mandatory for rail, air and ship transport"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        errors
+    }
+}
+
+///e-way bill generation payload as per nic e-way bill api
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EwayBillJson {
+    pub supply_type: String,
+    pub sub_supply_type: String,
+    pub doc_type: String,
+    pub doc_no: String,
+    pub doc_date: String,
+    pub from_gstin: String,
+    pub from_trd_name: String,
+    pub from_addr1: String,
+    pub from_addr2: String,
+    pub from_place: String,
+    pub from_pincode: u32,
+    pub from_state_code: u8,
+    pub act_from_state_code: u8,
+    pub to_gstin: String,
+    pub to_trd_name: String,
+    pub to_addr1: String,
+    pub to_addr2: String,
+    pub to_place: String,
+    pub to_pincode: u32,
+    pub to_state_code: u8,
+    pub act_to_state_code: u8,
+    pub transaction_type: u8,
+    pub total_value: f64,
+    pub cgst_value: f64,
+    pub sgst_value: f64,
+    pub igst_value: f64,
+    pub cess_value: f64,
+    pub cess_non_advol_value: f64,
+    pub other_value: f64,
+    pub tot_inv_value: f64,
+    pub transporter_id: String,
+    pub transporter_name: String,
+    pub trans_doc_no: String,
+    pub trans_mode: String,
+    pub trans_distance: String,
+    pub trans_doc_date: String,
+    pub vehicle_no: String,
+    pub vehicle_type: String,
+    pub item_list: Vec<EwayBillItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EwayBillItem {
+    pub product_name: String,
+    pub product_desc: String,
+    pub hsn_code: String,
+    pub quantity: f64,
+    pub qty_unit: String,
+    pub cgst_rate: f32,
+    pub sgst_rate: f32,
+    pub igst_rate: f32,
+    pub cess_rate: f32,
+    pub cess_nonadvol: f64,
+    pub taxable_amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct EwayBillPortalResponse {
+    pub eway_bill_no: i64,
+    pub eway_bill_date_ms: i64,
+    pub valid_upto_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EwayBill {
+    pub id: Uuid,
+    pub invoice_id: Uuid,
+    pub eway_bill_json: EwayBillJson,
+    pub eway_bill_no: Option<i64>,
+    pub eway_bill_date_ms: Option<i64>,
+    pub valid_upto_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EwayBillInvoiceLineDb {
+    pub hsn_code: Option<String>,
+    pub description: String,
+    pub quantity: f64,
+    pub uqc: Option<String>,
+    pub unit_price: f32,
+    pub discount_percentage: f32,
+    pub tax_percentage: f32,
+    pub cess_percentage: f32,
+    pub cess_amount_per_unit: f32,
+    pub retail_sale_price_for_cess: f32,
+    pub cess_calculation_strategy: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct EwayBillInvoiceDb {
+    pub invoice_number: String,
+    pub invoice_date_ms: i64,
+    pub service_invoice: bool,
+    pub igst_applicable: bool,
+    pub supplier_id: Uuid,
+    pub dispatch_from_id: Uuid,
+    pub billed_to_id: Option<Uuid>,
+    pub shipped_to_id: Option<Uuid>,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_additional_charges_amount: f64,
+    pub round_off: f64,
+    pub total_payable_amount: f64,
+    ///same as total payable amount for invoices in inr
+    pub total_payable_amount_inr: f64,
+    pub lines: Vec<EwayBillInvoiceLineDb>,
+}
+
+///name, gstin and address of a business entity as needed on the e-way bill
+#[derive(Debug, Default, Clone)]
+pub(crate) struct EwayBillParty {
+    pub name: String,
+    pub gstin: Option<String>,
+    pub addr1: String,
+    pub addr2: String,
+    pub place: String,
+    pub pincode: Option<u32>,
+    pub state_code: Option<u8>,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::invoicing::eway_bill::eway_bill_models::{
+        CreateEwayBillRequest, CreateEwayBillRequestBuilder, EwayBillInvoiceDb,
+        EwayBillInvoiceLineDb, EwayBillJson, EwayBillParty, TransportDistanceKm, TransportDocNo,
+        TransportMode, TransporterId, VehicleNo, VehicleType,
+    };
+    use crate::invoicing::eway_bill::eway_bill_service::{build_eway_bill_json, EwayBillParties};
+
+    pub fn a_create_eway_bill_request(
+        builder: CreateEwayBillRequestBuilder,
+    ) -> CreateEwayBillRequest {
+        CreateEwayBillRequest {
+            invoice_id: builder.invoice_id.unwrap_or_else(Uuid::now_v7),
+            transporter_id: builder.transporter_id.flatten(),
+            transporter_name: builder.transporter_name.flatten(),
+            transport_mode: builder.transport_mode.unwrap_or(Some(TransportMode::Road)),
+            vehicle_no: builder
+                .vehicle_no
+                .unwrap_or_else(|| Some(VehicleNo::new("KA01AB1234").unwrap())),
+            vehicle_type: builder.vehicle_type.unwrap_or(VehicleType::Regular),
+            transport_doc_no: builder.transport_doc_no.flatten(),
+            transport_doc_date_ms: builder.transport_doc_date_ms.flatten(),
+            distance_km: builder
+                .distance_km
+                .unwrap_or_else(|| TransportDistanceKm::new(120).unwrap()),
+        }
+    }
+
+    pub(crate) fn an_eway_bill_invoice_db() -> EwayBillInvoiceDb {
+        let supplier_id = Uuid::now_v7();
+        let customer_id = Uuid::now_v7();
+        EwayBillInvoiceDb {
+            invoice_number: "TES1".to_string(),
+            invoice_date_ms: 1706534012000,
+            service_invoice: false,
+            igst_applicable: false,
+            supplier_id,
+            dispatch_from_id: supplier_id,
+            billed_to_id: Some(customer_id),
+            shipped_to_id: Some(customer_id),
+            total_taxable_amount: 90000.0,
+            total_tax_amount: 16200.0,
+            total_additional_charges_amount: 0.0,
+            round_off: 0.0,
+            total_payable_amount: 106200.0,
+            total_payable_amount_inr: 106200.0,
+            lines: vec![EwayBillInvoiceLineDb {
+                hsn_code: Some("1001".to_string()),
+                description: "wheat".to_string(),
+                quantity: 1000.0,
+                uqc: Some("KGS".to_string()),
+                unit_price: 100.0,
+                discount_percentage: 10.0,
+                tax_percentage: 18.0,
+                cess_percentage: 0.0,
+                cess_amount_per_unit: 0.0,
+                retail_sale_price_for_cess: 0.0,
+                cess_calculation_strategy: "percentage_of_assessable_value".to_string(),
+            }],
+        }
+    }
+
+    pub(crate) fn an_eway_bill_party(gstin: Option<&str>) -> EwayBillParty {
+        EwayBillParty {
+            name: "sample business".to_string(),
+            gstin: gstin.map(|a| a.to_string()),
+            addr1: "line 1".to_string(),
+            addr2: "line 2".to_string(),
+            place: "bengaluru".to_string(),
+            pincode: Some(560001),
+            state_code: Some(29),
+        }
+    }
+
+    pub(crate) fn an_eway_bill_parties() -> EwayBillParties {
+        EwayBillParties {
+            supplier: an_eway_bill_party(Some("29AAFCD5862R000")),
+            dispatch_from: an_eway_bill_party(Some("29AAFCD5862R000")),
+            billed_to: Some(an_eway_bill_party(None)),
+            shipped_to: Some(an_eway_bill_party(None)),
+        }
+    }
+
+    pub fn an_eway_bill_json() -> EwayBillJson {
+        build_eway_bill_json(
+            &an_eway_bill_invoice_db(),
+            &an_eway_bill_parties(),
+            &a_create_eway_bill_request(Default::default()),
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    #[case(Some(TransportMode::Road), true, false, false, 0)]
+    #[case(Some(TransportMode::Road), false, false, false, 1)]
+    #[case(Some(TransportMode::Road), false, true, false, 0)]
+    #[case(Some(TransportMode::Rail), false, false, false, 1)]
+    #[case(Some(TransportMode::Air), false, false, true, 0)]
+    #[case(None, true, false, false, 1)]
+    #[case(None, false, true, false, 0)]
+    fn test_validate_transport_details(
+        #[case] mode: Option<TransportMode>,
+        #[case] vehicle_no: bool,
+        #[case] transporter_id: bool,
+        #[case] transport_doc: bool,
+        #[case] error_count: usize,
+    ) {
+        let mut builder = CreateEwayBillRequestBuilder::default();
+        builder
+            .transport_mode(mode)
+            .vehicle_no(vehicle_no.then(|| VehicleNo::new("ka01ab1234").unwrap()))
+            .transporter_id(transporter_id.then(|| TransporterId::new("29AAFCD5862R000").unwrap()))
+            .transport_doc_no(transport_doc.then(|| TransportDocNo::new("AWB/123").unwrap()))
+            .transport_doc_date_ms(transport_doc.then_some(1706534012000));
+        let req = a_create_eway_bill_request(builder);
+        let errors = req.validate_transport_details();
+        assert_that!(errors).has_length(error_count);
+    }
+
+    #[rstest]
+    #[case("ka 01 ab 1234", false)]
+    #[case("ka01ab1234", true)]
+    #[case("KA01", false)]
+    fn test_vehicle_no(#[case] input: &str, #[case] valid: bool) {
+        assert_eq!(VehicleNo::new(input).is_ok(), valid);
+    }
+}
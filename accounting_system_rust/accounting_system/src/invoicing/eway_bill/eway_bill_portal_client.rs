@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use anyhow::bail;
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+
+use crate::invoicing::eway_bill::eway_bill_models::{EwayBillJson, EwayBillPortalResponse};
+
+///submission of e-way bill json to the nic portal (directly or through a gsp)
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait EwayBillPortalClient: Send + Sync {
+    async fn generate_eway_bill(
+        &self,
+        gstin: &str,
+        eway_bill: &EwayBillJson,
+    ) -> anyhow::Result<EwayBillPortalResponse>;
+}
+
+struct UnconfiguredEwayBillPortalClient {}
+
+#[async_trait]
+impl EwayBillPortalClient for UnconfiguredEwayBillPortalClient {
+    async fn generate_eway_bill(
+        &self,
+        gstin: &str,
+        _eway_bill: &EwayBillJson,
+    ) -> anyhow::Result<EwayBillPortalResponse> {
+        bail!(
+            "e-way bill portal credentials are not configured for gstin {}",
+            gstin
+        )
+    }
+}
+
+//todo plug in gsp/nic api client once credentials management is in place
+pub fn get_eway_bill_portal_client() -> Arc<dyn EwayBillPortalClient> {
+    Arc::new(UnconfiguredEwayBillPortalClient {})
+}
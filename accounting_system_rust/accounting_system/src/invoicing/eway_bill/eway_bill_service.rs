@@ -0,0 +1,639 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, ensure};
+use async_trait::async_trait;
+use cess_models::CessStrategy;
+use chrono::{DateTime, Datelike};
+use deadpool_postgres::Pool;
+use invoicing_calculations::invoice_line::InvoiceLine;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::eway_bill::eway_bill_dao::{get_eway_bill_dao, EwayBillDao};
+use crate::invoicing::eway_bill::eway_bill_models::{
+    CreateEwayBillRequest, EwayBill, EwayBillInvoiceDb, EwayBillInvoiceLineDb, EwayBillItem,
+    EwayBillJson, EwayBillParty,
+};
+use crate::invoicing::eway_bill::eway_bill_portal_client::EwayBillPortalClient;
+use crate::masters::address_master::address_model::AddressDto;
+use crate::masters::business_entity_master::business_entity_models::BusinessEntityDto;
+use crate::masters::business_entity_master::business_entity_service::BusinessEntityService;
+use crate::masters::pincode_master::pincode_models::Pincode;
+
+#[derive(Debug, Error)]
+pub enum EwayBillServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("invoice {0} not found")]
+    InvoiceNotFound(Uuid),
+    #[error("e-way bill not generated for invoice {0}")]
+    EwayBillNotFound(Uuid),
+    #[error("e-way bill already submitted to portal for invoice {0}")]
+    AlreadySubmitted(Uuid),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait EwayBillService: Send + Sync {
+    ///builds and stores the e-way bill json against the invoice. can be regenerated till submitted
+    async fn generate_eway_bill(
+        &self,
+        req: CreateEwayBillRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EwayBill, EwayBillServiceError>;
+    async fn get_eway_bill_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBill>, EwayBillServiceError>;
+    async fn submit_eway_bill(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EwayBill, EwayBillServiceError>;
+}
+
+struct EwayBillServiceImpl {
+    dao: Arc<dyn EwayBillDao>,
+    business_entity_service: Arc<dyn BusinessEntityService>,
+    portal_client: Arc<dyn EwayBillPortalClient>,
+}
+
+pub fn get_eway_bill_service(
+    arc: Arc<Pool>,
+    business_entity_service: Arc<dyn BusinessEntityService>,
+    portal_client: Arc<dyn EwayBillPortalClient>,
+) -> Arc<dyn EwayBillService> {
+    let dao = get_eway_bill_dao(arc);
+    let service = EwayBillServiceImpl {
+        dao,
+        business_entity_service,
+        portal_client,
+    };
+    Arc::new(service)
+}
+
+pub(crate) struct EwayBillParties {
+    pub supplier: EwayBillParty,
+    pub dispatch_from: EwayBillParty,
+    pub billed_to: Option<EwayBillParty>,
+    pub shipped_to: Option<EwayBillParty>,
+}
+
+impl EwayBillServiceImpl {
+    async fn fetch_party(
+        &self,
+        id: Option<Uuid>,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBillParty>, EwayBillServiceError> {
+        let Some(id) = id else {
+            return Ok(None);
+        };
+        let entity = self
+            .business_entity_service
+            .get_business_entity_by_id(&id, &tenant_id)
+            .await
+            .map_err(|e| anyhow!(e))?
+            .ok_or_else(|| anyhow!("business entity {} not found", id))?;
+        Ok(Some(convert_to_eway_bill_party(&entity)))
+    }
+
+    async fn fetch_parties(
+        &self,
+        invoice: &EwayBillInvoiceDb,
+        tenant_id: Uuid,
+    ) -> Result<EwayBillParties, EwayBillServiceError> {
+        let supplier = self
+            .fetch_party(Some(invoice.supplier_id), tenant_id)
+            .await?
+            .unwrap_or_default();
+        let dispatch_from = self
+            .fetch_party(Some(invoice.dispatch_from_id), tenant_id)
+            .await?
+            .unwrap_or_default();
+        Ok(EwayBillParties {
+            supplier,
+            dispatch_from,
+            billed_to: self.fetch_party(invoice.billed_to_id, tenant_id).await?,
+            shipped_to: self.fetch_party(invoice.shipped_to_id, tenant_id).await?,
+        })
+    }
+}
+
+fn convert_to_eway_bill_party(entity: &BusinessEntityDto) -> EwayBillParty {
+    let address: Option<&AddressDto> = entity.address.as_deref();
+    EwayBillParty {
+        name: entity.business_entity.entity_type.get_name().to_string(),
+        gstin: entity
+            .business_entity
+            .entity_type
+            .extract_gstin()
+            .map(|a| a.get_str().to_string()),
+        addr1: address
+            .map(|a| a.address.line_1.get_inner().to_string())
+            .unwrap_or_default(),
+        addr2: address
+            .and_then(|a| a.address.line_2.as_ref())
+            .map(|a| a.get_inner().to_string())
+            .unwrap_or_default(),
+        place: address
+            .map(|a| a.city.city_name.inner().to_string())
+            .unwrap_or_default(),
+        pincode: address.and_then(|a| match a.pincode.pincode {
+            Pincode::IndianPincode(p) => Some(p),
+            Pincode::Others(_) => None,
+        }),
+        state_code: address.and_then(|a| a.state.state_code.parse::<u8>().ok()),
+    }
+}
+
+///e-way bill is mandatory only for the movement of goods of consignment value above this, in inr
+pub(crate) const EWAY_BILL_THRESHOLD_INR: f64 = 50000.0;
+
+pub(crate) fn validate_eway_bill_data(
+    invoice: &EwayBillInvoiceDb,
+    parties: &EwayBillParties,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    if invoice.service_invoice {
+        errors.push("e-way bill cannot be generated for a service invoice".to_string());
+    }
+    if invoice.lines.is_empty() {
+        errors.push("invoice has no lines".to_string());
+    }
+    if invoice.total_payable_amount_inr <= EWAY_BILL_THRESHOLD_INR {
+        errors.push(format!(
+            "e-way bill is not required for consignment value {:.2} which is not above {}",
+            invoice.total_payable_amount_inr, EWAY_BILL_THRESHOLD_INR
+        ));
+    }
+    for (index, line) in invoice.lines.iter().enumerate() {
+        if line.hsn_code.as_ref().is_none_or(|a| a.trim().is_empty()) {
+            errors.push(format!("hsn code is missing for line {}", index + 1));
+        }
+    }
+    if parties.supplier.gstin.is_none() {
+        errors.push("supplier gstin is mandatory for e-way bill".to_string());
+    }
+    let ship_to = parties.shipped_to.as_ref().or(parties.billed_to.as_ref());
+    let checks = [
+        ("dispatch from", Some(&parties.dispatch_from)),
+        ("supplier", Some(&parties.supplier)),
+        ("ship to", ship_to),
+        ("bill to", parties.billed_to.as_ref().or(ship_to)),
+    ];
+    for (party_type, party) in checks {
+        match party {
+            None => errors.push(format!("{} party is mandatory for e-way bill", party_type)),
+            Some(p) => {
+                if p.pincode.is_none() {
+                    errors.push(format!("{} should have a valid indian pincode", party_type));
+                }
+                if p.state_code.is_none() {
+                    errors.push(format!("{} should have a valid gst state code", party_type));
+                }
+            }
+        }
+    }
+    errors
+}
+
+fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn epoch_ms_to_nic_date(epoch_ms: i64) -> anyhow::Result<String> {
+    let date = DateTime::from_timestamp_millis(epoch_ms)
+        .ok_or_else(|| anyhow!("error parsing date"))?
+        .naive_utc();
+    Ok(format!(
+        "{:02}/{:02}/{}",
+        date.day(),
+        date.month(),
+        date.year()
+    ))
+}
+
+///1 regular, 2 bill to - ship to, 3 bill from - dispatch from, 4 combination of 2 and 3
+fn transaction_type(invoice: &EwayBillInvoiceDb) -> u8 {
+    let bill_to_ship_to =
+        invoice.shipped_to_id.is_some() && invoice.shipped_to_id != invoice.billed_to_id;
+    let bill_from_dispatch_from = invoice.dispatch_from_id != invoice.supplier_id;
+    match (bill_to_ship_to, bill_from_dispatch_from) {
+        (false, false) => 1,
+        (true, false) => 2,
+        (false, true) => 3,
+        (true, true) => 4,
+    }
+}
+
+struct LineAmounts {
+    taxable_amount: f64,
+    cess_advol: f64,
+    cess_non_advol: f64,
+}
+
+///amounts of a line computed from its stored rates and cess strategy the same way as when the invoice was issued
+fn compute_line_amounts(line: &EwayBillInvoiceLineDb) -> anyhow::Result<LineAmounts> {
+    let cess_strategy = CessStrategy::new(
+        line.cess_calculation_strategy.as_str(),
+        line.cess_percentage,
+        line.retail_sale_price_for_cess as f64,
+        line.cess_amount_per_unit as f64,
+    )?;
+    let per_unit_cess = cess_strategy.get_cess_amount_per_unit().unwrap_or(0.0) * line.quantity;
+    let percentage_applies_over_per_unit = matches!(
+        cess_strategy,
+        CessStrategy::MaxOfPercentageOfAssessableValueAndAmountPerUnit { .. }
+    );
+    let invoice_line = InvoiceLine::new(
+        line.quantity,
+        line.unit_price as f64,
+        line.discount_percentage,
+        line.tax_percentage,
+        cess_strategy,
+    )?;
+    let cess_amount = invoice_line.compute_cess_amount();
+    let cess_non_advol = if percentage_applies_over_per_unit && cess_amount > per_unit_cess {
+        0.0
+    } else {
+        per_unit_cess
+    };
+    Ok(LineAmounts {
+        taxable_amount: invoice_line.compute_taxable_amount(),
+        cess_advol: cess_amount - cess_non_advol,
+        cess_non_advol,
+    })
+}
+
+///expects data validated with [validate_eway_bill_data]
+pub(crate) fn build_eway_bill_json(
+    invoice: &EwayBillInvoiceDb,
+    parties: &EwayBillParties,
+    req: &CreateEwayBillRequest,
+) -> anyhow::Result<EwayBillJson> {
+    let ship_to = parties
+        .shipped_to
+        .as_ref()
+        .or(parties.billed_to.as_ref())
+        .ok_or_else(|| anyhow!("ship to party is mandatory for e-way bill"))?;
+    let bill_to = parties.billed_to.as_ref().unwrap_or(ship_to);
+    let amounts = invoice
+        .lines
+        .iter()
+        .map(compute_line_amounts)
+        .collect::<anyhow::Result<Vec<LineAmounts>>>()?;
+    let mut taxable_amounts: Vec<f64> = amounts.iter().map(|a| round_2(a.taxable_amount)).collect();
+    //item values are rounded separately, the difference to the taxable amount of the invoice is left on the last item
+    let residue = round_2(invoice.total_taxable_amount - taxable_amounts.iter().sum::<f64>());
+    ensure!(
+        (residue * 100.0).round().abs() <= taxable_amounts.len() as f64,
+        "line amounts of invoice {} do not add up to its taxable amount",
+        invoice.invoice_number
+    );
+    if let Some(last) = taxable_amounts.last_mut() {
+        *last = round_2(*last + residue);
+    }
+    let item_list: Vec<EwayBillItem> = invoice
+        .lines
+        .iter()
+        .zip(amounts.iter())
+        .zip(taxable_amounts)
+        .map(|((line, amounts), taxable_amount)| {
+            let (cgst_rate, sgst_rate, igst_rate) = if invoice.igst_applicable {
+                (0.0, 0.0, line.tax_percentage)
+            } else {
+                (line.tax_percentage / 2.0, line.tax_percentage / 2.0, 0.0)
+            };
+            EwayBillItem {
+                product_name: line.description.clone(),
+                product_desc: line.description.clone(),
+                hsn_code: line.hsn_code.clone().unwrap_or_default(),
+                quantity: line.quantity,
+                qty_unit: line.uqc.clone().unwrap_or_else(|| "OTH".to_string()),
+                cgst_rate,
+                sgst_rate,
+                igst_rate,
+                cess_rate: line.cess_percentage,
+                cess_nonadvol: round_2(amounts.cess_non_advol),
+                taxable_amount,
+            }
+        })
+        .collect();
+    let cess_value: f64 = amounts.iter().map(|a| a.cess_advol).sum();
+    let cess_non_advol_value: f64 = amounts.iter().map(|a| a.cess_non_advol).sum();
+    let (cgst_value, sgst_value, igst_value) = if invoice.igst_applicable {
+        (0.0, 0.0, invoice.total_tax_amount)
+    } else {
+        (
+            invoice.total_tax_amount / 2.0,
+            invoice.total_tax_amount / 2.0,
+            0.0,
+        )
+    };
+    Ok(EwayBillJson {
+        supply_type: "O".to_string(),
+        sub_supply_type: "1".to_string(),
+        doc_type: "INV".to_string(),
+        doc_no: invoice.invoice_number.clone(),
+        doc_date: epoch_ms_to_nic_date(invoice.invoice_date_ms)?,
+        from_gstin: parties.supplier.gstin.clone().unwrap_or_default(),
+        from_trd_name: parties.supplier.name.clone(),
+        from_addr1: parties.dispatch_from.addr1.clone(),
+        from_addr2: parties.dispatch_from.addr2.clone(),
+        from_place: parties.dispatch_from.place.clone(),
+        from_pincode: parties.dispatch_from.pincode.unwrap_or_default(),
+        from_state_code: parties.supplier.state_code.unwrap_or_default(),
+        act_from_state_code: parties.dispatch_from.state_code.unwrap_or_default(),
+        //unregistered person
+        to_gstin: bill_to.gstin.clone().unwrap_or_else(|| "URP".to_string()),
+        to_trd_name: bill_to.name.clone(),
+        to_addr1: ship_to.addr1.clone(),
+        to_addr2: ship_to.addr2.clone(),
+        to_place: ship_to.place.clone(),
+        to_pincode: ship_to.pincode.unwrap_or_default(),
+        to_state_code: bill_to.state_code.unwrap_or_default(),
+        act_to_state_code: ship_to.state_code.unwrap_or_default(),
+        transaction_type: transaction_type(invoice),
+        total_value: round_2(invoice.total_taxable_amount),
+        cgst_value: round_2(cgst_value),
+        sgst_value: round_2(sgst_value),
+        igst_value: round_2(igst_value),
+        cess_value: round_2(cess_value),
+        cess_non_advol_value: round_2(cess_non_advol_value),
+        other_value: round_2(invoice.total_additional_charges_amount + invoice.round_off),
+        tot_inv_value: round_2(invoice.total_payable_amount),
+        transporter_id: req
+            .transporter_id
+            .as_ref()
+            .map(|a| a.inner().to_string())
+            .unwrap_or_default(),
+        transporter_name: req
+            .transporter_name
+            .as_ref()
+            .map(|a| a.trim().to_string())
+            .unwrap_or_default(),
+        trans_doc_no: req
+            .transport_doc_no
+            .as_ref()
+            .map(|a| a.inner().to_string())
+            .unwrap_or_default(),
+        trans_mode: req
+            .transport_mode
+            .map(|a| a.nic_code().to_string())
+            .unwrap_or_default(),
+        trans_distance: req.distance_km.inner().to_string(),
+        trans_doc_date: req
+            .transport_doc_date_ms
+            .map(epoch_ms_to_nic_date)
+            .transpose()?
+            .unwrap_or_default(),
+        vehicle_no: req
+            .vehicle_no
+            .as_ref()
+            .map(|a| a.inner().to_string())
+            .unwrap_or_default(),
+        vehicle_type: req.vehicle_type.nic_code().to_string(),
+        item_list,
+    })
+}
+
+#[async_trait]
+impl EwayBillService for EwayBillServiceImpl {
+    async fn generate_eway_bill(
+        &self,
+        req: CreateEwayBillRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EwayBill, EwayBillServiceError> {
+        let mut errors = req.validate_transport_details();
+        let invoice = self
+            .dao
+            .get_invoice_for_eway_bill(req.invoice_id, tenant_id)
+            .await?
+            .ok_or(EwayBillServiceError::InvoiceNotFound(req.invoice_id))?;
+        let parties = self.fetch_parties(&invoice, tenant_id).await?;
+        errors.extend(validate_eway_bill_data(&invoice, &parties));
+        if !errors.is_empty() {
+            return Err(EwayBillServiceError::Validation(errors));
+        }
+        let eway_bill_json = build_eway_bill_json(&invoice, &parties, &req)?;
+        let id = self
+            .dao
+            .upsert_eway_bill_json(req.invoice_id, tenant_id, user_id, &eway_bill_json)
+            .await?
+            .ok_or(EwayBillServiceError::AlreadySubmitted(req.invoice_id))?;
+        Ok(EwayBill {
+            id,
+            invoice_id: req.invoice_id,
+            eway_bill_json,
+            eway_bill_no: None,
+            eway_bill_date_ms: None,
+            valid_upto_ms: None,
+        })
+    }
+
+    async fn get_eway_bill_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<EwayBill>, EwayBillServiceError> {
+        let eway_bill = self
+            .dao
+            .get_eway_bill_by_invoice_id(invoice_id, tenant_id)
+            .await?;
+        Ok(eway_bill)
+    }
+
+    async fn submit_eway_bill(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<EwayBill, EwayBillServiceError> {
+        let mut eway_bill = self
+            .dao
+            .get_eway_bill_by_invoice_id(invoice_id, tenant_id)
+            .await?
+            .ok_or(EwayBillServiceError::EwayBillNotFound(invoice_id))?;
+        if eway_bill.eway_bill_no.is_some() {
+            return Err(EwayBillServiceError::AlreadySubmitted(invoice_id));
+        }
+        let response = self
+            .portal_client
+            .generate_eway_bill(
+                eway_bill.eway_bill_json.from_gstin.as_str(),
+                &eway_bill.eway_bill_json,
+            )
+            .await?;
+        self.dao
+            .persist_eway_bill_portal_response(invoice_id, tenant_id, user_id, &response)
+            .await?;
+        eway_bill.eway_bill_no = Some(response.eway_bill_no);
+        eway_bill.eway_bill_date_ms = Some(response.eway_bill_date_ms);
+        eway_bill.valid_upto_ms = response.valid_upto_ms;
+        Ok(eway_bill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::{ResultAssertions, VecAssertions};
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::eway_bill::eway_bill_dao::MockEwayBillDao;
+    use crate::invoicing::eway_bill::eway_bill_models::tests::{
+        a_create_eway_bill_request, an_eway_bill_invoice_db, an_eway_bill_json,
+        an_eway_bill_parties,
+    };
+    use crate::invoicing::eway_bill::eway_bill_models::{EwayBill, EwayBillPortalResponse};
+    use crate::invoicing::eway_bill::eway_bill_portal_client::MockEwayBillPortalClient;
+    use crate::invoicing::eway_bill::eway_bill_service::{
+        build_eway_bill_json, validate_eway_bill_data, EwayBillService, EwayBillServiceError,
+        EwayBillServiceImpl, EWAY_BILL_THRESHOLD_INR,
+    };
+    use crate::masters::business_entity_master::business_entity_service::MockBusinessEntityService;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[test]
+    fn test_build_eway_bill_json() {
+        let invoice = an_eway_bill_invoice_db();
+        let parties = an_eway_bill_parties();
+        let req = a_create_eway_bill_request(Default::default());
+        assert_that!(validate_eway_bill_data(&invoice, &parties)).is_empty();
+        let json = build_eway_bill_json(&invoice, &parties, &req).unwrap();
+        assert_eq!(json.doc_date, "29/01/2024");
+        assert_eq!(json.transaction_type, 1);
+        assert_eq!(json.to_gstin, "URP");
+        assert_eq!(json.trans_mode, "1");
+        assert_eq!(json.item_list[0].taxable_amount, 90000.0);
+        assert_eq!(json.item_list[0].cgst_rate, 9.0);
+        assert_eq!(json.cgst_value, 8100.0);
+        assert_eq!(json.tot_inv_value, 106200.0);
+    }
+
+    #[test]
+    fn test_item_values_add_up_to_the_invoice_taxable_amount() {
+        let mut invoice = an_eway_bill_invoice_db();
+        let mut line = invoice.lines.remove(0);
+        line.quantity = 3.0;
+        line.unit_price = 33.335;
+        line.discount_percentage = 0.0;
+        line.cess_amount_per_unit = 2.0;
+        line.cess_calculation_strategy = "amount_per_unit".to_string();
+        invoice.lines = vec![line.clone(), line.clone(), line];
+        invoice.total_taxable_amount = 300.01;
+        let json = build_eway_bill_json(
+            &invoice,
+            &an_eway_bill_parties(),
+            &a_create_eway_bill_request(Default::default()),
+        )
+        .unwrap();
+        let items_total: f64 = json.item_list.iter().map(|a| a.taxable_amount).sum();
+        assert_eq!((items_total * 100.0).round(), 30001.0);
+        assert_eq!(json.cess_non_advol_value, 18.0);
+        assert_eq!(json.cess_value, 0.0);
+        invoice.total_taxable_amount = 310.0;
+        assert_that!(build_eway_bill_json(
+            &invoice,
+            &an_eway_bill_parties(),
+            &a_create_eway_bill_request(Default::default()),
+        ))
+        .is_err();
+    }
+
+    #[test]
+    fn test_validate_eway_bill_data() {
+        let mut invoice = an_eway_bill_invoice_db();
+        invoice.service_invoice = true;
+        invoice.lines[0].hsn_code = None;
+        let mut parties = an_eway_bill_parties();
+        parties.supplier.gstin = None;
+        parties.billed_to = None;
+        parties.shipped_to = None;
+        invoice.total_payable_amount_inr = EWAY_BILL_THRESHOLD_INR;
+        let errors = validate_eway_bill_data(&invoice, &parties);
+        assert_that!(errors).has_length(6);
+    }
+
+    #[tokio::test]
+    async fn test_submit_eway_bill() {
+        let invoice_id = Uuid::now_v7();
+        let mut dao = MockEwayBillDao::new();
+        dao.expect_get_eway_bill_by_invoice_id()
+            .returning(move |_, _| {
+                Ok(Some(EwayBill {
+                    id: Uuid::now_v7(),
+                    invoice_id,
+                    eway_bill_json: an_eway_bill_json(),
+                    eway_bill_no: None,
+                    eway_bill_date_ms: None,
+                    valid_upto_ms: None,
+                }))
+            });
+        dao.expect_persist_eway_bill_portal_response()
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+        let mut portal_client = MockEwayBillPortalClient::new();
+        portal_client
+            .expect_generate_eway_bill()
+            .withf(|gstin, _| gstin == "29AAFCD5862R000")
+            .returning(|_, _| {
+                Ok(EwayBillPortalResponse {
+                    eway_bill_no: 331001234567,
+                    eway_bill_date_ms: 1706534012000,
+                    valid_upto_ms: Some(1706620412000),
+                })
+            });
+        let service = EwayBillServiceImpl {
+            dao: Arc::new(dao),
+            business_entity_service: Arc::new(MockBusinessEntityService::new()),
+            portal_client: Arc::new(portal_client),
+        };
+        let eway_bill = service
+            .submit_eway_bill(invoice_id, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await
+            .unwrap();
+        assert_eq!(eway_bill.eway_bill_no, Some(331001234567));
+    }
+
+    #[tokio::test]
+    async fn test_submit_already_submitted_eway_bill() {
+        let mut dao = MockEwayBillDao::new();
+        dao.expect_get_eway_bill_by_invoice_id()
+            .returning(|invoice_id, _| {
+                Ok(Some(EwayBill {
+                    id: Uuid::now_v7(),
+                    invoice_id,
+                    eway_bill_json: an_eway_bill_json(),
+                    eway_bill_no: Some(331001234567),
+                    eway_bill_date_ms: Some(1706534012000),
+                    valid_upto_ms: None,
+                }))
+            });
+        let service = EwayBillServiceImpl {
+            dao: Arc::new(dao),
+            business_entity_service: Arc::new(MockBusinessEntityService::new()),
+            portal_client: Arc::new(MockEwayBillPortalClient::new()),
+        };
+        let result = service
+            .submit_eway_bill(Uuid::now_v7(), *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert_that!(result)
+            .is_err()
+            .matches(|a| matches!(a, EwayBillServiceError::AlreadySubmitted(_)));
+    }
+}
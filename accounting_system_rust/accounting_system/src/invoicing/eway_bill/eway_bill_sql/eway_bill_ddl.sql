@@ -0,0 +1,20 @@
+-- one e-way bill document per invoice. json is regenerated till it is submitted to the portal
+create table eway_bill
+(
+    id                uuid primary key,
+    entity_version_id integer default 0,
+    tenant_id         uuid references tenant (id)   not null,
+    active            bool,
+    approval_status   smallint                      not null,
+    remarks           varchar(70),
+    invoice_id        uuid references invoice (id)  not null,
+    eway_bill_json    jsonb                         not null,
+    eway_bill_no      bigint,
+    eway_bill_date_ms bigint,
+    valid_upto_ms     bigint,
+    created_by        uuid references app_user (id) not null,
+    updated_by        uuid references app_user (id),
+    created_at        bigint  default extract(epoch from now()) * 1000000,
+    updated_at        bigint  default extract(epoch from now()) * 1000000,
+    unique (tenant_id, invoice_id)
+);
@@ -0,0 +1,6 @@
+mod eway_bill_dao;
+pub mod eway_bill_db_mapping;
+pub mod eway_bill_http_api;
+pub mod eway_bill_models;
+pub mod eway_bill_portal_client;
+pub mod eway_bill_service;
@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::invoice_approval::invoice_approval_models::{
+    InvoiceApprover, InvoiceStatus, InvoiceStatusTransition, InvoiceWorkflowState,
+};
+
+const APPROVERS_QUERY: &str = "select user_id,created_by,created_at from invoice_approver \
+where tenant_id=$1 order by created_at";
+
+const IS_APPROVER_QUERY: &str =
+    "select exists(select 1 from invoice_approver where tenant_id=$1 and user_id=$2)";
+
+const ADD_APPROVER: &str = "insert into invoice_approver (id,tenant_id,user_id,created_by) \
+values (uuid_generate_v7(),$1,$2,$3) on conflict (tenant_id,user_id) do nothing";
+
+const REMOVE_APPROVER: &str = "delete from invoice_approver where tenant_id=$1 and user_id=$2";
+
+const WORKFLOW_STATE_QUERY: &str =
+    "select id,invoice_status::text,created_by from invoice where id=$1 and tenant_id=$2";
+
+const TRANSITION_INVOICE_STATUS: &str = "select transition_invoice_status($1,$2,$3,$4,$5,$6)";
+
+const TRANSITIONS_QUERY: &str = "select invoice_id,from_status::text,to_status::text,remarks,\
+created_by,created_at from invoice_status_transition where invoice_id=$1 and tenant_id=$2 \
+order by created_at";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait InvoiceApprovalDao: Send + Sync {
+    async fn get_approvers(&self, tenant_id: Uuid) -> Result<Vec<InvoiceApprover>, DaoError>;
+    async fn is_approver(&self, tenant_id: Uuid, user_id: Uuid) -> Result<bool, DaoError>;
+    async fn add_approver(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        created_by: Uuid,
+    ) -> Result<(), DaoError>;
+    ///returns false if the user was not an approver
+    async fn remove_approver(&self, tenant_id: Uuid, user_id: Uuid) -> Result<bool, DaoError>;
+    async fn get_invoice_workflow_state(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<InvoiceWorkflowState>, DaoError>;
+    ///moves the invoice to the to status and records the transition,
+    ///returns false if the invoice was not in the from status
+    async fn transition_invoice_status(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        from: InvoiceStatus,
+        to: InvoiceStatus,
+        remarks: Option<String>,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn get_status_transitions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceStatusTransition>, DaoError>;
+}
+
+struct InvoiceApprovalDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_invoice_approval_dao(arc: Arc<Pool>) -> Arc<dyn InvoiceApprovalDao> {
+    let dao = InvoiceApprovalDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for InvoiceApprover {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(InvoiceApprover {
+            user_id: row.get(0),
+            created_by: row.get(1),
+            created_at: row.get(2),
+        })
+    }
+}
+
+impl TryFrom<Row> for InvoiceWorkflowState {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let status: &str = row.get(1);
+        Ok(InvoiceWorkflowState {
+            invoice_id: row.get(0),
+            status: InvoiceStatus::from_db_str(status)?,
+            created_by: row.get(2),
+        })
+    }
+}
+
+impl TryFrom<Row> for InvoiceStatusTransition {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let from_status: Option<&str> = row.get(1);
+        let to_status: &str = row.get(2);
+        Ok(InvoiceStatusTransition {
+            invoice_id: row.get(0),
+            from_status: from_status.map(InvoiceStatus::from_db_str).transpose()?,
+            to_status: InvoiceStatus::from_db_str(to_status)?,
+            remarks: row.get(3),
+            created_by: row.get(4),
+            created_at: row.get(5),
+        })
+    }
+}
+
+#[async_trait]
+impl InvoiceApprovalDao for InvoiceApprovalDaoImpl {
+    async fn get_approvers(&self, tenant_id: Uuid) -> Result<Vec<InvoiceApprover>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(APPROVERS_QUERY, &[&tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn is_approver(&self, tenant_id: Uuid, user_id: Uuid) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(IS_APPROVER_QUERY, &[&tenant_id, &user_id])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn add_approver(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        created_by: Uuid,
+    ) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(ADD_APPROVER, &[&tenant_id, &user_id, &created_by])
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_approver(&self, tenant_id: Uuid, user_id: Uuid) -> Result<bool, DaoError> {
+        let deleted = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(REMOVE_APPROVER, &[&tenant_id, &user_id])
+            .await?;
+        Ok(deleted != 0)
+    }
+
+    async fn get_invoice_workflow_state(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<InvoiceWorkflowState>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query_opt(WORKFLOW_STATE_QUERY, &[&invoice_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()
+    }
+
+    async fn transition_invoice_status(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        from: InvoiceStatus,
+        to: InvoiceStatus,
+        remarks: Option<String>,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                TRANSITION_INVOICE_STATUS,
+                &[&tenant_id, &invoice_id, &from, &to, &remarks, &user_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn get_status_transitions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceStatusTransition>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(TRANSITIONS_QUERY, &[&invoice_id, &tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::boolean::BooleanAssertions;
+    use speculoos::option::OptionAssertions;
+    use speculoos::prelude::VecAssertions;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoice_approval::invoice_approval_dao::{
+        InvoiceApprovalDao, InvoiceApprovalDaoImpl,
+    };
+    use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_add_and_remove_approver() {
+        let dao = get_dao_generic(|c| InvoiceApprovalDaoImpl { postgres_client: c }, None).await;
+        dao.add_approver(*SEED_TENANT_ID, *SEED_USER_ID, *SEED_USER_ID)
+            .await
+            .unwrap();
+        dao.add_approver(*SEED_TENANT_ID, *SEED_USER_ID, *SEED_USER_ID)
+            .await
+            .unwrap();
+        assert_that!(dao.get_approvers(*SEED_TENANT_ID).await.unwrap()).has_length(1);
+        assert_that!(dao
+            .is_approver(*SEED_TENANT_ID, *SEED_USER_ID)
+            .await
+            .unwrap())
+        .is_true();
+        assert_that!(dao
+            .remove_approver(*SEED_TENANT_ID, *SEED_USER_ID)
+            .await
+            .unwrap())
+        .is_true();
+        assert_that!(dao
+            .is_approver(*SEED_TENANT_ID, *SEED_USER_ID)
+            .await
+            .unwrap())
+        .is_false();
+    }
+
+    #[tokio::test]
+    async fn test_transition_only_from_current_status() {
+        let dao = get_dao_generic(|c| InvoiceApprovalDaoImpl { postgres_client: c }, None).await;
+        let state = dao
+            .get_invoice_workflow_state(*SEED_INVOICE_ID, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(state.map(|a| a.status))
+            .is_some()
+            .is_equal_to(InvoiceStatus::Issued);
+        let moved = dao
+            .transition_invoice_status(
+                *SEED_INVOICE_ID,
+                *SEED_TENANT_ID,
+                InvoiceStatus::PendingApproval,
+                InvoiceStatus::Approved,
+                None,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(moved).is_false();
+        let transitions = dao
+            .get_status_transitions(*SEED_INVOICE_ID, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(transitions).is_empty();
+    }
+}
@@ -0,0 +1,53 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct InvoiceApprovalDbMapping {}
+
+const INVOICE_APPROVAL_DDL_SQL: &str =
+    include_str!("./invoice_approval_sql/invoice_approval_ddl.sql");
+const INVOICE_APPROVAL_SEED_DATA: &str =
+    include_str!("./invoice_approval_sql/invoice_approver.csv");
+const INVOICE_APPROVAL_INDEXES_SQL: &str =
+    include_str!("./invoice_approval_sql/invoice_approval_indexes.sql");
+const INVOICE_APPROVAL_FUNCTIONS_SQL: &str =
+    include_str!("./invoice_approval_sql/invoice_approval_functions_and_procedures.sql");
+impl DbStructMapping for InvoiceApprovalDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("invoice_approver")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        INVOICE_APPROVAL_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        INVOICE_APPROVAL_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        INVOICE_APPROVAL_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        INVOICE_APPROVAL_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::invoice_approval::invoice_approval_models::{
+    AddInvoiceApproverRequest, InvoiceTransitionRequest,
+};
+use crate::invoicing::invoice_approval::invoice_approval_service::{
+    InvoiceApprovalService, InvoiceApprovalServiceError,
+};
+use crate::setup_routes;
+
+impl ResponseError for InvoiceApprovalServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InvoiceApprovalServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InvoiceApprovalServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            InvoiceApprovalServiceError::InvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            InvoiceApprovalServiceError::InvalidTransition { .. } => StatusCode::CONFLICT,
+            InvoiceApprovalServiceError::Unauthorized { .. } => StatusCode::FORBIDDEN,
+            InvoiceApprovalServiceError::ApproverNotFound(_) => StatusCode::NOT_FOUND,
+            InvoiceApprovalServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn get_approvers(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data.get_approvers(tenant_id.inner()).await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn add_approver(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    request: web::Json<AddInvoiceApproverRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.add_approver(&request, tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+async fn remove_approver(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    approver_user_id: Path<Uuid>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.remove_approver(
+        approver_user_id.into_inner(),
+        tenant_id.inner(),
+        user_id.inner(),
+    )
+    .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+async fn submit_for_approval(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    invoice_id: Path<Uuid>,
+    request: web::Json<InvoiceTransitionRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .submit_for_approval(
+            invoice_id.into_inner(),
+            &request,
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn approve_invoice(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    invoice_id: Path<Uuid>,
+    request: web::Json<InvoiceTransitionRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .approve_invoice(
+            invoice_id.into_inner(),
+            &request,
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn reject_invoice(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    invoice_id: Path<Uuid>,
+    request: web::Json<InvoiceTransitionRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .reject_invoice(
+            invoice_id.into_inner(),
+            &request,
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_status_transitions(
+    data: Data<Arc<dyn InvoiceApprovalService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_status_transitions(invoice_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    InvoiceApprovalService,
+    "/invoice-approval",
+    "/approvers",
+    web::get().to(get_approvers),
+    "/approver/add",
+    web::post().to(add_approver),
+    "/approver/{user_id}/remove",
+    web::post().to(remove_approver),
+    "/invoice-id/{invoice_id}/submit",
+    web::post().to(submit_for_approval),
+    "/invoice-id/{invoice_id}/approve",
+    web::post().to(approve_invoice),
+    "/invoice-id/{invoice_id}/reject",
+    web::post().to(reject_invoice),
+    "/invoice-id/{invoice_id}/transitions",
+    web::get().to(get_status_transitions)
+);
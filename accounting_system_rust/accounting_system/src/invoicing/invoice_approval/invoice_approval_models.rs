@@ -0,0 +1,144 @@
+use anyhow::{bail, ensure};
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///lifecycle of an invoice, a number from the invoicing series is assigned only on issue
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "invoice_status", rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    Draft,
+    PendingApproval,
+    Approved,
+    Issued,
+}
+
+impl InvoiceStatus {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let status = match value {
+            "draft" => InvoiceStatus::Draft,
+            "pending_approval" => InvoiceStatus::PendingApproval,
+            "approved" => InvoiceStatus::Approved,
+            "issued" => InvoiceStatus::Issued,
+            _ => bail!("{} is not a valid invoice status", value),
+        };
+        Ok(status)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct TransitionRemarks(String);
+
+impl TransitionRemarks {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "transition remarks cannot be empty");
+        ensure!(
+            value.chars().count() <= 100,
+            "transition remarks cannot be more than 100 chars"
+        );
+        Ok(TransitionRemarks(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for TransitionRemarks {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        TransitionRemarks::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct InvoiceTransitionRequest {
+    pub remarks: Option<TransitionRemarks>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AddInvoiceApproverRequest {
+    pub user_id: Uuid,
+    ///tenant of the approver when the super user seeds the first approver of another tenant
+    #[serde(default)]
+    pub tenant_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceApprover {
+    pub user_id: Uuid,
+    pub created_by: Uuid,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceStatusTransition {
+    pub invoice_id: Uuid,
+    ///none for the creation of the draft
+    pub from_status: Option<InvoiceStatus>,
+    pub to_status: InvoiceStatus,
+    pub remarks: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: i64,
+}
+
+///current status of an invoice along with the user who created it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct InvoiceWorkflowState {
+    pub invoice_id: Uuid,
+    pub status: InvoiceStatus,
+    pub created_by: Uuid,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use speculoos::assert_that;
+    use speculoos::prelude::ResultAssertions;
+    use uuid::Uuid;
+
+    use crate::invoicing::invoice_approval::invoice_approval_models::{
+        InvoiceStatus, InvoiceWorkflowState, TransitionRemarks,
+    };
+
+    pub(crate) fn an_invoice_workflow_state(
+        status: InvoiceStatus,
+        created_by: Uuid,
+    ) -> InvoiceWorkflowState {
+        InvoiceWorkflowState {
+            invoice_id: Uuid::now_v7(),
+            status,
+            created_by,
+        }
+    }
+
+    #[rstest]
+    #[case("needs review", true)]
+    #[case("   ", false)]
+    #[case(& "a".repeat(101), false)]
+    fn test_transition_remarks(#[case] input: &str, #[case] valid: bool) {
+        let remarks = TransitionRemarks::new(input);
+        if valid {
+            assert_that!(remarks).is_ok();
+        } else {
+            assert_that!(remarks).is_err();
+        }
+    }
+
+    #[test]
+    fn test_invoice_status_serde_matches_db_names() {
+        for status in [
+            InvoiceStatus::Draft,
+            InvoiceStatus::PendingApproval,
+            InvoiceStatus::Approved,
+            InvoiceStatus::Issued,
+        ] {
+            let json = serde_json::to_value(status).unwrap();
+            let parsed = InvoiceStatus::from_db_str(json.as_str().unwrap()).unwrap();
+            assert_that!(parsed).is_equal_to(status);
+        }
+    }
+}
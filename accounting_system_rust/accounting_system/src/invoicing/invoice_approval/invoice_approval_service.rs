@@ -0,0 +1,596 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::invoice_approval::invoice_approval_dao::{
+    get_invoice_approval_dao, InvoiceApprovalDao,
+};
+use crate::invoicing::invoice_approval::invoice_approval_models::{
+    AddInvoiceApproverRequest, InvoiceApprover, InvoiceStatus, InvoiceStatusTransition,
+    InvoiceTransitionRequest, InvoiceWorkflowState,
+};
+use crate::tenant::tenant_service::SUPER_USER_ID;
+
+#[derive(Debug, Error)]
+pub enum InvoiceApprovalServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("invoice {0} not found")]
+    InvoiceNotFound(Uuid),
+    #[error("invoice {invoice_id} cannot move from {from:?} to {to:?}")]
+    InvalidTransition {
+        invoice_id: Uuid,
+        from: InvoiceStatus,
+        to: InvoiceStatus,
+    },
+    #[error("user {user_id} is not allowed to {action}")]
+    Unauthorized { user_id: Uuid, action: String },
+    #[error("approver {0} not found")]
+    ApproverNotFound(Uuid),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait InvoiceApprovalService: Send + Sync {
+    async fn get_approvers(
+        &self,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceApprover>, InvoiceApprovalServiceError>;
+    ///only approvers of the tenant can add more approvers. the first approver of a tenant is seeded by the
+    /// super user, who can add approvers to any tenant
+    async fn add_approver(
+        &self,
+        req: &AddInvoiceApproverRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError>;
+    async fn remove_approver(
+        &self,
+        approver_user_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError>;
+    ///true if the tenant has approvers configured, invoices must then go through the draft workflow
+    async fn is_approval_required(
+        &self,
+        tenant_id: Uuid,
+    ) -> Result<bool, InvoiceApprovalServiceError>;
+    ///draft can be edited only by its creator or an approver
+    async fn authorize_draft_edit(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError>;
+    ///approved invoice can be issued only by its creator or an approver
+    async fn authorize_issue(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError>;
+    async fn submit_for_approval(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError>;
+    async fn approve_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError>;
+    ///sends a pending or approved invoice back to draft, remarks are mandatory so that the creator
+    ///knows what to change
+    async fn reject_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError>;
+    async fn get_status_transitions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceStatusTransition>, InvoiceApprovalServiceError>;
+}
+
+struct InvoiceApprovalServiceImpl {
+    dao: Arc<dyn InvoiceApprovalDao>,
+}
+
+pub fn get_invoice_approval_service(arc: Arc<Pool>) -> Arc<dyn InvoiceApprovalService> {
+    let dao = get_invoice_approval_dao(arc);
+    let service = InvoiceApprovalServiceImpl { dao };
+    Arc::new(service)
+}
+
+impl InvoiceApprovalServiceImpl {
+    async fn get_state(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<InvoiceWorkflowState, InvoiceApprovalServiceError> {
+        self.dao
+            .get_invoice_workflow_state(invoice_id, tenant_id)
+            .await?
+            .ok_or(InvoiceApprovalServiceError::InvoiceNotFound(invoice_id))
+    }
+
+    async fn ensure_approver(
+        &self,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        action: &str,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        if self.dao.is_approver(tenant_id, user_id).await? {
+            Ok(())
+        } else {
+            Err(InvoiceApprovalServiceError::Unauthorized {
+                user_id,
+                action: action.to_string(),
+            })
+        }
+    }
+
+    async fn ensure_creator_or_approver(
+        &self,
+        state: &InvoiceWorkflowState,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        action: &str,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        if state.created_by == user_id {
+            return Ok(());
+        }
+        self.ensure_approver(tenant_id, user_id, action).await
+    }
+
+    async fn transition(
+        &self,
+        state: &InvoiceWorkflowState,
+        to: InvoiceStatus,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError> {
+        let moved = self
+            .dao
+            .transition_invoice_status(
+                state.invoice_id,
+                tenant_id,
+                state.status,
+                to,
+                req.remarks.as_ref().map(|a| a.inner().to_string()),
+                user_id,
+            )
+            .await?;
+        if !moved {
+            return Err(invalid_transition(state, to));
+        }
+        Ok(to)
+    }
+}
+
+fn invalid_transition(
+    state: &InvoiceWorkflowState,
+    to: InvoiceStatus,
+) -> InvoiceApprovalServiceError {
+    InvoiceApprovalServiceError::InvalidTransition {
+        invoice_id: state.invoice_id,
+        from: state.status,
+        to,
+    }
+}
+
+fn ensure_status(
+    state: &InvoiceWorkflowState,
+    expected: InvoiceStatus,
+    to: InvoiceStatus,
+) -> Result<(), InvoiceApprovalServiceError> {
+    if state.status == expected {
+        Ok(())
+    } else {
+        Err(invalid_transition(state, to))
+    }
+}
+
+#[async_trait]
+impl InvoiceApprovalService for InvoiceApprovalServiceImpl {
+    async fn get_approvers(
+        &self,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceApprover>, InvoiceApprovalServiceError> {
+        let approvers = self.dao.get_approvers(tenant_id).await?;
+        Ok(approvers)
+    }
+
+    async fn add_approver(
+        &self,
+        req: &AddInvoiceApproverRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        let approver_tenant_id = req.tenant_id.unwrap_or(tenant_id);
+        if user_id != *SUPER_USER_ID {
+            if approver_tenant_id != tenant_id {
+                return Err(InvoiceApprovalServiceError::Unauthorized {
+                    user_id,
+                    action: format!("add invoice approvers to tenant {}", approver_tenant_id),
+                });
+            }
+            self.ensure_approver(tenant_id, user_id, "add invoice approvers")
+                .await?;
+        }
+        self.dao
+            .add_approver(approver_tenant_id, req.user_id, user_id)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_approver(
+        &self,
+        approver_user_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        self.ensure_approver(tenant_id, user_id, "remove invoice approvers")
+            .await?;
+        let removed = self
+            .dao
+            .remove_approver(tenant_id, approver_user_id)
+            .await?;
+        if !removed {
+            return Err(InvoiceApprovalServiceError::ApproverNotFound(
+                approver_user_id,
+            ));
+        }
+        Ok(())
+    }
+
+    async fn is_approval_required(
+        &self,
+        tenant_id: Uuid,
+    ) -> Result<bool, InvoiceApprovalServiceError> {
+        let approvers = self.dao.get_approvers(tenant_id).await?;
+        Ok(!approvers.is_empty())
+    }
+
+    async fn authorize_draft_edit(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        let state = self.get_state(invoice_id, tenant_id).await?;
+        ensure_status(&state, InvoiceStatus::Draft, InvoiceStatus::Draft)?;
+        self.ensure_creator_or_approver(&state, tenant_id, user_id, "edit this draft invoice")
+            .await
+    }
+
+    async fn authorize_issue(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoiceApprovalServiceError> {
+        let state = self.get_state(invoice_id, tenant_id).await?;
+        //issuing an issued invoice again returns the same number, so retries are allowed
+        if state.status != InvoiceStatus::Issued {
+            ensure_status(&state, InvoiceStatus::Approved, InvoiceStatus::Issued)?;
+        }
+        self.ensure_creator_or_approver(&state, tenant_id, user_id, "issue this invoice")
+            .await
+    }
+
+    async fn submit_for_approval(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError> {
+        let state = self.get_state(invoice_id, tenant_id).await?;
+        ensure_status(&state, InvoiceStatus::Draft, InvoiceStatus::PendingApproval)?;
+        self.ensure_creator_or_approver(&state, tenant_id, user_id, "submit this draft invoice")
+            .await?;
+        if !self.is_approval_required(tenant_id).await? {
+            return Err(InvoiceApprovalServiceError::Validation(vec![
+                "no invoice approvers are configured for this tenant".to_string(),
+            ]));
+        }
+        self.transition(
+            &state,
+            InvoiceStatus::PendingApproval,
+            req,
+            tenant_id,
+            user_id,
+        )
+        .await
+    }
+
+    async fn approve_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError> {
+        let state = self.get_state(invoice_id, tenant_id).await?;
+        ensure_status(
+            &state,
+            InvoiceStatus::PendingApproval,
+            InvoiceStatus::Approved,
+        )?;
+        self.ensure_approver(tenant_id, user_id, "approve invoices")
+            .await?;
+        self.transition(&state, InvoiceStatus::Approved, req, tenant_id, user_id)
+            .await
+    }
+
+    async fn reject_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: &InvoiceTransitionRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoiceStatus, InvoiceApprovalServiceError> {
+        if req.remarks.is_none() {
+            return Err(InvoiceApprovalServiceError::Validation(vec![
+                "remarks are mandatory while rejecting an invoice".to_string(),
+            ]));
+        }
+        let state = self.get_state(invoice_id, tenant_id).await?;
+        if state.status != InvoiceStatus::Approved {
+            ensure_status(&state, InvoiceStatus::PendingApproval, InvoiceStatus::Draft)?;
+        }
+        self.ensure_approver(tenant_id, user_id, "reject invoices")
+            .await?;
+        self.transition(&state, InvoiceStatus::Draft, req, tenant_id, user_id)
+            .await
+    }
+
+    async fn get_status_transitions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceStatusTransition>, InvoiceApprovalServiceError> {
+        let transitions = self
+            .dao
+            .get_status_transitions(invoice_id, tenant_id)
+            .await?;
+        Ok(transitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::ResultAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoice_approval::invoice_approval_dao::MockInvoiceApprovalDao;
+    use crate::invoicing::invoice_approval::invoice_approval_models::tests::an_invoice_workflow_state;
+    use crate::invoicing::invoice_approval::invoice_approval_models::{
+        AddInvoiceApproverRequest, InvoiceStatus, InvoiceTransitionRequest, TransitionRemarks,
+    };
+    use crate::invoicing::invoice_approval::invoice_approval_service::{
+        InvoiceApprovalService, InvoiceApprovalServiceError, InvoiceApprovalServiceImpl,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_dao_with_state(status: InvoiceStatus, created_by: Uuid) -> MockInvoiceApprovalDao {
+        let mut dao = MockInvoiceApprovalDao::new();
+        let state = an_invoice_workflow_state(status, created_by);
+        dao.expect_get_invoice_workflow_state()
+            .returning(move |_, _| Ok(Some(state.clone())));
+        dao
+    }
+
+    #[tokio::test]
+    async fn test_submit_without_approvers() {
+        let mut dao = a_dao_with_state(InvoiceStatus::Draft, *SEED_USER_ID);
+        dao.expect_get_approvers().returning(|_| Ok(vec![]));
+        dao.expect_transition_invoice_status().never();
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .submit_for_approval(
+                Uuid::now_v7(),
+                &Default::default(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_approve_by_approver() {
+        let mut dao = a_dao_with_state(InvoiceStatus::PendingApproval, Uuid::now_v7());
+        dao.expect_is_approver().returning(|_, _| Ok(true));
+        dao.expect_transition_invoice_status()
+            .times(1)
+            .returning(|_, _, _, _, _, _| Ok(true));
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .approve_invoice(
+                Uuid::now_v7(),
+                &Default::default(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert_that!(resp)
+            .is_ok()
+            .is_equal_to(InvoiceStatus::Approved);
+    }
+
+    #[tokio::test]
+    async fn test_approve_by_non_approver() {
+        let mut dao = a_dao_with_state(InvoiceStatus::PendingApproval, *SEED_USER_ID);
+        dao.expect_is_approver().returning(|_, _| Ok(false));
+        dao.expect_transition_invoice_status().never();
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .approve_invoice(
+                Uuid::now_v7(),
+                &Default::default(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::Unauthorized { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_approve_draft_is_invalid_transition() {
+        let mut dao = a_dao_with_state(InvoiceStatus::Draft, *SEED_USER_ID);
+        dao.expect_transition_invoice_status().never();
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .approve_invoice(
+                Uuid::now_v7(),
+                &Default::default(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::InvalidTransition {
+                from: InvoiceStatus::Draft,
+                to: InvoiceStatus::Approved,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reject_needs_remarks() {
+        let mut dao = a_dao_with_state(InvoiceStatus::PendingApproval, Uuid::now_v7());
+        dao.expect_is_approver().returning(|_, _| Ok(true));
+        dao.expect_transition_invoice_status()
+            .times(1)
+            .returning(|_, _, _, to, remarks, _| {
+                Ok(to == InvoiceStatus::Draft && remarks.is_some())
+            });
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let without_remarks = service
+            .reject_invoice(
+                Uuid::now_v7(),
+                &Default::default(),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            without_remarks,
+            Err(InvoiceApprovalServiceError::Validation(_))
+        ));
+        let req = InvoiceTransitionRequest {
+            remarks: Some(TransitionRemarks::new("wrong customer").unwrap()),
+        };
+        let with_remarks = service
+            .reject_invoice(Uuid::now_v7(), &req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert_that!(with_remarks)
+            .is_ok()
+            .is_equal_to(InvoiceStatus::Draft);
+    }
+
+    #[tokio::test]
+    async fn test_issue_by_other_user() {
+        let mut dao = a_dao_with_state(InvoiceStatus::Approved, Uuid::now_v7());
+        dao.expect_is_approver().returning(|_, _| Ok(false));
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .authorize_issue(Uuid::now_v7(), *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::Unauthorized { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_approver() {
+        let user_id = Uuid::now_v7();
+        let req = AddInvoiceApproverRequest {
+            user_id: Uuid::now_v7(),
+            tenant_id: None,
+        };
+        let mut dao = MockInvoiceApprovalDao::new();
+        dao.expect_is_approver().returning(|_, _| Ok(false));
+        dao.expect_add_approver().never();
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service.add_approver(&req, *SEED_TENANT_ID, user_id).await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::Unauthorized { .. })
+        ));
+
+        let mut dao = MockInvoiceApprovalDao::new();
+        dao.expect_is_approver().returning(|_, _| Ok(true));
+        dao.expect_add_approver()
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service.add_approver(&req, *SEED_TENANT_ID, user_id).await;
+        assert_that!(resp).is_ok();
+    }
+
+    #[tokio::test]
+    async fn test_first_approver_is_seeded_by_the_super_user() {
+        let other_tenant_id = Uuid::now_v7();
+        let req = AddInvoiceApproverRequest {
+            user_id: Uuid::now_v7(),
+            tenant_id: Some(other_tenant_id),
+        };
+        let mut dao = MockInvoiceApprovalDao::new();
+        dao.expect_is_approver().never();
+        dao.expect_add_approver()
+            .withf(move |tenant_id, _, _| *tenant_id == other_tenant_id)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .add_approver(&req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert_that!(resp).is_ok();
+
+        let mut dao = MockInvoiceApprovalDao::new();
+        dao.expect_is_approver().returning(|_, _| Ok(true));
+        dao.expect_add_approver().never();
+        let service = InvoiceApprovalServiceImpl { dao: Arc::new(dao) };
+        let resp = service
+            .add_approver(&req, *SEED_TENANT_ID, Uuid::now_v7())
+            .await;
+        assert!(matches!(
+            resp,
+            Err(InvoiceApprovalServiceError::Unauthorized { .. })
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+-- users of a tenant who can approve draft invoices
+create table invoice_approver
+(
+    id         uuid primary key,
+    tenant_id  uuid references tenant (id)   not null,
+    user_id    uuid references app_user (id) not null,
+    created_by uuid references app_user (id) not null,
+    created_at bigint default extract(epoch from now()) * 1000000,
+    unique (tenant_id, user_id)
+);
+
+-- every status change of an invoice along with who made it
+create table invoice_status_transition
+(
+    id          uuid primary key,
+    tenant_id   uuid references tenant (id)   not null,
+    invoice_id  uuid references invoice (id)  not null,
+    from_status invoice_status,--null when the draft is created
+    to_status   invoice_status                not null,
+    remarks     varchar(100),
+    created_by  uuid references app_user (id) not null,
+    created_at  bigint default extract(epoch from now()) * 1000000
+);
@@ -0,0 +1,45 @@
+create trigger invoice_approver_audit_trigger
+    after update or delete
+    on invoice_approver
+    for each row
+execute function create_audit_entry();
+
+create or replace procedure record_invoice_status_transition(_tenant_id uuid, _invoice_id uuid,
+                                                             _from_status invoice_status,
+                                                             _to_status invoice_status, _remarks text,
+                                                             _created_by uuid) as
+$$
+BEGIN
+    insert into invoice_status_transition (id, tenant_id, invoice_id, from_status, to_status, remarks, created_by,
+                                           created_at)
+    values (uuid_generate_v7(), _tenant_id, _invoice_id, _from_status, _to_status, _remarks, _created_by, default);
+end;
+$$ language plpgsql;
+
+-- moves the invoice from one status to another, returns false if the invoice is not in the from status.
+-- approval_status follows the master status convention: 1 approved, 2 changes requested (sent back to draft),
+-- 0 otherwise
+create or replace function transition_invoice_status(_tenant_id uuid, _invoice_id uuid, _from_status invoice_status,
+                                                     _to_status invoice_status, _remarks text,
+                                                     _user_id uuid) returns bool as
+$$
+BEGIN
+    update invoice
+    set invoice_status=_to_status,
+        approval_status=case
+                            when _to_status = 'approved' then 1
+                            when _to_status = 'draft' then 2
+                            else 0 end,
+        entity_version_id=entity_version_id + 1,
+        updated_by=_user_id,
+        updated_at=extract(epoch from now()) * 1000000
+    where id = _invoice_id
+      and tenant_id = _tenant_id
+      and invoice_status = _from_status;
+    if not found then
+        return false;
+    end if;
+    call record_invoice_status_transition(_tenant_id, _invoice_id, _from_status, _to_status, _remarks, _user_id);
+    return true;
+end;
+$$ language plpgsql;
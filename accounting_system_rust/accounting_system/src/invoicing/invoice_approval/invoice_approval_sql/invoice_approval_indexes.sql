@@ -0,0 +1 @@
+create index if not exists invoice_status_transition_invoice_idx on invoice_status_transition (tenant_id, invoice_id);
@@ -0,0 +1,5 @@
+mod invoice_approval_dao;
+pub mod invoice_approval_db_mapping;
+pub mod invoice_approval_http_api;
+pub mod invoice_approval_models;
+pub mod invoice_approval_service;
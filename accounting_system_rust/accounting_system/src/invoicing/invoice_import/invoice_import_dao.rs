@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::invoice_import::invoice_import_models::{
+    ImportBusinessEntityDb, ImportProductDb,
+};
+
+const PRODUCTS_BY_TITLE_OR_HSN_QUERY: &str = "select id,title,hsn_sac_code from product_item \
+where tenant_id=$1 and active and (lower(title)=any($2) or hsn_sac_code=any($3))";
+
+const BUSINESS_ENTITIES_BY_GSTIN_OR_NAME_QUERY: &str = "select id,name,gstin from business_entity \
+where tenant_id=$1 and active and (upper(gstin)=any($2) or lower(name)=any($3))";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait InvoiceImportDao: Send + Sync {
+    ///titles are expected in lower case
+    async fn find_products(
+        &self,
+        tenant_id: Uuid,
+        titles: &[String],
+        hsn_sac_codes: &[String],
+    ) -> Result<Vec<ImportProductDb>, DaoError>;
+    ///gstins are expected in upper case and names in lower case
+    async fn find_business_entities(
+        &self,
+        tenant_id: Uuid,
+        gstins: &[String],
+        names: &[String],
+    ) -> Result<Vec<ImportBusinessEntityDb>, DaoError>;
+}
+
+struct InvoiceImportDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_invoice_import_dao(arc: Arc<Pool>) -> Arc<dyn InvoiceImportDao> {
+    let dao = InvoiceImportDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl From<Row> for ImportProductDb {
+    fn from(row: Row) -> Self {
+        ImportProductDb {
+            id: row.get(0),
+            title: row.get(1),
+            hsn_sac_code: row.get(2),
+        }
+    }
+}
+
+impl From<Row> for ImportBusinessEntityDb {
+    fn from(row: Row) -> Self {
+        ImportBusinessEntityDb {
+            id: row.get(0),
+            name: row.get(1),
+            gstin: row.get(2),
+        }
+    }
+}
+
+#[async_trait]
+impl InvoiceImportDao for InvoiceImportDaoImpl {
+    async fn find_products(
+        &self,
+        tenant_id: Uuid,
+        titles: &[String],
+        hsn_sac_codes: &[String],
+    ) -> Result<Vec<ImportProductDb>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(
+                PRODUCTS_BY_TITLE_OR_HSN_QUERY,
+                &[&tenant_id, &titles, &hsn_sac_codes],
+            )
+            .await?;
+        Ok(rows.into_iter().map(|a| a.into()).collect())
+    }
+
+    async fn find_business_entities(
+        &self,
+        tenant_id: Uuid,
+        gstins: &[String],
+        names: &[String],
+    ) -> Result<Vec<ImportBusinessEntityDb>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(
+                BUSINESS_ENTITIES_BY_GSTIN_OR_NAME_QUERY,
+                &[&tenant_id, &gstins, &names],
+            )
+            .await?;
+        Ok(rows.into_iter().map(|a| a.into()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::invoicing::invoice_import::invoice_import_dao::{
+        InvoiceImportDao, InvoiceImportDaoImpl,
+    };
+    use crate::masters::business_entity_master::business_entity_models::tests::SEED_BUSINESS_ENTITY_ID2;
+    use crate::masters::product_item_master::product_item_models::tests::SEED_PRODUCT_ITEM_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_find_products_and_business_entities() {
+        let dao = get_dao_generic(|c| InvoiceImportDaoImpl { postgres_client: c }, None).await;
+        let products = dao
+            .find_products(*SEED_TENANT_ID, &["product 1".to_string()], &[])
+            .await
+            .unwrap();
+        assert_that!(products.iter().map(|a| a.id).collect::<Vec<_>>())
+            .is_equal_to(vec![*SEED_PRODUCT_ITEM_ID]);
+        let entities = dao
+            .find_business_entities(*SEED_TENANT_ID, &["06MFNMS5291P1ZA".to_string()], &[])
+            .await
+            .unwrap();
+        assert_that!(entities).has_length(1);
+        assert_that!(entities[0].id).is_equal_to(*SEED_BUSINESS_ENTITY_ID2);
+    }
+}
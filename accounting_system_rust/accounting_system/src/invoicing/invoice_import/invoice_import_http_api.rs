@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Payload, Query};
+use actix_web::{error, web, HttpResponseBuilder, Responder, ResponseError};
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::invoice_import::invoice_import_models::InvoiceImportOptions;
+use crate::invoicing::invoice_import::invoice_import_service::{
+    InvoiceImportService, InvoiceImportServiceError,
+};
+use crate::setup_routes;
+
+const MAX_IMPORT_FILE_BYTES: usize = 10 * 1024 * 1024;
+
+impl ResponseError for InvoiceImportServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InvoiceImportServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InvoiceImportServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            InvoiceImportServiceError::Invoicing(e) => e.status_code(),
+            InvoiceImportServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn read_file(payload: Payload) -> actix_web::Result<web::Bytes> {
+    match payload.to_bytes_limited(MAX_IMPORT_FILE_BYTES).await {
+        Ok(file) => file,
+        Err(e) => Err(error::ErrorPayloadTooLarge(e)),
+    }
+}
+
+async fn preview_invoice_import(
+    data: Data<Arc<dyn InvoiceImportService>>,
+    options: Query<InvoiceImportOptions>,
+    payload: Payload,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let file = read_file(payload).await?;
+    let ap = data
+        .preview_invoice_import(&file, &options.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn commit_invoice_import(
+    data: Data<Arc<dyn InvoiceImportService>>,
+    options: Query<InvoiceImportOptions>,
+    payload: Payload,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let file = read_file(payload).await?;
+    let ap = data
+        .commit_invoice_import(
+            &file,
+            &options.into_inner(),
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    InvoiceImportService,
+    "/invoice-import",
+    "/preview",
+    web::post().to(preview_invoice_import),
+    "/commit",
+    web::post().to(commit_invoice_import)
+);
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::invoicing::invoicing_request_models::CreateInvoiceRequest;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFileFormat {
+    Csv,
+    Xlsx,
+}
+
+///invoice level values which are not part of the sheet, applied to every imported invoice
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceImportOptions {
+    pub format: ImportFileFormat,
+    pub supplier_id: Uuid,
+    pub invoice_template_id: Uuid,
+    pub invoicing_series_mst_id: Uuid,
+    pub currency_id: Uuid,
+    #[serde(default)]
+    pub service_invoice: bool,
+    #[serde(default)]
+    pub einvoicing_applicable: bool,
+}
+
+///columns of the sheet, one row is one invoice line and rows are grouped into invoices by reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImportColumn {
+    Reference,
+    CustomerGstin,
+    CustomerName,
+    ShipToGstin,
+    ShipToName,
+    OrderNumber,
+    OrderDate,
+    ProductTitle,
+    HsnSacCode,
+    Quantity,
+    FreeQuantity,
+    Uom,
+    UnitPrice,
+    DiscountPercentage,
+    Remarks,
+}
+
+impl ImportColumn {
+    pub const ALL: [ImportColumn; 15] = [
+        ImportColumn::Reference,
+        ImportColumn::CustomerGstin,
+        ImportColumn::CustomerName,
+        ImportColumn::ShipToGstin,
+        ImportColumn::ShipToName,
+        ImportColumn::OrderNumber,
+        ImportColumn::OrderDate,
+        ImportColumn::ProductTitle,
+        ImportColumn::HsnSacCode,
+        ImportColumn::Quantity,
+        ImportColumn::FreeQuantity,
+        ImportColumn::Uom,
+        ImportColumn::UnitPrice,
+        ImportColumn::DiscountPercentage,
+        ImportColumn::Remarks,
+    ];
+
+    ///accepted headers in lower case, the first one is used in error messages
+    pub fn headers(&self) -> &'static [&'static str] {
+        match self {
+            ImportColumn::Reference => &["reference", "external reference", "invoice reference"],
+            ImportColumn::CustomerGstin => &["customer gstin", "billed to gstin", "gstin"],
+            ImportColumn::CustomerName => &["customer name", "billed to", "customer"],
+            ImportColumn::ShipToGstin => &["ship to gstin", "shipped to gstin"],
+            ImportColumn::ShipToName => &["ship to name", "shipped to", "ship to"],
+            ImportColumn::OrderNumber => &["order number", "po number"],
+            ImportColumn::OrderDate => &["order date", "po date"],
+            ImportColumn::ProductTitle => &["product", "product title", "item"],
+            ImportColumn::HsnSacCode => &["hsn/sac", "hsn", "sac", "hsn sac code"],
+            ImportColumn::Quantity => &["quantity", "qty"],
+            ImportColumn::FreeQuantity => &["free quantity", "free qty"],
+            ImportColumn::Uom => &["uom", "unit"],
+            ImportColumn::UnitPrice => &["unit price", "rate", "price"],
+            ImportColumn::DiscountPercentage => &["discount percentage", "discount %", "discount"],
+            ImportColumn::Remarks => &["remarks", "invoice remarks"],
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.headers()[0]
+    }
+}
+
+///one line of the sheet after its cells are parsed, row is the row number as shown by spreadsheet tools
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImportSheetRow {
+    pub row: usize,
+    pub reference: String,
+    pub customer: ImportPartyRef,
+    pub ship_to: ImportPartyRef,
+    pub order_number: Option<String>,
+    pub order_date: Option<String>,
+    pub remarks: Option<String>,
+    pub product_title: Option<String>,
+    pub hsn_sac_code: Option<String>,
+    pub quantity: f64,
+    pub free_quantity: f64,
+    pub uom: Option<String>,
+    pub unit_price: f64,
+    pub discount_percentage: f64,
+}
+
+impl ImportSheetRow {
+    ///rows after the first one of an invoice may leave the invoice level columns empty
+    pub fn has_invoice_level_values(&self) -> bool {
+        !self.customer.is_empty()
+            || !self.ship_to.is_empty()
+            || self.order_number.is_some()
+            || self.order_date.is_some()
+            || self.remarks.is_some()
+    }
+
+    pub fn same_invoice_level_values(&self, other: &ImportSheetRow) -> bool {
+        self.customer == other.customer
+            && self.ship_to == other.ship_to
+            && self.order_number == other.order_number
+            && self.order_date == other.order_date
+            && self.remarks == other.remarks
+    }
+}
+
+///business entity as written in the sheet, gstin is preferred over name for resolution
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct ImportPartyRef {
+    pub gstin: Option<String>,
+    pub name: Option<String>,
+}
+
+impl ImportPartyRef {
+    pub fn is_empty(&self) -> bool {
+        self.gstin.is_none() && self.name.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImportProductDb {
+    pub id: Uuid,
+    pub title: String,
+    pub hsn_sac_code: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ImportBusinessEntityDb {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub gstin: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedInvoicePreview {
+    pub reference: String,
+    pub rows: Vec<usize>,
+    ///none when a row of the invoice could not be resolved
+    pub request: Option<CreateInvoiceRequest>,
+    pub errors: Vec<ImportRowError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceImportPreview {
+    pub invoices: Vec<ImportedInvoicePreview>,
+    ///errors of the header or of rows which could not be read, such rows are not part of any invoice
+    pub row_errors: Vec<ImportRowError>,
+}
+
+impl InvoiceImportPreview {
+    pub fn has_errors(&self) -> bool {
+        !self.row_errors.is_empty() || self.invoices.iter().any(|a| !a.errors.is_empty())
+    }
+
+    pub fn error_messages(&self) -> Vec<String> {
+        self.row_errors
+            .iter()
+            .chain(self.invoices.iter().flat_map(|a| a.errors.iter()))
+            .map(|a| format!("row {}: {}", a.row, a.message))
+            .collect()
+    }
+}
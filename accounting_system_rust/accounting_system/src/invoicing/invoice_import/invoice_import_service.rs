@@ -0,0 +1,907 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use calamine::{open_workbook_from_rs, Data, DataType, Reader, Xlsx};
+use chrono::NaiveDate;
+use deadpool_postgres::Pool;
+use itertools::Itertools;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use invoice_doc_generator::invoice_line::line_quantity::{FreeLineQuantity, LineQuantity};
+use invoice_doc_generator::invoice_line::unit_price::Price;
+use invoice_doc_generator::invoice_line1::UOM;
+use invoice_doc_generator::percentages::tax_discount_cess::DiscountPercentage;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::invoice_import::invoice_import_dao::{
+    get_invoice_import_dao, InvoiceImportDao,
+};
+use crate::invoicing::invoice_import::invoice_import_models::{
+    ImportBusinessEntityDb, ImportColumn, ImportFileFormat, ImportPartyRef, ImportProductDb,
+    ImportRowError, ImportSheetRow, ImportedInvoicePreview, InvoiceImportOptions,
+    InvoiceImportPreview,
+};
+use crate::invoicing::invoicing_request_models::{
+    BillShipDetail, BulkInvoiceItemResult, CreateInvoiceLineRequest, CreateInvoiceRequest,
+    CreateInvoicesInBulkRequest, InvoiceRemarks, PurchaseOrderDate, PurchaseOrderNo,
+    MAX_BULK_INVOICES,
+};
+use crate::invoicing::invoicing_service::{InvoicingService, InvoicingServiceError};
+
+const ORDER_DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%d-%m-%Y", "%d/%m/%Y"];
+
+#[derive(Debug, Error)]
+pub enum InvoiceImportServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("{0}")]
+    Invoicing(#[from] InvoicingServiceError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait InvoiceImportService: Send + Sync {
+    ///reads the sheet, resolves products and customers and validates every invoice without creating any
+    async fn preview_invoice_import(
+        &self,
+        file: &[u8],
+        options: &InvoiceImportOptions,
+        tenant_id: Uuid,
+    ) -> Result<InvoiceImportPreview, InvoiceImportServiceError>;
+    ///creates the invoices of the sheet, nothing is created if the preview of the sheet has errors
+    async fn commit_invoice_import(
+        &self,
+        file: &[u8],
+        options: &InvoiceImportOptions,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<BulkInvoiceItemResult>, InvoiceImportServiceError>;
+}
+
+struct InvoiceImportServiceImpl {
+    dao: Arc<dyn InvoiceImportDao>,
+    invoicing_service: Arc<dyn InvoicingService>,
+}
+
+pub fn get_invoice_import_service(
+    arc: Arc<Pool>,
+    invoicing_service: Arc<dyn InvoicingService>,
+) -> Arc<dyn InvoiceImportService> {
+    let dao = get_invoice_import_dao(arc);
+    let service = InvoiceImportServiceImpl {
+        dao,
+        invoicing_service,
+    };
+    Arc::new(service)
+}
+
+///returns the cells of the first sheet as text, the first row being the header
+pub(crate) fn read_sheet(
+    file: &[u8],
+    format: ImportFileFormat,
+) -> anyhow::Result<Vec<Vec<String>>> {
+    match format {
+        ImportFileFormat::Csv => csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(file)
+            .records()
+            .map(|a| {
+                a.map(|r| r.iter().map(|c| c.to_string()).collect())
+                    .context("invalid csv row")
+            })
+            .collect(),
+        ImportFileFormat::Xlsx => {
+            let mut workbook: Xlsx<_> =
+                open_workbook_from_rs(Cursor::new(file)).context("invalid xlsx file")?;
+            let range = workbook
+                .worksheet_range_at(0)
+                .context("xlsx file has no sheet")?
+                .context("invalid sheet in xlsx file")?;
+            Ok(range
+                .rows()
+                .map(|a| a.iter().map(cell_to_string).collect())
+                .collect())
+        }
+    }
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(_) | Data::DateTimeIso(_) => cell
+            .as_date()
+            .map(|a| a.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| cell.to_string()),
+        _ => cell.to_string().trim().to_string(),
+    }
+}
+
+fn normalise_header(header: &str) -> String {
+    header
+        .trim()
+        .to_lowercase()
+        .replace('_', " ")
+        .split_whitespace()
+        .join(" ")
+}
+
+///maps the columns of the sheet by their headers, reports the required columns which are missing
+pub(crate) fn map_columns(header: &[String]) -> Result<HashMap<ImportColumn, usize>, Vec<String>> {
+    let normalised = header.iter().map(|a| normalise_header(a)).collect_vec();
+    let columns: HashMap<ImportColumn, usize> = ImportColumn::ALL
+        .iter()
+        .filter_map(|column| {
+            normalised
+                .iter()
+                .position(|h| column.headers().contains(&h.as_str()))
+                .map(|index| (*column, index))
+        })
+        .collect();
+    let mut errors = vec![];
+    for column in [
+        ImportColumn::Reference,
+        ImportColumn::Quantity,
+        ImportColumn::UnitPrice,
+    ] {
+        if !columns.contains_key(&column) {
+            errors.push(format!("column {} is required", column.name()));
+        }
+    }
+    if !columns.contains_key(&ImportColumn::ProductTitle)
+        && !columns.contains_key(&ImportColumn::HsnSacCode)
+    {
+        errors.push(format!(
+            "column {} or {} is required",
+            ImportColumn::ProductTitle.name(),
+            ImportColumn::HsnSacCode.name()
+        ));
+    }
+    if errors.is_empty() {
+        Ok(columns)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_sheet_row(
+    row: usize,
+    cells: &[String],
+    columns: &HashMap<ImportColumn, usize>,
+) -> Result<ImportSheetRow, Vec<String>> {
+    let text = |column: ImportColumn| -> Option<String> {
+        columns
+            .get(&column)
+            .and_then(|index| cells.get(*index))
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .map(|a| a.to_string())
+    };
+    let mut errors = vec![];
+    let mut number = |column: ImportColumn, default: Option<f64>| -> f64 {
+        match text(column) {
+            Some(value) => value.replace(',', "").parse::<f64>().unwrap_or_else(|_| {
+                errors.push(format!("{} '{}' is not a number", column.name(), value));
+                0.0
+            }),
+            None => default.unwrap_or_else(|| {
+                errors.push(format!("{} is required", column.name()));
+                0.0
+            }),
+        }
+    };
+    let quantity = number(ImportColumn::Quantity, None);
+    let free_quantity = number(ImportColumn::FreeQuantity, Some(0.0));
+    let unit_price = number(ImportColumn::UnitPrice, None);
+    let discount_percentage = number(ImportColumn::DiscountPercentage, Some(0.0));
+    let reference = text(ImportColumn::Reference);
+    if reference.is_none() {
+        errors.push(format!("{} is required", ImportColumn::Reference.name()));
+    }
+    let product_title = text(ImportColumn::ProductTitle);
+    let hsn_sac_code = text(ImportColumn::HsnSacCode);
+    if product_title.is_none() && hsn_sac_code.is_none() {
+        errors.push(format!(
+            "{} or {} is required",
+            ImportColumn::ProductTitle.name(),
+            ImportColumn::HsnSacCode.name()
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(ImportSheetRow {
+        row,
+        reference: reference.unwrap_or_default(),
+        customer: ImportPartyRef {
+            gstin: text(ImportColumn::CustomerGstin).map(|a| a.to_uppercase()),
+            name: text(ImportColumn::CustomerName),
+        },
+        ship_to: ImportPartyRef {
+            gstin: text(ImportColumn::ShipToGstin).map(|a| a.to_uppercase()),
+            name: text(ImportColumn::ShipToName),
+        },
+        order_number: text(ImportColumn::OrderNumber),
+        order_date: text(ImportColumn::OrderDate),
+        remarks: text(ImportColumn::Remarks),
+        product_title,
+        hsn_sac_code,
+        quantity,
+        free_quantity,
+        uom: text(ImportColumn::Uom),
+        unit_price,
+        discount_percentage,
+    })
+}
+
+///parses the rows below the header, blank rows are skipped and unreadable rows are reported
+pub(crate) fn parse_sheet_rows(
+    sheet: &[Vec<String>],
+) -> (Vec<ImportSheetRow>, Vec<ImportRowError>) {
+    let Some(header) = sheet.first() else {
+        return (
+            vec![],
+            vec![ImportRowError {
+                row: 1,
+                message: "sheet is empty".to_string(),
+            }],
+        );
+    };
+    let columns = match map_columns(header) {
+        Ok(columns) => columns,
+        Err(errors) => {
+            return (
+                vec![],
+                errors
+                    .into_iter()
+                    .map(|message| ImportRowError { row: 1, message })
+                    .collect(),
+            );
+        }
+    };
+    let mut rows = vec![];
+    let mut row_errors = vec![];
+    for (index, cells) in sheet.iter().enumerate().skip(1) {
+        if cells.iter().all(|a| a.trim().is_empty()) {
+            continue;
+        }
+        //spreadsheet tools number rows from 1
+        let row = index + 1;
+        match parse_sheet_row(row, cells, &columns) {
+            Ok(parsed) => rows.push(parsed),
+            Err(errors) => {
+                row_errors.extend(
+                    errors
+                        .into_iter()
+                        .map(|message| ImportRowError { row, message }),
+                );
+            }
+        }
+    }
+    (rows, row_errors)
+}
+
+///groups rows by reference in the order the references first appear
+pub(crate) fn group_rows(rows: Vec<ImportSheetRow>) -> Vec<(String, Vec<ImportSheetRow>)> {
+    let mut groups: Vec<(String, Vec<ImportSheetRow>)> = vec![];
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        match positions.get(&row.reference) {
+            Some(position) => groups[*position].1.push(row),
+            None => {
+                positions.insert(row.reference.clone(), groups.len());
+                groups.push((row.reference.clone(), vec![row]));
+            }
+        }
+    }
+    groups
+}
+
+pub(crate) fn resolve_product(
+    row: &ImportSheetRow,
+    products: &[ImportProductDb],
+) -> Result<Uuid, String> {
+    let title = row.product_title.as_ref().map(|a| a.to_lowercase());
+    let matches = products
+        .iter()
+        .filter(|a| title.as_ref().is_none_or(|t| a.title.to_lowercase() == *t))
+        .filter(|a| {
+            row.hsn_sac_code
+                .as_ref()
+                .is_none_or(|h| a.hsn_sac_code == *h)
+        })
+        .collect_vec();
+    let description = match (&row.product_title, &row.hsn_sac_code) {
+        (Some(t), Some(h)) => format!("product '{}' with hsn/sac {}", t, h),
+        (Some(t), None) => format!("product '{}'", t),
+        (None, Some(h)) => format!("product with hsn/sac {}", h),
+        (None, None) => "product".to_string(),
+    };
+    match matches.as_slice() {
+        [product] => Ok(product.id),
+        [] => Err(format!("{} not found", description)),
+        _ => Err(format!(
+            "{} matches {} products, give both product title and hsn/sac",
+            description,
+            matches.len()
+        )),
+    }
+}
+
+///returns none if the sheet does not name the party
+pub(crate) fn resolve_party(
+    party: &ImportPartyRef,
+    entities: &[ImportBusinessEntityDb],
+) -> Result<Option<Uuid>, String> {
+    let (matches, description) = match (&party.gstin, &party.name) {
+        (Some(gstin), _) => (
+            entities
+                .iter()
+                .filter(|a| a.gstin.as_ref().is_some_and(|g| g.to_uppercase() == *gstin))
+                .collect_vec(),
+            format!("customer with gstin {}", gstin),
+        ),
+        (None, Some(name)) => (
+            entities
+                .iter()
+                .filter(|a| {
+                    a.name
+                        .as_ref()
+                        .is_some_and(|n| n.to_lowercase() == name.to_lowercase())
+                })
+                .collect_vec(),
+            format!("customer '{}'", name),
+        ),
+        (None, None) => return Ok(None),
+    };
+    match matches.as_slice() {
+        [entity] => Ok(Some(entity.id)),
+        [] => Err(format!("{} not found", description)),
+        _ => Err(format!(
+            "{} matches {} customers, give the gstin instead",
+            description,
+            matches.len()
+        )),
+    }
+}
+
+fn parse_order_date(value: &str) -> anyhow::Result<PurchaseOrderDate> {
+    let date = ORDER_DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+        .with_context(|| format!("order date '{}' is not in yyyy-mm-dd format", value))?;
+    PurchaseOrderDate::from_date(date)
+}
+
+fn build_invoice_line(
+    row: &ImportSheetRow,
+    products: &[ImportProductDb],
+) -> Result<CreateInvoiceLineRequest, Vec<String>> {
+    let mut errors = vec![];
+    let product_item_id = resolve_product(row, products)
+        .map_err(|e| errors.push(e))
+        .ok();
+    let uom = match row.uom.as_ref() {
+        Some(uom) => UOM::try_from(uom.clone())
+            .map_err(|_| errors.push(format!("uom '{}' is not valid", uom)))
+            .ok(),
+        None => Some(UOM::Piece),
+    };
+    let quantities = uom.map(|uom| {
+        (
+            LineQuantity::new(row.quantity, uom.clone()).map_err(|e| errors.push(e.to_string())),
+            FreeLineQuantity::new(row.free_quantity, uom).map_err(|e| errors.push(e.to_string())),
+        )
+    });
+    let unit_price = Price::new(row.unit_price)
+        .map_err(|e| errors.push(e.to_string()))
+        .ok();
+    let discount_percentage = DiscountPercentage::new(row.discount_percentage as f32)
+        .map_err(|e| errors.push(e.to_string()))
+        .ok();
+    match (product_item_id, quantities, unit_price, discount_percentage) {
+        (
+            Some(product_item_id),
+            Some((Ok(quantity), Ok(free_quantity))),
+            Some(unit_price),
+            Some(discount_percentage),
+        ) if errors.is_empty() => Ok(CreateInvoiceLineRequest {
+            product_item_id,
+            quantity,
+            free_quantity,
+            unit_price,
+            discount_percentage,
+            mrp: None,
+            batch_no: None,
+            expiry_date: None,
+            reverse_charge_applicable: false,
+        }),
+        _ => Err(errors),
+    }
+}
+
+///namespace of the idempotence keys of the invoices of a file, a reference is unique only within its file
+pub(crate) fn import_namespace(file: &[u8], tenant_id: Uuid) -> Uuid {
+    Uuid::new_v5(&tenant_id, file)
+}
+
+///builds the invoice request of a reference, invoice level values are taken from its first row.
+///the idempotence key is derived from the file and the reference so importing the same file again does not
+/// duplicate invoices, while another file reusing the reference creates its own invoice
+pub(crate) fn build_imported_invoice(
+    reference: String,
+    rows: Vec<ImportSheetRow>,
+    products: &[ImportProductDb],
+    entities: &[ImportBusinessEntityDb],
+    options: &InvoiceImportOptions,
+    import_namespace: Uuid,
+) -> ImportedInvoicePreview {
+    let first = &rows[0];
+    let mut errors: Vec<ImportRowError> = vec![];
+    let mut push = |row: usize, message: String| errors.push(ImportRowError { row, message });
+    for row in rows.iter().skip(1) {
+        if row.has_invoice_level_values() && !row.same_invoice_level_values(first) {
+            push(
+                row.row,
+                format!(
+                    "customer, order and remarks differ from row {} of reference {}",
+                    first.row, reference
+                ),
+            );
+        }
+    }
+    let customer_id = resolve_party(&first.customer, entities).unwrap_or_else(|e| {
+        push(first.row, e);
+        None
+    });
+    let ship_to_id = resolve_party(&first.ship_to, entities).unwrap_or_else(|e| {
+        push(first.row, e);
+        None
+    });
+    if customer_id.is_none() && !first.ship_to.is_empty() {
+        push(first.row, "ship to is given without a customer".to_string());
+    }
+    let order_number = first.order_number.clone().and_then(|a| {
+        PurchaseOrderNo::new(a)
+            .map_err(|e| push(first.row, e.to_string()))
+            .ok()
+    });
+    let order_date = first.order_date.as_ref().and_then(|a| {
+        parse_order_date(a)
+            .map_err(|e| push(first.row, e.to_string()))
+            .ok()
+    });
+    let invoice_remarks = first.remarks.as_ref().and_then(|a| {
+        InvoiceRemarks::new(a)
+            .map_err(|e| push(first.row, e.to_string()))
+            .ok()
+    });
+    let mut invoice_lines = Vec::with_capacity(rows.len());
+    for row in rows.iter() {
+        match build_invoice_line(row, products) {
+            Ok(line) => invoice_lines.push(line),
+            Err(line_errors) => {
+                for e in line_errors {
+                    push(row.row, e);
+                }
+            }
+        }
+    }
+    let row_numbers = rows.iter().map(|a| a.row).collect_vec();
+    if !errors.is_empty() {
+        return ImportedInvoicePreview {
+            reference,
+            rows: row_numbers,
+            request: None,
+            errors,
+        };
+    }
+    let b2b_invoice = customer_id.is_some_and(|id| {
+        entities
+            .iter()
+            .any(|a| a.id == id && a.gstin.as_ref().is_some_and(|g| !g.is_empty()))
+    });
+    let request = CreateInvoiceRequest {
+        idempotence_key: Uuid::new_v5(&import_namespace, reference.as_bytes()),
+        invoice_template_id: options.invoice_template_id,
+        invoicing_series_mst_id: options.invoicing_series_mst_id,
+        currency_id: options.currency_id,
+        service_invoice: options.service_invoice,
+        einvoicing_applicable: options.einvoicing_applicable,
+        b2b_invoice,
+        supplier_id: options.supplier_id,
+        dispatch_from_id: None,
+        bill_ship_detail: customer_id.map(|billed_to_customer_id| BillShipDetail {
+            billed_to_customer_id,
+            shipped_to_customer_id: ship_to_id.unwrap_or(billed_to_customer_id),
+        }),
+        order_number,
+        order_date,
+        payment_terms: None,
+        invoice_lines,
+        additional_charges: vec![],
+        invoice_remarks,
+        ecommerce_gstin: None,
+        export_detail: None,
+        service_category: None,
+        sez_lut_reference: None,
+        deemed_export: false,
+        email_to_customer: false,
+        ledger_accounts: None,
+    };
+    ImportedInvoicePreview {
+        reference,
+        rows: row_numbers,
+        request: Some(request),
+        errors,
+    }
+}
+
+impl InvoiceImportServiceImpl {
+    async fn fetch_products(
+        &self,
+        rows: &[ImportSheetRow],
+        tenant_id: Uuid,
+    ) -> Result<Vec<ImportProductDb>, InvoiceImportServiceError> {
+        let titles = rows
+            .iter()
+            .filter_map(|a| a.product_title.as_ref().map(|t| t.to_lowercase()))
+            .unique()
+            .collect_vec();
+        let hsn_sac_codes = rows
+            .iter()
+            .filter_map(|a| a.hsn_sac_code.clone())
+            .unique()
+            .collect_vec();
+        if titles.is_empty() && hsn_sac_codes.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .dao
+            .find_products(tenant_id, &titles, &hsn_sac_codes)
+            .await?)
+    }
+
+    async fn fetch_business_entities(
+        &self,
+        rows: &[ImportSheetRow],
+        tenant_id: Uuid,
+    ) -> Result<Vec<ImportBusinessEntityDb>, InvoiceImportServiceError> {
+        let parties = rows.iter().flat_map(|a| [&a.customer, &a.ship_to]);
+        let gstins = parties
+            .clone()
+            .filter_map(|a| a.gstin.clone())
+            .unique()
+            .collect_vec();
+        let names = parties
+            .filter(|a| a.gstin.is_none())
+            .filter_map(|a| a.name.as_ref().map(|n| n.to_lowercase()))
+            .unique()
+            .collect_vec();
+        if gstins.is_empty() && names.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self
+            .dao
+            .find_business_entities(tenant_id, &gstins, &names)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl InvoiceImportService for InvoiceImportServiceImpl {
+    async fn preview_invoice_import(
+        &self,
+        file: &[u8],
+        options: &InvoiceImportOptions,
+        tenant_id: Uuid,
+    ) -> Result<InvoiceImportPreview, InvoiceImportServiceError> {
+        let sheet = read_sheet(file, options.format).map_err(|e| {
+            InvoiceImportServiceError::Validation(vec![format!("could not read the file: {:#}", e)])
+        })?;
+        let (rows, row_errors) = parse_sheet_rows(&sheet);
+        let products = self.fetch_products(&rows, tenant_id).await?;
+        let entities = self.fetch_business_entities(&rows, tenant_id).await?;
+        let namespace = import_namespace(file, tenant_id);
+        let mut invoices = group_rows(rows)
+            .into_iter()
+            .map(|(reference, rows)| {
+                build_imported_invoice(reference, rows, &products, &entities, options, namespace)
+            })
+            .collect_vec();
+        let requests = invoices
+            .iter()
+            .filter_map(|a| a.request.clone())
+            .collect_vec();
+        if !requests.is_empty() {
+            let failures = self
+                .invoicing_service
+                .validate_invoices(&requests, tenant_id)
+                .await?;
+            for (invoice, errors) in invoices
+                .iter_mut()
+                .filter(|a| a.request.is_some())
+                .zip(failures)
+            {
+                let row = invoice.rows[0];
+                invoice.errors.extend(
+                    errors
+                        .into_iter()
+                        .map(|message| ImportRowError { row, message }),
+                );
+            }
+        }
+        Ok(InvoiceImportPreview {
+            invoices,
+            row_errors,
+        })
+    }
+
+    async fn commit_invoice_import(
+        &self,
+        file: &[u8],
+        options: &InvoiceImportOptions,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<BulkInvoiceItemResult>, InvoiceImportServiceError> {
+        let preview = self
+            .preview_invoice_import(file, options, tenant_id)
+            .await?;
+        if preview.has_errors() {
+            return Err(InvoiceImportServiceError::Validation(
+                preview.error_messages(),
+            ));
+        }
+        let mut requests = preview
+            .invoices
+            .into_iter()
+            .filter_map(|a| a.request)
+            .collect_vec();
+        if requests.is_empty() {
+            return Err(InvoiceImportServiceError::Validation(vec![
+                "sheet has no invoices".to_string(),
+            ]));
+        }
+        let mut results: Vec<BulkInvoiceItemResult> = Vec::with_capacity(requests.len());
+        while !requests.is_empty() {
+            let rest = requests.split_off(requests.len().min(MAX_BULK_INVOICES));
+            let invoices = std::mem::replace(&mut requests, rest);
+            let offset = results.len();
+            let created = self
+                .invoicing_service
+                .create_invoices_in_bulk(
+                    CreateInvoicesInBulkRequest { invoices },
+                    tenant_id,
+                    user_id,
+                )
+                .await?;
+            results.extend(created.into_iter().map(|mut a| {
+                a.index += offset;
+                a
+            }));
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::invoicing::invoice_import::invoice_import_dao::MockInvoiceImportDao;
+    use crate::invoicing::invoice_import::invoice_import_models::{
+        ImportBusinessEntityDb, ImportFileFormat, ImportProductDb, ImportRowError,
+        InvoiceImportOptions,
+    };
+    use crate::invoicing::invoice_import::invoice_import_service::{
+        build_imported_invoice, group_rows, import_namespace, parse_sheet_rows, read_sheet,
+        resolve_product, InvoiceImportService, InvoiceImportServiceError, InvoiceImportServiceImpl,
+    };
+    use crate::invoicing::invoicing_service::MockInvoicingService;
+
+    const CSV: &str =
+        "Reference,Customer GSTIN,Customer Name,Order Date,Product,HSN/SAC,Qty,UOM,Rate,Discount\n\
+INV-1,06MFNMS5291P1ZA,,2024-01-10,product 1,,2,box,100,5\n\
+INV-2,,walk in,,product 2,84614024,1,,50,\n\
+,,,,,,,,,\n\
+INV-1,,,,product 2,84614024,3,,40,\n\
+INV-3,,,,product 1,,x,,10,\n";
+
+    fn products() -> Vec<ImportProductDb> {
+        vec![
+            ImportProductDb {
+                id: Uuid::from_u128(1),
+                title: "Product 1".to_string(),
+                hsn_sac_code: "1001".to_string(),
+            },
+            ImportProductDb {
+                id: Uuid::from_u128(2),
+                title: "Product 2".to_string(),
+                hsn_sac_code: "84614024".to_string(),
+            },
+            ImportProductDb {
+                id: Uuid::from_u128(3),
+                title: "Product 2".to_string(),
+                hsn_sac_code: "84614025".to_string(),
+            },
+        ]
+    }
+
+    fn entities() -> Vec<ImportBusinessEntityDb> {
+        vec![ImportBusinessEntityDb {
+            id: Uuid::from_u128(10),
+            name: Some("Test supplier 2".to_string()),
+            gstin: Some("06MFNMS5291P1ZA".to_string()),
+        }]
+    }
+
+    fn options() -> InvoiceImportOptions {
+        InvoiceImportOptions {
+            format: ImportFileFormat::Csv,
+            supplier_id: Uuid::from_u128(20),
+            invoice_template_id: Uuid::from_u128(21),
+            invoicing_series_mst_id: Uuid::from_u128(22),
+            currency_id: Uuid::from_u128(23),
+            service_invoice: false,
+            einvoicing_applicable: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_sheet_rows_reports_unreadable_rows() {
+        let sheet = read_sheet(CSV.as_bytes(), ImportFileFormat::Csv).unwrap();
+        let (rows, errors) = parse_sheet_rows(&sheet);
+        assert_that!(rows.iter().map(|a| a.row).collect::<Vec<_>>()).is_equal_to(vec![2, 3, 5]);
+        assert_that!(errors).is_equal_to(vec![ImportRowError {
+            row: 6,
+            message: "quantity 'x' is not a number".to_string(),
+        }]);
+        let (_, errors) = parse_sheet_rows(&[vec!["Reference".to_string()]]);
+        assert_that!(errors).has_length(3);
+    }
+
+    #[test]
+    fn test_build_imported_invoice_groups_rows_and_resolves_masters() {
+        let sheet = read_sheet(CSV.as_bytes(), ImportFileFormat::Csv).unwrap();
+        let (rows, _) = parse_sheet_rows(&sheet);
+        let groups = group_rows(rows);
+        assert_that!(groups.iter().map(|a| a.0.as_str()).collect::<Vec<_>>())
+            .is_equal_to(vec!["INV-1", "INV-2"]);
+        let mut previews = groups
+            .into_iter()
+            .map(|(reference, rows)| {
+                build_imported_invoice(
+                    reference,
+                    rows,
+                    &products(),
+                    &entities(),
+                    &options(),
+                    import_namespace(CSV.as_bytes(), Uuid::from_u128(30)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let second = previews.pop().unwrap();
+        let first = previews.pop().unwrap();
+        assert_that!(first.errors).is_empty();
+        assert_that!(first.rows).is_equal_to(vec![2, 5]);
+        let request = first.request.unwrap();
+        assert!(request.b2b_invoice);
+        assert_that!(request.bill_ship_detail.unwrap().shipped_to_customer_id)
+            .is_equal_to(Uuid::from_u128(10));
+        assert_that!(request
+            .invoice_lines
+            .iter()
+            .map(|a| a.product_item_id)
+            .collect::<Vec<_>>())
+        .is_equal_to(vec![Uuid::from_u128(1), Uuid::from_u128(2)]);
+        assert!(second.request.is_none());
+        assert_that!(second.errors).is_equal_to(vec![ImportRowError {
+            row: 3,
+            message: "customer 'walk in' not found".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_resolve_product_needs_hsn_for_same_titles() {
+        let sheet = read_sheet(
+            "reference,product,qty,price\nINV-1,product 2,1,10\n".as_bytes(),
+            ImportFileFormat::Csv,
+        )
+        .unwrap();
+        let (rows, _) = parse_sheet_rows(&sheet);
+        let res = resolve_product(&rows[0], &products());
+        assert_that!(res).is_equal_to(Err(
+            "product 'product 2' matches 2 products, give both product title and hsn/sac"
+                .to_string(),
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_preview_attaches_invoice_validation_errors_to_first_row() {
+        let mut dao = MockInvoiceImportDao::new();
+        dao.expect_find_products()
+            .times(2)
+            .returning(|_, _, _| Ok(products()));
+        dao.expect_find_business_entities()
+            .times(2)
+            .returning(|_, _, _| Ok(entities()));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_validate_invoices()
+            .times(2)
+            .returning(|reqs, _| {
+                Ok(reqs
+                    .iter()
+                    .map(|_| vec!["invoice template id does not exists".to_string()])
+                    .collect())
+            });
+        let service = InvoiceImportServiceImpl {
+            dao: Arc::new(dao),
+            invoicing_service: Arc::new(invoicing_service),
+        };
+        let preview = service
+            .preview_invoice_import(CSV.as_bytes(), &options(), Uuid::from_u128(30))
+            .await
+            .unwrap();
+        assert_that!(preview.invoices[0].errors).is_equal_to(vec![ImportRowError {
+            row: 2,
+            message: "invoice template id does not exists".to_string(),
+        }]);
+        assert!(preview.has_errors());
+        let res = service
+            .commit_invoice_import(CSV.as_bytes(), &options(), Uuid::from_u128(30), Uuid::nil())
+            .await;
+        assert!(matches!(res, Err(InvoiceImportServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_files_reusing_a_reference_get_different_idempotence_keys() {
+        let mut dao = MockInvoiceImportDao::new();
+        dao.expect_find_products()
+            .returning(|_, _, _| Ok(products()));
+        dao.expect_find_business_entities()
+            .returning(|_, _, _| Ok(entities()));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_validate_invoices()
+            .returning(|reqs, _| Ok(reqs.iter().map(|_| vec![]).collect()));
+        let service = InvoiceImportServiceImpl {
+            dao: Arc::new(dao),
+            invoicing_service: Arc::new(invoicing_service),
+        };
+        let idempotence_key = |file: &'static str| {
+            let service = &service;
+            async move {
+                let preview = service
+                    .preview_invoice_import(file.as_bytes(), &options(), Uuid::from_u128(30))
+                    .await
+                    .unwrap();
+                preview.invoices[0]
+                    .request
+                    .as_ref()
+                    .unwrap()
+                    .idempotence_key
+            }
+        };
+        let first = idempotence_key("reference,product,qty,price\nINV-1,product 1,1,10\n").await;
+        let second = idempotence_key("reference,product,qty,price\nINV-1,product 1,2,10\n").await;
+        let first_again =
+            idempotence_key("reference,product,qty,price\nINV-1,product 1,1,10\n").await;
+        assert_ne!(first, second);
+        assert_eq!(first, first_again);
+    }
+}
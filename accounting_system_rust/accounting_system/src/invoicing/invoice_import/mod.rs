@@ -0,0 +1,4 @@
+mod invoice_import_dao;
+pub mod invoice_import_http_api;
+pub mod invoice_import_models;
+pub mod invoice_import_service;
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::background_job::background_job_models::{ClaimedJob, JobKind};
+use crate::background_job::background_job_runner::JobHandler;
+use crate::common_utils::email_client::EmailClient;
+use crate::invoicing::invoicing_request_models::InvoicePdfRequest;
+use crate::invoicing::invoicing_service::InvoicingService;
+
+///pdf and email of the invoice are enqueued by the e-invoice job once the irn is stored, so it carries their
+/// details. generic over the pdf request so that it can be enqueued from a borrowed request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EInvoiceJobPayload<P = InvoicePdfRequest> {
+    pub pdf_request: P,
+    pub email_to: Option<String>,
+}
+
+///generic over the pdf request so that it can be enqueued from a borrowed request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceEmailJobPayload<P = InvoicePdfRequest> {
+    pub to: String,
+    pub pdf_request: P,
+}
+
+fn parse_payload<T: for<'de> Deserialize<'de>>(job: &ClaimedJob) -> anyhow::Result<T> {
+    serde_json::from_value(job.payload.clone())
+        .with_context(|| format!("invalid payload for {:?} job {}", job.kind, job.id))
+}
+
+///renders and uploads the invoice pdf, an already uploaded pdf is not rendered again
+struct InvoicePdfJobHandler {
+    invoicing_service: Arc<dyn InvoicingService>,
+}
+
+#[async_trait]
+impl JobHandler for InvoicePdfJobHandler {
+    fn kind(&self) -> JobKind {
+        JobKind::InvoicePdf
+    }
+
+    async fn handle(&self, job: &ClaimedJob) -> anyhow::Result<()> {
+        let pdf_request: InvoicePdfRequest = parse_payload(job)?;
+        self.invoicing_service
+            .create_invoice_pdf(pdf_request)
+            .await?;
+        Ok(())
+    }
+}
+
+///registers the invoice with the invoice registration portal and stores the irn returned, then enqueues the pdf
+/// and email jobs of the invoice
+struct EInvoiceJobHandler {
+    invoicing_service: Arc<dyn InvoicingService>,
+}
+
+#[async_trait]
+impl JobHandler for EInvoiceJobHandler {
+    fn kind(&self) -> JobKind {
+        JobKind::EInvoice
+    }
+
+    async fn handle(&self, job: &ClaimedJob) -> anyhow::Result<()> {
+        let payload: EInvoiceJobPayload = parse_payload(job)?;
+        self.invoicing_service.generate_einvoice(payload).await?;
+        Ok(())
+    }
+}
+
+///sends the link of the invoice pdf, the pdf is created first if its job has not run yet
+struct InvoiceEmailJobHandler {
+    invoicing_service: Arc<dyn InvoicingService>,
+    email_client: Arc<dyn EmailClient>,
+}
+
+#[async_trait]
+impl JobHandler for InvoiceEmailJobHandler {
+    fn kind(&self) -> JobKind {
+        JobKind::InvoiceEmail
+    }
+
+    async fn handle(&self, job: &ClaimedJob) -> anyhow::Result<()> {
+        let payload: InvoiceEmailJobPayload = parse_payload(job)?;
+        let invoice_number = payload.pdf_request.invoice.invoice_number.clone();
+        let pdf_url = self
+            .invoicing_service
+            .create_invoice_pdf(payload.pdf_request)
+            .await?;
+        self.email_client
+            .send_invoice_email(
+                payload.to.as_str(),
+                invoice_number.as_str(),
+                pdf_url.as_str(),
+            )
+            .await
+    }
+}
+
+pub fn get_invoice_job_handlers(
+    invoicing_service: Arc<dyn InvoicingService>,
+    email_client: Arc<dyn EmailClient>,
+) -> Vec<Arc<dyn JobHandler>> {
+    vec![
+        Arc::new(InvoicePdfJobHandler {
+            invoicing_service: invoicing_service.clone(),
+        }),
+        Arc::new(EInvoiceJobHandler {
+            invoicing_service: invoicing_service.clone(),
+        }),
+        Arc::new(InvoiceEmailJobHandler {
+            invoicing_service,
+            email_client,
+        }),
+    ]
+}
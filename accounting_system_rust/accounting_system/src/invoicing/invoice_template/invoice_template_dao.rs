@@ -1,8 +1,10 @@
 use std::sync::Arc;use std::fmt::Write;
+use std::str::FromStr;
 
 use async_trait::async_trait;
 use const_format::concatcp;
 use deadpool_postgres::{GenericClient, Pool};
+use pdf_doc_generator::invoice_template::InvoiceLayout;
 use tokio_postgres::Row;
 use uuid::Uuid;
 use crate::invoicing::invoice_template::invoice_template_models::CreateInvoiceTemplateRequest;
@@ -13,10 +15,12 @@ use crate::common_utils::db_row_conversion_utils::{
 };
 use crate::common_utils::pg_util::pg_util::ToPostgresString;
 use crate::common_utils::utils::parse_db_output_of_insert_create_and_return_uuid;
-use crate::invoicing::invoice_template::invoice_template_models::{CreateInvoiceTemplateDbRequest, InvoiceTemplateMaster};
+use crate::invoicing::invoice_template::invoice_template_models::{
+    CreateInvoiceTemplateDbRequest, InvoiceTemplateMaster, InvoiceTemplateVersion,
+};
 
 const TABLE_NAME: &str = "invoice_template";
-const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,sample_doc_s3_id,created_by,updated_by,created_at,updated_at";
+const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,sample_doc_s3_id,layout,created_by,updated_by,created_at,updated_at";
 const QUERY_BY_ID: &str = concatcp!(
     "select ",
     SELECT_FIELDS,
@@ -25,6 +29,19 @@ const QUERY_BY_ID: &str = concatcp!(
     " where id=$1 and tenant_id=$2"
 );
 
+const CREATE_TEMPLATE_VERSION: &str = "select create_invoice_template_version($1,$2,$3,$4,$5)";
+
+const TEMPLATE_VERSION_FIELDS: &str =
+    "id,invoice_template_id,version_no,source_s3_id,created_by,created_at";
+
+const TEMPLATE_VERSIONS_QUERY: &str = concatcp!(
+    "select ",
+    TEMPLATE_VERSION_FIELDS,
+    " from invoice_template_version where invoice_template_id=$1 and tenant_id=$2 order by version_no desc"
+);
+
+const LATEST_TEMPLATE_VERSION_QUERY: &str = concatcp!(TEMPLATE_VERSIONS_QUERY, " limit 1");
+
 #[async_trait]
 pub trait InvoiceTemplateDao: Send + Sync {
     async fn create_invoice_template(&self,request: CreateInvoiceTemplateRequest, tenant_id: Uuid, user_id: Uuid)
@@ -34,6 +51,26 @@ pub trait InvoiceTemplateDao: Send + Sync {
         id: &Uuid,
         tenant_id: &Uuid,
     ) -> Result<Option<InvoiceTemplateMaster>, DaoError>;
+    ///returns the number of the new version, none when the template does not exist for the tenant
+    async fn create_template_version(
+        &self,
+        id: Uuid,
+        template_id: Uuid,
+        source_s3_id: &str,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<i32>, DaoError>;
+    ///newest version first
+    async fn get_template_versions(
+        &self,
+        template_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceTemplateVersion>, DaoError>;
+    async fn get_latest_template_version(
+        &self,
+        template_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<InvoiceTemplateVersion>, DaoError>;
 }
 
 pub struct InvoiceTemplateDaoImpl {
@@ -56,7 +93,23 @@ impl TryFrom<Row> for InvoiceTemplateMaster {
         Ok(InvoiceTemplateMaster {
             base_master_fields,
             sample_doc_s3_id: row.get(next_ind),
-            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 1, &row)?,
+            layout: InvoiceLayout::from_str(row.get(next_ind + 1))?,
+            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 2, &row)?,
+        })
+    }
+}
+
+impl TryFrom<Row> for InvoiceTemplateVersion {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(InvoiceTemplateVersion {
+            id: row.get(0),
+            invoice_template_id: row.get(1),
+            version_no: row.get(2),
+            source_s3_id: row.get(3),
+            created_by: row.get(4),
+            created_at: row.get(5),
         })
     }
 }
@@ -69,6 +122,7 @@ impl InvoiceTemplateDao for InvoiceTemplateDaoImpl {
         let re:CreateInvoiceTemplateDbRequest=CreateInvoiceTemplateDbRequest{
             idempotence_key: request.idempotence_key,
             sample_doc_s3_id: request.sample_doc_s3_id,
+            layout: request.layout,
             tenant_id,
             user_id,
         };
@@ -97,10 +151,55 @@ impl InvoiceTemplateDao for InvoiceTemplateDaoImpl {
             .transpose()?;
         Ok(entity)
     }
+
+    async fn create_template_version(
+        &self,
+        id: Uuid,
+        template_id: Uuid,
+        source_s3_id: &str,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Option<i32>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let row = conn
+            .query_one(
+                CREATE_TEMPLATE_VERSION,
+                &[&id, &tenant_id, &template_id, &source_s3_id, &user_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn get_template_versions(
+        &self,
+        template_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceTemplateVersion>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let rows = conn
+            .query(TEMPLATE_VERSIONS_QUERY, &[&template_id, &tenant_id])
+            .await?;
+        rows.into_iter().map(|a| a.try_into()).collect()
+    }
+
+    async fn get_latest_template_version(
+        &self,
+        template_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<InvoiceTemplateVersion>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let version = conn
+            .query_opt(LATEST_TEMPLATE_VERSION_QUERY, &[&template_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()?;
+        Ok(version)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use pdf_doc_generator::invoice_template::InvoiceLayout;
     use speculoos::assert_that;
     use speculoos::option::OptionAssertions;
     use uuid::Uuid;
@@ -127,9 +226,10 @@ mod tests {
         let id = dao.create_invoice_template(CreateInvoiceTemplateRequest{
             idempotence_key: Uuid::now_v7(),
             sample_doc_s3_id: None,
+            layout: InvoiceLayout::Thermal80mm,
         },*SEED_TENANT_ID,*SEED_USER_ID).await.unwrap();
         let template = dao.get_invoice_template_by_id(&id,&*SEED_TENANT_ID).await.unwrap();
-        assert_that!(template).is_some();
+        assert_that!(template.map(|a| a.layout)).is_equal_to(Some(InvoiceLayout::Thermal80mm));
         
     }
     #[tokio::test]
@@ -149,4 +249,52 @@ mod tests {
             .unwrap();
         assert_that!(a).is_some();
     }
+
+    #[tokio::test]
+    async fn test_template_versions_are_numbered_in_order() {
+        let dao = get_dao_generic(
+            |a| InvoiceTemplateDaoImpl {
+                postgres_client: a.clone(),
+            },
+            None,
+        )
+        .await;
+        let template_id = *SEED_INVOICE_TEMPLATE_ID;
+        let no_template = dao
+            .create_template_version(
+                Uuid::now_v7(),
+                Uuid::now_v7(),
+                "key",
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(no_template).is_none();
+        for key in ["first", "second"] {
+            dao.create_template_version(
+                Uuid::now_v7(),
+                template_id,
+                key,
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        }
+        let versions = dao
+            .get_template_versions(template_id, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        let numbers: Vec<(i32, &str)> = versions
+            .iter()
+            .map(|a| (a.version_no, a.source_s3_id.as_str()))
+            .collect();
+        assert_eq!(numbers, vec![(2, "second"), (1, "first")]);
+        let latest = dao
+            .get_latest_template_version(template_id, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(latest).is_equal_to(versions.into_iter().next());
+    }
 }
@@ -1,14 +1,25 @@
 use std::sync::Arc;
-use actix_web::{HttpResponseBuilder, Responder, ResponseError, web};
+use actix_web::{error, HttpResponseBuilder, Responder, ResponseError, web};
 use actix_web::http::StatusCode;
-use actix_web::web::{Data, Path};
+use actix_web::web::{Data, Path, Payload};
 use uuid::Uuid;
 use crate::setup_routes;
 use crate::invoicing::invoice_template::invoice_template_models::CreateInvoiceTemplateRequest;
 use crate::common_utils::utils::{TenantId, UserId};
 use crate::invoicing::invoice_template::invoice_template_service::{InvoiceTemplateService, InvoiceTemplateServiceError};
 
-impl ResponseError for InvoiceTemplateServiceError {}
+const MAX_TEMPLATE_ARCHIVE_BYTES: usize = 5 * 1024 * 1024;
+
+impl ResponseError for InvoiceTemplateServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InvoiceTemplateServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InvoiceTemplateServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            InvoiceTemplateServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+            InvoiceTemplateServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
 
 
 async fn create_invoice_template(
@@ -36,11 +47,49 @@ async fn get_invoice_template_by_id(
     Ok(HttpResponseBuilder::new(StatusCode::OK).json(template))
 }
 
+///body is a tar archive with main.typ at its root
+async fn create_invoice_template_version(
+    data: Data<Arc<dyn InvoiceTemplateService>>,
+    template_id: Path<Uuid>,
+    payload: Payload,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let archive = match payload.to_bytes_limited(MAX_TEMPLATE_ARCHIVE_BYTES).await {
+        Ok(archive) => archive?,
+        Err(e) => return Err(error::ErrorPayloadTooLarge(e)),
+    };
+    let version = data
+        .create_template_version(
+            template_id.into_inner(),
+            archive.to_vec(),
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(version))
+}
+
+async fn get_invoice_template_versions(
+    data: Data<Arc<dyn InvoiceTemplateService>>,
+    template_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let versions = data
+        .get_template_versions(template_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(versions))
+}
+
 setup_routes!(InvoiceTemplateService,
     "/invoice-template",
     "/create",web::post().to(create_invoice_template),
     "/id/{address_id}",
-    web::get().to(get_invoice_template_by_id));
+    web::get().to(get_invoice_template_by_id),
+    "/id/{template_id}/version",
+    web::post().to(create_invoice_template_version),
+    "/id/{template_id}/versions",
+    web::get().to(get_invoice_template_versions));
 #[cfg(test)]
 mod tests{
     use uuid::Uuid;
@@ -50,12 +99,16 @@ mod tests{
     use crate::invoicing::invoice_template::invoice_template_models::{CreateInvoiceTemplateRequest, InvoiceTemplateMaster};
     use crate::invoicing::invoice_template::invoice_template_service::{InvoiceTemplateService, MockInvoiceTemplateService};
     use crate::masters::company_master::company_master_models::base_master_fields::tests::a_base_master_field;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use pdf_doc_generator::invoice_template::InvoiceLayout;
+    use rstest::rstest;
     use super::*;
     #[tokio::test]
     async fn test_create_invoice_template(){
         let expected_invoice_template:InvoiceTemplateMaster = InvoiceTemplateMaster{
             base_master_fields: a_base_master_field(Default::default()),
             sample_doc_s3_id: None,
+            layout: InvoiceLayout::A4Portrait,
             audit_metadata: an_audit_metadata_base(Default::default()),
         };
         let ak =Arc::new(expected_invoice_template.clone());
@@ -71,7 +124,8 @@ mod tests{
     
         let create_req = CreateInvoiceTemplateRequest{
             idempotence_key:Uuid::default(),
-            sample_doc_s3_id:None
+            sample_doc_s3_id:None,
+            layout: InvoiceLayout::A4Portrait,
         };
         get_and_create_api_test_v2!(
           InvoiceTemplateMaster,
@@ -84,4 +138,31 @@ mod tests{
           *SEED_TENANT_ID
         );
     }
+
+    #[rstest]
+    #[case(InvoiceTemplateServiceError::Validation(vec!["unknown variable: invoice".to_string()]), 400)]
+    #[case(InvoiceTemplateServiceError::NotFound(Uuid::default()), 404)]
+    #[tokio::test]
+    async fn test_create_invoice_template_version_errors(
+        #[case] err: InvoiceTemplateServiceError,
+        #[case] http_code: u16,
+    ) {
+        use actix_web::test;
+        let mut mock = MockInvoiceTemplateService::new();
+        mock.expect_create_template_version()
+            .return_once(move |_, _, _, _| Err(err));
+        let mock: Arc<dyn InvoiceTemplateService> = Arc::new(mock);
+        let app = actix_web::App::new()
+            .service(map_endpoints_to_functions())
+            .app_data(Data::new(mock));
+        let app_service = test::init_service(app).await;
+        let request = test::TestRequest::post()
+            .uri(&format!("/invoice-template/id/{}/version", Uuid::default()))
+            .insert_header(("x-acc-tenant-id", SEED_TENANT_ID.to_string()))
+            .insert_header(("x-acc-user-id", SEED_USER_ID.to_string()))
+            .set_payload("not an archive")
+            .to_request();
+        let res = test::call_service(&app_service, request).await;
+        assert_eq!(res.status().as_u16(), http_code);
+    }
 }
\ No newline at end of file
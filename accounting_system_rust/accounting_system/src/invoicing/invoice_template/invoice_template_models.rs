@@ -1,4 +1,5 @@
 use derive_builder::Builder;
+use pdf_doc_generator::invoice_template::InvoiceLayout;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -10,6 +11,8 @@ use crate::masters::company_master::company_master_models::base_master_fields::B
 pub struct InvoiceTemplateMaster {
     pub base_master_fields: BaseMasterFields,
     pub sample_doc_s3_id: Option<String>,
+    ///used for rendering till the first version of the template is uploaded
+    pub layout: InvoiceLayout,
     pub audit_metadata: AuditMetadataBase,
 }
 
@@ -17,21 +20,43 @@ pub struct InvoiceTemplateMaster {
 pub struct CreateInvoiceTemplateRequest {
     pub idempotence_key: Uuid,
     pub sample_doc_s3_id: Option<String>,
+    #[serde(default)]
+    #[builder(default)]
+    pub layout: InvoiceLayout,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct CreateInvoiceTemplateDbRequest {
     pub idempotence_key: Uuid,
     pub sample_doc_s3_id: Option<String>,
+    pub layout: InvoiceLayout,
     pub tenant_id: Uuid,
     pub user_id: Uuid,
 }
 
+///typst sources of a template are never edited, every upload is a new version
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceTemplateVersion {
+    pub id: Uuid,
+    pub invoice_template_id: Uuid,
+    pub version_no: i32,
+    pub source_s3_id: String,
+    pub created_by: Uuid,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CreateInvoiceTemplateVersionResponse {
+    pub id: Uuid,
+    pub version_no: i32,
+}
+
 impl ToPostgresString for CreateInvoiceTemplateDbRequest {
     fn fmt_postgres(&self, f: &mut String) -> std::fmt::Result {
         let fields: &[&dyn ToPostgresString] = &[
             &self.idempotence_key,
             &self.sample_doc_s3_id,
+            &self.layout.as_str(),
             &self.tenant_id,
             &self.user_id,
         ];
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::Context;
 use async_trait::async_trait;
 use deadpool_postgres::Pool;
 use moka::future::Cache;
@@ -8,19 +9,34 @@ use thiserror::Error;
 use uuid::Uuid;
 #[cfg(test)]
 use mockall::automock;
+use pdf_doc_generator::invoice_template::{validate_invoice_template, InvoiceTemplateSource};
 use crate::common_utils::cache_utils::get_or_fetch_entity;
 use crate::common_utils::dao_error::DaoError;
 use crate::invoicing::invoice_template::invoice_template_dao::{
     get_invoice_template_dao, InvoiceTemplateDao,
 };
-use crate::invoicing::invoice_template::invoice_template_models::{CreateInvoiceTemplateRequest, InvoiceTemplateMaster};
+use crate::invoicing::invoice_template::invoice_template_models::{
+    CreateInvoiceTemplateRequest, CreateInvoiceTemplateVersionResponse, InvoiceTemplateMaster,
+    InvoiceTemplateVersion,
+};
+use crate::storage::storage_service::{StorageService, FINANCIAL_DOCS_BUCKET_NAME};
 
 #[derive(Debug, Error)]
 pub enum InvoiceTemplateServiceError {
     #[error(transparent)]
     Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("invoice template {0} not found")]
+    NotFound(Uuid),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
 }
 
+///typst cannot abort a compilation, a template exceeding this is rejected while its compilation runs to
+/// completion on the blocking pool
+const TEMPLATE_COMPILATION_TIMEOUT: Duration = Duration::from_secs(30);
+
 type TemplateEntityOpt = Option<Arc<InvoiceTemplateMaster>>;
 #[cfg_attr(test, automock)]
 #[async_trait]
@@ -37,12 +53,48 @@ pub trait InvoiceTemplateService: Send + Sync {
         id: Uuid,
         tenant_id: Uuid,
     ) -> Result<bool, InvoiceTemplateServiceError>;
+    ///the archive is trial compiled against a sample invoice before it becomes the latest version of the template
+    async fn create_template_version(
+        &self,
+        id: Uuid,
+        archive: Vec<u8>,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateInvoiceTemplateVersionResponse, InvoiceTemplateServiceError>;
+    async fn get_template_versions(
+        &self,
+        id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceTemplateVersion>, InvoiceTemplateServiceError>;
+    ///sources of the latest version, none when the template has no versions and the built-in layout is used
+    async fn get_template_source(
+        &self,
+        id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Arc<InvoiceTemplateSource>>, InvoiceTemplateServiceError>;
 }
 
 struct InvoiceTemplateServiceImpl {
     dao: Arc<dyn InvoiceTemplateDao>,
     //(tenant_id,id)
     cache_by_tenant_id_and_id: Cache<(Uuid, Uuid), Arc<InvoiceTemplateMaster>>,
+    storage_service: Arc<dyn StorageService>,
+    ///(tenant_id,version_id), versions are immutable
+    source_cache: Cache<(Uuid, Uuid), Arc<InvoiceTemplateSource>>,
+}
+
+fn create_template_source_key(tenant_id: Uuid, template_id: Uuid, version_id: Uuid) -> String {
+    format!(
+        "{}-invoice-template-{}-{}.tar",
+        tenant_id, template_id, version_id
+    )
+}
+
+fn parse_template_source(
+    archive: &[u8],
+) -> Result<InvoiceTemplateSource, InvoiceTemplateServiceError> {
+    InvoiceTemplateSource::from_tar(archive)
+        .map_err(|e| InvoiceTemplateServiceError::Validation(vec![format!("{:#}", e)]))
 }
 
 #[async_trait]
@@ -73,18 +125,96 @@ impl InvoiceTemplateService for InvoiceTemplateServiceImpl {
         let k = self.get_template_by_id(id, tenant_id).await?;
         Ok(k.is_some())
     }
+
+    async fn create_template_version(
+        &self,
+        id: Uuid,
+        archive: Vec<u8>,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateInvoiceTemplateVersionResponse, InvoiceTemplateServiceError> {
+        let source = parse_template_source(&archive)?;
+        //typst compilation is cpu bound
+        let compilation = tokio::task::spawn_blocking(move || validate_invoice_template(&source));
+        tokio::time::timeout(TEMPLATE_COMPILATION_TIMEOUT, compilation)
+            .await
+            .map_err(|_| {
+                InvoiceTemplateServiceError::Validation(vec![format!(
+                    "template did not compile within {} seconds",
+                    TEMPLATE_COMPILATION_TIMEOUT.as_secs()
+                )])
+            })?
+            .context("template compilation panicked")?
+            .map_err(InvoiceTemplateServiceError::Validation)?;
+        if !self.is_valid_template_id(id, tenant_id).await? {
+            return Err(InvoiceTemplateServiceError::NotFound(id));
+        }
+        let version_id = Uuid::now_v7();
+        let key = create_template_source_key(tenant_id, id, version_id);
+        self.storage_service
+            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), archive, None)
+            .await
+            .context("error uploading invoice template")?;
+        let version_no = self
+            .dao
+            .create_template_version(version_id, id, key.as_str(), tenant_id, user_id)
+            .await?
+            .ok_or(InvoiceTemplateServiceError::NotFound(id))?;
+        Ok(CreateInvoiceTemplateVersionResponse {
+            id: version_id,
+            version_no,
+        })
+    }
+
+    async fn get_template_versions(
+        &self,
+        id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceTemplateVersion>, InvoiceTemplateServiceError> {
+        Ok(self.dao.get_template_versions(id, tenant_id).await?)
+    }
+
+    async fn get_template_source(
+        &self,
+        id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Arc<InvoiceTemplateSource>>, InvoiceTemplateServiceError> {
+        let Some(version) = self.dao.get_latest_template_version(id, tenant_id).await? else {
+            return Ok(None);
+        };
+        let fetch_block = async {
+            let archive = self
+                .storage_service
+                .get_object(FINANCIAL_DOCS_BUCKET_NAME, version.source_s3_id.as_str())
+                .await
+                .context("error fetching invoice template")?;
+            let source = InvoiceTemplateSource::from_tar(&archive)
+                .with_context(|| format!("invalid archive of template version {}", version.id))?;
+            Ok(Some(source))
+        };
+        get_or_fetch_entity(tenant_id, version.id, &self.source_cache, fetch_block).await
+    }
 }
 
 #[allow(dead_code)]
-pub fn get_invoice_template_master_service(arc: Arc<Pool>) -> Arc<dyn InvoiceTemplateService> {
+pub fn get_invoice_template_master_service(
+    arc: Arc<Pool>,
+    storage_service: Arc<dyn StorageService>,
+) -> Arc<dyn InvoiceTemplateService> {
     let dao = get_invoice_template_dao(arc);
     let cache: Cache<(Uuid, Uuid), Arc<InvoiceTemplateMaster>> = Cache::builder()
         .time_to_live(Duration::from_secs(300))
         .max_capacity(1000)
         .build();
+    let source_cache: Cache<(Uuid, Uuid), Arc<InvoiceTemplateSource>> = Cache::builder()
+        .time_to_live(Duration::from_secs(3600))
+        .max_capacity(100)
+        .build();
     let service = InvoiceTemplateServiceImpl {
         dao,
         cache_by_tenant_id_and_id: cache,
+        storage_service,
+        source_cache,
     };
     Arc::new(service)
 }
@@ -7,6 +7,7 @@ create table invoice_template
     approval_status   smallint                      not null,
     remarks           varchar(70),
     sample_doc_s3_id  varchar(200),
+    layout            varchar(20)  default 'a4_landscape' not null, --built-in layout used when no version is uploaded
     created_by        uuid references app_user (id) not null,
     updated_by        uuid references app_user (id),
     created_at        bigint  default extract(epoch from now()) * 1000000,
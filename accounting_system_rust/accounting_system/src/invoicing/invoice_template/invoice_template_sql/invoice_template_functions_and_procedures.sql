@@ -2,6 +2,7 @@ create type create_invoice_template_request as
 (
     idempotence_key  uuid,
     sample_doc_s3_id text,
+    layout           text,
     tenant_id        uuid,
     user_id          uuid
 );
@@ -20,8 +21,8 @@ create or replace function create_invoice_template(req create_invoice_template_r
         if impacted_rows !=0 then
             select uuid_generate_v7() into invoice_template_id;
             insert into invoice_template (id, entity_version_id, tenant_id, active, approval_status, remarks,
-                                          sample_doc_s3_id, created_by, updated_by, created_at, updated_at)
-            values (invoice_template_id,0,req.tenant_id,true,1,null,req.sample_doc_s3_id,req.user_id,
+                                          sample_doc_s3_id, layout, created_by, updated_by, created_at, updated_at)
+            values (invoice_template_id,0,req.tenant_id,true,1,null,req.sample_doc_s3_id,req.layout,req.user_id,
                     req.user_id,default,default);
             update idempotence_store
             set response=json_build_object('id', invoice_template_id)
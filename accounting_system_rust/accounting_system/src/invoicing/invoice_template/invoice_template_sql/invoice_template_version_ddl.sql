@@ -0,0 +1,10 @@
+create table invoice_template_version
+(
+    id                  uuid primary key,
+    tenant_id           uuid references tenant (id)           not null,
+    invoice_template_id uuid references invoice_template (id) not null,
+    version_no          integer                               not null,--latest version is used to render invoices
+    source_s3_id        varchar(200)                          not null,--tar archive of main.typ and its assets
+    created_by          uuid references app_user (id)         not null,
+    created_at          bigint default extract(epoch from now()) * 1000000
+);
@@ -0,0 +1,22 @@
+--returns the number of the new version, null when the template does not exist for the tenant
+create or replace function create_invoice_template_version(p_id uuid, p_tenant_id uuid, p_invoice_template_id uuid,
+                                                           p_source_s3_id text, p_user_id uuid) returns integer as
+$$
+declare
+    next_version_no integer;
+begin
+    --versions of a template are numbered one at a time
+    perform 1 from invoice_template where id = p_invoice_template_id and tenant_id = p_tenant_id for update;
+    if not found then
+        return null;
+    end if;
+    select coalesce(max(version_no), 0) + 1
+    from invoice_template_version
+    where invoice_template_id = p_invoice_template_id
+    into next_version_no;
+    insert into invoice_template_version (id, tenant_id, invoice_template_id, version_no, source_s3_id, created_by,
+                                          created_at)
+    values (p_id, p_tenant_id, p_invoice_template_id, next_version_no, p_source_s3_id, p_user_id, default);
+    return next_version_no;
+end
+$$ language plpgsql;
@@ -0,0 +1 @@
+create unique index if not exists invoice_template_version_no_idx on invoice_template_version (invoice_template_id, version_no);
@@ -3,3 +3,4 @@ pub mod invoice_template_db_mapping;
 pub mod invoice_template_models;
 pub mod invoice_template_service;
 pub mod invoice_template_http_api;
+pub mod invoice_template_version_db_mapping;
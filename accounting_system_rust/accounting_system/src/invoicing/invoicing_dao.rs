@@ -4,35 +4,137 @@ use std::sync::Arc;
 use anyhow::Context;
 use async_trait::async_trait;
 use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
 use uuid::Uuid;
 
 use crate::common_utils::dao_error::DaoError;
 use crate::common_utils::pg_util::pg_util::ToPostgresString;
 use crate::common_utils::utils::parse_db_output_of_insert_create_and_return_json;
-use crate::invoicing::invoicing_dao_models::InvoiceDb;
-use crate::invoicing::invoicing_domain_models::CreateInvoiceDbResponse;
+use crate::invoicing::invoicing_dao_models::{
+    AmendInvoiceDbResponse, InvoiceAmendmentStateDb, InvoiceDb, InvoiceDraftDb,
+    InvoiceRenderDetailDb, InvoiceTransferDb,
+};
+use crate::invoicing::invoicing_domain_models::{CreateInvoiceDbResponse, EInvoicePortalResponse};
+use crate::invoicing::invoicing_request_models::InvoiceVersion;
+
+const CREATE_DRAFT_INVOICE: &str = "select create_draft_invoice($1,$2)";
+
+const UPDATE_DRAFT_INVOICE: &str = "select update_draft_invoice($1,$2,$3)";
+
+const INVOICE_DRAFT_QUERY: &str = "select draft_request,total_tax_amount,total_payable_amount \
+from invoice where id=$1 and tenant_id=$2";
+
+const ISSUE_INVOICE: &str = "select issue_invoice($1,$2,$3,$4,$5,$6,$7)";
+
+const INVOICE_AMENDMENT_STATE_QUERY: &str = "select i.invoice_number,i.invoice_status::text,\
+coalesce(i.entity_version_id,0),i.e_invoicing_applicable,i.supplier_business_entity,i.invoicing_mst_id,\
+i.invoice_date_ms,i.financial_year,i.amount_received+i.discount_allowed,\
+exists(select 1 from gstr1_filing f join business_entity s on upper(s.gstin)=f.gstin \
+where s.id=i.supplier_business_entity and f.tenant_id=i.tenant_id and f.active \
+and i.invoice_date_ms>=f.period_start_ms and i.invoice_date_ms<f.period_end_ms) \
+from invoice i where i.id=$1 and i.tenant_id=$2";
+
+const AMEND_INVOICE: &str = "select amend_invoice($1,$2,$3,$4)";
+
+const INVOICE_VERSIONS_QUERY: &str = "select entity_version_id,amendment_reason,amended_by,\
+created_at,invoice_snapshot,lines_snapshot,additional_charges_snapshot \
+from invoice_version where invoice_id=$1 and tenant_id=$2 order by entity_version_id";
+
+const INVOICE_RENDER_DETAIL_QUERY: &str = "select i.supplier_business_entity,i.invoice_template_id,\
+i.e_invoicing_applicable and i.b2b_invoice,e.irn,e.ack_no,e.ack_date_ms,e.signed_qr_code from invoice i \
+left join invoice_einvoice_detail e on e.invoice_id=i.id and e.tenant_id=i.tenant_id \
+where i.id=$1 and i.tenant_id=$2";
+
+const INSERT_EINVOICE_DETAIL: &str = "insert into invoice_einvoice_detail \
+(id,tenant_id,invoice_id,irn,ack_no,ack_date_ms,signed_qr_code) values (uuid_generate_v7(),$1,$2,$3,$4,$5,$6) \
+on conflict (tenant_id,invoice_id) do nothing";
 
 struct InvoicingDaoImpl {
     postgres_client: Arc<Pool>,
 }
 
+#[cfg_attr(test, automock)]
 #[async_trait]
 pub trait InvoicingDao: Send + Sync {
-    async fn create_invoice(
+    async fn create_invoice<'a>(
         &self,
-        invoice_db: &InvoiceDb,
+        invoice_db: &InvoiceDb<'a>,
     ) -> Result<CreateInvoiceDbResponse, DaoError>;
     async fn is_invoice_pdf_created(
         &self,
         tenant_id: Uuid,
         invoice_id: Uuid,
     ) -> Result<bool, DaoError>;
+    async fn get_invoice_render_detail(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceRenderDetailDb>, DaoError>;
     async fn persist_invoice_pdf_dtl(
         &self,
         tenant_id: Uuid,
         invoice_id: Uuid,
         pdf_key: &str,
     ) -> Result<(), DaoError>;
+    ///stores the irn of the invoice, the irn stored first is kept as the portal returns the same irn again
+    async fn persist_einvoice_detail(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        detail: &EInvoicePortalResponse,
+    ) -> Result<(), DaoError>;
+    ///creates the invoice without taking a number from the invoicing series, returns the invoice id
+    async fn create_draft_invoice<'a>(
+        &self,
+        invoice_db: &InvoiceDb<'a>,
+        draft_request: &serde_json::Value,
+    ) -> Result<Uuid, DaoError>;
+    ///replaces the content of the draft, returns false if the invoice is not a draft
+    async fn update_draft_invoice<'a>(
+        &self,
+        invoice_id: Uuid,
+        invoice_db: &InvoiceDb<'a>,
+        draft_request: &serde_json::Value,
+    ) -> Result<bool, DaoError>;
+    async fn get_invoice_draft(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceDraftDb>, DaoError>;
+    ///assigns the invoice number to an approved invoice and posts the transfers, returns none if the invoice does
+    /// not exist
+    #[allow(clippy::too_many_arguments)]
+    async fn issue_invoice(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        invoice_date_ms: i64,
+        financial_year: i16,
+        user_id: Uuid,
+        transfers: &[InvoiceTransferDb],
+        transfer_code: i16,
+    ) -> Result<Option<String>, DaoError>;
+    async fn get_invoice_amendment_state(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceAmendmentStateDb>, DaoError>;
+    ///keeps the current content as a version and replaces it, the checks of amendment are repeated under a row lock
+    async fn amend_invoice<'a>(
+        &self,
+        invoice_id: Uuid,
+        expected_version: i32,
+        invoice_db: &InvoiceDb<'a>,
+        amendment_reason: &str,
+    ) -> Result<AmendInvoiceDbResponse, DaoError>;
+    ///versions before each amendment, oldest first
+    async fn get_invoice_versions(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Vec<InvoiceVersion>, DaoError>;
 }
 
 pub fn get_invoicing_dao(arc: Arc<Pool>) -> Arc<dyn InvoicingDao> {
@@ -42,11 +144,42 @@ pub fn get_invoicing_dao(arc: Arc<Pool>) -> Arc<dyn InvoicingDao> {
     Arc::new(p)
 }
 
+impl From<Row> for InvoiceAmendmentStateDb {
+    fn from(row: Row) -> Self {
+        InvoiceAmendmentStateDb {
+            invoice_number: row.get(0),
+            invoice_status: row.get(1),
+            entity_version_id: row.get(2),
+            e_invoicing_applicable: row.get(3),
+            supplier_id: row.get(4),
+            invoicing_series_mst_id: row.get(5),
+            invoice_date_ms: row.get(6),
+            financial_year: row.get(7),
+            amount_settled: row.get(8),
+            gstr1_filed: row.get(9),
+        }
+    }
+}
+
+impl From<Row> for InvoiceVersion {
+    fn from(row: Row) -> Self {
+        InvoiceVersion {
+            entity_version_id: row.get(0),
+            amendment_reason: row.get(1),
+            amended_by: row.get(2),
+            amended_at: row.get(3),
+            invoice: row.get(4),
+            invoice_lines: row.get(5),
+            additional_charges: row.get(6),
+        }
+    }
+}
+
 #[async_trait]
 impl InvoicingDao for InvoicingDaoImpl {
-    async fn create_invoice(
+    async fn create_invoice<'a>(
         &self,
-        invoice_db: &InvoiceDb,
+        invoice_db: &InvoiceDb<'a>,
     ) -> Result<CreateInvoiceDbResponse, DaoError> {
         let mut simple_query = String::with_capacity(1500);
         write!(&mut simple_query, "begin transaction;\n")?;
@@ -78,6 +211,31 @@ impl InvoicingDao for InvoicingDaoImpl {
         Ok(is_created)
     }
 
+    async fn get_invoice_render_detail(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceRenderDetailDb>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let row = conn
+            .query_opt(INVOICE_RENDER_DETAIL_QUERY, &[&invoice_id, &tenant_id])
+            .await?;
+        Ok(row.map(|a| {
+            let irn: Option<String> = a.get(3);
+            InvoiceRenderDetailDb {
+                supplier_id: a.get(0),
+                invoice_template_id: a.get(1),
+                einvoice_required: a.get(2),
+                einvoice: irn.map(|irn| EInvoicePortalResponse {
+                    irn,
+                    ack_no: a.get(4),
+                    ack_date_ms: a.get(5),
+                    signed_qr_code: a.get(6),
+                }),
+            }
+        }))
+    }
+
     async fn persist_invoice_pdf_dtl(
         &self,
         tenant_id: Uuid,
@@ -92,6 +250,169 @@ impl InvoicingDao for InvoicingDaoImpl {
         let _ = conn.simple_query(query.as_str()).await?;
         Ok(())
     }
+
+    async fn persist_einvoice_detail(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        detail: &EInvoicePortalResponse,
+    ) -> Result<(), DaoError> {
+        let conn = self.postgres_client.get().await?;
+        conn.execute(
+            INSERT_EINVOICE_DETAIL,
+            &[
+                &tenant_id,
+                &invoice_id,
+                &detail.irn,
+                &detail.ack_no,
+                &detail.ack_date_ms,
+                &detail.signed_qr_code,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn create_draft_invoice<'a>(
+        &self,
+        invoice_db: &InvoiceDb<'a>,
+        draft_request: &serde_json::Value,
+    ) -> Result<Uuid, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_DRAFT_INVOICE, &[invoice_db, draft_request])
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let invoice_id = json
+            .get("invoice_id")
+            .and_then(|a| a.as_str())
+            .and_then(|a| Uuid::parse_str(a).ok())
+            .context("invalid create draft invoice response")?;
+        Ok(invoice_id)
+    }
+
+    async fn update_draft_invoice<'a>(
+        &self,
+        invoice_id: Uuid,
+        invoice_db: &InvoiceDb<'a>,
+        draft_request: &serde_json::Value,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                UPDATE_DRAFT_INVOICE,
+                &[&invoice_id, invoice_db, draft_request],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn get_invoice_draft(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceDraftDb>, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_opt(INVOICE_DRAFT_QUERY, &[&invoice_id, &tenant_id])
+            .await?;
+        Ok(row.map(|a| InvoiceDraftDb {
+            draft_request: a.get(0),
+            total_tax_amount: a.get(1),
+            total_payable_amount: a.get(2),
+        }))
+    }
+
+    async fn issue_invoice(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+        invoice_date_ms: i64,
+        financial_year: i16,
+        user_id: Uuid,
+        transfers: &[InvoiceTransferDb],
+        transfer_code: i16,
+    ) -> Result<Option<String>, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                ISSUE_INVOICE,
+                &[
+                    &tenant_id,
+                    &invoice_id,
+                    &invoice_date_ms,
+                    &financial_year,
+                    &user_id,
+                    &transfers,
+                    &transfer_code,
+                ],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn get_invoice_amendment_state(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Option<InvoiceAmendmentStateDb>, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_opt(INVOICE_AMENDMENT_STATE_QUERY, &[&invoice_id, &tenant_id])
+            .await?;
+        Ok(row.map(|a| a.into()))
+    }
+
+    async fn amend_invoice<'a>(
+        &self,
+        invoice_id: Uuid,
+        expected_version: i32,
+        invoice_db: &InvoiceDb<'a>,
+        amendment_reason: &str,
+    ) -> Result<AmendInvoiceDbResponse, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                AMEND_INVOICE,
+                &[
+                    &invoice_id,
+                    &expected_version,
+                    invoice_db,
+                    &amendment_reason,
+                ],
+            )
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let resp: AmendInvoiceDbResponse = serde_json::from_value(json)
+            .context("could not deserialize into AmendInvoiceDbResponse")?;
+        Ok(resp)
+    }
+
+    async fn get_invoice_versions(
+        &self,
+        tenant_id: Uuid,
+        invoice_id: Uuid,
+    ) -> Result<Vec<InvoiceVersion>, DaoError> {
+        let rows = self
+            .postgres_client
+            .get()
+            .await?
+            .query(INVOICE_VERSIONS_QUERY, &[&invoice_id, &tenant_id])
+            .await?;
+        Ok(rows.into_iter().map(|a| a.into()).collect())
+    }
 }
 
 #[cfg(test)]
@@ -118,14 +439,24 @@ mod tests {
     use crate::common_utils::pg_util::pg_util::ToPostgresString;
     use crate::common_utils::utils::parse_db_output_of_insert_create_and_return_json;
     use crate::invoicing::invoicing_dao::{InvoicingDao, InvoicingDaoImpl};
-    use crate::invoicing::invoicing_dao_models::convert_to_invoice_db;
+    use crate::accounting::account::account_models::tests::{
+        SEED_CREDIT_ACCOUNT_ID, SEED_DEBIT_ACCOUNT_ID,
+    };
+    use crate::invoicing::invoicing_dao_models::{
+        convert_to_invoice_db, AmendInvoiceDbStatus, InvoiceRenderDetailDb, InvoiceTransferDb,
+    };
+    use crate::invoicing::invoicing_domain_models::EInvoicePortalResponse;
     use crate::invoicing::invoicing_request_models::tests::{
         a_create_invoice_request, SEED_INVOICE_ID,
     };
+    use crate::invoicing::invoicing_request_models::SupplyClassification;
     use crate::invoicing::invoicing_series::invoicing_series_models::tests::SEED_INVOICING_SERIES_MST_ID;
     use crate::invoicing::payment_term::payment_term_models::tests::SEED_PAYMENT_TERM_ID;
+    use crate::invoicing::place_of_supply::tests::a_place_of_supply;
+    use crate::invoicing::place_of_supply::SupplyType;
     use crate::masters::product_item_master::product_item_models::tests::SEED_PRODUCT_ITEM_ID;
     use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+    use crate::tenant::tenant_models::GstRegistrationType;
 
     async fn get_dao() -> InvoicingDaoImpl {
         get_dao_generic(|c| InvoicingDaoImpl { postgres_client: c }, None).await
@@ -141,9 +472,14 @@ mod tests {
         let req = a_create_invoice_request(Default::default());
         let pids = get_products();
         let req = req
-            .to_create_invoice_with_all_details_included(pids)
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
             .unwrap();
-        let p = convert_to_invoice_db(&req, 2, false, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
         let dp = dao.create_invoice(&p).await.unwrap();
         dao.persist_invoice_pdf_dtl(*SEED_TENANT_ID, dp.invoice_id, "somekey")
             .await
@@ -163,15 +499,75 @@ mod tests {
         assert_that!(key).is_equal_to("somekey")
     }
 
+    #[tokio::test]
+    async fn test_get_invoice_render_detail() {
+        let dao = get_dao().await;
+        let req = a_create_invoice_request(Default::default());
+        let (supplier_id, invoice_template_id) = (req.supplier_id, req.invoice_template_id);
+        let req = req
+            .to_create_invoice_with_all_details_included(
+                get_products(),
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let mut expected = InvoiceRenderDetailDb {
+            supplier_id,
+            invoice_template_id,
+            einvoice_required: p.e_invoicing_applicable && p.b2b_invoice,
+            einvoice: None,
+        };
+        let dp = dao.create_invoice(&p).await.unwrap();
+        let detail = dao
+            .get_invoice_render_detail(*SEED_TENANT_ID, dp.invoice_id)
+            .await
+            .unwrap();
+        assert_that!(detail).is_equal_to(Some(expected.clone()));
+        let einvoice = EInvoicePortalResponse {
+            irn: "a5c12dca80e743321740b001fd70953e8738d109865d28ba4013750f2046f229".to_string(),
+            ack_no: "112010036563310".to_string(),
+            ack_date_ms: 1_700_000_000_000,
+            signed_qr_code: "signed.qr.code".to_string(),
+        };
+        dao.persist_einvoice_detail(*SEED_TENANT_ID, dp.invoice_id, &einvoice)
+            .await
+            .unwrap();
+        let regenerated = EInvoicePortalResponse {
+            ack_no: "112010036563311".to_string(),
+            ..einvoice.clone()
+        };
+        dao.persist_einvoice_detail(*SEED_TENANT_ID, dp.invoice_id, &regenerated)
+            .await
+            .unwrap();
+        expected.einvoice = Some(einvoice);
+        let detail = dao
+            .get_invoice_render_detail(*SEED_TENANT_ID, dp.invoice_id)
+            .await
+            .unwrap();
+        assert_that!(detail).is_equal_to(Some(expected));
+        let detail = dao
+            .get_invoice_render_detail(*SEED_TENANT_ID, Uuid::now_v7())
+            .await
+            .unwrap();
+        assert_that!(detail).is_none();
+    }
+
     #[tokio::test]
     async fn test_is_invoice_created() {
         let dao = get_dao().await;
         let req = a_create_invoice_request(Default::default());
         let pids = get_products();
         let req = req
-            .to_create_invoice_with_all_details_included(pids)
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
             .unwrap();
-        let p = convert_to_invoice_db(&req, 2, false, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
         let dp = dao.create_invoice(&p).await.unwrap();
         let _ = dao
             .postgres_client
@@ -213,9 +609,14 @@ mod tests {
         let req = a_create_invoice_request(Default::default());
         let pids = get_products();
         let req = req
-            .to_create_invoice_with_all_details_included(pids)
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
             .unwrap();
-        let p = convert_to_invoice_db(&req, 2, false, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
         let p = dao.create_invoice(&p).await.unwrap();
         let row = dao
             .postgres_client
@@ -228,15 +629,170 @@ mod tests {
         assert_that!(row).is_some();
     }
 
+    #[tokio::test]
+    async fn test_draft_invoice_gets_number_only_on_issue() {
+        let dao = get_dao().await;
+        let req = a_create_invoice_request(Default::default());
+        let draft_request = serde_json::to_value(&req).unwrap();
+        let pids = get_products();
+        let req = req
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let invoice_id = dao.create_draft_invoice(&p, &draft_request).await.unwrap();
+        let updated = dao
+            .update_draft_invoice(invoice_id, &p, &draft_request)
+            .await
+            .unwrap();
+        assert_that!(updated).is_true();
+        let draft = dao
+            .get_invoice_draft(*SEED_TENANT_ID, invoice_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_that!(draft.draft_request).is_some();
+        let conn = dao.postgres_client.get().await.unwrap();
+        let row = conn
+            .query_one(
+                "select invoice_number from invoice where id=$1",
+                &[&invoice_id],
+            )
+            .await
+            .unwrap();
+        let number: Option<String> = row.get(0);
+        assert_that!(number).is_none();
+        conn.execute(
+            "update invoice set invoice_status='approved' where id=$1",
+            &[&invoice_id],
+        )
+        .await
+        .unwrap();
+        let number = dao
+            .issue_invoice(
+                *SEED_TENANT_ID,
+                invoice_id,
+                p.invoice_date_ms,
+                p.financial_year,
+                *SEED_USER_ID,
+                &p.transfers,
+                p.transfer_code,
+            )
+            .await
+            .unwrap();
+        assert_that!(number).is_some();
+        let updated = dao
+            .update_draft_invoice(invoice_id, &p, &draft_request)
+            .await
+            .unwrap();
+        assert_that!(updated).is_false();
+    }
+
+    #[tokio::test]
+    async fn test_amend_invoice_keeps_prior_version() {
+        let dao = get_dao().await;
+        let req = a_create_invoice_request(Default::default());
+        let pids = get_products();
+        let req = req
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let created = dao.create_invoice(&p).await.unwrap();
+        let state = dao
+            .get_invoice_amendment_state(*SEED_TENANT_ID, created.invoice_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_that!(state.entity_version_id).is_equal_to(0);
+        assert_that!(state.gstr1_filed).is_false();
+        let resp = dao
+            .amend_invoice(created.invoice_id, 0, &p, "rate corrected")
+            .await
+            .unwrap();
+        assert_that!(resp.status).is_equal_to(AmendInvoiceDbStatus::Amended);
+        assert_that!(resp.entity_version_id).is_equal_to(Some(1));
+        let resp = dao
+            .amend_invoice(created.invoice_id, 0, &p, "rate corrected")
+            .await
+            .unwrap();
+        assert_that!(resp.status).is_equal_to(AmendInvoiceDbStatus::VersionConflict);
+        let versions = dao
+            .get_invoice_versions(*SEED_TENANT_ID, created.invoice_id)
+            .await
+            .unwrap();
+        assert_that!(versions.len()).is_equal_to(1);
+        assert_that!(versions[0].entity_version_id).is_equal_to(0);
+    }
+
+    #[tokio::test]
+    async fn test_amend_invoice_reposts_ledger_transfers() {
+        let dao = get_dao().await;
+        let req = a_create_invoice_request(Default::default())
+            .to_create_invoice_with_all_details_included(
+                get_products(),
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let mut p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        p.transfers = vec![InvoiceTransferDb {
+            debit_account_id: *SEED_DEBIT_ACCOUNT_ID,
+            credit_account_id: *SEED_CREDIT_ACCOUNT_ID,
+            amount: 118.0,
+        }];
+        let created = dao.create_invoice(&p).await.unwrap();
+        p.transfers[0].amount = 236.0;
+        let resp = dao
+            .amend_invoice(created.invoice_id, 0, &p, "rate corrected")
+            .await
+            .unwrap();
+        assert_that!(resp.status).is_equal_to(AmendInvoiceDbStatus::Amended);
+        let row = dao
+            .postgres_client
+            .get()
+            .await
+            .unwrap()
+            .query_one(
+                "select coalesce(sum(case when debit_account_id = $2 then amount else -amount end), 0)::bigint \
+                from transfer where grouping_id = $1",
+                &[&created.invoice_id, &*SEED_DEBIT_ACCOUNT_ID],
+            )
+            .await
+            .unwrap();
+        let net: i64 = row.get(0);
+        assert_that!(net).is_equal_to(23600);
+        p.transfers = vec![];
+        let resp = dao
+            .amend_invoice(created.invoice_id, 1, &p, "accounts removed")
+            .await
+            .unwrap();
+        assert_that!(resp.status).is_equal_to(AmendInvoiceDbStatus::LedgerAccountsRequired);
+    }
+
     #[tokio::test]
     async fn test_persist_invoice_lines() {
         let dao = get_dao().await;
         let req = a_create_invoice_request(Default::default());
         let pids = get_products();
         let req = req
-            .to_create_invoice_with_all_details_included(pids)
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
             .unwrap();
-        let p = convert_to_invoice_db(&req, 2, false, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
         let mut input_str = String::with_capacity(1000);
         write!(&mut input_str, "call persist_invoice_lines(").unwrap();
         p.fmt_postgres(&mut input_str).unwrap();
@@ -269,14 +825,19 @@ mod tests {
         let req = a_create_invoice_request(Default::default());
         let pids = get_products();
         let req = req
-            .to_create_invoice_with_all_details_included(pids)
+            .to_create_invoice_with_all_details_included(
+                pids,
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
             .unwrap();
-        let p = convert_to_invoice_db(&req, 2, false, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let p = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
         let mut input_str = String::with_capacity(1000);
         write!(&mut input_str, "select 1;").unwrap();
         write!(&mut input_str, "select create_invoice_table_entry(").unwrap();
         p.fmt_postgres(&mut input_str).unwrap();
-        write!(&mut input_str, ",'{}');", *SEED_PAYMENT_TERM_ID).unwrap();
+        write!(&mut input_str, ",'{}',null);", *SEED_PAYMENT_TERM_ID).unwrap();
         let rows = dao
             .postgres_client
             .get()
@@ -1,16 +1,23 @@
 use anyhow::{anyhow, Context};
 use chrono::TimeZone;
 use itertools::Itertools;
+use serde::Deserialize;
 use tokio_postgres::types::ToSql;
 use uuid::Uuid;
 use xxhash_rust::xxh32;
 
 use crate::common_utils::pg_util::pg_util::{create_composite_type_db_row, ToPostgresString};
 use crate::common_utils::utils::current_indian_financial_year;
+use crate::invoicing::invoicing_domain_models::EInvoicePortalResponse;
 use crate::invoicing::invoicing_request_models::{
     CreateAdditionalChargeRequest, CreateInvoiceLineRequestWithAllDetails,
-    CreateInvoiceWithAllDetailsIncluded, PaymentTermsValidated,
+    CreateInvoiceWithAllDetailsIncluded, DocumentType, ExportDetail, InvoiceLedgerAccounts,
+    PaymentTermsValidated,
 };
+use crate::invoicing::place_of_supply::PlaceOfSupply;
+
+///transaction type code of the ledger transfers posted for a sales invoice
+pub const INVOICE_TRANSFER_CODE: i16 = 5;
 
 #[derive(Debug, ToSql)]
 #[postgres(name = "create_payment_terms_request")]
@@ -101,6 +108,7 @@ pub struct AdditionalChargeDb<'a> {
     pub line_title: &'a str,
     pub title_xx_hash: i64,
     pub rate: f64,
+    pub tax_percentage: f32,
 }
 
 impl ToPostgresString for AdditionalChargeDb<'_> {
@@ -111,6 +119,7 @@ impl ToPostgresString for AdditionalChargeDb<'_> {
             &self.line_title,
             &self.title_xx_hash,
             &self.rate,
+            &self.tax_percentage,
         ];
         create_composite_type_db_row(fields, f)
     }
@@ -120,6 +129,63 @@ impl ToPostgresString for AdditionalChargeDb<'_> {
     }
 }
 
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_invoice_export_detail_request")]
+pub struct ExportDetailDb<'a> {
+    pub export_type: &'static str,
+    pub lut_reference: Option<&'a str>,
+    pub port_code: Option<&'a str>,
+    pub shipping_bill_number: Option<&'a str>,
+    pub shipping_bill_date_ms: Option<i64>,
+    pub country_id: Uuid,
+    pub exchange_rate: f64,
+    pub total_payable_amount_inr: f64,
+}
+
+impl ToPostgresString for ExportDetailDb<'_> {
+    fn fmt_postgres(&self, f: &mut String) -> std::fmt::Result {
+        let fields: &[&dyn ToPostgresString] = &[
+            &self.export_type,
+            &self.lut_reference,
+            &self.port_code,
+            &self.shipping_bill_number,
+            &self.shipping_bill_date_ms,
+            &self.country_id,
+            &self.exchange_rate,
+            &self.total_payable_amount_inr,
+        ];
+        create_composite_type_db_row(fields, f)
+    }
+
+    fn db_type_name(&self) -> &'static str {
+        "create_invoice_export_detail_request"
+    }
+}
+
+///one ledger transfer of the invoice, amount in currency units
+#[derive(Debug, Clone, PartialEq, ToSql)]
+#[postgres(name = "create_invoice_transfer_request")]
+pub struct InvoiceTransferDb {
+    pub debit_account_id: Uuid,
+    pub credit_account_id: Uuid,
+    pub amount: f64,
+}
+
+impl ToPostgresString for InvoiceTransferDb {
+    fn fmt_postgres(&self, f: &mut String) -> std::fmt::Result {
+        let fields: &[&dyn ToPostgresString] = &[
+            &self.debit_account_id,
+            &self.credit_account_id,
+            &self.amount,
+        ];
+        create_composite_type_db_row(fields, f)
+    }
+
+    fn db_type_name(&self) -> &'static str {
+        "create_invoice_transfer_request"
+    }
+}
+
 #[derive(Debug, ToSql)]
 #[postgres(name = "create_invoice_request")]
 pub struct InvoiceDb<'a> {
@@ -151,6 +217,14 @@ pub struct InvoiceDb<'a> {
     pub igst_applicable: bool,
     pub invoice_remarks: Option<&'a str>,
     pub ecommerce_gstin: Option<&'a str>,
+    pub export_detail: Option<ExportDetailDb<'a>>,
+    pub place_of_supply: &'a str,
+    pub supply_classification: &'static str,
+    pub sez_lut_reference: Option<&'a str>,
+    pub document_type: &'static str,
+    ///posted when the invoice is issued, empty if the request has no ledger accounts
+    pub transfers: Vec<InvoiceTransferDb>,
+    pub transfer_code: i16,
 }
 
 impl ToPostgresString for InvoiceDb<'_> {
@@ -184,6 +258,13 @@ impl ToPostgresString for InvoiceDb<'_> {
             &self.igst_applicable,
             &self.invoice_remarks,
             &self.ecommerce_gstin,
+            &self.export_detail,
+            &self.place_of_supply,
+            &self.supply_classification,
+            &self.sez_lut_reference,
+            &self.document_type,
+            &self.transfers,
+            &self.transfer_code,
         ];
         create_composite_type_db_row(fields, f)
     }
@@ -201,17 +282,121 @@ fn convert_to_payment_terms_db(req: &PaymentTermsValidated) -> PaymentTermsDb {
     }
 }
 
-fn convert_to_additional_charge_db(
-    req: &CreateAdditionalChargeRequest,
+fn convert_to_additional_charge_db<'a>(
+    req: &'a CreateAdditionalChargeRequest,
     line_no: i16,
-) -> AdditionalChargeDb {
-    AdditionalChargeDb {
+    invoice: &CreateInvoiceWithAllDetailsIncluded,
+) -> anyhow::Result<AdditionalChargeDb<'a>> {
+    Ok(AdditionalChargeDb {
         line_id: Uuid::now_v7(),
         line_no,
         line_title: req.line_title.inner(),
         title_xx_hash: compute_32_bit_xx_hash(req.line_title.inner()),
         rate: req.rate.inner(),
+        tax_percentage: invoice.additional_charge_tax_percentage(req)?,
+    })
+}
+
+fn convert_to_export_detail_db(req: &ExportDetail, total_payable_amount: f64) -> ExportDetailDb {
+    ExportDetailDb {
+        export_type: req.export_type.as_str(),
+        lut_reference: req.lut_reference.as_deref(),
+        port_code: req.port_code.as_deref(),
+        shipping_bill_number: req.shipping_bill_number.as_deref(),
+        shipping_bill_date_ms: req.shipping_bill_date_ms(),
+        country_id: req.country_id,
+        exchange_rate: req.exchange_rate,
+        total_payable_amount_inr: req.to_inr(total_payable_amount),
+    }
+}
+
+fn add_transfer(
+    transfers: &mut Vec<InvoiceTransferDb>,
+    debit_account_id: Uuid,
+    credit_account_id: Uuid,
+    amount: f64,
+) {
+    if amount == 0.0 {
+        return;
+    }
+    match transfers.iter_mut().find(|a| {
+        a.debit_account_id == debit_account_id && a.credit_account_id == credit_account_id
+    }) {
+        Some(transfer) => transfer.amount += amount,
+        None => transfers.push(InvoiceTransferDb {
+            debit_account_id,
+            credit_account_id,
+            amount,
+        }),
+    }
+}
+
+///the receivable account is debited with the amount payable. tax on the lines and the additional charges is
+/// credited to the output tax account of its head and the rest, including the round off, to the sales account.
+/// tax of a line under reverse charge is paid by the recipient, tax on the charges follows the lines unless every
+/// line is under reverse charge, and a bill of supply carries no tax. each head is rounded to the currency scale
+/// and an odd minor unit of the intra-state tax goes to cgst, so the heads add up to the tax rounded once
+pub(crate) fn compute_invoice_transfers(
+    req: &CreateInvoiceWithAllDetailsIncluded,
+    accounts: &InvoiceLedgerAccounts,
+    igst_applicable: bool,
+    total_payable_amount: f64,
+    currency_scale: i16,
+) -> anyhow::Result<Vec<InvoiceTransferDb>> {
+    let mut tax_amount = 0.0;
+    let mut cess_amount = 0.0;
+    if req.document_type != DocumentType::BillOfSupply {
+        for line in req
+            .invoice_lines
+            .iter()
+            .filter(|a| !a.reverse_charge_applicable)
+        {
+            tax_amount += line.tax_amount()?;
+            cess_amount += line.cess_amount()?;
+        }
+        if !req
+            .invoice_lines
+            .iter()
+            .all(|a| a.reverse_charge_applicable)
+        {
+            for charge in req.additional_charges.iter() {
+                tax_amount += req.additional_charge_tax_amount(charge)?;
+            }
+        }
+    }
+    let minor_unit = 10_f64.powi(currency_scale as i32);
+    let to_minor = |amount: f64| (amount * minor_unit).round() as i64;
+    let tax_minor = to_minor(tax_amount);
+    let (igst_minor, cgst_minor, sgst_minor) = if igst_applicable {
+        (tax_minor, 0, 0)
+    } else {
+        let sgst_minor = tax_minor / 2;
+        (0, tax_minor - sgst_minor, sgst_minor)
+    };
+    let tax_accounts = &accounts.output_tax_accounts;
+    let tax_heads = [
+        (tax_accounts.igst_account_id, igst_minor),
+        (tax_accounts.cgst_account_id, cgst_minor),
+        (tax_accounts.sgst_account_id, sgst_minor),
+        (tax_accounts.cess_account_id, to_minor(cess_amount)),
+    ];
+    let mut transfers = Vec::new();
+    let total_tax_minor: i64 = tax_heads.iter().map(|(_, amount)| amount).sum();
+    add_transfer(
+        &mut transfers,
+        accounts.receivable_account_id,
+        accounts.sales_account_id,
+        (to_minor(total_payable_amount) - total_tax_minor) as f64 / minor_unit,
+    );
+    for (account_id, amount) in tax_heads {
+        add_transfer(
+            &mut transfers,
+            accounts.receivable_account_id,
+            account_id,
+            amount as f64 / minor_unit,
+        );
     }
+    Ok(transfers)
 }
 
 fn convert_to_invoice_line_db(
@@ -222,7 +407,7 @@ fn convert_to_invoice_line_db(
     hasher.update(req.product_item_id.title.inner().as_bytes());
     hasher.update(req.product_item_id.hsn_sac_code.as_str().as_bytes());
     let hash = hasher.digest() as i64;
-    let cess_strategy = req.product_item_id.get_cess_rate()?;
+    let cess_strategy = req.cess_strategy()?;
     Ok(InvoiceLineDb {
         line_id: Uuid::now_v7(),
         line_no,
@@ -238,25 +423,14 @@ fn convert_to_invoice_line_db(
         quantity: req.quantity.get_quantity(),
         uqc: req.quantity.uom_as_str(),
         unit_price: req.unit_price.inner(),
-        tax_percentage: req
-            .product_item_id
-            .get_tax_rate()?
-            .tax_rate_percentage
-            .inner(),
+        tax_percentage: req.tax_percentage()?,
         discount_percentage: req.discount_percentage.inner(),
         cess_percentage: cess_strategy
-            .cess_strategy
             .get_cess_rate_percentage()
             .context("cess percentage cannot be none")?,
-        cess_amount_per_unit: cess_strategy
-            .cess_strategy
-            .get_cess_amount_per_unit()
-            .unwrap_or(0.0),
-        retail_sale_price_for_cess: cess_strategy
-            .cess_strategy
-            .get_retail_sale_price()
-            .unwrap_or(0.0),
-        cess_calculation_strategy: cess_strategy.cess_strategy.get_strategy_name(),
+        cess_amount_per_unit: cess_strategy.get_cess_amount_per_unit().unwrap_or(0.0),
+        retail_sale_price_for_cess: cess_strategy.get_retail_sale_price().unwrap_or(0.0),
+        cess_calculation_strategy: cess_strategy.get_strategy_name(),
         mrp: req.mrp.as_ref().map(|a| a.inner() as f32),
         batch_no: req.batch_no.as_ref().map(|a| a.inner()),
         expiry_date_ms: req.expiry_date.as_ref().map(|a| a.epoch_millis()).flatten(),
@@ -266,14 +440,25 @@ fn convert_to_invoice_line_db(
     })
 }
 
-pub fn convert_to_invoice_db(
-    req: &CreateInvoiceWithAllDetailsIncluded,
+pub fn convert_to_invoice_db<'a>(
+    req: &'a CreateInvoiceWithAllDetailsIncluded,
     currency_scale: i16,
-    igst_applicable: bool,
+    place_of_supply: &'a PlaceOfSupply,
     created_by: Uuid,
     tenant_id: Uuid,
-) -> anyhow::Result<InvoiceDb> {
+) -> anyhow::Result<InvoiceDb<'a>> {
     let date = chrono::Utc::now().naive_utc();
+    let total_payable_amount = req.total_amount(currency_scale)?;
+    let transfers = match req.ledger_accounts.as_ref() {
+        Some(accounts) => compute_invoice_transfers(
+            req,
+            accounts,
+            place_of_supply.igst_applicable(),
+            total_payable_amount,
+            currency_scale,
+        )?,
+        None => vec![],
+    };
     Ok(InvoiceDb {
         idempotence_key: req.idempotence_key,
         tenant_id,
@@ -315,18 +500,28 @@ pub fn convert_to_invoice_db(
             .additional_charges
             .iter()
             .enumerate()
-            .map(|a| convert_to_additional_charge_db(a.1, a.0 as i16))
-            .collect_vec(),
+            .map(|a| convert_to_additional_charge_db(a.1, a.0 as i16, req))
+            .collect::<anyhow::Result<Vec<AdditionalChargeDb>>>()?,
         financial_year: current_indian_financial_year() as i16,
         total_taxable_amount: req.total_taxable_amount()?,
         total_tax_amount: req.total_tax_amount()?,
         total_additional_charges_amount: req.total_additional_charge_amount(),
         round_off: 0.0,
-        total_payable_amount: req.total_amount(currency_scale)?,
+        total_payable_amount,
         created_by,
-        igst_applicable,
+        igst_applicable: place_of_supply.igst_applicable(),
         invoice_remarks: req.invoice_remarks.as_ref().map(|a| a.get_str()),
         ecommerce_gstin: req.ecommerce_gstin.as_ref().map(|a| a.get_str()),
+        export_detail: req
+            .export_detail
+            .as_ref()
+            .map(|a| convert_to_export_detail_db(a, total_payable_amount)),
+        place_of_supply: place_of_supply.state_code.as_str(),
+        supply_classification: req.supply_classification.as_str(),
+        sez_lut_reference: req.sez_lut_reference.as_ref().map(|a| a.inner()),
+        document_type: req.document_type.as_str(),
+        transfers,
+        transfer_code: INVOICE_TRANSFER_CODE,
     })
 }
 
@@ -335,3 +530,252 @@ fn compute_32_bit_xx_hash(st: &str) -> i64 {
     hasher.update(st.as_bytes());
     hasher.digest() as i64
 }
+
+///stored create request of a draft along with the totals that were approved
+#[derive(Debug)]
+pub struct InvoiceDraftDb {
+    pub draft_request: Option<serde_json::Value>,
+    pub total_tax_amount: f64,
+    pub total_payable_amount: f64,
+}
+
+///supplier whose assets and template whose layout are used for the invoice pdf, an invoice needing an e-invoice
+/// is rendered along with its irn
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceRenderDetailDb {
+    pub supplier_id: Uuid,
+    pub invoice_template_id: Uuid,
+    pub einvoice_required: bool,
+    pub einvoice: Option<EInvoicePortalResponse>,
+}
+
+///state of an invoice which decides whether it can be amended
+#[derive(Debug, Clone)]
+pub struct InvoiceAmendmentStateDb {
+    pub invoice_number: Option<String>,
+    pub invoice_status: String,
+    pub entity_version_id: i32,
+    pub e_invoicing_applicable: bool,
+    pub supplier_id: Uuid,
+    pub invoicing_series_mst_id: Uuid,
+    pub invoice_date_ms: i64,
+    pub financial_year: i16,
+    ///amount received along with the discount allowed on settlement
+    pub amount_settled: f64,
+    pub gstr1_filed: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AmendInvoiceDbStatus {
+    NotFound,
+    NotIssued,
+    Einvoiced,
+    SupplierChanged,
+    VersionConflict,
+    Gstr1Filed,
+    BelowAmountReceived,
+    LedgerAccountsRequired,
+    Amended,
+}
+
+///entity_version_id is the new version when amended and the current version otherwise
+#[derive(Debug, Deserialize)]
+pub struct AmendInvoiceDbResponse {
+    pub status: AmendInvoiceDbStatus,
+    pub entity_version_id: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use invoice_doc_generator::invoice_line::unit_price::Price;
+    use invoice_doc_generator::percentages::tax_discount_cess::GSTPercentage;
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use crate::gst_returns::gstr3b::gstr3b_models::tests::a_gst_ledger_accounts;
+    use crate::invoicing::invoicing_dao_models::{compute_invoice_transfers, InvoiceTransferDb};
+    use crate::invoicing::invoicing_request_models::tests::{
+        a_create_additional_charge_request, a_create_invoice_line_request, a_create_invoice_request,
+    };
+    use crate::invoicing::invoicing_request_models::{
+        AdditionalChargeTaxability, CreateAdditionalChargeRequestBuilder,
+        CreateInvoiceLineRequestBuilder, CreateInvoiceRequestBuilder,
+        CreateInvoiceWithAllDetailsIncluded, InvoiceLedgerAccounts, SupplyClassification,
+    };
+    use crate::masters::product_item_master::product_item_models::tests::{
+        a_product_item_response, SEED_PRODUCT_ITEM_ID,
+    };
+    use crate::tenant::tenant_models::GstRegistrationType;
+
+    fn an_invoice_with_charge(
+        reverse_charge: bool,
+        registration_type: GstRegistrationType,
+    ) -> CreateInvoiceWithAllDetailsIncluded {
+        let mut line = CreateInvoiceLineRequestBuilder::default();
+        line.reverse_charge_applicable(reverse_charge);
+        let mut charge = CreateAdditionalChargeRequestBuilder::default();
+        charge.rate(Price::new(100.0).unwrap());
+        charge.taxability(AdditionalChargeTaxability::DeclaredRate {
+            tax_percentage: GSTPercentage::new(18.0).unwrap(),
+        });
+        let mut builder = CreateInvoiceRequestBuilder::default();
+        builder.invoice_lines(vec![a_create_invoice_line_request(line)]);
+        builder.additional_charges(vec![a_create_additional_charge_request(charge)]);
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        a_create_invoice_request(builder)
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                registration_type,
+            )
+            .unwrap()
+    }
+
+    fn an_invoice_with_lines(lines: &[(f64, bool)]) -> CreateInvoiceWithAllDetailsIncluded {
+        let lines = lines
+            .iter()
+            .map(|(unit_price, reverse_charge)| {
+                let mut line = CreateInvoiceLineRequestBuilder::default();
+                line.unit_price(Price::new(*unit_price).unwrap());
+                line.reverse_charge_applicable(*reverse_charge);
+                a_create_invoice_line_request(line)
+            })
+            .collect();
+        let mut builder = CreateInvoiceRequestBuilder::default();
+        builder.invoice_lines(lines);
+        builder.additional_charges(vec![]);
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        a_create_invoice_request(builder)
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap()
+    }
+
+    fn amount_credited(transfers: &[InvoiceTransferDb], account_id: Uuid) -> f64 {
+        transfers
+            .iter()
+            .filter(|a| a.credit_account_id == account_id)
+            .map(|a| a.amount)
+            .sum()
+    }
+
+    #[rstest]
+    #[case(false)]
+    #[case(true)]
+    fn test_tax_on_charges_is_credited_to_output_tax_accounts(#[case] igst_applicable: bool) {
+        let req = an_invoice_with_charge(false, GstRegistrationType::Regular);
+        let accounts = InvoiceLedgerAccounts {
+            receivable_account_id: Uuid::now_v7(),
+            sales_account_id: Uuid::now_v7(),
+            output_tax_accounts: a_gst_ledger_accounts(),
+        };
+        let total_payable = req.total_amount(2).unwrap();
+        let transfers =
+            compute_invoice_transfers(&req, &accounts, igst_applicable, total_payable, 2).unwrap();
+        let tax = ((req.invoice_lines[0].tax_amount().unwrap() + 18.0) * 100.0).round() / 100.0;
+        let tax_accounts = &accounts.output_tax_accounts;
+        if igst_applicable {
+            assert!((amount_credited(&transfers, tax_accounts.igst_account_id) - tax).abs() < 1e-6);
+            assert_eq!(
+                amount_credited(&transfers, tax_accounts.cgst_account_id),
+                0.0
+            );
+        } else {
+            let cgst = amount_credited(&transfers, tax_accounts.cgst_account_id);
+            let sgst = amount_credited(&transfers, tax_accounts.sgst_account_id);
+            assert!((cgst + sgst - tax).abs() < 1e-6);
+            assert!((cgst - sgst).abs() < 0.01 + 1e-6);
+            assert_eq!(
+                amount_credited(&transfers, tax_accounts.igst_account_id),
+                0.0
+            );
+        }
+        assert!(transfers
+            .iter()
+            .all(|a| a.debit_account_id == accounts.receivable_account_id));
+        let total: f64 = transfers.iter().map(|a| a.amount).sum();
+        assert!((total - total_payable).abs() < 1e-6);
+    }
+
+    #[rstest]
+    #[case(true, GstRegistrationType::Regular)]
+    #[case(false, GstRegistrationType::Composition)]
+    fn test_reverse_charge_invoice_and_bill_of_supply_are_credited_to_sales_only(
+        #[case] reverse_charge: bool,
+        #[case] registration_type: GstRegistrationType,
+    ) {
+        let req = an_invoice_with_charge(reverse_charge, registration_type);
+        let accounts = InvoiceLedgerAccounts {
+            receivable_account_id: Uuid::now_v7(),
+            sales_account_id: Uuid::now_v7(),
+            output_tax_accounts: a_gst_ledger_accounts(),
+        };
+        let total_payable = req.total_amount(2).unwrap();
+        let transfers =
+            compute_invoice_transfers(&req, &accounts, false, total_payable, 2).unwrap();
+        assert_eq!(
+            transfers,
+            vec![InvoiceTransferDb {
+                debit_account_id: accounts.receivable_account_id,
+                credit_account_id: accounts.sales_account_id,
+                amount: total_payable,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_only_reverse_charge_lines_are_left_out_of_output_tax() {
+        let req = an_invoice_with_lines(&[(10.0, false), (20.0, true)]);
+        let accounts = InvoiceLedgerAccounts {
+            receivable_account_id: Uuid::now_v7(),
+            sales_account_id: Uuid::now_v7(),
+            output_tax_accounts: a_gst_ledger_accounts(),
+        };
+        let total_payable = req.total_amount(2).unwrap();
+        let transfers = compute_invoice_transfers(&req, &accounts, true, total_payable, 2).unwrap();
+        let forward_charge_tax =
+            (req.invoice_lines[0].tax_amount().unwrap() * 100.0).round() / 100.0;
+        assert!(forward_charge_tax > 0.0);
+        assert!(
+            (amount_credited(&transfers, accounts.output_tax_accounts.igst_account_id)
+                - forward_charge_tax)
+                .abs()
+                < 1e-6
+        );
+    }
+
+    #[rstest]
+    #[case(0.14)]
+    #[case(0.28)]
+    #[case(10.05)]
+    fn test_tax_heads_are_rounded_to_currency_scale_without_losing_paisa(#[case] unit_price: f64) {
+        let req = an_invoice_with_lines(&[(unit_price, false)]);
+        let accounts = InvoiceLedgerAccounts {
+            receivable_account_id: Uuid::now_v7(),
+            sales_account_id: Uuid::now_v7(),
+            output_tax_accounts: a_gst_ledger_accounts(),
+        };
+        let total_payable = req.total_amount(2).unwrap();
+        let transfers =
+            compute_invoice_transfers(&req, &accounts, false, total_payable, 2).unwrap();
+        let minor = |amount: f64| (amount * 100.0).round() as i64;
+        assert!(transfers
+            .iter()
+            .all(|a| (a.amount * 100.0 - minor(a.amount) as f64).abs() < 1e-6));
+        let tax_accounts = &accounts.output_tax_accounts;
+        let cgst = minor(amount_credited(&transfers, tax_accounts.cgst_account_id));
+        let sgst = minor(amount_credited(&transfers, tax_accounts.sgst_account_id));
+        assert_eq!(cgst + sgst, minor(req.total_tax_amount().unwrap()));
+        assert!((0..=1).contains(&(cgst - sgst)));
+        let total: i64 = transfers.iter().map(|a| minor(a.amount)).sum();
+        assert_eq!(total, minor(total_payable));
+    }
+}
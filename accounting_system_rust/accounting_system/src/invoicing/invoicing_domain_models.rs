@@ -18,6 +18,14 @@ pub struct CreateInvoiceDbResponse {
     pub invoice_number: String,
     pub invoice_id: Uuid,
 }
+///irn of an invoice registered with the invoice registration portal along with its acknowledgement
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct EInvoicePortalResponse {
+    pub irn: String,
+    pub ack_no: String,
+    pub ack_date_ms: i64,
+    pub signed_qr_code: String,
+}
 #[derive(Debug, Builder)]
 pub struct InvoiceLine {
     pub base_master_fields: BaseMasterFields,
@@ -2,15 +2,31 @@ use std::fmt::Display;
 use std::sync::Arc;
 
 use actix_web::http::StatusCode;
-use actix_web::web::Data;
+use actix_web::web::{Data, Path};
 use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
 
+use crate::common_utils::mime_types::MimeType;
 use crate::common_utils::utils::{TenantId, UserId};
-use crate::invoicing::invoicing_request_models::{CreateInvoiceRequest, InvoicePdfRequest};
+use crate::invoicing::invoicing_request_models::{
+    AmendInvoiceRequest, CreateInvoiceRequest, CreateInvoicesInBulkRequest, InvoicePdfRequest,
+};
 use crate::invoicing::invoicing_service::{InvoicingService, InvoicingServiceError};
 use crate::setup_routes;
 
-impl ResponseError for InvoicingServiceError {}
+impl ResponseError for InvoicingServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            InvoicingServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            InvoicingServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            InvoicingServiceError::InvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            InvoicingServiceError::VersionConflict { .. } => StatusCode::CONFLICT,
+            InvoicingServiceError::Approval(e) => e.status_code(),
+            InvoicingServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 async fn create_invoice(
     data: Data<Arc<dyn InvoicingService>>,
     request: web::Json<CreateInvoiceRequest>,
@@ -23,6 +39,18 @@ async fn create_invoice(
     Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
 }
 
+async fn create_invoices_in_bulk(
+    data: Data<Arc<dyn InvoicingService>>,
+    request: web::Json<CreateInvoicesInBulkRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_invoices_in_bulk(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
 async fn create_invoice_pdf(
     data: Data<Arc<dyn InvoicingService>>,
     request: web::Json<InvoicePdfRequest>,
@@ -33,11 +61,137 @@ async fn create_invoice_pdf(
     Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
 }
 
+async fn create_draft_invoice(
+    data: Data<Arc<dyn InvoicingService>>,
+    request: web::Json<CreateInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_draft_invoice(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn update_draft_invoice(
+    data: Data<Arc<dyn InvoicingService>>,
+    invoice_id: Path<Uuid>,
+    request: web::Json<CreateInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.update_draft_invoice(
+        invoice_id.into_inner(),
+        request.into_inner(),
+        tenant_id.inner(),
+        user_id.inner(),
+    )
+    .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+async fn issue_invoice(
+    data: Data<Arc<dyn InvoicingService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .issue_invoice(invoice_id.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn amend_invoice(
+    data: Data<Arc<dyn InvoicingService>>,
+    invoice_id: Path<Uuid>,
+    request: web::Json<AmendInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .amend_invoice(
+            invoice_id.into_inner(),
+            request.into_inner(),
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_invoice_versions(
+    data: Data<Arc<dyn InvoicingService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_invoice_versions(invoice_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_place_of_supply(
+    data: Data<Arc<dyn InvoicingService>>,
+    request: web::Json<CreateInvoiceRequest>,
+    tenant_id: TenantId,
+    _user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_place_of_supply(request.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn preview_invoice(
+    data: Data<Arc<dyn InvoicingService>>,
+    request: web::Json<CreateInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .preview_invoice(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn create_invoice_preview_pdf(
+    data: Data<Arc<dyn InvoicingService>>,
+    request: web::Json<CreateInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let pdf = data
+        .create_invoice_preview_pdf(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK)
+        .content_type(MimeType::Pdf.get_mime_type())
+        .body(pdf))
+}
+
 setup_routes!(
     InvoicingService,
     "/invoice",
     "/create",
     web::post().to(create_invoice),
+    "/create-bulk",
+    web::post().to(create_invoices_in_bulk),
     "/create-pdf",
-    web::post().to(create_invoice_pdf)
+    web::post().to(create_invoice_pdf),
+    "/draft/create",
+    web::post().to(create_draft_invoice),
+    "/draft/{invoice_id}/update",
+    web::post().to(update_draft_invoice),
+    "/draft/{invoice_id}/issue",
+    web::post().to(issue_invoice),
+    "/{invoice_id}/amend",
+    web::post().to(amend_invoice),
+    "/{invoice_id}/versions",
+    web::get().to(get_invoice_versions),
+    "/place-of-supply",
+    web::post().to(get_place_of_supply),
+    "/preview",
+    web::post().to(preview_invoice),
+    "/preview/pdf",
+    web::post().to(create_invoice_preview_pdf)
 );
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use anyhow::{Context, ensure};
+use anyhow::{anyhow, Context, ensure};
 use chrono::NaiveDate;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
@@ -10,11 +10,14 @@ use uuid::Uuid;
 use invoice_doc_generator::invoice_line::line_quantity::{FreeLineQuantity, LineQuantity};
 use invoice_doc_generator::invoice_line::line_title::LineTitle;
 use invoice_doc_generator::invoice_line::unit_price::Price;
-use invoice_doc_generator::percentages::tax_discount_cess::DiscountPercentage;
-use pdf_doc_generator::invoice_template::Invoice;
+use invoice_doc_generator::percentages::tax_discount_cess::{DiscountPercentage, GSTPercentage};
+use pdf_doc_generator::invoice_template::{Invoice, TaxSummary};
 
+use crate::gst_returns::gstr3b::gstr3b_models::GstLedgerAccounts;
+use crate::invoicing::place_of_supply::ServiceCategory;
 use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
 use crate::masters::product_item_master::product_item_models::ProductItemResponse;
+use crate::tenant::tenant_models::GstRegistrationType;
 
 #[derive(Debug)]
 pub struct CreateInvoiceWithAllDetailsIncluded {
@@ -37,6 +40,32 @@ pub struct CreateInvoiceWithAllDetailsIncluded {
     pub additional_charges: Vec<CreateAdditionalChargeRequest>,
     pub invoice_remarks: Option<InvoiceRemarks>,
     pub ecommerce_gstin: Option<GstinNo>,
+    pub export_detail: Option<ExportDetail>,
+    pub sez_lut_reference: Option<LutReference>,
+    pub supply_classification: SupplyClassification,
+    pub registration_type: GstRegistrationType,
+    pub document_type: DocumentType,
+    pub ledger_accounts: Option<InvoiceLedgerAccounts>,
+}
+
+impl CreateInvoiceWithAllDetailsIncluded {
+    ///declaration printed on a bill of supply explaining why no tax is charged
+    pub fn bill_of_supply_declaration(&self) -> Option<&'static str> {
+        if self.document_type != DocumentType::BillOfSupply {
+            return None;
+        }
+        Some(match self.registration_type {
+            GstRegistrationType::Composition => {
+                "composition taxable person, not eligible to collect tax on supplies"
+            }
+            GstRegistrationType::Unregistered => {
+                "supplier not registered under gst, not eligible to collect tax on supplies"
+            }
+            GstRegistrationType::Regular => {
+                "supply of exempted goods or services, no tax is chargeable on this supply"
+            }
+        })
+    }
 }
 #[derive(Debug)]
 pub struct CreateInvoiceLineRequestWithAllDetails {
@@ -50,17 +79,31 @@ pub struct CreateInvoiceLineRequestWithAllDetails {
     pub expiry_date: Option<ExpiryDateMs>,
     //is the line item payable under reverse charge
     pub reverse_charge_applicable: bool,
+    ///exports and sez supplies under lut/bond and bills of supply carry no tax or cess irrespective of the
+    /// rates of the product
+    pub no_tax_charged: bool,
 }
 
 impl CreateInvoiceRequest {
     pub fn to_create_invoice_with_all_details_included(
         self,
         product_items: Vec<Arc<ProductItemResponse>>,
+        supply_classification: SupplyClassification,
+        registration_type: GstRegistrationType,
     ) -> anyhow::Result<CreateInvoiceWithAllDetailsIncluded> {
         let map: HashMap<Uuid, Arc<ProductItemResponse>> = product_items
             .into_iter()
             .map(|a| (a.base_master_fields.id, a))
             .collect();
+        let mut exempt_supply = supply_classification == SupplyClassification::Regular;
+        for il in self.invoice_lines.iter() {
+            if let Some(product) = map.get(&il.product_item_id) {
+                exempt_supply &= product.get_tax_rate()?.tax_rate_percentage.inner() == 0.0;
+            }
+        }
+        let document_type = DocumentType::for_supply(registration_type, exempt_supply);
+        let no_tax_charged =
+            supply_classification.zero_rated() || document_type == DocumentType::BillOfSupply;
         let mut invoice_lines: Vec<CreateInvoiceLineRequestWithAllDetails> =
             Vec::with_capacity(self.invoice_lines.len());
         for il in self.invoice_lines.into_iter() {
@@ -79,6 +122,7 @@ impl CreateInvoiceRequest {
                 batch_no: il.batch_no,
                 expiry_date: il.expiry_date,
                 reverse_charge_applicable: il.reverse_charge_applicable,
+                no_tax_charged,
             };
             invoice_lines.push(pr);
         }
@@ -101,10 +145,16 @@ impl CreateInvoiceRequest {
             additional_charges: self.additional_charges,
             invoice_remarks: self.invoice_remarks,
             ecommerce_gstin: self.ecommerce_gstin,
+            export_detail: self.export_detail,
+            sez_lut_reference: self.sez_lut_reference,
+            supply_classification,
+            registration_type,
+            document_type,
+            ledger_accounts: self.ledger_accounts,
         })
     }
 }
-#[derive(Debug, Serialize, Deserialize, Builder)]
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
 pub struct CreateInvoiceRequest {
     pub idempotence_key: Uuid,
     pub invoice_template_id: Uuid,
@@ -125,6 +175,30 @@ pub struct CreateInvoiceRequest {
     pub additional_charges: Vec<CreateAdditionalChargeRequest>,
     pub invoice_remarks: Option<InvoiceRemarks>,
     pub ecommerce_gstin: Option<GstinNo>,
+    ///present only for export invoices
+    pub export_detail: Option<ExportDetail>,
+    ///decides the place of supply of service invoices, general if not given
+    pub service_category: Option<ServiceCategory>,
+    ///supplies to a sez unit or developer under lut/bond are made without payment of igst
+    pub sez_lut_reference: Option<LutReference>,
+    ///supply notified as deemed export, tax is charged as on a regular supply
+    #[serde(default)]
+    pub deemed_export: bool,
+    ///pdf of the issued invoice is emailed to the billed to customer
+    #[serde(default)]
+    pub email_to_customer: bool,
+    ///the issued invoice is posted to these accounts, it is not posted to the ledger if not given
+    #[serde(default)]
+    pub ledger_accounts: Option<InvoiceLedgerAccounts>,
+}
+
+///ledger accounts of a sales invoice. the receivable account is debited with the amount payable, output tax
+/// accounts are credited with the tax on the lines and the additional charges and the sales account with the rest
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct InvoiceLedgerAccounts {
+    pub receivable_account_id: Uuid,
+    pub sales_account_id: Uuid,
+    pub output_tax_accounts: GstLedgerAccounts,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Builder)]
@@ -137,6 +211,21 @@ pub struct BillShipDetail {
 pub struct CreateAdditionalChargeRequest {
     pub line_title: LineTitle,
     pub rate: Price,
+    #[serde(default)]
+    pub taxability: AdditionalChargeTaxability,
+}
+
+///gst on charges like freight, packing or insurance. charges of a composite supply are taxed at the rate
+/// of the principal supply unless a rate is declared for them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdditionalChargeTaxability {
+    #[default]
+    PrincipalSupply,
+    DeclaredRate {
+        tax_percentage: GSTPercentage,
+    },
+    NonTaxable,
 }
 
 #[derive(Debug, Serialize, Deserialize, Builder, Clone)]
@@ -181,6 +270,267 @@ impl TryFrom<String> for InvoiceRemarks {
     }
 }
 
+///exports are zero rated, either igst is paid and claimed as refund or the supply is made under lut/bond
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportType {
+    WithPaymentOfIgst,
+    UnderLut,
+}
+
+impl ExportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportType::WithPaymentOfIgst => "with_payment_of_igst",
+            ExportType::UnderLut => "under_lut",
+        }
+    }
+}
+
+///reference of the letter of undertaking/bond under which zero rated supplies are made without payment of igst
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct LutReference(String);
+
+impl LutReference {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim().to_uppercase();
+        ensure!(!value.is_empty(), "lut reference cannot be empty");
+        ensure!(
+            value.len() <= 20
+                && value
+                    .chars()
+                    .all(|a| a.is_ascii_alphanumeric() || a == '/' || a == '-'),
+            "lut reference can have at most 20 alphanumeric characters or / or -"
+        );
+        Ok(Self(value))
+    }
+
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for LutReference {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportDetailRequest {
+    export_type: ExportType,
+    lut_reference: Option<String>,
+    port_code: Option<String>,
+    shipping_bill_number: Option<String>,
+    shipping_bill_date: Option<NaiveDate>,
+    country_id: Uuid,
+    exchange_rate: f64,
+}
+
+///shipping bill is usually filed after the invoice, so port code and shipping bill are optional.
+/// exchange_rate is the value of one unit of the invoice currency in inr
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(try_from = "ExportDetailRequest")]
+pub struct ExportDetail {
+    pub export_type: ExportType,
+    pub lut_reference: Option<String>,
+    pub port_code: Option<String>,
+    pub shipping_bill_number: Option<String>,
+    pub shipping_bill_date: Option<NaiveDate>,
+    pub country_id: Uuid,
+    pub exchange_rate: f64,
+}
+
+impl ExportDetail {
+    pub fn shipping_bill_date_ms(&self) -> Option<i64> {
+        self.shipping_bill_date
+            .and_then(|a| a.and_hms_milli_opt(0, 0, 0, 0))
+            .map(|a| a.and_utc().timestamp_millis())
+    }
+
+    pub fn to_inr(&self, amount: f64) -> f64 {
+        (amount * self.exchange_rate * 100.0).round() / 100.0
+    }
+}
+
+fn non_empty_trimmed(value: Option<String>) -> Option<String> {
+    value
+        .map(|a| a.trim().to_uppercase())
+        .filter(|a| !a.is_empty())
+}
+
+impl TryFrom<ExportDetailRequest> for ExportDetail {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ExportDetailRequest) -> Result<Self, Self::Error> {
+        let lut_reference = non_empty_trimmed(value.lut_reference);
+        let port_code = non_empty_trimmed(value.port_code);
+        let shipping_bill_number = non_empty_trimmed(value.shipping_bill_number);
+        match value.export_type {
+            ExportType::UnderLut => ensure!(
+                lut_reference.is_some(),
+                "lut reference is mandatory for exports under lut/bond"
+            ),
+            ExportType::WithPaymentOfIgst => ensure!(
+                lut_reference.is_none(),
+                "lut reference is applicable only for exports under lut/bond"
+            ),
+        }
+        if let Some(lut_reference) = lut_reference.as_ref() {
+            LutReference::new(lut_reference)?;
+        }
+        if let Some(port_code) = port_code.as_ref() {
+            ensure!(
+                port_code.len() == 6 && port_code.chars().all(|a| a.is_ascii_alphanumeric()),
+                "port code should be of 6 alphanumeric characters"
+            );
+        }
+        if let Some(shipping_bill_number) = shipping_bill_number.as_ref() {
+            ensure!(
+                shipping_bill_number.len() <= 7
+                    && shipping_bill_number.chars().all(|a| a.is_ascii_digit()),
+                "shipping bill number can have at most 7 digits"
+            );
+        }
+        ensure!(
+            shipping_bill_number.is_some() == value.shipping_bill_date.is_some(),
+            "shipping bill number and date should be given together"
+        );
+        ensure!(
+            value.exchange_rate.is_finite() && value.exchange_rate > 0.0,
+            "exchange rate should be greater than 0"
+        );
+        Ok(ExportDetail {
+            export_type: value.export_type,
+            lut_reference,
+            port_code,
+            shipping_bill_number,
+            shipping_bill_date: value.shipping_bill_date,
+            country_id: value.country_id,
+            exchange_rate: value.exchange_rate,
+        })
+    }
+}
+
+///a bill of supply is issued instead of a tax invoice by suppliers who cannot charge tax and for exempt supplies
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    #[default]
+    TaxInvoice,
+    BillOfSupply,
+}
+
+impl DocumentType {
+    pub fn for_supply(registration_type: GstRegistrationType, exempt_supply: bool) -> Self {
+        if !registration_type.can_charge_tax() || exempt_supply {
+            DocumentType::BillOfSupply
+        } else {
+            DocumentType::TaxInvoice
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentType::TaxInvoice => "tax_invoice",
+            DocumentType::BillOfSupply => "bill_of_supply",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DocumentType::TaxInvoice => "Tax Invoice",
+            DocumentType::BillOfSupply => "Bill of Supply",
+        }
+    }
+}
+
+impl TryFrom<&str> for DocumentType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "tax_invoice" => Ok(DocumentType::TaxInvoice),
+            "bill_of_supply" => Ok(DocumentType::BillOfSupply),
+            _ => Err(anyhow!("invalid document type {}", value)),
+        }
+    }
+}
+
+///gst treatment of an invoice, decided from the recipient and the request. it is the invoice type of b2b
+/// invoices in gstr-1 and the supply type of the e-invoice
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyClassification {
+    #[default]
+    Regular,
+    SezWithPayment,
+    SezWithoutPayment,
+    DeemedExport,
+    ExportWithPayment,
+    ExportWithoutPayment,
+}
+
+impl SupplyClassification {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupplyClassification::Regular => "regular",
+            SupplyClassification::SezWithPayment => "sez_with_payment",
+            SupplyClassification::SezWithoutPayment => "sez_without_payment",
+            SupplyClassification::DeemedExport => "deemed_export",
+            SupplyClassification::ExportWithPayment => "export_with_payment",
+            SupplyClassification::ExportWithoutPayment => "export_without_payment",
+        }
+    }
+
+    ///supplies under lut/bond, no tax is charged on them
+    pub fn zero_rated(&self) -> bool {
+        matches!(
+            self,
+            SupplyClassification::SezWithoutPayment | SupplyClassification::ExportWithoutPayment
+        )
+    }
+
+    pub fn b2b_invoice_type(&self) -> &'static str {
+        match self {
+            SupplyClassification::SezWithPayment => "SEWP",
+            SupplyClassification::SezWithoutPayment => "SEWOP",
+            SupplyClassification::DeemedExport => "DE",
+            _ => "R",
+        }
+    }
+
+    pub fn e_invoice_supply_type(&self) -> &'static str {
+        match self {
+            SupplyClassification::Regular => "B2B",
+            SupplyClassification::SezWithPayment => "SEZWP",
+            SupplyClassification::SezWithoutPayment => "SEZWOP",
+            SupplyClassification::DeemedExport => "DEXP",
+            SupplyClassification::ExportWithPayment => "EXPWP",
+            SupplyClassification::ExportWithoutPayment => "EXPWOP",
+        }
+    }
+}
+
+impl TryFrom<&str> for SupplyClassification {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "regular" => Ok(SupplyClassification::Regular),
+            "sez_with_payment" => Ok(SupplyClassification::SezWithPayment),
+            "sez_without_payment" => Ok(SupplyClassification::SezWithoutPayment),
+            "deemed_export" => Ok(SupplyClassification::DeemedExport),
+            "export_with_payment" => Ok(SupplyClassification::ExportWithPayment),
+            "export_without_payment" => Ok(SupplyClassification::ExportWithoutPayment),
+            _ => Err(anyhow!("invalid supply classification {}", value)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct PaymentTerms {
     pub due_days: DueDays,
@@ -392,11 +742,133 @@ pub struct InvoicePdfRequest {
     pub invoice: Invoice,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDraftInvoiceResponse {
+    pub invoice_id: Uuid,
+}
+
+///totals and printable document of a request, computed without persisting it or taking a number
+#[derive(Debug)]
+pub struct ComputedInvoiceDocument {
+    pub document_date_ms: i64,
+    pub financial_year: i16,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_additional_charges_amount: f64,
+    pub total_payable_amount: f64,
+    ///invoice_number is left empty
+    pub document: Invoice,
+}
+
+pub const PREVIEW_WATERMARK: &str = "PREVIEW";
+
+///amounts of a request computed the same way as on creation, nothing is persisted and no number is taken
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoicePreview {
+    pub document_type: DocumentType,
+    pub place_of_supply: String,
+    pub igst_applicable: bool,
+    pub lines: Vec<InvoicePreviewLine>,
+    pub additional_charges: Vec<InvoicePreviewAdditionalCharge>,
+    ///tax breakup by rate as printed on the invoice
+    pub tax_summary: TaxSummary,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_cess_amount: f64,
+    pub total_additional_charges_amount: f64,
+    pub round_off: f64,
+    pub total_payable_amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoicePreviewLine {
+    pub line_no: i16,
+    pub product_item_id: Uuid,
+    pub discount_amount: f64,
+    pub taxable_amount: f64,
+    pub tax_percentage: f32,
+    pub tax_amount: f64,
+    pub cess_amount: f64,
+    pub line_net_total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoicePreviewAdditionalCharge {
+    pub line_no: i16,
+    pub rate: f64,
+    pub tax_percentage: f32,
+    pub tax_amount: f64,
+}
+
+pub const MAX_BULK_INVOICES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateInvoicesInBulkRequest {
+    pub invoices: Vec<CreateInvoiceRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BulkInvoiceItemStatus {
+    Created {
+        invoice_id: Uuid,
+        invoice_number: String,
+        pdf_request: Box<InvoicePdfRequest>,
+    },
+    ValidationFailed {
+        errors: Vec<String>,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+///outcome of one invoice of a bulk request, index is the position of the invoice in the request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkInvoiceItemResult {
+    pub index: usize,
+    pub idempotence_key: Uuid,
+    #[serde(flatten)]
+    pub status: BulkInvoiceItemStatus,
+}
+
+pub const AMENDMENT_REASON_MAX_LENGTH: usize = 100;
+
+///new content of an issued invoice, entity_version_id is the version the amendment was made on
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AmendInvoiceRequest {
+    pub entity_version_id: i32,
+    pub amendment_reason: String,
+    pub invoice: CreateInvoiceRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AmendInvoiceResponse {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub entity_version_id: i32,
+    pub pdf_url: String,
+}
+
+///content of an invoice as it was before an amendment, snapshots hold the rows as stored
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvoiceVersion {
+    pub entity_version_id: i32,
+    pub amendment_reason: String,
+    pub amended_by: Uuid,
+    pub amended_at: i64,
+    pub invoice: serde_json::Value,
+    pub invoice_lines: serde_json::Value,
+    pub additional_charges: serde_json::Value,
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::str::FromStr;
-    use std::sync::LazyLock;
+    use std::sync::{Arc, LazyLock};
 
+    use rstest::rstest;
+    use serde_json::json;
     use uuid::Uuid;
 
     use invoice_doc_generator::invoice_line::line_quantity::test_utils::{a_free_line_quantity, a_line_quantity};
@@ -407,15 +879,19 @@ pub mod tests {
     use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
     use crate::invoicing::invoice_template::invoice_template_models::tests::SEED_INVOICE_TEMPLATE_ID;
     use crate::invoicing::invoicing_request_models::{
-        BillShipDetail, BillShipDetailBuilder, CreateAdditionalChargeRequest,
-        CreateAdditionalChargeRequestBuilder, CreateInvoiceLineRequest,
-        CreateInvoiceLineRequestBuilder, CreateInvoiceRequest, CreateInvoiceRequestBuilder,
+        AdditionalChargeTaxability, BillShipDetail, BillShipDetailBuilder,
+        CreateAdditionalChargeRequest, CreateAdditionalChargeRequestBuilder,
+        CreateInvoiceLineRequest, CreateInvoiceLineRequestBuilder, CreateInvoiceRequest,
+        CreateInvoiceRequestBuilder, DocumentType, ExportDetail, ExportType, SupplyClassification,
     };
     use crate::invoicing::invoicing_series::invoicing_series_models::tests::SEED_INVOICING_SERIES_MST_ID;
     use crate::masters::business_entity_master::business_entity_models::tests::{
         SEED_BUSINESS_ENTITY_ID1, SEED_BUSINESS_ENTITY_ID2,
     };
-    use crate::masters::product_item_master::product_item_models::tests::SEED_PRODUCT_ITEM_ID;
+    use crate::masters::product_item_master::product_item_models::tests::{
+        a_product_item_response, SEED_PRODUCT_ITEM_ID,
+    };
+    use crate::tenant::tenant_models::GstRegistrationType;
 
     pub static SEED_INVOICE_ID: LazyLock<Uuid> =
         LazyLock::new(|| Uuid::from_str("018d5559-745a-7371-80c6-a4efaa2cafe6").unwrap());
@@ -449,6 +925,12 @@ pub mod tests {
             invoice_remarks: builder.invoice_remarks.flatten(),
             ecommerce_gstin: builder.ecommerce_gstin.flatten(),
             dispatch_from_id: builder.dispatch_from_id.flatten(),
+            export_detail: builder.export_detail.flatten(),
+            service_category: builder.service_category.flatten(),
+            sez_lut_reference: builder.sez_lut_reference.flatten(),
+            deemed_export: builder.deemed_export.unwrap_or(false),
+            email_to_customer: builder.email_to_customer.unwrap_or(false),
+            ledger_accounts: builder.ledger_accounts.flatten(),
         }
     }
 
@@ -471,6 +953,7 @@ pub mod tests {
                 .line_title
                 .unwrap_or(LineTitle::new("some line title".to_string()).unwrap()),
             rate: builder.rate.unwrap_or_else(|| Price::new(0.0).unwrap()),
+            taxability: builder.taxability.unwrap_or_default(),
         }
     }
 
@@ -497,4 +980,148 @@ pub mod tests {
             reverse_charge_applicable: builder.reverse_charge_applicable.unwrap_or(false),
         }
     }
+
+    #[rstest]
+    #[case(json!({"export_type": "under_lut", "lut_reference": "ad290324000123x"}), true)]
+    #[case(json!({"export_type": "under_lut"}), false)]
+    #[case(json!({"export_type": "with_payment_of_igst", "lut_reference": "ad29"}), false)]
+    #[case(json!({"export_type": "with_payment_of_igst", "port_code": "inmaa1"}), true)]
+    #[case(json!({"export_type": "with_payment_of_igst", "port_code": "inmaa"}), false)]
+    #[case(json!({"export_type": "with_payment_of_igst", "shipping_bill_number": "1234567"}), false)]
+    #[case(json!({"export_type": "with_payment_of_igst", "shipping_bill_number": "12345678",
+        "shipping_bill_date": "2024-01-29"}), false)]
+    #[case(json!({"export_type": "with_payment_of_igst", "shipping_bill_number": "1234567",
+        "shipping_bill_date": "2024-01-29"}), true)]
+    #[case(json!({"export_type": "with_payment_of_igst", "exchange_rate": 0.0}), false)]
+    fn test_export_detail(#[case] mut input: serde_json::Value, #[case] valid: bool) {
+        let obj = input.as_object_mut().unwrap();
+        obj.insert("country_id".to_string(), json!(Uuid::now_v7()));
+        obj.entry("exchange_rate").or_insert(json!(83.12));
+        assert_eq!(serde_json::from_value::<ExportDetail>(input).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_export_detail_to_inr() {
+        let detail: ExportDetail = serde_json::from_value(json!({
+            "export_type": "under_lut",
+            "lut_reference": " ad290324000123x ",
+            "country_id": Uuid::now_v7(),
+            "exchange_rate": 83.125,
+            "shipping_bill_number": "1234567",
+            "shipping_bill_date": "2024-01-29"
+        }))
+        .unwrap();
+        assert_eq!(detail.export_type, ExportType::UnderLut);
+        assert_eq!(detail.lut_reference.as_deref(), Some("AD290324000123X"));
+        assert_eq!(detail.to_inr(10.5), 872.81);
+        assert_eq!(detail.shipping_bill_date_ms(), Some(1706486400000));
+    }
+
+    #[rstest]
+    #[case(SupplyClassification::Regular, "R", "B2B", false)]
+    #[case(SupplyClassification::SezWithPayment, "SEWP", "SEZWP", false)]
+    #[case(SupplyClassification::SezWithoutPayment, "SEWOP", "SEZWOP", true)]
+    #[case(SupplyClassification::DeemedExport, "DE", "DEXP", false)]
+    #[case(SupplyClassification::ExportWithoutPayment, "R", "EXPWOP", true)]
+    fn test_supply_classification(
+        #[case] classification: SupplyClassification,
+        #[case] b2b_invoice_type: &str,
+        #[case] e_invoice_supply_type: &str,
+        #[case] zero_rated: bool,
+    ) {
+        assert_eq!(classification.b2b_invoice_type(), b2b_invoice_type);
+        assert_eq!(
+            classification.e_invoice_supply_type(),
+            e_invoice_supply_type
+        );
+        assert_eq!(classification.zero_rated(), zero_rated);
+        assert_eq!(
+            SupplyClassification::try_from(classification.as_str()).unwrap(),
+            classification
+        );
+    }
+
+    #[rstest]
+    #[case(GstRegistrationType::Regular, 5.0, DocumentType::TaxInvoice)]
+    #[case(GstRegistrationType::Regular, 0.0, DocumentType::BillOfSupply)]
+    #[case(GstRegistrationType::Composition, 5.0, DocumentType::BillOfSupply)]
+    #[case(GstRegistrationType::Unregistered, 5.0, DocumentType::BillOfSupply)]
+    fn test_document_type(
+        #[case] registration_type: GstRegistrationType,
+        #[case] tax_rate: f32,
+        #[case] document_type: DocumentType,
+    ) {
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        product.temporal_tax_rates[0].tax_rate_percentage = tax_rate.try_into().unwrap();
+        let req = a_create_invoice_request(Default::default())
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                registration_type,
+            )
+            .unwrap();
+        assert_eq!(req.document_type, document_type);
+        let bill_of_supply = document_type == DocumentType::BillOfSupply;
+        assert_eq!(req.bill_of_supply_declaration().is_some(), bill_of_supply);
+        let line = &req.invoice_lines[0];
+        assert_eq!(line.no_tax_charged, bill_of_supply);
+        if bill_of_supply {
+            assert_eq!(line.tax_percentage().unwrap(), 0.0);
+        }
+        assert_eq!(
+            DocumentType::try_from(document_type.as_str()).unwrap(),
+            document_type
+        );
+    }
+
+    #[rstest]
+    #[case(
+        AdditionalChargeTaxability::PrincipalSupply,
+        GstRegistrationType::Regular,
+        5.0
+    )]
+    #[case(AdditionalChargeTaxability::DeclaredRate { tax_percentage: 18.0.try_into().unwrap() },
+        GstRegistrationType::Regular, 18.0)]
+    #[case(
+        AdditionalChargeTaxability::NonTaxable,
+        GstRegistrationType::Regular,
+        0.0
+    )]
+    #[case(
+        AdditionalChargeTaxability::PrincipalSupply,
+        GstRegistrationType::Composition,
+        0.0
+    )]
+    fn test_additional_charge_taxability(
+        #[case] taxability: AdditionalChargeTaxability,
+        #[case] registration_type: GstRegistrationType,
+        #[case] tax_percentage: f32,
+    ) {
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        let charge = a_create_additional_charge_request(CreateAdditionalChargeRequestBuilder {
+            rate: Some(Price::new(100.0).unwrap()),
+            taxability: Some(taxability),
+            ..Default::default()
+        });
+        let req = a_create_invoice_request(CreateInvoiceRequestBuilder {
+            additional_charges: Some(vec![charge]),
+            ..Default::default()
+        })
+        .to_create_invoice_with_all_details_included(
+            vec![Arc::new(product)],
+            SupplyClassification::Regular,
+            registration_type,
+        )
+        .unwrap();
+        let charge = &req.additional_charges[0];
+        assert_eq!(
+            req.additional_charge_tax_percentage(charge).unwrap(),
+            tax_percentage
+        );
+        let lines_tax = req.invoice_lines[0].tax_amount().unwrap();
+        let total_tax = req.total_tax_amount().unwrap();
+        assert!((total_tax - lines_tax - tax_percentage as f64).abs() < 1e-6);
+    }
 }
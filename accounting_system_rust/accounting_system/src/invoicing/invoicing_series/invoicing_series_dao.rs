@@ -13,11 +13,11 @@ use crate::common_utils::db_row_conversion_utils::{
 use crate::common_utils::utils::parse_db_output_of_insert_create_and_return_uuid;
 use crate::invoicing::invoicing_series::invoicing_series_models::{
     CreateInvoiceNumberSeriesRequest, InvoiceNumberPrefix, InvoicingSeriesMaster,
-    InvoicingSeriesName,
+    InvoicingSeriesName, InvoicingSeriesType,
 };
 
 const TABLE_NAME: &str = "invoicing_series_mst";
-const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,name,prefix,zero_padded_counter,created_by,updated_by,created_at,updated_at";
+const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,name,prefix,zero_padded_counter,series_type::text,created_by,updated_by,created_at,updated_at";
 
 const QUERY_BY_ID: &str = concatcp!(
     "select ",
@@ -52,7 +52,8 @@ impl TryFrom<Row> for InvoicingSeriesMaster {
             name: InvoicingSeriesName::new(row.try_get(next_ind)?)?,
             prefix: InvoiceNumberPrefix::new(row.try_get(next_ind + 1)?)?,
             zero_padded_counter: row.try_get(next_ind + 2)?,
-            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 3, &row)?,
+            series_type: InvoicingSeriesType::from_db_str(row.try_get(next_ind + 3)?)?,
+            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 4, &row)?,
         };
         Ok(h)
     }
@@ -80,7 +81,7 @@ impl InvoicingSeriesDao for InvoicingSeriesDaoImpl {
         let simple_query = format!(
             r#"
            begin transaction;
-           select create_invoice_series(Row('{}','{}','{}','{}',{},{},{},'{}'));
+           select create_invoice_series(Row('{}','{}','{}','{}',{},{},{},'{}','{}'));
            commit;
            "#,
             request.idempotence_key,
@@ -90,7 +91,8 @@ impl InvoicingSeriesDao for InvoicingSeriesDaoImpl {
             request.zero_padded_counter,
             request.start_value.unwrap_or(0),
             request.financial_year.inner(),
-            user_id
+            user_id,
+            request.series_type.as_db_str()
         );
         let conn = self.postgres_client.get().await?;
         let rows = conn.simple_query(simple_query.as_str()).await?;
@@ -128,6 +130,9 @@ mod tests {
         InvoicingSeriesDao, InvoicingSeriesDaoImpl,
     };
     use crate::invoicing::invoicing_series::invoicing_series_models::tests::a_create_invoice_number_series_request;
+    use crate::invoicing::invoicing_series::invoicing_series_models::{
+        CreateInvoiceNumberSeriesRequestBuilder, InvoicingSeriesType,
+    };
     use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
 
     #[tokio::test]
@@ -139,7 +144,9 @@ mod tests {
             None,
         )
         .await;
-        let in_series = a_create_invoice_number_series_request(Default::default());
+        let mut builder = CreateInvoiceNumberSeriesRequestBuilder::default();
+        builder.series_type(InvoicingSeriesType::Quotation);
+        let in_series = a_create_invoice_number_series_request(builder);
         let p = dao
             .create_invoice_series(&in_series, *SEED_TENANT_ID, *SEED_USER_ID)
             .await
@@ -148,6 +155,8 @@ mod tests {
             .get_invoicing_series_by_id(p, *SEED_TENANT_ID)
             .await
             .unwrap();
-        assert_that!(jj).is_some();
+        assert_that!(jj.map(|a| a.series_type))
+            .is_some()
+            .is_equal_to(InvoicingSeriesType::Quotation);
     }
 }
@@ -1,4 +1,4 @@
-use anyhow::ensure;
+use anyhow::{bail, ensure};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -15,6 +15,38 @@ pub struct CreateInvoiceNumberSeriesRequest {
     ///primarily for migration purpose and nothing else
     pub start_value: Option<u32>,
     pub financial_year: FinancialYear,
+    #[serde(default)]
+    pub series_type: InvoicingSeriesType,
+}
+
+///type of the documents numbered from a series, a quotation or challan series cannot number tax invoices
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoicingSeriesType {
+    #[default]
+    TaxInvoice,
+    Quotation,
+    DeliveryChallan,
+}
+
+impl InvoicingSeriesType {
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            InvoicingSeriesType::TaxInvoice => "tax_invoice",
+            InvoicingSeriesType::Quotation => "quotation",
+            InvoicingSeriesType::DeliveryChallan => "delivery_challan",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let series_type = match value {
+            "tax_invoice" => InvoicingSeriesType::TaxInvoice,
+            "quotation" => InvoicingSeriesType::Quotation,
+            "delivery_challan" => InvoicingSeriesType::DeliveryChallan,
+            _ => bail!("{} is not a valid invoicing series type", value),
+        };
+        Ok(series_type)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -47,6 +79,7 @@ pub struct InvoicingSeriesMaster {
     pub name: InvoicingSeriesName,
     pub prefix: InvoiceNumberPrefix,
     pub zero_padded_counter: bool,
+    pub series_type: InvoicingSeriesType,
     pub audit_metadata: AuditMetadataBase,
 }
 
@@ -147,7 +180,7 @@ pub mod tests {
 
     use crate::invoicing::invoicing_series::invoicing_series_models::{
         CreateInvoiceNumberSeriesRequest, CreateInvoiceNumberSeriesRequestBuilder, FinancialYear,
-        InvoiceNumberPrefix, InvoicingSeriesName,
+        InvoiceNumberPrefix, InvoicingSeriesName, InvoicingSeriesType,
     };
 
     pub static SEED_INVOICING_SERIES_MST_ID: LazyLock<Uuid> =
@@ -168,6 +201,9 @@ pub mod tests {
             financial_year: builder
                 .financial_year
                 .unwrap_or(FinancialYear::new(2024).unwrap()),
+            series_type: builder
+                .series_type
+                .unwrap_or(InvoicingSeriesType::TaxInvoice),
         }
     }
 }
@@ -15,7 +15,7 @@ use crate::invoicing::invoicing_series::invoicing_series_dao::{
     get_invoicing_series_dao, InvoicingSeriesDao,
 };
 use crate::invoicing::invoicing_series::invoicing_series_models::{
-    CreateInvoiceNumberSeriesRequest, InvoicingSeriesMaster,
+    CreateInvoiceNumberSeriesRequest, InvoicingSeriesMaster, InvoicingSeriesType,
 };
 
 #[derive(Debug, Error)]
@@ -39,9 +39,11 @@ pub trait InvoicingSeriesService: Send + Sync {
         invoicing_series_id: Uuid,
         tenant_id: Uuid,
     ) -> Result<Option<Arc<InvoicingSeriesMaster>>, InvoicingSeriesServiceError>;
+    ///the series exists for the tenant and numbers documents of this type
     async fn is_valid_invoicing_series_id(
         &self,
         invoicing_series_id: Uuid,
+        series_type: InvoicingSeriesType,
         tenant_id: Uuid,
     ) -> Result<bool, InvoicingSeriesServiceError>;
 }
@@ -97,11 +99,12 @@ impl InvoicingSeriesService for InvoicingSeriesServiceImpl {
     async fn is_valid_invoicing_series_id(
         &self,
         invoicing_series_id: Uuid,
+        series_type: InvoicingSeriesType,
         tenant_id: Uuid,
     ) -> Result<bool, InvoicingSeriesServiceError> {
         let p = self
             .get_invoicing_series_by_id(invoicing_series_id, tenant_id)
             .await?;
-        Ok(p.is_some())
+        Ok(p.is_some_and(|a| a.series_type == series_type))
     }
 }
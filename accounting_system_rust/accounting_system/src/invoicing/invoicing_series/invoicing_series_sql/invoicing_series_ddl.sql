@@ -1,3 +1,5 @@
+create type invoicing_series_type as enum ('tax_invoice','quotation','delivery_challan');
+
 create table invoicing_series_mst
 (
     id                  uuid primary key,
@@ -10,6 +12,7 @@ create table invoicing_series_mst
     name   varchar(30) not null,
     prefix varchar(7)  not null,
     zero_padded_counter bool,
+    series_type         invoicing_series_type         not null,--a series numbers only one type of document
 
     created_by          uuid references app_user (id) not null,
     updated_by          uuid references app_user (id),
@@ -7,7 +7,8 @@ create type create_invoice_series_request as
     zero_padded_counter bool,
     start_value         int,
     financial_year      int,
-    created_by          uuid
+    created_by          uuid,
+    series_type         invoicing_series_type
 );
 
 create or replace function create_invoice_series(req create_invoice_series_request) returns uuid as
@@ -26,9 +27,10 @@ BEGIN
         select uuid_generate_v7() into invoice_series_id;
         select uuid_generate_v7() into invoice_series_counter_id;
         insert into invoicing_series_mst(id, entity_version_id, tenant_id, active, approval_status, remarks, name,
-                                         prefix, zero_padded_counter, created_by, updated_by, created_at, updated_at)
+                                         prefix, zero_padded_counter, series_type, created_by, updated_by, created_at,
+                                         updated_at)
         values (invoice_series_id, 0, req.tenant_id, true, 1, null, req.name, req.prefix, req.zero_padded_counter,
-                req.created_by, req.created_by, default, default);
+                req.series_type, req.created_by, req.created_by, default, default);
         insert into invoicing_series_counter(id, entity_version_id, tenant_id, invoicing_series_id, financial_year,
                                              counter, start_value, created_by,
                                              updated_by, created_at, updated_at)
@@ -1,30 +1,61 @@
 use std::cmp::Ordering;
-use std::error::Error;
-use std::future::Future;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use anyhow::Context;
 use async_trait::async_trait;
 use deadpool_postgres::Pool;
 use itertools::Itertools;
+#[cfg(test)]
+use mockall::automock;
 use thiserror::Error;
+use tracing::warn;
 use uuid::Uuid;
 
 use pdf_doc_generator::invoice_template;
+use pdf_doc_generator::invoice_template::{InvoiceAssets, InvoiceLayout, InvoiceTemplateSource};
 
+use crate::accounting::currency::currency_models::CurrencyMaster;
 use crate::accounting::currency::currency_service::CurrencyService;
+use crate::background_job::background_job_models::{EnqueueJobRequest, JobKind};
+use crate::background_job::background_job_service::BackgroundJobService;
 use crate::common_utils::dao_error::DaoError;
+use crate::common_utils::email_client::EmailClient;
 use crate::common_utils::utils::current_indian_date;
-use crate::invoicing::doc_conversion::{convert_to_invoice_doc_model, InvoiceDocCreationDataInput};
+use crate::invoicing::doc_conversion::{
+    convert_to_einvoice_detail, convert_to_invoice_doc_model, create_invoice_tax_summary,
+    InvoiceDocCreationDataInput,
+};
+use crate::invoicing::einvoice_portal_client::EInvoicePortalClient;
+use crate::invoicing::invoice_approval::invoice_approval_service::{
+    InvoiceApprovalService, InvoiceApprovalServiceError,
+};
+use crate::invoicing::invoice_jobs::{EInvoiceJobPayload, InvoiceEmailJobPayload};
 use crate::invoicing::invoice_template::invoice_template_service::InvoiceTemplateService;
 use crate::invoicing::invoicing_dao::{get_invoicing_dao, InvoicingDao};
-use crate::invoicing::invoicing_dao_models::convert_to_invoice_db;
-use crate::invoicing::invoicing_request_models::{CreateInvoiceRequest, InvoicePdfRequest};
+use crate::invoicing::invoicing_dao_models::{
+    convert_to_invoice_db, AmendInvoiceDbStatus, InvoiceAmendmentStateDb, InvoiceDb,
+};
+use crate::invoicing::invoicing_request_models::{
+    AmendInvoiceRequest, AmendInvoiceResponse, BulkInvoiceItemResult, BulkInvoiceItemStatus,
+    ComputedInvoiceDocument, CreateDraftInvoiceResponse, CreateInvoiceRequest,
+    CreateInvoiceWithAllDetailsIncluded, CreateInvoicesInBulkRequest, ExportType,
+    InvoicePdfRequest, InvoicePreview, InvoicePreviewAdditionalCharge, InvoicePreviewLine,
+    InvoiceVersion, SupplyClassification, AMENDMENT_REASON_MAX_LENGTH, MAX_BULK_INVOICES,
+    PREVIEW_WATERMARK,
+};
+use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
 use crate::invoicing::invoicing_series::invoicing_series_service::InvoicingSeriesService;
+use crate::invoicing::place_of_supply::{
+    determine_place_of_supply, PlaceOfSupply, PlaceOfSupplyInput, PosParty, SupplyKind,
+};
+use crate::masters::business_entity_master::business_entity_models::BusinessEntityDto;
 use crate::masters::business_entity_master::business_entity_service::BusinessEntityService;
-use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+use crate::masters::product_item_master::product_item_models::ProductItemResponse;
 use crate::masters::product_item_master::product_item_service::ProductItemService;
 use crate::storage::storage_service::{StorageService, FINANCIAL_DOCS_BUCKET_NAME};
+use crate::tenant::tenant_models::GstRegistrationType;
 use crate::tenant::tenant_service::TenantService;
 
 #[derive(Debug, Error)]
@@ -33,10 +64,20 @@ pub enum InvoicingServiceError {
     Db(#[from] DaoError),
     #[error("validation failures \n {}", .0.join("\n"))]
     Validation(Vec<String>),
+    #[error("invoice {0} not found")]
+    InvoiceNotFound(Uuid),
+    #[error("invoice {invoice_id} has changed, current version is {current_version}")]
+    VersionConflict {
+        invoice_id: Uuid,
+        current_version: i32,
+    },
+    #[error("{0}")]
+    Approval(#[from] InvoiceApprovalServiceError),
     #[error("{0}")]
     Other(#[from] anyhow::Error),
 }
 
+#[cfg_attr(test, automock)]
 #[async_trait]
 pub trait InvoicingService: Send + Sync {
     async fn create_invoice(
@@ -45,10 +86,110 @@ pub trait InvoicingService: Send + Sync {
         tenant_id: Uuid,
         user_id: Uuid,
     ) -> Result<InvoicePdfRequest, InvoicingServiceError>;
+    ///creates each invoice independently, a failing invoice does not stop the others
+    async fn create_invoices_in_bulk(
+        &self,
+        req: CreateInvoicesInBulkRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<BulkInvoiceItemResult>, InvoicingServiceError>;
     async fn create_invoice_pdf(
         &self,
         pdf_data: InvoicePdfRequest,
     ) -> Result<String, InvoicingServiceError>;
+    ///registers the invoice with the invoice registration portal and stores its irn, the pdf and email jobs of
+    /// the invoice are enqueued only after that
+    async fn generate_einvoice(
+        &self,
+        payload: EInvoiceJobPayload,
+    ) -> Result<(), InvoicingServiceError>;
+    ///creates an editable draft, no number is taken from the invoicing series until it is issued
+    async fn create_draft_invoice(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateDraftInvoiceResponse, InvoicingServiceError>;
+    async fn update_draft_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoicingServiceError>;
+    ///assigns the invoice number to an approved draft and dates it on the day of issue
+    async fn issue_invoice(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoicePdfRequest, InvoicingServiceError>;
+    ///validates the request and computes its totals and document, used by documents sharing the invoice lines
+    async fn compute_invoice_document(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ComputedInvoiceDocument, InvoicingServiceError>;
+    ///applies the validations of invoice creation, returns the failures of each request in the same order
+    async fn validate_invoices(
+        &self,
+        reqs: &[CreateInvoiceRequest],
+        tenant_id: Uuid,
+    ) -> Result<Vec<Vec<String>>, InvoicingServiceError>;
+    ///replaces the content of an issued invoice keeping its number and date, the previous content stays
+    /// queryable as a version. not allowed for e-invoiced invoices and once gstr-1 of the period is filed
+    async fn amend_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: AmendInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<AmendInvoiceResponse, InvoicingServiceError>;
+    async fn get_invoice_versions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceVersion>, InvoicingServiceError>;
+    ///place of supply the request would be invoiced with, along with the rules applied to decide it
+    async fn get_place_of_supply(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+    ) -> Result<PlaceOfSupply, InvoicingServiceError>;
+    ///runs the validations and computations of invoice creation and returns the line and summary amounts,
+    /// nothing is persisted and no invoice number is taken
+    async fn preview_invoice(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoicePreview, InvoicingServiceError>;
+    ///pdf of the request watermarked as a preview, it is neither persisted nor uploaded
+    async fn create_invoice_preview_pdf(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<u8>, InvoicingServiceError>;
+}
+
+///products, business entities and currencies referred by one or more invoice requests, keyed by id, along
+/// with the referred template and tax invoice series ids which exist for the tenant and the gst registration
+/// of the tenant
+struct InvoiceMasters {
+    products: HashMap<Uuid, Arc<ProductItemResponse>>,
+    business_entities: HashMap<Uuid, Arc<BusinessEntityDto>>,
+    currencies: HashMap<Uuid, Arc<CurrencyMaster>>,
+    invoice_template_ids: HashSet<Uuid>,
+    tax_invoice_series_ids: HashSet<Uuid>,
+    registration_type: GstRegistrationType,
+}
+
+struct PreparedInvoice {
+    req: CreateInvoiceWithAllDetailsIncluded,
+    currency: Arc<CurrencyMaster>,
+    place_of_supply: PlaceOfSupply,
 }
 
 #[allow(dead_code)]
@@ -61,20 +202,127 @@ struct InvoicingServiceImpl {
     invoice_template_service: Arc<dyn InvoiceTemplateService>,
     storage_service: Arc<dyn StorageService>,
     product_item_service: Arc<dyn ProductItemService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    background_job_service: Arc<dyn BackgroundJobService>,
+    einvoice_portal_client: Arc<dyn EInvoicePortalClient>,
+    email_client: Arc<dyn EmailClient>,
 }
 
 impl InvoicingServiceImpl {
-    async fn validate_create_invoice_request(
+    ///fetches the masters referred by the requests, each id is fetched or validated once
+    async fn fetch_masters(
         &self,
-        req: &CreateInvoiceRequest,
+        reqs: &[CreateInvoiceRequest],
         tenant_id: Uuid,
+    ) -> anyhow::Result<InvoiceMasters> {
+        let product_ids = reqs
+            .iter()
+            .flat_map(|a| a.invoice_lines.iter().map(|l| l.product_item_id))
+            .unique()
+            .collect_vec();
+        let products = self
+            .product_item_service
+            .get_products(product_ids, tenant_id)
+            .await
+            .context("error while fetching products")?
+            .into_iter()
+            .map(|a| (a.base_master_fields.id, a))
+            .collect();
+        let business_entity_ids = reqs
+            .iter()
+            .flat_map(|a| {
+                let bill_ship = a.bill_ship_detail.as_ref();
+                [
+                    Some(a.supplier_id),
+                    a.dispatch_from_id,
+                    bill_ship.map(|b| b.billed_to_customer_id),
+                    bill_ship.map(|b| b.shipped_to_customer_id),
+                ]
+            })
+            .flatten()
+            .unique()
+            .collect_vec();
+        let business_entities = self
+            .business_entity_service
+            .get_business_entities_by_ids(&business_entity_ids, &tenant_id)
+            .await
+            .context("error while fetching business entities")?
+            .into_iter()
+            .map(|a| (a.business_entity.base_master_fields.id, a))
+            .collect();
+        let registration_type = self
+            .tenant_service
+            .get_tenant_by_id(tenant_id)
+            .await
+            .context("error while fetching tenant")?
+            .context("tenant not found")?
+            .gst_registration_type;
+        let mut currencies = HashMap::new();
+        let currency_ids = reqs.iter().map(|a| a.currency_id).unique().collect_vec();
+        for currency_id in currency_ids {
+            if let Some(currency) = self
+                .currency_service
+                .get_currency_entry(currency_id, tenant_id)
+                .await
+                .context("error while fetching currency")?
+            {
+                currencies.insert(currency_id, currency);
+            }
+        }
+        let mut invoice_template_ids = HashSet::new();
+        let template_ids = reqs
+            .iter()
+            .map(|a| a.invoice_template_id)
+            .unique()
+            .collect_vec();
+        for template_id in template_ids {
+            if self
+                .invoice_template_service
+                .is_valid_template_id(template_id, tenant_id)
+                .await
+                .context("error while validating invoice template id")?
+            {
+                invoice_template_ids.insert(template_id);
+            }
+        }
+        let mut tax_invoice_series_ids = HashSet::new();
+        let series_ids = reqs
+            .iter()
+            .map(|a| a.invoicing_series_mst_id)
+            .unique()
+            .collect_vec();
+        for series_id in series_ids {
+            if self
+                .invoicing_series_service
+                .is_valid_invoicing_series_id(series_id, InvoicingSeriesType::TaxInvoice, tenant_id)
+                .await
+                .context("error while validating invoicing series id")?
+            {
+                tax_invoice_series_ids.insert(series_id);
+            }
+        }
+        Ok(InvoiceMasters {
+            products,
+            business_entities,
+            currencies,
+            invoice_template_ids,
+            tax_invoice_series_ids,
+            registration_type,
+        })
+    }
+
+    fn validate_create_invoice_request(
+        req: &CreateInvoiceRequest,
+        masters: &InvoiceMasters,
     ) -> Result<(), InvoicingServiceError> {
         let mut errors: Vec<String> = vec![];
-        self.validate_invoice_lines(req, tenant_id, &mut errors)
-            .await?;
+        Self::validate_invoice_lines(req, masters, &mut errors);
         Self::validate_order_date(req, &mut errors);
         Self::validate_invoice_bill_ship_detail(req, &mut errors);
-        self.validate_ids(req, tenant_id, &mut errors).await?;
+        Self::validate_export_detail(req, &mut errors);
+        Self::validate_supply_classification(req, masters, &mut errors);
+        Self::validate_email_to_customer(req, masters, &mut errors);
+        Self::validate_ids(req, masters, &mut errors);
         if !errors.is_empty() {
             Err(InvoicingServiceError::Validation(errors))
         } else {
@@ -95,33 +343,79 @@ impl InvoicingServiceImpl {
             }
         }
     }
-    async fn validate_invoice_lines(
-        &self,
+    fn validate_export_detail(req: &CreateInvoiceRequest, errors: &mut Vec<String>) {
+        if let Some(export_detail) = req.export_detail.as_ref() {
+            if export_detail.country_id == *INDIA_COUNTRY_ID {
+                errors.push("export country cannot be india".to_string());
+            }
+            if req.bill_ship_detail.is_none() {
+                errors.push("bill_ship_detail is mandatory for export invoice".to_string());
+            }
+            if req.invoice_lines.iter().any(|a| a.reverse_charge_applicable) {
+                errors.push("reverse charge is not applicable on export invoice".to_string());
+            }
+        }
+    }
+    ///lut for sez supplies applies only to a sez recipient, deemed exports are supplies to a registered
+    /// recipient in india other than a sez unit or developer
+    fn validate_supply_classification(
         req: &CreateInvoiceRequest,
-        tenant_id: Uuid,
+        masters: &InvoiceMasters,
+        errors: &mut Vec<String>,
+    ) {
+        let recipient = billed_to_entity(req, masters);
+        let sez_recipient =
+            recipient.is_some_and(|a| a.business_entity.sez_classification.is_sez());
+        if req.sez_lut_reference.is_some() && !sez_recipient {
+            errors.push(
+                "sez_lut_reference is applicable only for supplies to a sez unit or developer"
+                    .to_string(),
+            );
+        }
+        if sez_recipient
+            && req
+                .invoice_lines
+                .iter()
+                .any(|a| a.reverse_charge_applicable)
+        {
+            errors.push(
+                "reverse charge is not applicable on supplies to a sez unit or developer"
+                    .to_string(),
+            );
+        }
+        if req.deemed_export {
+            let registered =
+                recipient.is_some_and(|a| a.business_entity.entity_type.extract_gstin().is_some());
+            if req.export_detail.is_some() || sez_recipient || !registered {
+                errors.push(
+                    "deemed export is applicable only for a registered recipient in india other than a sez unit or developer"
+                        .to_string(),
+                );
+            }
+        }
+    }
+    fn validate_email_to_customer(
+        req: &CreateInvoiceRequest,
+        masters: &InvoiceMasters,
+        errors: &mut Vec<String>,
+    ) {
+        if req.email_to_customer && customer_email(req, masters).is_none() {
+            errors.push("email_to_customer needs a billed to customer having an email".to_string());
+        }
+    }
+    fn validate_invoice_lines(
+        req: &CreateInvoiceRequest,
+        masters: &InvoiceMasters,
         errors: &mut Vec<String>,
-    ) -> anyhow::Result<()> {
+    ) {
         if req.invoice_lines.is_empty() {
             errors.push("atleast one invoice line is required".to_string());
         }
-        let product_ids = req
-            .invoice_lines
-            .iter()
-            .map(|a| a.product_item_id)
-            .collect_vec();
-        let set: std::collections::HashSet<Uuid> = self
-            .product_item_service
-            .get_products(product_ids.clone(), tenant_id)
-            .await?
-            .into_iter()
-            .map(|a| a.base_master_fields.id)
-            .collect();
-        for x in product_ids {
-            if !set.contains(&x) {
+        for x in req.invoice_lines.iter().map(|a| a.product_item_id) {
+            if !masters.products.contains_key(&x) {
                 errors.push(format!("product id {} not found in system", x))
             }
         }
-        Ok(())
     }
     fn validate_order_date(req: &CreateInvoiceRequest, errors: &mut Vec<String>) {
         if let Some(date) = req.order_date.as_ref() {
@@ -134,233 +428,1044 @@ impl InvoicingServiceImpl {
         }
     }
 
-    async fn validate_ids(
-        &self,
+    fn validate_ids(
         req: &CreateInvoiceRequest,
-        tenant_id: Uuid,
+        masters: &InvoiceMasters,
         errors: &mut Vec<String>,
-    ) -> Result<(), InvoicingServiceError> {
-        async fn wrap<T: Error + Send + Sync + 'static>(
-            fetch_block: impl Future<Output = Result<bool, T>>,
-            err_msg: &str,
-            errors: &mut Vec<String>,
-        ) -> anyhow::Result<()> {
-            let valid = fetch_block.await.context("error during id validation")?;
-            if !valid {
-                errors.push(err_msg.to_string())
-            }
-            Ok(())
+    ) {
+        if !masters.currencies.contains_key(&req.currency_id) {
+            errors.push("currency id does not exists for this tenant id".to_string());
         }
-
-        wrap(
-            async {
-                self.currency_service
-                    .get_currency_entry(req.currency_id, tenant_id)
-                    .await
-                    .map(|a| a.is_some())
-            },
-            "currency id does not exists for this tenant id",
-            errors,
-        )
-        .await?;
-        wrap(
-            self.business_entity_service
-                .is_valid_business_entity_id(&req.supplier_id, &tenant_id),
-            "supplier id does not exists for this tenant id",
-            errors,
-        )
-        .await?;
-
-        if let Some(bill_ship) = req.bill_ship_detail.as_ref() {
-            wrap(
-                self.business_entity_service
-                    .is_valid_business_entity_id(&bill_ship.billed_to_customer_id, &tenant_id),
-                "bill_to_id does not exists for this tenant id",
-                errors,
-            )
-            .await?;
-            wrap(
-                self.business_entity_service
-                    .is_valid_business_entity_id(&bill_ship.shipped_to_customer_id, &tenant_id),
-                "ship_to_id does not exists for this tenant id",
-                errors,
-            )
-            .await?;
+        if !masters.business_entities.contains_key(&req.supplier_id) {
+            errors.push("supplier id does not exists for this tenant id".to_string());
         }
-        wrap(
-            self.invoice_template_service
-                .is_valid_template_id(req.invoice_template_id, tenant_id),
-            "invoice template id does not exists for this tenant id",
-            errors,
-        )
-        .await?;
-        Ok(())
-    }
-
-    async fn igst_applicable(
-        &self,
-        supplier_id: Uuid,
-        bill_to_id: Option<Uuid>,
-        tenant_id: Uuid,
-    ) -> anyhow::Result<bool> {
-        return if let Some(bill_to_id) = bill_to_id {
-            let supplier = self
-                .business_entity_service
-                .get_business_entity_by_id(&supplier_id, &tenant_id)
-                .await
-                .context("supplier fetch get_business_entity_by_id failed")?
-                .with_context(|| format!("business entity id not found for id:{}", supplier_id))?;
-
-            let bill_to = self
-                .business_entity_service
-                .get_business_entity_by_id(&bill_to_id, &tenant_id)
-                .await
-                .context("bill to fetch get_business_entity_by_id failed")?
-                .with_context(|| format!("business entity id not found for id:{}", bill_to_id))?;
-            let supplier_gstin = supplier
-                .business_entity
-                .entity_type
-                .extract_gstin()
-                .with_context(|| {
-                    format!(
-                        "could not extract gstin from supplier id {}",
-                        supplier.business_entity.base_master_fields.id
-                    )
-                })?;
-            let bill_to_gstin = bill_to.business_entity.entity_type.extract_gstin();
-            if let Some(bill_to_gstin) = bill_to_gstin {
-                is_gstin_from_same_state(supplier_gstin, bill_to_gstin).map(|a| !a)
-            } else {
-                return Ok(false);
+        if let Some(bill_ship) = req.bill_ship_detail.as_ref() {
+            if !masters
+                .business_entities
+                .contains_key(&bill_ship.billed_to_customer_id)
+            {
+                errors.push("bill_to_id does not exists for this tenant id".to_string());
             }
-        } else {
-            Ok(false)
-        };
+            if !masters
+                .business_entities
+                .contains_key(&bill_ship.shipped_to_customer_id)
+            {
+                errors.push("ship_to_id does not exists for this tenant id".to_string());
+            }
+        }
+        if !masters
+            .tax_invoice_series_ids
+            .contains(&req.invoicing_series_mst_id)
+        {
+            errors
+                .push("invoicing series id is not a tax invoice series of this tenant".to_string());
+        }
+        if !masters
+            .invoice_template_ids
+            .contains(&req.invoice_template_id)
+        {
+            errors.push("invoice template id does not exists for this tenant id".to_string());
+        }
     }
-}
-
-fn is_gstin_from_same_state(gstin1: &GstinNo, gstin2: &GstinNo) -> anyhow::Result<bool> {
-    Ok(gstin1.get_str().get(0..2).context("gstin short than 2")?
-        == gstin2.get_str().get(0..2).context("gstin short than 2")?)
-}
 
-#[async_trait]
-impl InvoicingService for InvoicingServiceImpl {
-    async fn create_invoice(
+    ///validates and stores one invoice, returns the invoice number along with its pdf request
+    async fn create_invoice_with_masters(
         &self,
         req: CreateInvoiceRequest,
+        masters: &InvoiceMasters,
         tenant_id: Uuid,
         user_id: Uuid,
-    ) -> Result<InvoicePdfRequest, InvoicingServiceError> {
-        self.validate_create_invoice_request(&req, tenant_id)
-            .await?;
-        let curr = self
-            .currency_service
-            .get_currency_entry(req.currency_id, tenant_id)
-            .await
-            .context("err while fetching currency from db")?
-            .context("currency not found in db")?;
-        let igst_applicable = self
-            .igst_applicable(
-                req.supplier_id,
-                req.bill_ship_detail
-                    .as_ref()
-                    .map(|a| a.billed_to_customer_id),
-                tenant_id,
-            )
-            .await?;
-        let p = req
-            .invoice_lines
-            .iter()
-            .map(|a| a.product_item_id)
-            .collect_vec();
-        let po = self
-            .product_item_service
-            .get_products(p, tenant_id)
-            .await
-            .unwrap();
-        let new_req = req.to_create_invoice_with_all_details_included(po)?;
-        let db_model =
-            convert_to_invoice_db(&new_req, curr.scale, igst_applicable, user_id, tenant_id)?;
+    ) -> Result<(String, InvoicePdfRequest), InvoicingServiceError> {
+        Self::validate_create_invoice_request(&req, masters)?;
+        let email_to = customer_email(&req, masters).filter(|_| req.email_to_customer);
+        let prepared = Self::prepare_invoice(req, masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
         let invoice_id = self.dao.create_invoice(&db_model).await?;
         let pdaf = InvoiceDocCreationDataInput {
             invoice: &db_model,
-            req: &new_req,
+            req: &prepared.req,
         };
         let inv = convert_to_invoice_doc_model(
             &pdaf,
-            invoice_id.invoice_number,
+            invoice_id.invoice_number.clone(),
             self.business_entity_service.clone(),
-            curr,
+            prepared.currency,
         )
         .await?;
-        Ok(InvoicePdfRequest {
+        let pdf_request = InvoicePdfRequest {
             tenant_id,
             invoice_id: invoice_id.invoice_id,
             invoice: inv,
+        };
+        self.enqueue_document_jobs(&pdf_request, &db_model, email_to)
+            .await?;
+        Ok((invoice_id.invoice_number, pdf_request))
+    }
+
+    ///pdf, e-invoice and email of an issued invoice are produced by background jobs keyed by the invoice id,
+    /// so creating the same invoice again does not enqueue them twice. an invoice needing an e-invoice gets its pdf
+    /// and email jobs once its irn is generated, or right away when no e-invoice portal is configured
+    async fn enqueue_document_jobs(
+        &self,
+        pdf_request: &InvoicePdfRequest,
+        invoice: &InvoiceDb<'_>,
+        email_to: Option<&str>,
+    ) -> Result<(), InvoicingServiceError> {
+        if !(invoice.e_invoicing_applicable && invoice.b2b_invoice) {
+            return self.enqueue_pdf_and_email_jobs(pdf_request, email_to).await;
+        }
+        if !self.einvoice_portal_client.is_configured() {
+            warn!(
+                invoice_id = %pdf_request.invoice_id,
+                "e-invoice portal is not configured, the invoice pdf is created without an irn"
+            );
+            return self.enqueue_pdf_and_email_jobs(pdf_request, email_to).await;
+        }
+        let payload = EInvoiceJobPayload {
+            pdf_request,
+            email_to: email_to.map(str::to_string),
+        };
+        let job = EnqueueJobRequest {
+            kind: JobKind::EInvoice,
+            idempotence_key: pdf_request.invoice_id,
+            payload: serde_json::to_value(payload).context("could not serialize e-invoice job")?,
+        };
+        self.background_job_service
+            .enqueue(pdf_request.tenant_id, job)
+            .await
+            .context("could not enqueue e-invoice job")?;
+        Ok(())
+    }
+
+    ///the email is left out when no email provider is configured, the pdf is still created
+    async fn enqueue_pdf_and_email_jobs(
+        &self,
+        pdf_request: &InvoicePdfRequest,
+        email_to: Option<&str>,
+    ) -> Result<(), InvoicingServiceError> {
+        let invoice_id = pdf_request.invoice_id;
+        let mut jobs = vec![EnqueueJobRequest {
+            kind: JobKind::InvoicePdf,
+            idempotence_key: invoice_id,
+            payload: serde_json::to_value(pdf_request)
+                .context("could not serialize invoice pdf request")?,
+        }];
+        match email_to {
+            Some(to) if self.email_client.is_configured() => {
+                let payload = InvoiceEmailJobPayload {
+                    to: to.to_string(),
+                    pdf_request,
+                };
+                jobs.push(EnqueueJobRequest {
+                    kind: JobKind::InvoiceEmail,
+                    idempotence_key: invoice_id,
+                    payload: serde_json::to_value(payload)
+                        .context("could not serialize invoice email job")?,
+                });
+            }
+            Some(_) => {
+                warn!(
+                    %invoice_id,
+                    "email provider is not configured, invoice is not emailed to the customer"
+                );
+            }
+            None => {}
+        }
+        for job in jobs {
+            self.background_job_service
+                .enqueue(pdf_request.tenant_id, job)
+                .await
+                .context("could not enqueue invoice document job")?;
+        }
+        Ok(())
+    }
+
+    ///fetches the currency of the request and computes the line level details
+    fn prepare_invoice(
+        req: CreateInvoiceRequest,
+        masters: &InvoiceMasters,
+    ) -> Result<PreparedInvoice, InvoicingServiceError> {
+        let currency = masters
+            .currencies
+            .get(&req.currency_id)
+            .cloned()
+            .context("currency not found in db")?;
+        let place_of_supply = Self::place_of_supply(&req, masters)?;
+        if masters.registration_type == GstRegistrationType::Composition
+            && place_of_supply.igst_applicable()
+        {
+            return Err(InvoicingServiceError::Validation(vec![
+                "composition taxable person cannot make inter-state supplies".to_string(),
+            ]));
+        }
+        let supply_classification = supply_classification(&req, masters);
+        let po = req
+            .invoice_lines
+            .iter()
+            .filter_map(|a| masters.products.get(&a.product_item_id).cloned())
+            .collect_vec();
+        let req = req.to_create_invoice_with_all_details_included(
+            po,
+            supply_classification,
+            masters.registration_type,
+        )?;
+        Ok(PreparedInvoice {
+            req,
+            currency,
+            place_of_supply,
         })
     }
-    async fn create_invoice_pdf(
+
+    ///an invoice needing an e-invoice is rendered only after its irn is stored, so that the irn and the signed qr
+    /// are printed on it. without a configured portal no irn can be generated and it is rendered without one
+    async fn upload_invoice_pdf(
         &self,
-        pdf_data: InvoicePdfRequest,
+        mut pdf_data: InvoicePdfRequest,
+        key: &str,
     ) -> Result<String, InvoicingServiceError> {
-        let is_processed = self
+        let detail = self
             .dao
-            .is_invoice_pdf_created(pdf_data.tenant_id, pdf_data.invoice_id)
-            .await?;
-        let key = create_storage_file_key(pdf_data.tenant_id, pdf_data.invoice_id);
-        if is_processed {
-            let ds = self
-                .storage_service
-                .get_object_url(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), None)
-                .await?;
-            return Ok(ds);
+            .get_invoice_render_detail(pdf_data.tenant_id, pdf_data.invoice_id)
+            .await?
+            .ok_or(InvoicingServiceError::InvoiceNotFound(pdf_data.invoice_id))?;
+        match detail.einvoice {
+            Some(einvoice) => {
+                pdf_data.invoice.einvoice_detail = Some(convert_to_einvoice_detail(einvoice)?);
+            }
+            None if detail.einvoice_required && self.einvoice_portal_client.is_configured() => {
+                return Err(InvoicingServiceError::Validation(vec![format!(
+                    "irn of invoice {} is not generated yet",
+                    pdf_data.invoice_id
+                )]));
+            }
+            None => {}
         }
-        let pdf_bytes = invoice_template::create_invoice_pdf(pdf_data.invoice)?;
+        let assets = self
+            .fetch_invoice_assets(pdf_data.tenant_id, detail.supplier_id)
+            .await;
+        let (layout, template) = self
+            .fetch_invoice_template(pdf_data.tenant_id, detail.invoice_template_id)
+            .await?;
+        let pdf_bytes = invoice_template::create_invoice_pdf(
+            pdf_data.invoice,
+            assets,
+            layout,
+            template.as_deref(),
+        )?;
         let uploaded_url = self
             .storage_service
-            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), pdf_bytes, None)
+            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key, pdf_bytes, None)
             .await?;
         self.dao
-            .persist_invoice_pdf_dtl(pdf_data.tenant_id, pdf_data.invoice_id, key.as_str())
+            .persist_invoice_pdf_dtl(pdf_data.tenant_id, pdf_data.invoice_id, key)
             .await?;
         Ok(uploaded_url)
     }
 
-    //template_id,series_mst_id,currency_id,supplier_id,billed_to,shipped_to ids must exist for this tenant
+    ///a missing or unreadable asset is left out of the invoice instead of failing the pdf
+    async fn fetch_invoice_assets(&self, tenant_id: Uuid, supplier_id: Uuid) -> InvoiceAssets {
+        let keys = match self
+            .business_entity_service
+            .get_invoice_assets(supplier_id, tenant_id)
+            .await
+        {
+            Ok(keys) => keys,
+            Err(e) => {
+                warn!(%e, %supplier_id, "could not fetch invoice assets of supplier");
+                return InvoiceAssets::default();
+            }
+        };
+        let (logo, signature, terms_and_conditions) = tokio::join!(
+            self.fetch_invoice_asset(keys.business_logo_s3_id.as_deref()),
+            self.fetch_invoice_asset(keys.invoice_signature_s3_id.as_deref()),
+            self.fetch_invoice_asset(keys.terms_and_conditions_s3_id.as_deref())
+        );
+        InvoiceAssets {
+            logo,
+            signature,
+            terms_and_conditions: terms_and_conditions.and_then(|a| String::from_utf8(a).ok()),
+        }
+    }
+
+    ///built-in layout of the template and the sources of its latest version. unlike the assets, a template which
+    /// cannot be fetched fails the pdf instead of falling back to the built-in layout
+    async fn fetch_invoice_template(
+        &self,
+        tenant_id: Uuid,
+        invoice_template_id: Uuid,
+    ) -> Result<(InvoiceLayout, Option<Arc<InvoiceTemplateSource>>), InvoicingServiceError> {
+        let layout = self
+            .invoice_template_service
+            .get_template_by_id(invoice_template_id, tenant_id)
+            .await
+            .context("could not fetch invoice template")?
+            .with_context(|| format!("invoice template {} not found", invoice_template_id))?
+            .layout;
+        let template = self
+            .invoice_template_service
+            .get_template_source(invoice_template_id, tenant_id)
+            .await
+            .context("could not fetch invoice template")?;
+        Ok((layout, template))
+    }
+
+    async fn fetch_invoice_asset(&self, key: Option<&str>) -> Option<Vec<u8>> {
+        let key = key?;
+        match self
+            .storage_service
+            .get_object(FINANCIAL_DOCS_BUCKET_NAME, key)
+            .await
+        {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                warn!(error = %format!("{:#}", e), key, "could not fetch invoice asset");
+                None
+            }
+        }
+    }
+
+    ///shipped to party is considered only when goods are delivered or the service is performed at another party
+    fn place_of_supply(
+        req: &CreateInvoiceRequest,
+        masters: &InvoiceMasters,
+    ) -> Result<PlaceOfSupply, InvoicingServiceError> {
+        let entity = |id: Uuid| {
+            masters
+                .business_entities
+                .get(&id)
+                .map(|a| pos_party(a))
+                .with_context(|| format!("business entity id not found for id:{}", id))
+        };
+        let (billed_to, shipped_to) = match req.bill_ship_detail.as_ref() {
+            Some(a) if a.billed_to_customer_id != a.shipped_to_customer_id => (
+                Some(entity(a.billed_to_customer_id)?),
+                Some(entity(a.shipped_to_customer_id)?),
+            ),
+            Some(a) => (Some(entity(a.billed_to_customer_id)?), None),
+            None => (None, None),
+        };
+        let input = PlaceOfSupplyInput {
+            supplier: entity(req.supplier_id)?,
+            billed_to,
+            shipped_to,
+            supply_kind: if req.service_invoice {
+                SupplyKind::Services(req.service_category.unwrap_or_default())
+            } else {
+                SupplyKind::Goods
+            },
+            export: req.export_detail.is_some(),
+        };
+        determine_place_of_supply(&input)
+            .map_err(|e| InvoicingServiceError::Validation(vec![e.to_string()]))
+    }
 }
 
-fn create_storage_file_key(tenant_id: Uuid, invoice_id: Uuid) -> String {
-    format!("{}-invoice-{}.pdf", tenant_id, invoice_id)
+fn billed_to_entity<'a>(
+    req: &CreateInvoiceRequest,
+    masters: &'a InvoiceMasters,
+) -> Option<&'a BusinessEntityDto> {
+    req.bill_ship_detail
+        .as_ref()
+        .and_then(|a| masters.business_entities.get(&a.billed_to_customer_id))
+        .map(|a| a.as_ref())
 }
 
-pub fn get_invoicing_service(
-    arc: Arc<Pool>,
-    tenant_service: Arc<dyn TenantService>,
-    currency_service: Arc<dyn CurrencyService>,
-    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
-    business_entity_service: Arc<dyn BusinessEntityService>,
-    invoice_template_service: Arc<dyn InvoiceTemplateService>,
-    storage_service: Arc<dyn StorageService>,
-    product_item_service: Arc<dyn ProductItemService>,
-) -> Arc<dyn InvoicingService> {
-    let invoicing_service_dao = get_invoicing_dao(arc);
-    let service = InvoicingServiceImpl {
-        dao: invoicing_service_dao,
-        tenant_service,
-        currency_service,
-        invoicing_series_service,
-        business_entity_service,
-        invoice_template_service,
-        storage_service,
-        product_item_service,
-    };
-    Arc::new(service)
+fn customer_email<'a>(req: &CreateInvoiceRequest, masters: &'a InvoiceMasters) -> Option<&'a str> {
+    billed_to_entity(req, masters)
+        .and_then(|a| a.business_entity.entity_type.extract_email())
+        .map(|a| a.inner())
 }
 
-#[cfg(test)]
+///supplies to a sez recipient are without payment of igst when made under lut/bond
+fn supply_classification(
+    req: &CreateInvoiceRequest,
+    masters: &InvoiceMasters,
+) -> SupplyClassification {
+    if let Some(export_detail) = req.export_detail.as_ref() {
+        return match export_detail.export_type {
+            ExportType::WithPaymentOfIgst => SupplyClassification::ExportWithPayment,
+            ExportType::UnderLut => SupplyClassification::ExportWithoutPayment,
+        };
+    }
+    let sez_recipient = billed_to_entity(req, masters)
+        .is_some_and(|a| a.business_entity.sez_classification.is_sez());
+    if sez_recipient {
+        if req.sez_lut_reference.is_some() {
+            SupplyClassification::SezWithoutPayment
+        } else {
+            SupplyClassification::SezWithPayment
+        }
+    } else if req.deemed_export {
+        SupplyClassification::DeemedExport
+    } else {
+        SupplyClassification::Regular
+    }
+}
+
+fn pos_party(entity: &BusinessEntityDto) -> PosParty<'_> {
+    let address = entity.address.as_ref();
+    PosParty {
+        gstin: entity
+            .business_entity
+            .entity_type
+            .extract_gstin()
+            .map(|a| a.get_str()),
+        state_code: address.map(|a| a.state.state_code.as_str()),
+        country_id: address.map(|a| a.country.id),
+        sez: entity.business_entity.sez_classification.is_sez(),
+    }
+}
+
+///first reason for which the invoice cannot be amended, the amount settled is checked once the new total is known
+fn amendment_blocked_status(
+    state: &InvoiceAmendmentStateDb,
+    req: &AmendInvoiceRequest,
+) -> Option<AmendInvoiceDbStatus> {
+    if state.invoice_status != "issued" {
+        Some(AmendInvoiceDbStatus::NotIssued)
+    } else if state.e_invoicing_applicable {
+        Some(AmendInvoiceDbStatus::Einvoiced)
+    } else if state.supplier_id != req.invoice.supplier_id {
+        Some(AmendInvoiceDbStatus::SupplierChanged)
+    } else if state.entity_version_id != req.entity_version_id {
+        Some(AmendInvoiceDbStatus::VersionConflict)
+    } else if state.gstr1_filed {
+        Some(AmendInvoiceDbStatus::Gstr1Filed)
+    } else {
+        None
+    }
+}
+
+fn amendment_error(
+    invoice_id: Uuid,
+    status: AmendInvoiceDbStatus,
+    current_version: i32,
+) -> InvoicingServiceError {
+    let validation = |msg: &str| InvoicingServiceError::Validation(vec![msg.to_string()]);
+    match status {
+        AmendInvoiceDbStatus::NotFound => InvoicingServiceError::InvoiceNotFound(invoice_id),
+        AmendInvoiceDbStatus::VersionConflict => InvoicingServiceError::VersionConflict {
+            invoice_id,
+            current_version,
+        },
+        AmendInvoiceDbStatus::NotIssued => {
+            validation("only issued invoices can be amended, edit the draft instead")
+        }
+        AmendInvoiceDbStatus::Einvoiced => validation(
+            "e-invoiced invoices cannot be amended, cancel the irn and issue a new invoice",
+        ),
+        AmendInvoiceDbStatus::SupplierChanged => {
+            validation("supplier of an invoice cannot be amended")
+        }
+        AmendInvoiceDbStatus::Gstr1Filed => validation(
+            "gstr-1 for the period of the invoice is filed, issue a credit or debit note instead",
+        ),
+        AmendInvoiceDbStatus::BelowAmountReceived => validation(
+            "amended total cannot be less than the amount already received against the invoice",
+        ),
+        AmendInvoiceDbStatus::LedgerAccountsRequired => validation(
+            "invoice is posted to the ledger, ledger accounts are needed to post the amendment",
+        ),
+        AmendInvoiceDbStatus::Amended => InvoicingServiceError::Other(anyhow::anyhow!(
+            "unexpected amendment status of invoice {}",
+            invoice_id
+        )),
+    }
+}
+
+#[async_trait]
+impl InvoicingService for InvoicingServiceImpl {
+    async fn create_invoice(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoicePdfRequest, InvoicingServiceError> {
+        if self
+            .invoice_approval_service
+            .is_approval_required(tenant_id)
+            .await?
+        {
+            return Err(InvoicingServiceError::Validation(vec![
+                "invoices of this tenant need approval, create a draft invoice instead".to_string(),
+            ]));
+        }
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        let (_, pdf_request) = self
+            .create_invoice_with_masters(req, &masters, tenant_id, user_id)
+            .await?;
+        Ok(pdf_request)
+    }
+
+    async fn create_invoices_in_bulk(
+        &self,
+        req: CreateInvoicesInBulkRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<BulkInvoiceItemResult>, InvoicingServiceError> {
+        if req.invoices.is_empty() {
+            return Err(InvoicingServiceError::Validation(vec![
+                "atleast one invoice is required".to_string(),
+            ]));
+        }
+        if req.invoices.len() > MAX_BULK_INVOICES {
+            return Err(InvoicingServiceError::Validation(vec![format!(
+                "at most {} invoices can be created in one request",
+                MAX_BULK_INVOICES
+            )]));
+        }
+        if self
+            .invoice_approval_service
+            .is_approval_required(tenant_id)
+            .await?
+        {
+            return Err(InvoicingServiceError::Validation(vec![
+                "invoices of this tenant need approval, create a draft invoice instead".to_string(),
+            ]));
+        }
+        let masters = self.fetch_masters(&req.invoices, tenant_id).await?;
+        let mut results = Vec::with_capacity(req.invoices.len());
+        for (index, invoice) in req.invoices.into_iter().enumerate() {
+            let idempotence_key = invoice.idempotence_key;
+            let status = match self
+                .create_invoice_with_masters(invoice, &masters, tenant_id, user_id)
+                .await
+            {
+                Ok((invoice_number, pdf_request)) => BulkInvoiceItemStatus::Created {
+                    invoice_id: pdf_request.invoice_id,
+                    invoice_number,
+                    pdf_request: Box::new(pdf_request),
+                },
+                Err(InvoicingServiceError::Validation(errors)) => {
+                    BulkInvoiceItemStatus::ValidationFailed { errors }
+                }
+                Err(e) => BulkInvoiceItemStatus::Failed {
+                    error: e.to_string(),
+                },
+            };
+            results.push(BulkInvoiceItemResult {
+                index,
+                idempotence_key,
+                status,
+            });
+        }
+        Ok(results)
+    }
+    async fn create_invoice_pdf(
+        &self,
+        pdf_data: InvoicePdfRequest,
+    ) -> Result<String, InvoicingServiceError> {
+        let is_processed = self
+            .dao
+            .is_invoice_pdf_created(pdf_data.tenant_id, pdf_data.invoice_id)
+            .await?;
+        let key = create_storage_file_key(pdf_data.tenant_id, pdf_data.invoice_id);
+        if is_processed {
+            let ds = self
+                .storage_service
+                .get_object_url(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), None)
+                .await?;
+            return Ok(ds);
+        }
+        self.upload_invoice_pdf(pdf_data, key.as_str()).await
+    }
+
+    async fn generate_einvoice(
+        &self,
+        payload: EInvoiceJobPayload,
+    ) -> Result<(), InvoicingServiceError> {
+        let tenant_id = payload.pdf_request.tenant_id;
+        let invoice_id = payload.pdf_request.invoice_id;
+        let detail = self
+            .einvoice_portal_client
+            .generate_irn(tenant_id, invoice_id)
+            .await?;
+        self.dao
+            .persist_einvoice_detail(tenant_id, invoice_id, &detail)
+            .await?;
+        self.enqueue_pdf_and_email_jobs(&payload.pdf_request, payload.email_to.as_deref())
+            .await
+    }
+
+    async fn create_draft_invoice(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateDraftInvoiceResponse, InvoicingServiceError> {
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req, &masters)?;
+        let draft_request =
+            serde_json::to_value(&req).context("could not serialize draft invoice request")?;
+        let prepared = Self::prepare_invoice(req, &masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        let invoice_id = self
+            .dao
+            .create_draft_invoice(&db_model, &draft_request)
+            .await?;
+        Ok(CreateDraftInvoiceResponse { invoice_id })
+    }
+
+    async fn update_draft_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), InvoicingServiceError> {
+        self.invoice_approval_service
+            .authorize_draft_edit(invoice_id, tenant_id, user_id)
+            .await?;
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req, &masters)?;
+        let draft_request =
+            serde_json::to_value(&req).context("could not serialize draft invoice request")?;
+        let prepared = Self::prepare_invoice(req, &masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        let updated = self
+            .dao
+            .update_draft_invoice(invoice_id, &db_model, &draft_request)
+            .await?;
+        if !updated {
+            return Err(InvoicingServiceError::Validation(vec![format!(
+                "invoice {} is not a draft anymore",
+                invoice_id
+            )]));
+        }
+        Ok(())
+    }
+
+    async fn issue_invoice(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoicePdfRequest, InvoicingServiceError> {
+        self.invoice_approval_service
+            .authorize_issue(invoice_id, tenant_id, user_id)
+            .await?;
+        let draft = self
+            .dao
+            .get_invoice_draft(tenant_id, invoice_id)
+            .await?
+            .ok_or(InvoicingServiceError::InvoiceNotFound(invoice_id))?;
+        let draft_request = draft.draft_request.ok_or_else(|| {
+            InvoicingServiceError::Validation(vec![format!(
+                "invoice {} was not created as a draft",
+                invoice_id
+            )])
+        })?;
+        let req: CreateInvoiceRequest = serde_json::from_value(draft_request)
+            .context("could not deserialize draft invoice request")?;
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req, &masters)?;
+        let email_to = customer_email(&req, &masters).filter(|_| req.email_to_customer);
+        let prepared = Self::prepare_invoice(req, &masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        //masters used while computing the draft may have changed after it was approved
+        if (db_model.total_payable_amount - draft.total_payable_amount).abs() > 0.005
+            || (db_model.total_tax_amount - draft.total_tax_amount).abs() > 0.005
+        {
+            return Err(InvoicingServiceError::Validation(vec![format!(
+                "totals of invoice {} changed after approval, it has to be rejected and approved again",
+                invoice_id
+            )]));
+        }
+        let invoice_number = self
+            .dao
+            .issue_invoice(
+                tenant_id,
+                invoice_id,
+                db_model.invoice_date_ms,
+                db_model.financial_year,
+                user_id,
+                &db_model.transfers,
+                db_model.transfer_code,
+            )
+            .await?
+            .ok_or(InvoicingServiceError::InvoiceNotFound(invoice_id))?;
+        let pdaf = InvoiceDocCreationDataInput {
+            invoice: &db_model,
+            req: &prepared.req,
+        };
+        let inv = convert_to_invoice_doc_model(
+            &pdaf,
+            invoice_number,
+            self.business_entity_service.clone(),
+            prepared.currency,
+        )
+        .await?;
+        let pdf_request = InvoicePdfRequest {
+            tenant_id,
+            invoice_id,
+            invoice: inv,
+        };
+        self.enqueue_document_jobs(&pdf_request, &db_model, email_to)
+            .await?;
+        Ok(pdf_request)
+    }
+
+    async fn compute_invoice_document(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ComputedInvoiceDocument, InvoicingServiceError> {
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req, &masters)?;
+        let prepared = Self::prepare_invoice(req, &masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        let pdaf = InvoiceDocCreationDataInput {
+            invoice: &db_model,
+            req: &prepared.req,
+        };
+        let document = convert_to_invoice_doc_model(
+            &pdaf,
+            String::new(),
+            self.business_entity_service.clone(),
+            prepared.currency,
+        )
+        .await?;
+        Ok(ComputedInvoiceDocument {
+            document_date_ms: db_model.invoice_date_ms,
+            financial_year: db_model.financial_year,
+            total_taxable_amount: db_model.total_taxable_amount,
+            total_tax_amount: db_model.total_tax_amount,
+            total_additional_charges_amount: db_model.total_additional_charges_amount,
+            total_payable_amount: db_model.total_payable_amount,
+            document,
+        })
+    }
+
+    async fn validate_invoices(
+        &self,
+        reqs: &[CreateInvoiceRequest],
+        tenant_id: Uuid,
+    ) -> Result<Vec<Vec<String>>, InvoicingServiceError> {
+        let masters = self.fetch_masters(reqs, tenant_id).await?;
+        let mut failures = Vec::with_capacity(reqs.len());
+        for req in reqs {
+            match Self::validate_create_invoice_request(req, &masters) {
+                Ok(()) => failures.push(vec![]),
+                Err(InvoicingServiceError::Validation(errors)) => failures.push(errors),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(failures)
+    }
+
+    async fn amend_invoice(
+        &self,
+        invoice_id: Uuid,
+        req: AmendInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<AmendInvoiceResponse, InvoicingServiceError> {
+        let amendment_reason = req.amendment_reason.trim().to_string();
+        if amendment_reason.is_empty() || amendment_reason.len() > AMENDMENT_REASON_MAX_LENGTH {
+            return Err(InvoicingServiceError::Validation(vec![format!(
+                "amendment reason is mandatory and cannot be longer than {} characters",
+                AMENDMENT_REASON_MAX_LENGTH
+            )]));
+        }
+        let state = self
+            .dao
+            .get_invoice_amendment_state(tenant_id, invoice_id)
+            .await?
+            .ok_or(InvoicingServiceError::InvoiceNotFound(invoice_id))?;
+        if let Some(status) = amendment_blocked_status(&state, &req) {
+            return Err(amendment_error(invoice_id, status, state.entity_version_id));
+        }
+        let invoice_number = state
+            .invoice_number
+            .clone()
+            .with_context(|| format!("issued invoice {} has no number", invoice_id))?;
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req.invoice), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req.invoice, &masters)?;
+        let prepared = Self::prepare_invoice(req.invoice, &masters)?;
+        let mut db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        //number, date and series are not amended
+        db_model.invoice_date_ms = state.invoice_date_ms;
+        db_model.financial_year = state.financial_year;
+        db_model.invoicing_series_mst_id = state.invoicing_series_mst_id;
+        db_model.e_invoicing_applicable = state.e_invoicing_applicable;
+        if (db_model.total_payable_amount * 100.0).round() < (state.amount_settled * 100.0).round()
+        {
+            return Err(amendment_error(
+                invoice_id,
+                AmendInvoiceDbStatus::BelowAmountReceived,
+                state.entity_version_id,
+            ));
+        }
+        let resp = self
+            .dao
+            .amend_invoice(
+                invoice_id,
+                req.entity_version_id,
+                &db_model,
+                amendment_reason.as_str(),
+            )
+            .await?;
+        let entity_version_id = resp.entity_version_id.unwrap_or(state.entity_version_id);
+        if resp.status != AmendInvoiceDbStatus::Amended {
+            return Err(amendment_error(invoice_id, resp.status, entity_version_id));
+        }
+        let pdaf = InvoiceDocCreationDataInput {
+            invoice: &db_model,
+            req: &prepared.req,
+        };
+        let inv = convert_to_invoice_doc_model(
+            &pdaf,
+            invoice_number.clone(),
+            self.business_entity_service.clone(),
+            prepared.currency,
+        )
+        .await?;
+        //every version gets its own pdf so that the one sent earlier stays downloadable
+        let key = create_amended_storage_file_key(tenant_id, invoice_id, entity_version_id);
+        let pdf_url = self
+            .upload_invoice_pdf(
+                InvoicePdfRequest {
+                    tenant_id,
+                    invoice_id,
+                    invoice: inv,
+                },
+                key.as_str(),
+            )
+            .await?;
+        Ok(AmendInvoiceResponse {
+            invoice_id,
+            invoice_number,
+            entity_version_id,
+            pdf_url,
+        })
+    }
+
+    async fn get_invoice_versions(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceVersion>, InvoicingServiceError> {
+        if self
+            .dao
+            .get_invoice_amendment_state(tenant_id, invoice_id)
+            .await?
+            .is_none()
+        {
+            return Err(InvoicingServiceError::InvoiceNotFound(invoice_id));
+        }
+        let versions = self.dao.get_invoice_versions(tenant_id, invoice_id).await?;
+        Ok(versions)
+    }
+
+    async fn get_place_of_supply(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+    ) -> Result<PlaceOfSupply, InvoicingServiceError> {
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::place_of_supply(&req, &masters)
+    }
+
+    async fn preview_invoice(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<InvoicePreview, InvoicingServiceError> {
+        let masters = self
+            .fetch_masters(std::slice::from_ref(&req), tenant_id)
+            .await?;
+        Self::validate_create_invoice_request(&req, &masters)?;
+        let prepared = Self::prepare_invoice(req, &masters)?;
+        let db_model = convert_to_invoice_db(
+            &prepared.req,
+            prepared.currency.scale,
+            &prepared.place_of_supply,
+            user_id,
+            tenant_id,
+        )?;
+        Ok(create_invoice_preview(
+            &prepared.req,
+            &db_model,
+            prepared.currency.scale,
+        )?)
+    }
+
+    async fn create_invoice_preview_pdf(
+        &self,
+        req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<u8>, InvoicingServiceError> {
+        let supplier_id = req.supplier_id;
+        let invoice_template_id = req.invoice_template_id;
+        let mut computed = self
+            .compute_invoice_document(req, tenant_id, user_id)
+            .await?;
+        computed.document.watermark = Some(PREVIEW_WATERMARK.to_string());
+        let assets = self.fetch_invoice_assets(tenant_id, supplier_id).await;
+        let (layout, template) = self
+            .fetch_invoice_template(tenant_id, invoice_template_id)
+            .await?;
+        Ok(invoice_template::create_invoice_pdf(
+            computed.document,
+            assets,
+            layout,
+            template.as_deref(),
+        )?)
+    }
+
+    //template_id,series_mst_id,currency_id,supplier_id,billed_to,shipped_to ids must exist for this tenant
+}
+
+///round off is the difference of the payable amount, rounded to the scale of the currency, from the sum of its
+/// components
+fn create_invoice_preview(
+    req: &CreateInvoiceWithAllDetailsIncluded,
+    invoice: &InvoiceDb,
+    currency_scale: i16,
+) -> anyhow::Result<InvoicePreview> {
+    let mut lines = Vec::with_capacity(req.invoice_lines.len());
+    for (line_no, line) in req.invoice_lines.iter().enumerate() {
+        lines.push(InvoicePreviewLine {
+            line_no: line_no as i16 + 1,
+            product_item_id: line.product_item_id.base_master_fields.id,
+            discount_amount: line.get_discount_amount()?,
+            taxable_amount: line.taxable_amount()?,
+            tax_percentage: line.tax_percentage()?,
+            tax_amount: line.tax_amount()?,
+            cess_amount: line.cess_amount()?,
+            line_net_total: line.net_line_total()?,
+        });
+    }
+    let mut additional_charges = Vec::with_capacity(req.additional_charges.len());
+    for (line_no, charge) in req.additional_charges.iter().enumerate() {
+        additional_charges.push(InvoicePreviewAdditionalCharge {
+            line_no: line_no as i16 + 1,
+            rate: charge.rate.inner(),
+            tax_percentage: req.additional_charge_tax_percentage(charge)?,
+            tax_amount: req.additional_charge_tax_amount(charge)?,
+        });
+    }
+    let total_cess_amount = req.total_cess_amount()?;
+    let minor_unit = 10_f64.powi(currency_scale as i32);
+    let round_off = invoice.total_payable_amount
+        - (invoice.total_taxable_amount
+            + invoice.total_tax_amount
+            + total_cess_amount
+            + invoice.total_additional_charges_amount);
+    let tax_summary = create_invoice_tax_summary(&InvoiceDocCreationDataInput { invoice, req })?;
+    Ok(InvoicePreview {
+        document_type: req.document_type,
+        place_of_supply: invoice.place_of_supply.to_string(),
+        igst_applicable: invoice.igst_applicable,
+        lines,
+        additional_charges,
+        tax_summary,
+        total_taxable_amount: invoice.total_taxable_amount,
+        total_tax_amount: invoice.total_tax_amount,
+        total_cess_amount,
+        total_additional_charges_amount: invoice.total_additional_charges_amount,
+        round_off: (round_off * minor_unit).round() / minor_unit,
+        total_payable_amount: invoice.total_payable_amount,
+    })
+}
+
+fn create_storage_file_key(tenant_id: Uuid, invoice_id: Uuid) -> String {
+    format!("{}-invoice-{}.pdf", tenant_id, invoice_id)
+}
+
+fn create_amended_storage_file_key(tenant_id: Uuid, invoice_id: Uuid, version: i32) -> String {
+    format!("{}-invoice-{}-v{}.pdf", tenant_id, invoice_id, version)
+}
+
+pub fn get_invoicing_service(
+    arc: Arc<Pool>,
+    tenant_service: Arc<dyn TenantService>,
+    currency_service: Arc<dyn CurrencyService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+    business_entity_service: Arc<dyn BusinessEntityService>,
+    invoice_template_service: Arc<dyn InvoiceTemplateService>,
+    storage_service: Arc<dyn StorageService>,
+    product_item_service: Arc<dyn ProductItemService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    background_job_service: Arc<dyn BackgroundJobService>,
+    einvoice_portal_client: Arc<dyn EInvoicePortalClient>,
+    email_client: Arc<dyn EmailClient>,
+) -> Arc<dyn InvoicingService> {
+    let invoicing_service_dao = get_invoicing_dao(arc);
+    let service = InvoicingServiceImpl {
+        dao: invoicing_service_dao,
+        tenant_service,
+        currency_service,
+        invoicing_series_service,
+        business_entity_service,
+        invoice_template_service,
+        storage_service,
+        product_item_service,
+        invoice_approval_service,
+        background_job_service,
+        einvoice_portal_client,
+        email_client,
+    };
+    Arc::new(service)
+}
+
+#[cfg(test)]
 mod tests {
     use chrono::Days;
+    use rstest::rstest;
     use speculoos::assert_that;
     use speculoos::prelude::VecAssertions;
 
@@ -371,8 +1476,78 @@ mod tests {
     use crate::invoicing::invoicing_request_models::tests::{
         a_create_invoice_line_request, a_create_invoice_request,
     };
-    use crate::invoicing::invoicing_request_models::PurchaseOrderDate;
-    use crate::invoicing::invoicing_service::InvoicingServiceImpl;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    use crate::accounting::currency::currency_service::MockCurrencyService;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::background_job::background_job_models::JobKind;
+    use crate::background_job::background_job_service::MockBackgroundJobService;
+    use crate::common_utils::email_client::MockEmailClient;
+    use crate::invoicing::einvoice_portal_client::MockEInvoicePortalClient;
+    use crate::invoicing::invoice_approval::invoice_approval_service::MockInvoiceApprovalService;
+    use crate::invoicing::invoice_jobs::EInvoiceJobPayload;
+    use crate::invoicing::invoice_template::invoice_template_service::MockInvoiceTemplateService;
+    use crate::invoicing::invoicing_dao::MockInvoicingDao;
+    use crate::invoicing::invoicing_dao_models::{
+        convert_to_invoice_db, AmendInvoiceDbStatus, InvoiceAmendmentStateDb, InvoiceRenderDetailDb,
+    };
+    use crate::invoicing::invoicing_domain_models::EInvoicePortalResponse;
+    use crate::invoicing::invoicing_request_models::{
+        AmendInvoiceRequest, CreateInvoiceRequest, CreateInvoicesInBulkRequest, ExportDetail,
+        InvoicePdfRequest, LutReference, PurchaseOrderDate, SupplyClassification,
+        MAX_BULK_INVOICES,
+    };
+    use crate::invoicing::invoicing_series::invoicing_series_service::MockInvoicingSeriesService;
+    use crate::invoicing::invoicing_service::{
+        amendment_blocked_status, create_invoice_preview, supply_classification, InvoiceMasters,
+        InvoicingService, InvoicingServiceError, InvoicingServiceImpl,
+    };
+    use crate::invoicing::place_of_supply::tests::a_place_of_supply;
+    use crate::invoicing::place_of_supply::SupplyType;
+    use crate::masters::business_entity_master::business_entity_models::{
+        BusinessEntityDto, BusinessEntityType, Email, SezClassification,
+    };
+    use crate::masters::business_entity_master::business_entity_service::MockBusinessEntityService;
+    use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+    use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+    use crate::masters::product_item_master::product_item_models::tests::{
+        a_product_item_response, SEED_PRODUCT_ITEM_ID,
+    };
+    use crate::masters::product_item_master::product_item_service::MockProductItemService;
+    use crate::storage::storage_service::MockStorageService;
+    use crate::tenant::tenant_models::tests::{a_tenant, SEED_TENANT_ID};
+    use crate::tenant::tenant_models::GstRegistrationType;
+    use crate::tenant::tenant_service::MockTenantService;
+
+    fn a_service(invoice_approval_service: MockInvoiceApprovalService) -> InvoicingServiceImpl {
+        InvoicingServiceImpl {
+            dao: Arc::new(MockInvoicingDao::new()),
+            tenant_service: Arc::new(MockTenantService::new()),
+            currency_service: Arc::new(MockCurrencyService::new()),
+            invoicing_series_service: Arc::new(MockInvoicingSeriesService::new()),
+            business_entity_service: Arc::new(MockBusinessEntityService::new()),
+            invoice_template_service: Arc::new(MockInvoiceTemplateService::new()),
+            storage_service: Arc::new(MockStorageService::new()),
+            product_item_service: Arc::new(MockProductItemService::new()),
+            invoice_approval_service: Arc::new(invoice_approval_service),
+            background_job_service: Arc::new(MockBackgroundJobService::new()),
+            einvoice_portal_client: Arc::new(MockEInvoicePortalClient::new()),
+            email_client: Arc::new(MockEmailClient::new()),
+        }
+    }
+
+    const INVOICE_DOC_JSON: &[u8] =
+        include_bytes!("../../../pdf_doc_generator/typst_templates/invoice/invoice_data.json");
+
+    fn a_pdf_request() -> InvoicePdfRequest {
+        InvoicePdfRequest {
+            tenant_id: *SEED_TENANT_ID,
+            invoice_id: Uuid::now_v7(),
+            invoice: serde_json::from_slice(INVOICE_DOC_JSON).unwrap(),
+        }
+    }
 
     #[tokio::test]
     async fn test_validate_order_date() {
@@ -416,4 +1591,603 @@ mod tests {
         assert_that!(errors[0])
             .is_equal_to("supplier id and shipped_to_customer_id cannot be same".to_string());
     }
+
+    fn an_export_detail(country_id: Uuid) -> ExportDetail {
+        serde_json::from_value(serde_json::json!({
+            "export_type": "under_lut",
+            "lut_reference": "AD290324000123X",
+            "country_id": country_id,
+            "exchange_rate": 83.12,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_export_detail() {
+        let mut req = a_create_invoice_request(Default::default());
+        req.export_detail = Some(an_export_detail(Uuid::now_v7()));
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_export_detail(&req, &mut errors);
+        assert_that!(errors).is_empty();
+        req.export_detail = Some(an_export_detail(*INDIA_COUNTRY_ID));
+        req.bill_ship_detail = None;
+        InvoicingServiceImpl::validate_export_detail(&req, &mut errors);
+        assert_that!(errors).is_equal_to(vec![
+            "export country cannot be india".to_string(),
+            "bill_ship_detail is mandatory for export invoice".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_draft_request_round_trip() {
+        //drafts are stored as json and deserialized again while issuing
+        let mut req = a_create_invoice_request(Default::default());
+        req.export_detail = Some(an_export_detail(Uuid::now_v7()));
+        let json = serde_json::to_value(&req).unwrap();
+        let parsed: CreateInvoiceRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_that!(serde_json::to_value(&parsed).unwrap()).is_equal_to(json);
+    }
+
+    #[tokio::test]
+    async fn test_create_invoices_in_bulk_rejects_empty_and_oversized_requests() {
+        let service = a_service(MockInvoiceApprovalService::new());
+        let empty = CreateInvoicesInBulkRequest { invoices: vec![] };
+        let res = service
+            .create_invoices_in_bulk(empty, Default::default(), Default::default())
+            .await;
+        assert!(matches!(res, Err(InvoicingServiceError::Validation(_))));
+        let oversized = CreateInvoicesInBulkRequest {
+            invoices: (0..=MAX_BULK_INVOICES)
+                .map(|_| a_create_invoice_request(Default::default()))
+                .collect(),
+        };
+        let res = service
+            .create_invoices_in_bulk(oversized, Default::default(), Default::default())
+            .await;
+        assert!(matches!(res, Err(InvoicingServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_invoices_in_bulk_rejected_when_approval_required() {
+        let mut approval = MockInvoiceApprovalService::new();
+        approval
+            .expect_is_approval_required()
+            .times(1)
+            .returning(|_| Ok(true));
+        let service = a_service(approval);
+        let req = CreateInvoicesInBulkRequest {
+            invoices: vec![
+                a_create_invoice_request(Default::default()),
+                a_create_invoice_request(Default::default()),
+            ],
+        };
+        let res = service
+            .create_invoices_in_bulk(req, Default::default(), Default::default())
+            .await;
+        assert!(matches!(res, Err(InvoicingServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_ids_shared_by_the_invoices_are_validated_once() {
+        let mut tenant_service = MockTenantService::new();
+        tenant_service
+            .expect_get_tenant_by_id()
+            .returning(|_| Ok(Some(Arc::new(a_tenant(Default::default())))));
+        let mut product_item_service = MockProductItemService::new();
+        product_item_service
+            .expect_get_products()
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        let mut business_entity_service = MockBusinessEntityService::new();
+        business_entity_service
+            .expect_get_business_entities_by_ids()
+            .times(1)
+            .returning(|_, _| Ok(vec![]));
+        let mut currency_service = MockCurrencyService::new();
+        currency_service
+            .expect_get_currency_entry()
+            .times(1)
+            .returning(|_, _| Ok(None));
+        let mut invoice_template_service = MockInvoiceTemplateService::new();
+        invoice_template_service
+            .expect_is_valid_template_id()
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let mut invoicing_series_service = MockInvoicingSeriesService::new();
+        invoicing_series_service
+            .expect_is_valid_invoicing_series_id()
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        let service = InvoicingServiceImpl {
+            tenant_service: Arc::new(tenant_service),
+            product_item_service: Arc::new(product_item_service),
+            business_entity_service: Arc::new(business_entity_service),
+            currency_service: Arc::new(currency_service),
+            invoice_template_service: Arc::new(invoice_template_service),
+            invoicing_series_service: Arc::new(invoicing_series_service),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        let reqs = [
+            a_create_invoice_request(Default::default()),
+            a_create_invoice_request(Default::default()),
+        ];
+        let failures = service
+            .validate_invoices(&reqs, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(failures).has_length(2);
+        for errors in failures {
+            assert!(errors.contains(&"currency id does not exists for this tenant id".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_validate_invoice_lines_reports_missing_products() {
+        let req = a_create_invoice_request(Default::default());
+        let masters = InvoiceMasters {
+            products: HashMap::new(),
+            business_entities: HashMap::new(),
+            currencies: HashMap::new(),
+            invoice_template_ids: HashSet::new(),
+            tax_invoice_series_ids: HashSet::new(),
+            registration_type: Default::default(),
+        };
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_invoice_lines(&req, &masters, &mut errors);
+        assert_that!(errors).has_length(req.invoice_lines.len());
+    }
+
+    fn an_entity_with_gstin(id: Uuid, gstin: &str) -> (Uuid, Arc<BusinessEntityDto>) {
+        let mut entity = BusinessEntityDto::default();
+        entity.business_entity.base_master_fields.id = id;
+        entity.business_entity.entity_type = BusinessEntityType::Other {
+            name: Default::default(),
+            email: None,
+            phone: Default::default(),
+            address_id: None,
+            gstin: Some(GstinNo::new(gstin).unwrap()),
+        };
+        (id, Arc::new(entity))
+    }
+
+    #[test]
+    fn test_place_of_supply_from_masters() {
+        let mut req = a_create_invoice_request(Default::default());
+        let bill_ship = req.bill_ship_detail.clone().unwrap();
+        let masters = InvoiceMasters {
+            products: HashMap::new(),
+            business_entities: HashMap::from([
+                an_entity_with_gstin(req.supplier_id, "05AABCA5291P1ZD"),
+                an_entity_with_gstin(bill_ship.billed_to_customer_id, "06MFNMS5291P1ZA"),
+            ]),
+            currencies: HashMap::new(),
+            invoice_template_ids: HashSet::new(),
+            tax_invoice_series_ids: HashSet::new(),
+            registration_type: Default::default(),
+        };
+        let pos = InvoicingServiceImpl::place_of_supply(&req, &masters).unwrap();
+        assert_that!(pos.state_code.as_str()).is_equal_to("06");
+        assert!(pos.igst_applicable());
+        req.bill_ship_detail = None;
+        let pos = InvoicingServiceImpl::place_of_supply(&req, &masters).unwrap();
+        assert_that!(pos.state_code.as_str()).is_equal_to("05");
+        assert!(!pos.igst_applicable());
+        req.export_detail = Some(an_export_detail(Uuid::now_v7()));
+        let pos = InvoicingServiceImpl::place_of_supply(&req, &masters).unwrap();
+        assert!(pos.is_export());
+    }
+
+    fn masters_with_sez_recipient(req: &CreateInvoiceRequest) -> InvoiceMasters {
+        let bill_ship = req.bill_ship_detail.clone().unwrap();
+        let (id, recipient) =
+            an_entity_with_gstin(bill_ship.billed_to_customer_id, "05MFNMS5291P1ZC");
+        let mut recipient = Arc::into_inner(recipient).unwrap();
+        recipient.business_entity.sez_classification = SezClassification::SezUnit;
+        InvoiceMasters {
+            products: HashMap::new(),
+            business_entities: HashMap::from([
+                an_entity_with_gstin(req.supplier_id, "05AABCA5291P1ZD"),
+                (id, Arc::new(recipient)),
+            ]),
+            currencies: HashMap::new(),
+            invoice_template_ids: HashSet::new(),
+            tax_invoice_series_ids: HashSet::new(),
+            registration_type: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_supply_to_sez_is_inter_state_and_zero_rated_under_lut() {
+        let mut req = a_create_invoice_request(Default::default());
+        let masters = masters_with_sez_recipient(&req);
+        let pos = InvoicingServiceImpl::place_of_supply(&req, &masters).unwrap();
+        assert!(pos.igst_applicable());
+        assert_eq!(
+            supply_classification(&req, &masters),
+            SupplyClassification::SezWithPayment
+        );
+        req.sez_lut_reference = Some(LutReference::new("ad290324000123x").unwrap());
+        let classification = supply_classification(&req, &masters);
+        assert_eq!(classification, SupplyClassification::SezWithoutPayment);
+        assert!(classification.zero_rated());
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_supply_classification(&req, &masters, &mut errors);
+        assert_that!(errors).is_empty();
+    }
+
+    #[test]
+    fn test_validate_supply_classification() {
+        let mut req = a_create_invoice_request(Default::default());
+        let bill_ship = req.bill_ship_detail.clone().unwrap();
+        let masters = InvoiceMasters {
+            products: HashMap::new(),
+            business_entities: HashMap::from([
+                an_entity_with_gstin(req.supplier_id, "05AABCA5291P1ZD"),
+                an_entity_with_gstin(bill_ship.billed_to_customer_id, "06MFNMS5291P1ZA"),
+            ]),
+            currencies: HashMap::new(),
+            invoice_template_ids: HashSet::new(),
+            tax_invoice_series_ids: HashSet::new(),
+            registration_type: Default::default(),
+        };
+        req.sez_lut_reference = Some(LutReference::new("ad290324000123x").unwrap());
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_supply_classification(&req, &masters, &mut errors);
+        assert_that!(errors).has_length(1);
+        req.sez_lut_reference = None;
+        req.deemed_export = true;
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_supply_classification(&req, &masters, &mut errors);
+        assert_that!(errors).is_empty();
+        assert_eq!(
+            supply_classification(&req, &masters),
+            SupplyClassification::DeemedExport
+        );
+        let sez_masters = masters_with_sez_recipient(&req);
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_supply_classification(&req, &sez_masters, &mut errors);
+        assert_that!(errors).has_length(1);
+    }
+
+    #[test]
+    fn test_validate_email_to_customer() {
+        let mut req = a_create_invoice_request(Default::default());
+        req.email_to_customer = true;
+        let bill_ship = req.bill_ship_detail.clone().unwrap();
+        let mut masters = InvoiceMasters {
+            products: HashMap::new(),
+            business_entities: HashMap::from([an_entity_with_gstin(
+                bill_ship.billed_to_customer_id,
+                "06MFNMS5291P1ZA",
+            )]),
+            currencies: HashMap::new(),
+            invoice_template_ids: HashSet::new(),
+            tax_invoice_series_ids: HashSet::new(),
+            registration_type: Default::default(),
+        };
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_email_to_customer(&req, &masters, &mut errors);
+        assert_that!(errors).has_length(1);
+        let (id, customer) =
+            an_entity_with_gstin(bill_ship.billed_to_customer_id, "06MFNMS5291P1ZA");
+        let mut customer = Arc::into_inner(customer).unwrap();
+        if let BusinessEntityType::Other { email, .. } = &mut customer.business_entity.entity_type {
+            *email = Some(Email::new("accounts@customer.in").unwrap());
+        }
+        masters.business_entities.insert(id, Arc::new(customer));
+        let mut errors: Vec<String> = vec![];
+        InvoicingServiceImpl::validate_email_to_customer(&req, &masters, &mut errors);
+        assert_that!(errors).is_empty();
+    }
+
+    fn an_amend_invoice_request() -> AmendInvoiceRequest {
+        AmendInvoiceRequest {
+            entity_version_id: 2,
+            amendment_reason: "rate corrected".to_string(),
+            invoice: a_create_invoice_request(Default::default()),
+        }
+    }
+
+    fn an_amendable_state(req: &AmendInvoiceRequest) -> InvoiceAmendmentStateDb {
+        InvoiceAmendmentStateDb {
+            invoice_number: Some("INV-1".to_string()),
+            invoice_status: "issued".to_string(),
+            entity_version_id: req.entity_version_id,
+            e_invoicing_applicable: false,
+            supplier_id: req.invoice.supplier_id,
+            invoicing_series_mst_id: req.invoice.invoicing_series_mst_id,
+            invoice_date_ms: 1706534012000,
+            financial_year: 2023,
+            amount_settled: 0.0,
+            gstr1_filed: false,
+        }
+    }
+
+    #[test]
+    fn test_amendment_blocked_status() {
+        let req = an_amend_invoice_request();
+        let state = an_amendable_state(&req);
+        assert_that!(amendment_blocked_status(&state, &req)).is_equal_to(None);
+        let cases = [
+            (
+                InvoiceAmendmentStateDb {
+                    invoice_status: "draft".to_string(),
+                    ..state.clone()
+                },
+                AmendInvoiceDbStatus::NotIssued,
+            ),
+            (
+                InvoiceAmendmentStateDb {
+                    e_invoicing_applicable: true,
+                    ..state.clone()
+                },
+                AmendInvoiceDbStatus::Einvoiced,
+            ),
+            (
+                InvoiceAmendmentStateDb {
+                    supplier_id: Uuid::now_v7(),
+                    ..state.clone()
+                },
+                AmendInvoiceDbStatus::SupplierChanged,
+            ),
+            (
+                InvoiceAmendmentStateDb {
+                    entity_version_id: 3,
+                    ..state.clone()
+                },
+                AmendInvoiceDbStatus::VersionConflict,
+            ),
+            (
+                InvoiceAmendmentStateDb {
+                    gstr1_filed: true,
+                    ..state.clone()
+                },
+                AmendInvoiceDbStatus::Gstr1Filed,
+            ),
+        ];
+        for (state, status) in cases {
+            assert_that!(amendment_blocked_status(&state, &req)).is_equal_to(Some(status));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_amend_invoice_rejects_stale_version() {
+        let req = an_amend_invoice_request();
+        let state = InvoiceAmendmentStateDb {
+            entity_version_id: 3,
+            ..an_amendable_state(&req)
+        };
+        let mut dao = MockInvoicingDao::new();
+        dao.expect_get_invoice_amendment_state()
+            .times(1)
+            .returning(move |_, _| Ok(Some(state.clone())));
+        dao.expect_amend_invoice().never();
+        let service = InvoicingServiceImpl {
+            dao: Arc::new(dao),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        let res = service
+            .amend_invoice(Uuid::now_v7(), req, Default::default(), Default::default())
+            .await;
+        assert!(matches!(
+            res,
+            Err(InvoicingServiceError::VersionConflict {
+                current_version: 3,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_einvoice_pdf_is_not_rendered_before_irn() {
+        let mut dao = MockInvoicingDao::new();
+        dao.expect_is_invoice_pdf_created()
+            .returning(|_, _| Ok(false));
+        dao.expect_get_invoice_render_detail().returning(|_, _| {
+            Ok(Some(InvoiceRenderDetailDb {
+                supplier_id: Uuid::now_v7(),
+                invoice_template_id: Uuid::now_v7(),
+                einvoice_required: true,
+                einvoice: None,
+            }))
+        });
+        dao.expect_persist_invoice_pdf_dtl().never();
+        let mut storage_service = MockStorageService::new();
+        storage_service.expect_upload_object().never();
+        let (einvoice_portal_client, _) = a_configured_client(true);
+        let service = InvoicingServiceImpl {
+            dao: Arc::new(dao),
+            storage_service: Arc::new(storage_service),
+            einvoice_portal_client: Arc::new(einvoice_portal_client),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        let res = service.create_invoice_pdf(a_pdf_request()).await;
+        assert!(matches!(res, Err(InvoicingServiceError::Validation(_))));
+    }
+
+    ///background job service recording the kinds of the jobs enqueued
+    fn a_job_service(enqueued: Arc<Mutex<Vec<JobKind>>>) -> MockBackgroundJobService {
+        let mut job_service = MockBackgroundJobService::new();
+        job_service.expect_enqueue().returning(move |_, req| {
+            enqueued.lock().unwrap().push(req.kind);
+            Ok(Uuid::now_v7())
+        });
+        job_service
+    }
+
+    fn a_configured_client(configured: bool) -> (MockEInvoicePortalClient, MockEmailClient) {
+        let mut einvoice_portal_client = MockEInvoicePortalClient::new();
+        einvoice_portal_client
+            .expect_is_configured()
+            .return_const(configured);
+        let mut email_client = MockEmailClient::new();
+        email_client.expect_is_configured().return_const(configured);
+        (einvoice_portal_client, email_client)
+    }
+
+    #[rstest]
+    #[case(false, true, vec![JobKind::InvoicePdf, JobKind::InvoiceEmail])]
+    #[case(false, false, vec![JobKind::InvoicePdf])]
+    #[case(true, true, vec![JobKind::EInvoice])]
+    #[case(true, false, vec![JobKind::InvoicePdf])]
+    #[tokio::test]
+    async fn test_enqueue_document_jobs(
+        #[case] einvoice_required: bool,
+        #[case] clients_configured: bool,
+        #[case] expected: Vec<JobKind>,
+    ) {
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        let req = a_create_invoice_request(Default::default())
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let mut invoice =
+            convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        invoice.e_invoicing_applicable = einvoice_required;
+        invoice.b2b_invoice = einvoice_required;
+        let enqueued = Arc::new(Mutex::new(vec![]));
+        let (einvoice_portal_client, email_client) = a_configured_client(clients_configured);
+        let service = InvoicingServiceImpl {
+            background_job_service: Arc::new(a_job_service(enqueued.clone())),
+            einvoice_portal_client: Arc::new(einvoice_portal_client),
+            email_client: Arc::new(email_client),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        service
+            .enqueue_document_jobs(&a_pdf_request(), &invoice, Some("accounts@customer.in"))
+            .await
+            .unwrap();
+        assert_eq!(*enqueued.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_generate_einvoice_stores_irn_before_enqueueing_pdf() {
+        let pdf_request = a_pdf_request();
+        let invoice_id = pdf_request.invoice_id;
+        let response = EInvoicePortalResponse {
+            irn: "a5c12dca80e743321740b001fd70953e8738d109865d28ba4013750f2046f229".to_string(),
+            ack_no: "112010036563310".to_string(),
+            ack_date_ms: 1_700_000_000_000,
+            signed_qr_code: "signed.qr.code".to_string(),
+        };
+        let (mut client, email_client) = a_configured_client(true);
+        let returned = response.clone();
+        client
+            .expect_generate_irn()
+            .times(1)
+            .returning(move |_, _| Ok(returned.clone()));
+        let enqueued = Arc::new(Mutex::new(vec![]));
+        let mut dao = MockInvoicingDao::new();
+        let enqueued_before_persist = enqueued.clone();
+        dao.expect_persist_einvoice_detail()
+            .withf(move |_, id, detail| *id == invoice_id && *detail == response)
+            .times(1)
+            .returning(move |_, _, _| {
+                assert!(enqueued_before_persist.lock().unwrap().is_empty());
+                Ok(())
+            });
+        let service = InvoicingServiceImpl {
+            dao: Arc::new(dao),
+            background_job_service: Arc::new(a_job_service(enqueued.clone())),
+            einvoice_portal_client: Arc::new(client),
+            email_client: Arc::new(email_client),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        service
+            .generate_einvoice(EInvoiceJobPayload {
+                pdf_request,
+                email_to: Some("accounts@customer.in".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            *enqueued.lock().unwrap(),
+            vec![JobKind::InvoicePdf, JobKind::InvoiceEmail]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_irn_generation_enqueues_no_pdf() {
+        let (mut client, email_client) = a_configured_client(true);
+        client
+            .expect_generate_irn()
+            .returning(|_, _| Err(anyhow::anyhow!("irp is down")));
+        let mut dao = MockInvoicingDao::new();
+        dao.expect_persist_einvoice_detail().never();
+        let mut job_service = MockBackgroundJobService::new();
+        job_service.expect_enqueue().never();
+        let service = InvoicingServiceImpl {
+            dao: Arc::new(dao),
+            background_job_service: Arc::new(job_service),
+            einvoice_portal_client: Arc::new(client),
+            email_client: Arc::new(email_client),
+            ..a_service(MockInvoiceApprovalService::new())
+        };
+        let res = service
+            .generate_einvoice(EInvoiceJobPayload {
+                pdf_request: a_pdf_request(),
+                email_to: None,
+            })
+            .await;
+        assert!(matches!(res, Err(InvoicingServiceError::Other(_))));
+    }
+
+    #[tokio::test]
+    async fn test_amend_invoice_requires_reason() {
+        let service = a_service(MockInvoiceApprovalService::new());
+        let mut req = an_amend_invoice_request();
+        req.amendment_reason = "  ".to_string();
+        let res = service
+            .amend_invoice(Uuid::now_v7(), req, Default::default(), Default::default())
+            .await;
+        assert!(matches!(res, Err(InvoicingServiceError::Validation(_))));
+    }
+
+    #[test]
+    fn test_invoice_preview_amounts_add_up_to_payable() {
+        let mut product = a_product_item_response(Default::default());
+        product.base_master_fields.id = *SEED_PRODUCT_ITEM_ID;
+        let req = a_create_invoice_request(Default::default())
+            .to_create_invoice_with_all_details_included(
+                vec![Arc::new(product)],
+                SupplyClassification::Regular,
+                GstRegistrationType::Regular,
+            )
+            .unwrap();
+        let pos = a_place_of_supply(SupplyType::IntraState);
+        let invoice = convert_to_invoice_db(&req, 2, &pos, *SEED_USER_ID, *SEED_TENANT_ID).unwrap();
+        let preview = create_invoice_preview(&req, &invoice, 2).unwrap();
+        assert_eq!(preview.lines.len(), 1);
+        assert_eq!(preview.additional_charges.len(), 1);
+        assert_eq!(preview.lines[0].line_no, 1);
+        assert_eq!(preview.additional_charges[0].line_no, 1);
+        assert_eq!(
+            preview.round_off,
+            (preview.round_off * 100.0).round() / 100.0
+        );
+        assert_eq!(preview.lines[0].product_item_id, *SEED_PRODUCT_ITEM_ID);
+        let lines_total: f64 = preview.lines.iter().map(|a| a.line_net_total).sum();
+        let charges_total: f64 = preview
+            .additional_charges
+            .iter()
+            .map(|a| a.rate + a.tax_amount)
+            .sum();
+        assert!(
+            (lines_total + charges_total + preview.round_off - preview.total_payable_amount).abs()
+                < 1e-6
+        );
+        let summary_tax: f64 = preview
+            .tax_summary
+            .cgst_lines
+            .iter()
+            .chain(preview.tax_summary.sgst_lines.iter())
+            .map(|a| a.tax_amount)
+            .sum();
+        assert!((summary_tax - preview.total_tax_amount).abs() < 1e-6);
+    }
 }
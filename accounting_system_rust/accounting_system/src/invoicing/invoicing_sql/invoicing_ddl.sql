@@ -1,6 +1,12 @@
 CREATE TYPE cess_calculation_strategy AS ENUM ('percentage_of_assessable_value','amount_per_unit',
     'percentage_of_assessable_value_and_amount_per_unit','max_of_percentage_of_assessable_value_and_amount_per_unit',
     'percentage_of_retail_sale_price');
+CREATE TYPE invoice_payment_status AS ENUM ('unpaid','partially_paid','paid');
+CREATE TYPE invoice_status AS ENUM ('draft','pending_approval','approved','issued');
+CREATE TYPE invoice_export_type AS ENUM ('with_payment_of_igst','under_lut');
+CREATE TYPE invoice_supply_classification AS ENUM ('regular','sez_with_payment','sez_without_payment','deemed_export',
+    'export_with_payment','export_without_payment');
+CREATE TYPE invoice_document_type AS ENUM ('tax_invoice','bill_of_supply');
 -- user before generating an invoice in any case will know who is the supplier entity and will also know the customer
 -- this can store invoice details, credit note details, delivery challan details
 
@@ -14,7 +20,7 @@ create table invoice
     remarks                         varchar(70),
     invoicing_mst_id                uuid references invoicing_series_mst (id) not null,
     financial_year                  smallint                                  not null,
-    invoice_number                  varchar(20)                               not null,
+    invoice_number                  varchar(20),--assigned from the invoicing series only when the invoice is issued
     currency_id                     uuid references currency_master (id)      not null,
     service_invoice                 bool                                      not null,
     invoice_date_ms                 bigint                                    not null,
@@ -37,6 +43,22 @@ create table invoice
     payment_term_id                 uuid references payment_term,
     invoice_remarks                 varchar(100),
     ecommerce_gstin                 varchar(16),
+    amount_received                 double precision default 0              not null,--sum of receipt allocations
+    discount_allowed                double precision default 0              not null,--early payment discount allowed on settlement
+    payment_status                  invoice_payment_status default 'unpaid'  not null,
+    invoice_status                  invoice_status default 'issued'          not null,
+    draft_request                   jsonb,--create request of a draft, used to build the invoice again on issue
+    export_type                     invoice_export_type,--null for supplies other than exports
+    lut_reference                   varchar(20),--only for exports and sez supplies under lut/bond
+    port_code                       varchar(6),
+    shipping_bill_number            varchar(7),
+    shipping_bill_date_ms           bigint,
+    export_country_id               uuid references country_master (id),
+    exchange_rate                   double precision,--inr value of one unit of the invoice currency
+    total_payable_amount_inr        double precision,
+    place_of_supply                 varchar(2),--gst state code, 96 for exports
+    supply_classification           invoice_supply_classification default 'regular' not null,
+    document_type                   invoice_document_type default 'tax_invoice' not null,
     created_by                      uuid references app_user (id)             not null,
     updated_by                      uuid references app_user (id),
     created_at                      bigint  default extract(epoch from now()) * 1000000,
@@ -77,3 +99,35 @@ create table invoice_line
     updated_at                 bigint                                   default extract(epoch from now()) * 1000000
 );
 
+
+
+-- content of an issued invoice as it was before each amendment, the current content stays in invoice and invoice_line
+create table invoice_version
+(
+    id                          uuid primary key,
+    tenant_id                   uuid references tenant (id)   not null,
+    invoice_id                  uuid references invoice (id)  not null,
+    entity_version_id           integer                       not null,--version of the invoice which was amended
+    invoice_snapshot            jsonb                         not null,
+    lines_snapshot              jsonb                         not null,
+    additional_charges_snapshot jsonb                         not null,
+    amendment_reason            varchar(100)                  not null,
+    amended_by                  uuid references app_user (id) not null,
+    created_at                  bigint default extract(epoch from now()) * 1000000,
+    unique (invoice_id, entity_version_id)
+);
+
+
+-- response of the invoice registration portal, an e-invoice pdf is rendered only once the irn is stored
+create table invoice_einvoice_detail
+(
+    id             uuid primary key,
+    tenant_id      uuid references tenant (id)  not null,
+    invoice_id     uuid references invoice (id) not null,
+    irn            varchar(64)                  not null,
+    ack_no         varchar(20)                  not null,
+    ack_date_ms    bigint                       not null,
+    signed_qr_code text                         not null,
+    created_at     bigint default extract(epoch from now()) * 1000000,
+    unique (tenant_id, invoice_id)
+);
@@ -31,6 +31,25 @@ create type create_invoice_line_request as
 );
 
 
+create type create_invoice_export_detail_request as
+(
+    export_type              text,
+    lut_reference            text,
+    port_code                text,
+    shipping_bill_number     text,
+    shipping_bill_date_ms    bigint,
+    country_id               uuid,
+    exchange_rate            double precision,
+    total_payable_amount_inr double precision
+);
+
+create type create_invoice_transfer_request as
+(
+    debit_account_id  uuid,
+    credit_account_id uuid,
+    amount            double precision
+);
+
 create type create_invoice_request as
 (
     idempotence_key                 uuid,
@@ -60,9 +79,98 @@ create type create_invoice_request as
     created_by                      uuid,
     igst_applicable                 bool,
     invoice_remarks                 text,
-    ecommerce_gstin                 text
+    ecommerce_gstin                 text,
+    export_detail                   create_invoice_export_detail_request,
+    place_of_supply                 text,
+    supply_classification           text,
+    sez_lut_reference               text,
+    document_type                   text,
+    transfers                       create_invoice_transfer_request[],
+    transfer_code                   smallint
 );
 
+--amount is in minor units of the currency
+create or replace procedure post_invoice_ledger_transfer(_tenant_id uuid, _invoice_id uuid, _debit_account_id uuid,
+                                                         _credit_account_id uuid, _code smallint, _amount bigint,
+                                                         _created_at bigint) as
+$$
+DECLARE
+    txn       transfer;
+    result    jsonb;
+    ledger_id uuid;
+BEGIN
+    select ledger_master_id
+    from user_account
+    where id = _debit_account_id
+      and tenant_id = _tenant_id
+    into ledger_id;
+    txn := row (uuid_generate_v7(), _tenant_id, _invoice_id, _invoice_id, _debit_account_id, _credit_account_id,
+        null, ledger_id, _code, _amount, 'invoice', 1, _created_at)::transfer;
+    result := json_build_object('txn_id', txn.id, 'committed', true, 'reason', '[]'::jsonb);
+    call create_ledger_transfer(txn, result);
+    if (result -> 'committed')::boolean = false then
+        raise exception 'ledger transfer for invoice % failed %', _invoice_id, result -> 'reason';
+    end if;
+end;
+$$ language plpgsql;
+
+--transfers are computed by the service and posted as is, amounts are converted to minor units as per the
+--currency scale
+create or replace procedure post_invoice_ledger_transfers(_tenant_id uuid, _invoice_id uuid, _currency_id uuid,
+                                                          _transfers create_invoice_transfer_request[],
+                                                          _code smallint, _created_at bigint) as
+$$
+DECLARE
+    trf          create_invoice_transfer_request;
+    curr_scale   smallint;
+    minor_amount bigint;
+BEGIN
+    if _transfers is null then
+        return;
+    end if;
+    select scale from currency_master where id = _currency_id into curr_scale;
+    foreach trf in array _transfers
+        loop
+            minor_amount := round((trf.amount * power(10, coalesce(curr_scale, 0)))::numeric)::bigint;
+            if minor_amount != 0 then
+                call post_invoice_ledger_transfer(_tenant_id, _invoice_id, trf.debit_account_id,
+                                                  trf.credit_account_id, _code, minor_amount, _created_at);
+            end if;
+        end loop;
+end;
+$$ language plpgsql;
+
+--posts the opposite of whatever is still posted for the invoice between each pair of accounts, so that its
+--postings net to zero before the amended transfers are posted
+create or replace procedure reverse_invoice_ledger_transfers(_tenant_id uuid, _invoice_id uuid, _code smallint,
+                                                             _created_at bigint) as
+$$
+DECLARE
+    posted record;
+BEGIN
+    for posted in select t.debit_account_id, t.credit_account_id, sum(t.amount)::bigint as amount
+                  from (select debit_account_id, credit_account_id, amount
+                        from transfer
+                        where tenant_id = _tenant_id
+                          and grouping_id = _invoice_id
+                          and code = _code
+                          and transfer_type = 1
+                        union all
+                        select credit_account_id, debit_account_id, -amount
+                        from transfer
+                        where tenant_id = _tenant_id
+                          and grouping_id = _invoice_id
+                          and code = _code
+                          and transfer_type = 1) t
+                  group by t.debit_account_id, t.credit_account_id
+                  having sum(t.amount) > 0
+        loop
+            call post_invoice_ledger_transfer(_tenant_id, _invoice_id, posted.credit_account_id,
+                                              posted.debit_account_id, _code, posted.amount, _created_at);
+        end loop;
+end;
+$$ language plpgsql;
+
 create or replace function get_invoice_number(invoice_number_prefix text, invoice_counter integer,
                                               zero_padding bool) returns text as
 $$
@@ -114,31 +222,52 @@ BEGIN
 END
 $$ language plpgsql;
 
-create or replace function create_invoice_table_entry(req create_invoice_request, _payment_term_id uuid) returns jsonb as
+-- a draft (_draft_request not null) is created without consuming a number from the invoicing series
+create or replace function create_invoice_table_entry(req create_invoice_request, _payment_term_id uuid,
+                                                      _draft_request jsonb) returns jsonb as
 $$
 DECLARE
-    inv_number text;
-    inv_id     uuid;
+    inv_number      text;
+    inv_id          uuid;
+    _status         invoice_status := 'draft';
+    _approval_status smallint      := 0;
 BEGIN
     select uuid_generate_v7() into inv_id;
-    select create_invoice_number(req.invoicing_series_mst_id,
-                                 req.financial_year, req.tenant_id, req.created_by)
-    into inv_number;
+    if _draft_request is null then
+        select create_invoice_number(req.invoicing_series_mst_id,
+                                     req.financial_year, req.tenant_id, req.created_by)
+        into inv_number;
+        _status := 'issued';
+        _approval_status := 1;
+    end if;
     insert into invoice (id, entity_version_id, tenant_id, active, approval_status, remarks, invoicing_mst_id,
                          financial_year, invoice_number, currency_id, service_invoice, invoice_date_ms,
                          e_invoicing_applicable, supplier_business_entity, dispatch_from_business_entity, b2b_invoice,
                          billed_to_business_entity, shipped_to_business_entity, purchase_order_number,
                          einvoice_json_s3_id, total_taxable_amount,
                          total_tax_amount, total_additional_charges_amount, round_off, total_payable_amount,
-                         invoice_pdf_s3_id, invoice_template_id, payment_term_id, invoice_remarks, ecommerce_gstin,
-                         created_by, updated_by, created_at, updated_at)
-    values (inv_id, 0, req.tenant_id, true, 1, null, req.invoicing_series_mst_id, req.financial_year, inv_number,
+                         igst_applicable, invoice_pdf_s3_id, invoice_template_id, payment_term_id, invoice_remarks,
+                         ecommerce_gstin, invoice_status, draft_request, export_type, lut_reference, port_code,
+                         shipping_bill_number, shipping_bill_date_ms, export_country_id, exchange_rate,
+                         total_payable_amount_inr, place_of_supply, supply_classification, document_type,
+                         created_by, updated_by,
+                         created_at,
+                         updated_at)
+    values (inv_id, 0, req.tenant_id, true, _approval_status, null, req.invoicing_series_mst_id, req.financial_year,
+            inv_number,
             req.currency_id, req.service_invoice, req.invoice_date_ms, req.e_invoicing_applicable, req.supplier_id,
             req.dispatch_from_id, req.b2b_invoice, req.billed_to_customer_id,
             req.shipped_to_customer_id, req.order_number, null,
             req.total_taxable_amount, req.total_tax_amount, req.total_additional_charges_amount, req.round_off,
-            req.total_payable_amount, null, req.invoice_template_id, _payment_term_id, req.invoice_remarks,
-            req.ecommerce_gstin, req.created_by, req.created_by,
+            req.total_payable_amount, req.igst_applicable, null, req.invoice_template_id, _payment_term_id,
+            req.invoice_remarks,
+            req.ecommerce_gstin, _status, _draft_request, (req.export_detail).export_type::invoice_export_type,
+            coalesce((req.export_detail).lut_reference, req.sez_lut_reference), (req.export_detail).port_code,
+            (req.export_detail).shipping_bill_number, (req.export_detail).shipping_bill_date_ms,
+            (req.export_detail).country_id, (req.export_detail).exchange_rate,
+            (req.export_detail).total_payable_amount_inr, req.place_of_supply,
+            req.supply_classification::invoice_supply_classification,
+            req.document_type::invoice_document_type, req.created_by, req.created_by,
             default, default);
     return jsonb_build_object('invoice_number', inv_number, 'invoice_id', inv_id);
 END
@@ -181,31 +310,44 @@ $$ language plpgsql;
 
 
 
-create or replace function create_invoice(req create_invoice_request) returns jsonb as
+create or replace function create_invoice_entries(req create_invoice_request, _draft_request jsonb) returns jsonb as
 $$
 DECLARE
-    resp            jsonb;
     invoice_id      uuid;
     invoice_id_num  jsonb;
-    impacted_rows   int;
     payment_term_id uuid;
     payment_terms   create_payment_terms_request := req.payment_terms;
+BEGIN
+    if payment_terms is not null then
+        select get_or_create_payment_term(payment_terms.due_days, payment_terms.discount_days,
+                                          payment_terms.discount_percent, req.tenant_id,
+                                          req.created_by)
+        into payment_term_id;
+    end if;
+    select create_invoice_table_entry(req, payment_term_id, _draft_request) into invoice_id_num;
+    select invoice_id_num ->> 'invoice_id' into invoice_id;
+    call persist_invoice_lines(req, invoice_id);
+    call persist_additional_charge(req.additional_charges, invoice_id, req.tenant_id, req.created_by);
+    return invoice_id_num;
+end;
+$$ language plpgsql;
+
+
+create or replace function create_invoice(req create_invoice_request) returns jsonb as
+$$
+DECLARE
+    resp           jsonb;
+    invoice_id_num jsonb;
+    impacted_rows  int;
 BEGIN
     insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
     values (req.idempotence_key, 'create_invoice', null, default, default)
     on conflict do nothing;
     get diagnostics impacted_rows= row_count;
     if impacted_rows != 0 then
-        if payment_terms is not null then
-            select get_or_create_payment_term(payment_terms.due_days, payment_terms.discount_days,
-                                              payment_terms.discount_percent, req.tenant_id,
-                                              req.created_by)
-            into payment_term_id;
-        end if;
-        select create_invoice_table_entry(req, payment_term_id) into invoice_id_num;
-        select invoice_id_num ->> 'invoice_id' into invoice_id;
-        call persist_invoice_lines(req, invoice_id);
-        call persist_additional_charge(req.additional_charges, invoice_id, req.tenant_id, req.created_by);
+        select create_invoice_entries(req, null) into invoice_id_num;
+        call post_invoice_ledger_transfers(req.tenant_id, (invoice_id_num ->> 'invoice_id')::uuid, req.currency_id,
+                                           req.transfers, req.transfer_code, req.invoice_date_ms * 1000);
         update idempotence_store
         set response=invoice_id_num
         where idempotence_key = req.idempotence_key
@@ -222,3 +364,281 @@ BEGIN
 end;
 
 $$ language plpgsql;
+
+
+create or replace function create_draft_invoice(req create_invoice_request, _draft_request jsonb) returns jsonb as
+$$
+DECLARE
+    resp           jsonb;
+    invoice_id_num jsonb;
+    impacted_rows  int;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_draft_invoice', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        select create_invoice_entries(req, _draft_request) into invoice_id_num;
+        call record_invoice_status_transition(req.tenant_id, (invoice_id_num ->> 'invoice_id')::uuid, null,
+                                              'draft', null, req.created_by);
+        update idempotence_store
+        set response=invoice_id_num
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_draft_invoice';
+        return invoice_id_num;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_draft_invoice'
+        into resp;
+        return resp;
+    end if;
+end;
+
+$$ language plpgsql;
+
+
+-- replaces the content of a draft, returns false if the invoice is not a draft anymore
+create or replace function update_draft_invoice(_invoice_id uuid, req create_invoice_request,
+                                                _draft_request jsonb) returns bool as
+$$
+DECLARE
+    _payment_term_id uuid;
+    payment_terms    create_payment_terms_request := req.payment_terms;
+BEGIN
+    perform 1
+    from invoice
+    where id = _invoice_id
+      and tenant_id = req.tenant_id
+      and invoice_status = 'draft'
+        for update;
+    if not found then
+        return false;
+    end if;
+    if payment_terms is not null then
+        select get_or_create_payment_term(payment_terms.due_days, payment_terms.discount_days,
+                                          payment_terms.discount_percent, req.tenant_id,
+                                          req.created_by)
+        into _payment_term_id;
+    end if;
+    update invoice
+    set entity_version_id=entity_version_id + 1,
+        invoicing_mst_id=req.invoicing_series_mst_id,
+        financial_year=req.financial_year,
+        currency_id=req.currency_id,
+        service_invoice=req.service_invoice,
+        invoice_date_ms=req.invoice_date_ms,
+        e_invoicing_applicable=req.e_invoicing_applicable,
+        supplier_business_entity=req.supplier_id,
+        dispatch_from_business_entity=req.dispatch_from_id,
+        b2b_invoice=req.b2b_invoice,
+        billed_to_business_entity=req.billed_to_customer_id,
+        shipped_to_business_entity=req.shipped_to_customer_id,
+        purchase_order_number=req.order_number,
+        total_taxable_amount=req.total_taxable_amount,
+        total_tax_amount=req.total_tax_amount,
+        total_additional_charges_amount=req.total_additional_charges_amount,
+        round_off=req.round_off,
+        total_payable_amount=req.total_payable_amount,
+        igst_applicable=req.igst_applicable,
+        invoice_template_id=req.invoice_template_id,
+        payment_term_id=_payment_term_id,
+        invoice_remarks=req.invoice_remarks,
+        ecommerce_gstin=req.ecommerce_gstin,
+        draft_request=_draft_request,
+        export_type=(req.export_detail).export_type::invoice_export_type,
+        lut_reference=coalesce((req.export_detail).lut_reference, req.sez_lut_reference),
+        port_code=(req.export_detail).port_code,
+        shipping_bill_number=(req.export_detail).shipping_bill_number,
+        shipping_bill_date_ms=(req.export_detail).shipping_bill_date_ms,
+        export_country_id=(req.export_detail).country_id,
+        exchange_rate=(req.export_detail).exchange_rate,
+        total_payable_amount_inr=(req.export_detail).total_payable_amount_inr,
+        place_of_supply=req.place_of_supply,
+        supply_classification=req.supply_classification::invoice_supply_classification,
+        document_type=req.document_type::invoice_document_type,
+        updated_by=req.created_by,
+        updated_at=extract(epoch from now()) * 1000000
+    where id = _invoice_id
+      and tenant_id = req.tenant_id;
+    delete from invoice_line where invoice_table_id = _invoice_id and tenant_id = req.tenant_id;
+    delete from additional_charge where invoice_table_id = _invoice_id and tenant_id = req.tenant_id;
+    call persist_invoice_lines(req, _invoice_id);
+    call persist_additional_charge(req.additional_charges, _invoice_id, req.tenant_id, req.created_by);
+    return true;
+end;
+$$ language plpgsql;
+
+
+-- assigns the number from the invoicing series to an approved invoice, dates it on the day of issue and posts it
+-- to the ledger, returns null if the invoice does not exist
+create or replace function issue_invoice(_tenant_id uuid, _invoice_id uuid, _invoice_date_ms bigint,
+                                         _financial_year smallint, _issued_by uuid,
+                                         _transfers create_invoice_transfer_request[],
+                                         _transfer_code smallint) returns text as
+$$
+DECLARE
+    inv        invoice;
+    inv_number text;
+BEGIN
+    select * from invoice where id = _invoice_id and tenant_id = _tenant_id for update into inv;
+    if inv is null then
+        return null;
+    end if;
+    if inv.invoice_status = 'issued' then
+        return inv.invoice_number;
+    end if;
+    if inv.invoice_status != 'approved' then
+        raise exception 'invoice % is not approved', _invoice_id;
+    end if;
+    select create_invoice_number(inv.invoicing_mst_id, _financial_year, _tenant_id, _issued_by)
+    into inv_number;
+    update invoice
+    set entity_version_id=entity_version_id + 1,
+        invoice_number=inv_number,
+        invoice_date_ms=_invoice_date_ms,
+        financial_year=_financial_year,
+        invoice_status='issued',
+        approval_status=1,
+        updated_by=_issued_by,
+        updated_at=extract(epoch from now()) * 1000000
+    where id = _invoice_id
+      and tenant_id = _tenant_id;
+    call record_invoice_status_transition(_tenant_id, _invoice_id, 'approved', 'issued', null, _issued_by);
+    call post_invoice_ledger_transfers(_tenant_id, _invoice_id, inv.currency_id, _transfers, _transfer_code,
+                                       _invoice_date_ms * 1000);
+    return inv_number;
+end;
+$$ language plpgsql;
+
+
+-- amends an issued invoice in place after keeping its current content in invoice_version. number, date, series and
+-- supplier of the invoice stay as they are. the checks are repeated here under the row lock so that concurrent
+-- amendments, receipts and gstr-1 filing cannot slip in between the check in the service and the update
+create or replace function amend_invoice(_invoice_id uuid, _expected_version integer, req create_invoice_request,
+                                         _amendment_reason text) returns jsonb as
+$$
+DECLARE
+    inv              invoice;
+    _payment_term_id uuid;
+    payment_terms    create_payment_terms_request := req.payment_terms;
+    settled          double precision;
+BEGIN
+    select * from invoice where id = _invoice_id and tenant_id = req.tenant_id for update into inv;
+    if inv is null then
+        return jsonb_build_object('status', 'not_found');
+    end if;
+    if inv.invoice_status != 'issued' then
+        return jsonb_build_object('status', 'not_issued', 'entity_version_id', inv.entity_version_id);
+    end if;
+    if inv.e_invoicing_applicable then
+        return jsonb_build_object('status', 'einvoiced', 'entity_version_id', inv.entity_version_id);
+    end if;
+    if inv.supplier_business_entity != req.supplier_id then
+        return jsonb_build_object('status', 'supplier_changed', 'entity_version_id', inv.entity_version_id);
+    end if;
+    if inv.entity_version_id != _expected_version then
+        return jsonb_build_object('status', 'version_conflict', 'entity_version_id', inv.entity_version_id);
+    end if;
+    if exists(select 1
+              from gstr1_filing f
+                       join business_entity s on upper(s.gstin) = f.gstin
+              where s.id = inv.supplier_business_entity
+                and f.tenant_id = inv.tenant_id
+                and f.active
+                and inv.invoice_date_ms >= f.period_start_ms
+                and inv.invoice_date_ms < f.period_end_ms) then
+        return jsonb_build_object('status', 'gstr1_filed', 'entity_version_id', inv.entity_version_id);
+    end if;
+    settled := inv.amount_received + inv.discount_allowed;
+    if round(req.total_payable_amount::numeric, 2) < round(settled::numeric, 2) then
+        return jsonb_build_object('status', 'below_amount_received', 'entity_version_id', inv.entity_version_id);
+    end if;
+    if coalesce(cardinality(req.transfers), 0) = 0 and exists(select 1
+                                                               from transfer
+                                                               where tenant_id = req.tenant_id
+                                                                 and grouping_id = _invoice_id
+                                                                 and code = req.transfer_code) then
+        return jsonb_build_object('status', 'ledger_accounts_required', 'entity_version_id', inv.entity_version_id);
+    end if;
+    insert into invoice_version (id, tenant_id, invoice_id, entity_version_id, invoice_snapshot, lines_snapshot,
+                                 additional_charges_snapshot, amendment_reason, amended_by, created_at)
+    values (uuid_generate_v7(), inv.tenant_id, _invoice_id, inv.entity_version_id, to_jsonb(inv),
+            coalesce((select jsonb_agg(to_jsonb(il) order by il.line_number)
+                      from invoice_line il
+                      where il.invoice_table_id = _invoice_id
+                        and il.tenant_id = inv.tenant_id), '[]'::jsonb),
+            coalesce((select jsonb_agg(to_jsonb(ac) order by ac.line_no)
+                      from additional_charge ac
+                      where ac.invoice_table_id = _invoice_id
+                        and ac.tenant_id = inv.tenant_id), '[]'::jsonb),
+            _amendment_reason, req.created_by, default);
+    if payment_terms is not null then
+        select get_or_create_payment_term(payment_terms.due_days, payment_terms.discount_days,
+                                          payment_terms.discount_percent, req.tenant_id,
+                                          req.created_by)
+        into _payment_term_id;
+    end if;
+    update invoice
+    set entity_version_id=inv.entity_version_id + 1,
+        currency_id=req.currency_id,
+        service_invoice=req.service_invoice,
+        dispatch_from_business_entity=req.dispatch_from_id,
+        b2b_invoice=req.b2b_invoice,
+        billed_to_business_entity=req.billed_to_customer_id,
+        shipped_to_business_entity=req.shipped_to_customer_id,
+        purchase_order_number=req.order_number,
+        total_taxable_amount=req.total_taxable_amount,
+        total_tax_amount=req.total_tax_amount,
+        total_additional_charges_amount=req.total_additional_charges_amount,
+        round_off=req.round_off,
+        total_payable_amount=req.total_payable_amount,
+        igst_applicable=req.igst_applicable,
+        invoice_pdf_s3_id=null,--pdf of the previous version is kept in its snapshot
+        invoice_template_id=req.invoice_template_id,
+        payment_term_id=_payment_term_id,
+        invoice_remarks=req.invoice_remarks,
+        ecommerce_gstin=req.ecommerce_gstin,
+        export_type=(req.export_detail).export_type::invoice_export_type,
+        lut_reference=coalesce((req.export_detail).lut_reference, req.sez_lut_reference),
+        port_code=(req.export_detail).port_code,
+        shipping_bill_number=(req.export_detail).shipping_bill_number,
+        shipping_bill_date_ms=(req.export_detail).shipping_bill_date_ms,
+        export_country_id=(req.export_detail).country_id,
+        exchange_rate=(req.export_detail).exchange_rate,
+        total_payable_amount_inr=(req.export_detail).total_payable_amount_inr,
+        place_of_supply=req.place_of_supply,
+        supply_classification=req.supply_classification::invoice_supply_classification,
+        document_type=req.document_type::invoice_document_type,
+        payment_status=case
+                           when settled = 0 then 'unpaid'
+                           when round(settled::numeric, 2) >= round(req.total_payable_amount::numeric, 2)
+                               then 'paid'
+                           else 'partially_paid' end::invoice_payment_status,
+        updated_by=req.created_by,
+        updated_at=extract(epoch from now()) * 1000000
+    where id = _invoice_id
+      and tenant_id = req.tenant_id;
+    delete from invoice_line where invoice_table_id = _invoice_id and tenant_id = req.tenant_id;
+    delete from additional_charge where invoice_table_id = _invoice_id and tenant_id = req.tenant_id;
+    call persist_invoice_lines(req, _invoice_id);
+    call persist_additional_charge(req.additional_charges, _invoice_id, req.tenant_id, req.created_by);
+    update invoice_line
+    set entity_version_id=inv.entity_version_id + 1
+    where invoice_table_id = _invoice_id
+      and tenant_id = req.tenant_id;
+    --postings of the amended invoice stay on its date, amendments are not allowed once gstr-1 of the period is filed
+    call reverse_invoice_ledger_transfers(req.tenant_id, _invoice_id, req.transfer_code, inv.invoice_date_ms * 1000);
+    call post_invoice_ledger_transfers(req.tenant_id, _invoice_id, req.currency_id, req.transfers, req.transfer_code,
+                                       inv.invoice_date_ms * 1000);
+    return jsonb_build_object('status', 'amended', 'entity_version_id', inv.entity_version_id + 1);
+end;
+$$ language plpgsql;
+
+
+create trigger invoice_audit_trigger
+    after update or delete
+    on invoice
+    for each row
+execute function create_audit_entry();
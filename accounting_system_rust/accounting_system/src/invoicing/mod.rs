@@ -1,6 +1,13 @@
 pub mod additional_charge;
+pub mod ar_aging;
+pub mod delivery_challan;
 mod calculations;
 mod doc_conversion;
+pub mod einvoice_portal_client;
+pub mod eway_bill;
+pub mod invoice_approval;
+pub mod invoice_import;
+pub mod invoice_jobs;
 pub mod invoice_template;
 mod invoicing_dao;
 mod invoicing_dao_models;
@@ -14,3 +21,8 @@ mod invoicing_transformers;
 pub mod line_subtitle;
 pub mod line_title;
 pub mod payment_term;
+pub mod place_of_supply;
+pub mod purchase_invoice;
+pub mod quotation;
+pub mod receipt;
+pub mod recurring_invoice;
@@ -0,0 +1,384 @@
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+
+///state code reported as place of supply for supplies outside india
+pub const EXPORT_PLACE_OF_SUPPLY: &str = "96";
+
+///services with their own place of supply rule under section 12 of the igst act, rest are general.
+/// for the location based categories the shipped to party is taken as the location of performance,
+/// the property or the event
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceCategory {
+    #[default]
+    General,
+    ImmovableProperty,
+    PerformedAtLocation,
+    TrainingAndPerformanceAppraisal,
+    EventAdmission,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SupplyKind {
+    Goods,
+    Services(ServiceCategory),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyType {
+    IntraState,
+    InterState,
+}
+
+///location details of a party to the supply, state is taken from the gstin when the address is not on record
+#[derive(Debug, Clone, Default)]
+pub struct PosParty<'a> {
+    pub gstin: Option<&'a str>,
+    pub state_code: Option<&'a str>,
+    pub country_id: Option<Uuid>,
+    pub sez: bool,
+}
+
+impl PosParty<'_> {
+    fn registered(&self) -> bool {
+        self.gstin.is_some()
+    }
+
+    fn outside_india(&self) -> bool {
+        self.country_id.is_some_and(|a| a != *INDIA_COUNTRY_ID)
+    }
+
+    fn state(&self) -> Option<String> {
+        self.state_code
+            .or_else(|| self.gstin.and_then(|a| a.get(0..2)))
+            .map(|a| format!("{:0>2}", a.trim()))
+    }
+}
+
+///shipped_to is given only when the goods are delivered or the service is performed at a party other than billed_to
+#[derive(Debug, Clone)]
+pub struct PlaceOfSupplyInput<'a> {
+    pub supplier: PosParty<'a>,
+    pub billed_to: Option<PosParty<'a>>,
+    pub shipped_to: Option<PosParty<'a>>,
+    pub supply_kind: SupplyKind,
+    pub export: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PlaceOfSupply {
+    pub state_code: String,
+    pub supply_type: SupplyType,
+    pub explanation: String,
+}
+
+impl PlaceOfSupply {
+    pub fn igst_applicable(&self) -> bool {
+        self.supply_type == SupplyType::InterState
+    }
+
+    pub fn is_export(&self) -> bool {
+        self.state_code == EXPORT_PLACE_OF_SUPPLY
+    }
+}
+
+///decides the state of place of supply as per sections 10 and 12 of the igst act, and whether the supply is
+/// inter-state as per sections 7 and 8
+pub fn determine_place_of_supply(input: &PlaceOfSupplyInput) -> anyhow::Result<PlaceOfSupply> {
+    let supplier_state = input
+        .supplier
+        .state()
+        .context("state of the supplier could not be determined")?;
+    let recipient = input.billed_to.as_ref();
+    let location = input.shipped_to.as_ref().or(recipient);
+    let outside_india = match input.supply_kind {
+        SupplyKind::Goods => location.is_some_and(|a| a.outside_india()),
+        SupplyKind::Services(_) => recipient.is_some_and(|a| a.outside_india()),
+    };
+    if input.export || outside_india {
+        return Ok(PlaceOfSupply {
+            state_code: EXPORT_PLACE_OF_SUPPLY.to_string(),
+            supply_type: SupplyType::InterState,
+            explanation: "supply is an export, which is an inter-state supply (section 7(5)(a))"
+                .to_string(),
+        });
+    }
+    let (state, reason) = match input.supply_kind {
+        SupplyKind::Goods => goods_place_of_supply(input, &supplier_state)?,
+        SupplyKind::Services(category) => {
+            service_place_of_supply(input, category, &supplier_state)?
+        }
+    };
+    let sez = input.supplier.sez || recipient.is_some_and(|a| a.sez);
+    let (supply_type, explanation) = if sez {
+        (
+            SupplyType::InterState,
+            format!("{reason}. supply by or to a sez unit or developer is inter-state (section 7(5)(b))"),
+        )
+    } else if state == supplier_state {
+        (
+            SupplyType::IntraState,
+            format!("{reason}. supplier is in the same state, supply is intra-state (section 8)"),
+        )
+    } else {
+        (
+            SupplyType::InterState,
+            format!("{reason}. supplier is in state {supplier_state}, supply is inter-state (section 7)"),
+        )
+    };
+    Ok(PlaceOfSupply {
+        state_code: state,
+        supply_type,
+        explanation,
+    })
+}
+
+fn goods_place_of_supply(
+    input: &PlaceOfSupplyInput,
+    supplier_state: &str,
+) -> anyhow::Result<(String, String)> {
+    let Some(billed_to) = input.billed_to.as_ref() else {
+        return Ok((
+            supplier_state.to_string(),
+            "goods are delivered at the supplier without movement to a recipient on record, place of supply is \
+            the location of the supplier (section 10(1)(c))"
+                .to_string(),
+        ));
+    };
+    let billed_to_state = billed_to.state();
+    match (input.shipped_to.as_ref(), billed_to_state) {
+        (Some(_), Some(state)) => Ok((
+            state,
+            "goods are delivered to a third person on the direction of the billed to party, place of supply is \
+            the location of the billed to party (section 10(1)(b))"
+                .to_string(),
+        )),
+        (None, Some(state)) => Ok((
+            state,
+            "movement of goods terminates at the billed to party, place of supply is the location of delivery \
+            (section 10(1)(a))"
+                .to_string(),
+        )),
+        (Some(shipped_to), None) => {
+            let state = shipped_to
+                .state()
+                .ok_or_else(|| anyhow!("state of the shipped to party could not be determined"))?;
+            Ok((
+                state,
+                "location of the billed to party is not on record, place of supply is the location where \
+                movement of goods terminates (section 10(1)(a))"
+                    .to_string(),
+            ))
+        }
+        (None, None) => Ok((
+            supplier_state.to_string(),
+            "location of the billed to party is not on record, place of supply is the location of the supplier \
+            (section 10(1)(c))"
+                .to_string(),
+        )),
+    }
+}
+
+fn service_place_of_supply(
+    input: &PlaceOfSupplyInput,
+    category: ServiceCategory,
+    supplier_state: &str,
+) -> anyhow::Result<(String, String)> {
+    let recipient = input.billed_to.as_ref();
+    let location_state = input
+        .shipped_to
+        .as_ref()
+        .or(recipient)
+        .and_then(|a| a.state());
+    let recipient_state = recipient.and_then(|a| a.state());
+    let registered_recipient_state = recipient.filter(|a| a.registered()).and_then(|a| a.state());
+    let located = |reason: &str| -> anyhow::Result<(String, String)> {
+        let state = location_state
+            .clone()
+            .with_context(|| format!("location could not be determined, {reason}"))?;
+        Ok((state, reason.to_string()))
+    };
+    match category {
+        ServiceCategory::ImmovableProperty => located(
+            "place of supply for services related to immovable property is the location of the property \
+            (section 12(3))",
+        ),
+        ServiceCategory::PerformedAtLocation => located(
+            "place of supply for services performed at a location is where they are actually performed \
+            (section 12(4))",
+        ),
+        ServiceCategory::EventAdmission => located(
+            "place of supply for admission to an event is where the event is held (section 12(6))",
+        ),
+        ServiceCategory::TrainingAndPerformanceAppraisal => match registered_recipient_state {
+            Some(state) => Ok((
+                state,
+                "training services to a registered person, place of supply is the location of the recipient \
+                (section 12(5)(a))"
+                    .to_string(),
+            )),
+            None => located(
+                "training services to an unregistered person, place of supply is where they are performed \
+                (section 12(5)(b))",
+            ),
+        },
+        ServiceCategory::General => match (registered_recipient_state, recipient_state) {
+            (Some(state), _) => Ok((
+                state,
+                "services to a registered person, place of supply is the location of the recipient \
+                (section 12(2)(a))"
+                    .to_string(),
+            )),
+            (None, Some(state)) => Ok((
+                state,
+                "services to an unregistered person with address on record, place of supply is the location \
+                of the recipient (section 12(2)(b))"
+                    .to_string(),
+            )),
+            (None, None) => Ok((
+                supplier_state.to_string(),
+                "address of the recipient is not on record, place of supply is the location of the supplier \
+                (section 12(2)(b))"
+                    .to_string(),
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use uuid::Uuid;
+
+    use crate::invoicing::place_of_supply::{
+        determine_place_of_supply, PlaceOfSupply, PlaceOfSupplyInput, PosParty, ServiceCategory,
+        SupplyKind, SupplyType, EXPORT_PLACE_OF_SUPPLY,
+    };
+    use crate::masters::country_master::country_model::INDIA_COUNTRY_ID;
+
+    pub fn a_place_of_supply(supply_type: SupplyType) -> PlaceOfSupply {
+        PlaceOfSupply {
+            state_code: "05".to_string(),
+            supply_type,
+            explanation: "".to_string(),
+        }
+    }
+
+    fn a_party(gstin: Option<&'static str>, state_code: Option<&'static str>) -> PosParty<'static> {
+        PosParty {
+            gstin,
+            state_code,
+            country_id: Some(*INDIA_COUNTRY_ID),
+            sez: false,
+        }
+    }
+
+    fn an_input(supply_kind: SupplyKind) -> PlaceOfSupplyInput<'static> {
+        PlaceOfSupplyInput {
+            supplier: a_party(Some("05AABCA5291P1ZD"), Some("05")),
+            billed_to: Some(a_party(Some("05MFNMS5291P1ZA"), Some("05"))),
+            shipped_to: None,
+            supply_kind,
+            export: false,
+        }
+    }
+
+    #[test]
+    fn test_goods_to_registered_recipient_in_same_state() {
+        let pos = determine_place_of_supply(&an_input(SupplyKind::Goods)).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert_eq!(pos.supply_type, SupplyType::IntraState);
+        assert!(pos.explanation.contains("10(1)(a)"));
+    }
+
+    #[test]
+    fn test_bill_to_ship_to_goods_follow_billed_to_party() {
+        let mut input = an_input(SupplyKind::Goods);
+        input.shipped_to = Some(a_party(Some("27MFNMS5291P1ZA"), Some("27")));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert!(!pos.igst_applicable());
+        assert!(pos.explanation.contains("10(1)(b)"));
+        input.billed_to = Some(a_party(Some("06MFNMS5291P1ZA"), Some("06")));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "06");
+        assert!(pos.igst_applicable());
+    }
+
+    #[test]
+    fn test_unregistered_recipient_uses_address_then_supplier_location() {
+        let mut input = an_input(SupplyKind::Services(ServiceCategory::General));
+        input.billed_to = Some(a_party(None, Some("29")));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "29");
+        assert!(pos.igst_applicable());
+        input.billed_to = Some(a_party(None, None));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert!(!pos.igst_applicable());
+        input.supply_kind = SupplyKind::Goods;
+        input.billed_to = None;
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert!(pos.explanation.contains("10(1)(c)"));
+    }
+
+    #[test]
+    fn test_state_is_taken_from_gstin_without_address() {
+        let mut input = an_input(SupplyKind::Goods);
+        input.billed_to = Some(a_party(Some("27MFNMS5291P1ZA"), None));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "27");
+        assert_eq!(pos.supply_type, SupplyType::InterState);
+    }
+
+    #[test]
+    fn test_location_based_services() {
+        let mut input = an_input(SupplyKind::Services(ServiceCategory::ImmovableProperty));
+        input.shipped_to = Some(a_party(None, Some("27")));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "27");
+        assert!(pos.explanation.contains("12(3)"));
+        input.supply_kind = SupplyKind::Services(ServiceCategory::TrainingAndPerformanceAppraisal);
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert!(pos.explanation.contains("12(5)(a)"));
+        input.billed_to = Some(a_party(None, Some("05")));
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "27");
+        assert!(pos.explanation.contains("12(5)(b)"));
+    }
+
+    #[test]
+    fn test_sez_supply_is_inter_state() {
+        let mut input = an_input(SupplyKind::Goods);
+        input.billed_to.as_mut().unwrap().sez = true;
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, "05");
+        assert_eq!(pos.supply_type, SupplyType::InterState);
+        assert!(pos.explanation.contains("7(5)(b)"));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut input = an_input(SupplyKind::Services(ServiceCategory::General));
+        input.billed_to.as_mut().unwrap().country_id = Some(Uuid::now_v7());
+        let pos = determine_place_of_supply(&input).unwrap();
+        assert_eq!(pos.state_code, EXPORT_PLACE_OF_SUPPLY);
+        assert!(pos.igst_applicable());
+        assert!(pos.is_export());
+        let mut input = an_input(SupplyKind::Goods);
+        input.export = true;
+        assert!(determine_place_of_supply(&input).unwrap().is_export());
+    }
+
+    #[test]
+    fn test_supplier_state_is_mandatory() {
+        let mut input = an_input(SupplyKind::Goods);
+        input.supplier = a_party(None, None);
+        assert!(determine_place_of_supply(&input).is_err());
+    }
+}
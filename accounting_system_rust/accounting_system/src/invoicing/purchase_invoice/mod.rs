@@ -0,0 +1,5 @@
+mod purchase_invoice_dao;
+pub mod purchase_invoice_db_mapping;
+pub mod purchase_invoice_http_api;
+pub mod purchase_invoice_models;
+pub mod purchase_invoice_service;
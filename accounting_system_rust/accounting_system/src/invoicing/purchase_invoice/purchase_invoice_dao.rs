@@ -0,0 +1,236 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::purchase_invoice::purchase_invoice_models::{
+    CreatePurchaseInvoiceResponse, ItcEligibility, PurchaseInvoice, PurchaseInvoiceDb,
+    PurchaseInvoiceLine,
+};
+
+const CREATE_PURCHASE_INVOICE: &str = "select create_purchase_invoice($1)";
+
+const PURCHASE_INVOICE_QUERY: &str = "select id,supplier_business_entity,\
+recipient_business_entity,supplier_invoice_number,self_invoice_number,purchase_invoice_date_ms,\
+currency_id,reverse_charge,igst_applicable,total_taxable_amount,total_tax_amount,\
+total_itc_amount,total_payable_amount,payable_account_id,remarks from purchase_invoice \
+where id=$1 and tenant_id=$2";
+
+const PURCHASE_INVOICE_LINES_QUERY: &str = "select line_number,product_item_id,hsn_sac_code,\
+quantity,uqc,unit_price,discount_percentage,tax_percentage,taxable_amount,igst_amount,\
+cgst_amount,sgst_amount,cess_amount,itc_eligibility::text,debit_account_id \
+from purchase_invoice_line where purchase_invoice_id=$1 and tenant_id=$2 order by line_number";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PurchaseInvoiceDao: Send + Sync {
+    ///stores the purchase invoice with its lines, numbers the self invoice if a series is given
+    ///and posts the ledger transfers
+    async fn create_purchase_invoice<'a>(
+        &self,
+        purchase_invoice_db: &PurchaseInvoiceDb<'a>,
+    ) -> Result<CreatePurchaseInvoiceResponse, DaoError>;
+    async fn get_purchase_invoice_by_id(
+        &self,
+        purchase_invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<PurchaseInvoice>, DaoError>;
+}
+
+struct PurchaseInvoiceDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_purchase_invoice_dao(arc: Arc<Pool>) -> Arc<dyn PurchaseInvoiceDao> {
+    let dao = PurchaseInvoiceDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for PurchaseInvoiceLine {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let itc_eligibility: &str = row.get(13);
+        Ok(PurchaseInvoiceLine {
+            line_no: row.get(0),
+            product_item_id: row.get(1),
+            hsn_sac_code: row.get(2),
+            quantity: row.get(3),
+            uqc: row.get(4),
+            unit_price: row.get(5),
+            discount_percentage: row.get(6),
+            tax_percentage: row.get(7),
+            taxable_amount: row.get(8),
+            igst_amount: row.get(9),
+            cgst_amount: row.get(10),
+            sgst_amount: row.get(11),
+            cess_amount: row.get(12),
+            itc_eligibility: ItcEligibility::from_db_str(itc_eligibility)?,
+            debit_account_id: row.get(14),
+        })
+    }
+}
+
+impl TryFrom<Row> for PurchaseInvoice {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(PurchaseInvoice {
+            id: row.get(0),
+            supplier_id: row.get(1),
+            recipient_id: row.get(2),
+            supplier_invoice_number: row.get(3),
+            self_invoice_number: row.get(4),
+            purchase_invoice_date_ms: row.get(5),
+            currency_id: row.get(6),
+            reverse_charge: row.get(7),
+            igst_applicable: row.get(8),
+            total_taxable_amount: row.get(9),
+            total_tax_amount: row.get(10),
+            total_itc_amount: row.get(11),
+            total_payable_amount: row.get(12),
+            payable_account_id: row.get(13),
+            remarks: row.get(14),
+            lines: vec![],
+        })
+    }
+}
+
+#[async_trait]
+impl PurchaseInvoiceDao for PurchaseInvoiceDaoImpl {
+    async fn create_purchase_invoice<'a>(
+        &self,
+        purchase_invoice_db: &PurchaseInvoiceDb<'a>,
+    ) -> Result<CreatePurchaseInvoiceResponse, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_PURCHASE_INVOICE, &[purchase_invoice_db])
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let resp =
+            serde_json::from_value(json).context("invalid create purchase invoice response")?;
+        Ok(resp)
+    }
+
+    async fn get_purchase_invoice_by_id(
+        &self,
+        purchase_invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<PurchaseInvoice>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let purchase_invoice: Option<PurchaseInvoice> = conn
+            .query_opt(PURCHASE_INVOICE_QUERY, &[&purchase_invoice_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()?;
+        let Some(mut purchase_invoice) = purchase_invoice else {
+            return Ok(None);
+        };
+        purchase_invoice.lines = conn
+            .query(
+                PURCHASE_INVOICE_LINES_QUERY,
+                &[&purchase_invoice_id, &tenant_id],
+            )
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<PurchaseInvoiceLine>, DaoError>>()?;
+        Ok(Some(purchase_invoice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::option::OptionAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::account::account_models::tests::{
+        SEED_CREDIT_ACCOUNT_ID, SEED_DEBIT_ACCOUNT_ID,
+    };
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::purchase_invoice::purchase_invoice_dao::{
+        PurchaseInvoiceDao, PurchaseInvoiceDaoImpl,
+    };
+    use crate::invoicing::purchase_invoice::purchase_invoice_models::{
+        ItcEligibility, PurchaseInvoiceDb, PurchaseInvoiceLine, PurchaseTransferDb,
+        PURCHASE_INVOICE_TRANSFER_CODE,
+    };
+    use crate::masters::business_entity_master::business_entity_models::tests::{
+        SEED_BUSINESS_ENTITY_ID1, SEED_BUSINESS_ENTITY_ID2,
+    };
+    use crate::masters::product_item_master::product_item_models::tests::SEED_PRODUCT_ITEM_ID;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_create_purchase_invoice_is_idempotent() {
+        let dao = get_dao_generic(|c| PurchaseInvoiceDaoImpl { postgres_client: c }, None).await;
+        let line = PurchaseInvoiceLine {
+            line_no: 1,
+            product_item_id: *SEED_PRODUCT_ITEM_ID,
+            hsn_sac_code: "1001".to_string(),
+            quantity: 1.0,
+            uqc: "NOS".to_string(),
+            unit_price: 100.0,
+            discount_percentage: 0.0,
+            tax_percentage: 18.0,
+            taxable_amount: 100.0,
+            igst_amount: 18.0,
+            cgst_amount: 0.0,
+            sgst_amount: 0.0,
+            cess_amount: 0.0,
+            itc_eligibility: ItcEligibility::Eligible,
+            debit_account_id: *SEED_DEBIT_ACCOUNT_ID,
+        };
+        let db = PurchaseInvoiceDb {
+            idempotence_key: Uuid::now_v7(),
+            tenant_id: *SEED_TENANT_ID,
+            supplier_id: *SEED_BUSINESS_ENTITY_ID2,
+            recipient_id: *SEED_BUSINESS_ENTITY_ID1,
+            supplier_invoice_number: Some("PB/2024/001"),
+            self_invoice_series_mst_id: None,
+            purchase_invoice_date_ms: 0,
+            financial_year: 2024,
+            currency_id: *SEED_CURRENCY_ID,
+            reverse_charge: false,
+            igst_applicable: true,
+            lines: vec![(&line).into()],
+            total_taxable_amount: 100.0,
+            total_tax_amount: 18.0,
+            total_itc_amount: 18.0,
+            total_payable_amount: 118.0,
+            payable_account_id: *SEED_CREDIT_ACCOUNT_ID,
+            transfers: vec![PurchaseTransferDb {
+                debit_account_id: *SEED_DEBIT_ACCOUNT_ID,
+                credit_account_id: *SEED_CREDIT_ACCOUNT_ID,
+                amount: 118.0,
+            }],
+            transfer_code: PURCHASE_INVOICE_TRANSFER_CODE,
+            remarks: None,
+            created_by: *SEED_USER_ID,
+        };
+        let first = dao.create_purchase_invoice(&db).await.unwrap();
+        let second = dao.create_purchase_invoice(&db).await.unwrap();
+        assert_that!(second).is_equal_to(&first);
+        assert_that!(first.self_invoice_number).is_none();
+        let fetched = dao
+            .get_purchase_invoice_by_id(first.purchase_invoice_id, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(fetched.map(|a| a.lines))
+            .is_some()
+            .is_equal_to(vec![line]);
+    }
+}
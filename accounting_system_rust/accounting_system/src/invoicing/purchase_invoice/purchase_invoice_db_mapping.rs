@@ -0,0 +1,53 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct PurchaseInvoiceDbMapping {}
+
+const PURCHASE_INVOICE_DDL_SQL: &str =
+    include_str!("./purchase_invoice_sql/purchase_invoice_ddl.sql");
+const PURCHASE_INVOICE_SEED_DATA: &str =
+    include_str!("./purchase_invoice_sql/purchase_invoice.csv");
+const PURCHASE_INVOICE_INDEXES_SQL: &str =
+    include_str!("./purchase_invoice_sql/purchase_invoice_indexes.sql");
+const PURCHASE_INVOICE_FUNCTIONS_SQL: &str =
+    include_str!("./purchase_invoice_sql/purchase_invoice_functions_and_procedures.sql");
+impl DbStructMapping for PurchaseInvoiceDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("purchase_invoice")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        PURCHASE_INVOICE_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        PURCHASE_INVOICE_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        PURCHASE_INVOICE_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        PURCHASE_INVOICE_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
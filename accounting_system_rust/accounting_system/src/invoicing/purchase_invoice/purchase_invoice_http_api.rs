@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::purchase_invoice::purchase_invoice_models::CreatePurchaseInvoiceRequest;
+use crate::invoicing::purchase_invoice::purchase_invoice_service::{
+    PurchaseInvoiceService, PurchaseInvoiceServiceError,
+};
+use crate::setup_routes;
+
+impl ResponseError for PurchaseInvoiceServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PurchaseInvoiceServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            PurchaseInvoiceServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            PurchaseInvoiceServiceError::PurchaseInvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            PurchaseInvoiceServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn create_purchase_invoice(
+    data: Data<Arc<dyn PurchaseInvoiceService>>,
+    request: web::Json<CreatePurchaseInvoiceRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_purchase_invoice(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_purchase_invoice(
+    data: Data<Arc<dyn PurchaseInvoiceService>>,
+    purchase_invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_purchase_invoice(purchase_invoice_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    PurchaseInvoiceService,
+    "/purchase-invoice",
+    "/create",
+    web::post().to(create_purchase_invoice),
+    "/id/{purchase_invoice_id}",
+    web::get().to(get_purchase_invoice)
+);
@@ -0,0 +1,377 @@
+use anyhow::{bail, ensure, Context};
+use chrono::{Datelike, NaiveDate, TimeZone};
+use derive_builder::Builder;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use invoice_doc_generator::invoice_line::line_quantity::LineQuantity;
+use invoice_doc_generator::invoice_line::unit_price::Price;
+use invoice_doc_generator::percentages::tax_discount_cess::DiscountPercentage;
+
+use crate::gst_returns::gstr3b::gstr3b_models::GstLedgerAccounts;
+use crate::invoicing::invoicing_request_models::InvoiceRemarks;
+
+///transaction type code of the ledger transfers posted for a purchase invoice
+pub const PURCHASE_INVOICE_TRANSFER_CODE: i16 = 4;
+
+///whether the tax paid on a purchase line can be taken as input tax credit
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "itc_eligibility", rename_all = "snake_case")]
+pub enum ItcEligibility {
+    Eligible,
+    ///blocked credits of section 17(5), e.g. motor vehicles, food and beverages
+    Blocked,
+    ///not available for other reasons, the tax becomes part of the cost
+    Ineligible,
+}
+
+impl ItcEligibility {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let eligibility = match value {
+            "eligible" => ItcEligibility::Eligible,
+            "blocked" => ItcEligibility::Blocked,
+            "ineligible" => ItcEligibility::Ineligible,
+            _ => bail!("{} is not a valid itc eligibility", value),
+        };
+        Ok(eligibility)
+    }
+}
+
+///invoice number on the bill of the supplier
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct SupplierInvoiceNo(String);
+
+impl SupplierInvoiceNo {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "supplier invoice number cannot be empty");
+        ensure!(
+            value.len() <= 16,
+            "supplier invoice number cannot be more than 16 chars"
+        );
+        ensure!(
+            value
+                .chars()
+                .all(|a| a.is_ascii_alphanumeric() || a == '/' || a == '-'),
+            "supplier invoice number can only contain alphanumeric characters or / or -"
+        );
+        Ok(SupplierInvoiceNo(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for SupplierInvoiceNo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        SupplierInvoiceNo::new(value.as_str())
+    }
+}
+
+///date of the bill of the supplier, itc is reported in the return period of this date
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct PurchaseInvoiceDate(NaiveDate);
+
+impl PurchaseInvoiceDate {
+    pub fn new(value: String) -> anyhow::Result<Self> {
+        let p = NaiveDate::parse_from_str(value.as_str(), "%Y-%m-%d")
+            .context("purchase invoice date must be in yyyy-mm-dd format")?;
+        Ok(PurchaseInvoiceDate(p))
+    }
+    pub fn get_date(&self) -> &NaiveDate {
+        &self.0
+    }
+    ///start of the day in indian standard time, same as the bounds of a return period
+    pub fn epoch_millis(&self) -> Option<i64> {
+        self.0
+            .and_hms_milli_opt(0, 0, 0, 0)
+            .and_then(|a| chrono_tz::Asia::Kolkata.from_local_datetime(&a).single())
+            .map(|a| a.timestamp_millis())
+    }
+    ///indian financial year starting in april, identified by the year it starts in
+    pub fn financial_year(&self) -> i16 {
+        let year = if self.0.month() < 4 {
+            self.0.year() - 1
+        } else {
+            self.0.year()
+        };
+        year as i16
+    }
+}
+
+impl TryFrom<String> for PurchaseInvoiceDate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        PurchaseInvoiceDate::new(value)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreatePurchaseInvoiceLineRequest {
+    pub product_item_id: Uuid,
+    pub quantity: LineQuantity,
+    pub unit_price: Price,
+    pub discount_percentage: DiscountPercentage,
+    pub itc_eligibility: ItcEligibility,
+    ///expense, asset or inventory account debited with the value of the line
+    pub debit_account_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreatePurchaseInvoiceRequest {
+    pub idempotence_key: Uuid,
+    ///business entity from whom the goods or services are bought
+    pub supplier_id: Uuid,
+    ///registered business entity of the tenant receiving the supply, itc is taken in its gstin
+    pub recipient_id: Uuid,
+    ///mandatory for registered suppliers
+    pub supplier_invoice_number: Option<SupplierInvoiceNo>,
+    pub purchase_invoice_date: PurchaseInvoiceDate,
+    pub currency_id: Uuid,
+    ///tax is paid by the recipient instead of the supplier
+    pub reverse_charge: bool,
+    ///series of the self invoice raised by the recipient for reverse charge purchases from
+    ///unregistered suppliers
+    pub self_invoice_series_mst_id: Option<Uuid>,
+    pub lines: Vec<CreatePurchaseInvoiceLineRequest>,
+    ///supplier account credited with the amount payable to the supplier
+    pub payable_account_id: Uuid,
+    ///debited with the eligible itc
+    pub input_tax_accounts: GstLedgerAccounts,
+    ///credited with the tax payable under reverse charge, mandatory for reverse charge purchases
+    pub reverse_charge_tax_accounts: Option<GstLedgerAccounts>,
+    pub remarks: Option<InvoiceRemarks>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CreatePurchaseInvoiceResponse {
+    pub purchase_invoice_id: Uuid,
+    ///present for reverse charge purchases from unregistered suppliers
+    pub self_invoice_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PurchaseInvoiceLine {
+    pub line_no: i16,
+    pub product_item_id: Uuid,
+    pub hsn_sac_code: String,
+    pub quantity: f64,
+    pub uqc: String,
+    pub unit_price: f64,
+    pub discount_percentage: f32,
+    pub tax_percentage: f32,
+    pub taxable_amount: f64,
+    pub igst_amount: f64,
+    pub cgst_amount: f64,
+    pub sgst_amount: f64,
+    pub cess_amount: f64,
+    pub itc_eligibility: ItcEligibility,
+    pub debit_account_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PurchaseInvoice {
+    pub id: Uuid,
+    pub supplier_id: Uuid,
+    pub recipient_id: Uuid,
+    pub supplier_invoice_number: Option<String>,
+    pub self_invoice_number: Option<String>,
+    pub purchase_invoice_date_ms: i64,
+    pub currency_id: Uuid,
+    pub reverse_charge: bool,
+    pub igst_applicable: bool,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    ///tax on lines eligible for itc
+    pub total_itc_amount: f64,
+    ///amount payable to the supplier, tax under reverse charge is paid to the government instead
+    pub total_payable_amount: f64,
+    pub payable_account_id: Uuid,
+    pub remarks: Option<String>,
+    pub lines: Vec<PurchaseInvoiceLine>,
+}
+
+impl PurchaseInvoiceLine {
+    pub fn tax_amount(&self) -> f64 {
+        self.igst_amount + self.cgst_amount + self.sgst_amount + self.cess_amount
+    }
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_purchase_invoice_line_request")]
+pub(crate) struct PurchaseInvoiceLineDb<'a> {
+    pub line_no: i16,
+    pub product_item_id: Uuid,
+    pub hsn_sac_code: &'a str,
+    pub quantity: f64,
+    pub uqc: &'a str,
+    pub unit_price: f64,
+    pub discount_percentage: f32,
+    pub tax_percentage: f32,
+    pub taxable_amount: f64,
+    pub igst_amount: f64,
+    pub cgst_amount: f64,
+    pub sgst_amount: f64,
+    pub cess_amount: f64,
+    pub itc_eligibility: ItcEligibility,
+    pub debit_account_id: Uuid,
+}
+
+impl<'a> From<&'a PurchaseInvoiceLine> for PurchaseInvoiceLineDb<'a> {
+    fn from(line: &'a PurchaseInvoiceLine) -> Self {
+        PurchaseInvoiceLineDb {
+            line_no: line.line_no,
+            product_item_id: line.product_item_id,
+            hsn_sac_code: line.hsn_sac_code.as_str(),
+            quantity: line.quantity,
+            uqc: line.uqc.as_str(),
+            unit_price: line.unit_price,
+            discount_percentage: line.discount_percentage,
+            tax_percentage: line.tax_percentage,
+            taxable_amount: line.taxable_amount,
+            igst_amount: line.igst_amount,
+            cgst_amount: line.cgst_amount,
+            sgst_amount: line.sgst_amount,
+            cess_amount: line.cess_amount,
+            itc_eligibility: line.itc_eligibility,
+            debit_account_id: line.debit_account_id,
+        }
+    }
+}
+
+///one ledger transfer of the purchase invoice, amount in currency units
+#[derive(Debug, Clone, PartialEq, ToSql)]
+#[postgres(name = "create_purchase_invoice_transfer_request")]
+pub(crate) struct PurchaseTransferDb {
+    pub debit_account_id: Uuid,
+    pub credit_account_id: Uuid,
+    pub amount: f64,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_purchase_invoice_request")]
+pub(crate) struct PurchaseInvoiceDb<'a> {
+    pub idempotence_key: Uuid,
+    pub tenant_id: Uuid,
+    pub supplier_id: Uuid,
+    pub recipient_id: Uuid,
+    pub supplier_invoice_number: Option<&'a str>,
+    pub self_invoice_series_mst_id: Option<Uuid>,
+    pub purchase_invoice_date_ms: i64,
+    pub financial_year: i16,
+    pub currency_id: Uuid,
+    pub reverse_charge: bool,
+    pub igst_applicable: bool,
+    pub lines: Vec<PurchaseInvoiceLineDb<'a>>,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_itc_amount: f64,
+    pub total_payable_amount: f64,
+    pub payable_account_id: Uuid,
+    pub transfers: Vec<PurchaseTransferDb>,
+    pub transfer_code: i16,
+    pub remarks: Option<&'a str>,
+    pub created_by: Uuid,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use invoice_doc_generator::invoice_line::line_quantity::test_utils::a_line_quantity;
+    use invoice_doc_generator::invoice_line::unit_price::Price;
+    use invoice_doc_generator::percentages::tax_discount_cess::DiscountPercentage;
+
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::gst_returns::gstr3b::gstr3b_models::tests::a_gst_ledger_accounts;
+    use crate::invoicing::purchase_invoice::purchase_invoice_models::{
+        CreatePurchaseInvoiceLineRequest, CreatePurchaseInvoiceLineRequestBuilder,
+        CreatePurchaseInvoiceRequest, CreatePurchaseInvoiceRequestBuilder, ItcEligibility,
+        PurchaseInvoiceDate, SupplierInvoiceNo,
+    };
+    use crate::masters::business_entity_master::business_entity_models::tests::{
+        SEED_BUSINESS_ENTITY_ID1, SEED_BUSINESS_ENTITY_ID2,
+    };
+    use crate::masters::product_item_master::product_item_models::tests::SEED_PRODUCT_ITEM_ID;
+
+    pub fn a_create_purchase_invoice_line_request(
+        builder: CreatePurchaseInvoiceLineRequestBuilder,
+    ) -> CreatePurchaseInvoiceLineRequest {
+        CreatePurchaseInvoiceLineRequest {
+            product_item_id: builder.product_item_id.unwrap_or(*SEED_PRODUCT_ITEM_ID),
+            quantity: builder
+                .quantity
+                .unwrap_or_else(|| a_line_quantity(Default::default())),
+            unit_price: builder
+                .unit_price
+                .unwrap_or_else(|| Price::new(100.0).unwrap()),
+            discount_percentage: builder
+                .discount_percentage
+                .unwrap_or_else(|| DiscountPercentage::new(0.0).unwrap()),
+            itc_eligibility: builder.itc_eligibility.unwrap_or(ItcEligibility::Eligible),
+            debit_account_id: builder.debit_account_id.unwrap_or_else(Uuid::now_v7),
+        }
+    }
+
+    pub fn a_create_purchase_invoice_request(
+        builder: CreatePurchaseInvoiceRequestBuilder,
+    ) -> CreatePurchaseInvoiceRequest {
+        CreatePurchaseInvoiceRequest {
+            idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
+            supplier_id: builder.supplier_id.unwrap_or(*SEED_BUSINESS_ENTITY_ID2),
+            recipient_id: builder.recipient_id.unwrap_or(*SEED_BUSINESS_ENTITY_ID1),
+            supplier_invoice_number: builder
+                .supplier_invoice_number
+                .unwrap_or_else(|| Some(SupplierInvoiceNo::new("PB/2024/001").unwrap())),
+            purchase_invoice_date: builder
+                .purchase_invoice_date
+                .unwrap_or_else(|| PurchaseInvoiceDate::new("2024-01-15".to_string()).unwrap()),
+            currency_id: builder.currency_id.unwrap_or(*SEED_CURRENCY_ID),
+            reverse_charge: builder.reverse_charge.unwrap_or(false),
+            self_invoice_series_mst_id: builder.self_invoice_series_mst_id.flatten(),
+            lines: builder.lines.unwrap_or_else(|| {
+                vec![a_create_purchase_invoice_line_request(Default::default())]
+            }),
+            payable_account_id: builder.payable_account_id.unwrap_or_else(Uuid::now_v7),
+            input_tax_accounts: builder
+                .input_tax_accounts
+                .unwrap_or_else(a_gst_ledger_accounts),
+            reverse_charge_tax_accounts: builder.reverse_charge_tax_accounts.flatten(),
+            remarks: builder.remarks.flatten(),
+        }
+    }
+
+    #[rstest]
+    #[case("PB/2024/001", true)]
+    #[case("INV-17", true)]
+    #[case("12345678901234567", false)]
+    #[case("inv 1", false)]
+    #[case("", false)]
+    fn test_supplier_invoice_no(#[case] input: &str, #[case] valid: bool) {
+        assert_eq!(SupplierInvoiceNo::new(input).is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case("2024-03-31", 2023)]
+    #[case("2024-04-01", 2024)]
+    #[case("2025-01-10", 2024)]
+    fn test_purchase_invoice_financial_year(#[case] input: &str, #[case] year: i16) {
+        let date = PurchaseInvoiceDate::new(input.to_string()).unwrap();
+        assert_eq!(date.financial_year(), year);
+    }
+
+    #[test]
+    fn test_itc_eligibility_db_str() {
+        let eligibility: ItcEligibility = serde_json::from_str("\"blocked\"").unwrap();
+        assert_eq!(ItcEligibility::from_db_str("blocked").unwrap(), eligibility);
+        assert!(ItcEligibility::from_db_str("partial").is_err());
+    }
+}
@@ -0,0 +1,702 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use invoice_doc_generator::invoice_line::line_quantity::FreeLineQuantity;
+
+use crate::accounting::currency::currency_service::CurrencyService;
+use crate::common_utils::dao_error::DaoError;
+use crate::common_utils::utils::current_indian_date;
+use crate::invoicing::invoicing_request_models::CreateInvoiceLineRequestWithAllDetails;
+use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
+use crate::invoicing::invoicing_series::invoicing_series_service::InvoicingSeriesService;
+use crate::invoicing::purchase_invoice::purchase_invoice_dao::{
+    get_purchase_invoice_dao, PurchaseInvoiceDao,
+};
+use crate::invoicing::purchase_invoice::purchase_invoice_models::{
+    CreatePurchaseInvoiceLineRequest, CreatePurchaseInvoiceRequest, CreatePurchaseInvoiceResponse,
+    ItcEligibility, PurchaseInvoice, PurchaseInvoiceDb, PurchaseInvoiceLine, PurchaseTransferDb,
+    PURCHASE_INVOICE_TRANSFER_CODE,
+};
+use crate::masters::business_entity_master::business_entity_models::BusinessEntityDto;
+use crate::masters::business_entity_master::business_entity_service::BusinessEntityService;
+use crate::masters::product_item_master::product_item_models::ProductItemResponse;
+use crate::masters::product_item_master::product_item_service::ProductItemService;
+
+#[derive(Debug, Error)]
+pub enum PurchaseInvoiceServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("purchase invoice {0} not found")]
+    PurchaseInvoiceNotFound(Uuid),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PurchaseInvoiceService: Send + Sync {
+    ///tax is computed with the same engine as sales invoices, the ledger transfers are posted
+    ///along with the purchase invoice
+    async fn create_purchase_invoice(
+        &self,
+        req: CreatePurchaseInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreatePurchaseInvoiceResponse, PurchaseInvoiceServiceError>;
+    async fn get_purchase_invoice(
+        &self,
+        purchase_invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<PurchaseInvoice, PurchaseInvoiceServiceError>;
+}
+
+struct PurchaseInvoiceServiceImpl {
+    dao: Arc<dyn PurchaseInvoiceDao>,
+    business_entity_service: Arc<dyn BusinessEntityService>,
+    product_item_service: Arc<dyn ProductItemService>,
+    currency_service: Arc<dyn CurrencyService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+}
+
+pub fn get_purchase_invoice_service(
+    arc: Arc<Pool>,
+    business_entity_service: Arc<dyn BusinessEntityService>,
+    product_item_service: Arc<dyn ProductItemService>,
+    currency_service: Arc<dyn CurrencyService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+) -> Arc<dyn PurchaseInvoiceService> {
+    let dao = get_purchase_invoice_dao(arc);
+    let service = PurchaseInvoiceServiceImpl {
+        dao,
+        business_entity_service,
+        product_item_service,
+        currency_service,
+        invoicing_series_service,
+    };
+    Arc::new(service)
+}
+
+///gst state code from the gstin if registered, else from the state of the address
+fn state_code_of(entity: &BusinessEntityDto) -> Option<&str> {
+    entity
+        .business_entity
+        .entity_type
+        .extract_gstin()
+        .and_then(|a| a.get_str().get(0..2))
+        .or_else(|| entity.address.as_ref().map(|a| a.state.state_code.as_str()))
+}
+
+fn validate_purchase_invoice_request(req: &CreatePurchaseInvoiceRequest, errors: &mut Vec<String>) {
+    if req.lines.is_empty() {
+        errors.push("atleast one purchase invoice line is required".to_string());
+    }
+    if req.supplier_id == req.recipient_id {
+        errors.push("supplier id and recipient id cannot be same".to_string());
+    }
+    if req
+        .purchase_invoice_date
+        .get_date()
+        .cmp(&current_indian_date())
+        == Ordering::Greater
+    {
+        errors.push("purchase invoice date cannot be of future".to_string());
+    }
+    if req.reverse_charge && req.reverse_charge_tax_accounts.is_none() {
+        errors.push(
+            "reverse charge tax accounts are required for reverse charge purchases".to_string(),
+        );
+    }
+}
+
+///taxable value and tax of the line as computed for a sales invoice line of the same product
+pub(crate) fn compute_purchase_line(
+    line_no: i16,
+    req: &CreatePurchaseInvoiceLineRequest,
+    product: Arc<ProductItemResponse>,
+    igst_applicable: bool,
+) -> anyhow::Result<PurchaseInvoiceLine> {
+    let tax_percentage = product.get_tax_rate()?.tax_rate_percentage.inner();
+    let hsn_sac_code = product.hsn_sac_code.as_str().to_string();
+    let line = CreateInvoiceLineRequestWithAllDetails {
+        product_item_id: product,
+        quantity: req.quantity.clone(),
+        free_quantity: FreeLineQuantity::new(0.0, req.quantity.get_uom().clone())?,
+        unit_price: req.unit_price.clone(),
+        discount_percentage: req.discount_percentage.clone(),
+        mrp: None,
+        batch_no: None,
+        expiry_date: None,
+        reverse_charge_applicable: false,
+        no_tax_charged: false,
+    };
+    let tax = line.tax_amount()?;
+    let (igst_amount, cgst_amount, sgst_amount) = if igst_applicable {
+        (tax, 0.0, 0.0)
+    } else {
+        (0.0, tax / 2.0, tax / 2.0)
+    };
+    Ok(PurchaseInvoiceLine {
+        line_no,
+        product_item_id: req.product_item_id,
+        hsn_sac_code,
+        quantity: req.quantity.get_quantity(),
+        uqc: req.quantity.uom_as_str().to_string(),
+        unit_price: req.unit_price.inner(),
+        discount_percentage: req.discount_percentage.inner(),
+        tax_percentage,
+        taxable_amount: line.taxable_amount()?,
+        igst_amount,
+        cgst_amount,
+        sgst_amount,
+        cess_amount: line.cess_amount()?,
+        itc_eligibility: req.itc_eligibility,
+        debit_account_id: req.debit_account_id,
+    })
+}
+
+fn add_transfer(
+    transfers: &mut Vec<PurchaseTransferDb>,
+    debit_account_id: Uuid,
+    credit_account_id: Uuid,
+    amount: f64,
+) {
+    if amount == 0.0 {
+        return;
+    }
+    match transfers.iter_mut().find(|a| {
+        a.debit_account_id == debit_account_id && a.credit_account_id == credit_account_id
+    }) {
+        Some(transfer) => transfer.amount += amount,
+        None => transfers.push(PurchaseTransferDb {
+            debit_account_id,
+            credit_account_id,
+            amount,
+        }),
+    }
+}
+
+///the line account is debited with the taxable value against the supplier. eligible itc is debited
+///to the input tax accounts, other tax is added to the cost of the line. tax under reverse charge
+///is credited to the reverse charge tax accounts as it is paid to the government, not the supplier
+pub(crate) fn compute_purchase_transfers(
+    req: &CreatePurchaseInvoiceRequest,
+    lines: &[PurchaseInvoiceLine],
+) -> anyhow::Result<Vec<PurchaseTransferDb>> {
+    let tax_credit_accounts = if req.reverse_charge {
+        Some(
+            req.reverse_charge_tax_accounts
+                .as_ref()
+                .context("reverse charge tax accounts are required for reverse charge purchases")?,
+        )
+    } else {
+        None
+    };
+    let mut transfers = Vec::new();
+    for line in lines {
+        add_transfer(
+            &mut transfers,
+            line.debit_account_id,
+            req.payable_account_id,
+            line.taxable_amount,
+        );
+        let heads = [
+            (
+                line.igst_amount,
+                req.input_tax_accounts.igst_account_id,
+                tax_credit_accounts.map(|a| a.igst_account_id),
+            ),
+            (
+                line.cgst_amount,
+                req.input_tax_accounts.cgst_account_id,
+                tax_credit_accounts.map(|a| a.cgst_account_id),
+            ),
+            (
+                line.sgst_amount,
+                req.input_tax_accounts.sgst_account_id,
+                tax_credit_accounts.map(|a| a.sgst_account_id),
+            ),
+            (
+                line.cess_amount,
+                req.input_tax_accounts.cess_account_id,
+                tax_credit_accounts.map(|a| a.cess_account_id),
+            ),
+        ];
+        for (amount, input_account_id, reverse_charge_account_id) in heads {
+            let debit_account_id = if line.itc_eligibility == ItcEligibility::Eligible {
+                input_account_id
+            } else {
+                line.debit_account_id
+            };
+            let credit_account_id = reverse_charge_account_id.unwrap_or(req.payable_account_id);
+            add_transfer(&mut transfers, debit_account_id, credit_account_id, amount);
+        }
+    }
+    Ok(transfers)
+}
+
+impl PurchaseInvoiceServiceImpl {
+    async fn get_business_entity(
+        &self,
+        id: Uuid,
+        tenant_id: Uuid,
+        name: &str,
+        errors: &mut Vec<String>,
+    ) -> anyhow::Result<Option<Arc<BusinessEntityDto>>> {
+        let entity = self
+            .business_entity_service
+            .get_business_entity_by_id(&id, &tenant_id)
+            .await
+            .with_context(|| format!("error while fetching {}", name))?;
+        if entity.is_none() {
+            errors.push(format!("{} id does not exists for this tenant id", name));
+        }
+        Ok(entity)
+    }
+
+    async fn get_products(
+        &self,
+        req: &CreatePurchaseInvoiceRequest,
+        tenant_id: Uuid,
+        errors: &mut Vec<String>,
+    ) -> anyhow::Result<HashMap<Uuid, Arc<ProductItemResponse>>> {
+        let product_ids = req.lines.iter().map(|a| a.product_item_id).collect();
+        let products: HashMap<Uuid, Arc<ProductItemResponse>> = self
+            .product_item_service
+            .get_products(product_ids, tenant_id)
+            .await
+            .context("error while fetching products")?
+            .into_iter()
+            .map(|a| (a.base_master_fields.id, a))
+            .collect();
+        for line in req.lines.iter() {
+            if !products.contains_key(&line.product_item_id) {
+                errors.push(format!(
+                    "product id {} not found in system",
+                    line.product_item_id
+                ));
+            }
+        }
+        Ok(products)
+    }
+
+    ///the self invoice series is needed only for reverse charge purchases from unregistered suppliers
+    async fn validate_self_invoice_series(
+        &self,
+        req: &CreatePurchaseInvoiceRequest,
+        supplier_registered: bool,
+        tenant_id: Uuid,
+        errors: &mut Vec<String>,
+    ) -> anyhow::Result<()> {
+        if supplier_registered || !req.reverse_charge {
+            if req.self_invoice_series_mst_id.is_some() {
+                errors.push(
+                    "self invoice is raised only for reverse charge purchases from unregistered suppliers"
+                        .to_string(),
+                );
+            }
+            return Ok(());
+        }
+        let Some(series_id) = req.self_invoice_series_mst_id else {
+            errors.push(
+                "self invoice series id is required for reverse charge purchases from unregistered suppliers"
+                    .to_string(),
+            );
+            return Ok(());
+        };
+        let valid = self
+            .invoicing_series_service
+            .is_valid_invoicing_series_id(series_id, InvoicingSeriesType::TaxInvoice, tenant_id)
+            .await
+            .context("error during self invoice series validation")?;
+        if !valid {
+            errors.push("self invoice series id does not exists for this tenant id".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PurchaseInvoiceService for PurchaseInvoiceServiceImpl {
+    async fn create_purchase_invoice(
+        &self,
+        req: CreatePurchaseInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreatePurchaseInvoiceResponse, PurchaseInvoiceServiceError> {
+        let mut errors = Vec::new();
+        validate_purchase_invoice_request(&req, &mut errors);
+        let currency = self
+            .currency_service
+            .get_currency_entry(req.currency_id, tenant_id)
+            .await
+            .context("error while fetching currency")?;
+        if currency.is_none() {
+            errors.push("currency id does not exists for this tenant id".to_string());
+        }
+        let supplier = self
+            .get_business_entity(req.supplier_id, tenant_id, "supplier", &mut errors)
+            .await?;
+        let recipient = self
+            .get_business_entity(req.recipient_id, tenant_id, "recipient", &mut errors)
+            .await?;
+        let products = self.get_products(&req, tenant_id, &mut errors).await?;
+        let (Some(supplier), Some(recipient)) = (supplier, recipient) else {
+            return Err(PurchaseInvoiceServiceError::Validation(errors));
+        };
+        let supplier_registered = supplier
+            .business_entity
+            .entity_type
+            .extract_gstin()
+            .is_some();
+        if supplier_registered && req.supplier_invoice_number.is_none() {
+            errors.push("supplier invoice number is required for registered suppliers".to_string());
+        }
+        self.validate_self_invoice_series(&req, supplier_registered, tenant_id, &mut errors)
+            .await?;
+        let recipient_state = recipient
+            .business_entity
+            .entity_type
+            .extract_gstin()
+            .and_then(|a| a.get_str().get(0..2));
+        if recipient_state.is_none() {
+            errors.push(
+                "recipient must be registered under gst to take input tax credit".to_string(),
+            );
+        }
+        //inter-state suppliers must be registered, so an unregistered supplier without
+        //an address is taken to be in the state of the recipient
+        let supplier_state = state_code_of(&supplier).or(recipient_state);
+        if !errors.is_empty() {
+            return Err(PurchaseInvoiceServiceError::Validation(errors));
+        }
+        let igst_applicable = supplier_state != recipient_state;
+        let lines = req
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| {
+                compute_purchase_line(
+                    idx as i16 + 1,
+                    line,
+                    products[&line.product_item_id].clone(),
+                    igst_applicable,
+                )
+            })
+            .collect::<anyhow::Result<Vec<PurchaseInvoiceLine>>>()?;
+        let total_taxable_amount: f64 = lines.iter().map(|a| a.taxable_amount).sum();
+        let total_tax_amount: f64 = lines.iter().map(|a| a.tax_amount()).sum();
+        if !supplier_registered && !req.reverse_charge && total_tax_amount != 0.0 {
+            return Err(PurchaseInvoiceServiceError::Validation(vec![
+                "unregistered supplier cannot charge tax, purchase should be under reverse charge"
+                    .to_string(),
+            ]));
+        }
+        let total_itc_amount: f64 = lines
+            .iter()
+            .filter(|a| a.itc_eligibility == ItcEligibility::Eligible)
+            .map(|a| a.tax_amount())
+            .sum();
+        let total_payable_amount = if req.reverse_charge {
+            total_taxable_amount
+        } else {
+            total_taxable_amount + total_tax_amount
+        };
+        let transfers = compute_purchase_transfers(&req, &lines)?;
+        let db = PurchaseInvoiceDb {
+            idempotence_key: req.idempotence_key,
+            tenant_id,
+            supplier_id: req.supplier_id,
+            recipient_id: req.recipient_id,
+            supplier_invoice_number: req.supplier_invoice_number.as_ref().map(|a| a.inner()),
+            self_invoice_series_mst_id: req.self_invoice_series_mst_id,
+            purchase_invoice_date_ms: req
+                .purchase_invoice_date
+                .epoch_millis()
+                .context("invalid purchase invoice date")?,
+            financial_year: req.purchase_invoice_date.financial_year(),
+            currency_id: req.currency_id,
+            reverse_charge: req.reverse_charge,
+            igst_applicable,
+            lines: lines.iter().map(|a| a.into()).collect(),
+            total_taxable_amount,
+            total_tax_amount,
+            total_itc_amount,
+            total_payable_amount,
+            payable_account_id: req.payable_account_id,
+            transfers,
+            transfer_code: PURCHASE_INVOICE_TRANSFER_CODE,
+            remarks: req.remarks.as_ref().map(|a| a.get_str()),
+            created_by: user_id,
+        };
+        let resp = self.dao.create_purchase_invoice(&db).await?;
+        Ok(resp)
+    }
+
+    async fn get_purchase_invoice(
+        &self,
+        purchase_invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<PurchaseInvoice, PurchaseInvoiceServiceError> {
+        self.dao
+            .get_purchase_invoice_by_id(purchase_invoice_id, tenant_id)
+            .await?
+            .ok_or(PurchaseInvoiceServiceError::PurchaseInvoiceNotFound(
+                purchase_invoice_id,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::currency::currency_models::tests::a_currency_master;
+    use crate::accounting::currency::currency_service::MockCurrencyService;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::gst_returns::gstr3b::gstr3b_models::tests::a_gst_ledger_accounts;
+    use crate::invoicing::invoicing_series::invoicing_series_service::MockInvoicingSeriesService;
+    use crate::invoicing::purchase_invoice::purchase_invoice_dao::MockPurchaseInvoiceDao;
+    use crate::invoicing::purchase_invoice::purchase_invoice_models::tests::{
+        a_create_purchase_invoice_line_request, a_create_purchase_invoice_request,
+    };
+    use crate::invoicing::purchase_invoice::purchase_invoice_models::{
+        CreatePurchaseInvoiceLineRequestBuilder, CreatePurchaseInvoiceRequestBuilder,
+        CreatePurchaseInvoiceResponse, ItcEligibility,
+    };
+    use crate::invoicing::purchase_invoice::purchase_invoice_service::{
+        compute_purchase_line, compute_purchase_transfers, PurchaseInvoiceService,
+        PurchaseInvoiceServiceError, PurchaseInvoiceServiceImpl,
+    };
+    use crate::masters::business_entity_master::business_entity_models::tests::a_business_entity_master;
+    use crate::masters::business_entity_master::business_entity_models::{
+        BusinessEntityDto, BusinessEntityMasterBuilder, BusinessEntityType,
+    };
+    use crate::masters::business_entity_master::business_entity_service::MockBusinessEntityService;
+    use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
+    use crate::masters::product_item_master::product_item_models::tests::a_product_item_response;
+    use crate::masters::product_item_master::product_item_service::MockProductItemService;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_business_entity_dto(gstin: Option<GstinNo>) -> Arc<BusinessEntityDto> {
+        let entity_type = match gstin {
+            Some(gstin) => BusinessEntityType::EligibleSupplier {
+                name: Default::default(),
+                email: Default::default(),
+                phone: Default::default(),
+                address_id: Default::default(),
+                gstin,
+            },
+            None => BusinessEntityType::Other {
+                name: Default::default(),
+                email: None,
+                phone: Default::default(),
+                address_id: None,
+                gstin: None,
+            },
+        };
+        let mut builder = BusinessEntityMasterBuilder::default();
+        builder.entity_type(entity_type);
+        Arc::new(BusinessEntityDto {
+            business_entity: a_business_entity_master(builder),
+            address: None,
+        })
+    }
+
+    fn a_service(
+        dao: MockPurchaseInvoiceDao,
+        supplier: Arc<BusinessEntityDto>,
+    ) -> PurchaseInvoiceServiceImpl {
+        let req = a_create_purchase_invoice_request(Default::default());
+        let supplier_id = req.supplier_id;
+        let mut business_entity_service = MockBusinessEntityService::new();
+        business_entity_service
+            .expect_get_business_entity_by_id()
+            .returning(move |id, _| {
+                if *id == supplier_id {
+                    Ok(Some(supplier.clone()))
+                } else {
+                    Ok(Some(a_business_entity_dto(Some(GstinNo::default()))))
+                }
+            });
+        let mut product_item_service = MockProductItemService::new();
+        product_item_service
+            .expect_get_products()
+            .returning(|ids, _| {
+                Ok(ids
+                    .into_iter()
+                    .map(|id| {
+                        let mut product = a_product_item_response(Default::default());
+                        product.base_master_fields.id = id;
+                        Arc::new(product)
+                    })
+                    .collect())
+            });
+        let mut currency_service = MockCurrencyService::new();
+        currency_service
+            .expect_get_currency_entry()
+            .returning(|_, _| Ok(Some(Arc::new(a_currency_master(Default::default())))));
+        let mut invoicing_series_service = MockInvoicingSeriesService::new();
+        invoicing_series_service
+            .expect_is_valid_invoicing_series_id()
+            .returning(|_, _, _| Ok(true));
+        PurchaseInvoiceServiceImpl {
+            dao: Arc::new(dao),
+            business_entity_service: Arc::new(business_entity_service),
+            product_item_service: Arc::new(product_item_service),
+            currency_service: Arc::new(currency_service),
+            invoicing_series_service: Arc::new(invoicing_series_service),
+        }
+    }
+
+    #[test]
+    fn test_compute_purchase_line_splits_tax_by_state() {
+        let req = a_create_purchase_invoice_line_request(Default::default());
+        let product = Arc::new(a_product_item_response(Default::default()));
+        let inter_state = compute_purchase_line(1, &req, product.clone(), true).unwrap();
+        let intra_state = compute_purchase_line(1, &req, product, false).unwrap();
+        assert_eq!(inter_state.taxable_amount, intra_state.taxable_amount);
+        assert_eq!(inter_state.cgst_amount, 0.0);
+        assert_eq!(intra_state.igst_amount, 0.0);
+        assert_eq!(intra_state.cgst_amount, intra_state.sgst_amount);
+        assert_eq!(inter_state.tax_amount(), intra_state.tax_amount());
+    }
+
+    #[test]
+    fn test_compute_purchase_transfers() {
+        let debit_account_id = Uuid::now_v7();
+        let mut line_builder = CreatePurchaseInvoiceLineRequestBuilder::default();
+        line_builder.debit_account_id(debit_account_id);
+        let line_req = a_create_purchase_invoice_line_request(line_builder);
+        let mut blocked_builder = CreatePurchaseInvoiceLineRequestBuilder::default();
+        blocked_builder
+            .debit_account_id(debit_account_id)
+            .itc_eligibility(ItcEligibility::Blocked);
+        let blocked_req = a_create_purchase_invoice_line_request(blocked_builder);
+        let mut builder = CreatePurchaseInvoiceRequestBuilder::default();
+        builder.lines(vec![line_req.clone(), blocked_req.clone()]);
+        let req = a_create_purchase_invoice_request(builder);
+        let product = Arc::new(a_product_item_response(Default::default()));
+        let lines = vec![
+            compute_purchase_line(1, &line_req, product.clone(), true).unwrap(),
+            compute_purchase_line(2, &blocked_req, product, true).unwrap(),
+        ];
+        let transfers = compute_purchase_transfers(&req, &lines).unwrap();
+        //taxable value and blocked tax to the line account, eligible igst to the input account
+        assert_that!(transfers).has_length(2);
+        let line_total = lines[0].taxable_amount + lines[1].taxable_amount + lines[1].igst_amount;
+        assert_eq!(transfers[0].debit_account_id, debit_account_id);
+        assert_eq!(transfers[0].amount, line_total);
+        assert_eq!(
+            transfers[1].debit_account_id,
+            req.input_tax_accounts.igst_account_id
+        );
+        assert_eq!(transfers[1].amount, lines[0].igst_amount);
+        assert!(transfers
+            .iter()
+            .all(|a| a.credit_account_id == req.payable_account_id));
+    }
+
+    #[test]
+    fn test_reverse_charge_tax_is_credited_to_reverse_charge_accounts() {
+        let rcm_accounts = a_gst_ledger_accounts();
+        let mut builder = CreatePurchaseInvoiceRequestBuilder::default();
+        builder
+            .reverse_charge(true)
+            .reverse_charge_tax_accounts(Some(rcm_accounts));
+        let req = a_create_purchase_invoice_request(builder);
+        let product = Arc::new(a_product_item_response(Default::default()));
+        let lines = vec![compute_purchase_line(1, &req.lines[0], product, false).unwrap()];
+        let transfers = compute_purchase_transfers(&req, &lines).unwrap();
+        assert_that!(transfers).has_length(3);
+        assert_eq!(
+            transfers[1].debit_account_id,
+            req.input_tax_accounts.cgst_account_id
+        );
+        assert_eq!(transfers[1].credit_account_id, rcm_accounts.cgst_account_id);
+        assert_eq!(transfers[2].credit_account_id, rcm_accounts.sgst_account_id);
+    }
+
+    #[tokio::test]
+    async fn test_registered_supplier_requires_invoice_number() {
+        let mut dao = MockPurchaseInvoiceDao::new();
+        dao.expect_create_purchase_invoice().never();
+        let service = a_service(dao, a_business_entity_dto(Some(GstinNo::default())));
+        let mut builder = CreatePurchaseInvoiceRequestBuilder::default();
+        builder.supplier_invoice_number(None);
+        let resp = service
+            .create_purchase_invoice(
+                a_create_purchase_invoice_request(builder),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(resp, Err(PurchaseInvoiceServiceError::Validation(a)) if a.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_supplier_cannot_charge_tax() {
+        let mut dao = MockPurchaseInvoiceDao::new();
+        dao.expect_create_purchase_invoice().never();
+        let service = a_service(dao, a_business_entity_dto(None));
+        let resp = service
+            .create_purchase_invoice(
+                a_create_purchase_invoice_request(Default::default()),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(PurchaseInvoiceServiceError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reverse_charge_purchase_from_unregistered_supplier() {
+        let series_id = Uuid::now_v7();
+        let mut dao = MockPurchaseInvoiceDao::new();
+        dao.expect_create_purchase_invoice()
+            .withf(move |db| {
+                db.self_invoice_series_mst_id == Some(series_id)
+                    && db.total_payable_amount == db.total_taxable_amount
+                    && db.total_itc_amount == db.total_tax_amount
+            })
+            .returning(|_| {
+                Ok(CreatePurchaseInvoiceResponse {
+                    purchase_invoice_id: Uuid::now_v7(),
+                    self_invoice_number: Some("SI1".to_string()),
+                })
+            });
+        let service = a_service(dao, a_business_entity_dto(None));
+        let mut builder = CreatePurchaseInvoiceRequestBuilder::default();
+        builder
+            .supplier_invoice_number(None)
+            .reverse_charge(true)
+            .self_invoice_series_mst_id(Some(series_id))
+            .reverse_charge_tax_accounts(Some(a_gst_ledger_accounts()));
+        let resp = service
+            .create_purchase_invoice(
+                a_create_purchase_invoice_request(builder),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.self_invoice_number.as_deref(), Some("SI1"));
+    }
+}
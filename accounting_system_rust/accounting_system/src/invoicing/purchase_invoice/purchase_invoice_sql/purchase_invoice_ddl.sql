@@ -0,0 +1,53 @@
+create type itc_eligibility as enum ('eligible','blocked','ineligible');
+
+create table purchase_invoice
+(
+    id                        uuid primary key,
+    entity_version_id         integer default 0,
+    tenant_id                 uuid references tenant (id)            not null,
+    active                    bool,
+    approval_status           smallint                               not null,
+    remarks                   varchar(100),
+    supplier_business_entity  uuid references business_entity (id)   not null,
+    recipient_business_entity uuid references business_entity (id)   not null,--registered entity of the tenant taking the itc
+    supplier_invoice_number   varchar(16),
+    self_invoice_number       varchar(20),--raised by the recipient for reverse charge purchases from unregistered suppliers
+    purchase_invoice_date_ms  bigint                                 not null,
+    financial_year            smallint                               not null,
+    currency_id               uuid references currency_master (id)   not null,
+    reverse_charge            bool                                   not null,
+    igst_applicable           bool                                   not null,
+    total_taxable_amount      double precision                       not null,
+    total_tax_amount          double precision                       not null,
+    total_itc_amount          double precision                       not null,
+    total_payable_amount      double precision                       not null,--payable to the supplier
+    payable_account_id        uuid references user_account (id)      not null,--supplier account credited by this purchase
+    created_by                uuid references app_user (id)          not null,
+    updated_by                uuid references app_user (id),
+    created_at                bigint  default extract(epoch from now()) * 1000000,
+    updated_at                bigint  default extract(epoch from now()) * 1000000
+);
+
+create table purchase_invoice_line
+(
+    id                  uuid primary key,
+    tenant_id           uuid references tenant (id)           not null,
+    purchase_invoice_id uuid references purchase_invoice (id) not null,
+    line_number         smallint                              not null,
+    product_item_id     uuid references product_item (id)     not null,
+    hsn_sac_code        varchar(10)                           not null,
+    quantity            double precision                      not null,
+    uqc                 varchar(15)                           not null,
+    unit_price          double precision                      not null,
+    discount_percentage real                                  not null,
+    tax_percentage      real                                  not null,
+    taxable_amount      double precision                      not null,
+    igst_amount         double precision                      not null,
+    cgst_amount         double precision                      not null,
+    sgst_amount         double precision                      not null,
+    cess_amount         double precision                      not null,
+    itc_eligibility     itc_eligibility                       not null,
+    debit_account_id    uuid references user_account (id)     not null,--expense, asset or inventory account of the line
+    created_by          uuid references app_user (id)         not null,
+    created_at          bigint default extract(epoch from now()) * 1000000
+);
@@ -0,0 +1,162 @@
+create type create_purchase_invoice_line_request as
+(
+    line_no             smallint,
+    product_item_id     uuid,
+    hsn_sac_code        text,
+    quantity            double precision,
+    uqc                 text,
+    unit_price          double precision,
+    discount_percentage real,
+    tax_percentage      real,
+    taxable_amount      double precision,
+    igst_amount         double precision,
+    cgst_amount         double precision,
+    sgst_amount         double precision,
+    cess_amount         double precision,
+    itc_eligibility     itc_eligibility,
+    debit_account_id    uuid
+);
+
+create type create_purchase_invoice_transfer_request as
+(
+    debit_account_id  uuid,
+    credit_account_id uuid,
+    amount            double precision
+);
+
+create type create_purchase_invoice_request as
+(
+    idempotence_key            uuid,
+    tenant_id                  uuid,
+    supplier_id                uuid,
+    recipient_id               uuid,
+    supplier_invoice_number    text,
+    self_invoice_series_mst_id uuid,
+    purchase_invoice_date_ms   bigint,
+    financial_year             smallint,
+    currency_id                uuid,
+    reverse_charge             bool,
+    igst_applicable            bool,
+    lines                      create_purchase_invoice_line_request[],
+    total_taxable_amount       double precision,
+    total_tax_amount           double precision,
+    total_itc_amount           double precision,
+    total_payable_amount       double precision,
+    payable_account_id         uuid,
+    transfers                  create_purchase_invoice_transfer_request[],
+    transfer_code              smallint,
+    remarks                    text,
+    created_by                 uuid
+);
+
+--amount is converted to minor units as per the currency scale
+create or replace procedure post_purchase_invoice_ledger_transfer(_tenant_id uuid, _purchase_invoice_id uuid,
+                                                                  _debit_account_id uuid,
+                                                                  _credit_account_id uuid,
+                                                                  _currency_id uuid, _code smallint,
+                                                                  _amount double precision, _date_ms bigint) as
+$$
+DECLARE
+    txn        transfer;
+    result     jsonb;
+    ledger_id  uuid;
+    curr_scale smallint;
+BEGIN
+    select ledger_master_id
+    from user_account
+    where id = _debit_account_id
+      and tenant_id = _tenant_id
+    into ledger_id;
+    select scale from currency_master where id = _currency_id into curr_scale;
+    txn := row (uuid_generate_v7(), _tenant_id, _purchase_invoice_id, _purchase_invoice_id, _debit_account_id,
+        _credit_account_id, null, ledger_id, _code,
+        round((_amount * power(10, coalesce(curr_scale, 0)))::numeric)::bigint, 'purchase invoice', 1,
+        _date_ms * 1000)::transfer;
+    result := json_build_object('txn_id', txn.id, 'committed', true, 'reason', '[]'::jsonb);
+    call create_ledger_transfer(txn, result);
+    if (result -> 'committed')::boolean = false then
+        raise exception 'ledger transfer for purchase invoice % failed %', _purchase_invoice_id,
+            result -> 'reason';
+    end if;
+end;
+$$ language plpgsql;
+
+--the self invoice is numbered from its series only when the series is given,
+--transfers are computed by the service and posted as is
+create or replace function create_purchase_invoice(req create_purchase_invoice_request) returns jsonb as
+$$
+DECLARE
+    resp                 jsonb;
+    impacted_rows        int;
+    _purchase_invoice_id uuid := uuid_generate_v7();
+    _self_invoice_number text;
+    line                 create_purchase_invoice_line_request;
+    trf                  create_purchase_invoice_transfer_request;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_purchase_invoice', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        if req.self_invoice_series_mst_id is not null then
+            select create_invoice_number(req.self_invoice_series_mst_id, req.financial_year, req.tenant_id,
+                                         req.created_by)
+            into _self_invoice_number;
+        end if;
+        insert into purchase_invoice (id, entity_version_id, tenant_id, active, approval_status, remarks,
+                                      supplier_business_entity, recipient_business_entity,
+                                      supplier_invoice_number, self_invoice_number, purchase_invoice_date_ms,
+                                      financial_year, currency_id, reverse_charge, igst_applicable,
+                                      total_taxable_amount, total_tax_amount, total_itc_amount,
+                                      total_payable_amount, payable_account_id, created_by, updated_by,
+                                      created_at, updated_at)
+        values (_purchase_invoice_id, 0, req.tenant_id, true, 1, req.remarks, req.supplier_id, req.recipient_id,
+                req.supplier_invoice_number, _self_invoice_number, req.purchase_invoice_date_ms,
+                req.financial_year, req.currency_id, req.reverse_charge, req.igst_applicable,
+                req.total_taxable_amount, req.total_tax_amount, req.total_itc_amount, req.total_payable_amount,
+                req.payable_account_id, req.created_by, req.created_by, default, default);
+        foreach line in array req.lines
+            loop
+                insert into purchase_invoice_line (id, tenant_id, purchase_invoice_id, line_number,
+                                                   product_item_id, hsn_sac_code, quantity, uqc, unit_price,
+                                                   discount_percentage, tax_percentage, taxable_amount,
+                                                   igst_amount, cgst_amount, sgst_amount, cess_amount,
+                                                   itc_eligibility, debit_account_id, created_by, created_at)
+                values (uuid_generate_v7(), req.tenant_id, _purchase_invoice_id, line.line_no,
+                        line.product_item_id, line.hsn_sac_code, line.quantity, line.uqc, line.unit_price,
+                        line.discount_percentage, line.tax_percentage, line.taxable_amount, line.igst_amount,
+                        line.cgst_amount, line.sgst_amount, line.cess_amount, line.itc_eligibility,
+                        line.debit_account_id, req.created_by, default);
+            end loop;
+        if req.transfers is not null then
+            foreach trf in array req.transfers
+                loop
+                    call post_purchase_invoice_ledger_transfer(req.tenant_id, _purchase_invoice_id,
+                                                               trf.debit_account_id, trf.credit_account_id,
+                                                               req.currency_id, req.transfer_code, trf.amount,
+                                                               req.purchase_invoice_date_ms);
+                end loop;
+        end if;
+        resp := jsonb_build_object('purchase_invoice_id', _purchase_invoice_id, 'self_invoice_number',
+                                   _self_invoice_number);
+        update idempotence_store
+        set response=resp
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_purchase_invoice';
+        return resp;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_purchase_invoice'
+        into resp;
+        return resp;
+    end if;
+end;
+$$ language plpgsql;
+
+create trigger purchase_invoice_audit_trigger
+    after update or delete
+    on purchase_invoice
+    for each row
+execute function create_audit_entry();
@@ -0,0 +1,8 @@
+create unique index if not exists purchase_invoice_supplier_invoice_number_idx on purchase_invoice (tenant_id,
+                                                                                                   supplier_business_entity,
+                                                                                                   financial_year,
+                                                                                                   upper(supplier_invoice_number))
+    where supplier_invoice_number is not null;
+create index if not exists purchase_invoice_recipient_date_idx on purchase_invoice (tenant_id, recipient_business_entity,
+                                                                                    purchase_invoice_date_ms);
+create index if not exists purchase_invoice_line_purchase_invoice_idx on purchase_invoice_line (tenant_id, purchase_invoice_id);
@@ -0,0 +1,5 @@
+mod quotation_dao;
+pub mod quotation_db_mapping;
+pub mod quotation_http_api;
+pub mod quotation_models;
+pub mod quotation_service;
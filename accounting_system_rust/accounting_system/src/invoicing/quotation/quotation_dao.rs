@@ -0,0 +1,359 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::quotation::quotation_models::{
+    CreateQuotationDbResponse, QuotationDb, QuotationDetail, QuotationType,
+};
+
+const CREATE_QUOTATION: &str = "select create_quotation($1)";
+
+const QUOTATION_QUERY: &str = "select id,quotation_type::text,quotation_number,quotation_date_ms,\
+valid_until_ms,total_taxable_amount,total_tax_amount,total_additional_charges_amount,\
+total_payable_amount,converted_invoice_id,quotation_request from quotation \
+where id=$1 and tenant_id=$2";
+
+const CLAIM_QUOTATION_FOR_CONVERSION: &str = "update quotation set conversion_idempotence_key=$3 \
+where tenant_id=$1 and id=$2 and converted_invoice_id is null \
+and (conversion_idempotence_key is null or conversion_idempotence_key=$3)";
+
+const RELEASE_QUOTATION_CLAIM: &str = "update quotation set conversion_idempotence_key=null \
+where tenant_id=$1 and id=$2 and converted_invoice_id is null and conversion_idempotence_key=$3";
+
+const MARK_QUOTATION_CONVERTED: &str = "select mark_quotation_converted($1,$2,$3,$4)";
+
+const IS_QUOTATION_PDF_CREATED: &str = "select exists(select 1 from quotation where tenant_id=$1 \
+and id=$2 and quotation_pdf_s3_id is not null)";
+
+const PERSIST_QUOTATION_PDF: &str =
+    "update quotation set quotation_pdf_s3_id=$3 where tenant_id=$1 and id=$2";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait QuotationDao: Send + Sync {
+    ///takes the number from the quotation series and stores the quotation
+    async fn create_quotation(
+        &self,
+        quotation_db: &QuotationDb,
+    ) -> Result<CreateQuotationDbResponse, DaoError>;
+    async fn get_quotation_by_id(
+        &self,
+        quotation_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<QuotationDetail>, DaoError>;
+    ///reserves the quotation for the conversion with this idempotence key before the invoice is created, returns
+    /// false if it is converted or another conversion holds it
+    async fn claim_quotation_for_conversion(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        idempotence_key: Uuid,
+    ) -> Result<bool, DaoError>;
+    ///frees the claim of a conversion which failed before the invoice was linked
+    async fn release_quotation_claim(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        idempotence_key: Uuid,
+    ) -> Result<(), DaoError>;
+    ///links the invoice to the quotation, returns false if it is linked to another invoice
+    async fn mark_quotation_converted(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        invoice_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn is_quotation_pdf_created(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn persist_quotation_pdf_dtl(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        pdf_key: &str,
+    ) -> Result<(), DaoError>;
+}
+
+struct QuotationDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_quotation_dao(arc: Arc<Pool>) -> Arc<dyn QuotationDao> {
+    let dao = QuotationDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for QuotationDetail {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let quotation_type: &str = row.get(1);
+        let request: serde_json::Value = row.get(10);
+        Ok(QuotationDetail {
+            id: row.get(0),
+            quotation_type: QuotationType::from_db_str(quotation_type)?,
+            quotation_number: row.get(2),
+            quotation_date_ms: row.get(3),
+            valid_until_ms: row.get(4),
+            total_taxable_amount: row.get(5),
+            total_tax_amount: row.get(6),
+            total_additional_charges_amount: row.get(7),
+            total_payable_amount: row.get(8),
+            converted_invoice_id: row.get(9),
+            request: serde_json::from_value(request)
+                .context("could not deserialize stored quotation request")?,
+        })
+    }
+}
+
+#[async_trait]
+impl QuotationDao for QuotationDaoImpl {
+    async fn create_quotation(
+        &self,
+        quotation_db: &QuotationDb,
+    ) -> Result<CreateQuotationDbResponse, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_QUOTATION, &[quotation_db])
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let resp = serde_json::from_value(json).context("invalid create quotation response")?;
+        Ok(resp)
+    }
+
+    async fn get_quotation_by_id(
+        &self,
+        quotation_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<QuotationDetail>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query_opt(QUOTATION_QUERY, &[&quotation_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()
+    }
+
+    async fn claim_quotation_for_conversion(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        idempotence_key: Uuid,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(
+                CLAIM_QUOTATION_FOR_CONVERSION,
+                &[&tenant_id, &quotation_id, &idempotence_key],
+            )
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn release_quotation_claim(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        idempotence_key: Uuid,
+    ) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(
+                RELEASE_QUOTATION_CLAIM,
+                &[&tenant_id, &quotation_id, &idempotence_key],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_quotation_converted(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        invoice_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(
+                MARK_QUOTATION_CONVERTED,
+                &[&tenant_id, &quotation_id, &invoice_id, &user_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn is_quotation_pdf_created(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(IS_QUOTATION_PDF_CREATED, &[&tenant_id, &quotation_id])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn persist_quotation_pdf_dtl(
+        &self,
+        tenant_id: Uuid,
+        quotation_id: Uuid,
+        pdf_key: &str,
+    ) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(
+                PERSIST_QUOTATION_PDF,
+                &[&tenant_id, &quotation_id, &pdf_key],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::boolean::BooleanAssertions;
+    use speculoos::option::OptionAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::invoicing::quotation::quotation_dao::{QuotationDao, QuotationDaoImpl};
+    use crate::invoicing::quotation::quotation_models::tests::a_create_quotation_request;
+    use crate::invoicing::quotation::quotation_models::{QuotationDb, QuotationType};
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_quotation_db(idempotence_key: Uuid) -> QuotationDb {
+        let req = a_create_quotation_request(Default::default());
+        QuotationDb {
+            idempotence_key,
+            tenant_id: *SEED_TENANT_ID,
+            quotation_type: QuotationType::ProformaInvoice,
+            quotation_series_mst_id: req.quotation_series_mst_id,
+            financial_year: 2024,
+            quotation_date_ms: 0,
+            valid_until_ms: None,
+            currency_id: req.currency_id,
+            supplier_id: req.supplier_id,
+            billed_to_customer_id: req
+                .bill_ship_detail
+                .as_ref()
+                .map(|a| a.billed_to_customer_id),
+            total_taxable_amount: 20.0,
+            total_tax_amount: 0.0,
+            total_additional_charges_amount: 0.0,
+            total_payable_amount: 20.0,
+            quotation_request: serde_json::to_value(&req).unwrap(),
+            created_by: *SEED_USER_ID,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_quotation_is_idempotent() {
+        let dao = get_dao_generic(|c| QuotationDaoImpl { postgres_client: c }, None).await;
+        let db = a_quotation_db(Uuid::now_v7());
+        let first = dao.create_quotation(&db).await.unwrap();
+        let second = dao.create_quotation(&db).await.unwrap();
+        assert_that!(second).is_equal_to(&first);
+        let fetched = dao
+            .get_quotation_by_id(first.quotation_id, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(fetched.map(|a| a.quotation_number))
+            .is_some()
+            .is_equal_to(first.quotation_number);
+    }
+
+    #[tokio::test]
+    async fn test_quotation_is_converted_only_once() {
+        let dao = get_dao_generic(|c| QuotationDaoImpl { postgres_client: c }, None).await;
+        let created = dao
+            .create_quotation(&a_quotation_db(Uuid::now_v7()))
+            .await
+            .unwrap();
+        let linked = dao
+            .mark_quotation_converted(
+                *SEED_TENANT_ID,
+                created.quotation_id,
+                *SEED_INVOICE_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(linked).is_true();
+        let relinked = dao
+            .mark_quotation_converted(
+                *SEED_TENANT_ID,
+                created.quotation_id,
+                *SEED_INVOICE_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(relinked).is_true();
+        let fetched = dao
+            .get_quotation_by_id(created.quotation_id, *SEED_TENANT_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_that!(fetched.converted_invoice_id)
+            .is_some()
+            .is_equal_to(*SEED_INVOICE_ID);
+    }
+
+    #[tokio::test]
+    async fn test_quotation_is_claimed_by_one_conversion_at_a_time() {
+        let dao = get_dao_generic(|c| QuotationDaoImpl { postgres_client: c }, None).await;
+        let created = dao
+            .create_quotation(&a_quotation_db(Uuid::now_v7()))
+            .await
+            .unwrap();
+        let (first, second) = (Uuid::now_v7(), Uuid::now_v7());
+        let claim = |key: Uuid| {
+            dao.claim_quotation_for_conversion(*SEED_TENANT_ID, created.quotation_id, key)
+        };
+        assert_that!(claim(first).await.unwrap()).is_true();
+        assert_that!(claim(first).await.unwrap()).is_true();
+        assert_that!(claim(second).await.unwrap()).is_false();
+        dao.release_quotation_claim(*SEED_TENANT_ID, created.quotation_id, first)
+            .await
+            .unwrap();
+        assert_that!(claim(second).await.unwrap()).is_true();
+        dao.mark_quotation_converted(
+            *SEED_TENANT_ID,
+            created.quotation_id,
+            *SEED_INVOICE_ID,
+            *SEED_USER_ID,
+        )
+        .await
+        .unwrap();
+        assert_that!(claim(second).await.unwrap()).is_false();
+    }
+}
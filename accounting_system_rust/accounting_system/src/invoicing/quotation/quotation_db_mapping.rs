@@ -0,0 +1,50 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct QuotationDbMapping {}
+
+const QUOTATION_DDL_SQL: &str = include_str!("./quotation_sql/quotation_ddl.sql");
+const QUOTATION_SEED_DATA: &str = include_str!("./quotation_sql/quotation.csv");
+const QUOTATION_INDEXES_SQL: &str = include_str!("./quotation_sql/quotation_indexes.sql");
+const QUOTATION_FUNCTIONS_SQL: &str =
+    include_str!("./quotation_sql/quotation_functions_and_procedures.sql");
+impl DbStructMapping for QuotationDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("quotation")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        QUOTATION_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        QUOTATION_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        QUOTATION_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        QUOTATION_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
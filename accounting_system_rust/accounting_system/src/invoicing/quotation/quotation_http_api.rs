@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::quotation::quotation_models::{
+    ConvertQuotationRequest, CreateQuotationRequest, QuotationPdfRequest,
+};
+use crate::invoicing::quotation::quotation_service::{QuotationService, QuotationServiceError};
+use crate::setup_routes;
+
+impl ResponseError for QuotationServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            QuotationServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            QuotationServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            QuotationServiceError::QuotationNotFound(_) => StatusCode::NOT_FOUND,
+            QuotationServiceError::AlreadyConverted(_) => StatusCode::CONFLICT,
+            QuotationServiceError::Invoicing(e) => e.status_code(),
+            QuotationServiceError::Approval(e) => e.status_code(),
+            QuotationServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn create_quotation(
+    data: Data<Arc<dyn QuotationService>>,
+    request: web::Json<CreateQuotationRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_quotation(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn create_quotation_pdf(
+    data: Data<Arc<dyn QuotationService>>,
+    request: web::Json<QuotationPdfRequest>,
+    _tenant_id: TenantId,
+    _user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data.create_quotation_pdf(request.into_inner()).await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_quotation(
+    data: Data<Arc<dyn QuotationService>>,
+    quotation_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_quotation(quotation_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn convert_quotation(
+    data: Data<Arc<dyn QuotationService>>,
+    quotation_id: Path<Uuid>,
+    request: web::Json<ConvertQuotationRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .convert_quotation(
+            quotation_id.into_inner(),
+            request.into_inner(),
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    QuotationService,
+    "/quotation",
+    "/create",
+    web::post().to(create_quotation),
+    "/create-pdf",
+    web::post().to(create_quotation_pdf),
+    "/id/{quotation_id}",
+    web::get().to(get_quotation),
+    "/id/{quotation_id}/convert",
+    web::post().to(convert_quotation)
+);
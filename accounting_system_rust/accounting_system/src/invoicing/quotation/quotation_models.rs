@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context};
+use chrono::{Datelike, NaiveDate};
+use derive_builder::Builder;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use invoice_doc_generator::invoice_line::line_quantity::LineQuantity;
+use invoice_doc_generator::invoice_line::unit_price::Price;
+use pdf_doc_generator::invoice_template::DocDate;
+use pdf_doc_generator::quotation_template::Quotation;
+
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoicing_request_models::{
+    BillShipDetail, CreateAdditionalChargeRequest, CreateInvoiceLineRequest, CreateInvoiceRequest,
+    InvoiceLedgerAccounts, InvoicePdfRequest, InvoiceRemarks, PaymentTermsValidated,
+    PurchaseOrderDate, PurchaseOrderNo,
+};
+
+///pre-sale documents sharing the invoice lines, neither of them is a tax invoice
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "quotation_type", rename_all = "snake_case")]
+pub enum QuotationType {
+    Quotation,
+    ProformaInvoice,
+}
+
+impl QuotationType {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let quotation_type = match value {
+            "quotation" => QuotationType::Quotation,
+            "proforma_invoice" => QuotationType::ProformaInvoice,
+            _ => bail!("{} is not a valid quotation type", value),
+        };
+        Ok(quotation_type)
+    }
+
+    ///heading printed on the document
+    pub fn title(&self) -> &'static str {
+        match self {
+            QuotationType::Quotation => "quotation",
+            QuotationType::ProformaInvoice => "proforma invoice",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct ValidUntilDate(NaiveDate);
+
+impl ValidUntilDate {
+    pub fn new(value: String) -> anyhow::Result<Self> {
+        let p = NaiveDate::parse_from_str(value.as_str(), "%Y-%m-%d")
+            .context("valid until date must be in yyyy-mm-dd format")?;
+        Ok(ValidUntilDate(p))
+    }
+    pub fn get_date(&self) -> &NaiveDate {
+        &self.0
+    }
+    pub fn epoch_millis(&self) -> Option<i64> {
+        self.0
+            .and_hms_milli_opt(0, 0, 0, 0)
+            .map(|a| a.and_utc().timestamp_millis())
+    }
+    pub fn to_doc_date(&self) -> DocDate {
+        DocDate {
+            month: self.0.month() as u16,
+            year: self.0.year() as u16,
+            day: self.0.day() as u16,
+        }
+    }
+}
+
+impl TryFrom<String> for ValidUntilDate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ValidUntilDate::new(value)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreateQuotationRequest {
+    pub idempotence_key: Uuid,
+    pub quotation_type: QuotationType,
+    ///numbers are taken from this series, it has to be different from the tax invoice series
+    pub quotation_series_mst_id: Uuid,
+    ///template of the tax invoice created on conversion
+    pub invoice_template_id: Uuid,
+    pub currency_id: Uuid,
+    pub service_invoice: bool,
+    pub b2b_invoice: bool,
+    pub supplier_id: Uuid,
+    ///if  none then same as that of supplier id
+    pub dispatch_from_id: Option<Uuid>,
+    pub bill_ship_detail: Option<BillShipDetail>,
+    pub payment_terms: Option<PaymentTermsValidated>,
+    pub valid_until: Option<ValidUntilDate>,
+    pub invoice_lines: Vec<CreateInvoiceLineRequest>,
+    pub additional_charges: Vec<CreateAdditionalChargeRequest>,
+    pub remarks: Option<InvoiceRemarks>,
+}
+
+impl CreateQuotationRequest {
+    ///applies the agreed changes to the quoted lines, all failures are returned together
+    pub fn apply_line_overrides(
+        &mut self,
+        overrides: &[QuotationLineOverride],
+    ) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+        let mut seen = HashSet::new();
+        for ov in overrides {
+            if ov.line_no == 0 || ov.line_no as usize > self.invoice_lines.len() {
+                errors.push(format!("line no {} not found in quotation", ov.line_no));
+            } else if !seen.insert(ov.line_no) {
+                errors.push(format!(
+                    "line no {} is overridden more than once",
+                    ov.line_no
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        for ov in overrides {
+            let line = &mut self.invoice_lines[ov.line_no as usize - 1];
+            if let Some(quantity) = ov.quantity.as_ref() {
+                line.quantity = quantity.clone();
+            }
+            if let Some(unit_price) = ov.unit_price.as_ref() {
+                line.unit_price = unit_price.clone();
+            }
+        }
+        Ok(())
+    }
+
+    ///invoice request carrying the quoted lines, also used to compute the totals of the quotation itself
+    pub fn into_create_invoice_request(
+        self,
+        idempotence_key: Uuid,
+        invoicing_series_mst_id: Uuid,
+        einvoicing_applicable: bool,
+        order_number: Option<PurchaseOrderNo>,
+        order_date: Option<PurchaseOrderDate>,
+    ) -> CreateInvoiceRequest {
+        CreateInvoiceRequest {
+            idempotence_key,
+            invoice_template_id: self.invoice_template_id,
+            invoicing_series_mst_id,
+            currency_id: self.currency_id,
+            service_invoice: self.service_invoice,
+            einvoicing_applicable,
+            b2b_invoice: self.b2b_invoice,
+            supplier_id: self.supplier_id,
+            dispatch_from_id: self.dispatch_from_id,
+            bill_ship_detail: self.bill_ship_detail,
+            order_number,
+            order_date,
+            payment_terms: self.payment_terms,
+            invoice_lines: self.invoice_lines,
+            additional_charges: self.additional_charges,
+            invoice_remarks: self.remarks,
+            ecommerce_gstin: None,
+            export_detail: None,
+            service_category: None,
+            sez_lut_reference: None,
+            deemed_export: false,
+            email_to_customer: false,
+            ledger_accounts: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuotationLineOverride {
+    ///1 based position of the line in the quotation
+    pub line_no: u16,
+    pub quantity: Option<LineQuantity>,
+    pub unit_price: Option<Price>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder)]
+pub struct ConvertQuotationRequest {
+    pub idempotence_key: Uuid,
+    pub invoicing_series_mst_id: Uuid,
+    pub einvoicing_applicable: bool,
+    pub order_number: Option<PurchaseOrderNo>,
+    pub order_date: Option<PurchaseOrderDate>,
+    ///lines not listed are invoiced as quoted
+    #[serde(default)]
+    pub line_overrides: Vec<QuotationLineOverride>,
+    ///the invoice is posted to these accounts on issue
+    #[serde(default)]
+    pub ledger_accounts: Option<InvoiceLedgerAccounts>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvertQuotationResponse {
+    pub invoice_id: Uuid,
+    ///draft when invoices of the tenant need approval, issued otherwise
+    pub invoice_status: InvoiceStatus,
+    ///present when the invoice was issued
+    pub invoice_pdf_request: Option<InvoicePdfRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotationPdfRequest {
+    pub tenant_id: Uuid,
+    pub quotation_id: Uuid,
+    pub quotation: Quotation,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CreateQuotationDbResponse {
+    pub quotation_id: Uuid,
+    pub quotation_number: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotationDetail {
+    pub id: Uuid,
+    pub quotation_type: QuotationType,
+    pub quotation_number: String,
+    pub quotation_date_ms: i64,
+    pub valid_until_ms: Option<i64>,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_additional_charges_amount: f64,
+    pub total_payable_amount: f64,
+    ///tax invoice created from this quotation
+    pub converted_invoice_id: Option<Uuid>,
+    pub request: CreateQuotationRequest,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_quotation_request")]
+pub(crate) struct QuotationDb {
+    pub idempotence_key: Uuid,
+    pub tenant_id: Uuid,
+    pub quotation_type: QuotationType,
+    pub quotation_series_mst_id: Uuid,
+    pub financial_year: i16,
+    pub quotation_date_ms: i64,
+    pub valid_until_ms: Option<i64>,
+    pub currency_id: Uuid,
+    pub supplier_id: Uuid,
+    pub billed_to_customer_id: Option<Uuid>,
+    pub total_taxable_amount: f64,
+    pub total_tax_amount: f64,
+    pub total_additional_charges_amount: f64,
+    pub total_payable_amount: f64,
+    pub quotation_request: serde_json::Value,
+    pub created_by: Uuid,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use speculoos::assert_that;
+    use speculoos::prelude::{ResultAssertions, VecAssertions};
+    use uuid::Uuid;
+
+    use invoice_doc_generator::invoice_line::line_quantity::test_utils::a_line_quantity;
+    use invoice_doc_generator::invoice_line::unit_price::Price;
+
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::invoicing::invoice_template::invoice_template_models::tests::SEED_INVOICE_TEMPLATE_ID;
+    use crate::invoicing::invoicing_request_models::tests::{
+        a_bill_ship_detail, a_create_invoice_line_request,
+    };
+    use crate::invoicing::invoicing_series::invoicing_series_models::tests::SEED_INVOICING_SERIES_MST_ID;
+    use crate::invoicing::quotation::quotation_models::{
+        CreateQuotationRequest, CreateQuotationRequestBuilder, QuotationLineOverride,
+        QuotationType, ValidUntilDate,
+    };
+    use crate::masters::business_entity_master::business_entity_models::tests::SEED_BUSINESS_ENTITY_ID1;
+
+    pub fn a_create_quotation_request(
+        builder: CreateQuotationRequestBuilder,
+    ) -> CreateQuotationRequest {
+        CreateQuotationRequest {
+            idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
+            quotation_type: builder.quotation_type.unwrap_or(QuotationType::Quotation),
+            quotation_series_mst_id: builder
+                .quotation_series_mst_id
+                .unwrap_or(*SEED_INVOICING_SERIES_MST_ID),
+            invoice_template_id: builder
+                .invoice_template_id
+                .unwrap_or(*SEED_INVOICE_TEMPLATE_ID),
+            currency_id: builder.currency_id.unwrap_or(*SEED_CURRENCY_ID),
+            service_invoice: builder.service_invoice.unwrap_or(false),
+            b2b_invoice: builder.b2b_invoice.unwrap_or(true),
+            supplier_id: builder.supplier_id.unwrap_or(*SEED_BUSINESS_ENTITY_ID1),
+            dispatch_from_id: builder.dispatch_from_id.flatten(),
+            bill_ship_detail: builder
+                .bill_ship_detail
+                .unwrap_or_else(|| Some(a_bill_ship_detail(Default::default()))),
+            payment_terms: builder.payment_terms.flatten(),
+            valid_until: builder.valid_until.flatten(),
+            invoice_lines: builder.invoice_lines.unwrap_or_else(|| {
+                vec![
+                    a_create_invoice_line_request(Default::default()),
+                    a_create_invoice_line_request(Default::default()),
+                ]
+            }),
+            additional_charges: builder.additional_charges.unwrap_or_default(),
+            remarks: builder.remarks.flatten(),
+        }
+    }
+
+    fn a_line_override(line_no: u16, unit_price: Option<f64>) -> QuotationLineOverride {
+        QuotationLineOverride {
+            line_no,
+            quantity: None,
+            unit_price: unit_price.map(|a| Price::new(a).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_apply_line_overrides_changes_only_listed_lines() {
+        let mut req = a_create_quotation_request(Default::default());
+        let overrides = vec![QuotationLineOverride {
+            line_no: 2,
+            quantity: Some(a_line_quantity(Default::default())),
+            unit_price: Some(Price::new(7.5).unwrap()),
+        }];
+        req.apply_line_overrides(&overrides).unwrap();
+        assert_that!(req.invoice_lines[0].unit_price.inner()).is_equal_to(10.0);
+        assert_that!(req.invoice_lines[1].unit_price.inner()).is_equal_to(7.5);
+    }
+
+    #[rstest]
+    #[case(vec![a_line_override(0, Some(1.0))], 1)]
+    #[case(vec![a_line_override(3, Some(1.0))], 1)]
+    #[case(vec![a_line_override(1, Some(1.0)), a_line_override(1, None)], 1)]
+    #[case(vec![a_line_override(0, None), a_line_override(5, None)], 2)]
+    fn test_apply_line_overrides_validation(
+        #[case] overrides: Vec<QuotationLineOverride>,
+        #[case] error_count: usize,
+    ) {
+        let mut req = a_create_quotation_request(Default::default());
+        let errors = req.apply_line_overrides(&overrides).unwrap_err();
+        assert_that!(errors).has_length(error_count);
+        assert_that!(req.invoice_lines[0].unit_price.inner()).is_equal_to(10.0);
+    }
+
+    #[test]
+    fn test_quotation_request_round_trip() {
+        //quotations are stored as json and deserialized again on conversion
+        let mut builder = CreateQuotationRequestBuilder::default();
+        builder.valid_until(Some(ValidUntilDate::new("2024-05-09".to_string()).unwrap()));
+        let req = a_create_quotation_request(builder);
+        let json = serde_json::to_value(&req).unwrap();
+        let parsed: CreateQuotationRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_that!(serde_json::to_value(&parsed).unwrap()).is_equal_to(json);
+    }
+
+    #[rstest]
+    #[case("2024-05-09", true)]
+    #[case("09-05-2024", false)]
+    fn test_valid_until_date(#[case] input: &str, #[case] valid: bool) {
+        let date = ValidUntilDate::new(input.to_string());
+        if valid {
+            assert_that!(date).is_ok();
+        } else {
+            assert_that!(date).is_err();
+        }
+    }
+}
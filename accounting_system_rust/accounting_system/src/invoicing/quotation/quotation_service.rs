@@ -0,0 +1,603 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use pdf_doc_generator::quotation_template::{create_quotation_pdf, Quotation};
+
+use crate::common_utils::dao_error::DaoError;
+use crate::common_utils::utils::current_indian_date;
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoice_approval::invoice_approval_service::{
+    InvoiceApprovalService, InvoiceApprovalServiceError,
+};
+use crate::invoicing::invoicing_request_models::CreateInvoiceRequest;
+use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
+use crate::invoicing::invoicing_series::invoicing_series_service::InvoicingSeriesService;
+use crate::invoicing::invoicing_service::{InvoicingService, InvoicingServiceError};
+use crate::invoicing::quotation::quotation_dao::{get_quotation_dao, QuotationDao};
+use crate::invoicing::quotation::quotation_models::{
+    ConvertQuotationRequest, ConvertQuotationResponse, CreateQuotationRequest, QuotationDb,
+    QuotationDetail, QuotationPdfRequest,
+};
+use crate::storage::storage_service::{StorageService, FINANCIAL_DOCS_BUCKET_NAME};
+
+#[derive(Debug, Error)]
+pub enum QuotationServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("quotation {0} not found")]
+    QuotationNotFound(Uuid),
+    #[error("quotation {0} is already converted to an invoice")]
+    AlreadyConverted(Uuid),
+    #[error("{0}")]
+    Invoicing(#[from] InvoicingServiceError),
+    #[error("{0}")]
+    Approval(#[from] InvoiceApprovalServiceError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait QuotationService: Send + Sync {
+    ///totals are computed the same way as for a tax invoice, the number comes from the quotation series
+    async fn create_quotation(
+        &self,
+        req: CreateQuotationRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<QuotationPdfRequest, QuotationServiceError>;
+    async fn create_quotation_pdf(
+        &self,
+        pdf_data: QuotationPdfRequest,
+    ) -> Result<String, QuotationServiceError>;
+    async fn get_quotation(
+        &self,
+        quotation_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<QuotationDetail, QuotationServiceError>;
+    ///creates the tax invoice from the quoted lines, as a draft if invoices of the tenant need approval. the
+    /// quotation is claimed before the invoice is created so only one conversion of it can run
+    async fn convert_quotation(
+        &self,
+        quotation_id: Uuid,
+        req: ConvertQuotationRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertQuotationResponse, QuotationServiceError>;
+}
+
+struct QuotationServiceImpl {
+    dao: Arc<dyn QuotationDao>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    storage_service: Arc<dyn StorageService>,
+}
+
+pub fn get_quotation_service(
+    arc: Arc<Pool>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoicing_series_service: Arc<dyn InvoicingSeriesService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+    storage_service: Arc<dyn StorageService>,
+) -> Arc<dyn QuotationService> {
+    let dao = get_quotation_dao(arc);
+    let service = QuotationServiceImpl {
+        dao,
+        invoicing_service,
+        invoicing_series_service,
+        invoice_approval_service,
+        storage_service,
+    };
+    Arc::new(service)
+}
+
+impl QuotationServiceImpl {
+    async fn validate_create_quotation_request(
+        &self,
+        req: &CreateQuotationRequest,
+        tenant_id: Uuid,
+    ) -> Result<(), QuotationServiceError> {
+        let mut errors: Vec<String> = vec![];
+        let valid_series = self
+            .invoicing_series_service
+            .is_valid_invoicing_series_id(
+                req.quotation_series_mst_id,
+                InvoicingSeriesType::Quotation,
+                tenant_id,
+            )
+            .await
+            .context("error during quotation series validation")?;
+        if !valid_series {
+            errors.push("quotation series id is not a quotation series of this tenant".to_string());
+        }
+        if let Some(valid_until) = req.valid_until.as_ref() {
+            if *valid_until.get_date() < current_indian_date() {
+                errors.push("valid until date cannot be in the past".to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(QuotationServiceError::Validation(errors))
+        }
+    }
+
+    async fn create_converted_invoice(
+        &self,
+        invoice_req: CreateInvoiceRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertQuotationResponse, QuotationServiceError> {
+        let response = if self
+            .invoice_approval_service
+            .is_approval_required(tenant_id)
+            .await?
+        {
+            let draft = self
+                .invoicing_service
+                .create_draft_invoice(invoice_req, tenant_id, user_id)
+                .await?;
+            ConvertQuotationResponse {
+                invoice_id: draft.invoice_id,
+                invoice_status: InvoiceStatus::Draft,
+                invoice_pdf_request: None,
+            }
+        } else {
+            let pdf_request = self
+                .invoicing_service
+                .create_invoice(invoice_req, tenant_id, user_id)
+                .await?;
+            ConvertQuotationResponse {
+                invoice_id: pdf_request.invoice_id,
+                invoice_status: InvoiceStatus::Issued,
+                invoice_pdf_request: Some(pdf_request),
+            }
+        };
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl QuotationService for QuotationServiceImpl {
+    async fn create_quotation(
+        &self,
+        req: CreateQuotationRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<QuotationPdfRequest, QuotationServiceError> {
+        self.validate_create_quotation_request(&req, tenant_id)
+            .await?;
+        let quotation_request =
+            serde_json::to_value(&req).context("could not serialize quotation request")?;
+        let quotation_type = req.quotation_type;
+        let valid_until = req.valid_until.clone();
+        let billed_to_customer_id = req
+            .bill_ship_detail
+            .as_ref()
+            .map(|a| a.billed_to_customer_id);
+        let (idempotence_key, series_id, currency_id, supplier_id) = (
+            req.idempotence_key,
+            req.quotation_series_mst_id,
+            req.currency_id,
+            req.supplier_id,
+        );
+        let invoice_req =
+            req.into_create_invoice_request(idempotence_key, series_id, false, None, None);
+        let computed = self
+            .invoicing_service
+            .compute_invoice_document(invoice_req, tenant_id, user_id)
+            .await?;
+        let db = QuotationDb {
+            idempotence_key,
+            tenant_id,
+            quotation_type,
+            quotation_series_mst_id: series_id,
+            financial_year: computed.financial_year,
+            quotation_date_ms: computed.document_date_ms,
+            valid_until_ms: valid_until.as_ref().and_then(|a| a.epoch_millis()),
+            currency_id,
+            supplier_id,
+            billed_to_customer_id,
+            total_taxable_amount: computed.total_taxable_amount,
+            total_tax_amount: computed.total_tax_amount,
+            total_additional_charges_amount: computed.total_additional_charges_amount,
+            total_payable_amount: computed.total_payable_amount,
+            quotation_request,
+            created_by: user_id,
+        };
+        let created = self.dao.create_quotation(&db).await?;
+        let mut document = computed.document;
+        document.invoice_number = created.quotation_number;
+        //payment qr and irn belong to the tax invoice only
+        document.b2c_qr_payload = None;
+        document.einvoice_detail = None;
+        Ok(QuotationPdfRequest {
+            tenant_id,
+            quotation_id: created.quotation_id,
+            quotation: Quotation {
+                title: quotation_type.title().to_string(),
+                valid_until: valid_until.as_ref().map(|a| a.to_doc_date()),
+                document,
+            },
+        })
+    }
+
+    async fn create_quotation_pdf(
+        &self,
+        pdf_data: QuotationPdfRequest,
+    ) -> Result<String, QuotationServiceError> {
+        let is_processed = self
+            .dao
+            .is_quotation_pdf_created(pdf_data.tenant_id, pdf_data.quotation_id)
+            .await?;
+        let key = create_storage_file_key(pdf_data.tenant_id, pdf_data.quotation_id);
+        if is_processed {
+            let url = self
+                .storage_service
+                .get_object_url(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), None)
+                .await?;
+            return Ok(url);
+        }
+        let pdf_bytes = create_quotation_pdf(&pdf_data.quotation)?;
+        let uploaded_url = self
+            .storage_service
+            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), pdf_bytes, None)
+            .await?;
+        self.dao
+            .persist_quotation_pdf_dtl(pdf_data.tenant_id, pdf_data.quotation_id, key.as_str())
+            .await?;
+        Ok(uploaded_url)
+    }
+
+    async fn get_quotation(
+        &self,
+        quotation_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<QuotationDetail, QuotationServiceError> {
+        self.dao
+            .get_quotation_by_id(quotation_id, tenant_id)
+            .await?
+            .ok_or(QuotationServiceError::QuotationNotFound(quotation_id))
+    }
+
+    async fn convert_quotation(
+        &self,
+        quotation_id: Uuid,
+        req: ConvertQuotationRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<ConvertQuotationResponse, QuotationServiceError> {
+        let quotation = self.get_quotation(quotation_id, tenant_id).await?;
+        if quotation.converted_invoice_id.is_some() {
+            return Err(QuotationServiceError::AlreadyConverted(quotation_id));
+        }
+        let valid_series = self
+            .invoicing_series_service
+            .is_valid_invoicing_series_id(
+                req.invoicing_series_mst_id,
+                InvoicingSeriesType::TaxInvoice,
+                tenant_id,
+            )
+            .await
+            .context("error during invoicing series validation")?;
+        if !valid_series {
+            return Err(QuotationServiceError::Validation(vec![
+                "invoicing series id is not a tax invoice series of this tenant".to_string(),
+            ]));
+        }
+        let mut quoted = quotation.request;
+        quoted
+            .apply_line_overrides(&req.line_overrides)
+            .map_err(QuotationServiceError::Validation)?;
+        let idempotence_key = req.idempotence_key;
+        let mut invoice_req = quoted.into_create_invoice_request(
+            idempotence_key,
+            req.invoicing_series_mst_id,
+            req.einvoicing_applicable,
+            req.order_number,
+            req.order_date,
+        );
+        invoice_req.ledger_accounts = req.ledger_accounts;
+        //claimed before the invoice is created so that parallel conversions cannot create two invoices
+        let claimed = self
+            .dao
+            .claim_quotation_for_conversion(tenant_id, quotation_id, idempotence_key)
+            .await?;
+        if !claimed {
+            return Err(QuotationServiceError::AlreadyConverted(quotation_id));
+        }
+        let response = match self
+            .create_converted_invoice(invoice_req, tenant_id, user_id)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                self.dao
+                    .release_quotation_claim(tenant_id, quotation_id, idempotence_key)
+                    .await?;
+                return Err(e);
+            }
+        };
+        //a retry with the same idempotence key gets the same invoice back and links it again
+        let linked = self
+            .dao
+            .mark_quotation_converted(tenant_id, quotation_id, response.invoice_id, user_id)
+            .await?;
+        if !linked {
+            return Err(QuotationServiceError::AlreadyConverted(quotation_id));
+        }
+        Ok(response)
+    }
+}
+
+fn create_storage_file_key(tenant_id: Uuid, quotation_id: Uuid) -> String {
+    format!("{}-quotation-{}.pdf", tenant_id, quotation_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::ResultAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+    use crate::invoicing::invoice_approval::invoice_approval_service::MockInvoiceApprovalService;
+    use crate::invoicing::invoicing_request_models::CreateDraftInvoiceResponse;
+    use crate::invoicing::invoicing_series::invoicing_series_models::InvoicingSeriesType;
+    use crate::invoicing::invoicing_series::invoicing_series_service::MockInvoicingSeriesService;
+    use crate::invoicing::invoicing_service::{InvoicingServiceError, MockInvoicingService};
+    use crate::invoicing::quotation::quotation_dao::MockQuotationDao;
+    use crate::invoicing::quotation::quotation_models::tests::a_create_quotation_request;
+    use crate::invoicing::quotation::quotation_models::{
+        ConvertQuotationRequest, QuotationDetail, QuotationLineOverride, QuotationType,
+    };
+    use crate::invoicing::quotation::quotation_service::{
+        QuotationService, QuotationServiceError, QuotationServiceImpl,
+    };
+    use crate::storage::storage_service::MockStorageService;
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_quotation_detail(converted_invoice_id: Option<Uuid>) -> QuotationDetail {
+        QuotationDetail {
+            id: Uuid::now_v7(),
+            quotation_type: QuotationType::Quotation,
+            quotation_number: "QT1".to_string(),
+            quotation_date_ms: 0,
+            valid_until_ms: None,
+            total_taxable_amount: 20.0,
+            total_tax_amount: 0.0,
+            total_additional_charges_amount: 0.0,
+            total_payable_amount: 20.0,
+            converted_invoice_id,
+            request: a_create_quotation_request(Default::default()),
+        }
+    }
+
+    fn a_convert_request(line_overrides: Vec<QuotationLineOverride>) -> ConvertQuotationRequest {
+        ConvertQuotationRequest {
+            idempotence_key: Uuid::now_v7(),
+            invoicing_series_mst_id: Uuid::now_v7(),
+            einvoicing_applicable: false,
+            order_number: None,
+            order_date: None,
+            line_overrides,
+            ledger_accounts: None,
+        }
+    }
+
+    fn a_service(
+        dao: MockQuotationDao,
+        invoicing_service: MockInvoicingService,
+        invoice_approval_service: MockInvoiceApprovalService,
+        tax_invoice_series: bool,
+    ) -> QuotationServiceImpl {
+        let mut invoicing_series_service = MockInvoicingSeriesService::new();
+        invoicing_series_service
+            .expect_is_valid_invoicing_series_id()
+            .returning(move |_, series_type, _| {
+                Ok(tax_invoice_series && series_type == InvoicingSeriesType::TaxInvoice)
+            });
+        QuotationServiceImpl {
+            dao: Arc::new(dao),
+            invoicing_service: Arc::new(invoicing_service),
+            invoicing_series_service: Arc::new(invoicing_series_service),
+            invoice_approval_service: Arc::new(invoice_approval_service),
+            storage_service: Arc::new(MockStorageService::new()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_convert_already_converted_quotation() {
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .returning(|_, _| Ok(Some(a_quotation_detail(Some(Uuid::now_v7())))));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service.expect_create_draft_invoice().never();
+        let service = a_service(
+            dao,
+            invoicing_service,
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let resp = service
+            .convert_quotation(
+                Uuid::now_v7(),
+                a_convert_request(vec![]),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(QuotationServiceError::AlreadyConverted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_convert_with_unknown_line_override() {
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .returning(|_, _| Ok(Some(a_quotation_detail(None))));
+        dao.expect_mark_quotation_converted().never();
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        let service = a_service(
+            dao,
+            invoicing_service,
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let overrides = vec![QuotationLineOverride {
+            line_no: 9,
+            quantity: None,
+            unit_price: None,
+        }];
+        let resp = service
+            .convert_quotation(
+                Uuid::now_v7(),
+                a_convert_request(overrides),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(resp, Err(QuotationServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_convert_creates_draft_when_approval_required() {
+        let invoice_id = Uuid::now_v7();
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .returning(|_, _| Ok(Some(a_quotation_detail(None))));
+        dao.expect_claim_quotation_for_conversion()
+            .times(1)
+            .returning(|_, _, _| Ok(true));
+        dao.expect_mark_quotation_converted()
+            .withf(move |_, _, inv, _| *inv == invoice_id)
+            .times(1)
+            .returning(|_, _, _, _| Ok(true));
+        let mut approval_service = MockInvoiceApprovalService::new();
+        approval_service
+            .expect_is_approval_required()
+            .returning(|_| Ok(true));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service
+            .expect_create_draft_invoice()
+            .withf(|req, _, _| req.invoice_lines.len() == 2 && req.order_number.is_none())
+            .times(1)
+            .returning(move |_, _, _| Ok(CreateDraftInvoiceResponse { invoice_id }));
+        let service = a_service(dao, invoicing_service, approval_service, true);
+        let resp = service
+            .convert_quotation(
+                Uuid::now_v7(),
+                a_convert_request(vec![]),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert_that!(resp).is_ok();
+        let resp = resp.unwrap();
+        assert_that!(resp.invoice_id).is_equal_to(invoice_id);
+        assert_that!(resp.invoice_status).is_equal_to(InvoiceStatus::Draft);
+        assert!(resp.invoice_pdf_request.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_convert_rejects_series_of_other_documents() {
+        let quotation = a_quotation_detail(None);
+        let mut req = a_convert_request(vec![]);
+        req.invoicing_series_mst_id = quotation.request.quotation_series_mst_id;
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .return_once(move |_, _| Ok(Some(quotation)));
+        dao.expect_claim_quotation_for_conversion().never();
+        let service = a_service(
+            dao,
+            MockInvoicingService::new(),
+            MockInvoiceApprovalService::new(),
+            false,
+        );
+        let resp = service
+            .convert_quotation(Uuid::now_v7(), req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(resp, Err(QuotationServiceError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_convert_of_quotation_claimed_by_another_conversion() {
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .returning(|_, _| Ok(Some(a_quotation_detail(None))));
+        dao.expect_claim_quotation_for_conversion()
+            .returning(|_, _, _| Ok(false));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service.expect_create_draft_invoice().never();
+        let service = a_service(
+            dao,
+            invoicing_service,
+            MockInvoiceApprovalService::new(),
+            true,
+        );
+        let resp = service
+            .convert_quotation(
+                Uuid::now_v7(),
+                a_convert_request(vec![]),
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await;
+        assert!(matches!(
+            resp,
+            Err(QuotationServiceError::AlreadyConverted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_conversion_releases_the_claim() {
+        let req = a_convert_request(vec![]);
+        let idempotence_key = req.idempotence_key;
+        let mut dao = MockQuotationDao::new();
+        dao.expect_get_quotation_by_id()
+            .returning(|_, _| Ok(Some(a_quotation_detail(None))));
+        dao.expect_claim_quotation_for_conversion()
+            .returning(|_, _, _| Ok(true));
+        dao.expect_release_quotation_claim()
+            .withf(move |_, _, key| *key == idempotence_key)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        dao.expect_mark_quotation_converted().never();
+        let mut approval_service = MockInvoiceApprovalService::new();
+        approval_service
+            .expect_is_approval_required()
+            .returning(|_| Ok(false));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_create_invoice()
+            .returning(|_, _, _| {
+                Err(InvoicingServiceError::Validation(vec![
+                    "supplier id does not exists for this tenant id".to_string(),
+                ]))
+            });
+        let service = a_service(dao, invoicing_service, approval_service, true);
+        let resp = service
+            .convert_quotation(Uuid::now_v7(), req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(resp, Err(QuotationServiceError::Invoicing(_))));
+    }
+}
@@ -0,0 +1,34 @@
+create type quotation_type as enum ('quotation','proforma_invoice');
+
+create table quotation
+(
+    id                              uuid primary key,
+    entity_version_id               integer default 0,
+    tenant_id                       uuid references tenant (id)               not null,
+    active                          bool,
+    approval_status                 smallint                                  not null,
+    remarks                         varchar(70),
+    quotation_type                  quotation_type                            not null,
+    quotation_series_mst_id         uuid references invoicing_series_mst (id) not null,--own series, separate from tax invoices
+    financial_year                  smallint                                  not null,
+    quotation_number                varchar(20)                               not null,
+    quotation_date_ms               bigint                                    not null,
+    valid_until_ms                  bigint,
+    currency_id                     uuid references currency_master (id)      not null,
+    supplier_business_entity        uuid references business_entity (id)      not null,
+    billed_to_business_entity       uuid references business_entity (id),
+    total_taxable_amount            double precision                          not null,
+    total_tax_amount                double precision                          not null,
+    total_additional_charges_amount double precision                          not null,
+    total_payable_amount            double precision                          not null,
+    quotation_request               jsonb                                     not null,--lines and parties, used to build the tax invoice on conversion
+    quotation_pdf_s3_id             varchar(200),
+    converted_invoice_id            uuid references invoice (id),--tax invoice created from this quotation
+    converted_by                    uuid references app_user (id),
+    converted_at                    bigint,
+    conversion_idempotence_key      uuid,--claimed by the conversion in progress, released when it fails
+    created_by                      uuid references app_user (id)             not null,
+    updated_by                      uuid references app_user (id),
+    created_at                      bigint  default extract(epoch from now()) * 1000000,
+    updated_at                      bigint  default extract(epoch from now()) * 1000000
+);
@@ -0,0 +1,91 @@
+create type create_quotation_request as
+(
+    idempotence_key                 uuid,
+    tenant_id                       uuid,
+    quotation_type                  quotation_type,
+    quotation_series_mst_id         uuid,
+    financial_year                  smallint,
+    quotation_date_ms               bigint,
+    valid_until_ms                  bigint,
+    currency_id                     uuid,
+    supplier_id                     uuid,
+    billed_to_customer_id           uuid,
+    total_taxable_amount            double precision,
+    total_tax_amount                double precision,
+    total_additional_charges_amount double precision,
+    total_payable_amount            double precision,
+    quotation_request               jsonb,
+    created_by                      uuid
+);
+
+--numbers are taken from the quotation series with the same counter logic as invoices
+create or replace function create_quotation(req create_quotation_request) returns jsonb as
+$$
+DECLARE
+    resp          jsonb;
+    _quotation_id uuid := uuid_generate_v7();
+    _number       text;
+    impacted_rows int;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_quotation', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        select create_invoice_number(req.quotation_series_mst_id, req.financial_year, req.tenant_id,
+                                     req.created_by)
+        into _number;
+        insert into quotation (id, entity_version_id, tenant_id, active, approval_status, remarks, quotation_type,
+                               quotation_series_mst_id, financial_year, quotation_number, quotation_date_ms,
+                               valid_until_ms, currency_id, supplier_business_entity, billed_to_business_entity,
+                               total_taxable_amount, total_tax_amount, total_additional_charges_amount,
+                               total_payable_amount, quotation_request, quotation_pdf_s3_id, converted_invoice_id,
+                               converted_by, converted_at, created_by, updated_by, created_at, updated_at)
+        values (_quotation_id, 0, req.tenant_id, true, 1, null, req.quotation_type, req.quotation_series_mst_id,
+                req.financial_year, _number, req.quotation_date_ms, req.valid_until_ms, req.currency_id,
+                req.supplier_id, req.billed_to_customer_id, req.total_taxable_amount, req.total_tax_amount,
+                req.total_additional_charges_amount, req.total_payable_amount, req.quotation_request, null, null,
+                null, null, req.created_by, req.created_by, default, default);
+        resp := jsonb_build_object('quotation_id', _quotation_id, 'quotation_number', _number);
+        update idempotence_store
+        set response=resp
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_quotation';
+        return resp;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_quotation'
+        into resp;
+        return resp;
+    end if;
+end;
+$$ language plpgsql;
+
+--links the tax invoice to the quotation, returns false if it was already converted to another invoice
+create or replace function mark_quotation_converted(_tenant_id uuid, _quotation_id uuid, _invoice_id uuid,
+                                                    _converted_by uuid) returns bool as
+$$
+DECLARE
+    impacted_rows int;
+BEGIN
+    update quotation
+    set converted_invoice_id = _invoice_id,
+        converted_by         = _converted_by,
+        converted_at         = extract(epoch from now()) * 1000000,
+        updated_by           = _converted_by,
+        updated_at           = extract(epoch from now()) * 1000000
+    where id = _quotation_id
+      and tenant_id = _tenant_id
+      and (converted_invoice_id is null or converted_invoice_id = _invoice_id);
+    get diagnostics impacted_rows= row_count;
+    return impacted_rows != 0;
+end;
+$$ language plpgsql;
+
+create trigger quotation_audit_trigger
+    after update or delete
+    on quotation
+    for each row
+execute function create_audit_entry();
@@ -0,0 +1,2 @@
+create unique index if not exists quotation_number_idx on quotation (tenant_id, quotation_series_mst_id, financial_year, quotation_number);
+create index if not exists quotation_converted_invoice_idx on quotation (tenant_id, converted_invoice_id);
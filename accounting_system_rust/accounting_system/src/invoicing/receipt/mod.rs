@@ -0,0 +1,5 @@
+mod receipt_dao;
+pub mod receipt_db_mapping;
+pub mod receipt_http_api;
+pub mod receipt_models;
+pub mod receipt_service;
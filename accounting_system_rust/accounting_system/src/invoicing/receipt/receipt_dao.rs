@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::receipt::receipt_models::{
+    convert_to_receipt_allocation_db, convert_to_receipt_db, CreateReceiptRequest,
+    CreateReceiptResponse, InvoiceForSettlementDb, InvoicePaymentStatus, PaymentMode, Receipt,
+    ReceiptAllocation, ReceiptAllocationWithDiscount, EARLY_PAYMENT_DISCOUNT_TRANSFER_CODE,
+};
+
+const CREATE_RECEIPT: &str = "select create_receipt($1)";
+
+const ALLOCATE_RECEIPT: &str = "call allocate_receipt_amount($1,$2,$3,$4,$5)";
+
+const INVOICES_FOR_SETTLEMENT_QUERY: &str = "select i.id,i.invoice_number,i.invoice_date_ms,\
+i.billed_to_business_entity,i.currency_id,i.total_payable_amount,i.amount_received,i.discount_allowed,\
+i.payment_status::text,pt.discount_days,pt.discount_percent \
+from invoice i left join payment_term pt on i.payment_term_id=pt.id \
+where i.tenant_id=$1 and i.id=any($2) and i.invoice_status='issued'";
+
+const RECEIPT_QUERY: &str = "select id,business_entity_id,currency_id,payment_mode::text,\
+payment_reference,receipt_date_ms,amount,unallocated_amount,transfer_id,receivable_account_id,\
+discount_allowed_account_id,remarks from receipt where id=$1 and tenant_id=$2";
+
+const ALLOCATIONS_BY_RECEIPT_ID_QUERY: &str =
+    "select receipt_id,invoice_id,amount,discount_amount,\
+created_at from receipt_allocation where receipt_id=$1 and tenant_id=$2 order by created_at";
+
+const ALLOCATIONS_BY_INVOICE_ID_QUERY: &str =
+    "select receipt_id,invoice_id,amount,discount_amount,\
+created_at from receipt_allocation where invoice_id=$1 and tenant_id=$2 order by created_at";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ReceiptDao: Send + Sync {
+    ///creates the receipt, allocates it to invoices and posts the ledger transfers in one transaction
+    async fn create_receipt(
+        &self,
+        req: &CreateReceiptRequest,
+        allocations: &[ReceiptAllocationWithDiscount],
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateReceiptResponse, DaoError>;
+    async fn allocate_receipt(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        allocations: &[ReceiptAllocationWithDiscount],
+    ) -> Result<(), DaoError>;
+    async fn get_invoices_for_settlement(
+        &self,
+        tenant_id: Uuid,
+        invoice_ids: &[Uuid],
+    ) -> Result<Vec<InvoiceForSettlementDb>, DaoError>;
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Receipt>, DaoError>;
+    async fn get_allocations_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<ReceiptAllocation>, DaoError>;
+}
+
+struct ReceiptDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_receipt_dao(arc: Arc<Pool>) -> Arc<dyn ReceiptDao> {
+    let dao = ReceiptDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for InvoiceForSettlementDb {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let status: &str = row.get(8);
+        Ok(InvoiceForSettlementDb {
+            invoice_id: row.get(0),
+            invoice_number: row.get(1),
+            invoice_date_ms: row.get(2),
+            billed_to_id: row.get(3),
+            currency_id: row.get(4),
+            total_payable_amount: row.get(5),
+            amount_received: row.get(6),
+            discount_allowed: row.get(7),
+            payment_status: InvoicePaymentStatus::from_db_str(status)?,
+            discount_days: row.get(9),
+            discount_percent: row.get(10),
+        })
+    }
+}
+
+impl TryFrom<Row> for ReceiptAllocation {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        Ok(ReceiptAllocation {
+            receipt_id: row.get(0),
+            invoice_id: row.get(1),
+            amount: row.get(2),
+            discount_amount: row.get(3),
+            created_at: row.get(4),
+        })
+    }
+}
+
+#[async_trait]
+impl ReceiptDao for ReceiptDaoImpl {
+    async fn create_receipt(
+        &self,
+        req: &CreateReceiptRequest,
+        allocations: &[ReceiptAllocationWithDiscount],
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateReceiptResponse, DaoError> {
+        let db = convert_to_receipt_db(req, allocations, tenant_id, user_id);
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_RECEIPT, &[&db])
+            .await?;
+        let json: serde_json::Value = row.get(0);
+        let resp = serde_json::from_value(json).context("invalid create receipt response")?;
+        Ok(resp)
+    }
+
+    async fn allocate_receipt(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+        allocations: &[ReceiptAllocationWithDiscount],
+    ) -> Result<(), DaoError> {
+        let allocations = convert_to_receipt_allocation_db(allocations);
+        self.postgres_client
+            .get()
+            .await?
+            .execute(
+                ALLOCATE_RECEIPT,
+                &[
+                    &receipt_id,
+                    &tenant_id,
+                    &allocations,
+                    &EARLY_PAYMENT_DISCOUNT_TRANSFER_CODE,
+                    &user_id,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_invoices_for_settlement(
+        &self,
+        tenant_id: Uuid,
+        invoice_ids: &[Uuid],
+    ) -> Result<Vec<InvoiceForSettlementDb>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(INVOICES_FOR_SETTLEMENT_QUERY, &[&tenant_id, &invoice_ids])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Receipt>, DaoError> {
+        let conn = self.postgres_client.get().await?;
+        let Some(row) = conn
+            .query_opt(RECEIPT_QUERY, &[&receipt_id, &tenant_id])
+            .await?
+        else {
+            return Ok(None);
+        };
+        let allocations = conn
+            .query(ALLOCATIONS_BY_RECEIPT_ID_QUERY, &[&receipt_id, &tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect::<Result<Vec<ReceiptAllocation>, DaoError>>()?;
+        let mode: &str = row.get(3);
+        Ok(Some(Receipt {
+            id: row.get(0),
+            business_entity_id: row.get(1),
+            currency_id: row.get(2),
+            payment_mode: PaymentMode::from_db_str(mode)?,
+            payment_reference: row.get(4),
+            receipt_date_ms: row.get(5),
+            amount: row.get(6),
+            unallocated_amount: row.get(7),
+            transfer_id: row.get(8),
+            receivable_account_id: row.get(9),
+            discount_allowed_account_id: row.get(10),
+            remarks: row.get(11),
+            allocations,
+        }))
+    }
+
+    async fn get_allocations_by_invoice_id(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<ReceiptAllocation>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(ALLOCATIONS_BY_INVOICE_ID_QUERY, &[&invoice_id, &tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::option::OptionAssertions;
+    use speculoos::prelude::VecAssertions;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::invoicing::receipt::receipt_dao::{ReceiptDao, ReceiptDaoImpl};
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    #[tokio::test]
+    async fn test_get_invoices_for_settlement() {
+        let dao = get_dao_generic(|c| ReceiptDaoImpl { postgres_client: c }, None).await;
+        let invoices = dao
+            .get_invoices_for_settlement(*SEED_TENANT_ID, &[*SEED_INVOICE_ID])
+            .await
+            .unwrap();
+        assert_that!(invoices).has_length(1);
+        let receipt = dao
+            .get_receipt_by_id(*SEED_INVOICE_ID, *SEED_TENANT_ID)
+            .await
+            .unwrap();
+        assert_that!(receipt).is_none();
+    }
+}
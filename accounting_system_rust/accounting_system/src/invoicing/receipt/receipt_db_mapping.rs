@@ -0,0 +1,50 @@
+use crate::db_schema_syncer::db_struct_mapper::DbStructMapping;
+
+pub struct ReceiptDbMapping {}
+
+const RECEIPT_DDL_SQL: &str = include_str!("./receipt_sql/receipt_ddl.sql");
+const RECEIPT_SEED_DATA: &str = include_str!("./receipt_sql/receipt.csv");
+const RECEIPT_INDEXES_SQL: &str = include_str!("./receipt_sql/receipt_indexes.sql");
+const RECEIPT_FUNCTIONS_SQL: &str =
+    include_str!("./receipt_sql/receipt_functions_and_procedures.sql");
+impl DbStructMapping for ReceiptDbMapping {
+    fn table_name(&self) -> Option<&'static str> {
+        Some("receipt")
+    }
+
+    fn get_ddl_script(&self) -> &'static str {
+        RECEIPT_DDL_SQL
+    }
+
+    fn get_index_creation_script(&self) -> &'static str {
+        RECEIPT_INDEXES_SQL
+    }
+
+    fn get_functions_and_procedures_script(&self) -> &'static str {
+        RECEIPT_FUNCTIONS_SQL
+    }
+
+    fn get_seed_data_script(&self) -> &'static str {
+        RECEIPT_SEED_DATA
+    }
+
+    fn get_migration_ddl_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_functions_and_procedures_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migration_dml_statements_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_index_creation_script(&self) -> String {
+        todo!()
+    }
+
+    fn get_migrations_seed_data_script(&self) -> String {
+        todo!()
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::receipt::receipt_models::{AllocateReceiptRequest, CreateReceiptRequest};
+use crate::invoicing::receipt::receipt_service::{ReceiptService, ReceiptServiceError};
+use crate::setup_routes;
+
+impl ResponseError for ReceiptServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ReceiptServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ReceiptServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            ReceiptServiceError::ReceiptNotFound(_) => StatusCode::NOT_FOUND,
+            ReceiptServiceError::InvoiceNotFound(_) => StatusCode::NOT_FOUND,
+            ReceiptServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn create_receipt(
+    data: Data<Arc<dyn ReceiptService>>,
+    request: web::Json<CreateReceiptRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_receipt(&request, tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn allocate_receipt(
+    data: Data<Arc<dyn ReceiptService>>,
+    receipt_id: Path<Uuid>,
+    request: web::Json<AllocateReceiptRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .allocate_receipt(
+            receipt_id.into_inner(),
+            &request,
+            tenant_id.inner(),
+            user_id.inner(),
+        )
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_receipt(
+    data: Data<Arc<dyn ReceiptService>>,
+    receipt_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_receipt_by_id(receipt_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_invoice_settlement(
+    data: Data<Arc<dyn ReceiptService>>,
+    invoice_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_invoice_settlement(invoice_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+setup_routes!(
+    ReceiptService,
+    "/receipt",
+    "/create",
+    web::post().to(create_receipt),
+    "/{receipt_id}/allocate",
+    web::post().to(allocate_receipt),
+    "/id/{receipt_id}",
+    web::get().to(get_receipt),
+    "/invoice-id/{invoice_id}/settlement",
+    web::get().to(get_invoice_settlement)
+);
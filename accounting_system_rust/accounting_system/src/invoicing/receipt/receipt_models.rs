@@ -0,0 +1,416 @@
+use anyhow::{bail, ensure};
+use derive_builder::Builder;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+///transaction type code of the ledger transfer posted for a customer receipt
+pub const RECEIPT_TRANSFER_CODE: i16 = 2;
+///transaction type code of the ledger transfer posted for an early payment discount
+pub const EARLY_PAYMENT_DISCOUNT_TRANSFER_CODE: i16 = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "payment_mode", rename_all = "snake_case")]
+pub enum PaymentMode {
+    Cash,
+    Cheque,
+    BankTransfer,
+    Upi,
+    Card,
+    Other,
+}
+
+impl PaymentMode {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let mode = match value {
+            "cash" => PaymentMode::Cash,
+            "cheque" => PaymentMode::Cheque,
+            "bank_transfer" => PaymentMode::BankTransfer,
+            "upi" => PaymentMode::Upi,
+            "card" => PaymentMode::Card,
+            "other" => PaymentMode::Other,
+            _ => bail!("{} is not a valid payment mode", value),
+        };
+        Ok(mode)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoicePaymentStatus {
+    Unpaid,
+    PartiallyPaid,
+    Paid,
+}
+
+impl InvoicePaymentStatus {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let status = match value {
+            "unpaid" => InvoicePaymentStatus::Unpaid,
+            "partially_paid" => InvoicePaymentStatus::PartiallyPaid,
+            "paid" => InvoicePaymentStatus::Paid,
+            _ => bail!("{} is not a valid invoice payment status", value),
+        };
+        Ok(status)
+    }
+}
+
+///cheque no, utr, upi transaction id etc.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct PaymentReference(String);
+
+impl PaymentReference {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "payment reference cannot be empty");
+        ensure!(
+            value.len() <= 50,
+            "payment reference cannot be more than 50 chars"
+        );
+        ensure!(
+            value
+                .chars()
+                .all(|a| a.is_ascii_alphanumeric() || a == '/' || a == '-' || a == ' '),
+            "payment reference can only contain alphanumeric characters, space, / or -"
+        );
+        Ok(PaymentReference(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for PaymentReference {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        PaymentReference::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "f64")]
+pub struct ReceiptAmount(f64);
+
+impl ReceiptAmount {
+    pub fn new(value: f64) -> anyhow::Result<Self> {
+        ensure!(value.is_finite(), "receipt amount should be a valid number");
+        ensure!(value > 0.0, "receipt amount should be more than 0");
+        ensure!(
+            value <= 1_000_000_000_000.0,
+            "receipt amount cannot be more than 1,000,000,000,000"
+        );
+        ensure!(
+            (value * 100.0 - (value * 100.0).round()).abs() < 1e-6,
+            "receipt amount cannot have more than 2 decimal places"
+        );
+        Ok(ReceiptAmount(value))
+    }
+    pub fn inner(&self) -> f64 {
+        self.0
+    }
+}
+
+impl TryFrom<f64> for ReceiptAmount {
+    type Error = anyhow::Error;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        ReceiptAmount::new(value)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct ReceiptRemarks(String);
+
+impl ReceiptRemarks {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "receipt remarks cannot be empty");
+        ensure!(
+            value.chars().count() <= 70,
+            "receipt remarks cannot be more than 70 chars"
+        );
+        Ok(ReceiptRemarks(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for ReceiptRemarks {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ReceiptRemarks::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReceiptAllocationRequest {
+    pub invoice_id: Uuid,
+    pub amount: ReceiptAmount,
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreateReceiptRequest {
+    pub idempotence_key: Uuid,
+    ///customer from whom the money is received
+    pub business_entity_id: Uuid,
+    pub currency_id: Uuid,
+    pub payment_mode: PaymentMode,
+    pub payment_reference: Option<PaymentReference>,
+    pub receipt_date_ms: i64,
+    pub amount: ReceiptAmount,
+    ///amount not allocated to any invoice is kept as advance against the customer
+    #[serde(default)]
+    pub allocations: Vec<ReceiptAllocationRequest>,
+    ///bank or cash account which is debited
+    pub deposit_account_id: Uuid,
+    ///receivable account of the customer which is credited
+    pub receivable_account_id: Uuid,
+    ///debited for early payment discounts. required when a payment within the discount window settles an invoice
+    #[serde(default)]
+    pub discount_allowed_account_id: Option<Uuid>,
+    pub remarks: Option<ReceiptRemarks>,
+}
+
+///allocation of the unallocated amount of an existing receipt (advance) to invoices
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AllocateReceiptRequest {
+    pub allocations: Vec<ReceiptAllocationRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CreateReceiptResponse {
+    pub receipt_id: Uuid,
+    pub transfer_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReceiptAllocation {
+    pub receipt_id: Uuid,
+    pub invoice_id: Uuid,
+    pub amount: f64,
+    pub discount_amount: f64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Receipt {
+    pub id: Uuid,
+    pub business_entity_id: Uuid,
+    pub currency_id: Uuid,
+    pub payment_mode: PaymentMode,
+    pub payment_reference: Option<String>,
+    pub receipt_date_ms: i64,
+    pub amount: f64,
+    pub unallocated_amount: f64,
+    pub transfer_id: Option<Uuid>,
+    pub receivable_account_id: Uuid,
+    pub discount_allowed_account_id: Option<Uuid>,
+    pub remarks: Option<String>,
+    pub allocations: Vec<ReceiptAllocation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceSettlement {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub total_payable_amount: f64,
+    pub amount_received: f64,
+    pub discount_allowed: f64,
+    pub outstanding_amount: f64,
+    pub payment_status: InvoicePaymentStatus,
+    pub allocations: Vec<ReceiptAllocation>,
+}
+
+///invoice fields needed to validate allocations against it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct InvoiceForSettlementDb {
+    pub invoice_id: Uuid,
+    pub invoice_number: String,
+    pub invoice_date_ms: i64,
+    pub billed_to_id: Option<Uuid>,
+    pub currency_id: Uuid,
+    pub total_payable_amount: f64,
+    pub amount_received: f64,
+    pub discount_allowed: f64,
+    pub payment_status: InvoicePaymentStatus,
+    pub discount_days: Option<i32>,
+    pub discount_percent: Option<i32>,
+}
+
+impl InvoiceForSettlementDb {
+    pub fn outstanding_amount(&self) -> f64 {
+        ((self.total_payable_amount - self.amount_received - self.discount_allowed) * 100.0).round()
+            / 100.0
+    }
+}
+
+///allocation along with the early payment discount allowed on it
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReceiptAllocationWithDiscount {
+    pub invoice_id: Uuid,
+    pub amount: f64,
+    pub discount_amount: f64,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_receipt_allocation_request")]
+pub(crate) struct ReceiptAllocationDb {
+    pub invoice_id: Uuid,
+    pub amount: f64,
+    pub discount_amount: f64,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_receipt_request")]
+pub(crate) struct ReceiptDb<'a> {
+    pub idempotence_key: Uuid,
+    pub tenant_id: Uuid,
+    pub business_entity_id: Uuid,
+    pub currency_id: Uuid,
+    pub payment_mode: PaymentMode,
+    pub payment_reference: Option<&'a str>,
+    pub receipt_date_ms: i64,
+    pub amount: f64,
+    pub allocations: Vec<ReceiptAllocationDb>,
+    pub deposit_account_id: Uuid,
+    pub receivable_account_id: Uuid,
+    pub discount_allowed_account_id: Option<Uuid>,
+    pub transfer_code: i16,
+    pub discount_transfer_code: i16,
+    pub remarks: Option<&'a str>,
+    pub created_by: Uuid,
+}
+
+pub(crate) fn convert_to_receipt_allocation_db(
+    allocations: &[ReceiptAllocationWithDiscount],
+) -> Vec<ReceiptAllocationDb> {
+    allocations
+        .iter()
+        .map(|a| ReceiptAllocationDb {
+            invoice_id: a.invoice_id,
+            amount: a.amount,
+            discount_amount: a.discount_amount,
+        })
+        .collect()
+}
+
+pub(crate) fn convert_to_receipt_db<'a>(
+    req: &'a CreateReceiptRequest,
+    allocations: &[ReceiptAllocationWithDiscount],
+    tenant_id: Uuid,
+    user_id: Uuid,
+) -> ReceiptDb<'a> {
+    ReceiptDb {
+        idempotence_key: req.idempotence_key,
+        tenant_id,
+        business_entity_id: req.business_entity_id,
+        currency_id: req.currency_id,
+        payment_mode: req.payment_mode,
+        payment_reference: req.payment_reference.as_ref().map(|a| a.inner()),
+        receipt_date_ms: req.receipt_date_ms,
+        amount: req.amount.inner(),
+        allocations: convert_to_receipt_allocation_db(allocations),
+        deposit_account_id: req.deposit_account_id,
+        receivable_account_id: req.receivable_account_id,
+        discount_allowed_account_id: req.discount_allowed_account_id,
+        transfer_code: RECEIPT_TRANSFER_CODE,
+        discount_transfer_code: EARLY_PAYMENT_DISCOUNT_TRANSFER_CODE,
+        remarks: req.remarks.as_ref().map(|a| a.inner()),
+        created_by: user_id,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use rstest::rstest;
+    use uuid::Uuid;
+
+    use crate::accounting::currency::currency_models::tests::SEED_CURRENCY_ID;
+    use crate::invoicing::invoicing_request_models::tests::SEED_INVOICE_ID;
+    use crate::invoicing::receipt::receipt_models::{
+        CreateReceiptRequest, CreateReceiptRequestBuilder, InvoiceForSettlementDb,
+        InvoicePaymentStatus, PaymentMode, PaymentReference, ReceiptAllocationRequest,
+        ReceiptAmount, ReceiptRemarks,
+    };
+
+    ///billed to business entity of the seed invoice
+    pub const SEED_CUSTOMER_ID: &str = "018d5efd-009f-7e36-9d4f-8ad30460cada";
+
+    pub fn a_create_receipt_request(builder: CreateReceiptRequestBuilder) -> CreateReceiptRequest {
+        CreateReceiptRequest {
+            idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
+            business_entity_id: builder
+                .business_entity_id
+                .unwrap_or_else(|| Uuid::parse_str(SEED_CUSTOMER_ID).unwrap()),
+            currency_id: builder.currency_id.unwrap_or(*SEED_CURRENCY_ID),
+            payment_mode: builder.payment_mode.unwrap_or(PaymentMode::BankTransfer),
+            payment_reference: builder
+                .payment_reference
+                .unwrap_or_else(|| Some(PaymentReference::new("UTR123456").unwrap())),
+            receipt_date_ms: builder.receipt_date_ms.unwrap_or(1706620412000),
+            amount: builder
+                .amount
+                .unwrap_or_else(|| ReceiptAmount::new(6.0).unwrap()),
+            allocations: builder.allocations.unwrap_or_else(|| {
+                vec![ReceiptAllocationRequest {
+                    invoice_id: *SEED_INVOICE_ID,
+                    amount: ReceiptAmount::new(6.0).unwrap(),
+                }]
+            }),
+            deposit_account_id: builder.deposit_account_id.unwrap_or_else(Uuid::now_v7),
+            receivable_account_id: builder.receivable_account_id.unwrap_or_else(Uuid::now_v7),
+            discount_allowed_account_id: builder.discount_allowed_account_id.flatten(),
+            remarks: builder
+                .remarks
+                .unwrap_or_else(|| Some(ReceiptRemarks::new("part payment").unwrap())),
+        }
+    }
+
+    pub(crate) fn an_invoice_for_settlement_db() -> InvoiceForSettlementDb {
+        InvoiceForSettlementDb {
+            invoice_id: *SEED_INVOICE_ID,
+            invoice_number: "TES1".to_string(),
+            invoice_date_ms: 1706534012000,
+            billed_to_id: Some(Uuid::parse_str(SEED_CUSTOMER_ID).unwrap()),
+            currency_id: *SEED_CURRENCY_ID,
+            total_payable_amount: 6.0,
+            amount_received: 0.0,
+            discount_allowed: 0.0,
+            payment_status: InvoicePaymentStatus::Unpaid,
+            discount_days: None,
+            discount_percent: None,
+        }
+    }
+
+    #[rstest]
+    #[case(0.0, false)]
+    #[case(-1.0, false)]
+    #[case(10.255, false)]
+    #[case(10.25, true)]
+    #[case(f64::NAN, false)]
+    fn test_receipt_amount(#[case] input: f64, #[case] valid: bool) {
+        assert_eq!(ReceiptAmount::new(input).is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case("UTR/2024-001", true)]
+    #[case("chq 000123", true)]
+    #[case("abc'; drop table", false)]
+    #[case("", false)]
+    fn test_payment_reference(#[case] input: &str, #[case] valid: bool) {
+        assert_eq!(PaymentReference::new(input).is_ok(), valid);
+    }
+
+    #[test]
+    fn test_payment_mode_db_str() {
+        let mode: PaymentMode = serde_json::from_str("\"bank_transfer\"").unwrap();
+        assert_eq!(PaymentMode::from_db_str("bank_transfer").unwrap(), mode);
+        assert!(PaymentMode::from_db_str("barter").is_err());
+    }
+}
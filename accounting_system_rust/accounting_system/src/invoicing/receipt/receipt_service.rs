@@ -0,0 +1,472 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::common_utils::utils::epoch_ms_to_indian_date;
+use crate::invoicing::receipt::receipt_dao::{get_receipt_dao, ReceiptDao};
+use crate::invoicing::receipt::receipt_models::{
+    AllocateReceiptRequest, CreateReceiptRequest, CreateReceiptResponse, InvoiceForSettlementDb,
+    InvoiceSettlement, Receipt, ReceiptAllocationRequest, ReceiptAllocationWithDiscount,
+};
+
+#[derive(Debug, Error)]
+pub enum ReceiptServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("receipt {0} not found")]
+    ReceiptNotFound(Uuid),
+    #[error("invoice {0} not found")]
+    InvoiceNotFound(Uuid),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait ReceiptService: Send + Sync {
+    ///records the money received, settles the allocated invoices and posts the ledger transfer.
+    ///amount left after allocations stays on the receipt as advance
+    async fn create_receipt(
+        &self,
+        req: &CreateReceiptRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateReceiptResponse, ReceiptServiceError>;
+    ///allocates the advance/excess amount of an existing receipt to invoices
+    async fn allocate_receipt(
+        &self,
+        receipt_id: Uuid,
+        req: &AllocateReceiptRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Receipt, ReceiptServiceError>;
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Receipt>, ReceiptServiceError>;
+    async fn get_invoice_settlement(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<InvoiceSettlement, ReceiptServiceError>;
+}
+
+struct ReceiptServiceImpl {
+    dao: Arc<dyn ReceiptDao>,
+}
+
+pub fn get_receipt_service(arc: Arc<Pool>) -> Arc<dyn ReceiptService> {
+    let dao = get_receipt_dao(arc);
+    let service = ReceiptServiceImpl { dao };
+    Arc::new(service)
+}
+
+fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+///checks that every allocation targets an open invoice of the payer in the receipt currency,
+///does not exceed the invoice outstanding and that together they fit in the available amount
+pub(crate) fn validate_allocations(
+    payer_id: Uuid,
+    currency_id: Uuid,
+    available_amount: f64,
+    allocations: &[ReceiptAllocationRequest],
+    invoices: &[InvoiceForSettlementDb],
+) -> Vec<String> {
+    let mut errors = vec![];
+    let mut seen = HashSet::new();
+    let mut total = 0.0;
+    for alloc in allocations {
+        if !seen.insert(alloc.invoice_id) {
+            errors.push(format!(
+                "invoice {} is allocated more than once",
+                alloc.invoice_id
+            ));
+            continue;
+        }
+        total += alloc.amount.inner();
+        let Some(invoice) = invoices.iter().find(|a| a.invoice_id == alloc.invoice_id) else {
+            errors.push(format!("invoice {} not found", alloc.invoice_id));
+            continue;
+        };
+        if invoice.billed_to_id != Some(payer_id) {
+            errors.push(format!(
+                "invoice {} is not billed to the payer of this receipt",
+                invoice.invoice_number
+            ));
+        }
+        if invoice.currency_id != currency_id {
+            errors.push(format!(
+                "currency of invoice {} does not match receipt currency",
+                invoice.invoice_number
+            ));
+        }
+        if round_2(alloc.amount.inner()) > invoice.outstanding_amount() {
+            errors.push(format!(
+                "allocation of {} exceeds outstanding amount {} of invoice {}",
+                alloc.amount.inner(),
+                invoice.outstanding_amount(),
+                invoice.invoice_number
+            ));
+        }
+    }
+    if round_2(total) > round_2(available_amount) {
+        errors.push(format!(
+            "total allocation {} exceeds available amount {}",
+            round_2(total),
+            round_2(available_amount)
+        ));
+    }
+    errors
+}
+
+///a payment dated within the discount window of the invoice which leaves a balance no more than
+///the discount on offer settles the invoice. the balance is allowed as early payment discount
+pub(crate) fn compute_early_payment_discount(
+    receipt_date_ms: i64,
+    amount: f64,
+    invoice: &InvoiceForSettlementDb,
+) -> anyhow::Result<f64> {
+    let (Some(days), Some(percent)) = (invoice.discount_days, invoice.discount_percent) else {
+        return Ok(0.0);
+    };
+    if days <= 0 || percent <= 0 {
+        return Ok(0.0);
+    }
+    let elapsed = epoch_ms_to_indian_date(receipt_date_ms)?
+        - epoch_ms_to_indian_date(invoice.invoice_date_ms)?;
+    if elapsed.num_days() > days as i64 {
+        return Ok(0.0);
+    }
+    let offered = round_2(invoice.total_payable_amount * percent as f64 / 100.0);
+    let balance = round_2(invoice.outstanding_amount() - amount);
+    if balance > 0.0 && balance <= round_2(offered - invoice.discount_allowed) {
+        Ok(balance)
+    } else {
+        Ok(0.0)
+    }
+}
+
+///attaches early payment discounts to validated allocations. fails if a discount applies but
+///there is no account to book it against
+pub(crate) fn apply_early_payment_discounts(
+    receipt_date_ms: i64,
+    discount_allowed_account_id: Option<Uuid>,
+    allocations: &[ReceiptAllocationRequest],
+    invoices: &[InvoiceForSettlementDb],
+) -> Result<Vec<ReceiptAllocationWithDiscount>, ReceiptServiceError> {
+    let mut result = Vec::with_capacity(allocations.len());
+    for alloc in allocations {
+        let discount_amount = match invoices.iter().find(|a| a.invoice_id == alloc.invoice_id) {
+            Some(invoice) => {
+                compute_early_payment_discount(receipt_date_ms, alloc.amount.inner(), invoice)?
+            }
+            None => 0.0,
+        };
+        result.push(ReceiptAllocationWithDiscount {
+            invoice_id: alloc.invoice_id,
+            amount: alloc.amount.inner(),
+            discount_amount,
+        });
+    }
+    if discount_allowed_account_id.is_none() && result.iter().any(|a| a.discount_amount > 0.0) {
+        return Err(ReceiptServiceError::Validation(vec![
+            "discount allowed account is required as the receipt is within the early payment discount window"
+                .to_string(),
+        ]));
+    }
+    Ok(result)
+}
+
+impl ReceiptServiceImpl {
+    async fn fetch_invoices(
+        &self,
+        allocations: &[ReceiptAllocationRequest],
+        tenant_id: Uuid,
+    ) -> Result<Vec<InvoiceForSettlementDb>, ReceiptServiceError> {
+        if allocations.is_empty() {
+            return Ok(vec![]);
+        }
+        let ids: Vec<Uuid> = allocations.iter().map(|a| a.invoice_id).collect();
+        let invoices = self
+            .dao
+            .get_invoices_for_settlement(tenant_id, &ids)
+            .await?;
+        Ok(invoices)
+    }
+}
+
+#[async_trait]
+impl ReceiptService for ReceiptServiceImpl {
+    async fn create_receipt(
+        &self,
+        req: &CreateReceiptRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<CreateReceiptResponse, ReceiptServiceError> {
+        let invoices = self.fetch_invoices(&req.allocations, tenant_id).await?;
+        let errors = validate_allocations(
+            req.business_entity_id,
+            req.currency_id,
+            req.amount.inner(),
+            &req.allocations,
+            &invoices,
+        );
+        if !errors.is_empty() {
+            return Err(ReceiptServiceError::Validation(errors));
+        }
+        let allocations = apply_early_payment_discounts(
+            req.receipt_date_ms,
+            req.discount_allowed_account_id,
+            &req.allocations,
+            &invoices,
+        )?;
+        let resp = self
+            .dao
+            .create_receipt(req, &allocations, tenant_id, user_id)
+            .await?;
+        Ok(resp)
+    }
+
+    async fn allocate_receipt(
+        &self,
+        receipt_id: Uuid,
+        req: &AllocateReceiptRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Receipt, ReceiptServiceError> {
+        let receipt = self
+            .dao
+            .get_receipt_by_id(receipt_id, tenant_id)
+            .await?
+            .ok_or(ReceiptServiceError::ReceiptNotFound(receipt_id))?;
+        if req.allocations.is_empty() {
+            return Err(ReceiptServiceError::Validation(vec![
+                "allocations cannot be empty".to_string(),
+            ]));
+        }
+        let invoices = self.fetch_invoices(&req.allocations, tenant_id).await?;
+        let errors = validate_allocations(
+            receipt.business_entity_id,
+            receipt.currency_id,
+            receipt.unallocated_amount,
+            &req.allocations,
+            &invoices,
+        );
+        if !errors.is_empty() {
+            return Err(ReceiptServiceError::Validation(errors));
+        }
+        let allocations = apply_early_payment_discounts(
+            receipt.receipt_date_ms,
+            receipt.discount_allowed_account_id,
+            &req.allocations,
+            &invoices,
+        )?;
+        self.dao
+            .allocate_receipt(receipt_id, tenant_id, user_id, &allocations)
+            .await?;
+        self.dao
+            .get_receipt_by_id(receipt_id, tenant_id)
+            .await?
+            .ok_or(ReceiptServiceError::ReceiptNotFound(receipt_id))
+    }
+
+    async fn get_receipt_by_id(
+        &self,
+        receipt_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<Receipt>, ReceiptServiceError> {
+        let receipt = self.dao.get_receipt_by_id(receipt_id, tenant_id).await?;
+        Ok(receipt)
+    }
+
+    async fn get_invoice_settlement(
+        &self,
+        invoice_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<InvoiceSettlement, ReceiptServiceError> {
+        let invoice = self
+            .dao
+            .get_invoices_for_settlement(tenant_id, &[invoice_id])
+            .await?
+            .pop()
+            .ok_or(ReceiptServiceError::InvoiceNotFound(invoice_id))?;
+        let allocations = self
+            .dao
+            .get_allocations_by_invoice_id(invoice_id, tenant_id)
+            .await?;
+        Ok(InvoiceSettlement {
+            invoice_id: invoice.invoice_id,
+            outstanding_amount: invoice.outstanding_amount(),
+            invoice_number: invoice.invoice_number,
+            total_payable_amount: invoice.total_payable_amount,
+            amount_received: invoice.amount_received,
+            discount_allowed: invoice.discount_allowed,
+            payment_status: invoice.payment_status,
+            allocations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use speculoos::assert_that;
+    use speculoos::prelude::{ResultAssertions, VecAssertions};
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::receipt::receipt_dao::MockReceiptDao;
+    use crate::invoicing::receipt::receipt_models::tests::{
+        a_create_receipt_request, an_invoice_for_settlement_db,
+    };
+    use crate::invoicing::receipt::receipt_models::{
+        CreateReceiptRequestBuilder, CreateReceiptResponse, ReceiptAllocationRequest, ReceiptAmount,
+    };
+    use crate::invoicing::receipt::receipt_service::{
+        apply_early_payment_discounts, compute_early_payment_discount, validate_allocations,
+        ReceiptService, ReceiptServiceError, ReceiptServiceImpl,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn an_allocation(invoice_id: Uuid, amount: f64) -> ReceiptAllocationRequest {
+        ReceiptAllocationRequest {
+            invoice_id,
+            amount: ReceiptAmount::new(amount).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_validate_allocations() {
+        let invoice = an_invoice_for_settlement_db();
+        let payer = invoice.billed_to_id.unwrap();
+        let ok = validate_allocations(
+            payer,
+            invoice.currency_id,
+            10.0,
+            &[an_allocation(invoice.invoice_id, 4.0)],
+            std::slice::from_ref(&invoice),
+        );
+        assert_that!(ok).is_empty();
+        let exceeds_outstanding = validate_allocations(
+            payer,
+            invoice.currency_id,
+            10.0,
+            &[an_allocation(invoice.invoice_id, 7.0)],
+            std::slice::from_ref(&invoice),
+        );
+        assert_that!(exceeds_outstanding).has_length(1);
+        let exceeds_available = validate_allocations(
+            payer,
+            invoice.currency_id,
+            3.0,
+            &[an_allocation(invoice.invoice_id, 4.0)],
+            std::slice::from_ref(&invoice),
+        );
+        assert_that!(exceeds_available).has_length(1);
+        let other_payer = validate_allocations(
+            Uuid::now_v7(),
+            invoice.currency_id,
+            10.0,
+            &[
+                an_allocation(invoice.invoice_id, 4.0),
+                an_allocation(Uuid::now_v7(), 1.0),
+            ],
+            &[invoice],
+        );
+        assert_that!(other_payer).has_length(2);
+    }
+
+    #[test]
+    fn test_compute_early_payment_discount() {
+        let mut invoice = an_invoice_for_settlement_db();
+        invoice.total_payable_amount = 1000.0;
+        invoice.discount_days = Some(10);
+        invoice.discount_percent = Some(2);
+        let within_window = invoice.invoice_date_ms + 10 * 86_400_000;
+        let after_window = invoice.invoice_date_ms + 11 * 86_400_000;
+        assert_eq!(
+            compute_early_payment_discount(within_window, 980.0, &invoice).unwrap(),
+            20.0
+        );
+        assert_eq!(
+            compute_early_payment_discount(within_window, 1000.0, &invoice).unwrap(),
+            0.0
+        );
+        assert_eq!(
+            compute_early_payment_discount(within_window, 500.0, &invoice).unwrap(),
+            0.0
+        );
+        assert_eq!(
+            compute_early_payment_discount(after_window, 980.0, &invoice).unwrap(),
+            0.0
+        );
+        let allocations = [an_allocation(invoice.invoice_id, 980.0)];
+        let without_account =
+            apply_early_payment_discounts(within_window, None, &allocations, &[invoice.clone()]);
+        assert!(matches!(
+            without_account,
+            Err(ReceiptServiceError::Validation(_))
+        ));
+        let with_account = apply_early_payment_discounts(
+            within_window,
+            Some(Uuid::now_v7()),
+            &allocations,
+            &[invoice],
+        )
+        .unwrap();
+        assert_eq!(with_account[0].discount_amount, 20.0);
+    }
+
+    #[tokio::test]
+    async fn test_create_receipt() {
+        let mut dao = MockReceiptDao::new();
+        dao.expect_get_invoices_for_settlement()
+            .returning(|_, _| Ok(vec![an_invoice_for_settlement_db()]));
+        dao.expect_create_receipt()
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(CreateReceiptResponse {
+                    receipt_id: Uuid::now_v7(),
+                    transfer_id: Uuid::now_v7(),
+                })
+            });
+        let service = ReceiptServiceImpl { dao: Arc::new(dao) };
+        let req = a_create_receipt_request(Default::default());
+        let resp = service
+            .create_receipt(&req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert_that!(resp).is_ok();
+    }
+
+    #[tokio::test]
+    async fn test_create_receipt_with_excess_allocation() {
+        let mut dao = MockReceiptDao::new();
+        dao.expect_get_invoices_for_settlement()
+            .returning(|_, _| Ok(vec![an_invoice_for_settlement_db()]));
+        dao.expect_create_receipt().never();
+        let service = ReceiptServiceImpl { dao: Arc::new(dao) };
+        let invoice_id = an_invoice_for_settlement_db().invoice_id;
+        let mut builder = CreateReceiptRequestBuilder::default();
+        builder
+            .amount(ReceiptAmount::new(10.0).unwrap())
+            .allocations(vec![an_allocation(invoice_id, 10.0)]);
+        let req = a_create_receipt_request(builder);
+        let resp = service
+            .create_receipt(&req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert!(matches!(resp, Err(ReceiptServiceError::Validation(_))));
+    }
+}
@@ -0,0 +1,38 @@
+create type payment_mode as enum ('cash','cheque','bank_transfer','upi','card','other');
+
+create table receipt
+(
+    id                 uuid primary key,
+    entity_version_id  integer default 0,
+    tenant_id          uuid references tenant (id)          not null,
+    active             bool,
+    approval_status    smallint                             not null,
+    remarks            varchar(70),
+    business_entity_id uuid references business_entity (id) not null,--customer from whom the money is received
+    currency_id        uuid references currency_master (id) not null,
+    payment_mode       payment_mode                         not null,
+    payment_reference  varchar(50),
+    receipt_date_ms    bigint                               not null,
+    amount             double precision                     not null,
+    unallocated_amount double precision                     not null,--advance or excess payment available for allocation
+    transfer_id        uuid,--ledger transfer posted for this receipt
+    receivable_account_id uuid references user_account (id) not null,--customer account credited by this receipt
+    discount_allowed_account_id uuid references user_account (id),--debited for early payment discounts allowed
+    created_by         uuid references app_user (id)        not null,
+    updated_by         uuid references app_user (id),
+    created_at         bigint  default extract(epoch from now()) * 1000000,
+    updated_at         bigint  default extract(epoch from now()) * 1000000
+);
+
+create table receipt_allocation
+(
+    id         uuid primary key,
+    tenant_id  uuid references tenant (id)   not null,
+    receipt_id uuid references receipt (id)  not null,
+    invoice_id uuid references invoice (id)  not null,
+    amount     double precision              not null,
+    discount_amount double precision default 0 not null,--early payment discount allowed with this allocation
+    discount_transfer_id uuid,--ledger transfer posted for the discount
+    created_by uuid references app_user (id) not null,
+    created_at bigint default extract(epoch from now()) * 1000000
+);
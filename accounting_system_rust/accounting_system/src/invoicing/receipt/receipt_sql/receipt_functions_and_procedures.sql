@@ -0,0 +1,188 @@
+create type create_receipt_allocation_request as
+(
+    invoice_id      uuid,
+    amount          double precision,
+    discount_amount double precision
+);
+
+create type create_receipt_request as
+(
+    idempotence_key             uuid,
+    tenant_id                   uuid,
+    business_entity_id          uuid,
+    currency_id                 uuid,
+    payment_mode                payment_mode,
+    payment_reference           text,
+    receipt_date_ms             bigint,
+    amount                      double precision,
+    allocations                 create_receipt_allocation_request[],
+    deposit_account_id          uuid,
+    receivable_account_id       uuid,
+    discount_allowed_account_id uuid,
+    transfer_code               smallint,
+    discount_transfer_code      smallint,
+    remarks                     text,
+    created_by                  uuid
+);
+
+--amount is converted to minor units as per the currency scale
+create or replace procedure post_receipt_ledger_transfer(_transfer_id uuid, _tenant_id uuid, _receipt_id uuid,
+                                                         _debit_account_id uuid, _credit_account_id uuid,
+                                                         _currency_id uuid, _code smallint,
+                                                         _amount double precision, _remarks text,
+                                                         _date_ms bigint) as
+$$
+DECLARE
+    txn        transfer;
+    result     jsonb;
+    ledger_id  uuid;
+    curr_scale smallint;
+BEGIN
+    select ledger_master_id
+    from user_account
+    where id = _debit_account_id
+      and tenant_id = _tenant_id
+    into ledger_id;
+    select scale from currency_master where id = _currency_id into curr_scale;
+    txn := row (_transfer_id, _tenant_id, _receipt_id, _receipt_id, _debit_account_id,
+        _credit_account_id, null, ledger_id, _code,
+        round((_amount * power(10, coalesce(curr_scale, 0)))::numeric)::bigint, _remarks, 1,
+        _date_ms * 1000)::transfer;
+    result := json_build_object('txn_id', txn.id, 'committed', true, 'reason', '[]'::jsonb);
+    call create_ledger_transfer(txn, result);
+    if (result -> 'committed')::boolean = false then
+        raise exception 'ledger transfer for receipt % failed %', _receipt_id, result -> 'reason';
+    end if;
+end;
+$$ language plpgsql;
+
+--allocates against invoices of the customer and moves the invoice payment status.
+--early payment discounts are booked against the discount allowed account of the receipt.
+--validations are done by the service, checks here only guard against concurrent updates
+create or replace procedure allocate_receipt_amount(_receipt_id uuid, _tenant_id uuid,
+                                                    _allocations create_receipt_allocation_request[],
+                                                    _discount_transfer_code smallint,
+                                                    _created_by uuid) as
+$$
+DECLARE
+    rec              receipt;
+    alloc            create_receipt_allocation_request;
+    inv              invoice;
+    total_allocation double precision := 0;
+    total_discount   double precision := 0;
+    discount         double precision;
+    disc_trf_id      uuid              := uuid_generate_v7();
+BEGIN
+    select * from receipt where id = _receipt_id and tenant_id = _tenant_id for update into rec;
+    if rec is null then
+        raise exception 'receipt % not found', _receipt_id;
+    end if;
+    if _allocations is null then
+        return;
+    end if;
+    foreach alloc in array _allocations
+        loop
+            discount := coalesce(alloc.discount_amount, 0);
+            select *
+            from invoice
+            where id = alloc.invoice_id
+              and tenant_id = _tenant_id
+                for update
+            into inv;
+            if inv is null or inv.invoice_status != 'issued'
+                or inv.billed_to_business_entity is distinct from rec.business_entity_id
+                or inv.currency_id != rec.currency_id then
+                raise exception 'invoice % cannot be settled by receipt %', alloc.invoice_id, _receipt_id;
+            end if;
+            if alloc.amount <= 0 or discount < 0 or
+               round((inv.amount_received + inv.discount_allowed + alloc.amount + discount)::numeric, 2) >
+               round(inv.total_payable_amount::numeric, 2) then
+                raise exception 'allocation of % exceeds outstanding of invoice %', alloc.amount, alloc.invoice_id;
+            end if;
+            insert into receipt_allocation (id, tenant_id, receipt_id, invoice_id, amount, discount_amount,
+                                            discount_transfer_id, created_by, created_at)
+            values (uuid_generate_v7(), _tenant_id, _receipt_id, alloc.invoice_id, alloc.amount, discount,
+                    case when discount > 0 then disc_trf_id end, _created_by, default);
+            update invoice
+            set amount_received=inv.amount_received + alloc.amount,
+                discount_allowed=inv.discount_allowed + discount,
+                payment_status=case
+                                   when round((inv.amount_received + inv.discount_allowed + alloc.amount +
+                                               discount)::numeric, 2) >=
+                                        round(inv.total_payable_amount::numeric, 2) then 'paid'
+                                   else 'partially_paid' end::invoice_payment_status,
+                entity_version_id=inv.entity_version_id + 1,
+                updated_by=_created_by,
+                updated_at=extract(epoch from now()) * 1000000
+            where id = alloc.invoice_id
+              and tenant_id = _tenant_id;
+            total_allocation := total_allocation + alloc.amount;
+            total_discount := total_discount + discount;
+        end loop;
+    if round(total_allocation::numeric, 2) > round(rec.unallocated_amount::numeric, 2) then
+        raise exception 'allocations of % exceed unallocated amount % of receipt %',
+            total_allocation, rec.unallocated_amount, _receipt_id;
+    end if;
+    if total_discount > 0 then
+        if rec.discount_allowed_account_id is null then
+            raise exception 'discount allowed account is not set for receipt %', _receipt_id;
+        end if;
+        call post_receipt_ledger_transfer(disc_trf_id, _tenant_id, _receipt_id, rec.discount_allowed_account_id,
+                                          rec.receivable_account_id, rec.currency_id, _discount_transfer_code,
+                                          total_discount, 'early payment discount', rec.receipt_date_ms);
+    end if;
+    update receipt
+    set unallocated_amount=rec.unallocated_amount - total_allocation,
+        entity_version_id=rec.entity_version_id + 1,
+        updated_by=_created_by,
+        updated_at=extract(epoch from now()) * 1000000
+    where id = _receipt_id
+      and tenant_id = _tenant_id;
+end;
+$$ language plpgsql;
+
+--debit to the bank/cash account and credit to the customer receivable account for the full receipt amount
+create or replace function create_receipt(req create_receipt_request) returns jsonb as
+$$
+DECLARE
+    resp          jsonb;
+    impacted_rows int;
+    rec_id        uuid;
+    trf_id        uuid;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_receipt', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        select uuid_generate_v7() into rec_id;
+        select uuid_generate_v7() into trf_id;
+        insert into receipt (id, entity_version_id, tenant_id, active, approval_status, remarks, business_entity_id,
+                             currency_id, payment_mode, payment_reference, receipt_date_ms, amount,
+                             unallocated_amount, transfer_id, receivable_account_id, discount_allowed_account_id,
+                             created_by, updated_by, created_at, updated_at)
+        values (rec_id, 0, req.tenant_id, true, 1, req.remarks, req.business_entity_id, req.currency_id,
+                req.payment_mode, req.payment_reference, req.receipt_date_ms, req.amount, req.amount, trf_id,
+                req.receivable_account_id, req.discount_allowed_account_id,
+                req.created_by, req.created_by, default, default);
+        call post_receipt_ledger_transfer(trf_id, req.tenant_id, rec_id, req.deposit_account_id,
+                                          req.receivable_account_id, req.currency_id, req.transfer_code,
+                                          req.amount, 'customer receipt', req.receipt_date_ms);
+        call allocate_receipt_amount(rec_id, req.tenant_id, req.allocations, req.discount_transfer_code,
+                                     req.created_by);
+        select jsonb_build_object('receipt_id', rec_id, 'transfer_id', trf_id) into resp;
+        update idempotence_store
+        set response=resp
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_receipt';
+        return resp;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_receipt'
+        into resp;
+        return resp;
+    end if;
+end;
+$$ language plpgsql;
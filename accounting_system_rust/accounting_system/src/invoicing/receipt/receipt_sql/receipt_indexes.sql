@@ -0,0 +1,2 @@
+create index if not exists receipt_allocation_invoice_idx on receipt_allocation (tenant_id, invoice_id);
+create index if not exists receipt_allocation_receipt_idx on receipt_allocation (tenant_id, receipt_id);
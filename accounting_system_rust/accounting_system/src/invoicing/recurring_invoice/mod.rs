@@ -0,0 +1,6 @@
+mod recurring_invoice_dao;
+pub mod recurring_invoice_db_mapping;
+pub mod recurring_invoice_http_api;
+pub mod recurring_invoice_models;
+pub mod recurring_invoice_scheduler;
+pub mod recurring_invoice_service;
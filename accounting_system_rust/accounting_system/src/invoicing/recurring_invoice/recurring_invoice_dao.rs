@@ -0,0 +1,373 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use deadpool_postgres::{GenericClient, Pool};
+#[cfg(test)]
+use mockall::automock;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::recurring_invoice::recurring_invoice_models::{
+    RecurrenceFrequency, RecurringInvoiceProfile, RecurringInvoiceProfileDb, RecurringInvoiceRun,
+    RecurringInvoiceRunDb, RecurringIssueMode, RecurringRunStatus,
+};
+
+const CREATE_PROFILE: &str = "select create_recurring_invoice_profile($1)";
+
+const PROFILE_COLUMNS: &str = "select id,tenant_id,profile_name,frequency::text,start_date_ms,\
+end_date_ms,prorate_first_cycle,issue_mode::text,paused,next_cycle_no,next_run_date_ms,\
+invoice_request,created_by from recurring_invoice_profile";
+
+const RECORD_RUN: &str = "select record_recurring_invoice_run($1)";
+
+const RUNS_QUERY: &str = "select profile_id,cycle_no,cycle_start_date_ms,cycle_end_date_ms,\
+status::text,invoice_id,invoice_status::text,error_message,attempt_count,updated_at \
+from recurring_invoice_run where tenant_id=$1 and profile_id=$2 order by cycle_no desc";
+
+const PAUSE_PROFILE: &str = "update recurring_invoice_profile set paused=true,updated_by=$3,\
+updated_at=extract(epoch from now()) * 1000000 where tenant_id=$1 and id=$2 and active";
+
+const RESUME_PROFILE: &str = "update recurring_invoice_profile set paused=false,next_cycle_no=$3,\
+next_run_date_ms=$4,updated_by=$5,updated_at=extract(epoch from now()) * 1000000 \
+where tenant_id=$1 and id=$2 and active";
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait RecurringInvoiceDao: Send + Sync {
+    async fn create_profile(&self, profile: &RecurringInvoiceProfileDb) -> Result<Uuid, DaoError>;
+    async fn get_profile_by_id(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<RecurringInvoiceProfile>, DaoError>;
+    ///active and not paused profiles of all tenants whose next cycle starts on or before the date. profiles whose
+    /// next cycle was not attempted yet come first and failing ones by their last attempt, so profiles failing every
+    /// pass cannot keep the others out of the batch
+    async fn get_due_profiles(
+        &self,
+        as_of_date_ms: i64,
+        limit: i64,
+    ) -> Result<Vec<RecurringInvoiceProfile>, DaoError>;
+    async fn record_run(&self, run: &RecurringInvoiceRunDb) -> Result<(), DaoError>;
+    async fn get_runs(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+    ) -> Result<Vec<RecurringInvoiceRun>, DaoError>;
+    async fn pause_profile(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn resume_profile(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+        next_cycle_no: i32,
+        next_run_date_ms: Option<i64>,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+}
+
+struct RecurringInvoiceDaoImpl {
+    postgres_client: Arc<Pool>,
+}
+
+pub fn get_recurring_invoice_dao(arc: Arc<Pool>) -> Arc<dyn RecurringInvoiceDao> {
+    let dao = RecurringInvoiceDaoImpl {
+        postgres_client: arc,
+    };
+    Arc::new(dao)
+}
+
+impl TryFrom<Row> for RecurringInvoiceProfile {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let frequency: &str = row.get(3);
+        let issue_mode: &str = row.get(7);
+        let invoice_request: serde_json::Value = row.get(11);
+        Ok(RecurringInvoiceProfile {
+            id: row.get(0),
+            tenant_id: row.get(1),
+            profile_name: row.get(2),
+            frequency: RecurrenceFrequency::from_db_str(frequency)?,
+            start_date_ms: row.get(4),
+            end_date_ms: row.get(5),
+            prorate_first_cycle: row.get(6),
+            issue_mode: RecurringIssueMode::from_db_str(issue_mode)?,
+            paused: row.get(8),
+            next_cycle_no: row.get(9),
+            next_run_date_ms: row.get(10),
+            invoice_request: serde_json::from_value(invoice_request)
+                .context("could not deserialize stored recurring invoice request")?,
+            created_by: row.get(12),
+        })
+    }
+}
+
+impl TryFrom<Row> for RecurringInvoiceRun {
+    type Error = DaoError;
+
+    fn try_from(row: Row) -> Result<Self, Self::Error> {
+        let status: &str = row.get(4);
+        let invoice_status: Option<&str> = row.get(6);
+        Ok(RecurringInvoiceRun {
+            profile_id: row.get(0),
+            cycle_no: row.get(1),
+            cycle_start_date_ms: row.get(2),
+            cycle_end_date_ms: row.get(3),
+            status: RecurringRunStatus::from_db_str(status)?,
+            invoice_id: row.get(5),
+            invoice_status: invoice_status.map(InvoiceStatus::from_db_str).transpose()?,
+            error_message: row.get(7),
+            attempt_count: row.get(8),
+            updated_at: row.get(9),
+        })
+    }
+}
+
+#[async_trait]
+impl RecurringInvoiceDao for RecurringInvoiceDaoImpl {
+    async fn create_profile(&self, profile: &RecurringInvoiceProfileDb) -> Result<Uuid, DaoError> {
+        let row = self
+            .postgres_client
+            .get()
+            .await?
+            .query_one(CREATE_PROFILE, &[profile])
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn get_profile_by_id(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<RecurringInvoiceProfile>, DaoError> {
+        let query = format!("{} where id=$1 and tenant_id=$2", PROFILE_COLUMNS);
+        self.postgres_client
+            .get()
+            .await?
+            .query_opt(query.as_str(), &[&profile_id, &tenant_id])
+            .await?
+            .map(|a| a.try_into())
+            .transpose()
+    }
+
+    async fn get_due_profiles(
+        &self,
+        as_of_date_ms: i64,
+        limit: i64,
+    ) -> Result<Vec<RecurringInvoiceProfile>, DaoError> {
+        let query = format!(
+            "{} where active and not paused and next_run_date_ms<=$1 \
+            order by (select r.updated_at from recurring_invoice_run r \
+            where r.profile_id=recurring_invoice_profile.id \
+            and r.cycle_no=recurring_invoice_profile.next_cycle_no) nulls first,next_run_date_ms limit $2",
+            PROFILE_COLUMNS
+        );
+        self.postgres_client
+            .get()
+            .await?
+            .query(query.as_str(), &[&as_of_date_ms, &limit])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn record_run(&self, run: &RecurringInvoiceRunDb) -> Result<(), DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .execute(RECORD_RUN, &[run])
+            .await?;
+        Ok(())
+    }
+
+    async fn get_runs(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+    ) -> Result<Vec<RecurringInvoiceRun>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(RUNS_QUERY, &[&tenant_id, &profile_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
+    async fn pause_profile(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(PAUSE_PROFILE, &[&tenant_id, &profile_id, &user_id])
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn resume_profile(
+        &self,
+        tenant_id: Uuid,
+        profile_id: Uuid,
+        next_cycle_no: i32,
+        next_run_date_ms: Option<i64>,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(
+                RESUME_PROFILE,
+                &[
+                    &tenant_id,
+                    &profile_id,
+                    &next_cycle_no,
+                    &next_run_date_ms,
+                    &user_id,
+                ],
+            )
+            .await?;
+        Ok(updated != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speculoos::assert_that;
+    use speculoos::option::OptionAssertions;
+    use speculoos::prelude::VecAssertions;
+    use uuid::Uuid;
+
+    use crate::accounting::postgres_factory::test_utils_postgres::get_dao_generic;
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+    use crate::invoicing::invoicing_request_models::tests::{
+        a_create_invoice_request, SEED_INVOICE_ID,
+    };
+    use crate::invoicing::recurring_invoice::recurring_invoice_dao::{
+        RecurringInvoiceDao, RecurringInvoiceDaoImpl,
+    };
+    use crate::invoicing::recurring_invoice::recurring_invoice_models::{
+        cycle_idempotence_key, RecurrenceFrequency, RecurringInvoiceProfileDb,
+        RecurringInvoiceRunDb, RecurringIssueMode, RecurringRunStatus,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn a_profile_db() -> RecurringInvoiceProfileDb {
+        RecurringInvoiceProfileDb {
+            idempotence_key: Uuid::now_v7(),
+            tenant_id: *SEED_TENANT_ID,
+            profile_name: "monthly rent".to_string(),
+            frequency: RecurrenceFrequency::Monthly,
+            start_date_ms: 0,
+            end_date_ms: None,
+            prorate_first_cycle: false,
+            issue_mode: RecurringIssueMode::AutoIssue,
+            next_run_date_ms: Some(0),
+            invoice_request: serde_json::to_value(a_create_invoice_request(Default::default()))
+                .unwrap(),
+            created_by: *SEED_USER_ID,
+        }
+    }
+
+    fn a_run_db(profile_id: Uuid, status: RecurringRunStatus) -> RecurringInvoiceRunDb {
+        let succeeded = status == RecurringRunStatus::Succeeded;
+        RecurringInvoiceRunDb {
+            tenant_id: *SEED_TENANT_ID,
+            profile_id,
+            cycle_no: 1,
+            cycle_start_date_ms: 0,
+            cycle_end_date_ms: 1,
+            idempotence_key: cycle_idempotence_key(profile_id, 1),
+            status,
+            invoice_id: succeeded.then_some(*SEED_INVOICE_ID),
+            invoice_status: succeeded.then_some(InvoiceStatus::Issued),
+            error_message: (!succeeded).then(|| "series not found".to_string()),
+            next_run_date_ms: Some(2),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_profile_is_idempotent() {
+        let dao = get_dao_generic(|c| RecurringInvoiceDaoImpl { postgres_client: c }, None).await;
+        let db = a_profile_db();
+        let first = dao.create_profile(&db).await.unwrap();
+        let second = dao.create_profile(&db).await.unwrap();
+        assert_that!(second).is_equal_to(first);
+        let fetched = dao.get_profile_by_id(first, *SEED_TENANT_ID).await.unwrap();
+        assert_that!(fetched.map(|a| a.next_cycle_no))
+            .is_some()
+            .is_equal_to(1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_is_retried_and_success_moves_profile_to_next_cycle() {
+        let dao = get_dao_generic(|c| RecurringInvoiceDaoImpl { postgres_client: c }, None).await;
+        let profile_id = dao.create_profile(&a_profile_db()).await.unwrap();
+        dao.record_run(&a_run_db(profile_id, RecurringRunStatus::Failed))
+            .await
+            .unwrap();
+        let profile = dao
+            .get_profile_by_id(profile_id, *SEED_TENANT_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_that!(profile.next_cycle_no).is_equal_to(1);
+        dao.record_run(&a_run_db(profile_id, RecurringRunStatus::Succeeded))
+            .await
+            .unwrap();
+        //a late failure of a parallel scheduler does not overwrite the success
+        dao.record_run(&a_run_db(profile_id, RecurringRunStatus::Failed))
+            .await
+            .unwrap();
+        let runs = dao.get_runs(*SEED_TENANT_ID, profile_id).await.unwrap();
+        assert_that!(runs).has_length(1);
+        assert_that!(runs[0].status).is_equal_to(RecurringRunStatus::Succeeded);
+        assert_that!(runs[0].attempt_count).is_equal_to(2);
+        let profile = dao
+            .get_profile_by_id(profile_id, *SEED_TENANT_ID)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_that!(profile.next_cycle_no).is_equal_to(2);
+        assert_that!(profile.next_run_date_ms)
+            .is_some()
+            .is_equal_to(2);
+    }
+
+    #[tokio::test]
+    async fn test_failing_profiles_are_picked_after_the_ones_not_attempted() {
+        let dao = get_dao_generic(|c| RecurringInvoiceDaoImpl { postgres_client: c }, None).await;
+        let failing_id = dao.create_profile(&a_profile_db()).await.unwrap();
+        let mut later = a_profile_db();
+        later.next_run_date_ms = Some(1);
+        let later_id = dao.create_profile(&later).await.unwrap();
+        dao.record_run(&a_run_db(failing_id, RecurringRunStatus::Failed))
+            .await
+            .unwrap();
+        let due: Vec<Uuid> = dao
+            .get_due_profiles(1, 10000)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        let position = |id: Uuid| due.iter().position(|a| *a == id).unwrap();
+        assert!(position(later_id) < position(failing_id));
+    }
+}
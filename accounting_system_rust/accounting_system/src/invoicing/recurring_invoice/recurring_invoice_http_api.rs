@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use actix_web::http::StatusCode;
+use actix_web::web::{Data, Path};
+use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use uuid::Uuid;
+
+use crate::common_utils::utils::{TenantId, UserId};
+use crate::invoicing::recurring_invoice::recurring_invoice_models::CreateRecurringInvoiceProfileRequest;
+use crate::invoicing::recurring_invoice::recurring_invoice_service::{
+    RecurringInvoiceService, RecurringInvoiceServiceError,
+};
+use crate::setup_routes;
+
+impl ResponseError for RecurringInvoiceServiceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RecurringInvoiceServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            RecurringInvoiceServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            RecurringInvoiceServiceError::ProfileNotFound(_) => StatusCode::NOT_FOUND,
+            RecurringInvoiceServiceError::Invoicing(e) => e.status_code(),
+            RecurringInvoiceServiceError::Approval(e) => e.status_code(),
+            RecurringInvoiceServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+async fn create_profile(
+    data: Data<Arc<dyn RecurringInvoiceService>>,
+    request: web::Json<CreateRecurringInvoiceProfileRequest>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .create_profile(request.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_profile(
+    data: Data<Arc<dyn RecurringInvoiceService>>,
+    profile_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_profile(profile_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn get_runs(
+    data: Data<Arc<dyn RecurringInvoiceService>>,
+    profile_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let ap = data
+        .get_runs(profile_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(ap))
+}
+
+async fn pause_profile(
+    data: Data<Arc<dyn RecurringInvoiceService>>,
+    profile_id: Path<Uuid>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.pause_profile(profile_id.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+async fn resume_profile(
+    data: Data<Arc<dyn RecurringInvoiceService>>,
+    profile_id: Path<Uuid>,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    data.resume_profile(profile_id.into_inner(), tenant_id.inner(), user_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+setup_routes!(
+    RecurringInvoiceService,
+    "/recurring-invoice",
+    "/create",
+    web::post().to(create_profile),
+    "/id/{profile_id}",
+    web::get().to(get_profile),
+    "/id/{profile_id}/runs",
+    web::get().to(get_runs),
+    "/id/{profile_id}/pause",
+    web::post().to(pause_profile),
+    "/id/{profile_id}/resume",
+    web::post().to(resume_profile)
+);
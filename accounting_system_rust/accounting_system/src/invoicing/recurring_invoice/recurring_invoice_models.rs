@@ -0,0 +1,603 @@
+use anyhow::{bail, ensure, Context};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use derive_builder::Builder;
+use postgres_types::ToSql;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use invoice_doc_generator::invoice_line::line_quantity::{FreeLineQuantity, LineQuantity};
+
+use crate::common_utils::utils::epoch_ms_to_indian_date;
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoicing_request_models::{CreateInvoiceRequest, InvoiceRemarks};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "recurrence_frequency", rename_all = "snake_case")]
+pub enum RecurrenceFrequency {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl RecurrenceFrequency {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let frequency = match value {
+            "weekly" => RecurrenceFrequency::Weekly,
+            "monthly" => RecurrenceFrequency::Monthly,
+            "quarterly" => RecurrenceFrequency::Quarterly,
+            "yearly" => RecurrenceFrequency::Yearly,
+            _ => bail!("{} is not a valid recurrence frequency", value),
+        };
+        Ok(frequency)
+    }
+
+    ///month end dates are clamped, 31st jan plus a month is the last day of feb
+    fn add_periods(&self, date: NaiveDate, periods: u32) -> Option<NaiveDate> {
+        match self {
+            RecurrenceFrequency::Weekly => date.checked_add_days(Days::new(7 * periods as u64)),
+            RecurrenceFrequency::Monthly => date.checked_add_months(Months::new(periods)),
+            RecurrenceFrequency::Quarterly => date.checked_add_months(Months::new(3 * periods)),
+            RecurrenceFrequency::Yearly => date.checked_add_months(Months::new(12 * periods)),
+        }
+    }
+
+    ///start of the calendar period containing the date, quarters and years follow the indian financial year
+    fn period_start(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            RecurrenceFrequency::Weekly => {
+                date.checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+            }
+            RecurrenceFrequency::Monthly => date.with_day(1),
+            RecurrenceFrequency::Quarterly => {
+                let month = date.month0();
+                //financial year quarters start in april, july, october and january
+                let quarter_start_month0 = month - (month + 9) % 3;
+                NaiveDate::from_ymd_opt(date.year(), quarter_start_month0 + 1, 1)
+            }
+            RecurrenceFrequency::Yearly => {
+                let year = if date.month() < 4 {
+                    date.year() - 1
+                } else {
+                    date.year()
+                };
+                NaiveDate::from_ymd_opt(year, 4, 1)
+            }
+        }
+    }
+}
+
+///invoices of a cycle are either issued right away or left as drafts for review
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "recurring_issue_mode", rename_all = "snake_case")]
+pub enum RecurringIssueMode {
+    AutoIssue,
+    Draft,
+}
+
+impl RecurringIssueMode {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let mode = match value {
+            "auto_issue" => RecurringIssueMode::AutoIssue,
+            "draft" => RecurringIssueMode::Draft,
+            _ => bail!("{} is not a valid recurring issue mode", value),
+        };
+        Ok(mode)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSql)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "recurring_run_status", rename_all = "snake_case")]
+pub enum RecurringRunStatus {
+    Succeeded,
+    Failed,
+}
+
+impl RecurringRunStatus {
+    pub fn from_db_str(value: &str) -> anyhow::Result<Self> {
+        let status = match value {
+            "succeeded" => RecurringRunStatus::Succeeded,
+            "failed" => RecurringRunStatus::Failed,
+            _ => bail!("{} is not a valid recurring run status", value),
+        };
+        Ok(status)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct RecurringProfileName(String);
+
+impl RecurringProfileName {
+    pub fn new(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        ensure!(!value.is_empty(), "profile name cannot be empty");
+        ensure!(
+            value.chars().count() <= 50,
+            "profile name cannot be more than 50 chars"
+        );
+        Ok(RecurringProfileName(value.to_string()))
+    }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl TryFrom<String> for RecurringProfileName {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        RecurringProfileName::new(value.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct RecurrenceDate(NaiveDate);
+
+impl RecurrenceDate {
+    pub fn new(value: String) -> anyhow::Result<Self> {
+        let p = NaiveDate::parse_from_str(value.as_str(), "%Y-%m-%d")
+            .context("recurrence date must be in yyyy-mm-dd format")?;
+        Ok(RecurrenceDate(p))
+    }
+    pub fn from_date(date: NaiveDate) -> Self {
+        RecurrenceDate(date)
+    }
+    pub fn get_date(&self) -> &NaiveDate {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for RecurrenceDate {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        RecurrenceDate::new(value)
+    }
+}
+
+///dates of the profile are stored as epoch millis of the start of the day
+pub(crate) fn date_to_epoch_millis(date: NaiveDate) -> Option<i64> {
+    date.and_hms_milli_opt(0, 0, 0, 0)
+        .map(|a| a.and_utc().timestamp_millis())
+}
+
+#[derive(Debug, Serialize, Deserialize, Builder, Clone)]
+pub struct CreateRecurringInvoiceProfileRequest {
+    pub idempotence_key: Uuid,
+    pub profile_name: RecurringProfileName,
+    pub frequency: RecurrenceFrequency,
+    pub start_date: RecurrenceDate,
+    ///no invoice is generated for cycles starting after this date
+    pub end_date: Option<RecurrenceDate>,
+    ///aligns the cycles to calendar periods and bills the first partial period proportionally
+    #[serde(default)]
+    pub prorate_first_cycle: bool,
+    pub issue_mode: RecurringIssueMode,
+    ///idempotence key of the template is replaced by one derived from the profile and cycle
+    pub invoice_template: CreateInvoiceRequest,
+}
+
+impl CreateRecurringInvoiceProfileRequest {
+    pub fn schedule(&self) -> RecurrenceSchedule {
+        RecurrenceSchedule {
+            frequency: self.frequency,
+            start_date: *self.start_date.get_date(),
+            end_date: self.end_date.as_ref().map(|a| *a.get_date()),
+            prorate_first_cycle: self.prorate_first_cycle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecurrenceSchedule {
+    pub frequency: RecurrenceFrequency,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub prorate_first_cycle: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BillingCycle {
+    pub cycle_no: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    ///share of the full period billed in this cycle, below 1 only for a prorated first cycle
+    pub proration_factor: f64,
+}
+
+impl RecurrenceSchedule {
+    ///cycles are numbered from 1, none once the cycle starts after the end date
+    pub fn cycle(&self, cycle_no: i32) -> Option<BillingCycle> {
+        if cycle_no < 1 {
+            return None;
+        }
+        let anchor = self.frequency.period_start(self.start_date)?;
+        let cycle = if self.prorate_first_cycle && anchor != self.start_date {
+            let period_end = self.frequency.add_periods(anchor, cycle_no as u32)?;
+            if cycle_no == 1 {
+                let billed_days = (period_end - self.start_date).num_days();
+                let period_days = (period_end - anchor).num_days();
+                BillingCycle {
+                    cycle_no,
+                    start_date: self.start_date,
+                    end_date: period_end.pred_opt()?,
+                    proration_factor: billed_days as f64 / period_days as f64,
+                }
+            } else {
+                BillingCycle {
+                    cycle_no,
+                    start_date: self.frequency.add_periods(anchor, cycle_no as u32 - 1)?,
+                    end_date: period_end.pred_opt()?,
+                    proration_factor: 1.0,
+                }
+            }
+        } else {
+            //computed from the start date every time so that month end dates do not drift
+            BillingCycle {
+                cycle_no,
+                start_date: self
+                    .frequency
+                    .add_periods(self.start_date, cycle_no as u32 - 1)?,
+                end_date: self
+                    .frequency
+                    .add_periods(self.start_date, cycle_no as u32)?
+                    .pred_opt()?,
+                proration_factor: 1.0,
+            }
+        };
+        match self.end_date {
+            Some(end_date) if cycle.start_date > end_date => None,
+            _ => Some(cycle),
+        }
+    }
+
+    ///first cycle from the given cycle no which starts on or after the date
+    pub fn first_cycle_on_or_after(
+        &self,
+        date: NaiveDate,
+        from_cycle_no: i32,
+    ) -> Option<BillingCycle> {
+        let mut cycle_no = from_cycle_no.max(1);
+        loop {
+            let cycle = self.cycle(cycle_no)?;
+            if cycle.start_date >= date {
+                return Some(cycle);
+            }
+            cycle_no += 1;
+        }
+    }
+}
+
+///same key for every attempt of a cycle, so retries and parallel schedulers create the invoice only once
+pub fn cycle_idempotence_key(profile_id: Uuid, cycle_no: i32) -> Uuid {
+    Uuid::new_v5(
+        &profile_id,
+        format!("recurring-invoice-cycle-{}", cycle_no).as_bytes(),
+    )
+}
+
+///invoice request of a cycle built from the template of the profile
+pub fn cycle_invoice_request(
+    template: &CreateInvoiceRequest,
+    profile_id: Uuid,
+    cycle: &BillingCycle,
+) -> anyhow::Result<CreateInvoiceRequest> {
+    let mut req = template.clone();
+    req.idempotence_key = cycle_idempotence_key(profile_id, cycle.cycle_no);
+    if cycle.proration_factor < 1.0 {
+        for line in req.invoice_lines.iter_mut() {
+            line.quantity = LineQuantity::new(
+                prorate(line.quantity.get_quantity(), cycle.proration_factor),
+                line.quantity.get_uom().clone(),
+            )
+            .context("prorated quantity is not valid")?;
+            line.free_quantity = FreeLineQuantity::new(
+                prorate(line.free_quantity.get_quantity(), cycle.proration_factor),
+                line.free_quantity.get_uom().clone(),
+            )
+            .context("prorated free quantity is not valid")?;
+        }
+    }
+    if req.invoice_remarks.is_none() {
+        req.invoice_remarks = Some(InvoiceRemarks::new(
+            format!(
+                "billing period {} to {}",
+                cycle.start_date.format("%d-%m-%Y"),
+                cycle.end_date.format("%d-%m-%Y")
+            )
+            .as_str(),
+        )?);
+    }
+    Ok(req)
+}
+
+fn prorate(quantity: f64, factor: f64) -> f64 {
+    (quantity * factor * 10_000.0).round() / 10_000.0
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecurringInvoiceProfile {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub profile_name: String,
+    pub frequency: RecurrenceFrequency,
+    pub start_date_ms: i64,
+    pub end_date_ms: Option<i64>,
+    pub prorate_first_cycle: bool,
+    pub issue_mode: RecurringIssueMode,
+    pub paused: bool,
+    pub next_cycle_no: i32,
+    ///none once all the cycles till the end date are generated
+    pub next_run_date_ms: Option<i64>,
+    pub invoice_request: CreateInvoiceRequest,
+    ///invoices are generated on behalf of this user
+    pub created_by: Uuid,
+}
+
+impl RecurringInvoiceProfile {
+    pub fn schedule(&self) -> anyhow::Result<RecurrenceSchedule> {
+        Ok(RecurrenceSchedule {
+            frequency: self.frequency,
+            start_date: epoch_ms_to_indian_date(self.start_date_ms)?,
+            end_date: self.end_date_ms.map(epoch_ms_to_indian_date).transpose()?,
+            prorate_first_cycle: self.prorate_first_cycle,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecurringInvoiceRun {
+    pub profile_id: Uuid,
+    pub cycle_no: i32,
+    pub cycle_start_date_ms: i64,
+    pub cycle_end_date_ms: i64,
+    pub status: RecurringRunStatus,
+    pub invoice_id: Option<Uuid>,
+    pub invoice_status: Option<InvoiceStatus>,
+    pub error_message: Option<String>,
+    pub attempt_count: i32,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecurringRunFailure {
+    pub tenant_id: Uuid,
+    pub profile_id: Uuid,
+    pub cycle_no: i32,
+    pub error_message: String,
+}
+
+///outcome of one scheduler pass, a failed profile is retried on the next pass
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct RecurringInvoiceRunSummary {
+    pub profiles_processed: u32,
+    pub invoices_generated: u32,
+    pub failures: Vec<RecurringRunFailure>,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "create_recurring_invoice_profile_request")]
+pub(crate) struct RecurringInvoiceProfileDb {
+    pub idempotence_key: Uuid,
+    pub tenant_id: Uuid,
+    pub profile_name: String,
+    pub frequency: RecurrenceFrequency,
+    pub start_date_ms: i64,
+    pub end_date_ms: Option<i64>,
+    pub prorate_first_cycle: bool,
+    pub issue_mode: RecurringIssueMode,
+    pub next_run_date_ms: Option<i64>,
+    pub invoice_request: serde_json::Value,
+    pub created_by: Uuid,
+}
+
+#[derive(Debug, ToSql)]
+#[postgres(name = "recurring_invoice_run_request")]
+pub(crate) struct RecurringInvoiceRunDb {
+    pub tenant_id: Uuid,
+    pub profile_id: Uuid,
+    pub cycle_no: i32,
+    pub cycle_start_date_ms: i64,
+    pub cycle_end_date_ms: i64,
+    pub idempotence_key: Uuid,
+    pub status: RecurringRunStatus,
+    pub invoice_id: Option<Uuid>,
+    pub invoice_status: Option<InvoiceStatus>,
+    pub error_message: Option<String>,
+    ///start of the cycle after this one, profile moves to it only on success
+    pub next_run_date_ms: Option<i64>,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use chrono::NaiveDate;
+    use rstest::rstest;
+    use speculoos::assert_that;
+    use speculoos::option::OptionAssertions;
+    use speculoos::prelude::ResultAssertions;
+    use uuid::Uuid;
+
+    use invoice_doc_generator::invoice_line::line_quantity::LineQuantity;
+    use invoice_doc_generator::invoice_line1::UOM;
+
+    use crate::invoicing::invoicing_request_models::tests::{
+        a_create_invoice_line_request, a_create_invoice_request,
+    };
+    use crate::invoicing::invoicing_request_models::{
+        CreateInvoiceLineRequestBuilder, CreateInvoiceRequestBuilder,
+    };
+    use crate::invoicing::recurring_invoice::recurring_invoice_models::{
+        cycle_idempotence_key, cycle_invoice_request, BillingCycle,
+        CreateRecurringInvoiceProfileRequest, CreateRecurringInvoiceProfileRequestBuilder,
+        RecurrenceDate, RecurrenceFrequency, RecurrenceSchedule, RecurringIssueMode,
+        RecurringProfileName,
+    };
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    pub fn a_create_recurring_invoice_profile_request(
+        builder: CreateRecurringInvoiceProfileRequestBuilder,
+    ) -> CreateRecurringInvoiceProfileRequest {
+        CreateRecurringInvoiceProfileRequest {
+            idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
+            profile_name: builder
+                .profile_name
+                .unwrap_or_else(|| RecurringProfileName::new("monthly rent").unwrap()),
+            frequency: builder.frequency.unwrap_or(RecurrenceFrequency::Monthly),
+            start_date: builder
+                .start_date
+                .unwrap_or_else(|| RecurrenceDate::from_date(date("2024-04-01"))),
+            end_date: builder.end_date.flatten(),
+            prorate_first_cycle: builder.prorate_first_cycle.unwrap_or(false),
+            issue_mode: builder.issue_mode.unwrap_or(RecurringIssueMode::AutoIssue),
+            invoice_template: builder
+                .invoice_template
+                .unwrap_or_else(|| a_create_invoice_request(Default::default())),
+        }
+    }
+
+    fn a_schedule(
+        frequency: RecurrenceFrequency,
+        start_date: &str,
+        prorate_first_cycle: bool,
+    ) -> RecurrenceSchedule {
+        RecurrenceSchedule {
+            frequency,
+            start_date: date(start_date),
+            end_date: None,
+            prorate_first_cycle,
+        }
+    }
+
+    #[rstest]
+    #[case(RecurrenceFrequency::Weekly, 2, "2024-02-07", "2024-02-13")]
+    #[case(RecurrenceFrequency::Monthly, 1, "2024-01-31", "2024-02-28")]
+    #[case(RecurrenceFrequency::Monthly, 2, "2024-02-29", "2024-03-30")]
+    #[case(RecurrenceFrequency::Monthly, 3, "2024-03-31", "2024-04-29")]
+    #[case(RecurrenceFrequency::Quarterly, 2, "2024-04-30", "2024-07-30")]
+    #[case(RecurrenceFrequency::Yearly, 2, "2025-01-31", "2026-01-30")]
+    fn test_cycles_anchored_on_start_date(
+        #[case] frequency: RecurrenceFrequency,
+        #[case] cycle_no: i32,
+        #[case] start: &str,
+        #[case] end: &str,
+    ) {
+        let cycle = a_schedule(frequency, "2024-01-31", false)
+            .cycle(cycle_no)
+            .unwrap();
+        assert_that!(cycle).is_equal_to(BillingCycle {
+            cycle_no,
+            start_date: date(start),
+            end_date: date(end),
+            proration_factor: 1.0,
+        });
+    }
+
+    #[rstest]
+    #[case(RecurrenceFrequency::Weekly, "2024-01-10", "2024-01-14", 5.0 / 7.0)]
+    #[case(RecurrenceFrequency::Monthly, "2024-01-10", "2024-01-31", 22.0 / 31.0)]
+    #[case(RecurrenceFrequency::Quarterly, "2024-02-15", "2024-03-31", 46.0 / 91.0)]
+    #[case(RecurrenceFrequency::Yearly, "2024-02-15", "2024-03-31", 46.0 / 366.0)]
+    fn test_prorated_first_cycle(
+        #[case] frequency: RecurrenceFrequency,
+        #[case] start: &str,
+        #[case] end: &str,
+        #[case] factor: f64,
+    ) {
+        let schedule = a_schedule(frequency, start, true);
+        let first = schedule.cycle(1).unwrap();
+        assert_that!(first.start_date).is_equal_to(date(start));
+        assert_that!(first.end_date).is_equal_to(date(end));
+        assert_that!(first.proration_factor).is_equal_to(factor);
+        let second = schedule.cycle(2).unwrap();
+        assert_that!(second.start_date).is_equal_to(first.end_date.succ_opt().unwrap());
+        assert_that!(second.proration_factor).is_equal_to(1.0);
+    }
+
+    #[test]
+    fn test_start_on_period_boundary_is_not_prorated() {
+        let cycle = a_schedule(RecurrenceFrequency::Monthly, "2024-05-01", true)
+            .cycle(1)
+            .unwrap();
+        assert_that!(cycle.end_date).is_equal_to(date("2024-05-31"));
+        assert_that!(cycle.proration_factor).is_equal_to(1.0);
+    }
+
+    #[test]
+    fn test_no_cycle_after_end_date() {
+        let schedule = RecurrenceSchedule {
+            end_date: Some(date("2024-03-15")),
+            ..a_schedule(RecurrenceFrequency::Monthly, "2024-01-01", false)
+        };
+        assert_that!(schedule.cycle(3)).is_some();
+        assert_that!(schedule.cycle(4)).is_none();
+        assert_that!(schedule.cycle(0)).is_none();
+    }
+
+    #[test]
+    fn test_first_cycle_on_or_after() {
+        let schedule = a_schedule(RecurrenceFrequency::Monthly, "2024-01-15", false);
+        let cycle = schedule
+            .first_cycle_on_or_after(date("2024-04-20"), 2)
+            .unwrap();
+        assert_that!(cycle.cycle_no).is_equal_to(5);
+        assert_that!(cycle.start_date).is_equal_to(date("2024-05-15"));
+    }
+
+    #[test]
+    fn test_cycle_idempotence_key_is_deterministic() {
+        let profile_id = Uuid::now_v7();
+        assert_that!(cycle_idempotence_key(profile_id, 3))
+            .is_equal_to(cycle_idempotence_key(profile_id, 3));
+        assert_ne!(
+            cycle_idempotence_key(profile_id, 3),
+            cycle_idempotence_key(profile_id, 4)
+        );
+        assert_ne!(
+            cycle_idempotence_key(profile_id, 3),
+            cycle_idempotence_key(Uuid::now_v7(), 3)
+        );
+    }
+
+    #[test]
+    fn test_cycle_invoice_request_prorates_quantity() {
+        let mut line_builder = CreateInvoiceLineRequestBuilder::default();
+        line_builder.quantity(LineQuantity::new(2.0, UOM::Piece).unwrap());
+        let mut builder = CreateInvoiceRequestBuilder::default();
+        builder.invoice_lines(vec![a_create_invoice_line_request(line_builder)]);
+        let template = a_create_invoice_request(builder);
+        let profile_id = Uuid::now_v7();
+        let cycle = BillingCycle {
+            cycle_no: 1,
+            start_date: date("2024-01-10"),
+            end_date: date("2024-01-31"),
+            proration_factor: 22.0 / 31.0,
+        };
+        let req = cycle_invoice_request(&template, profile_id, &cycle).unwrap();
+        assert_that!(req.idempotence_key).is_equal_to(cycle_idempotence_key(profile_id, 1));
+        assert_that!(req.invoice_lines[0].quantity.get_quantity()).is_equal_to(1.4194);
+        assert_that!(req.invoice_remarks.map(|a| a.get_str().to_string()))
+            .is_some()
+            .is_equal_to("billing period 10-01-2024 to 31-01-2024".to_string());
+    }
+
+    #[rstest]
+    #[case("2024-02-30", false)]
+    #[case("15-02-2024", false)]
+    #[case("2024-02-15", true)]
+    fn test_recurrence_date(#[case] input: &str, #[case] valid: bool) {
+        let parsed = RecurrenceDate::new(input.to_string());
+        if valid {
+            assert_that!(parsed).is_ok();
+        } else {
+            assert_that!(parsed).is_err();
+        }
+    }
+}
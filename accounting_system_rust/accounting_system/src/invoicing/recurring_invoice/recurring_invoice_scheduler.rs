@@ -0,0 +1,47 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info};
+
+use crate::common_utils::utils::current_indian_date;
+use crate::invoicing::recurring_invoice::recurring_invoice_service::RecurringInvoiceService;
+
+pub const RECURRING_INVOICE_SCHEDULER_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+///runs the due profiles every interval, failed cycles stay due and are retried on the next tick
+pub fn spawn_recurring_invoice_scheduler(
+    service: Arc<dyn RecurringInvoiceService>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match service.run_due_profiles(current_indian_date()).await {
+                Ok(summary) => {
+                    if summary.profiles_processed > 0 {
+                        info!(
+                            profiles = summary.profiles_processed,
+                            invoices = summary.invoices_generated,
+                            failures = summary.failures.len(),
+                            "recurring invoice run completed"
+                        );
+                    }
+                    for failure in summary.failures.iter() {
+                        error!(
+                            tenant_id = %failure.tenant_id,
+                            profile_id = %failure.profile_id,
+                            cycle_no = failure.cycle_no,
+                            error = failure.error_message.as_str(),
+                            "recurring invoice generation failed"
+                        );
+                    }
+                }
+                Err(e) => error!(%e, "recurring invoice run failed"),
+            }
+        }
+    })
+}
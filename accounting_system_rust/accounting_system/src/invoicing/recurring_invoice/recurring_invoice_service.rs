@@ -0,0 +1,519 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use deadpool_postgres::Pool;
+#[cfg(test)]
+use mockall::automock;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::common_utils::dao_error::DaoError;
+use crate::common_utils::utils::current_indian_date;
+use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+use crate::invoicing::invoice_approval::invoice_approval_service::{
+    InvoiceApprovalService, InvoiceApprovalServiceError,
+};
+use crate::invoicing::invoicing_service::{InvoicingService, InvoicingServiceError};
+use crate::invoicing::recurring_invoice::recurring_invoice_dao::{
+    get_recurring_invoice_dao, RecurringInvoiceDao,
+};
+use crate::invoicing::recurring_invoice::recurring_invoice_models::{
+    cycle_idempotence_key, cycle_invoice_request, date_to_epoch_millis, BillingCycle,
+    CreateRecurringInvoiceProfileRequest, RecurringInvoiceProfile, RecurringInvoiceProfileDb,
+    RecurringInvoiceRun, RecurringInvoiceRunDb, RecurringInvoiceRunSummary, RecurringIssueMode,
+    RecurringRunFailure, RecurringRunStatus,
+};
+
+///profiles picked up in one pass of the scheduler, the rest are picked up in the next pass as the profiles
+/// failing in this pass are ordered after them
+const DUE_PROFILES_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Error)]
+pub enum RecurringInvoiceServiceError {
+    #[error("error in db {0}")]
+    Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("recurring invoice profile {0} not found")]
+    ProfileNotFound(Uuid),
+    #[error("{0}")]
+    Invoicing(#[from] InvoicingServiceError),
+    #[error("{0}")]
+    Approval(#[from] InvoiceApprovalServiceError),
+    #[error("{0}")]
+    Other(#[from] anyhow::Error),
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait RecurringInvoiceService: Send + Sync {
+    ///the invoice template is validated the same way as a regular invoice request
+    async fn create_profile(
+        &self,
+        req: CreateRecurringInvoiceProfileRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Uuid, RecurringInvoiceServiceError>;
+    async fn get_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<RecurringInvoiceProfile, RecurringInvoiceServiceError>;
+    async fn get_runs(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<RecurringInvoiceRun>, RecurringInvoiceServiceError>;
+    async fn pause_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), RecurringInvoiceServiceError>;
+    ///cycles which started while the profile was paused are skipped
+    async fn resume_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), RecurringInvoiceServiceError>;
+    ///generates the invoices of all cycles started on or before the date, failures are reported per profile
+    async fn run_due_profiles(
+        &self,
+        as_of: NaiveDate,
+    ) -> Result<RecurringInvoiceRunSummary, RecurringInvoiceServiceError>;
+}
+
+struct RecurringInvoiceServiceImpl {
+    dao: Arc<dyn RecurringInvoiceDao>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+}
+
+pub fn get_recurring_invoice_service(
+    arc: Arc<Pool>,
+    invoicing_service: Arc<dyn InvoicingService>,
+    invoice_approval_service: Arc<dyn InvoiceApprovalService>,
+) -> Arc<dyn RecurringInvoiceService> {
+    let dao = get_recurring_invoice_dao(arc);
+    let service = RecurringInvoiceServiceImpl {
+        dao,
+        invoicing_service,
+        invoice_approval_service,
+    };
+    Arc::new(service)
+}
+
+impl RecurringInvoiceServiceImpl {
+    fn validate_schedule(
+        req: &CreateRecurringInvoiceProfileRequest,
+    ) -> Result<(), RecurringInvoiceServiceError> {
+        let mut errors: Vec<String> = vec![];
+        let start_date = req.start_date.get_date();
+        if *start_date < current_indian_date() {
+            errors.push("start date cannot be in the past".to_string());
+        }
+        if let Some(end_date) = req.end_date.as_ref() {
+            if end_date.get_date() < start_date {
+                errors.push("end date cannot be before start date".to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(RecurringInvoiceServiceError::Validation(errors))
+        }
+    }
+
+    ///draft when asked by the profile or when invoices of the tenant need approval
+    async fn generate_cycle_invoice(
+        &self,
+        profile: &RecurringInvoiceProfile,
+        cycle: &BillingCycle,
+    ) -> Result<(Uuid, InvoiceStatus), RecurringInvoiceServiceError> {
+        let req = cycle_invoice_request(&profile.invoice_request, profile.id, cycle)?;
+        let draft = profile.issue_mode == RecurringIssueMode::Draft
+            || self
+                .invoice_approval_service
+                .is_approval_required(profile.tenant_id)
+                .await?;
+        if draft {
+            let created = self
+                .invoicing_service
+                .create_draft_invoice(req, profile.tenant_id, profile.created_by)
+                .await?;
+            Ok((created.invoice_id, InvoiceStatus::Draft))
+        } else {
+            //pdf, e-invoice and email of the issued invoice are produced by the jobs enqueued on its creation
+            let pdf_request = self
+                .invoicing_service
+                .create_invoice(req, profile.tenant_id, profile.created_by)
+                .await?;
+            Ok((pdf_request.invoice_id, InvoiceStatus::Issued))
+        }
+    }
+
+    ///generates the due cycles of a profile in order and stops at the first failure
+    async fn run_profile(
+        &self,
+        profile: &RecurringInvoiceProfile,
+        as_of: NaiveDate,
+    ) -> Result<u32, RecurringRunFailure> {
+        let failure = |cycle_no: i32, error_message: String| RecurringRunFailure {
+            tenant_id: profile.tenant_id,
+            profile_id: profile.id,
+            cycle_no,
+            error_message,
+        };
+        let schedule = profile
+            .schedule()
+            .map_err(|e| failure(profile.next_cycle_no, e.to_string()))?;
+        let mut generated = 0;
+        let mut cycle_no = profile.next_cycle_no;
+        while let Some(cycle) = schedule.cycle(cycle_no).filter(|a| a.start_date <= as_of) {
+            let result = self.generate_cycle_invoice(profile, &cycle).await;
+            let next_run_date_ms = schedule
+                .cycle(cycle_no + 1)
+                .and_then(|a| date_to_epoch_millis(a.start_date));
+            let mut run = RecurringInvoiceRunDb {
+                tenant_id: profile.tenant_id,
+                profile_id: profile.id,
+                cycle_no,
+                cycle_start_date_ms: date_to_epoch_millis(cycle.start_date).unwrap_or_default(),
+                cycle_end_date_ms: date_to_epoch_millis(cycle.end_date).unwrap_or_default(),
+                idempotence_key: cycle_idempotence_key(profile.id, cycle_no),
+                status: RecurringRunStatus::Succeeded,
+                invoice_id: None,
+                invoice_status: None,
+                error_message: None,
+                next_run_date_ms,
+            };
+            let error_message = match result {
+                Ok((invoice_id, invoice_status)) => {
+                    run.invoice_id = Some(invoice_id);
+                    run.invoice_status = Some(invoice_status);
+                    None
+                }
+                Err(e) => {
+                    run.status = RecurringRunStatus::Failed;
+                    run.error_message = Some(e.to_string().chars().take(1000).collect());
+                    Some(e.to_string())
+                }
+            };
+            self.dao
+                .record_run(&run)
+                .await
+                .map_err(|e| failure(cycle_no, e.to_string()))?;
+            if let Some(error_message) = error_message {
+                return Err(failure(cycle_no, error_message));
+            }
+            generated += 1;
+            cycle_no += 1;
+        }
+        Ok(generated)
+    }
+}
+
+#[async_trait]
+impl RecurringInvoiceService for RecurringInvoiceServiceImpl {
+    async fn create_profile(
+        &self,
+        req: CreateRecurringInvoiceProfileRequest,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Uuid, RecurringInvoiceServiceError> {
+        Self::validate_schedule(&req)?;
+        self.invoicing_service
+            .compute_invoice_document(req.invoice_template.clone(), tenant_id, user_id)
+            .await?;
+        let schedule = req.schedule();
+        let first_cycle = schedule.cycle(1).ok_or_else(|| {
+            RecurringInvoiceServiceError::Validation(vec![
+                "no billing cycle starts before the end date".to_string(),
+            ])
+        })?;
+        let db = RecurringInvoiceProfileDb {
+            idempotence_key: req.idempotence_key,
+            tenant_id,
+            profile_name: req.profile_name.inner().to_string(),
+            frequency: req.frequency,
+            start_date_ms: date_to_epoch_millis(schedule.start_date)
+                .context("invalid start date")?,
+            end_date_ms: schedule.end_date.and_then(date_to_epoch_millis),
+            prorate_first_cycle: req.prorate_first_cycle,
+            issue_mode: req.issue_mode,
+            next_run_date_ms: date_to_epoch_millis(first_cycle.start_date),
+            invoice_request: serde_json::to_value(&req.invoice_template)
+                .context("could not serialize recurring invoice template")?,
+            created_by: user_id,
+        };
+        let profile_id = self.dao.create_profile(&db).await?;
+        Ok(profile_id)
+    }
+
+    async fn get_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<RecurringInvoiceProfile, RecurringInvoiceServiceError> {
+        self.dao
+            .get_profile_by_id(profile_id, tenant_id)
+            .await?
+            .ok_or(RecurringInvoiceServiceError::ProfileNotFound(profile_id))
+    }
+
+    async fn get_runs(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Vec<RecurringInvoiceRun>, RecurringInvoiceServiceError> {
+        self.get_profile(profile_id, tenant_id).await?;
+        let runs = self.dao.get_runs(tenant_id, profile_id).await?;
+        Ok(runs)
+    }
+
+    async fn pause_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), RecurringInvoiceServiceError> {
+        let updated = self
+            .dao
+            .pause_profile(tenant_id, profile_id, user_id)
+            .await?;
+        if !updated {
+            return Err(RecurringInvoiceServiceError::ProfileNotFound(profile_id));
+        }
+        Ok(())
+    }
+
+    async fn resume_profile(
+        &self,
+        profile_id: Uuid,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), RecurringInvoiceServiceError> {
+        let profile = self.get_profile(profile_id, tenant_id).await?;
+        let next_cycle = profile
+            .schedule()?
+            .first_cycle_on_or_after(current_indian_date(), profile.next_cycle_no);
+        let next_cycle_no = next_cycle
+            .as_ref()
+            .map(|a| a.cycle_no)
+            .unwrap_or(profile.next_cycle_no);
+        let next_run_date_ms = next_cycle.and_then(|a| date_to_epoch_millis(a.start_date));
+        let updated = self
+            .dao
+            .resume_profile(
+                tenant_id,
+                profile_id,
+                next_cycle_no,
+                next_run_date_ms,
+                user_id,
+            )
+            .await?;
+        if !updated {
+            return Err(RecurringInvoiceServiceError::ProfileNotFound(profile_id));
+        }
+        Ok(())
+    }
+
+    async fn run_due_profiles(
+        &self,
+        as_of: NaiveDate,
+    ) -> Result<RecurringInvoiceRunSummary, RecurringInvoiceServiceError> {
+        let as_of_ms = date_to_epoch_millis(as_of).context("invalid run date")?;
+        let profiles = self
+            .dao
+            .get_due_profiles(as_of_ms, DUE_PROFILES_BATCH_SIZE)
+            .await?;
+        let mut summary = RecurringInvoiceRunSummary::default();
+        for profile in profiles.iter() {
+            summary.profiles_processed += 1;
+            match self.run_profile(profile, as_of).await {
+                Ok(generated) => summary.invoices_generated += generated,
+                Err(failure) => summary.failures.push(failure),
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::NaiveDate;
+    use speculoos::assert_that;
+    use speculoos::prelude::{ResultAssertions, VecAssertions};
+    use uuid::Uuid;
+
+    use crate::accounting::user::user_models::SEED_USER_ID;
+    use crate::invoicing::invoice_approval::invoice_approval_models::InvoiceStatus;
+    use crate::invoicing::invoice_approval::invoice_approval_service::MockInvoiceApprovalService;
+    use crate::invoicing::invoicing_request_models::tests::a_create_invoice_request;
+    use crate::invoicing::invoicing_request_models::CreateDraftInvoiceResponse;
+    use crate::invoicing::invoicing_service::{InvoicingServiceError, MockInvoicingService};
+    use crate::invoicing::recurring_invoice::recurring_invoice_dao::MockRecurringInvoiceDao;
+    use crate::invoicing::recurring_invoice::recurring_invoice_models::tests::a_create_recurring_invoice_profile_request;
+    use crate::invoicing::recurring_invoice::recurring_invoice_models::{
+        cycle_idempotence_key, date_to_epoch_millis, CreateRecurringInvoiceProfileRequestBuilder,
+        RecurrenceDate, RecurrenceFrequency, RecurringInvoiceProfile, RecurringIssueMode,
+        RecurringRunStatus,
+    };
+    use crate::invoicing::recurring_invoice::recurring_invoice_service::{
+        RecurringInvoiceService, RecurringInvoiceServiceError, RecurringInvoiceServiceImpl,
+    };
+    use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn a_profile(issue_mode: RecurringIssueMode, next_cycle_no: i32) -> RecurringInvoiceProfile {
+        RecurringInvoiceProfile {
+            id: Uuid::now_v7(),
+            tenant_id: *SEED_TENANT_ID,
+            profile_name: "monthly rent".to_string(),
+            frequency: RecurrenceFrequency::Monthly,
+            start_date_ms: date_to_epoch_millis(date("2024-01-01")).unwrap(),
+            end_date_ms: None,
+            prorate_first_cycle: false,
+            issue_mode,
+            paused: false,
+            next_cycle_no,
+            next_run_date_ms: None,
+            invoice_request: a_create_invoice_request(Default::default()),
+            created_by: *SEED_USER_ID,
+        }
+    }
+
+    fn a_service(
+        dao: MockRecurringInvoiceDao,
+        invoicing_service: MockInvoicingService,
+        approval_required: bool,
+    ) -> RecurringInvoiceServiceImpl {
+        let mut approval_service = MockInvoiceApprovalService::new();
+        approval_service
+            .expect_is_approval_required()
+            .returning(move |_| Ok(approval_required));
+        RecurringInvoiceServiceImpl {
+            dao: Arc::new(dao),
+            invoicing_service: Arc::new(invoicing_service),
+            invoice_approval_service: Arc::new(approval_service),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_catches_up_due_cycles_with_deterministic_keys() {
+        let profile = a_profile(RecurringIssueMode::Draft, 2);
+        let profile_id = profile.id;
+        let mut dao = MockRecurringInvoiceDao::new();
+        dao.expect_get_due_profiles()
+            .return_once(move |_, _| Ok(vec![profile]));
+        dao.expect_record_run()
+            .times(2)
+            .withf(|run| {
+                run.status == RecurringRunStatus::Succeeded
+                    && run.invoice_status == Some(InvoiceStatus::Draft)
+            })
+            .returning(|_| Ok(()));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_create_draft_invoice()
+            .times(2)
+            .withf(move |req, _, _| {
+                req.idempotence_key == cycle_idempotence_key(profile_id, 2)
+                    || req.idempotence_key == cycle_idempotence_key(profile_id, 3)
+            })
+            .returning(|_, _, _| {
+                Ok(CreateDraftInvoiceResponse {
+                    invoice_id: Uuid::now_v7(),
+                })
+            });
+        let service = a_service(dao, invoicing_service, false);
+        //cycles 2 and 3 start on 1st feb and 1st mar, cycle 4 starts after the run date
+        let summary = service.run_due_profiles(date("2024-03-15")).await.unwrap();
+        assert_that!(summary.profiles_processed).is_equal_to(1);
+        assert_that!(summary.invoices_generated).is_equal_to(2);
+        assert_that!(summary.failures).is_empty();
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_recorded_and_reported_per_profile() {
+        let failing = a_profile(RecurringIssueMode::AutoIssue, 1);
+        let failing_id = failing.id;
+        let mut dao = MockRecurringInvoiceDao::new();
+        dao.expect_get_due_profiles()
+            .return_once(move |_, _| Ok(vec![failing]));
+        dao.expect_record_run()
+            .times(1)
+            .withf(|run| {
+                run.status == RecurringRunStatus::Failed
+                    && run.error_message.as_deref() == Some("invoice series not found")
+            })
+            .returning(|_| Ok(()));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service
+            .expect_create_invoice()
+            .returning(|_, _, _| {
+                Err(InvoicingServiceError::Other(anyhow::anyhow!(
+                    "invoice series not found"
+                )))
+            });
+        let service = a_service(dao, invoicing_service, false);
+        let summary = service.run_due_profiles(date("2024-03-15")).await.unwrap();
+        assert_that!(summary.invoices_generated).is_equal_to(0);
+        assert_that!(summary.failures).has_length(1);
+        assert_that!(summary.failures[0].profile_id).is_equal_to(failing_id);
+        assert_that!(summary.failures[0].cycle_no).is_equal_to(1);
+    }
+
+    #[tokio::test]
+    async fn test_auto_issue_creates_draft_when_approval_required() {
+        let mut dao = MockRecurringInvoiceDao::new();
+        dao.expect_get_due_profiles()
+            .return_once(|_, _| Ok(vec![a_profile(RecurringIssueMode::AutoIssue, 1)]));
+        dao.expect_record_run()
+            .withf(|run| run.invoice_status == Some(InvoiceStatus::Draft))
+            .returning(|_| Ok(()));
+        let mut invoicing_service = MockInvoicingService::new();
+        invoicing_service.expect_create_invoice().never();
+        invoicing_service
+            .expect_create_draft_invoice()
+            .times(1)
+            .returning(|_, _, _| {
+                Ok(CreateDraftInvoiceResponse {
+                    invoice_id: Uuid::now_v7(),
+                })
+            });
+        let service = a_service(dao, invoicing_service, true);
+        let summary = service.run_due_profiles(date("2024-01-20")).await.unwrap();
+        assert_that!(summary.invoices_generated).is_equal_to(1);
+    }
+
+    #[tokio::test]
+    async fn test_create_profile_validates_dates() {
+        let mut builder = CreateRecurringInvoiceProfileRequestBuilder::default();
+        builder
+            .start_date(RecurrenceDate::from_date(date("2020-01-01")))
+            .end_date(Some(RecurrenceDate::from_date(date("2019-12-01"))));
+        let req = a_create_recurring_invoice_profile_request(builder);
+        let service = a_service(
+            MockRecurringInvoiceDao::new(),
+            MockInvoicingService::new(),
+            false,
+        );
+        let result = service
+            .create_profile(req, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await;
+        assert_that!(result).is_err();
+        assert!(matches!(
+            result,
+            Err(RecurringInvoiceServiceError::Validation(ref errors)) if errors.len() == 2
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+create type recurrence_frequency as enum ('weekly','monthly','quarterly','yearly');
+create type recurring_issue_mode as enum ('auto_issue','draft');
+create type recurring_run_status as enum ('succeeded','failed');
+
+create table recurring_invoice_profile
+(
+    id                  uuid primary key,
+    entity_version_id   integer default 0,
+    tenant_id           uuid references tenant (id)   not null,
+    active              bool,
+    approval_status     smallint                      not null,
+    remarks             varchar(70),
+    profile_name        varchar(50)                   not null,
+    frequency           recurrence_frequency          not null,
+    start_date_ms       bigint                        not null,
+    end_date_ms         bigint,
+    prorate_first_cycle bool                          not null,
+    issue_mode          recurring_issue_mode          not null,
+    paused              bool                          not null,
+    next_cycle_no       integer                       not null,--cycles are numbered from 1
+    next_run_date_ms    bigint,--start of the next cycle, null once all cycles till the end date are generated
+    invoice_request     jsonb                         not null,--template of the invoice created every cycle
+    created_by          uuid references app_user (id) not null,
+    updated_by          uuid references app_user (id),
+    created_at          bigint  default extract(epoch from now()) * 1000000,
+    updated_at          bigint  default extract(epoch from now()) * 1000000
+);
+
+create table recurring_invoice_run
+(
+    id                  uuid primary key,
+    tenant_id           uuid references tenant (id)                    not null,
+    profile_id          uuid references recurring_invoice_profile (id) not null,
+    cycle_no            integer                                        not null,
+    cycle_start_date_ms bigint                                         not null,
+    cycle_end_date_ms   bigint                                         not null,
+    idempotence_key     uuid                                           not null,--derived from profile id and cycle no
+    status              recurring_run_status                           not null,
+    invoice_id          uuid references invoice (id),
+    invoice_status      invoice_status,
+    error_message       varchar(1000),--reason of the last failed attempt
+    attempt_count       integer                                        not null,
+    created_at          bigint default extract(epoch from now()) * 1000000,
+    updated_at          bigint default extract(epoch from now()) * 1000000
+);
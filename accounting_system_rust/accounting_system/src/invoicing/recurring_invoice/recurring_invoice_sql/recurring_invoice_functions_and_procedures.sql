@@ -0,0 +1,103 @@
+create type create_recurring_invoice_profile_request as
+(
+    idempotence_key     uuid,
+    tenant_id           uuid,
+    profile_name        text,
+    frequency           recurrence_frequency,
+    start_date_ms       bigint,
+    end_date_ms         bigint,
+    prorate_first_cycle bool,
+    issue_mode          recurring_issue_mode,
+    next_run_date_ms    bigint,
+    invoice_request     jsonb,
+    created_by          uuid
+);
+
+create type recurring_invoice_run_request as
+(
+    tenant_id           uuid,
+    profile_id          uuid,
+    cycle_no            integer,
+    cycle_start_date_ms bigint,
+    cycle_end_date_ms   bigint,
+    idempotence_key     uuid,
+    status              recurring_run_status,
+    invoice_id          uuid,
+    invoice_status      invoice_status,
+    error_message       text,
+    next_run_date_ms    bigint
+);
+
+create or replace function create_recurring_invoice_profile(req create_recurring_invoice_profile_request) returns uuid as
+$$
+DECLARE
+    _profile_id   uuid := uuid_generate_v7();
+    impacted_rows int;
+    resp          jsonb;
+BEGIN
+    insert into idempotence_store (idempotence_key, workflow_type, response, created_at, updated_at)
+    values (req.idempotence_key, 'create_recurring_invoice_profile', null, default, default)
+    on conflict do nothing;
+    get diagnostics impacted_rows= row_count;
+    if impacted_rows != 0 then
+        insert into recurring_invoice_profile (id, entity_version_id, tenant_id, active, approval_status, remarks,
+                                               profile_name, frequency, start_date_ms, end_date_ms,
+                                               prorate_first_cycle, issue_mode, paused, next_cycle_no,
+                                               next_run_date_ms, invoice_request, created_by, updated_by,
+                                               created_at, updated_at)
+        values (_profile_id, 0, req.tenant_id, true, 1, null, req.profile_name, req.frequency, req.start_date_ms,
+                req.end_date_ms, req.prorate_first_cycle, req.issue_mode, false, 1, req.next_run_date_ms,
+                req.invoice_request, req.created_by, req.created_by, default, default);
+        resp := jsonb_build_object('id', _profile_id);
+        update idempotence_store
+        set response=resp
+        where idempotence_key = req.idempotence_key
+          and workflow_type = 'create_recurring_invoice_profile';
+        return _profile_id;
+    else
+        select response
+        from idempotence_store
+        where idempotence_store.idempotence_key = req.idempotence_key
+          and workflow_type = 'create_recurring_invoice_profile'
+        into resp;
+        return (resp ->> 'id')::uuid;
+    end if;
+end;
+$$ language plpgsql;
+
+--records an attempt of a cycle, the profile moves to the next cycle only when the attempt succeeded.
+--a succeeded cycle is never overwritten by a failure reported by a parallel scheduler
+create or replace function record_recurring_invoice_run(req recurring_invoice_run_request) returns void as
+$$
+BEGIN
+    insert into recurring_invoice_run (id, tenant_id, profile_id, cycle_no, cycle_start_date_ms, cycle_end_date_ms,
+                                       idempotence_key, status, invoice_id, invoice_status, error_message,
+                                       attempt_count, created_at, updated_at)
+    values (uuid_generate_v7(), req.tenant_id, req.profile_id, req.cycle_no, req.cycle_start_date_ms,
+            req.cycle_end_date_ms, req.idempotence_key, req.status, req.invoice_id, req.invoice_status,
+            req.error_message, 1, default, default)
+    on conflict (profile_id, cycle_no) do update
+        set status         = excluded.status,
+            invoice_id     = excluded.invoice_id,
+            invoice_status = excluded.invoice_status,
+            error_message  = excluded.error_message,
+            attempt_count  = recurring_invoice_run.attempt_count + 1,
+            updated_at     = extract(epoch from now()) * 1000000
+    where recurring_invoice_run.status != 'succeeded';
+    if req.status = 'succeeded' then
+        update recurring_invoice_profile
+        set next_cycle_no    = req.cycle_no + 1,
+            next_run_date_ms = req.next_run_date_ms,
+            updated_at       = extract(epoch from now()) * 1000000
+        where id = req.profile_id
+          and tenant_id = req.tenant_id
+          and next_cycle_no = req.cycle_no;
+    end if;
+end;
+$$ language plpgsql;
+
+create trigger recurring_invoice_profile_audit_trigger
+    after update or delete
+    on recurring_invoice_profile
+    for each row
+execute function create_audit_entry();
@@ -0,0 +1,2 @@
+create index if not exists recurring_invoice_profile_due_idx on recurring_invoice_profile (next_run_date_ms) where active and not paused;
+create unique index if not exists recurring_invoice_run_cycle_idx on recurring_invoice_run (profile_id, cycle_no);
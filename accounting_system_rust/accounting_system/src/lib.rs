@@ -4,9 +4,11 @@ pub mod accounting;
 pub mod ledger;
 
 mod audit_table;
+pub mod background_job;
 pub mod common_utils;
 mod configurations;
 pub mod db_schema_syncer;
+pub mod gst_returns;
 pub mod invoicing;
 pub mod masters;
 pub mod storage;
@@ -13,11 +13,33 @@ use crate::accounting::currency::currency_service::get_currency_service;
 use crate::accounting::postgres_factory::get_postgres_conn_pool;
 use crate::accounting::user::user_service::get_user_service;
 use crate::audit_table::audit_service::get_audit_service;
+use crate::background_job::background_job_runner::{
+    spawn_background_job_runner, BACKGROUND_JOB_POLL_INTERVAL,
+};
+use crate::background_job::background_job_service::get_background_job_service;
+use crate::common_utils::email_client::get_email_client;
 use crate::common_utils::pagination::pagination_utils::pagination_header_middleware;
 use crate::common_utils::utils::tenant_user_header_middleware;
+use crate::gst_returns::gstr1::gstr1_service::get_gstr1_service;
+use crate::gst_returns::gstr3b::gstr3b_service::get_gstr3b_service;
+use crate::invoicing::ar_aging::ar_aging_service::get_ar_aging_service;
+use crate::invoicing::delivery_challan::delivery_challan_service::get_delivery_challan_service;
+use crate::invoicing::einvoice_portal_client::get_einvoice_portal_client;
+use crate::invoicing::eway_bill::eway_bill_portal_client::get_eway_bill_portal_client;
+use crate::invoicing::eway_bill::eway_bill_service::get_eway_bill_service;
+use crate::invoicing::invoice_approval::invoice_approval_service::get_invoice_approval_service;
+use crate::invoicing::invoice_import::invoice_import_service::get_invoice_import_service;
+use crate::invoicing::invoice_jobs::get_invoice_job_handlers;
 use crate::invoicing::invoice_template::invoice_template_service::get_invoice_template_master_service;
 use crate::invoicing::invoicing_series::invoicing_series_service::get_invoicing_series_service;
 use crate::invoicing::invoicing_service::get_invoicing_service;
+use crate::invoicing::purchase_invoice::purchase_invoice_service::get_purchase_invoice_service;
+use crate::invoicing::quotation::quotation_service::get_quotation_service;
+use crate::invoicing::receipt::receipt_service::get_receipt_service;
+use crate::invoicing::recurring_invoice::recurring_invoice_scheduler::{
+    spawn_recurring_invoice_scheduler, RECURRING_INVOICE_SCHEDULER_INTERVAL,
+};
+use crate::invoicing::recurring_invoice::recurring_invoice_service::get_recurring_invoice_service;
 use crate::ledger::ledger_transfer_service::get_ledger_transfer_service;
 use crate::ledger::ledgermaster::ledger_master_service::get_ledger_master_service;
 use crate::masters::address_master::address_service::get_address_service;
@@ -36,9 +58,11 @@ mod accounting;
 mod ledger;
 
 mod audit_table;
+mod background_job;
 mod common_utils;
 mod configurations;
 mod db_schema_syncer;
+mod gst_returns;
 mod invoicing;
 mod masters;
 mod storage;
@@ -107,10 +131,15 @@ async fn main() -> io::Result<()> {
         state_master_service.clone(),
     );
     let business_entity_service =
-        get_business_entity_master_service(pool.clone(), address_service.clone());
-    let invoice_template_service = get_invoice_template_master_service(pool.clone());
+        get_business_entity_master_service(pool.clone(), address_service.clone(), storage.clone());
+    let invoice_template_service =
+        get_invoice_template_master_service(pool.clone(), storage.clone());
     let invoicing_series_service = get_invoicing_series_service(pool.clone());
     let product_item_serv = get_product_item_service(pool.clone());
+    let invoice_approval_service = get_invoice_approval_service(pool.clone());
+    let background_job_service = get_background_job_service(pool.clone());
+    let einvoice_portal_client = get_einvoice_portal_client();
+    let email_client = get_email_client();
     let invoicing_service = get_invoicing_service(
         pool.clone(),
         tenant_service.clone(),
@@ -120,7 +149,56 @@ async fn main() -> io::Result<()> {
         invoice_template_service.clone(),
         storage.clone(),
         product_item_serv.clone(),
+        invoice_approval_service.clone(),
+        background_job_service.clone(),
+        einvoice_portal_client,
+        email_client.clone(),
     );
+    spawn_background_job_runner(
+        background_job_service.clone(),
+        get_invoice_job_handlers(invoicing_service.clone(), email_client),
+        BACKGROUND_JOB_POLL_INTERVAL,
+    );
+    let invoice_import_service = get_invoice_import_service(pool.clone(), invoicing_service.clone());
+    let eway_bill_service = get_eway_bill_service(
+        pool.clone(),
+        business_entity_service.clone(),
+        get_eway_bill_portal_client(),
+    );
+    let quotation_service = get_quotation_service(
+        pool.clone(),
+        invoicing_service.clone(),
+        invoicing_series_service.clone(),
+        invoice_approval_service.clone(),
+        storage.clone(),
+    );
+    let delivery_challan_service = get_delivery_challan_service(
+        pool.clone(),
+        invoicing_service.clone(),
+        invoicing_series_service.clone(),
+        invoice_approval_service.clone(),
+        storage.clone(),
+    );
+    let purchase_invoice_service = get_purchase_invoice_service(
+        pool.clone(),
+        business_entity_service.clone(),
+        product_item_serv.clone(),
+        currency_service.clone(),
+        invoicing_series_service.clone(),
+    );
+    let recurring_invoice_service = get_recurring_invoice_service(
+        pool.clone(),
+        invoicing_service.clone(),
+        invoice_approval_service.clone(),
+    );
+    spawn_recurring_invoice_scheduler(
+        recurring_invoice_service.clone(),
+        RECURRING_INVOICE_SCHEDULER_INTERVAL,
+    );
+    let receipt_service = get_receipt_service(pool.clone());
+    let ar_aging_service = get_ar_aging_service(pool.clone());
+    let gstr1_service = get_gstr1_service(pool.clone());
+    let gstr3b_service = get_gstr3b_service(pool.clone(), gstr1_service.clone());
     // let invoice_template_service= get_invoice_template_service();
     println!("{}", std::process::id());
     HttpServer::new(move || {
@@ -191,6 +269,66 @@ async fn main() -> io::Result<()> {
             .configure(|conf| {
                 invoicing::invoicing_http_api::init_routes(conf, invoicing_service.clone())
             })
+            .configure(|conf| {
+                background_job::background_job_http_api::init_routes(
+                    conf,
+                    background_job_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::invoice_approval::invoice_approval_http_api::init_routes(
+                    conf,
+                    invoice_approval_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::invoice_import::invoice_import_http_api::init_routes(
+                    conf,
+                    invoice_import_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::eway_bill::eway_bill_http_api::init_routes(
+                    conf,
+                    eway_bill_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::quotation::quotation_http_api::init_routes(
+                    conf,
+                    quotation_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::delivery_challan::delivery_challan_http_api::init_routes(
+                    conf,
+                    delivery_challan_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::purchase_invoice::purchase_invoice_http_api::init_routes(
+                    conf,
+                    purchase_invoice_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::receipt::receipt_http_api::init_routes(conf, receipt_service.clone())
+            })
+            .configure(|conf| {
+                invoicing::recurring_invoice::recurring_invoice_http_api::init_routes(
+                    conf,
+                    recurring_invoice_service.clone(),
+                )
+            })
+            .configure(|conf| {
+                invoicing::ar_aging::ar_aging_http_api::init_routes(conf, ar_aging_service.clone())
+            })
+            .configure(|conf| {
+                gst_returns::gstr1::gstr1_http_api::init_routes(conf, gstr1_service.clone())
+            })
+            .configure(|conf| {
+                gst_returns::gstr3b::gstr3b_http_api::init_routes(conf, gstr3b_service.clone())
+            })
             .configure(|conf| {
                 masters::product_item_master::product_item_http_api::init_routes(
                     conf,
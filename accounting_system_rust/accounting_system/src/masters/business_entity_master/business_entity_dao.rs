@@ -13,8 +13,8 @@ use crate::common_utils::db_row_conversion_utils::{
 };
 use crate::common_utils::utils::parse_db_output_of_insert_create_and_return_uuid;
 use crate::masters::business_entity_master::business_entity_models::{
-    BusinessEntityMaster, BusinessEntityName, BusinessEntityType, CreateBusinessEntityRequest,
-    Email, PhoneNumber,
+    BusinessEntityInvoiceAssets, BusinessEntityMaster, BusinessEntityName, BusinessEntityType,
+    CreateBusinessEntityRequest, Email, InvoiceAssetKind, PhoneNumber, SezClassification,
 };
 use crate::masters::company_master::company_master_models::gstin_no::GstinNo;
 
@@ -32,12 +32,32 @@ pub trait BusinessEntityDao: Send + Sync {
         id: &Uuid,
         tenant_id: &Uuid,
     ) -> Result<Option<BusinessEntityMaster>, DaoError>;
+    ///business entities not found for the tenant are omitted
+    async fn get_business_entities_by_ids(
+        &self,
+        ids: &[Uuid],
+        tenant_id: &Uuid,
+    ) -> Result<Vec<BusinessEntityMaster>, DaoError>;
     async fn is_business_entity_exist(&self, id: &Uuid, tenant_id: &Uuid)
         -> Result<bool, DaoError>;
+    ///false when the business entity does not exist for the tenant
+    async fn upsert_invoice_asset(
+        &self,
+        business_entity_id: Uuid,
+        kind: InvoiceAssetKind,
+        s3_id: &str,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError>;
+    async fn get_invoice_assets(
+        &self,
+        business_entity_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<BusinessEntityInvoiceAssets>, DaoError>;
 }
 
 const TABLE_NAME: &str = "business_entity";
-const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,eligible_supplier,name,email,phone,address_id,gstin,created_by,updated_by,created_at,updated_at";
+const SELECT_FIELDS: &str = "id,entity_version_id,tenant_id,active,approval_status,remarks,eligible_supplier,name,email,phone,address_id,gstin,sez_classification::text,created_by,updated_by,created_at,updated_at";
 const QUERY_BY_ID: &str = concatcp!(
     "select ",
     SELECT_FIELDS,
@@ -46,6 +66,28 @@ const QUERY_BY_ID: &str = concatcp!(
     " where id=$1 and tenant_id=$2"
 );
 
+const QUERY_BY_IDS: &str = concatcp!(
+    "select ",
+    SELECT_FIELDS,
+    " from ",
+    TABLE_NAME,
+    " where id=any($1) and tenant_id=$2"
+);
+
+///only the asset being uploaded is passed, the others keep their stored keys
+const UPSERT_INVOICE_ASSET: &str = "insert into business_entity_invoice_detail (id,tenant_id,active,\
+approval_status,business_entity_id,business_logo_s3_id,invoice_signature_s3_id,terms_and_conditions_s3_id,\
+e_invoicing_applicable,created_by,updated_by) select $1,tenant_id,true,1,id,$4,$5,$6,false,$7,$7 \
+from business_entity where id=$2 and tenant_id=$3 on conflict (business_entity_id) do update set \
+business_logo_s3_id=coalesce(excluded.business_logo_s3_id,business_entity_invoice_detail.business_logo_s3_id),\
+invoice_signature_s3_id=coalesce(excluded.invoice_signature_s3_id,business_entity_invoice_detail.invoice_signature_s3_id),\
+terms_and_conditions_s3_id=coalesce(excluded.terms_and_conditions_s3_id,business_entity_invoice_detail.terms_and_conditions_s3_id),\
+entity_version_id=business_entity_invoice_detail.entity_version_id+1,updated_by=excluded.updated_by,\
+updated_at=extract(epoch from now()) * 1000000";
+
+const GET_INVOICE_ASSETS: &str = "select business_logo_s3_id,invoice_signature_s3_id,\
+terms_and_conditions_s3_id from business_entity_invoice_detail where business_entity_id=$1 and tenant_id=$2";
+
 struct BusinessEntityDaoImpl {
     postgres_client: Arc<Pool>,
 }
@@ -82,10 +124,13 @@ impl TryFrom<Row> for BusinessEntityMaster {
                     .context("error during db row conversion")?,
             }
         };
+        let sez_classification = SezClassification::try_from(row.get::<usize, &str>(next_ind + 6))
+            .context("error during db row conversion")?;
         Ok(BusinessEntityMaster {
             base_master_fields,
             entity_type: e_type,
-            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 6, &row)?,
+            sez_classification,
+            audit_metadata: convert_row_to_audit_metadata_base(next_ind + 7, &row)?,
         })
     }
 }
@@ -116,7 +161,7 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
             } => {
                 format!(
                     "Row('{}','{}',{}::smallint,true,'{}','{}','{}','{address_id}','{}',\
-                 '{}')",
+                 '{}','{}')",
                     r.idempotence_key,
                     tenant_id,
                     1,
@@ -124,7 +169,8 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
                     email.inner(),
                     phone.inner(),
                     gstin.get_str(),
-                    user_id
+                    user_id,
+                    r.sez_classification.as_str()
                 )
             }
             BusinessEntityType::Other {
@@ -136,7 +182,7 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
             } => {
                 format!(
                     "Row('{}','{}',{}::smallint,false,'{}',{},'{}',{},{},\
-                 '{}')",
+                 '{}','{}')",
                     r.idempotence_key,
                     tenant_id,
                     1,
@@ -153,7 +199,8 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
                         .as_ref()
                         .map(|a| format!("'{}'", a.get_str()))
                         .unwrap_or("null".to_string()),
-                    user_id
+                    user_id,
+                    r.sez_classification.as_str()
                 )
             }
         };
@@ -187,6 +234,21 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
         Ok(en)
     }
 
+    async fn get_business_entities_by_ids(
+        &self,
+        ids: &[Uuid],
+        tenant_id: &Uuid,
+    ) -> Result<Vec<BusinessEntityMaster>, DaoError> {
+        self.postgres_client
+            .get()
+            .await?
+            .query(QUERY_BY_IDS, &[&ids, &tenant_id])
+            .await?
+            .into_iter()
+            .map(|a| a.try_into())
+            .collect()
+    }
+
     async fn is_business_entity_exist(
         &self,
         id: &Uuid,
@@ -204,6 +266,54 @@ impl BusinessEntityDao for BusinessEntityDaoImpl {
         let exists: bool = row.get(0);
         Ok(exists)
     }
+
+    async fn upsert_invoice_asset(
+        &self,
+        business_entity_id: Uuid,
+        kind: InvoiceAssetKind,
+        s3_id: &str,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, DaoError> {
+        let s3_id_of = |a: InvoiceAssetKind| if a == kind { Some(s3_id) } else { None };
+        let updated = self
+            .postgres_client
+            .get()
+            .await?
+            .execute(
+                UPSERT_INVOICE_ASSET,
+                &[
+                    &Uuid::now_v7(),
+                    &business_entity_id,
+                    &tenant_id,
+                    &s3_id_of(InvoiceAssetKind::Logo),
+                    &s3_id_of(InvoiceAssetKind::Signature),
+                    &s3_id_of(InvoiceAssetKind::TermsAndConditions),
+                    &user_id,
+                ],
+            )
+            .await?;
+        Ok(updated != 0)
+    }
+
+    async fn get_invoice_assets(
+        &self,
+        business_entity_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<Option<BusinessEntityInvoiceAssets>, DaoError> {
+        let assets = self
+            .postgres_client
+            .get()
+            .await?
+            .query_opt(GET_INVOICE_ASSETS, &[&business_entity_id, &tenant_id])
+            .await?
+            .map(|row| BusinessEntityInvoiceAssets {
+                business_logo_s3_id: row.get(0),
+                invoice_signature_s3_id: row.get(1),
+                terms_and_conditions_s3_id: row.get(2),
+            });
+        Ok(assets)
+    }
 }
 
 #[cfg(test)]
@@ -222,6 +332,9 @@ mod tests {
     use crate::masters::business_entity_master::business_entity_models::tests::{
         a_create_business_entity_request, SEED_BUSINESS_ENTITY_ID2,
     };
+    use crate::masters::business_entity_master::business_entity_models::{
+        BusinessEntityInvoiceAssets, InvoiceAssetKind,
+    };
     use crate::tenant::tenant_models::tests::SEED_TENANT_ID;
 
     #[tokio::test]
@@ -245,6 +358,26 @@ mod tests {
         assert!(!not_exist);
     }
 
+    #[tokio::test]
+    async fn test_get_business_entities_by_ids_omits_unknown_ids() {
+        let dao = get_dao_generic(
+            |a| BusinessEntityDaoImpl {
+                postgres_client: a.clone(),
+            },
+            None,
+        )
+        .await;
+        let entities = dao
+            .get_business_entities_by_ids(
+                &[*SEED_BUSINESS_ENTITY_ID2, Uuid::now_v7()],
+                &SEED_TENANT_ID,
+            )
+            .await
+            .unwrap();
+        assert_that!(entities.len()).is_equal_to(1);
+        assert_that!(entities[0].base_master_fields.id).is_equal_to(*SEED_BUSINESS_ENTITY_ID2);
+    }
+
     #[tokio::test]
     async fn test_create_and_get_dao() {
         let dao = get_dao_generic(
@@ -262,4 +395,49 @@ mod tests {
         let k = dao.get_business_entity(&p, &*SEED_TENANT_ID).await.unwrap();
         assert_that!(k).is_some();
     }
+
+    #[tokio::test]
+    async fn test_upsert_and_get_invoice_assets() {
+        let dao = get_dao_generic(
+            |a| BusinessEntityDaoImpl {
+                postgres_client: a.clone(),
+            },
+            None,
+        )
+        .await;
+        let be = a_create_business_entity_request(Default::default()).try_into().unwrap();
+        let id = dao
+            .create_business_entity(&be, *SEED_TENANT_ID, *SEED_USER_ID)
+            .await
+            .unwrap();
+        let none = dao.get_invoice_assets(id, *SEED_TENANT_ID).await.unwrap();
+        assert_that!(none).is_none();
+        for (kind, key) in [
+            (InvoiceAssetKind::Logo, "logo-key"),
+            (InvoiceAssetKind::TermsAndConditions, "tnc-key"),
+        ] {
+            let upserted = dao
+                .upsert_invoice_asset(id, kind, key, *SEED_TENANT_ID, *SEED_USER_ID)
+                .await
+                .unwrap();
+            assert!(upserted);
+        }
+        let assets = dao.get_invoice_assets(id, *SEED_TENANT_ID).await.unwrap();
+        assert_that!(assets).is_equal_to(Some(BusinessEntityInvoiceAssets {
+            business_logo_s3_id: Some("logo-key".to_string()),
+            invoice_signature_s3_id: None,
+            terms_and_conditions_s3_id: Some("tnc-key".to_string()),
+        }));
+        let missing_entity = dao
+            .upsert_invoice_asset(
+                Uuid::now_v7(),
+                InvoiceAssetKind::Logo,
+                "logo-key",
+                *SEED_TENANT_ID,
+                *SEED_USER_ID,
+            )
+            .await
+            .unwrap();
+        assert!(!missing_entity);
+    }
 }
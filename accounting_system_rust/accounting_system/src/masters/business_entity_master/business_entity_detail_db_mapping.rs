@@ -4,6 +4,8 @@ pub struct BusinessEntityDetailDbMapping {}
 
 const BUSINESS_ENTITY_DETAIL_SEED_CSV: &str =
     include_str!("./business_entity_master_sql/business_entity_invoice_detail.csv");
+const BUSINESS_ENTITY_DETAIL_INDEXES_SQL: &str =
+    include_str!("./business_entity_master_sql/business_entity_invoice_detail_indexes.sql");
 
 impl DbStructMapping for BusinessEntityDetailDbMapping {
     fn table_name(&self) -> Option<&'static str> {
@@ -15,7 +17,7 @@ impl DbStructMapping for BusinessEntityDetailDbMapping {
     }
 
     fn get_index_creation_script(&self) -> &'static str {
-        ""
+        BUSINESS_ENTITY_DETAIL_INDEXES_SQL
     }
 
     fn get_functions_and_procedures_script(&self) -> &'static str {
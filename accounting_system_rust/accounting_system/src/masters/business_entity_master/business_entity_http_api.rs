@@ -1,21 +1,27 @@
 use std::sync::Arc;
 
 use actix_web::http::StatusCode;
-use actix_web::web::{Data, Path};
-use actix_web::{web, HttpResponseBuilder, Responder, ResponseError};
+use actix_web::web::{Data, Path, Payload};
+use actix_web::{error, web, HttpResponseBuilder, Responder, ResponseError};
 use uuid::Uuid;
 
 use crate::common_utils::utils::{TenantId, UserId};
-use crate::masters::business_entity_master::business_entity_models::{CreateBusinessEntityRequestRaw};
+use crate::masters::business_entity_master::business_entity_models::{
+    CreateBusinessEntityRequestRaw, InvoiceAssetKind,
+};
 use crate::masters::business_entity_master::business_entity_service::{
     BusinessEntityService, BusinessEntityServiceError,
 };
 use crate::setup_routes;
 
+const MAX_INVOICE_ASSET_BYTES: usize = 1024 * 1024;
+
 impl ResponseError for BusinessEntityServiceError {
     fn status_code(&self) -> StatusCode {
         match self {
             BusinessEntityServiceError::Db(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            BusinessEntityServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            BusinessEntityServiceError::NotFound(_) => StatusCode::NOT_FOUND,
             BusinessEntityServiceError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -43,13 +49,52 @@ async fn get_business_entity_master_by_id(
         .await?;
     Ok(HttpResponseBuilder::new(StatusCode::OK).json(pd))
 }
+
+async fn upload_invoice_asset(
+    data: Data<Arc<dyn BusinessEntityService>>,
+    path: Path<(Uuid, InvoiceAssetKind)>,
+    payload: Payload,
+    tenant_id: TenantId,
+    user_id: UserId,
+) -> actix_web::Result<impl Responder> {
+    let bytes = match payload.to_bytes_limited(MAX_INVOICE_ASSET_BYTES).await {
+        Ok(bytes) => bytes?,
+        Err(e) => return Err(error::ErrorPayloadTooLarge(e)),
+    };
+    let (business_entity_id, kind) = path.into_inner();
+    data.upload_invoice_asset(
+        business_entity_id,
+        kind,
+        bytes.to_vec(),
+        tenant_id.inner(),
+        user_id.inner(),
+    )
+    .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).finish())
+}
+
+async fn get_invoice_assets(
+    data: Data<Arc<dyn BusinessEntityService>>,
+    business_entity_id: Path<Uuid>,
+    tenant_id: TenantId,
+) -> actix_web::Result<impl Responder> {
+    let assets = data
+        .get_invoice_assets(business_entity_id.into_inner(), tenant_id.inner())
+        .await?;
+    Ok(HttpResponseBuilder::new(StatusCode::OK).json(assets))
+}
+
 setup_routes!(
     BusinessEntityService,
     "/business-entity",
     "/create",
     web::post().to(create_business_entity_master),
     "/id/{business_entity_id}",
-    web::get().to(get_business_entity_master_by_id)
+    web::get().to(get_business_entity_master_by_id),
+    "/id/{business_entity_id}/invoice-asset",
+    web::get().to(get_invoice_assets),
+    "/id/{business_entity_id}/invoice-asset/{kind}",
+    web::post().to(upload_invoice_asset)
 );
 
 #[cfg(test)]
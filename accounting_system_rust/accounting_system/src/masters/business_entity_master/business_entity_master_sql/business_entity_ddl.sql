@@ -1,3 +1,5 @@
+create type sez_classification as enum ('not_applicable','sez_unit','sez_developer');
+
 create table business_entity
 (
     id                uuid primary key,
@@ -12,6 +14,7 @@ create table business_entity
     phone             varchar(15),
     address_id        uuid,
     gstin             varchar(50),
+    sez_classification sez_classification default 'not_applicable' not null,
     created_by        uuid references app_user (id) not null,
     updated_by        uuid references app_user (id),
     created_at        bigint  default extract(epoch from now()) * 1000000,
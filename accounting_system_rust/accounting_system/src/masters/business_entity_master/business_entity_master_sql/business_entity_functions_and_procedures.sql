@@ -9,7 +9,8 @@ create type create_business_entity_request as
     phone             text,
     address_id        uuid,
     gstin             text,
-    created_by        uuid
+    created_by        uuid,
+    sez_classification text
 );
 create or replace function create_business_entity(req create_business_entity_request) returns uuid as
 $$
@@ -26,9 +27,10 @@ BEGIN
         select uuid_generate_v7() into business_entity_id;
         insert into business_entity(id, entity_version_id, tenant_id, active, approval_status,
                                     remarks, eligible_supplier, name, email, phone, address_id,
-                                    gstin, created_by, updated_by, created_at, updated_at)
+                                    gstin, sez_classification, created_by, updated_by, created_at, updated_at)
         values (business_entity_id, 0, req.tenant_id, true, req.approval_status, null, req.eligible_supplier, req.name,
-                req.email, req.phone, req.address_id, req.gstin, req.created_by, req.created_by, default, default);
+                req.email, req.phone, req.address_id, req.gstin,
+                coalesce(req.sez_classification, 'not_applicable')::sez_classification, req.created_by, req.created_by, default, default);
         update idempotence_store
         set response=json_build_object('id', business_entity_id)
         where idempotence_store.idempotence_key = req.idempotence_key
@@ -0,0 +1,2 @@
+--one invoice detail row per business entity, invoice assets are upserted on it
+create unique index if not exists business_entity_invoice_detail_entity_idx on business_entity_invoice_detail (business_entity_id);
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use anyhow::{Context, ensure};
+use anyhow::{anyhow, Context, ensure};
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -24,9 +24,48 @@ pub struct BusinessEntityMaster {
     pub base_master_fields: BaseMasterFields,
     #[serde(flatten)]
     pub entity_type: BusinessEntityType,
+    #[serde(default)]
+    pub sez_classification: SezClassification,
     pub audit_metadata: AuditMetadataBase,
 }
 
+///supplies to a sez unit or developer are zero-rated and always inter-state
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SezClassification {
+    #[default]
+    NotApplicable,
+    SezUnit,
+    SezDeveloper,
+}
+
+impl SezClassification {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SezClassification::NotApplicable => "not_applicable",
+            SezClassification::SezUnit => "sez_unit",
+            SezClassification::SezDeveloper => "sez_developer",
+        }
+    }
+
+    pub fn is_sez(&self) -> bool {
+        *self != SezClassification::NotApplicable
+    }
+}
+
+impl TryFrom<&str> for SezClassification {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "not_applicable" => Ok(SezClassification::NotApplicable),
+            "sez_unit" => Ok(SezClassification::SezUnit),
+            "sez_developer" => Ok(SezClassification::SezDeveloper),
+            _ => Err(anyhow!("invalid sez classification {}", value)),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum BusinessEntityType {
@@ -53,6 +92,12 @@ impl BusinessEntityType {
             BusinessEntityType::Other { gstin, .. } => gstin.as_ref(),
         }
     }
+    pub fn extract_email(&self) -> Option<&Email> {
+        match self {
+            BusinessEntityType::EligibleSupplier { email, .. } => Some(email),
+            BusinessEntityType::Other { email, .. } => email.as_ref(),
+        }
+    }
     pub fn get_name(&self) -> &str {
         match self {
             BusinessEntityType::EligibleSupplier { name, .. } => name.inner(),
@@ -165,6 +210,8 @@ pub struct CreateBusinessEntityRequest {
     pub idempotence_key: Uuid,
     #[serde(flatten)]
     pub entity_type: BusinessEntityType,
+    #[serde(default)]
+    pub sez_classification: SezClassification,
 }
 
 #[derive(Debug, Serialize, Deserialize, Builder)]
@@ -175,13 +222,18 @@ pub struct CreateBusinessEntityRequestRaw {
     pub phone: PhoneNumber,
     pub address_id: Option<Uuid>,
     pub gstin: Option<GstinNo>,
-
+    #[serde(default)]
+    pub sez_classification: SezClassification,
 }
 
 impl TryFrom<CreateBusinessEntityRequestRaw> for CreateBusinessEntityRequest {
     type Error = anyhow::Error;
 
     fn try_from(value: CreateBusinessEntityRequestRaw) -> Result<Self, Self::Error> {
+        ensure!(
+            !value.sez_classification.is_sez() || value.gstin.is_some(),
+            "gstin is mandatory for a sez unit or developer"
+        );
         let entity_type: BusinessEntityType = if value.gstin.is_none() || value.address_id.is_none() || value.email.is_none() {
             BusinessEntityType::Other {
                 name: value.name,
@@ -203,10 +255,39 @@ impl TryFrom<CreateBusinessEntityRequestRaw> for CreateBusinessEntityRequest {
         Ok(CreateBusinessEntityRequest {
             idempotence_key: value.idempotence_key,
             entity_type,
+            sez_classification: value.sez_classification,
         })
     }
 }
 
+///assets of a supplier printed on its invoices
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceAssetKind {
+    Logo,
+    Signature,
+    ///plain utf-8 text
+    TermsAndConditions,
+}
+
+impl InvoiceAssetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvoiceAssetKind::Logo => "logo",
+            InvoiceAssetKind::Signature => "signature",
+            InvoiceAssetKind::TermsAndConditions => "terms_and_conditions",
+        }
+    }
+}
+
+///storage keys of the invoice assets of a business entity, none for the ones not uploaded
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct BusinessEntityInvoiceAssets {
+    pub business_logo_s3_id: Option<String>,
+    pub invoice_signature_s3_id: Option<String>,
+    pub terms_and_conditions_s3_id: Option<String>,
+}
+
 impl Default for BusinessEntityName {
     fn default() -> Self {
         Self("Default Business Entity".to_string())
@@ -260,6 +341,7 @@ pub mod tests {
         BusinessEntityMaster {
             base_master_fields: b.base_master_fields.unwrap_or_default(),
             entity_type: b.entity_type.unwrap_or_default(),
+            sez_classification: b.sez_classification.unwrap_or_default(),
             audit_metadata: Default::default(),
         }
     }
@@ -274,6 +356,7 @@ pub mod tests {
             phone: b.phone.unwrap_or(PhoneNumber::new("1234567891").unwrap()),
             address_id: b.address_id.flatten(),
             gstin: b.gstin.flatten(),
+            sez_classification: b.sez_classification.unwrap_or_default(),
         }
     }
 }
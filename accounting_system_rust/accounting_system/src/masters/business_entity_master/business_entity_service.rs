@@ -10,18 +10,31 @@ use moka::future::Cache;
 use thiserror::Error;
 use uuid::Uuid;
 
+use pdf_doc_generator::invoice_template;
+
 use crate::common_utils::cache_utils::get_or_fetch_entity;
 use crate::common_utils::dao_error::DaoError;
 use crate::masters::address_master::address_service::AddressService;
 use crate::masters::business_entity_master::business_entity_dao::{
     get_business_entity_dao, BusinessEntityDao,
 };
-use crate::masters::business_entity_master::business_entity_models::{BusinessEntityDto, CreateBusinessEntityRequest, CreateBusinessEntityRequestRaw};
+use crate::masters::business_entity_master::business_entity_models::{
+    BusinessEntityDto, BusinessEntityInvoiceAssets, BusinessEntityMaster,
+    CreateBusinessEntityRequest, CreateBusinessEntityRequestRaw, InvoiceAssetKind,
+};
+use crate::storage::storage_service::{StorageService, FINANCIAL_DOCS_BUCKET_NAME};
+
+const MAX_INVOICE_IMAGE_BYTES: usize = 512 * 1024;
+const MAX_TERMS_AND_CONDITIONS_CHARS: usize = 4000;
 
 #[derive(Debug, Error)]
 pub enum BusinessEntityServiceError {
     #[error(transparent)]
     Db(#[from] DaoError),
+    #[error("validation failures \n {}", .0.join("\n"))]
+    Validation(Vec<String>),
+    #[error("business entity {0} not found")]
+    NotFound(Uuid),
     #[error("{0}")]
     Other(#[from] anyhow::Error),
 }
@@ -35,6 +48,13 @@ pub trait BusinessEntityService: Send + Sync {
         tenant_id: &Uuid,
     ) -> Result<Option<Arc<BusinessEntityDto>>, BusinessEntityServiceError>;
 
+    ///business entities not found for the tenant are omitted, the ones not cached are fetched in one query
+    async fn get_business_entities_by_ids(
+        &self,
+        ids: &[Uuid],
+        tenant_id: &Uuid,
+    ) -> Result<Vec<Arc<BusinessEntityDto>>, BusinessEntityServiceError>;
+
     async fn is_valid_business_entity_id(
         &self,
         id: &Uuid,
@@ -46,11 +66,28 @@ pub trait BusinessEntityService: Send + Sync {
         tenant_id: Uuid,
         user_id: Uuid,
     ) -> Result<Uuid, BusinessEntityServiceError>;
+    ///stores the asset and records it on the invoice detail of the business entity, an earlier upload of the
+    /// same kind is replaced
+    async fn upload_invoice_asset(
+        &self,
+        business_entity_id: Uuid,
+        kind: InvoiceAssetKind,
+        bytes: Vec<u8>,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), BusinessEntityServiceError>;
+    ///storage keys of the invoice assets, all none when nothing is uploaded
+    async fn get_invoice_assets(
+        &self,
+        business_entity_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<BusinessEntityInvoiceAssets, BusinessEntityServiceError>;
 }
 
 struct BusinessEntityServiceImpl {
     dao: Arc<dyn BusinessEntityDao>,
     address_service: Arc<dyn AddressService>,
+    storage_service: Arc<dyn StorageService>,
     ///tenant_id, business_entity_id
     ///lets keep the size to be 1000 and ttl to be 5 minutes
     cache_id: Cache<(Uuid, Uuid), Arc<BusinessEntityDto>>,
@@ -60,6 +97,7 @@ struct BusinessEntityServiceImpl {
 pub fn get_business_entity_master_service(
     arc: Arc<Pool>,
     address_service: Arc<dyn AddressService>,
+    storage_service: Arc<dyn StorageService>,
 ) -> Arc<dyn BusinessEntityService> {
     let dao = get_business_entity_dao(arc);
     let cache: Cache<(Uuid, Uuid), Arc<BusinessEntityDto>> = Cache::builder()
@@ -69,11 +107,81 @@ pub fn get_business_entity_master_service(
     let service = BusinessEntityServiceImpl {
         dao,
         address_service,
+        storage_service,
         cache_id: cache,
     };
     Arc::new(service)
 }
 
+fn create_invoice_asset_key(
+    tenant_id: Uuid,
+    business_entity_id: Uuid,
+    kind: InvoiceAssetKind,
+) -> String {
+    format!(
+        "{}-business-entity-{}-{}",
+        tenant_id,
+        business_entity_id,
+        kind.as_str()
+    )
+}
+
+fn validate_invoice_asset(kind: InvoiceAssetKind, bytes: &[u8]) -> Vec<String> {
+    let mut errors = vec![];
+    match kind {
+        InvoiceAssetKind::Logo | InvoiceAssetKind::Signature => {
+            if bytes.len() > MAX_INVOICE_IMAGE_BYTES {
+                errors.push(format!(
+                    "{} cannot be more than {} bytes",
+                    kind.as_str(),
+                    MAX_INVOICE_IMAGE_BYTES
+                ));
+            }
+            if !invoice_template::is_supported_image(bytes) {
+                errors.push(format!(
+                    "{} should be a png, jpg, gif or svg image",
+                    kind.as_str()
+                ));
+            }
+        }
+        InvoiceAssetKind::TermsAndConditions => match std::str::from_utf8(bytes) {
+            Ok(text) if text.trim().is_empty() => {
+                errors.push("terms and conditions cannot be empty".to_string())
+            }
+            Ok(text) if text.chars().count() > MAX_TERMS_AND_CONDITIONS_CHARS => {
+                errors.push(format!(
+                    "terms and conditions cannot be more than {} chars",
+                    MAX_TERMS_AND_CONDITIONS_CHARS
+                ))
+            }
+            Ok(_) => {}
+            Err(_) => errors.push("terms and conditions should be utf-8 text".to_string()),
+        },
+    }
+    errors
+}
+
+impl BusinessEntityServiceImpl {
+    async fn to_business_entity_dto(
+        &self,
+        entity: BusinessEntityMaster,
+        tenant_id: &Uuid,
+    ) -> Result<BusinessEntityDto, BusinessEntityServiceError> {
+        let address = if let Some(addr_id) = entity.entity_type.get_address_id() {
+            self.address_service
+                .get_address_by_id(*tenant_id, addr_id)
+                .await
+                .context("error fetching address for business entity")?
+        } else {
+            None
+        };
+        Ok(BusinessEntityDto {
+            business_entity: entity,
+            address,
+        })
+    }
+}
+
 #[async_trait]
 impl BusinessEntityService for BusinessEntityServiceImpl {
     async fn get_business_entity_by_id(
@@ -84,18 +192,7 @@ impl BusinessEntityService for BusinessEntityServiceImpl {
         get_or_fetch_entity(*tenant_id, *id, &self.cache_id, async {
             let business_entity_master = self.dao.get_business_entity(id, tenant_id).await?;
             let business_entity_dto = if let Some(entity) = business_entity_master {
-                let address = if let Some(addr_id) = entity.entity_type.get_address_id() {
-                    self.address_service
-                        .get_address_by_id(*tenant_id, addr_id)
-                        .await
-                        .context("error fetching address for business entity")?
-                } else {
-                    None
-                };
-                Some(BusinessEntityDto {
-                    business_entity: entity,
-                    address,
-                })
+                Some(self.to_business_entity_dto(entity, tenant_id).await?)
             } else {
                 None
             };
@@ -104,6 +201,35 @@ impl BusinessEntityService for BusinessEntityServiceImpl {
         .await
     }
 
+    async fn get_business_entities_by_ids(
+        &self,
+        ids: &[Uuid],
+        tenant_id: &Uuid,
+    ) -> Result<Vec<Arc<BusinessEntityDto>>, BusinessEntityServiceError> {
+        let mut entities = Vec::with_capacity(ids.len());
+        let mut uncached_ids = Vec::new();
+        for id in ids {
+            match self.cache_id.get(&(*tenant_id, *id)).await {
+                Some(entity) => entities.push(entity),
+                None => uncached_ids.push(*id),
+            }
+        }
+        if uncached_ids.is_empty() {
+            return Ok(entities);
+        }
+        for entity in self
+            .dao
+            .get_business_entities_by_ids(&uncached_ids, tenant_id)
+            .await?
+        {
+            let id = entity.base_master_fields.id;
+            let dto = Arc::new(self.to_business_entity_dto(entity, tenant_id).await?);
+            self.cache_id.insert((*tenant_id, id), dto.clone()).await;
+            entities.push(dto);
+        }
+        Ok(entities)
+    }
+
     async fn is_valid_business_entity_id(
         &self,
         id: &Uuid,
@@ -126,4 +252,75 @@ impl BusinessEntityService for BusinessEntityServiceImpl {
             .await?;
         Ok(kk)
     }
+
+    async fn upload_invoice_asset(
+        &self,
+        business_entity_id: Uuid,
+        kind: InvoiceAssetKind,
+        bytes: Vec<u8>,
+        tenant_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), BusinessEntityServiceError> {
+        let errors = validate_invoice_asset(kind, &bytes);
+        if !errors.is_empty() {
+            return Err(BusinessEntityServiceError::Validation(errors));
+        }
+        if !self
+            .is_valid_business_entity_id(&business_entity_id, &tenant_id)
+            .await?
+        {
+            return Err(BusinessEntityServiceError::NotFound(business_entity_id));
+        }
+        let key = create_invoice_asset_key(tenant_id, business_entity_id, kind);
+        self.storage_service
+            .upload_object(FINANCIAL_DOCS_BUCKET_NAME, key.as_str(), bytes, None)
+            .await
+            .context("error uploading invoice asset")?;
+        let updated = self
+            .dao
+            .upsert_invoice_asset(business_entity_id, kind, key.as_str(), tenant_id, user_id)
+            .await?;
+        if !updated {
+            return Err(BusinessEntityServiceError::NotFound(business_entity_id));
+        }
+        Ok(())
+    }
+
+    async fn get_invoice_assets(
+        &self,
+        business_entity_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<BusinessEntityInvoiceAssets, BusinessEntityServiceError> {
+        let assets = self
+            .dao
+            .get_invoice_assets(business_entity_id, tenant_id)
+            .await?;
+        Ok(assets.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::masters::business_entity_master::business_entity_models::InvoiceAssetKind;
+    use crate::masters::business_entity_master::business_entity_service::validate_invoice_asset;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    #[rstest]
+    #[case(InvoiceAssetKind::Logo, PNG_HEADER.to_vec(), 0)]
+    #[case(InvoiceAssetKind::Signature, b"<svg xmlns='http://www.w3.org/2000/svg'></svg>".to_vec(), 0)]
+    #[case(InvoiceAssetKind::Logo, b"plain text".to_vec(), 1)]
+    #[case(InvoiceAssetKind::Logo, [PNG_HEADER, &[0; 512 * 1024]].concat(), 1)]
+    #[case(InvoiceAssetKind::TermsAndConditions, b"goods once sold will not be taken back".to_vec(), 0)]
+    #[case(InvoiceAssetKind::TermsAndConditions, b"  ".to_vec(), 1)]
+    #[case(InvoiceAssetKind::TermsAndConditions, vec![0xff, 0xfe], 1)]
+    fn test_validate_invoice_asset(
+        #[case] kind: InvoiceAssetKind,
+        #[case] bytes: Vec<u8>,
+        #[case] error_count: usize,
+    ) {
+        assert_eq!(validate_invoice_asset(kind, &bytes).len(), error_count);
+    }
 }
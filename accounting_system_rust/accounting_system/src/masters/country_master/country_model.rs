@@ -24,6 +24,9 @@ impl CountryName {
         }
         Ok(CountryName(name.to_ascii_uppercase()))
     }
+    pub fn inner(&self) -> &str {
+        self.0.as_str()
+    }
 }
 #[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
 pub struct CountryMaster {
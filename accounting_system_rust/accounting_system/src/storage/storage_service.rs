@@ -2,10 +2,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
 
 use storage_service::storage_service::Storage;
 use storage_service::AwsStorageService;
 
+#[cfg_attr(test, automock)]
 #[async_trait]
 pub trait StorageService: Send + Sync {
     async fn upload_object(
@@ -12,9 +12,10 @@ use crate::common_utils::dao_error::DaoError;
 use crate::common_utils::utils::{
     get_current_time_us, parse_db_output_of_insert_create_and_return_uuid,
 };
-use crate::tenant::tenant_models::{CreateTenantRequest, Tenant};
+use crate::tenant::tenant_models::{CreateTenantRequest, GstRegistrationType, Tenant};
 
-const SELECT_FIELDS: &str = "id,display_name,created_by,updated_by,created_at,updated_at";
+const SELECT_FIELDS: &str =
+    "id,display_name,gst_registration_type::text,created_by,updated_by,created_at,updated_at";
 const TABLE_NAME: &str = "tenant";
 const BY_ID_QUERY: &str = concatcp!(
     "select ",
@@ -51,11 +52,13 @@ impl TryFrom<&Row> for Tenant {
         Ok(Tenant {
             id: row.get(0),
             display_name: row.get(1),
+            gst_registration_type: GstRegistrationType::try_from(row.get::<usize, &str>(2))
+                .context("error during db row conversion")?,
             audit_metadata: AuditMetadataBase {
-                created_by: row.get(2),
-                updated_by: row.get(3),
-                created_at: row.get(4),
-                updated_at: row.get(5),
+                created_by: row.get(3),
+                updated_by: row.get(4),
+                created_at: row.get(5),
+                updated_at: row.get(6),
             },
         })
     }
@@ -87,7 +90,7 @@ impl TenantDao for TenantDaoImpl {
         let simple_query = format!(
             r#"
         begin transaction;
-        select create_tenant(Row('{}','{}','{}','{}',{},{}));
+        select create_tenant(Row('{}','{}','{}','{}',{},{},'{}'));
         commit;
         "#,
             tenant.idempotence_key,
@@ -95,7 +98,8 @@ impl TenantDao for TenantDaoImpl {
             user_id,
             user_id,
             get_current_time_us().context("error getting current time")?,
-            get_current_time_us().context("error getting current time")?
+            get_current_time_us().context("error getting current time")?,
+            tenant.gst_registration_type.as_str()
         );
         let conn = self.postgres_client.get().await?;
         let rows = conn.simple_query(simple_query.as_str()).await?;
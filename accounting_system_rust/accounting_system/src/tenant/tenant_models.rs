@@ -1,3 +1,4 @@
+use anyhow::anyhow;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -8,6 +9,8 @@ use crate::accounting::currency::currency_models::AuditMetadataBase;
 pub struct Tenant {
     pub id: Uuid,
     pub display_name: String,
+    #[serde(default)]
+    pub gst_registration_type: GstRegistrationType,
     pub audit_metadata: AuditMetadataBase,
 }
 
@@ -15,6 +18,45 @@ pub struct Tenant {
 pub struct CreateTenantRequest {
     pub idempotence_key: Uuid,
     pub display_name: String,
+    #[serde(default)]
+    pub gst_registration_type: GstRegistrationType,
+}
+
+///composition dealers and unregistered persons cannot charge tax, they issue a bill of supply
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GstRegistrationType {
+    #[default]
+    Regular,
+    Composition,
+    Unregistered,
+}
+
+impl GstRegistrationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GstRegistrationType::Regular => "regular",
+            GstRegistrationType::Composition => "composition",
+            GstRegistrationType::Unregistered => "unregistered",
+        }
+    }
+
+    pub fn can_charge_tax(&self) -> bool {
+        *self == GstRegistrationType::Regular
+    }
+}
+
+impl TryFrom<&str> for GstRegistrationType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "regular" => Ok(GstRegistrationType::Regular),
+            "composition" => Ok(GstRegistrationType::Composition),
+            "unregistered" => Ok(GstRegistrationType::Unregistered),
+            _ => Err(anyhow!("invalid gst registration type {}", value)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -35,12 +77,14 @@ pub mod tests {
         CreateTenantRequest {
             idempotence_key: builder.idempotence_key.unwrap_or_else(Uuid::now_v7),
             display_name: builder.display_name.unwrap_or("".to_string()),
+            gst_registration_type: builder.gst_registration_type.unwrap_or_default(),
         }
     }
     pub fn a_tenant(builder: TenantBuilder) -> Tenant {
         Tenant {
             id: builder.id.unwrap_or(*SEED_TENANT_ID),
             display_name: builder.display_name.unwrap_or("".to_string()),
+            gst_registration_type: builder.gst_registration_type.unwrap_or_default(),
             audit_metadata: builder
                 .audit_metadata
                 .unwrap_or_else(|| an_audit_metadata_base(Default::default())),
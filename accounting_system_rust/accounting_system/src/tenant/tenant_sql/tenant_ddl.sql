@@ -1,12 +1,15 @@
 
+create type gst_registration_type as enum ('regular','composition','unregistered');
+
 create table if not exists tenant
 (
-    id           uuid primary key,
-    display_name varchar(100) not null,
-    created_by   uuid         not null,
-    updated_by   uuid,
-    created_at   bigint default extract(epoch from now()) * 1000000,
-    updated_at   bigint default extract(epoch from now()) * 1000000
+    id                    uuid primary key,
+    display_name          varchar(100)          not null,
+    gst_registration_type gst_registration_type not null default 'regular',
+    created_by            uuid                  not null,
+    updated_by            uuid,
+    created_at            bigint default extract(epoch from now()) * 1000000,
+    updated_at            bigint default extract(epoch from now()) * 1000000
 );
 
 create type create_tenant_request as
@@ -16,5 +19,6 @@ create type create_tenant_request as
     created_by      uuid,
     updated_by      uuid,
     created_at      bigint,
-    updated_at      bigint
+    updated_at      bigint,
+    gst_registration_type text
 );
\ No newline at end of file
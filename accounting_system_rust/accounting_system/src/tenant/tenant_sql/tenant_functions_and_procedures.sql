@@ -14,8 +14,10 @@ begin
 --         raise notice 'resp=%', impacted_rows;
     if impacted_rows != 0 then
         select uuid_generate_v7() into tenant_id;
-        insert into tenant (id, display_name, created_by, updated_by, created_at, updated_at)
-        values (tenant_id, req.display_name, req.created_by, req.updated_by, req.created_at, req.updated_at);
+        insert into tenant (id, display_name, gst_registration_type, created_by, updated_by, created_at, updated_at)
+        values (tenant_id, req.display_name,
+                coalesce(req.gst_registration_type, 'regular')::gst_registration_type,
+                req.created_by, req.updated_by, req.created_at, req.updated_at);
         update idempotence_store
         set response=jsonb_build_object('id', tenant_id)
         where idempotence_key = req.idempotence_key
@@ -41,6 +41,10 @@ impl<T> BaseLineQuantity<T> {
     pub fn uom_as_str(&self) -> &str {
         self.uom.as_str()
     }
+
+    pub fn get_uom(&self) -> &UOM {
+        &self.uom
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,7 +38,8 @@ pub struct CustomerDetail {
 #[allow(dead_code)]
 pub struct AdditionalCharge {
     unit_price: Price,
-    tax_percent: TaxPercentage, // lets not tax additional charge just show it.
+    ///rate of the principal supply unless a rate is declared for the charge
+    tax_percent: TaxPercentage,
 }
 
 #[allow(dead_code)]
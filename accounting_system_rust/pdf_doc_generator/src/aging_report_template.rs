@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use typst::foundations::Bytes;
+use typst_pdf::PdfOptions;
+
+use crate::world::InMemoryWorld;
+
+const MAIN: &str = include_str!("../typst_templates/aging_report/main.typ");
+
+fn get_file_map(data: Vec<u8>) -> HashMap<&'static str, Bytes> {
+    let mut map = HashMap::new();
+    map.insert("main.typ", Bytes::new(MAIN));
+    map.insert("aging_report_data.json", Bytes::new(data));
+    map
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct AgingBuckets {
+    pub current: f64,
+    pub days_1_30: f64,
+    pub days_31_60: f64,
+    pub days_61_90: f64,
+    pub above_90: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgingReportCustomerRow {
+    pub customer_name: String,
+    pub currency: String,
+    pub buckets: AgingBuckets,
+    pub unapplied_credits: f64,
+    pub net_outstanding: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgingReportTotalRow {
+    pub label: String,
+    pub currency: String,
+    pub buckets: AgingBuckets,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgingReport {
+    pub title: String,
+    pub as_of_date: String,
+    pub customers: Vec<AgingReportCustomerRow>,
+    pub company_unit_totals: Vec<AgingReportTotalRow>,
+    pub currency_totals: Vec<AgingReportTotalRow>,
+}
+
+pub fn create_aging_report_pdf(input: &AgingReport) -> anyhow::Result<Vec<u8>> {
+    let data = serde_json::to_vec(input).context("error during serialisation")?;
+    let world = InMemoryWorld::new(MAIN, get_file_map(data));
+    let document = typst::compile(&world)
+        .output
+        .map_err(|_a| anyhow!("error during typst compilation"))?;
+    let pdf = typst_pdf::pdf(&document, &PdfOptions::default())
+        .map_err(|_a| anyhow!("error during pdf compilation"))?;
+    comemo::evict(0);
+    Ok(pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aging_report_template::{create_aging_report_pdf, AgingReport};
+
+    const JSON_DATA: &[u8] =
+        include_bytes!("../typst_templates/aging_report/aging_report_data.json");
+
+    #[test]
+    fn test_aging_report_pdf_creation() {
+        let report: AgingReport = serde_json::from_slice(JSON_DATA).unwrap();
+        let pdf = create_aging_report_pdf(&report).unwrap();
+        assert!(!pdf.is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use typst::foundations::Bytes;
+use typst_pdf::PdfOptions;
+
+use crate::invoice_template::{invoice_component_files, DocDate, Invoice, InvoiceParty};
+use crate::world::InMemoryWorld;
+
+const MAIN: &str = include_str!("../typst_templates/delivery_challan/main.typ");
+
+fn get_file_map(data: Vec<u8>) -> HashMap<&'static str, Bytes> {
+    let mut map = invoice_component_files();
+    map.insert("main.typ", Bytes::new(MAIN));
+    map.insert("delivery_challan_data.json", Bytes::new(data));
+    map
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryChallanLine {
+    pub line_no: u16,
+    pub item: String,
+    pub hsn_sac: String,
+    pub batch_no: Option<String>,
+    pub quantity: f64,
+    pub free_quantity: f64,
+    pub uqc: String,
+    pub unit_price: f64,
+    ///value of the goods after discount, no tax is charged on a challan
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryChallan {
+    pub challan_number: String,
+    pub challan_date: DocDate,
+    ///printed below the heading, reason for moving the goods without a tax invoice
+    pub reason: String,
+    pub consignor: InvoiceParty,
+    pub dispatch_from: Option<InvoiceParty>,
+    pub consignee: Option<InvoiceParty>,
+    pub shipped_to: Option<InvoiceParty>,
+    pub lines: Vec<DeliveryChallanLine>,
+    pub total_quantity: f64,
+    pub total_value: f64,
+    pub remarks: Option<String>,
+}
+
+impl DeliveryChallan {
+    ///keeps the parties and lines of the computed invoice document and drops the tax details
+    pub fn from_invoice_document(reason: String, document: Invoice) -> Self {
+        let lines: Vec<DeliveryChallanLine> = document
+            .invoice_lines_table
+            .lines
+            .into_iter()
+            .map(|a| DeliveryChallanLine {
+                line_no: a.line_no,
+                item: a.item,
+                hsn_sac: a.hsn_sac,
+                batch_no: a.batch_no,
+                quantity: a.quantity,
+                free_quantity: a.free_quantity,
+                uqc: a.uqc,
+                unit_price: a.unit_price,
+                value: round_2(
+                    a.quantity * a.unit_price * (1.0 - a.discount_percentage as f64 / 100.0),
+                ),
+            })
+            .collect();
+        DeliveryChallan {
+            challan_number: document.invoice_number,
+            challan_date: document.invoice_date,
+            reason,
+            consignor: document.supplier,
+            dispatch_from: document.dispatch_from,
+            consignee: document.billed_to,
+            shipped_to: document.shipped_to,
+            total_quantity: lines.iter().map(|a| a.quantity + a.free_quantity).sum(),
+            total_value: round_2(lines.iter().map(|a| a.value).sum()),
+            lines,
+            remarks: document.invoice_remarks,
+        }
+    }
+}
+
+fn round_2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+pub fn create_delivery_challan_pdf(input: &DeliveryChallan) -> anyhow::Result<Vec<u8>> {
+    let data = serde_json::to_vec(input).context("error during serialisation")?;
+    let world = InMemoryWorld::new(MAIN, get_file_map(data));
+    let document = typst::compile(&world)
+        .output
+        .map_err(|_a| anyhow!("error during typst compilation"))?;
+    let pdf = typst_pdf::pdf(&document, &PdfOptions::default())
+        .map_err(|_a| anyhow!("error during pdf compilation"))?;
+    comemo::evict(0);
+    Ok(pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::delivery_challan_template::{create_delivery_challan_pdf, DeliveryChallan};
+    use crate::quotation_template::Quotation;
+
+    const JSON_DATA: &[u8] =
+        include_bytes!("../typst_templates/delivery_challan/delivery_challan_data.json");
+    const QUOTATION_JSON_DATA: &[u8] =
+        include_bytes!("../typst_templates/quotation/quotation_data.json");
+
+    #[test]
+    fn test_delivery_challan_pdf_creation() {
+        let challan: DeliveryChallan = serde_json::from_slice(JSON_DATA).unwrap();
+        let pdf = create_delivery_challan_pdf(&challan).unwrap();
+        assert!(!pdf.is_empty());
+    }
+
+    #[test]
+    fn test_from_invoice_document_drops_tax() {
+        let quotation: Quotation = serde_json::from_slice(QUOTATION_JSON_DATA).unwrap();
+        let challan =
+            DeliveryChallan::from_invoice_document("job work".to_string(), quotation.document);
+        assert_eq!(challan.challan_number, "QT/1");
+        assert_eq!(challan.lines[0].value, 500.0);
+        assert_eq!(challan.total_value, 500.0);
+        assert_eq!(challan.total_quantity, 10.0);
+    }
+}
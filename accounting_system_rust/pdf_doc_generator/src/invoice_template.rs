@@ -1,48 +1,296 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::io::Read;
+use std::path::Component;
+use std::str::FromStr;
 
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{SeqAccess, Visitor};
 use serde::ser::SerializeSeq;
 use typst::foundations::{Bytes, Smart};
+use typst::model::Document;
 use typst_pdf::PdfOptions;
 use crate::world::InMemoryWorld;
 
 const MAIN: &str = include_str!("../typst_templates/invoice/main.typ");
+const A4_PORTRAIT_MAIN: &str = include_str!("../typst_templates/invoice/main_a4_portrait.typ");
+const A5_MAIN: &str = include_str!("../typst_templates/invoice/main_a5.typ");
+const THERMAL_80MM_MAIN: &str = include_str!("../typst_templates/invoice/main_thermal_80mm.typ");
+const COMPACT_INVOICE: &str = include_str!("../typst_templates/invoice/compact_invoice.typ");
 const INVOICE_LINES: &str = include_str!("../typst_templates/invoice/invoice_lines.typ");
 const INVOICE_SUMMARY: &str = include_str!("../typst_templates/invoice/invoice_summary.typ");
 const TAX_SUMMARY: &str = include_str!("../typst_templates/invoice/tax_summary.typ");
 const SUNSET_PNG: &[u8] = include_bytes!("../typst_templates/invoice/sunset.png");
-const EINVOICE_QR: &[u8] = include_bytes!("../typst_templates/invoice/einvoice_qr.png");
 const TABLEX_PACKAGE_TYP: &[u8] = include_bytes!("../typst_templates/invoice/tablex.typ");
 const TABLEX_TOML: &[u8] = include_bytes!("../typst_templates/invoice/typst.toml");
-
-fn get_file_map(data: Vec<u8>) -> HashMap<&'static str, Bytes> {
-    let entry_main = Bytes::new(MAIN);
+///tenant templates are trial compiled against this invoice before they are accepted
+const SAMPLE_INVOICE_DATA: &[u8] = include_bytes!("../typst_templates/invoice/invoice_data.json");
+
+const EINVOICE_QR_FILE_NAME: &str = "einvoice_qr.svg";
+const INVOICE_ASSETS_FILE_NAME: &str = "invoice_assets.json";
+const TERMS_AND_CONDITIONS_FILE_NAME: &str = "terms_and_conditions.txt";
+///file names by image format, in the order png, jpg, gif, svg
+const LOGO_FILE_NAMES: [&str; 4] = ["logo.png", "logo.jpg", "logo.gif", "logo.svg"];
+const SIGNATURE_FILE_NAMES: [&str; 4] = [
+    "signature.png",
+    "signature.jpg",
+    "signature.gif",
+    "signature.svg",
+];
+const INVOICE_DATA_FILE_NAME: &str = "invoice_data.json";
+const TEMPLATE_MAIN_FILE_NAME: &str = "main.typ";
+const MAX_TEMPLATE_FILES: usize = 50;
+
+///sub templates and assets shared by the invoice and the documents that reuse its tables
+pub(crate) fn invoice_component_files() -> HashMap<&'static str, Bytes> {
     let entry_invoice_lines = Bytes::new(INVOICE_LINES);
     let entry_invoice_summary = Bytes::new(INVOICE_SUMMARY);
     let entry_tax_summary = Bytes::new(TAX_SUMMARY);
+    let entry_compact_invoice = Bytes::new(COMPACT_INVOICE);
     let entry_sunset_png = Bytes::new(SUNSET_PNG);
-    let entry_einvoice_qr = Bytes::new(EINVOICE_QR);
     let entry_tablex_package_typ = Bytes::new(TABLEX_PACKAGE_TYP);
     let entry_tablex_toml = Bytes::new(TABLEX_TOML);
-    let entry_json_data = Bytes::new(data);
     let mut map = HashMap::new();
-    let empty: Vec<u8> = Vec::new();
-    map.insert("main.typ", entry_main);
     map.insert("invoice_lines.typ", entry_invoice_lines);
     map.insert("invoice_summary.typ", entry_invoice_summary);
     map.insert("tax_summary.typ", entry_tax_summary);
+    map.insert("compact_invoice.typ", entry_compact_invoice);
     map.insert("sunset.png", entry_sunset_png);
-    map.insert("einvoice_qr.png", entry_einvoice_qr);
-    map.insert("invoice_data.json", entry_json_data);
-    map.insert("preview/tablex/0.0.8/tablex.typ", entry_tablex_package_typ);
-    map.insert("preview/tablex/0.0.8/typst.toml", entry_tablex_toml);
-    map.insert("preview/tablex/0.0.8", Bytes::new(empty));
+    map.insert("preview/tablex/0.0.9/tablex.typ", entry_tablex_package_typ);
+    map.insert("preview/tablex/0.0.9/typst.toml", entry_tablex_toml);
     map
 }
 
+///built-in layouts of the invoice, all of them render the same invoice data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceLayout {
+    ///a column for every field of the lines
+    #[default]
+    A4Landscape,
+    A4Portrait,
+    A5,
+    ///receipt for 80mm thermal printers, printed as one continuous page
+    #[serde(rename = "thermal_80mm")]
+    Thermal80mm,
+}
+
+impl InvoiceLayout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvoiceLayout::A4Landscape => "a4_landscape",
+            InvoiceLayout::A4Portrait => "a4_portrait",
+            InvoiceLayout::A5 => "a5",
+            InvoiceLayout::Thermal80mm => "thermal_80mm",
+        }
+    }
+
+    fn main_source(&self) -> &'static str {
+        match self {
+            InvoiceLayout::A4Landscape => MAIN,
+            InvoiceLayout::A4Portrait => A4_PORTRAIT_MAIN,
+            InvoiceLayout::A5 => A5_MAIN,
+            InvoiceLayout::Thermal80mm => THERMAL_80MM_MAIN,
+        }
+    }
+}
+
+impl FromStr for InvoiceLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let layout = match value {
+            "a4_landscape" => InvoiceLayout::A4Landscape,
+            "a4_portrait" => InvoiceLayout::A4Portrait,
+            "a5" => InvoiceLayout::A5,
+            "thermal_80mm" => InvoiceLayout::Thermal80mm,
+            _ => bail!("{} is not a valid invoice layout", value),
+        };
+        Ok(layout)
+    }
+}
+
+///typst sources and assets of a tenant template. main.typ is compiled with the invoice data files, the files of
+/// the archive and the shared invoice components available to it
+#[derive(Debug, Clone)]
+pub struct InvoiceTemplateSource {
+    main: String,
+    files: HashMap<String, Bytes>,
+}
+
+///files generated for every invoice, a template cannot replace them
+fn is_reserved_file_name(name: &str) -> bool {
+    [
+        TEMPLATE_MAIN_FILE_NAME,
+        INVOICE_DATA_FILE_NAME,
+        INVOICE_ASSETS_FILE_NAME,
+        EINVOICE_QR_FILE_NAME,
+        TERMS_AND_CONDITIONS_FILE_NAME,
+    ]
+    .contains(&name)
+        || LOGO_FILE_NAMES.contains(&name)
+        || SIGNATURE_FILE_NAMES.contains(&name)
+}
+
+impl InvoiceTemplateSource {
+    ///reads a tar archive with main.typ at its root, directories are kept in the file names
+    pub fn from_tar(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut archive = tar::Archive::new(bytes);
+        let mut main = None;
+        let mut files = HashMap::new();
+        for entry in archive.entries().context("template is not a tar archive")? {
+            let mut entry = entry.context("template is not a tar archive")?;
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            let path = entry
+                .path()
+                .context("invalid file name in template")?
+                .into_owned();
+            if !entry.header().entry_type().is_file() {
+                return Err(anyhow!("{} is not a regular file", path.display()));
+            }
+            let mut components = vec![];
+            for component in path.components() {
+                match component {
+                    Component::Normal(a) => {
+                        components.push(a.to_str().with_context(|| {
+                            format!("{} is not a utf-8 file name", path.display())
+                        })?)
+                    }
+                    Component::CurDir => {}
+                    _ => return Err(anyhow!("{} is outside the template", path.display())),
+                }
+            }
+            let name = components.join("/");
+            let mut content = vec![];
+            entry
+                .read_to_end(&mut content)
+                .with_context(|| format!("could not read {}", name))?;
+            if name == TEMPLATE_MAIN_FILE_NAME {
+                main = Some(String::from_utf8(content).context("main.typ is not utf-8 text")?);
+            } else if is_reserved_file_name(name.as_str()) {
+                return Err(anyhow!(
+                    "{} is generated for every invoice, rename it",
+                    name
+                ));
+            } else {
+                files.insert(name, Bytes::new(content));
+            }
+            if files.len() > MAX_TEMPLATE_FILES {
+                return Err(anyhow!(
+                    "template cannot have more than {} files",
+                    MAX_TEMPLATE_FILES
+                ));
+            }
+        }
+        let main = main.context("main.typ not found at the root of the template")?;
+        Ok(Self { main, files })
+    }
+}
+
+///logo, signature and terms and conditions of the supplier. an asset which is absent or is not an image typst
+/// can decode is left out of the invoice, images which fail to decode leave out both the logo and signature
+#[derive(Debug, Default)]
+pub struct InvoiceAssets {
+    pub logo: Option<Vec<u8>>,
+    pub signature: Option<Vec<u8>>,
+    pub terms_and_conditions: Option<String>,
+}
+
+///names of the asset files present in the file map, none for the ones left out
+#[derive(Debug, Serialize, PartialEq)]
+struct InvoiceAssetFiles {
+    logo: Option<&'static str>,
+    signature: Option<&'static str>,
+    terms_and_conditions: Option<&'static str>,
+}
+
+///index of the format in the asset file names, detected from the magic bytes
+fn image_format_index(bytes: &[u8]) -> Option<usize> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(0)
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some(1)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(2)
+    } else {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]);
+        let head = head.trim_start_matches('\u{feff}').trim_start();
+        if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+            Some(3)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn is_supported_image(bytes: &[u8]) -> bool {
+    image_format_index(bytes).is_some()
+}
+
+fn get_file_map(
+    main: &str,
+    data: Vec<u8>,
+    qr_code_svg: Option<String>,
+    assets: InvoiceAssets,
+    template: Option<&InvoiceTemplateSource>,
+) -> serde_json::Result<HashMap<String, Bytes>> {
+    let entry_main = Bytes::new(main);
+    let entry_json_data = Bytes::new(data);
+    let mut map = invoice_component_files();
+    map.insert(TEMPLATE_MAIN_FILE_NAME, entry_main);
+    map.insert(INVOICE_DATA_FILE_NAME, entry_json_data);
+    if let Some(svg) = qr_code_svg {
+        map.insert(EINVOICE_QR_FILE_NAME, Bytes::new(svg.into_bytes()));
+    }
+    let mut insert_image = |names: [&'static str; 4], bytes: Option<Vec<u8>>| {
+        let bytes = bytes?;
+        let name = names[image_format_index(&bytes)?];
+        map.insert(name, Bytes::new(bytes));
+        Some(name)
+    };
+    let logo = insert_image(LOGO_FILE_NAMES, assets.logo);
+    let signature = insert_image(SIGNATURE_FILE_NAMES, assets.signature);
+    let terms_and_conditions = assets.terms_and_conditions
+        .filter(|a| !a.trim().is_empty())
+        .map(|a| {
+            map.insert(TERMS_AND_CONDITIONS_FILE_NAME, Bytes::new(a.into_bytes()));
+            TERMS_AND_CONDITIONS_FILE_NAME
+        });
+    let asset_files = InvoiceAssetFiles {
+        logo,
+        signature,
+        terms_and_conditions,
+    };
+    let asset_files = serde_json::to_vec(&asset_files)?;
+    map.insert(INVOICE_ASSETS_FILE_NAME, Bytes::new(asset_files));
+    let mut map: HashMap<String, Bytes> =
+        map.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+    if let Some(template) = template {
+        for (name, content) in template.files.iter() {
+            map.insert(name.clone(), content.clone());
+        }
+    }
+    Ok(map)
+}
+
+///a tenant template takes the place of the built-in layout
+fn main_source(layout: InvoiceLayout, template: Option<&InvoiceTemplateSource>) -> &str {
+    template.map_or(layout.main_source(), |a| a.main.as_str())
+}
+
+fn create_qr_code_svg(payload: &str) -> anyhow::Result<String> {
+    let code = qrcode::QrCode::new(payload.as_bytes())
+        .context("qr code payload too large")?;
+    let svg = code.render::<qrcode::render::svg::Color>()
+        .min_dimensions(200, 200)
+        .quiet_zone(false)
+        .build();
+    Ok(svg)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DocDate {
     //todo derive from epoch millis or date. decide on one
@@ -88,6 +336,7 @@ pub struct HeaderAndUnit(pub Header, pub Unit, pub JsonKey);
 pub struct InvoiceSummary {
     pub taxable_amt: f64,
     pub tax_amt: f64,
+    pub cess_amt: f64,
     pub additional_charges_amt: f64,
     pub round_off: f64,
     pub total_payable_amount: f64,
@@ -278,6 +527,7 @@ pub struct TaxSummary {
 pub struct AdditionalCharge {
     pub name: String,
     pub rate: f64,
+    pub tax_percentage: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -296,15 +546,45 @@ pub struct InvoiceParty {
     pub address: Address,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EInvoiceDetail {
+    pub irn_no: String,
+    pub ack_no: String,
+    pub ack_date: DocDate,
+    ///signed qr code (jwt) as returned by irp after irn generation. printed as is in the qr code
+    pub signed_qr_code: String,
+}
+
+///printed on export invoices, amounts are in the invoice currency except the inr total
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportDeclaration {
+    pub declaration: String,
+    pub lut_reference: Option<String>,
+    pub port_code: Option<String>,
+    pub shipping_bill_number: Option<String>,
+    pub shipping_bill_date: Option<DocDate>,
+    pub destination_country: Option<String>,
+    pub currency: String,
+    pub exchange_rate: f64,
+    pub total_payable_amount_inr: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Invoice {
+    ///tax invoice or bill of supply
+    pub document_title: String,
     pub invoice_number: String,
     pub invoice_date: DocDate,
     pub order_date: Option<DocDate>,
     pub payment_term: String,
     pub order_number: Option<String>,
+    ///gst state code of the place of supply, 96 for exports
+    pub place_of_supply: String,
     pub service_invoice: bool,
-    pub irn_no: String,
+    pub einvoice_detail: Option<EInvoiceDetail>,
+    ///invoice details (seller gstin, number, date and value) in a qr for b2c invoices where irn is not
+    /// generated, it carries no payment details
+    pub b2c_qr_payload: Option<String>,
     pub supplier: InvoiceParty,
     pub dispatch_from: Option<InvoiceParty>,
     pub billed_to: Option<InvoiceParty>,
@@ -315,29 +595,114 @@ pub struct Invoice {
     pub invoice_lines_table: InvoiceLineTable,
     pub invoice_remarks: Option<String>,
     pub ecommerce_gstin: Option<String>,
+    pub export_declaration: Option<ExportDeclaration>,
+    ///reason for not charging tax, present only on a bill of supply
+    pub bill_of_supply_declaration: Option<String>,
+    ///printed across every page of documents that are not issued, like previews
+    pub watermark: Option<String>,
+}
+
+impl Invoice {
+    ///signed irp qr code takes precedence over b2c dynamic qr. none when e-invoicing does not apply
+    fn qr_code_payload(&self) -> Option<&str> {
+        self.einvoice_detail.as_ref()
+            .map(|a| a.signed_qr_code.as_str())
+            .or(self.b2c_qr_payload.as_deref())
+    }
+}
+
+fn diagnostic_messages(errors: &[typst::diag::SourceDiagnostic]) -> Vec<String> {
+    errors.iter().map(|a| a.message.to_string()).collect()
 }
 
-pub fn create_invoice_pdf(input: Invoice) -> anyhow::Result<Vec<u8>> {
-    let a = serde_json::to_vec(&input).context("error during serialisation")?;
-    let map = get_file_map(a);
-    let world = InMemoryWorld::new(MAIN, map);
-    let _k = std::time::SystemTime::now();
-    let document = typst::compile(&world).output
-        .map_err(|_a| anyhow!("error during typst compilation"))?;
-    let pdf = typst_pdf::pdf(&document,&PdfOptions::default())
-        .map_err(|_a| anyhow!("error during pdf compilation"))?;
+///errors of the typst compilation are returned as their messages, so that template authors can fix their sources
+fn compile_invoice_document(
+    input: &Invoice,
+    assets: InvoiceAssets,
+    layout: InvoiceLayout,
+    template: Option<&InvoiceTemplateSource>,
+) -> Result<Document, Vec<String>> {
+    let qr_code_svg = input.qr_code_payload()
+        .map(create_qr_code_svg)
+        .transpose()
+        .map_err(|e| vec![format!("{:#}", e)])?;
+    let a =
+        serde_json::to_vec(input).map_err(|e| vec![format!("error during serialisation {}", e)])?;
+    let main = main_source(layout, template);
+    let map = get_file_map(main, a, qr_code_svg, assets, template)
+        .map_err(|e| vec![format!("error during serialisation {}", e)])?;
+    let world = InMemoryWorld::new(main, map);
+    typst::compile(&world)
+        .output
+        .map_err(|e| diagnostic_messages(&e))
+}
+
+fn render_invoice_pdf(
+    input: &Invoice,
+    assets: InvoiceAssets,
+    layout: InvoiceLayout,
+    template: Option<&InvoiceTemplateSource>,
+) -> Result<Vec<u8>, Vec<String>> {
+    let without_images =
+        (assets.logo.is_some() || assets.signature.is_some()).then(|| InvoiceAssets {
+            logo: None,
+            signature: None,
+            terms_and_conditions: assets.terms_and_conditions.clone(),
+        });
+    let render = |assets| {
+        compile_invoice_document(input, assets, layout, template).and_then(|document| {
+            typst_pdf::pdf(&document, &PdfOptions::default()).map_err(|e| diagnostic_messages(&e))
+        })
+    };
+    //an image can pass the format check and still fail to decode, the invoice is issued without the images then
+    let pdf = match (render(assets), without_images) {
+        (Err(_), Some(assets)) => render(assets),
+        (pdf, _) => pdf,
+    };
     //invoice creation does not have that much reusable data. also this evicts all cache everywhere
     comemo::evict(0);
-    Ok(pdf)
+    pdf
+}
+
+///renders with the built-in layout when no tenant template is given
+pub fn create_invoice_pdf(
+    input: Invoice,
+    assets: InvoiceAssets,
+    layout: InvoiceLayout,
+    template: Option<&InvoiceTemplateSource>,
+) -> anyhow::Result<Vec<u8>> {
+    render_invoice_pdf(&input, assets, layout, template)
+        .map_err(|e| anyhow!("error during typst compilation {}", e.join("\n")))
+}
+
+///trial compiles the template against the sample invoice, returns the compilation errors
+pub fn validate_invoice_template(template: &InvoiceTemplateSource) -> Result<(), Vec<String>> {
+    let sample: Invoice = serde_json::from_slice(SAMPLE_INVOICE_DATA)
+        .map_err(|e| vec![format!("error parsing the sample invoice {}", e)])?;
+    render_invoice_pdf(
+        &sample,
+        InvoiceAssets::default(),
+        InvoiceLayout::default(),
+        Some(template),
+    )?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
+    use std::str::FromStr;
 
     use typst::foundations::Smart;
+    use typst::layout::{Frame, FrameItem};
     use typst_pdf::PdfOptions;
-    use crate::invoice_template::{get_file_map, InvoiceTableHeaderNameEnum, MAIN};
+
+    use crate::invoice_template::{
+        compile_invoice_document, create_invoice_pdf, create_qr_code_svg, get_file_map,
+        main_source, validate_invoice_template, ExportDeclaration, Invoice, InvoiceAssetFiles,
+        InvoiceAssets, InvoiceLayout, InvoiceTableHeaderNameEnum, InvoiceTemplateSource,
+        INVOICE_ASSETS_FILE_NAME, INVOICE_DATA_FILE_NAME, MAIN, SUNSET_PNG, THERMAL_80MM_MAIN,
+    };
     use crate::world::InMemoryWorld;
 
     const JSON_DATA: &[u8] = include_bytes!("../typst_templates/invoice/invoice_data.json");
@@ -345,7 +710,14 @@ mod tests {
     #[test]
     fn test_pdf_creation() {
         let data = JSON_DATA.to_vec();
-        let map = get_file_map(data);
+        let invoice: Invoice = serde_json::from_slice(&data).unwrap();
+        let qr_code_svg = invoice.qr_code_payload().map(|a| create_qr_code_svg(a).unwrap());
+        let assets = InvoiceAssets {
+            logo: Some(SUNSET_PNG.to_vec()),
+            signature: None,
+            terms_and_conditions: Some("goods once sold will not be taken back".to_string()),
+        };
+        let map = get_file_map(MAIN, data, qr_code_svg, assets, None).unwrap();
         let world = InMemoryWorld::new(MAIN, map);
         let k = std::time::SystemTime::now();
         let document = typst::compile(&world).output.expect("Error compiling typst.");
@@ -353,6 +725,220 @@ mod tests {
         fs::write("./out220913.pdf", pdf).expect("Error writing PDF.");
     }
 
+    const LAYOUTS: [InvoiceLayout; 4] = [
+        InvoiceLayout::A4Landscape,
+        InvoiceLayout::A4Portrait,
+        InvoiceLayout::A5,
+        InvoiceLayout::Thermal80mm,
+    ];
+
+    ///enough lines with long item names to run over several pages in every layout
+    fn a_long_invoice() -> Invoice {
+        let mut data: serde_json::Value = serde_json::from_slice(JSON_DATA).unwrap();
+        let line = data["invoice_lines_table"]["lines"][0].clone();
+        let lines: Vec<serde_json::Value> = (0..120)
+            .map(|line_no| {
+                let mut line = line.clone();
+                line["line_no"] = line_no.into();
+                line["item"] = "long item description that has to wrap ".repeat(3).into();
+                line
+            })
+            .collect();
+        data["invoice_lines_table"]["lines"] = lines.into();
+        //the line table headers are deserialized from borrowed strings
+        serde_json::from_slice(&serde_json::to_vec(&data).unwrap()).unwrap()
+    }
+
+    fn an_export_invoice() -> Invoice {
+        let mut invoice = a_long_invoice();
+        invoice.place_of_supply = "96".to_string();
+        invoice.export_declaration = Some(ExportDeclaration {
+            declaration: "supply meant for export under lut without payment of igst".to_string(),
+            lut_reference: Some("AD2901240001234".to_string()),
+            port_code: Some("INNSA1".to_string()),
+            shipping_bill_number: None,
+            shipping_bill_date: None,
+            destination_country: Some("Germany".to_string()),
+            currency: "USD".to_string(),
+            exchange_rate: 83.12,
+            total_payable_amount_inr: 41560.0,
+        });
+        invoice
+    }
+
+    fn frame_text(frame: &Frame, text: &mut String) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => frame_text(&group.frame, text),
+                FrameItem::Text(item) => text.push_str(&item.text),
+                _ => {}
+            }
+        }
+    }
+
+    ///text of all pages without whitespace, as wrapped lines are laid out as separate text items
+    fn document_text(invoice: &Invoice, layout: InvoiceLayout) -> String {
+        let document = compile_invoice_document(invoice, InvoiceAssets::default(), layout, None)
+            .unwrap_or_else(|e| panic!("{:?} {:?}", layout, e));
+        let mut text = String::new();
+        for page in &document.pages {
+            frame_text(&page.frame, &mut text);
+        }
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_pdf_creation_in_every_layout() {
+        for layout in LAYOUTS {
+            let assets = InvoiceAssets {
+                logo: Some(SUNSET_PNG.to_vec()),
+                signature: Some(SUNSET_PNG.to_vec()),
+                terms_and_conditions: Some("goods once sold will not be taken back".to_string()),
+            };
+            let pdf = create_invoice_pdf(a_long_invoice(), assets, layout, None);
+            assert!(pdf.is_ok(), "{:?} {:?}", layout, pdf.err());
+            let text = document_text(&an_export_invoice(), layout);
+            assert!(
+                text.contains("supplymeantforexportunderlutwithoutpaymentofigst"),
+                "{:?} does not print the export declaration",
+                layout
+            );
+            assert!(text.contains("AD2901240001234"), "{:?}", layout);
+        }
+    }
+
+    #[test]
+    fn test_layout_names() {
+        for layout in LAYOUTS {
+            assert_eq!(InvoiceLayout::from_str(layout.as_str()).unwrap(), layout);
+            assert_eq!(
+                serde_json::to_value(layout).unwrap(),
+                serde_json::Value::from(layout.as_str())
+            );
+        }
+        assert!(InvoiceLayout::from_str("a3").is_err());
+        let map = get_file_map(
+            main_source(InvoiceLayout::Thermal80mm, None),
+            JSON_DATA.to_vec(),
+            None,
+            InvoiceAssets::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(&map["main.typ"][..], THERMAL_80MM_MAIN.as_bytes());
+        assert!(map.contains_key("compact_invoice.typ"));
+    }
+
+    #[test]
+    fn test_qr_code_payload_prefers_signed_irp_qr() {
+        let mut invoice: Invoice = serde_json::from_slice(JSON_DATA).unwrap();
+        invoice.b2c_qr_payload = Some("05AABCA5291P1ZD|INV-1|29/01/2024|118.00|18.00".to_string());
+        let signed_qr = invoice.einvoice_detail.as_ref().unwrap().signed_qr_code.clone();
+        assert_eq!(invoice.qr_code_payload(), Some(signed_qr.as_str()));
+        invoice.einvoice_detail = None;
+        assert_eq!(invoice.qr_code_payload(), Some("05AABCA5291P1ZD|INV-1|29/01/2024|118.00|18.00"));
+        invoice.b2c_qr_payload = None;
+        assert_eq!(invoice.qr_code_payload(), None);
+        let svg = create_qr_code_svg(&signed_qr).unwrap();
+        assert!(svg.contains("<svg"));
+    }
+
+    #[test]
+    fn test_invoice_is_rendered_without_images_which_fail_to_decode() {
+        let corrupt_png = [b"\x89PNG\r\n\x1a\n".as_slice(), b"rest of the png is lost"].concat();
+        for layout in LAYOUTS {
+            let assets = InvoiceAssets {
+                logo: Some(corrupt_png.clone()),
+                signature: Some(SUNSET_PNG.to_vec()),
+                terms_and_conditions: Some("goods once sold will not be taken back".to_string()),
+            };
+            let invoice: Invoice = serde_json::from_slice(JSON_DATA).unwrap();
+            let pdf = create_invoice_pdf(invoice, assets, layout, None);
+            assert!(pdf.is_ok(), "{:?} {:?}", layout, pdf.err());
+        }
+    }
+
+    #[test]
+    fn test_missing_and_unsupported_assets_are_left_out() {
+        let assets = InvoiceAssets {
+            logo: Some(SUNSET_PNG.to_vec()),
+            signature: Some(b"not an image".to_vec()),
+            terms_and_conditions: Some("  ".to_string()),
+        };
+        let map = get_file_map(MAIN, JSON_DATA.to_vec(), None, assets, None).unwrap();
+        let asset_files: serde_json::Value =
+            serde_json::from_slice(&map[INVOICE_ASSETS_FILE_NAME]).unwrap();
+        let expected = InvoiceAssetFiles {
+            logo: Some("logo.png"),
+            signature: None,
+            terms_and_conditions: None,
+        };
+        assert_eq!(asset_files, serde_json::to_value(expected).unwrap());
+        assert!(map.contains_key("logo.png"));
+        assert!(!map.keys().any(|a| a.starts_with("signature")));
+    }
+
+    fn a_template_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *content).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_template_from_tar() {
+        let archive = a_template_archive(&[
+            ("./main.typ", b"#let invoice = json(\"invoice_data.json\")"),
+            ("images/stamp.png", SUNSET_PNG),
+        ]);
+        let template = InvoiceTemplateSource::from_tar(&archive).unwrap();
+        let map = get_file_map(
+            main_source(InvoiceLayout::Thermal80mm, Some(&template)),
+            JSON_DATA.to_vec(),
+            None,
+            InvoiceAssets::default(),
+            Some(&template),
+        )
+        .unwrap();
+        assert_eq!(
+            &map["main.typ"][..],
+            b"#let invoice = json(\"invoice_data.json\")"
+        );
+        assert_eq!(&map["images/stamp.png"][..], SUNSET_PNG);
+        assert_eq!(&map[INVOICE_DATA_FILE_NAME][..], JSON_DATA);
+        assert!(map.contains_key("invoice_lines.typ"));
+        let missing_main = a_template_archive(&[("layout.typ", b"")]);
+        assert!(InvoiceTemplateSource::from_tar(&missing_main).is_err());
+        let reserved = a_template_archive(&[("main.typ", b""), (INVOICE_DATA_FILE_NAME, b"{}")]);
+        assert!(InvoiceTemplateSource::from_tar(&reserved).is_err());
+        assert!(InvoiceTemplateSource::from_tar(b"not an archive").is_err());
+    }
+
+    #[test]
+    fn test_template_cannot_read_outside_its_files() {
+        let archive = a_template_archive(&[
+            ("main.typ", b"#include \"layout.typ\""),
+            (
+                "layout.typ",
+                b"#let invoice = json(\"invoice_data.json\")\n#invoice.invoice_number",
+            ),
+        ]);
+        let template = InvoiceTemplateSource::from_tar(&archive).unwrap();
+        assert_eq!(validate_invoice_template(&template), Ok(()));
+        let archive = a_template_archive(&[("main.typ", b"#read(\"/etc/hostname\")")]);
+        let template = InvoiceTemplateSource::from_tar(&archive).unwrap();
+        assert!(validate_invoice_template(&template).is_err());
+        let archive =
+            a_template_archive(&[("main.typ", b"#import \"@preview/cetz:0.3.1\": canvas")]);
+        let template = InvoiceTemplateSource::from_tar(&archive).unwrap();
+        assert!(validate_invoice_template(&template).is_err());
+    }
+
     #[test]
     fn test_serialization_and_deserialization() {
         let a = InvoiceTableHeaderNameEnum::Discount("%".to_string());
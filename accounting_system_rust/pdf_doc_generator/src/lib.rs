@@ -1,3 +1,6 @@
+pub mod aging_report_template;
+pub mod delivery_challan_template;
 mod fonts;
 pub mod invoice_template;
+pub mod quotation_template;
 mod world;
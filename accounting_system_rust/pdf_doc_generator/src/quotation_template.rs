@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use typst::foundations::Bytes;
+use typst_pdf::PdfOptions;
+
+use crate::invoice_template::{invoice_component_files, DocDate, Invoice};
+use crate::world::InMemoryWorld;
+
+const MAIN: &str = include_str!("../typst_templates/quotation/main.typ");
+
+fn get_file_map(data: Vec<u8>) -> HashMap<&'static str, Bytes> {
+    let mut map = invoice_component_files();
+    map.insert("main.typ", Bytes::new(MAIN));
+    map.insert("quotation_data.json", Bytes::new(data));
+    map
+}
+
+///quotations and proforma invoices share the invoice tables, document.invoice_number holds the quotation number
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Quotation {
+    ///printed as the heading, quotation or proforma invoice
+    pub title: String,
+    pub valid_until: Option<DocDate>,
+    pub document: Invoice,
+}
+
+pub fn create_quotation_pdf(input: &Quotation) -> anyhow::Result<Vec<u8>> {
+    let data = serde_json::to_vec(input).context("error during serialisation")?;
+    let world = InMemoryWorld::new(MAIN, get_file_map(data));
+    let document = typst::compile(&world)
+        .output
+        .map_err(|_a| anyhow!("error during typst compilation"))?;
+    let pdf = typst_pdf::pdf(&document, &PdfOptions::default())
+        .map_err(|_a| anyhow!("error during pdf compilation"))?;
+    comemo::evict(0);
+    Ok(pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::quotation_template::{create_quotation_pdf, Quotation};
+
+    const JSON_DATA: &[u8] = include_bytes!("../typst_templates/quotation/quotation_data.json");
+
+    #[test]
+    fn test_quotation_pdf_creation() {
+        let quotation: Quotation = serde_json::from_slice(JSON_DATA).unwrap();
+        let pdf = create_quotation_pdf(&quotation).unwrap();
+        assert!(!pdf.is_empty());
+    }
+}
@@ -7,9 +7,7 @@ use typst::foundations::{Bytes, Datetime};
 use typst::text::{Font, FontBook};
 use typst::{Library, World};
 use typst::syntax::{FileId, Source};
-use typst::diag::{FileError, FileResult, PackageError, PackageResult};
-use typst::ecow::EcoString;
-use typst::syntax::package::PackageSpec;
+use typst::diag::{FileError, FileResult, PackageError};
 use typst::utils::LazyHash;
 use crate::fonts::register_fonts;
 
@@ -44,6 +42,8 @@ impl FileEntry {
     }
 }
 
+///only the files of the file map can be read, packages included. templates cannot read the disk or download
+/// packages
 #[derive(Debug)]
 pub struct InMemoryWorld {
     root: PathBuf,
@@ -52,14 +52,13 @@ pub struct InMemoryWorld {
     book: LazyHash<FontBook>,
     fonts: Vec<Font>,
     files: RwLock<HashMap<FileId, FileEntry>>, // Use RwLock
-    package_cache_dir: PathBuf,
     time: OffsetDateTime,
-    http: ureq::Agent,
-    file_map: HashMap<&'static str, Bytes>,
+    file_map: HashMap<String, Bytes>,
 }
 
 impl InMemoryWorld {
-    pub fn new(content: &str, file_map: HashMap<&'static str, Bytes>) -> Self {
+    ///files of a package are keyed by `namespace/name/version/path`
+    pub fn new<K: Into<String>>(content: &str, file_map: HashMap<K, Bytes>) -> Self {
         let fonts = register_fonts();
         let main_id = FileId::new(None, typst::syntax::VirtualPath::new("main.typ"));
 
@@ -76,87 +75,29 @@ impl InMemoryWorld {
             book: LazyHash::new(FontBook::from_fonts(&fonts)),
             fonts,
             files: RwLock::new(files),
-            package_cache_dir: std::env::var_os("CACHE_DIRECTORY")
-                .map(|os_path| os_path.into())
-                .unwrap_or(std::env::temp_dir()),
             time: OffsetDateTime::now_utc(),
-            http: ureq::Agent::new(),
-            file_map,
+            file_map: file_map.into_iter().map(|(k, v)| (k.into(), v)).collect(),
         }
     }
 
-    fn download_package(&self, spec: &PackageSpec) -> PackageResult<PathBuf> {
-        let package_subdir = format!("{}/{}/{}", spec.namespace, spec.name, spec.version);
-        let path = self.package_cache_dir.join(package_subdir);
-
-        if let Some(path_str) = path.to_str() {
-            if self.file_map.contains_key(path_str) {
-                return Ok(path);
-            }
-        }
-
-        if path.exists() {
-            return Ok(path);
-        }
-
-        eprintln!("downloading {spec}");
-        let url = format!(
-            "https://packages.typst.org/{}/{}-{}.tar.gz",
-            spec.namespace, spec.name, spec.version,
-        );
-
-        let response = retry(|| {
-            let response = self
-                .http
-                .get(&url)
-                .call()
-                .map_err(|error| format!("{error}"))?;
-
-            let status = response.status();
-            if !http_successful(status) {
-                return Err(format!(
-                    "response returned unsuccessful status code {status}",
-                ));
-            }
-
-            Ok(response)
-        })
-            .map_err(|error| PackageError::NetworkFailed(Some(EcoString::from(error))))?;
-
-        let mut compressed_archive = Vec::new();
-        response
-            .into_reader()
-            .read_to_end(&mut compressed_archive)
-            .map_err(|error| PackageError::NetworkFailed(Some(format!("{error}").into())))?;
-
-        let raw_archive = zune_inflate::DeflateDecoder::new(&compressed_archive)
-            .decode_gzip()
-            .map_err(|error| PackageError::MalformedArchive(Some(format!("{error}").into())))?;
-
-        let mut archive = tar::Archive::new(raw_archive.as_slice());
-        archive.unpack(&path).map_err(|error| {
-            _ = std::fs::remove_dir_all(&path);
-            PackageError::MalformedArchive(Some(format!("{error}").into()))
-        })?;
-
-        Ok(path)
-    }
-
     fn get_file(&self, id: FileId) -> FileResult<RwLockWriteGuard<'_, HashMap<FileId, FileEntry>>> {
         let mut files = self.files.write().unwrap();
         if let std::collections::hash_map::Entry::Vacant(e) = files.entry(id) {
             let path = if let Some(package) = id.package() {
-                let package_dir = self.download_package(package)?;
-                id.vpath().resolve(&package_dir)
+                let package_dir = format!("{}/{}/{}", package.namespace, package.name, package.version);
+                id.vpath().resolve(&PathBuf::from(package_dir))
             } else {
                 id.vpath().resolve(&self.root)
             }
                 .ok_or(FileError::AccessDenied)?;
 
-            let content = if let Some(a) = self.file_map.get(path.to_str().unwrap()) {
-                a.clone()
-            } else {
-                Bytes::new( std::fs::read(&path).map_err(|error| FileError::from_io(error, &path))?)
+            let content = path.to_str().and_then(|a| self.file_map.get(a)).cloned();
+            let content = match (content, id.package()) {
+                (Some(a), _) => a,
+                (None, Some(package)) => {
+                    return Err(FileError::Package(PackageError::NotFound(package.clone())))
+                }
+                (None, None) => return Err(FileError::NotFound(path)),
             };
 
             e.insert(FileEntry::new(content.to_vec(), None));
@@ -199,15 +140,3 @@ impl World for InMemoryWorld {
         Some(Datetime::Date(time.date()))
     }
 }
-
-fn retry<T, E>(mut f: impl FnMut() -> Result<T, E>) -> Result<T, E> {
-    if let Ok(ok) = f() {
-        Ok(ok)
-    } else {
-        f()
-    }
-}
-
-fn http_successful(status: u16) -> bool {
-    status / 100 == 2
-}
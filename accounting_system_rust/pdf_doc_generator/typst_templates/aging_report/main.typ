@@ -0,0 +1,42 @@
+#set page(flipped: true, margin: 1.5cm)
+#set text(9pt)
+#let report = json("aging_report_data.json")
+
+#let amt(value) = {
+  align(right)[#str(calc.round(value, digits: 2))]
+}
+
+#let bucket_cells(b) = (
+  amt(b.current), amt(b.days_1_30), amt(b.days_31_60), amt(b.days_61_90), amt(b.above_90), amt(b.total)
+)
+
+#let bucket_headers = ([*current*], [*1-30*], [*31-60*], [*61-90*], [*90+*], [*total*])
+
+#align(center, text(14pt)[= *#report.title*])
+#align(center)[as of #report.as_of_date]
+
+== customers
+#table(
+  columns: (2fr, 0.7fr, 1fr, 1fr, 1fr, 1fr, 1fr, 1fr, 1fr, 1fr),
+  fill: (_c, r) => if r == 0 { luma(220) } else if calc.even(r) { luma(245) } else { white },
+  [*customer*], [*currency*], ..bucket_headers, [*credits*], [*net*],
+  ..report.customers.map(c => (
+    [#c.customer_name], [#c.currency], ..bucket_cells(c.buckets), amt(c.unapplied_credits), amt(c.net_outstanding)
+  )).flatten()
+)
+
+== company unit totals
+#table(
+  columns: (2fr, 0.7fr, 1fr, 1fr, 1fr, 1fr, 1fr, 1fr),
+  fill: (_c, r) => if r == 0 { luma(220) } else { white },
+  [*company unit*], [*currency*], ..bucket_headers,
+  ..report.company_unit_totals.map(c => ([#c.label], [#c.currency], ..bucket_cells(c.buckets))).flatten()
+)
+
+== currency totals
+#table(
+  columns: (2fr, 0.7fr, 1fr, 1fr, 1fr, 1fr, 1fr, 1fr),
+  fill: (_c, r) => if r == 0 { luma(220) } else { white },
+  [], [*currency*], ..bucket_headers,
+  ..report.currency_totals.map(c => ([#c.label], [#c.currency], ..bucket_cells(c.buckets))).flatten()
+)
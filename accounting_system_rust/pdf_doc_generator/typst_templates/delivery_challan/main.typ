@@ -0,0 +1,105 @@
+#import "@preview/tablex:0.0.9": tablex, cellx,vlinex,hlinex,colspanx
+#set page(flipped: true)
+#let challan_model = json("delivery_challan_data.json")
+
+#let format_address(address)={
+  [#address.line_1 \ #address.line_2 \ #address.city_name pincode:#address.pincode]
+}
+#let format_date(date)={
+  datetime(year:date.year,month:date.month,day:date.day).display("[day]-[month repr:short]-[year]")
+}
+#let party_name(party)={
+  if party == none { [] } else { party.name }
+}
+#let party_gstin(party)={
+  if party == none { [] } else { party.gstin }
+}
+#let party_address(party)={
+  if party == none { [] } else { format_address(party.address) }
+}
+#let optional(value)={
+  if value == none { [] } else { value }
+}
+#let consignor_heading(name,address)=[
+ #grid(columns: (1fr,3fr,1fr),
+ align(center+horizon)[#image("sunset.png")],
+   align(center+horizon, text(12pt)[
+  = *#name*
+    #format_address(address)
+  ]),
+ align(center+horizon, text(16pt)[*DELIVERY CHALLAN*])
+)
+]
+
+#let party_details(consignor,consignee,shipped_to)=[
+  #tablex(
+    auto-vlines: false,
+    columns: (0.4fr,1fr,1fr,1fr),
+    fill:(col, _r) => if calc.odd(_r) { luma(240) } else { white },
+    align:(col, row) =>
+    if row == 0 { center }
+    else if col == 0 { left+horizon }
+    else { right },
+    auto-hlines:false,
+    vlinex(),(),(),(),vlinex(),
+    hlinex(),
+    [],[*consignor*],[*consignee*],[*shipped to*],
+    [*name*],consignor.name,party_name(consignee),party_name(shipped_to),
+    [*gstin*],consignor.gstin,party_gstin(consignee),party_gstin(shipped_to),
+    [*address*],format_address(consignor.address),party_address(consignee),party_address(shipped_to),hlinex()
+  )
+]
+
+#let challan_key_vals(challan)=[
+  #set terms(separator: [: ])
+  / Challan no: #challan.challan_number
+
+  / Challan date: #format_date(challan.challan_date)
+
+  / Reason: #challan.reason
+]
+
+#let challan_lines(challan)={
+  let rows = ()
+  for l in challan.lines {
+    rows.push(str(l.line_no))
+    rows.push(l.item)
+    rows.push(l.hsn_sac)
+    rows.push(optional(l.batch_no))
+    rows.push(str(l.quantity))
+    rows.push(str(l.free_quantity))
+    rows.push(l.uqc)
+    rows.push(str(l.unit_price))
+    rows.push(str(l.value))
+  }
+  tablex(
+    columns: (0.3fr,2fr,0.7fr,0.7fr,0.6fr,0.6fr,0.5fr,0.7fr,0.8fr),
+    align:(col, row) => if col == 1 { left } else { center },
+    fill:(col, _r) => if calc.odd(_r) { luma(240) } else { white },
+    auto-hlines:false,
+    hlinex(),
+    [*sno*],[*item*],[*hsn/sac*],[*batch*],[*qty*],[*free qty*],[*uqc*],[*rate*],[*value*],
+    hlinex(),
+    ..rows,
+    hlinex(),
+    colspanx(4)[*total*],(),(),(),[*#challan.total_quantity*],[],[],[],[*#challan.total_value*],
+    hlinex()
+  )
+}
+
+#show: set page(margin: (x:10pt,y:5pt))
+#consignor_heading(challan_model.consignor.name,challan_model.consignor.address)
+#line(length: 100%)
+
+#grid(columns: (2.8fr,0.05fr,1.15fr),
+party_details(challan_model.consignor,challan_model.consignee,challan_model.shipped_to),
+[],
+challan_key_vals(challan_model)
+)
+#challan_lines(challan_model)
+
+#if challan_model.remarks != none [
+  *Remarks:* #challan_model.remarks
+]
+
+#align(center, text(8pt)[this is not a tax invoice, no tax is payable on the goods moved under this challan])
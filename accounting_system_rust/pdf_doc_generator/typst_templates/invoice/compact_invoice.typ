@@ -0,0 +1,289 @@
+//invoice for portrait pages, shared by the a4 portrait and a5 layouts. the lines table has one wrapping item
+//column instead of a column for every field, its header is repeated on every page and the totals follow the
+//last line without being split over two pages
+
+#let format_date(date) = {
+  if date != none {
+    datetime(year: date.year, month: date.month, day: date.day).display("[day]-[month repr:short]-[year]")
+  }
+}
+
+//two decimals, amounts are already rounded to the scale of the currency
+#let format_amount(value) = {
+  let s = str(calc.round(value, digits: 2))
+  let parts = s.split(".")
+  if parts.len() == 1 {
+    s + ".00"
+  } else if parts.at(1).len() == 1 {
+    s + "0"
+  } else {
+    s
+  }
+}
+
+#let format_address(address) = [
+  #address.line_1 \ #address.line_2 \ #address.city_name pincode:#address.pincode
+]
+
+//long values without spaces, like the irn, can break anywhere instead of running off the page
+#let breakable(value) = value.clusters().join("\u{200b}")
+
+#let watermark(value, size) = {
+  if value != none {
+    rotate(-30deg, text(size, fill: luma(225))[*#value*])
+  }
+}
+
+#let page_footer = context {
+  let current = counter(page).get().first()
+  let total = counter(page).final().first()
+  set text(0.85em)
+  grid(
+    columns: (1fr, auto),
+    if current < total [continued on next page],
+    [page #current of #total],
+  )
+}
+
+#let has_qr_code(invoice_model) = {
+  invoice_model.einvoice_detail != none or invoice_model.b2c_qr_payload != none
+}
+
+#let qr_code_caption(invoice_model) = {
+  if invoice_model.einvoice_detail != none [einvoicing qr code] else [invoice qr code]
+}
+
+#let line_tax_percentage(line) = {
+  line.igst_percentage + line.cgst_percentage + line.sgst_percentage + line.cess_percentage
+}
+
+#let item_details(line, service_invoice) = {
+  let details = ()
+  if line.hsn_sac != "" {
+    details.push((if service_invoice { "SAC " } else { "HSN " }) + line.hsn_sac)
+  }
+  if line.batch_no != none {
+    details.push("batch " + line.batch_no)
+  }
+  if line.expiry_date != none {
+    details.push("exp " + format_date(line.expiry_date))
+  }
+  if line.mrp != none {
+    details.push("mrp " + format_amount(line.mrp))
+  }
+  [#line.item #if details.len() > 0 [\ #text(0.85em, fill: luma(90), details.join(", "))]]
+}
+
+#let quantity(line) = {
+  [#line.quantity #line.uqc #if line.free_quantity > 0 [\ +#line.free_quantity free]]
+}
+
+#let lines_table(invoice_model) = {
+  let table_data = invoice_model.invoice_lines_table
+  table(
+    columns: (auto, 1fr, auto, auto, auto, auto, auto),
+    inset: 4pt,
+    stroke: (x, y) => if y == 0 { (top: 0.5pt, bottom: 0.5pt) } else { (bottom: 0.25pt + luma(200)) },
+    align: (x, y) => if x == 1 { left + horizon } else if y == 0 { center + horizon } else { right + horizon },
+    table.header(repeat: true, [*\#*], [*item*], [*qty*], [*rate*], [*disc %*], [*tax %*], [*amount*]),
+    ..table_data.lines.map(line => (
+      str(line.line_no),
+      item_details(line, invoice_model.service_invoice),
+      quantity(line),
+      format_amount(line.unit_price),
+      str(line.discount_percentage),
+      str(calc.round(line_tax_percentage(line), digits: 2)),
+      format_amount(line.line_total),
+    )).flatten(),
+    table.footer(
+      repeat: false,
+      table.cell(colspan: 6, align: right)[*total amount*],
+      [*#format_amount(table_data.invoice_lines_total)*],
+    ),
+  )
+}
+
+#let party(title, invoice_party) = {
+  if invoice_party != none [
+    *#title* \
+    #invoice_party.name \
+    #format_address(invoice_party.address)
+    #if invoice_party.gstin != "" [\ GSTIN: #invoice_party.gstin]
+  ]
+}
+
+#let document_details(invoice_model) = {
+  let details = (
+    ([Invoice no], invoice_model.invoice_number),
+    ([Invoice date], format_date(invoice_model.invoice_date)),
+    ([Place of supply], invoice_model.place_of_supply),
+  )
+  if invoice_model.order_number != none {
+    details.push(([Order no], invoice_model.order_number))
+  }
+  if invoice_model.order_date != none {
+    details.push(([Order date], format_date(invoice_model.order_date)))
+  }
+  if invoice_model.payment_term != "" {
+    details.push(([Payment terms], invoice_model.payment_term))
+  }
+  if invoice_model.ecommerce_gstin != none {
+    details.push(([E-commerce GSTIN], invoice_model.ecommerce_gstin))
+  }
+  if invoice_model.einvoice_detail != none {
+    details.push(([Ack no], invoice_model.einvoice_detail.ack_no))
+    details.push(([Ack date], format_date(invoice_model.einvoice_detail.ack_date)))
+  }
+  grid(
+    columns: (auto, 1fr),
+    column-gutter: 6pt,
+    row-gutter: 4pt,
+    ..details.map(((key, value)) => ([*#key*], [#value])).flatten(),
+  )
+}
+
+#let document_header(invoice_model, invoice_assets, qr_size) = {
+  grid(
+    columns: (auto, 1fr, auto),
+    column-gutter: 8pt,
+    align: horizon,
+    if invoice_assets.logo != none {
+      image(invoice_assets.logo, height: qr_size * 0.7, fit: "contain")
+    },
+    [
+      #text(1.4em)[*#invoice_model.supplier.name*] \
+      #format_address(invoice_model.supplier.address) \
+      GSTIN: #invoice_model.supplier.gstin
+    ],
+    if has_qr_code(invoice_model) {
+      figure(
+        image("einvoice_qr.svg", height: qr_size),
+        caption: qr_code_caption(invoice_model),
+        numbering: none,
+      )
+    },
+  )
+}
+
+#let declarations(invoice_model) = {
+  let export_declaration = invoice_model.export_declaration
+  if export_declaration != none {
+    block(width: 100%, stroke: 0.5pt, inset: 4pt)[
+      *#export_declaration.declaration* \
+      #if export_declaration.lut_reference != none [LUT/bond no: #export_declaration.lut_reference #h(1em)]
+      #if export_declaration.port_code != none [Port code: #export_declaration.port_code #h(1em)]
+      #if export_declaration.shipping_bill_number != none [Shipping bill: #export_declaration.shipping_bill_number dated #format_date(export_declaration.shipping_bill_date) #h(1em)]
+      #if export_declaration.destination_country != none [Country of destination: #export_declaration.destination_country #h(1em)]
+      Exchange rate: 1 #export_declaration.currency = INR #export_declaration.exchange_rate #h(1em)
+      Total in INR: #format_amount(export_declaration.total_payable_amount_inr)
+    ]
+  }
+  if invoice_model.bill_of_supply_declaration != none {
+    block(width: 100%, stroke: 0.5pt, inset: 4pt)[*#invoice_model.bill_of_supply_declaration*]
+  }
+}
+
+#let tax_summary_table(tax_summary) = {
+  let rows = ()
+  for (tax_type, tax_lines) in (
+    ("IGST", tax_summary.igst_lines),
+    ("CGST", tax_summary.cgst_lines),
+    ("SGST", tax_summary.sgst_lines),
+  ) {
+    for tax_line in tax_lines {
+      rows.push(([#tax_type #tax_line.tax_slab%], format_amount(tax_line.tax_amount)))
+    }
+  }
+  table(
+    columns: (1fr, auto),
+    stroke: none,
+    inset: 3pt,
+    align: (left, right),
+    table.header([*tax*], [*amount*]),
+    table.hline(stroke: 0.5pt),
+    ..rows.flatten(),
+    table.hline(stroke: 0.5pt),
+    [*total tax*], [*#format_amount(tax_summary.total_tax_amount)*],
+  )
+}
+
+#let invoice_summary_table(invoice_model) = {
+  let summary = invoice_model.invoice_summary
+  let rows = (([taxable amount], summary.taxable_amt),)
+  if invoice_model.bill_of_supply_declaration == none {
+    rows.push(([tax amount], summary.tax_amt))
+  }
+  if summary.cess_amt != 0 {
+    rows.push(([cess amount], summary.cess_amt))
+  }
+  if invoice_model.additional_charges.len() > 0 {
+    rows.push(([additional charges], summary.additional_charges_amt))
+  }
+  if summary.round_off != 0 {
+    rows.push(([round off], summary.round_off))
+  }
+  table(
+    columns: (1fr, auto),
+    stroke: none,
+    inset: 3pt,
+    align: (left, right),
+    ..rows.map(((label, value)) => (label, format_amount(value))).flatten(),
+    table.hline(stroke: 0.5pt),
+    [*total payable*], [*#format_amount(summary.total_payable_amount)*],
+  )
+}
+
+#let terms_and_signature(invoice_model, invoice_assets) = {
+  grid(
+    columns: (1fr, auto),
+    column-gutter: 12pt,
+    if invoice_assets.terms_and_conditions != none [
+      *Terms and conditions* \
+      #text(0.85em)[#read(invoice_assets.terms_and_conditions)]
+    ],
+    align(right)[
+      For #invoice_model.supplier.name \
+      #if invoice_assets.signature != none {
+        image(invoice_assets.signature, height: 1.2cm, fit: "contain")
+      } else {
+        v(1.2cm)
+      }
+      Authorised signatory
+    ],
+  )
+}
+
+#let compact_invoice(invoice_model, invoice_assets, qr_size: 2.5cm) = [
+  #document_header(invoice_model, invoice_assets, qr_size)
+  #align(center, text(1.2em)[*#invoice_model.document_title*])
+  #line(length: 100%, stroke: 0.5pt)
+  #grid(
+    columns: (1fr, 1fr),
+    column-gutter: 12pt,
+    stack(
+      spacing: 8pt,
+      ..(
+        party("Billed to", invoice_model.billed_to),
+        party("Shipped to", invoice_model.shipped_to),
+      ).filter(a => a != none),
+    ),
+    document_details(invoice_model),
+  )
+  #if invoice_model.einvoice_detail != none [
+    *IRN:* #text(0.85em, breakable(invoice_model.einvoice_detail.irn_no))
+  ]
+  #declarations(invoice_model)
+  #lines_table(invoice_model)
+  #block(breakable: false, width: 100%)[
+    #grid(
+      columns: (1fr, 1fr),
+      column-gutter: 12pt,
+      if invoice_model.bill_of_supply_declaration == none {
+        tax_summary_table(invoice_model.tax_summary)
+      },
+      invoice_summary_table(invoice_model),
+    )
+    #if invoice_model.invoice_remarks != none [*Remarks:* #invoice_model.invoice_remarks]
+  ]
+  #block(breakable: false, width: 100%, terms_and_signature(invoice_model, invoice_assets))
+]
@@ -14,6 +14,7 @@
     auto-hlines: false,
     align:center+horizon,
     header-rows:2,
+    repeat-header:true,
     fill:(col, _r) => if calc.even(_r) and _r!=0 { luma(240) } else { white },
     hlinex(),
     ..invoice_lines.header_and_units.map(it=>it.first()).
@@ -23,7 +24,7 @@
     hlinex(),
     ..spread_lines(invoice_lines.lines,invoice_lines.header_and_units.map(it=>it.at(2))),
     hlinex(),
-    colspanx(invoice_lines.header_and_units.len()-1)[#align(right,[*total amount* (add additional ..charge breakup above)])],[193424.00],
+    colspanx(invoice_lines.header_and_units.len()-1)[#align(right,[*total amount*])],[#invoice_lines.invoice_lines_total],
      hlinex()
   ))
 ]
@@ -17,6 +17,7 @@ tablex(
   hlinex(),
   colspanx(2)[taxable amt],data.taxable_amt,
   colspanx(2)[tax amt],data.tax_amt,
+  colspanx(2)[cess amt],data.cess_amt,
   colspanx(2)[add. chrgs],data.additional_charges_amt,
   colspanx(2)[round off],data.round_off,
     hlinex(),
@@ -4,19 +4,35 @@
 #import "invoice_summary.typ"
 #set page(flipped: true)
 #let invoice_model = json("invoice_data.json")
+#let invoice_assets = json("invoice_assets.json")
+#set page(background: if invoice_model.watermark != none {
+  rotate(-30deg,text(96pt,fill:luma(225))[*#invoice_model.watermark*])
+})
 
 #let format_address(address)={
   [#address.line_1 \ #address.line_2 \ #address.city_name pincode:#address.pincode]
 }
-#let supplier_heading(name,gstin,address)=[
+#let qr_code_figure(einvoice_detail,b2c_qr_payload)={
+  if einvoice_detail != none {
+    figure(image("einvoice_qr.svg",height:3.5cm),caption:[einvoicing qr code],numbering:none)
+  } else if b2c_qr_payload != none {
+    figure(image("einvoice_qr.svg",height:3.5cm),caption:[invoice qr code],numbering:none)
+  }
+}
+#let supplier_logo(logo)={
+  if logo != none {
+    image(logo,height:2.5cm,fit:"contain")
+  }
+}
+#let supplier_heading(name,gstin,address,einvoice_detail,b2c_qr_payload)=[
  #grid(columns: (1fr,3fr,1fr),
- align(center+horizon)[#image("sunset.png")],
+ align(center+horizon,supplier_logo(invoice_assets.logo)),
    align(center+horizon, text(12pt)[
   = *#name*
     #format_address(address)
   ])
 
- ,figure(image("einvoice_qr.png"),caption:[einvoicing qr code],numbering:none)
+ ,align(center+horizon,qr_code_figure(einvoice_detail,b2c_qr_payload))
 )
 
 ]
@@ -59,6 +75,65 @@
   }
 }
 
+#let get_einvoice_details(einvoice_detail)={
+  if einvoice_detail == none {
+
+  }else{
+  [/ IRN no: #text(7pt)[#einvoice_detail.irn_no]
+
+  / Ack no: #einvoice_detail.ack_no
+
+  / Ack date: #datetime(year:einvoice_detail.ack_date.year,
+month:einvoice_detail.ack_date.month,
+day:einvoice_detail.ack_date.day).display("[day]-[month repr:short]-[year]")]
+  }
+}
+
+#let get_export_declaration(export_declaration)={
+  if export_declaration == none {
+
+  }else{
+  let sb_date = export_declaration.shipping_bill_date
+  block(width:100%,stroke:0.5pt,inset:4pt)[
+    *#export_declaration.declaration* \
+    #if export_declaration.lut_reference != none [LUT/bond no: #export_declaration.lut_reference #h(1em)]
+    #if export_declaration.port_code != none [Port code: #export_declaration.port_code #h(1em)]
+    #if export_declaration.shipping_bill_number != none [Shipping bill: #export_declaration.shipping_bill_number dated #datetime(year:sb_date.year,
+month:sb_date.month,
+day:sb_date.day).display("[day]-[month repr:short]-[year]") #h(1em)]
+    #if export_declaration.destination_country != none [Country of destination: #export_declaration.destination_country #h(1em)]
+    Exchange rate: 1 #export_declaration.currency = INR #export_declaration.exchange_rate #h(1em)
+    Total in INR: #export_declaration.total_payable_amount_inr
+  ]
+  }
+}
+
+#let get_bill_of_supply_declaration(declaration)={
+  if declaration == none {
+
+  }else{
+  block(width:100%,stroke:0.5pt,inset:4pt)[*#declaration*]
+  }
+}
+
+#let terms_and_signature(supplier_name,assets)=[
+  #grid(columns:(2fr,1fr),
+    if assets.terms_and_conditions != none [
+      *Terms and conditions* \
+      #text(8pt)[#read(assets.terms_and_conditions)]
+    ],
+    align(right)[
+      For #supplier_name \
+      #if assets.signature != none {
+        image(assets.signature,height:1.5cm,fit:"contain")
+      } else {
+        v(1.5cm)
+      }
+      Authorised signatory
+    ]
+  )
+]
+
 #let prepare_header_key_vals(hdrs)=[
   #set terms(separator: [: ])
   / Invoice no:#hdrs.invoice_number
@@ -69,15 +144,18 @@ day:hdrs.invoice_date.day).display("[day]-[month repr:short]-[year]")
 
   / Order no:#hdrs.order_number
 
+  / Place of supply:#hdrs.place_of_supply
+
   #get_order_date(hdrs.order_date)
 
   #get_payment_terms_key(hdrs.payment_term)
 
-  / IRN no: #hdrs.irn_no
+  #get_einvoice_details(hdrs.einvoice_detail)
 
 ]
 #show: set page(margin: (x:10pt,y:5pt))
-#supplier_heading(invoice_model.supplier.name,invoice_model.supplier.gstin,invoice_model.supplier.address)
+#supplier_heading(invoice_model.supplier.name,invoice_model.supplier.gstin,invoice_model.supplier.address,invoice_model.einvoice_detail,invoice_model.b2c_qr_payload)
+#align(center,text(11pt)[*#invoice_model.document_title*])
 #line(length: 100%)
 
 #grid(columns: (2.8fr,0.05fr,1.15fr),
@@ -87,11 +165,17 @@ invoice_model.shipped_to),
 [],
 prepare_header_key_vals(invoice_model)
 )
+#get_export_declaration(invoice_model.export_declaration)
+#get_bill_of_supply_declaration(invoice_model.bill_of_supply_declaration)
 #invoice_lines.invoice_line_tableV2(invoice_model.invoice_lines_table)
 
-#grid(
+//the summaries are moved to the next page together instead of being split over two pages
+#block(breakable:false,grid(
 
   columns:(1fr,0.5fr,1fr),
-  align(center,tax_summary.tax_summary_table(invoice_model.tax_summary)),[],
+  if invoice_model.bill_of_supply_declaration == none {
+    align(center,tax_summary.tax_summary_table(invoice_model.tax_summary))
+  },[],
   align(center,invoice_summary.invoice_summary(invoice_model.invoice_summary))
-)
+))
+#terms_and_signature(invoice_model.supplier.name,invoice_assets)
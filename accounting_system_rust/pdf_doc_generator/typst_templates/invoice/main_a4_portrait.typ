@@ -0,0 +1,11 @@
+#import "compact_invoice.typ": compact_invoice, page_footer, watermark
+#let invoice_model = json("invoice_data.json")
+#let invoice_assets = json("invoice_assets.json")
+#set page(
+  paper: "a4",
+  margin: (x: 1.5cm, top: 1.2cm, bottom: 1.6cm),
+  background: watermark(invoice_model.watermark, 96pt),
+  footer: page_footer,
+)
+#set text(9pt)
+#compact_invoice(invoice_model, invoice_assets, qr_size: 3cm)
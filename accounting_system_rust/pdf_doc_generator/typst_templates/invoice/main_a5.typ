@@ -0,0 +1,11 @@
+#import "compact_invoice.typ": compact_invoice, page_footer, watermark
+#let invoice_model = json("invoice_data.json")
+#let invoice_assets = json("invoice_assets.json")
+#set page(
+  paper: "a5",
+  margin: (x: 1cm, top: 0.8cm, bottom: 1.2cm),
+  background: watermark(invoice_model.watermark, 64pt),
+  footer: page_footer,
+)
+#set text(7.5pt)
+#compact_invoice(invoice_model, invoice_assets, qr_size: 2cm)
@@ -0,0 +1,135 @@
+#import "compact_invoice.typ": format_date, format_amount, format_address, breakable, watermark, has_qr_code, qr_code_caption, line_tax_percentage, declarations
+#let invoice_model = json("invoice_data.json")
+#let invoice_assets = json("invoice_assets.json")
+//receipt paper is a continuous roll, the page grows with the invoice instead of breaking into pages
+#set page(
+  width: 80mm,
+  height: auto,
+  margin: (x: 3mm, y: 4mm),
+  background: watermark(invoice_model.watermark, 28pt),
+)
+#set text(7.5pt)
+#set par(spacing: 0.6em)
+
+#let separator = line(length: 100%, stroke: (thickness: 0.5pt, dash: "dashed"))
+
+#let key_values(rows) = grid(
+  columns: (auto, 1fr),
+  column-gutter: 4pt,
+  row-gutter: 3pt,
+  ..rows.map(((key, value)) => ([#key], align(right)[#value])).flatten(),
+)
+
+#let document_details = {
+  let details = (
+    ([Invoice no], invoice_model.invoice_number),
+    ([Date], format_date(invoice_model.invoice_date)),
+    ([Place of supply], invoice_model.place_of_supply),
+  )
+  if invoice_model.order_number != none {
+    details.push(([Order no], invoice_model.order_number))
+  }
+  if invoice_model.payment_term != "" {
+    details.push(([Payment terms], invoice_model.payment_term))
+  }
+  if invoice_model.billed_to != none {
+    details.push(([Billed to], invoice_model.billed_to.name))
+    if invoice_model.billed_to.gstin != "" {
+      details.push(([GSTIN], invoice_model.billed_to.gstin))
+    }
+  }
+  details
+}
+
+//every line is printed as a small block, the item on its own row so that long names wrap over the full width
+#let invoice_line(item_line) = block(breakable: false, width: 100%)[
+  #item_line.item \
+  #grid(
+    columns: (1fr, auto),
+    text(fill: luma(60))[
+      #item_line.quantity #item_line.uqc x #format_amount(item_line.unit_price)
+      #if item_line.discount_percentage > 0 [less #item_line.discount_percentage%]
+      #if line_tax_percentage(item_line) > 0 [tax #calc.round(line_tax_percentage(item_line), digits: 2)%]
+    ],
+    format_amount(item_line.line_total),
+  )
+]
+
+#let summary_rows = {
+  let summary = invoice_model.invoice_summary
+  let rows = (([Taxable amount], format_amount(summary.taxable_amt)),)
+  if invoice_model.bill_of_supply_declaration == none {
+    for (tax_type, tax_lines) in (
+      ("IGST", invoice_model.tax_summary.igst_lines),
+      ("CGST", invoice_model.tax_summary.cgst_lines),
+      ("SGST", invoice_model.tax_summary.sgst_lines),
+    ) {
+      for tax_line in tax_lines {
+        rows.push(([#tax_type #tax_line.tax_slab%], format_amount(tax_line.tax_amount)))
+      }
+    }
+  }
+  if summary.cess_amt != 0 {
+    rows.push(([Cess], format_amount(summary.cess_amt)))
+  }
+  if invoice_model.additional_charges.len() > 0 {
+    rows.push(([Additional charges], format_amount(summary.additional_charges_amt)))
+  }
+  if summary.round_off != 0 {
+    rows.push(([Round off], format_amount(summary.round_off)))
+  }
+  rows
+}
+
+#align(center)[
+  #if invoice_assets.logo != none {
+    image(invoice_assets.logo, height: 1.2cm, fit: "contain")
+  }
+  #text(10pt)[*#invoice_model.supplier.name*] \
+  #format_address(invoice_model.supplier.address) \
+  GSTIN: #invoice_model.supplier.gstin \
+  #text(9pt)[*#invoice_model.document_title*]
+]
+#separator
+#key_values(document_details)
+#declarations(invoice_model)
+#separator
+#for item_line in invoice_model.invoice_lines_table.lines {
+  invoice_line(item_line)
+}
+#separator
+#block(breakable: false, width: 100%)[
+  #key_values(summary_rows)
+  #separator
+  #text(9pt, key_values(((strong[Total], strong(format_amount(invoice_model.invoice_summary.total_payable_amount))),)))
+]
+#if has_qr_code(invoice_model) {
+  align(center, figure(
+    image("einvoice_qr.svg", width: 3cm),
+    caption: qr_code_caption(invoice_model),
+    numbering: none,
+  ))
+}
+#if invoice_model.einvoice_detail != none [
+  IRN: #text(6pt, breakable(invoice_model.einvoice_detail.irn_no)) \
+  Ack no: #invoice_model.einvoice_detail.ack_no, #format_date(invoice_model.einvoice_detail.ack_date)
+]
+#if invoice_model.invoice_remarks != none [
+  #separator
+  #invoice_model.invoice_remarks
+]
+#if invoice_assets.terms_and_conditions != none [
+  #separator
+  #text(6.5pt)[#read(invoice_assets.terms_and_conditions)]
+]
+#separator
+//signed in the space below the supplier name when no signature image is uploaded
+#block(breakable: false, width: 100%, align(center)[
+  For #invoice_model.supplier.name \
+  #if invoice_assets.signature != none {
+    image(invoice_assets.signature, height: 1cm, fit: "contain")
+  } else {
+    v(1cm)
+  }
+  Authorised signatory
+])
@@ -0,0 +1,101 @@
+#import "@preview/tablex:0.0.9": tablex, cellx,vlinex,hlinex
+#import "invoice_lines.typ"
+#import "tax_summary.typ"
+#import "invoice_summary.typ"
+#set page(flipped: true)
+#let quotation_model = json("quotation_data.json")
+#let document_model = quotation_model.document
+
+#let format_address(address)={
+  [#address.line_1 \ #address.line_2 \ #address.city_name pincode:#address.pincode]
+}
+#let format_date(date)={
+  datetime(year:date.year,month:date.month,day:date.day).display("[day]-[month repr:short]-[year]")
+}
+#let party_name(party)={
+  if party == none { [] } else { party.name }
+}
+#let party_gstin(party)={
+  if party == none { [] } else { party.gstin }
+}
+#let party_address(party)={
+  if party == none { [] } else { format_address(party.address) }
+}
+#let supplier_heading(title,name,address)=[
+ #grid(columns: (1fr,3fr,1fr),
+ align(center+horizon)[#image("sunset.png")],
+   align(center+horizon, text(12pt)[
+  = *#name*
+    #format_address(address)
+  ]),
+ align(center+horizon, text(16pt)[*#upper(title)*])
+)
+]
+
+#let header_details(supplier,billed_to,shipped_to)=[
+  #tablex(
+    auto-vlines: false,
+    columns: (0.4fr,1fr,1fr,1fr),
+    fill:(col, _r) => if calc.odd(_r) { luma(240) } else { white },
+    align:(col, row) =>
+    if row == 0 { center }
+    else if col == 0 { left+horizon }
+    else { right },
+    auto-hlines:false,
+    vlinex(),(),(),(),vlinex(),
+    hlinex(),
+    [],[*supplier*],[*quoted to*],[*shipped to*],
+    [*name*],supplier.name,party_name(billed_to),party_name(shipped_to),
+    [*gstin*],supplier.gstin,party_gstin(billed_to),party_gstin(shipped_to),
+    [*address*],format_address(supplier.address),party_address(billed_to),party_address(shipped_to),hlinex()
+  )
+]
+
+#let get_valid_until(valid_until)={
+  if valid_until == none {
+
+  }else{
+  [/ Valid until: #format_date(valid_until)]
+  }
+}
+#let get_payment_terms_key(payment_terms)={
+  if payment_terms== none or payment_terms=="" {
+
+  }else{
+  [/ Payment terms: #payment_terms]
+  }
+}
+
+#let prepare_header_key_vals(title,hdrs,valid_until)=[
+  #set terms(separator: [: ])
+  / #title no: #hdrs.invoice_number
+
+  / #title date: #format_date(hdrs.invoice_date)
+
+  #get_valid_until(valid_until)
+
+  #get_payment_terms_key(hdrs.payment_term)
+]
+#show: set page(margin: (x:10pt,y:5pt))
+#supplier_heading(quotation_model.title,document_model.supplier.name,document_model.supplier.address)
+#line(length: 100%)
+
+#grid(columns: (2.8fr,0.05fr,1.15fr),
+header_details(document_model.supplier,
+document_model.billed_to,
+document_model.shipped_to),
+[],
+prepare_header_key_vals(quotation_model.title,document_model,quotation_model.valid_until)
+)
+#invoice_lines.invoice_line_tableV2(document_model.invoice_lines_table)
+
+#grid(
+  columns:(1fr,0.5fr,1fr),
+  align(center,tax_summary.tax_summary_table(document_model.tax_summary)),[],
+  align(center,invoice_summary.invoice_summary(document_model.invoice_summary))
+)
+#if document_model.invoice_remarks != none [
+  *Remarks:* #document_model.invoice_remarks
+]
+
+#align(center, text(8pt)[this is not a tax invoice])